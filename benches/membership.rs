@@ -0,0 +1,78 @@
+/// Benchmarks for membership checks against large literal sets
+///
+/// Compares the code shape previously generated for `country in [...]`
+/// (allocate a `Vec`, scan it linearly) with the sorted `const` table and
+/// binary search generated for sets of `MEMBERSHIP_TABLE_THRESHOLD` or
+/// more literals.
+#[cfg(test)]
+mod benchmarks {
+    use std::hint::black_box;
+
+    const ITERATIONS: usize = 10_000;
+
+    fn codes() -> Vec<String> {
+        (0..200)
+            .map(|i| {
+                format!(
+                    "{}{}",
+                    (b'A' + (i / 26) as u8) as char,
+                    (b'A' + (i % 26) as u8) as char
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn bench_linear_scan() {
+        let codes = codes();
+        let probes: Vec<&str> = vec!["AA", "EM", "HR", "ZZ"];
+        let start = std::time::Instant::now();
+        for _ in 0..ITERATIONS {
+            for probe in &probes {
+                let table: Vec<&str> = codes.iter().map(String::as_str).collect();
+                black_box(table.contains(black_box(probe)));
+            }
+        }
+        let elapsed = start.elapsed();
+        let per_check = elapsed.as_nanos() as f64 / (ITERATIONS * probes.len()) as f64;
+        println!("Linear scan (200 codes): {:.1}ns per check", per_check);
+    }
+
+    #[test]
+    fn bench_sorted_table() {
+        let mut codes = codes();
+        codes.sort();
+        let table: Vec<&str> = codes.iter().map(String::as_str).collect();
+        let probes: Vec<&str> = vec!["AA", "EM", "HR", "ZZ"];
+        let start = std::time::Instant::now();
+        for _ in 0..ITERATIONS {
+            for probe in &probes {
+                black_box(table.binary_search(black_box(probe)).is_ok());
+            }
+        }
+        let elapsed = start.elapsed();
+        let per_check = elapsed.as_nanos() as f64 / (ITERATIONS * probes.len()) as f64;
+        println!("Sorted table (200 codes): {:.1}ns per check", per_check);
+    }
+
+    #[test]
+    fn bench_generated_membership() {
+        use elo_rust::codegen::optimization::MembershipTable;
+        use elo_rust::parser::Parser;
+
+        let literals: Vec<String> = codes().iter().map(|c| format!("'{}'", c)).collect();
+        let source = format!("country in [{}]", literals.join(", "));
+        let expr = Parser::parse(&source).unwrap();
+        let start = std::time::Instant::now();
+        for _ in 0..100 {
+            if let elo_rust::ast::Expr::BinaryOp { right, .. } = &expr {
+                black_box(MembershipTable::from_array(right));
+            }
+        }
+        let elapsed = start.elapsed();
+        println!(
+            "Table detection (200 codes): {:.2}µs per expression",
+            elapsed.as_micros() as f64 / 100.0
+        );
+    }
+}
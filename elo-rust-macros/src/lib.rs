@@ -0,0 +1,302 @@
+#![forbid(unsafe_code)]
+#![warn(missing_docs, rust_2018_idioms, unreachable_pub)]
+
+//! Compile-time ELO validators
+//!
+//! `#[elo_validate]` turns ELO expressions attached to a struct into a
+//! `validate` method, generated by [`elo_rust::RustCodeGenerator`] while the
+//! struct is compiled:
+//!
+//! ```ignore
+//! use elo_rust_macros::elo_validate;
+//!
+//! #[elo_validate("age >= 18 || guardian_approved")]
+//! pub struct User {
+//!     age: i64,
+//!     guardian_approved: bool,
+//!     #[elo("name != ''")]
+//!     name: String,
+//! }
+//!
+//! let user = User { age: 16, guardian_approved: false, name: "Ada".to_string() };
+//! let errors = user.validate().unwrap_err();
+//! assert_eq!(errors.to_string(), "validate: Validation failed");
+//! ```
+//!
+//! Identifiers in the expressions are fields of the struct, and their
+//! declared types reach the generator: `score > 5` on an `f64` field
+//! compares with `5.0`. The expression on the struct and the
+//! `#[elo("...")]` expressions on its fields are checked separately, and
+//! `validate` returns the
+//! [`ValidationErrors`](elo_rust::runtime::ValidationErrors) of every one
+//! that fails. Errors of the struct's expression name the method as their
+//! rule, those of a field's expression the field. `fn_name = "check"` names
+//! the method differently.
+//!
+//! The generated code calls into the runtime of `elo-rust`, so crates using
+//! the macro depend on `elo-rust` as well. Invalid expressions are reported
+//! as compile errors pointing at the string that holds them.
+
+use elo_rust::codegen::types::{RustType, TypeContext, TypeInfo};
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::{Fields, GenericArgument, Ident, ItemStruct, LitStr, PathArguments, Token, Type};
+
+/// Generate a `validate` method from ELO expressions on a struct and its fields
+///
+/// See the [crate documentation](crate) for an example.
+#[proc_macro_attribute]
+pub fn elo_validate(attr: TokenStream, item: TokenStream) -> TokenStream {
+    expand(attr.into(), item.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Arguments of `#[elo_validate("expr", fn_name = "name")]`
+struct Args {
+    expr: Option<LitStr>,
+    fn_name: Ident,
+}
+
+impl Parse for Args {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let mut args = Args {
+            expr: None,
+            fn_name: Ident::new("validate", Span::call_site()),
+        };
+        if input.peek(LitStr) {
+            args.expr = Some(input.parse()?);
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        while !input.is_empty() {
+            let key: Ident = input.parse()?;
+            if key != "fn_name" {
+                return Err(syn::Error::new(
+                    key.span(),
+                    "unknown argument; expected `fn_name = \"...\"`",
+                ));
+            }
+            input.parse::<Token![=]>()?;
+            let name: LitStr = input.parse()?;
+            args.fn_name = name.parse()?;
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(args)
+    }
+}
+
+fn expand(attr: TokenStream2, item: TokenStream2) -> syn::Result<TokenStream2> {
+    let args: Args = syn::parse2(attr)?;
+    let mut item: ItemStruct = syn::parse2(item)?;
+    if !item.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &item.generics,
+            "elo_validate does not support generic structs",
+        ));
+    }
+
+    // Each expression with the rule name its errors are reported under
+    let mut exprs: Vec<(String, LitStr)> = args
+        .expr
+        .into_iter()
+        .map(|expr| (args.fn_name.unraw().to_string(), expr))
+        .collect();
+    if let Fields::Named(fields) = &mut item.fields {
+        for field in &mut fields.named {
+            let mut kept = Vec::with_capacity(field.attrs.len());
+            for attr in field.attrs.drain(..) {
+                if attr.path().is_ident("elo") {
+                    let name = field.ident.as_ref().map(|i| i.unraw().to_string());
+                    exprs.push((name.unwrap_or_default(), attr.parse_args()?));
+                } else {
+                    kept.push(attr);
+                }
+            }
+            field.attrs = kept;
+        }
+    }
+    if exprs.is_empty() {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "elo_validate needs an expression, on the struct or in #[elo(\"...\")] on a field",
+        ));
+    }
+
+    let type_name = item.ident.to_string();
+    let mut type_info = TypeInfo::new(&type_name);
+    for field in &item.fields {
+        if let Some(ident) = &field.ident {
+            type_info.add_field(&ident.unraw().to_string(), rust_type(&field.ty));
+        }
+    }
+    let mut context = TypeContext::new();
+    context.register_type(&type_name, type_info);
+    let generator = elo_rust::RustCodeGenerator::with_context(context);
+    let mut checks = Vec::with_capacity(exprs.len());
+    for (i, (name, expr)) in exprs.iter().enumerate() {
+        // The validator function is named after its rule; keywords fall back
+        let rule =
+            syn::parse_str::<Ident>(name).unwrap_or_else(|_| format_ident!("__elo_rule_{}", i));
+        let code = generator
+            .generate_validator(&rule.to_string(), &expr.value(), &type_name)
+            .map_err(|e| syn::Error::new(expr.span(), format!("invalid ELO expression: {}", e)))?;
+        // Each rule in its own block, so equally named rules do not clash
+        checks.push(quote! {
+            if let ::core::result::Result::Err(failed) = {
+                #code
+                #rule(self)
+            } {
+                errors.merge(failed);
+            }
+        });
+    }
+
+    let ident = &item.ident;
+    let fn_name = &args.fn_name;
+    let sources: Vec<String> = exprs.iter().map(|(_, expr)| expr.value()).collect();
+    let doc = format!("Check this value against `{}`", sources.join("` and `"));
+    Ok(quote! {
+        #item
+
+        impl #ident {
+            #[doc = #doc]
+            #[allow(unused_parens, unused_braces, clippy::all)]
+            pub fn #fn_name(&self) -> ::core::result::Result<(), elo_rust::runtime::ValidationErrors> {
+                let mut errors = elo_rust::runtime::ValidationErrors::new();
+                #(#checks)*
+                if errors.is_empty() {
+                    ::core::result::Result::Ok(())
+                } else {
+                    ::core::result::Result::Err(errors)
+                }
+            }
+        }
+    })
+}
+
+/// ELO type of a field, so generated code can convert literals compared with it
+fn rust_type(ty: &Type) -> RustType {
+    let segment = match ty {
+        Type::Reference(reference) => return rust_type(&reference.elem),
+        Type::Path(path) => match path.path.segments.last() {
+            Some(segment) => segment,
+            None => return RustType::Unknown,
+        },
+        _ => return RustType::Unknown,
+    };
+    let argument = || match &segment.arguments {
+        PathArguments::AngleBracketed(args) => args
+            .args
+            .iter()
+            .find_map(|arg| match arg {
+                GenericArgument::Type(ty) => Some(rust_type(ty)),
+                _ => None,
+            })
+            .unwrap_or(RustType::Unknown),
+        _ => RustType::Unknown,
+    };
+    match segment.ident.to_string().as_str() {
+        "i8" | "i16" | "i32" | "i64" | "isize" | "u8" | "u16" | "u32" | "u64" | "usize" => {
+            RustType::Integer
+        }
+        "f32" | "f64" => RustType::Float,
+        "bool" => RustType::Bool,
+        "String" | "str" => RustType::String,
+        "Option" => RustType::Option(Box::new(argument())),
+        "Vec" => RustType::Array(Box::new(argument())),
+        _ => RustType::Unknown,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand_error(attr: TokenStream2, item: TokenStream2) -> String {
+        expand(attr, item).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_expand_strips_field_attributes() {
+        let code = expand(
+            quote!("age >= 18", fn_name = "check"),
+            quote! {
+                struct User {
+                    age: i64,
+                    #[elo("name != ''")]
+                    #[allow(dead_code)]
+                    name: String,
+                }
+            },
+        )
+        .unwrap()
+        .to_string();
+        assert!(code.contains("pub fn check"));
+        assert!(code.contains("fn check (input : & User)"));
+        assert!(code.contains("fn name (input : & User)"));
+        assert!(code.contains("# [allow (dead_code)]"));
+        assert!(!code.contains("# [elo"));
+    }
+
+    #[test]
+    fn test_expand_errors() {
+        assert!(expand_error(
+            quote!("age >="),
+            quote!(
+                struct User {
+                    age: i64,
+                }
+            )
+        )
+        .starts_with("invalid ELO expression: Parse error"));
+        assert_eq!(
+            expand_error(
+                quote!(),
+                quote!(
+                    struct User {
+                        age: i64,
+                    }
+                )
+            ),
+            "elo_validate needs an expression, on the struct or in #[elo(\"...\")] on a field"
+        );
+        assert_eq!(
+            expand_error(
+                quote!("x > 0"),
+                quote!(
+                    struct Wrapper<T> {
+                        x: T,
+                    }
+                )
+            ),
+            "elo_validate does not support generic structs"
+        );
+        assert_eq!(
+            expand_error(
+                quote!("x > 0", name = "v"),
+                quote!(
+                    struct User {
+                        x: i64,
+                    }
+                )
+            ),
+            "unknown argument; expected `fn_name = \"...\"`"
+        );
+        assert!(expand(
+            quote!("x > 0"),
+            quote!(
+                enum E {
+                    A,
+                }
+            )
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,135 @@
+//! Validators generated by `#[elo_validate]`, compiled and run
+
+use elo_rust::runtime::{ValidationError, ValidationErrors};
+use elo_rust_macros::elo_validate;
+
+fn errors(errors: Vec<ValidationError>) -> Result<(), ValidationErrors> {
+    Err(ValidationErrors { errors })
+}
+
+#[elo_validate("age >= 18 || guardian_approved")]
+struct User {
+    age: i64,
+    guardian_approved: bool,
+    #[elo("require name != '' else 'name is required'")]
+    name: String,
+}
+
+#[elo_validate("(nickname ?| 'anonymous') != 'admin'", fn_name = "check")]
+struct Profile {
+    nickname: Option<String>,
+}
+
+#[test]
+fn test_struct_and_field_rules() {
+    let user = |age, guardian_approved, name: &str| User {
+        age,
+        guardian_approved,
+        name: name.to_string(),
+    };
+    assert_eq!(user(30, false, "Ada").validate(), Ok(()));
+    assert_eq!(user(16, true, "Ada").validate(), Ok(()));
+    assert_eq!(
+        user(16, false, "Ada").validate(),
+        errors(vec![ValidationError::new(
+            "validate",
+            "Validation failed",
+            "validate"
+        )])
+    );
+    assert_eq!(
+        user(16, false, "").validate(),
+        errors(vec![
+            ValidationError::new("validate", "Validation failed", "validate"),
+            ValidationError::new("name", "name is required", "name").with_value(""),
+        ])
+    );
+}
+
+#[test]
+fn test_custom_method_name() {
+    let profile = |nickname: Option<&str>| Profile {
+        nickname: nickname.map(str::to_string),
+    };
+    assert_eq!(profile(None).check(), Ok(()));
+    assert_eq!(profile(Some("ada")).check(), Ok(()));
+    assert!(profile(Some("admin")).check().is_err());
+}
+
+#[elo_validate("age >= 18 && verified == true")]
+struct Account {
+    age: i64,
+    verified: bool,
+    #[elo("require email like '*@example.com' else 'use your work email'")]
+    email: String,
+}
+
+#[test]
+fn test_like_on_a_field() {
+    let account = |email: &str| Account {
+        age: 25,
+        verified: true,
+        email: email.to_string(),
+    };
+    assert_eq!(account("john@example.com").validate(), Ok(()));
+    assert_eq!(
+        account("john@gmail.com").validate(),
+        errors(vec![ValidationError::new(
+            "email",
+            "use your work email",
+            "email"
+        )
+        .with_value("john@gmail.com")])
+    );
+}
+
+#[elo_validate("length(email) > 3 && length(items) == 2 && score > 5 && ratio * 2 <= 1")]
+struct Order {
+    email: String,
+    items: Vec<i64>,
+    score: f64,
+    ratio: f64,
+}
+
+#[test]
+fn test_lengths_and_float_fields() {
+    let order = |email: &str, items: Vec<i64>, score| Order {
+        email: email.to_string(),
+        items,
+        score,
+        ratio: 0.25,
+    };
+    assert_eq!(order("ada@x.io", vec![1, 2], 5.5).validate(), Ok(()));
+    assert!(order("a@x", vec![1, 2], 5.5).validate().is_err());
+    assert!(order("ada@x.io", vec![1], 5.5).validate().is_err());
+    assert!(order("ada@x.io", vec![1, 2], 5.0).validate().is_err());
+}
+
+#[elo_validate("length(trim(email)) > 3 && length(trim(email)) < 10")]
+struct Signup {
+    email: String,
+}
+
+#[test]
+fn test_shared_call_keeps_its_type() {
+    let signup = |email: &str| Signup {
+        email: email.to_string(),
+    };
+    assert_eq!(signup("  ada@x.io ").validate(), Ok(()));
+    assert!(signup(" a@x ").validate().is_err());
+    assert!(signup("ada@example.com").validate().is_err());
+}
+
+#[elo_validate("(items |> sum() > 0) && (age |> fn(v ~> v * 2) == 42)")]
+struct Basket {
+    items: Vec<i64>,
+    age: i64,
+}
+
+#[test]
+fn test_pipes_into_comparisons() {
+    let basket = |items: Vec<i64>, age| Basket { items, age };
+    assert_eq!(basket(vec![1, 2], 21).validate(), Ok(()));
+    assert!(basket(vec![], 21).validate().is_err());
+    assert!(basket(vec![1], 20).validate().is_err());
+}
@@ -0,0 +1,197 @@
+//! Generated validators vs hand-written Rust
+//!
+//! Times the code the generator emits for a few representative rules
+//! against the validator a Rust developer would write by hand for the same
+//! struct, and prints the overhead of each:
+//!
+//! ```text
+//! cargo run --release --example bench_compare
+//! cargo run --release --example bench_compare -- --show-code
+//! ```
+//!
+//! The `generated` module includes the generator's output for each rule
+//! from `bench_compare/generated.rs`, which `--show-code` prints and
+//! `--write-code` rewrites; a test fails when the file falls behind the
+//! generator.
+
+use elo_rust::codegen::RustCodeGenerator;
+use elo_rust::runtime::bench::ComparisonReport;
+use std::hint::black_box;
+
+const ITERATIONS: u64 = 200_000;
+
+#[derive(Debug, Clone)]
+pub struct LineItem {
+    pub qty: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Order {
+    pub age: i64,
+    pub verified: bool,
+    pub email: String,
+    pub country: String,
+    pub name: String,
+    pub items: Vec<LineItem>,
+}
+
+/// Rules compared, with their ELO source
+const RULES: &[(&str, &str)] = &include!("bench_compare/rules.rs");
+
+/// Generator output for [`RULES`], written by `--write-code`
+#[allow(clippy::bool_comparison)]
+mod generated {
+    use super::Order;
+
+    include!("bench_compare/generated.rs");
+}
+
+/// What a developer would write for the same rules
+mod hand_written {
+    use super::Order;
+    use elo_rust::runtime::{ValidationError, ValidationErrors};
+    use regex::Regex;
+    use std::sync::OnceLock;
+
+    fn check(ok: bool, rule: &str) -> Result<(), ValidationErrors> {
+        if ok {
+            Ok(())
+        } else {
+            let mut errors = ValidationErrors::new();
+            errors.push(ValidationError::new(rule, "Validation failed", rule));
+            Err(errors)
+        }
+    }
+
+    pub fn is_adult(order: &Order) -> Result<(), ValidationErrors> {
+        check(order.age >= 18 && order.verified, "is_adult")
+    }
+
+    pub fn email_domain(order: &Order) -> Result<(), ValidationErrors> {
+        static EMAIL: OnceLock<Regex> = OnceLock::new();
+        let email = EMAIL.get_or_init(|| Regex::new("^[a-z]+@example[.]com$").unwrap());
+        check(email.is_match(&order.email), "email_domain")
+    }
+
+    pub fn eu_or_us(order: &Order) -> Result<(), ValidationErrors> {
+        let listed = matches!(
+            order.country.as_str(),
+            "AT" | "BE"
+                | "DE"
+                | "DK"
+                | "ES"
+                | "FI"
+                | "FR"
+                | "GB"
+                | "IE"
+                | "IT"
+                | "NL"
+                | "NO"
+                | "PL"
+                | "PT"
+                | "SE"
+                | "US"
+        );
+        check(listed, "eu_or_us")
+    }
+
+    pub fn has_items(order: &Order) -> Result<(), ValidationErrors> {
+        check(
+            order.name.chars().nth(2).is_some() && order.items.iter().any(|i| i.qty > 0),
+            "has_items",
+        )
+    }
+}
+
+fn orders() -> Vec<Order> {
+    let order = |age, email: &str, country: &str, qty| Order {
+        age,
+        verified: age % 2 == 0,
+        email: email.to_string(),
+        country: country.to_string(),
+        name: "Ada Lovelace".to_string(),
+        items: vec![LineItem { qty: 0 }, LineItem { qty }],
+    };
+    vec![
+        order(34, "ada@example.com", "GB", 2),
+        order(17, "bob@example.org", "CA", 0),
+        order(52, "eve@example.com", "US", 1),
+    ]
+}
+
+/// Generator output for [`RULES`], as included by the `generated` module
+fn generated_code() -> String {
+    let generator = RustCodeGenerator::new();
+    let mut code =
+        String::from("// Generated by `cargo run --example bench_compare -- --write-code`\n");
+    for (rule, source) in RULES {
+        match generator.generate_validator(rule, source, "Order") {
+            Ok(validator) => code.push_str(&format!("\n// {}\n{}\n", source, validator)),
+            Err(error) => code.push_str(&format!("\n// {}: {}\n", rule, error)),
+        }
+    }
+    code
+}
+
+type Validator = fn(&Order) -> Result<(), elo_rust::runtime::ValidationErrors>;
+
+fn main() {
+    if std::env::args().any(|arg| arg == "--show-code") {
+        print!("{}", generated_code());
+        return;
+    }
+    if std::env::args().any(|arg| arg == "--write-code") {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/examples/bench_compare/generated.rs"
+        );
+        std::fs::write(path, generated_code()).expect("cannot write the generated code");
+        return;
+    }
+
+    let pairs: [(Validator, Validator); 4] = [
+        (generated::is_adult, hand_written::is_adult),
+        (generated::email_domain, hand_written::email_domain),
+        (generated::eu_or_us, hand_written::eu_or_us),
+        (generated::has_items, hand_written::has_items),
+    ];
+
+    let orders = &orders();
+    let mut report = ComparisonReport::new(ITERATIONS);
+    for ((rule, source), (generated, hand_written)) in RULES.iter().zip(pairs) {
+        for order in orders {
+            assert_eq!(
+                generated(order).is_ok(),
+                hand_written(order).is_ok(),
+                "{} disagrees on {:?}",
+                rule,
+                order
+            );
+        }
+        let run = |validate: Validator| {
+            move || {
+                orders
+                    .iter()
+                    .filter(|order| validate(black_box(order)).is_ok())
+                    .count()
+            }
+        };
+        report.compare(rule, source, run(generated), run(hand_written));
+    }
+
+    println!("=== Generated vs hand-written validators ===\n");
+    println!(
+        "{} iterations over {} orders each\n",
+        ITERATIONS,
+        orders.len()
+    );
+    print!("{}", report);
+    if let Some(worst) = report.worst() {
+        println!(
+            "\nLargest overhead: {} ({:+.1}%) for `{}`",
+            worst.rule,
+            worst.overhead_percent(),
+            worst.source
+        );
+    }
+}
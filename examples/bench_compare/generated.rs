@@ -0,0 +1,13 @@
+// Generated by `cargo run --example bench_compare -- --write-code`
+
+// age >= 18 && verified == true
+pub fn is_adult (input : & Order) -> Result < () , elo_rust :: runtime :: ValidationErrors > { let result = input . age >= 18i64 && input . verified == true ; if result { Ok (()) } else { let mut errors = elo_rust :: runtime :: ValidationErrors :: new () ; errors . push (elo_rust :: runtime :: ValidationError :: new ("is_adult" , "Validation failed" , "is_adult")) ; Err (errors) } }
+
+// matches(email, '^[a-z]+@example[.]com$')
+pub fn email_domain (input : & Order) -> Result < () , elo_rust :: runtime :: ValidationErrors > { let result = { static __ELO_REGEX : :: std :: sync :: OnceLock < Option < elo_rust :: runtime :: pattern :: Regex > , > = :: std :: sync :: OnceLock :: new () ; match __ELO_REGEX . get_or_init (|| elo_rust :: runtime :: pattern :: Regex :: new ("^[a-z]+@example[.]com$") . ok ()) { Some (regex) => regex . is_match (& input . email) , None => false , } } ; if result { Ok (()) } else { let mut errors = elo_rust :: runtime :: ValidationErrors :: new () ; errors . push ({ # [allow (unused_imports)] use elo_rust :: runtime :: inspect :: { InspectDebug as _ , InspectDisplay as _ , InspectNone as _ , } ; let error = elo_rust :: runtime :: ValidationError :: new ("email" , "Validation failed" , "email_domain") ; match (&& & elo_rust :: runtime :: inspect :: Inspect (& input . email)) . reported_value () { Some (value) => error . with_value (value) , None => error , } }) ; Err (errors) } }
+
+// country in ['AT', 'BE', 'DE', 'DK', 'ES', 'FI', 'FR', 'GB', 'IE', 'IT', 'NL', 'NO', 'PL', 'PT', 'SE', 'US']
+pub fn eu_or_us (input : & Order) -> Result < () , elo_rust :: runtime :: ValidationErrors > { let result = { const TABLE : & [& str] = & ["AT" , "BE" , "DE" , "DK" , "ES" , "FI" , "FR" , "GB" , "IE" , "IT" , "NL" , "NO" , "PL" , "PT" , "SE" , "US"] ; TABLE . binary_search (& :: core :: convert :: AsRef :: < str > :: as_ref (& input . country)) . is_ok () } ; if result { Ok (()) } else { let mut errors = elo_rust :: runtime :: ValidationErrors :: new () ; errors . push ({ # [allow (unused_imports)] use elo_rust :: runtime :: inspect :: { InspectDebug as _ , InspectDisplay as _ , InspectNone as _ , } ; let error = elo_rust :: runtime :: ValidationError :: new ("country" , "Validation failed" , "eu_or_us") ; match (&& & elo_rust :: runtime :: inspect :: Inspect (& input . country)) . reported_value () { Some (value) => error . with_value (value) , None => error , } }) ; Err (errors) } }
+
+// length(name) > 2 && any(items, fn(i ~> i.qty > 0))
+pub fn has_items (input : & Order) -> Result < () , elo_rust :: runtime :: ValidationErrors > { let result = (input . name . len () as i64) > 2i64 && input . items . iter () . any (| i | i . qty > 0i64) ; if result { Ok (()) } else { let mut errors = elo_rust :: runtime :: ValidationErrors :: new () ; errors . push (elo_rust :: runtime :: ValidationError :: new ("has_items" , "Validation failed" , "has_items")) ; Err (errors) } }
@@ -0,0 +1,14 @@
+// Rules timed by the `bench_compare` example, with their ELO source; a test
+// reads them too, to check `generated.rs` is the generator's output for them
+[
+    ("is_adult", "age >= 18 && verified == true"),
+    ("email_domain", "matches(email, '^[a-z]+@example[.]com$')"),
+    (
+        "eu_or_us",
+        "country in ['AT', 'BE', 'DE', 'DK', 'ES', 'FI', 'FR', 'GB', 'IE', 'IT', 'NL', 'NO', 'PL', 'PT', 'SE', 'US']",
+    ),
+    (
+        "has_items",
+        "length(name) > 2 && any(items, fn(i ~> i.qty > 0))",
+    ),
+]
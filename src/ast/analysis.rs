@@ -0,0 +1,454 @@
+//! Structural analysis of expressions
+//!
+//! Size, depth and variable usage of an [`Expr`], as needed by complexity
+//! limits, rule dependency tracking and result caching.
+//!
+//! [`field_dependencies`] lists the input fields an expression reads, so
+//! that applications patching an entity can re-run only the rules whose
+//! fields changed (see [`crate::rules::RuleSet::affected_rules`]).
+//!
+//! [`complexity`] summarizes the evaluation cost of an expression, to spot
+//! pathological rules and to enforce budgets on untrusted input.
+//!
+//! [`find_counterexample`] searches for an input that fails an expression,
+//! or proves that none exists (see [`super::solver`]).
+
+pub use super::solver::{
+    find_counterexample, find_example, Counterexample, InputValue, SolverOutcome,
+};
+use super::{BinaryOperator, Expr};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// Path to an input field, such as `user.address.city`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FieldPath {
+    segments: Vec<String>,
+}
+
+impl FieldPath {
+    /// Create a path from its segments
+    pub fn new<I, S>(segments: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            segments: segments.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Path segments, outermost first
+    pub fn segments(&self) -> &[String] {
+        &self.segments
+    }
+
+    /// Top-level field the path starts at
+    pub fn root(&self) -> &str {
+        self.segments.first().map(String::as_str).unwrap_or("")
+    }
+
+    /// Check if `self` is `other` or one of its ancestors
+    pub fn is_prefix_of(&self, other: &FieldPath) -> bool {
+        other.segments.starts_with(&self.segments)
+    }
+
+    /// Check if a change to one path can affect the value read at the other
+    ///
+    /// Replacing `user` changes `user.name`, and editing `user.name` changes
+    /// `user`, so paths overlap when either is a prefix of the other.
+    pub fn overlaps(&self, other: &FieldPath) -> bool {
+        self.is_prefix_of(other) || other.is_prefix_of(self)
+    }
+}
+
+impl From<&str> for FieldPath {
+    fn from(path: &str) -> Self {
+        Self::new(path.split('.'))
+    }
+}
+
+impl fmt::Display for FieldPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.segments.join("."))
+    }
+}
+
+/// Input fields read by an expression, sorted and deduplicated
+///
+/// # Example
+///
+/// ```
+/// use elo_rust::ast::analysis::{field_dependencies, FieldPath};
+/// use elo_rust::parser::Parser;
+///
+/// let expr = Parser::parse("user.age >= 18 && length(email) > 3").unwrap();
+/// assert_eq!(
+///     field_dependencies(&expr),
+///     vec![FieldPath::from("email"), FieldPath::from("user.age")]
+/// );
+/// ```
+pub fn field_dependencies(expr: &Expr) -> Vec<FieldPath> {
+    expr.referenced_fields()
+        .iter()
+        .map(|path| FieldPath::from(path.as_str()))
+        .collect()
+}
+
+/// Cost of visiting any node
+const NODE_COST: u64 = 1;
+/// Extra cost of a function call
+const CALL_COST: u64 = 4;
+/// Extra cost of a `like` glob match
+const LIKE_COST: u64 = 10;
+/// Extra cost of a regular expression match
+const REGEX_COST: u64 = 50;
+/// Assumed collection size: a lambda body runs once per element
+const LAMBDA_ITERATIONS: u64 = 10;
+
+/// Generated tokens of the validator function and its failure reporting
+const BASE_TOKENS: usize = 100;
+/// Generated tokens of any node
+const NODE_TOKENS: usize = 4;
+/// Extra generated tokens of a function call or pipe stage
+const CALL_TOKENS: usize = 8;
+/// Extra generated tokens of a lazily compiled regex
+const REGEX_TOKENS: usize = 80;
+/// Extra generated tokens of a `like` glob match
+const LIKE_TOKENS: usize = 20;
+/// Extra generated tokens of a closure
+const LAMBDA_TOKENS: usize = 10;
+
+/// Size and estimated evaluation cost of an expression
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ComplexityReport {
+    /// Number of AST nodes
+    pub nodes: usize,
+    /// Longest root-to-leaf path
+    pub depth: usize,
+    /// Function calls, including pipe stages
+    pub function_calls: usize,
+    /// Regular expression matches (`matches`)
+    pub regex_count: usize,
+    /// Deepest nesting of lambdas, 0 without any
+    pub lambda_depth: usize,
+    /// Estimated number of Rust tokens in the generated validator
+    pub estimated_code_size: usize,
+    /// Relative cost of one evaluation; lambda bodies count once per
+    /// assumed collection element, regexes and globs are weighted
+    pub estimated_cost: u64,
+}
+
+impl fmt::Display for ComplexityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "nodes:          {}", self.nodes)?;
+        writeln!(f, "depth:          {}", self.depth)?;
+        writeln!(f, "function calls: {}", self.function_calls)?;
+        writeln!(f, "regexes:        {}", self.regex_count)?;
+        writeln!(f, "lambda depth:   {}", self.lambda_depth)?;
+        writeln!(f, "code size:      ~{} tokens", self.estimated_code_size)?;
+        write!(f, "estimated cost: {}", self.estimated_cost)
+    }
+}
+
+/// Measure the size and estimated cost of an expression
+///
+/// # Example
+///
+/// ```
+/// use elo_rust::ast::analysis::complexity;
+/// use elo_rust::parser::Parser;
+///
+/// let expr = Parser::parse("matches(email, '^[a-z]+@') && age >= 18").unwrap();
+/// let report = complexity(&expr);
+/// assert_eq!(report.function_calls, 1);
+/// assert_eq!(report.regex_count, 1);
+/// assert!(report.estimated_cost > report.nodes as u64);
+/// assert_eq!(report.lambda_depth, 0);
+/// ```
+pub fn complexity(expr: &Expr) -> ComplexityReport {
+    let mut report = ComplexityReport {
+        nodes: expr.node_count(),
+        depth: expr.max_depth(),
+        lambda_depth: expr.lambda_depth(),
+        estimated_code_size: BASE_TOKENS,
+        ..ComplexityReport::default()
+    };
+    report.estimated_cost = cost(expr, &mut report);
+    report
+}
+
+/// Estimated cost of `expr`, counting calls, regexes and generated code
+/// size into `report`
+fn cost(expr: &Expr, report: &mut ComplexityReport) -> u64 {
+    let mut own = NODE_COST;
+    report.estimated_code_size += NODE_TOKENS;
+    match expr {
+        Expr::FunctionCall { name, .. } => {
+            report.function_calls += 1;
+            report.estimated_code_size += CALL_TOKENS;
+            own += CALL_COST;
+            if name == "matches" {
+                report.regex_count += 1;
+                report.estimated_code_size += REGEX_TOKENS;
+                own += REGEX_COST;
+            }
+        }
+        Expr::Pipe { functions, .. } => {
+            let stages = functions
+                .iter()
+                .filter(|f| matches!(f, Expr::Identifier(_)))
+                .count();
+            report.function_calls += stages;
+            report.estimated_code_size += CALL_TOKENS * stages;
+            own += CALL_COST * stages as u64;
+        }
+        Expr::BinaryOp {
+            op: BinaryOperator::Like,
+            ..
+        } => {
+            report.estimated_code_size += LIKE_TOKENS;
+            own += LIKE_COST;
+        }
+        Expr::Lambda { body, .. } => {
+            // The body runs per element but is generated once
+            report.estimated_code_size += LAMBDA_TOKENS;
+            return own.saturating_add(cost(body, report).saturating_mul(LAMBDA_ITERATIONS));
+        }
+        _ => {}
+    }
+    expr.children().iter().fold(own, |total, child| {
+        total.saturating_add(cost(child, report))
+    })
+}
+
+impl Expr {
+    /// Total number of nodes in the expression, including this one
+    pub fn node_count(&self) -> usize {
+        1 + self
+            .children()
+            .iter()
+            .map(|c| c.node_count())
+            .sum::<usize>()
+    }
+
+    /// Number of nodes on the longest root-to-leaf path (a leaf has depth 1)
+    pub fn max_depth(&self) -> usize {
+        1 + self
+            .children()
+            .iter()
+            .map(|c| c.max_depth())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Deepest nesting of lambdas: 0 without any, 1 for `fn(x ~> x > 0)`
+    pub fn lambda_depth(&self) -> usize {
+        let inner = self
+            .children()
+            .iter()
+            .map(|c| c.lambda_depth())
+            .max()
+            .unwrap_or(0);
+        match self {
+            Expr::Lambda { .. } => inner + 1,
+            _ => inner,
+        }
+    }
+
+    /// Identifiers used but not bound by an enclosing `let` or lambda
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use elo_rust::parser::Parser;
+    ///
+    /// let expr = Parser::parse("let min = 18 in age >= min && any(roles, fn(r ~> r == admin))")
+    ///     .unwrap();
+    /// let free: Vec<String> = expr.free_variables().into_iter().collect();
+    /// assert_eq!(free, vec!["admin", "age", "roles"]);
+    /// ```
+    pub fn free_variables(&self) -> BTreeSet<String> {
+        let mut out = BTreeSet::new();
+        collect(self, &mut Vec::new(), &mut out, false);
+        out
+    }
+
+    /// Dotted field paths read from free variables
+    ///
+    /// Each maximal field access chain rooted at a free variable is reported
+    /// once: `user.address.city == 'Paris' && age > 18` references
+    /// `user.address.city` and `age`.
+    pub fn referenced_fields(&self) -> BTreeSet<String> {
+        let mut out = BTreeSet::new();
+        collect(self, &mut Vec::new(), &mut out, true);
+        out
+    }
+}
+
+/// Dotted path of a field access chain rooted at an identifier
+fn field_path(expr: &Expr) -> Option<(&str, String)> {
+    match expr {
+        Expr::Identifier(name) => Some((name, name.clone())),
+        Expr::FieldAccess { receiver, field } => {
+            field_path(receiver).map(|(root, path)| (root, format!("{}.{}", path, field)))
+        }
+        _ => None,
+    }
+}
+
+fn collect(expr: &Expr, bound: &mut Vec<String>, out: &mut BTreeSet<String>, paths: bool) {
+    match expr {
+        Expr::Identifier(name) => {
+            if !bound.contains(name) {
+                out.insert(name.clone());
+            }
+        }
+        Expr::FieldAccess { receiver, .. } => match field_path(expr) {
+            Some((root, path)) if paths => {
+                if !bound.iter().any(|b| b == root) {
+                    out.insert(path);
+                }
+            }
+            _ => collect(receiver, bound, out, paths),
+        },
+        Expr::Lambda { param, body } => {
+            bound.push(param.clone());
+            collect(body, bound, out, paths);
+            bound.pop();
+        }
+        // A bare identifier stage (`name |> trim`) names a function, and a
+        // lambda stage binds its parameter
+        Expr::Pipe { value, functions } => match Expr::desugar_pipe(value, functions) {
+            Ok(desugared) => collect(&desugared, bound, out, paths),
+            // A stage the value cannot be piped into still reads the value
+            Err(_) => collect(value, bound, out, paths),
+        },
+        Expr::Let { name, value, body } => {
+            collect(value, bound, out, paths);
+            bound.push(name.clone());
+            collect(body, bound, out, paths);
+            bound.pop();
+        }
+        _ => {
+            for child in expr.children() {
+                collect(child, bound, out, paths);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+
+    fn parse(source: &str) -> Expr {
+        Parser::parse(source).unwrap()
+    }
+
+    fn set(items: &[&str]) -> std::collections::BTreeSet<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_node_count_and_depth() {
+        let leaf = parse("age");
+        assert_eq!(leaf.node_count(), 1);
+        assert_eq!(leaf.max_depth(), 1);
+
+        // (age >= 18) && (!banned): And, Gte, age, 18, Not, banned
+        let expr = parse("age >= 18 && !banned");
+        assert_eq!(expr.node_count(), 6);
+        assert_eq!(expr.max_depth(), 3);
+    }
+
+    #[test]
+    fn test_free_variables_respect_bindings() {
+        assert_eq!(
+            parse("let x = y in x + z").free_variables(),
+            set(&["y", "z"])
+        );
+        // The let value is outside the binding's scope
+        assert_eq!(parse("let x = x in x").free_variables(), set(&["x"]));
+        assert_eq!(
+            parse("all(items, fn(i ~> i.qty > min_qty))").free_variables(),
+            set(&["items", "min_qty"])
+        );
+        assert_eq!(parse("length(name) > 0").free_variables(), set(&["name"]));
+        assert_eq!(parse("name |> trim").free_variables(), set(&["name"]));
+    }
+
+    #[test]
+    fn test_referenced_fields() {
+        assert_eq!(
+            parse("user.address.city == 'Paris' && age > 18 && user.address.zip != ''")
+                .referenced_fields(),
+            set(&["age", "user.address.city", "user.address.zip"])
+        );
+        assert_eq!(
+            parse("any(items, fn(i ~> i.price > limits.max))").referenced_fields(),
+            set(&["items", "limits.max"])
+        );
+        // Field access on a computed receiver reads the free variables inside it
+        assert_eq!(
+            parse("(a ?? b).value > 0").referenced_fields(),
+            set(&["a", "b"])
+        );
+    }
+
+    #[test]
+    fn test_field_path_overlaps() {
+        let user = FieldPath::from("user");
+        let name = FieldPath::from("user.name");
+        let age = FieldPath::from("user.age");
+        assert!(user.overlaps(&name));
+        assert!(name.overlaps(&user));
+        assert!(!name.overlaps(&age));
+        assert!(!FieldPath::from("username").overlaps(&user));
+        assert_eq!(name.root(), "user");
+        assert_eq!(name.to_string(), "user.name");
+    }
+
+    #[test]
+    fn test_complexity() {
+        let report = complexity(&parse("age >= 18"));
+        assert_eq!(report.nodes, 3);
+        assert_eq!(report.depth, 2);
+        assert_eq!(report.function_calls, 0);
+        assert_eq!(report.estimated_cost, 3);
+
+        let report = complexity(&parse("name |> trim |> lowercase"));
+        assert_eq!(report.function_calls, 2);
+
+        // A lambda body runs once per element, so it dominates the cost
+        let flat = complexity(&parse("a > 1 && b > 2"));
+        let nested = complexity(&parse("any(items, fn(i ~> i > 1 && i > 2))"));
+        assert!(nested.estimated_cost > flat.estimated_cost * 5);
+
+        let report = complexity(&parse("matches(a, 'x') || matches(b, 'y')"));
+        assert_eq!(report.regex_count, 2);
+    }
+
+    #[test]
+    fn test_lambda_depth_and_code_size() {
+        assert_eq!(parse("age >= 18").lambda_depth(), 0);
+        assert_eq!(parse("any(xs, fn(x ~> x > 0))").lambda_depth(), 1);
+        let nested = parse("all(xs, fn(x ~> any(x, fn(y ~> y > 0)))) && any(zs, fn(z ~> z))");
+        assert_eq!(nested.lambda_depth(), 2);
+
+        let simple = complexity(&parse("age >= 18"));
+        assert_eq!(simple.estimated_code_size, BASE_TOKENS + 3 * NODE_TOKENS);
+        // A regex is compiled once, but costs far more code than a comparison
+        let regex = complexity(&parse("matches(email, '@')"));
+        assert!(regex.estimated_code_size > simple.estimated_code_size + REGEX_TOKENS);
+    }
+
+    #[test]
+    fn test_field_dependencies() {
+        assert_eq!(
+            field_dependencies(&parse("let x = a.b in x.c > d")),
+            vec![FieldPath::from("a.b"), FieldPath::from("d")]
+        );
+    }
+}
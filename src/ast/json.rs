@@ -0,0 +1,131 @@
+//! Versioned JSON documents of ELO expressions
+//!
+//! Available with the `serde-support` feature. An [`Expr`] serializes as an
+//! object tagged with its variant, `{"kind": "binary_op", "value": {...}}`,
+//! with variants, operators and temporal keywords in snake case. Literals
+//! are `{"integer": 1}`, `{"float": 1.5}` or `{"boolean": true}`.
+//!
+//! Stored or transmitted ASTs are wrapped in an [`AstDocument`], whose
+//! `schema_version` is bumped whenever the shape changes incompatibly, so
+//! readers can reject documents they do not understand instead of
+//! misreading them.
+//!
+//! # Example
+//!
+//! ```
+//! use elo_rust::ast::Expr;
+//! use elo_rust::parser::Parser;
+//!
+//! let expr = Parser::parse("age >= 18").unwrap();
+//! let json = expr.to_json_string();
+//! assert_eq!(
+//!     json,
+//!     r#"{"schema_version":1,"expr":{"kind":"binary_op","value":{"op":"gte","left":{"kind":"identifier","value":"age"},"right":{"kind":"literal","value":{"integer":18}}}}}"#
+//! );
+//! assert_eq!(Expr::from_json_str(&json), Ok(expr));
+//! ```
+
+use super::Expr;
+use serde::{Deserialize, Serialize};
+
+/// Version of the serialized AST shape written by this build
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// An expression together with the schema version it was serialized with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AstDocument {
+    /// The [`SCHEMA_VERSION`] of the writer
+    pub schema_version: u32,
+    /// The expression
+    pub expr: Expr,
+}
+
+impl AstDocument {
+    /// Wrap an expression at the current schema version
+    pub fn new(expr: Expr) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            expr,
+        }
+    }
+
+    /// The expression, if the document has a schema version this build reads
+    pub fn into_expr(self) -> Result<Expr, String> {
+        if self.schema_version != SCHEMA_VERSION {
+            return Err(format!(
+                "unsupported AST schema version {} (expected {})",
+                self.schema_version, SCHEMA_VERSION
+            ));
+        }
+        Ok(self.expr)
+    }
+}
+
+/// A borrowed [`AstDocument`], to serialize without cloning the expression
+#[derive(Serialize)]
+struct AstDocumentRef<'a> {
+    schema_version: u32,
+    expr: &'a Expr,
+}
+
+impl Expr {
+    /// Serialize as a compact JSON [`AstDocument`]
+    ///
+    /// Non-finite float literals, which ELO source cannot spell, are
+    /// written as `null` and do not read back.
+    pub fn to_json_string(&self) -> String {
+        let document = AstDocumentRef {
+            schema_version: SCHEMA_VERSION,
+            expr: self,
+        };
+        serde_json::to_string(&document).expect("an AST has only string map keys")
+    }
+
+    /// Read an expression from a JSON [`AstDocument`]
+    pub fn from_json_str(text: &str) -> Result<Expr, String> {
+        serde_json::from_str::<AstDocument>(text)
+            .map_err(|e| format!("invalid AST JSON: {}", e))?
+            .into_expr()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+
+    #[test]
+    fn test_round_trip() {
+        for source in [
+            "let total = price * qty in total > 0 && total <= 1000",
+            "if vip then discount >= 0.1 else discount == 0",
+            "items |> map(fn(i ~> i.qty * 2.5)) |> sum ?| 0",
+            "nick ?? 'anon' like 'a%' || !active",
+            "created >= TODAY - @duration(P30D) && due < @datetime(2024-01-15T10:30:00Z)",
+            "guard weight in 1kg..=20kg in require ok else 'not ok' && (warn_if x : 'x')",
+            "{name: 'ada', tags: ['a', 'b']}.tags[0] == ${tag} && user.`in` != null",
+            "-x ^ 2 > SOQ",
+        ] {
+            let expr = Parser::parse(source).unwrap();
+            let json = expr.to_json_string();
+            assert_eq!(Expr::from_json_str(&json), Ok(expr), "{}", json);
+        }
+    }
+
+    #[test]
+    fn test_schema_version() {
+        let json = Parser::parse("x").unwrap().to_json_string();
+        assert_eq!(
+            json,
+            r#"{"schema_version":1,"expr":{"kind":"identifier","value":"x"}}"#
+        );
+        let error = Expr::from_json_str(&json.replace(":1,", ":2,")).unwrap_err();
+        assert!(
+            error.contains("unsupported AST schema version 2"),
+            "{}",
+            error
+        );
+        let error = Expr::from_json_str(r#"{"expr":{"kind":"null"}}"#).unwrap_err();
+        assert!(error.contains("schema_version"), "{}", error);
+    }
+}
@@ -5,15 +5,30 @@
 
 use std::fmt;
 
+pub mod analysis;
+#[cfg(feature = "serde-support")]
+pub mod json;
+pub mod printer;
+pub mod solver;
+pub mod span;
 pub mod visitor;
 
-pub use visitor::Visitor;
+pub use span::{Span, SpanTree, Spanned};
+pub use visitor::{walk_expr, walk_expr_mut, Fold, Visitor, VisitorMut, Walker};
 
 /// Top-level ELO expression type
 ///
 /// Represents any valid ELO expression that can be parsed and executed.
 /// This is an exhaustive enum of all expression forms in ELO.
+///
+/// With the `serde-support` feature it (de)serializes as
+/// `{"kind": "binary_op", "value": {...}}` objects, see [`json`].
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde-support",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "kind", content = "value", rename_all = "snake_case")
+)]
 pub enum Expr {
     /// Literal values: numbers (int/float) or booleans
     Literal(Literal),
@@ -24,6 +39,12 @@ pub enum Expr {
     /// Variable reference (identifier)
     Identifier(String),
 
+    /// Parameter placeholder: ${name}
+    ///
+    /// Replaced by a literal from a [`Params`](crate::params::Params) map
+    /// before code generation.
+    Placeholder(String),
+
     /// Field access: receiver.field (e.g., user.age)
     FieldAccess {
         /// The expression being accessed
@@ -32,6 +53,14 @@ pub enum Expr {
         field: String,
     },
 
+    /// Index access: collection[index] (e.g., items[0], matrix[i][j])
+    Index {
+        /// The array being indexed
+        collection: Box<Expr>,
+        /// Zero-based position of the element
+        index: Box<Expr>,
+    },
+
     /// Binary operation: left op right
     BinaryOp {
         /// The binary operator
@@ -89,6 +118,16 @@ pub enum Expr {
     /// Array literal: [expr1, expr2, ...]
     Array(Vec<Expr>),
 
+    /// Range literal: start..end (exclusive) or start..=end (inclusive)
+    Range {
+        /// Lower bound
+        start: Box<Expr>,
+        /// Upper bound
+        end: Box<Expr>,
+        /// Whether the upper bound is part of the range
+        inclusive: bool,
+    },
+
     /// Object literal: {key1: value1, key2: value2, ...}
     Object(Vec<(String, Expr)>),
 
@@ -108,6 +147,16 @@ pub enum Expr {
         alternative: Box<Expr>,
     },
 
+    /// Null-default operator: expr ?? default
+    ///
+    /// Evaluates to `default` when `value` is null or refers to a missing field.
+    NullDefault {
+        /// Possibly missing or null value
+        value: Box<Expr>,
+        /// Value used when `value` is missing or null
+        default: Box<Expr>,
+    },
+
     /// Guard expression: guard condition in expr
     Guard {
         /// Condition that must be true
@@ -116,6 +165,39 @@ pub enum Expr {
         body: Box<Expr>,
     },
 
+    /// Required condition: require condition else 'message'
+    ///
+    /// Evaluates to the condition; when it is false a validation error with
+    /// `message` is emitted. Also written `condition message 'message'`.
+    Require {
+        /// Condition that must hold
+        condition: Box<Expr>,
+        /// Error message reported when the condition is false
+        message: String,
+    },
+
+    /// Warning condition: warn_if condition : 'message'
+    ///
+    /// Never fails validation; when the condition is true a warning with
+    /// `message` is emitted.
+    WarnIf {
+        /// Condition that triggers the warning
+        condition: Box<Expr>,
+        /// Warning message reported when the condition is true
+        message: String,
+    },
+
+    /// Numeric literal with a unit suffix: 50kg, 2.5km
+    Quantity {
+        /// Numeric magnitude
+        value: f64,
+        /// Unit symbol as written (e.g., "kg")
+        unit: String,
+    },
+
+    /// Percentage literal as written: 15% holds 15.0 and stands for 0.15
+    Percentage(f64),
+
     /// Date literal: @date(2024-01-15)
     Date(String), // ISO8601 date: YYYY-MM-DD
 
@@ -132,8 +214,170 @@ pub enum Expr {
     String(String),
 }
 
+impl Expr {
+    /// Direct sub-expressions of this node, in source order
+    pub fn children(&self) -> Vec<&Expr> {
+        match self {
+            Expr::FieldAccess { receiver, .. } => vec![receiver],
+            Expr::Index { collection, index } => vec![collection, index],
+            Expr::BinaryOp { left, right, .. } => vec![left, right],
+            Expr::UnaryOp { operand, .. } => vec![operand],
+            Expr::FunctionCall { args, .. } | Expr::Array(args) => args.iter().collect(),
+            Expr::Lambda { body, .. } => vec![body],
+            Expr::Let { value, body, .. } => vec![value, body],
+            Expr::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => vec![condition, then_branch, else_branch],
+            Expr::Range { start, end, .. } => vec![start, end],
+            Expr::Object(fields) => fields.iter().map(|(_, value)| value).collect(),
+            Expr::Pipe { value, functions } => {
+                std::iter::once(&**value).chain(functions.iter()).collect()
+            }
+            Expr::Alternative {
+                primary,
+                alternative,
+            } => vec![primary, alternative],
+            Expr::NullDefault { value, default } => vec![value, default],
+            Expr::Guard { condition, body } => vec![condition, body],
+            Expr::Require { condition, .. } | Expr::WarnIf { condition, .. } => vec![condition],
+            Expr::Literal(_)
+            | Expr::Null
+            | Expr::Identifier(_)
+            | Expr::Placeholder(_)
+            | Expr::Quantity { .. }
+            | Expr::Percentage(_)
+            | Expr::Date(_)
+            | Expr::DateTime(_)
+            | Expr::Duration(_)
+            | Expr::TemporalKeyword(_)
+            | Expr::String(_) => Vec::new(),
+        }
+    }
+
+    /// Mutable direct sub-expressions of this node, in source order
+    pub fn children_mut(&mut self) -> Vec<&mut Expr> {
+        match self {
+            Expr::FieldAccess { receiver, .. } => vec![receiver],
+            Expr::Index { collection, index } => vec![collection, index],
+            Expr::BinaryOp { left, right, .. } => vec![left, right],
+            Expr::UnaryOp { operand, .. } => vec![operand],
+            Expr::FunctionCall { args, .. } | Expr::Array(args) => args.iter_mut().collect(),
+            Expr::Lambda { body, .. } => vec![body],
+            Expr::Let { value, body, .. } => vec![value, body],
+            Expr::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => vec![condition, then_branch, else_branch],
+            Expr::Range { start, end, .. } => vec![start, end],
+            Expr::Object(fields) => fields.iter_mut().map(|(_, value)| value).collect(),
+            Expr::Pipe { value, functions } => std::iter::once(&mut **value)
+                .chain(functions.iter_mut())
+                .collect(),
+            Expr::Alternative {
+                primary,
+                alternative,
+            } => vec![primary, alternative],
+            Expr::NullDefault { value, default } => vec![value, default],
+            Expr::Guard { condition, body } => vec![condition, body],
+            Expr::Require { condition, .. } | Expr::WarnIf { condition, .. } => vec![condition],
+            Expr::Literal(_)
+            | Expr::Null
+            | Expr::Identifier(_)
+            | Expr::Placeholder(_)
+            | Expr::Quantity { .. }
+            | Expr::Percentage(_)
+            | Expr::Date(_)
+            | Expr::DateTime(_)
+            | Expr::Duration(_)
+            | Expr::TemporalKeyword(_)
+            | Expr::String(_) => Vec::new(),
+        }
+    }
+
+    /// The expression a pipe `value |> stage |> ...` stands for
+    ///
+    /// Each stage is applied to the result of the previous one:
+    ///
+    /// - `v |> f(a)` calls `f(v, a)` and `v |> f` calls `f(v)`
+    /// - `v |> fn(x ~> body)` evaluates `body` with `x` bound to `v`, like
+    ///   `let x = v in body`
+    /// - `v |> (f |> g)` applies the stages of the nested pipe in turn
+    /// - `v |> f() > 0` pipes into the leftmost operand, `f(v) > 0`; the
+    ///   same goes for the other binary operators, `??` and `?|`
+    ///
+    /// Any other stage cannot take the piped value and is an error.
+    pub fn desugar_pipe(value: &Expr, functions: &[Expr]) -> Result<Expr, String> {
+        // Chained pipes parse left-nested: `(v |> f) |> g`
+        let value = match value {
+            Expr::Pipe { value, functions } => Expr::desugar_pipe(value, functions)?,
+            value => value.clone(),
+        };
+        functions.iter().try_fold(value, Expr::pipe_into)
+    }
+
+    /// `stage` applied to `piped`
+    fn pipe_into(piped: Expr, stage: &Expr) -> Result<Expr, String> {
+        Ok(match stage {
+            Expr::FunctionCall { name, args } => Expr::FunctionCall {
+                name: name.clone(),
+                args: std::iter::once(piped).chain(args.iter().cloned()).collect(),
+            },
+            Expr::Identifier(name) => Expr::FunctionCall {
+                name: name.clone(),
+                args: vec![piped],
+            },
+            Expr::Lambda { param, body } => Expr::Let {
+                name: param.clone(),
+                value: Box::new(piped),
+                body: body.clone(),
+            },
+            Expr::Pipe { value, functions } => {
+                Expr::desugar_pipe(&Expr::pipe_into(piped, value)?, functions)?
+            }
+            Expr::BinaryOp { op, left, right } => Expr::BinaryOp {
+                op: *op,
+                left: Box::new(Expr::pipe_into(piped, left)?),
+                right: right.clone(),
+            },
+            Expr::NullDefault { value, default } => Expr::NullDefault {
+                value: Box::new(Expr::pipe_into(piped, value)?),
+                default: default.clone(),
+            },
+            Expr::Alternative {
+                primary,
+                alternative,
+            } => Expr::Alternative {
+                primary: Box::new(Expr::pipe_into(piped, primary)?),
+                alternative: alternative.clone(),
+            },
+            other => {
+                return Err(format!(
+                    "cannot pipe a value into '{}'",
+                    printer::format(other)
+                ))
+            }
+        })
+    }
+
+    /// Check that every pipe in the expression can be desugared
+    pub fn check_pipes(&self) -> Result<(), String> {
+        if let Expr::Pipe { value, functions } = self {
+            Expr::desugar_pipe(value, functions)?;
+        }
+        self.children().into_iter().try_for_each(Expr::check_pipes)
+    }
+}
+
 /// Literal value types
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde-support",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum Literal {
     /// Integer literal
     Integer(i64),
@@ -147,6 +391,11 @@ pub enum Literal {
 
 /// Binary operators supported in ELO
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde-support",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum BinaryOperator {
     // Arithmetic operators
     /// Addition: +
@@ -175,6 +424,10 @@ pub enum BinaryOperator {
     Gt,
     /// Greater than or equal: >=
     Gte,
+    /// Membership: value in collection
+    In,
+    /// Glob pattern match: text like 'pattern'
+    Like,
 
     // Logical operators
     /// Logical AND: &&
@@ -185,6 +438,11 @@ pub enum BinaryOperator {
 
 /// Unary operators supported in ELO
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde-support",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum UnaryOperator {
     /// Logical NOT: !
     Not,
@@ -196,6 +454,11 @@ pub enum UnaryOperator {
 
 /// Temporal keywords for date/time operations
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde-support",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum TemporalKeyword {
     /// Current date and time
     Now,
@@ -250,6 +513,8 @@ impl fmt::Display for BinaryOperator {
             Self::Lte => write!(f, "<="),
             Self::Gt => write!(f, ">"),
             Self::Gte => write!(f, ">="),
+            Self::In => write!(f, "in"),
+            Self::Like => write!(f, "like"),
             Self::And => write!(f, "&&"),
             Self::Or => write!(f, "||"),
         }
@@ -270,6 +535,23 @@ impl fmt::Display for UnaryOperator {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_children() {
+        let expr = Expr::BinaryOp {
+            op: BinaryOperator::Add,
+            left: Box::new(Expr::Identifier("a".to_string())),
+            right: Box::new(Expr::Literal(Literal::Integer(1))),
+        };
+        assert_eq!(
+            expr.children(),
+            vec![
+                &Expr::Identifier("a".to_string()),
+                &Expr::Literal(Literal::Integer(1))
+            ]
+        );
+        assert!(Expr::Null.children().is_empty());
+    }
+
     #[test]
     fn test_literal_integer() {
         let expr = Expr::Literal(Literal::Integer(42));
@@ -443,6 +725,41 @@ mod tests {
         matches!(expr, Expr::Pipe { .. });
     }
 
+    #[test]
+    fn test_desugar_pipe() {
+        let desugar = |source: &str| match crate::parser::Parser::parse(source).unwrap() {
+            Expr::Pipe { value, functions } => Expr::desugar_pipe(&value, &functions).unwrap(),
+            other => panic!("not a pipe: {:?}", other),
+        };
+        let parse = |source: &str| crate::parser::Parser::parse(source).unwrap();
+        assert_eq!(
+            desugar("name |> trim |> starts_with('A')"),
+            parse("starts_with(trim(name), 'A')")
+        );
+        assert_eq!(
+            desugar("price |> fn(p ~> p * 2)"),
+            parse("let p = price in p * 2")
+        );
+        assert_eq!(
+            desugar("name |> (trim |> fn(n ~> length(n)))"),
+            parse("let n = trim(name) in length(n)")
+        );
+        // The value goes to the leftmost operand of a comparison
+        assert_eq!(desugar("items |> sum() > 0"), parse("sum(items) > 0"));
+        assert_eq!(
+            desugar("age |> fn(v ~> v * 2) == 42"),
+            parse("(let v = age in v * 2) == 42")
+        );
+        assert_eq!(desugar("name |> trim ?? 'x'"), parse("trim(name) ?? 'x'"));
+        assert_eq!(
+            Expr::desugar_pipe(&parse("age"), &[parse("-3")]),
+            Err("cannot pipe a value into '-3'".to_string())
+        );
+        let error = crate::parser::Parser::parse("age |> 3 + 1").unwrap_err();
+        assert_eq!(error.message, "cannot pipe a value into '3'");
+        assert_eq!(error.column, 8);
+    }
+
     #[test]
     fn test_guard() {
         let expr = Expr::Guard {
@@ -456,6 +773,59 @@ mod tests {
         matches!(expr, Expr::Guard { .. });
     }
 
+    #[test]
+    fn test_null_default() {
+        let expr = Expr::NullDefault {
+            value: Box::new(Expr::Identifier("limit".to_string())),
+            default: Box::new(Expr::Literal(Literal::Integer(1000))),
+        };
+        assert!(matches!(expr, Expr::NullDefault { .. }));
+    }
+
+    #[test]
+    fn test_range() {
+        let expr = Expr::Range {
+            start: Box::new(Expr::Literal(Literal::Integer(1))),
+            end: Box::new(Expr::Literal(Literal::Integer(10))),
+            inclusive: true,
+        };
+        assert!(matches!(
+            expr,
+            Expr::Range {
+                inclusive: true,
+                ..
+            }
+        ));
+        assert_eq!(BinaryOperator::In.to_string(), "in");
+    }
+
+    #[test]
+    fn test_quantity() {
+        let expr = Expr::Quantity {
+            value: 50.0,
+            unit: "kg".to_string(),
+        };
+        assert!(matches!(expr, Expr::Quantity { .. }));
+    }
+
+    #[test]
+    fn test_require() {
+        let expr = Expr::Require {
+            condition: Box::new(Expr::Identifier("verified".to_string())),
+            message: "Account must be verified".to_string(),
+        };
+        matches!(expr, Expr::Require { .. });
+    }
+
+    #[test]
+    fn test_warn_if() {
+        let expr = Expr::WarnIf {
+            condition: Box::new(Expr::Identifier("legacy".to_string())),
+            message: "Legacy account".to_string(),
+        };
+        matches!(expr, Expr::WarnIf { .. });
+    }
+
     #[test]
     fn test_alternative() {
         let expr = Expr::Alternative {
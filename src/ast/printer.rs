@@ -0,0 +1,578 @@
+//! Rendering expressions back to ELO source
+//!
+//! [`format`] prints an [`Expr`] on one line in canonical form: single
+//! spaces around binary operators, parentheses only where precedence
+//! requires them, and quantifiers in their function form. Parsing the
+//! output gives back the same expression.
+//!
+//! [`format_with`] takes [`FormatOptions`] to spread rules over several
+//! indented lines, as tooling and generated doc comments show them, or to
+//! print operators without spaces.
+//!
+//! # Example
+//!
+//! ```
+//! use elo_rust::ast::printer::{self, FormatOptions};
+//! use elo_rust::parser::Parser;
+//!
+//! let expr = Parser::parse("(age>=18)&&((a+b)*c<10)").unwrap();
+//! assert_eq!(printer::format(&expr), "age >= 18 && (a + b) * c < 10");
+//!
+//! let options = FormatOptions {
+//!     indent: Some(2),
+//!     ..FormatOptions::default()
+//! };
+//! assert_eq!(
+//!     printer::format_with(&expr, &options),
+//!     "age >= 18\n  && (a + b) * c < 10"
+//! );
+//! ```
+
+use super::{BinaryOperator, Expr, Literal, TemporalKeyword, UnaryOperator};
+use crate::codegen::float::format_float;
+use crate::parser::{Lexer, Token};
+
+/// Binding strength of each syntactic level, loosest first
+///
+/// Prefix forms (`let`, `if`, `guard`, lambdas, `require`, `warn_if`)
+/// extend as far right as possible and sit at the lowest level.
+mod precedence {
+    pub(super) const PREFIX: u8 = 0;
+    pub(super) const PIPE: u8 = 1;
+    pub(super) const ALTERNATIVE: u8 = 2;
+    pub(super) const OR: u8 = 3;
+    pub(super) const AND: u8 = 4;
+    pub(super) const EQUALITY: u8 = 5;
+    pub(super) const COMPARISON: u8 = 6;
+    pub(super) const NULL_DEFAULT: u8 = 7;
+    pub(super) const RANGE: u8 = 8;
+    pub(super) const ADDITIVE: u8 = 9;
+    pub(super) const MULTIPLICATIVE: u8 = 10;
+    pub(super) const POWER: u8 = 11;
+    pub(super) const UNARY: u8 = 12;
+    pub(super) const PRIMARY: u8 = 13;
+}
+
+/// Layout of the source printed by [`format_with`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// Spaces per indentation level; `None` prints on one line
+    ///
+    /// With indentation, each operand of a top-level `&&` or `||` chain
+    /// starts a line with its operator, `let` and `guard` bodies start a
+    /// line of their own and `if` branches are indented below `then` and
+    /// `else`.
+    pub indent: Option<usize>,
+    /// Put spaces around infix operators (`a + b`) rather than not (`a+b`)
+    ///
+    /// Word operators such as `in` keep their spaces either way.
+    pub operator_spacing: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            indent: None,
+            operator_spacing: true,
+        }
+    }
+}
+
+/// Render `expr` as canonical ELO source
+pub fn format(expr: &Expr) -> String {
+    format_with(expr, &FormatOptions::default())
+}
+
+/// Render `expr` as ELO source laid out as `options` say
+pub fn format_with(expr: &Expr, options: &FormatOptions) -> String {
+    let printer = Printer { options };
+    match options.indent {
+        Some(width) => printer.block(expr, width, 0),
+        None => printer.format(expr),
+    }
+}
+
+/// Renders expressions with a set of [`FormatOptions`]
+struct Printer<'a> {
+    options: &'a FormatOptions,
+}
+
+impl Printer<'_> {
+    /// Render `expr` on one line
+    fn format(&self, expr: &Expr) -> String {
+        self.print(expr, precedence::PREFIX)
+    }
+
+    /// Render `expr` over several lines, `depth` levels of `width` spaces deep
+    fn block(&self, expr: &Expr, width: usize, depth: usize) -> String {
+        let pad = |depth: usize| " ".repeat(width * depth);
+        match expr {
+            Expr::Let { name, value, body } => format!(
+                "let {} = {} in\n{}{}",
+                identifier(name),
+                self.before_in(value),
+                pad(depth),
+                self.block(body, width, depth)
+            ),
+            Expr::Guard { condition, body } => format!(
+                "guard {} in\n{}{}",
+                self.before_in(condition),
+                pad(depth),
+                self.block(body, width, depth)
+            ),
+            Expr::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => format!(
+                "if {} then\n{}{}\n{}else\n{}{}",
+                self.format(condition),
+                pad(depth + 1),
+                self.block(then_branch, width, depth + 1),
+                pad(depth),
+                pad(depth + 1),
+                self.block(else_branch, width, depth + 1)
+            ),
+            Expr::BinaryOp {
+                op: op @ (BinaryOperator::And | BinaryOperator::Or),
+                ..
+            } => {
+                let level = binary_precedence(*op);
+                let mut operands = chain(expr, *op).into_iter();
+                let mut text = operands
+                    .next()
+                    .map(|first| self.print(first, level))
+                    .unwrap_or_default();
+                for operand in operands {
+                    text.push_str(&format!(
+                        "\n{}{} {}",
+                        pad(depth + 1),
+                        op,
+                        self.print(operand, level + 1)
+                    ));
+                }
+                text
+            }
+            _ => self.format(expr),
+        }
+    }
+
+    /// `left op right`, spaced as the options say
+    fn infix(&self, left: String, op: &str, right: String) -> String {
+        let spaced = self.options.operator_spacing
+            || op.starts_with(char::is_alphabetic)
+            // Keep `a - -1` from reading as `a--1`
+            || right.starts_with(['-', '+']);
+        if spaced {
+            format!("{} {} {}", left, op, right)
+        } else {
+            format!("{}{}{}", left, op, right)
+        }
+    }
+
+    /// Render `expr`, parenthesized if it binds looser than `min`
+    fn print(&self, expr: &Expr, min: u8) -> String {
+        let text = match expr {
+            Expr::Literal(Literal::Integer(n)) => n.to_string(),
+            Expr::Literal(Literal::Float(f)) => format_float(*f),
+            Expr::Literal(Literal::Boolean(b)) => b.to_string(),
+            Expr::Null => "null".to_string(),
+            Expr::String(s) => quote(s),
+            Expr::Identifier(name) => identifier(name),
+            Expr::Placeholder(name) => format!("${{{}}}", name),
+            Expr::FieldAccess { receiver, field } => {
+                format!(
+                    "{}.{}",
+                    self.print(receiver, precedence::PRIMARY),
+                    identifier(field)
+                )
+            }
+            Expr::Index { collection, index } => format!(
+                "{}[{}]",
+                self.print(collection, precedence::PRIMARY),
+                self.format(index)
+            ),
+            Expr::BinaryOp { op, left, right } => {
+                let level = binary_precedence(*op);
+                // `^` groups to the right, everything else to the left
+                let (left_min, right_min) = if *op == BinaryOperator::Pow {
+                    (level + 1, level)
+                } else {
+                    (level, level + 1)
+                };
+                self.infix(
+                    self.print(left, left_min),
+                    &op.to_string(),
+                    self.print(right, right_min),
+                )
+            }
+            Expr::UnaryOp { op, operand } => {
+                let operand_text = self.print(operand, precedence::UNARY);
+                // Keep `- -x` and `-(-1)` from lexing differently
+                if matches!(op, UnaryOperator::Neg | UnaryOperator::Plus)
+                    && operand_text.starts_with(['-', '+'])
+                {
+                    format!("{}({})", op, operand_text)
+                } else {
+                    format!("{}{}", op, operand_text)
+                }
+            }
+            Expr::FunctionCall { name, args } => format!("{}({})", name, self.list(args)),
+            Expr::Lambda { param, body } => {
+                format!("fn({} ~> {})", identifier(param), self.format(body))
+            }
+            Expr::Let { name, value, body } => format!(
+                "let {} = {} in {}",
+                identifier(name),
+                self.before_in(value),
+                self.format(body)
+            ),
+            Expr::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => format!(
+                "if {} then {} else {}",
+                self.format(condition),
+                self.format(then_branch),
+                self.format(else_branch)
+            ),
+            Expr::Array(elements) => format!("[{}]", self.list(elements)),
+            Expr::Object(fields) => {
+                let fields: Vec<String> = fields
+                    .iter()
+                    .map(|(key, value)| {
+                        let key = if is_plain_identifier(key) {
+                            key.clone()
+                        } else {
+                            quote(key)
+                        };
+                        format!("{}: {}", key, self.format(value))
+                    })
+                    .collect();
+                format!("{{{}}}", fields.join(", "))
+            }
+            Expr::Range {
+                start,
+                end,
+                inclusive,
+            } => format!(
+                "{}{}{}",
+                self.print(start, precedence::ADDITIVE),
+                if *inclusive { "..=" } else { ".." },
+                self.print(end, precedence::ADDITIVE)
+            ),
+            Expr::Pipe { value, functions } => {
+                let mut text = self.print(value, precedence::PIPE);
+                for function in functions {
+                    text = self.infix(text, "|>", self.print(function, precedence::ALTERNATIVE));
+                }
+                text
+            }
+            Expr::Alternative {
+                primary,
+                alternative,
+            } => self.infix(
+                self.print(primary, precedence::OR),
+                "?|",
+                self.print(alternative, precedence::ALTERNATIVE),
+            ),
+            Expr::NullDefault { value, default } => self.infix(
+                self.print(value, precedence::RANGE),
+                "??",
+                self.print(default, precedence::NULL_DEFAULT),
+            ),
+            Expr::Guard { condition, body } => {
+                format!(
+                    "guard {} in {}",
+                    self.before_in(condition),
+                    self.format(body)
+                )
+            }
+            Expr::Require { condition, message } => {
+                format!("require {} else {}", self.format(condition), quote(message))
+            }
+            Expr::WarnIf { condition, message } => {
+                format!("warn_if {} : {}", self.format(condition), quote(message))
+            }
+            // Suffixed numbers keep their written form: `50kg`, `15%`
+            Expr::Quantity { value, unit } => format!("{}{}", value, unit),
+            Expr::Percentage(percent) => format!("{}%", percent),
+            Expr::Date(date) => format!("@date({})", date),
+            Expr::DateTime(datetime) => format!("@datetime({})", datetime),
+            Expr::Duration(duration) => format!("@duration({})", duration),
+            Expr::TemporalKeyword(keyword) => temporal_keyword(*keyword).to_string(),
+        };
+        if level(expr) < min {
+            format!("({})", text)
+        } else {
+            text
+        }
+    }
+
+    /// Comma-separated expressions
+    fn list(&self, exprs: &[Expr]) -> String {
+        exprs
+            .iter()
+            .map(|expr| self.format(expr))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Render the part of `let`/`guard` before `in`, where a bare `in`
+    /// operator would end it early
+    fn before_in(&self, expr: &Expr) -> String {
+        let text = self.format(expr);
+        if mentions_in(expr) {
+            format!("({})", text)
+        } else {
+            text
+        }
+    }
+}
+
+/// The operands of a left-nested chain of `op`
+fn chain(expr: &Expr, op: BinaryOperator) -> Vec<&Expr> {
+    match expr {
+        Expr::BinaryOp {
+            op: chained,
+            left,
+            right,
+        } if *chained == op => {
+            let mut operands = chain(left, op);
+            operands.push(right);
+            operands
+        }
+        expr => vec![expr],
+    }
+}
+
+/// Syntactic level of an expression
+fn level(expr: &Expr) -> u8 {
+    match expr {
+        Expr::Let { .. }
+        | Expr::If { .. }
+        | Expr::Guard { .. }
+        | Expr::Require { .. }
+        | Expr::WarnIf { .. } => precedence::PREFIX,
+        Expr::Pipe { .. } => precedence::PIPE,
+        Expr::BinaryOp { op, .. } => binary_precedence(*op),
+        Expr::Alternative { .. } => precedence::ALTERNATIVE,
+        Expr::NullDefault { .. } => precedence::NULL_DEFAULT,
+        Expr::Range { .. } => precedence::RANGE,
+        Expr::UnaryOp { .. } => precedence::UNARY,
+        // A negative literal prints with a leading minus sign
+        Expr::Literal(Literal::Integer(n)) if *n < 0 => precedence::UNARY,
+        Expr::Literal(Literal::Float(f)) if f.is_sign_negative() => precedence::UNARY,
+        Expr::Quantity { value, .. } | Expr::Percentage(value) if value.is_sign_negative() => {
+            precedence::UNARY
+        }
+        _ => precedence::PRIMARY,
+    }
+}
+
+fn binary_precedence(op: BinaryOperator) -> u8 {
+    match op {
+        BinaryOperator::Or => precedence::OR,
+        BinaryOperator::And => precedence::AND,
+        BinaryOperator::Eq | BinaryOperator::Neq => precedence::EQUALITY,
+        BinaryOperator::Lt
+        | BinaryOperator::Lte
+        | BinaryOperator::Gt
+        | BinaryOperator::Gte
+        | BinaryOperator::In
+        | BinaryOperator::Like => precedence::COMPARISON,
+        BinaryOperator::Add | BinaryOperator::Sub => precedence::ADDITIVE,
+        BinaryOperator::Mul | BinaryOperator::Div | BinaryOperator::Mod => {
+            precedence::MULTIPLICATIVE
+        }
+        BinaryOperator::Pow => precedence::POWER,
+    }
+}
+
+fn mentions_in(expr: &Expr) -> bool {
+    matches!(
+        expr,
+        Expr::BinaryOp {
+            op: BinaryOperator::In,
+            ..
+        }
+    ) || expr.children().into_iter().any(mentions_in)
+}
+
+/// Quote a string literal, escaping what the lexer unescapes
+fn quote(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('\'');
+    for ch in s.chars() {
+        match ch {
+            '\'' => quoted.push_str("\\'"),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            ch => quoted.push(ch),
+        }
+    }
+    quoted.push('\'');
+    quoted
+}
+
+/// A name, in backticks if it would otherwise read as a keyword
+fn identifier(name: &str) -> String {
+    if is_plain_identifier(name) {
+        name.to_string()
+    } else {
+        format!("`{}`", name)
+    }
+}
+
+fn is_plain_identifier(name: &str) -> bool {
+    matches!(
+        Lexer::new(name).tokenize().as_deref(),
+        Ok([Token::Identifier(ident), Token::Eof]) if ident == name
+    )
+}
+
+fn temporal_keyword(keyword: TemporalKeyword) -> &'static str {
+    match keyword {
+        TemporalKeyword::Now => "NOW",
+        TemporalKeyword::Today => "TODAY",
+        TemporalKeyword::Tomorrow => "TOMORROW",
+        TemporalKeyword::Yesterday => "YESTERDAY",
+        TemporalKeyword::StartOfDay => "SOD",
+        TemporalKeyword::EndOfDay => "EOD",
+        TemporalKeyword::StartOfWeek => "SOW",
+        TemporalKeyword::EndOfWeek => "EOW",
+        TemporalKeyword::StartOfMonth => "SOM",
+        TemporalKeyword::EndOfMonth => "EOM",
+        TemporalKeyword::StartOfQuarter => "SOQ",
+        TemporalKeyword::EndOfQuarter => "EOQ",
+        TemporalKeyword::StartOfYear => "SOY",
+        TemporalKeyword::EndOfYear => "EOY",
+        TemporalKeyword::BeginningOfTime => "BOT",
+        TemporalKeyword::EndOfTime => "EOT",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+
+    fn round_trip(source: &str) -> String {
+        let expr = Parser::parse(source).unwrap();
+        let printed = format(&expr);
+        assert_eq!(Parser::parse(&printed).unwrap(), expr, "{}", printed);
+        printed
+    }
+
+    fn round_trip_with(source: &str, options: &FormatOptions) -> String {
+        let expr = Parser::parse(source).unwrap();
+        let printed = format_with(&expr, options);
+        assert_eq!(Parser::parse(&printed).unwrap(), expr, "{}", printed);
+        printed
+    }
+
+    #[test]
+    fn test_indented_layout() {
+        let options = FormatOptions {
+            indent: Some(4),
+            ..FormatOptions::default()
+        };
+        assert_eq!(
+            round_trip_with("a && (b || c) && d", &options),
+            "a\n    && (b || c)\n    && d"
+        );
+        assert_eq!(
+            round_trip_with(
+                "let total = price * qty in if vip then total > 0 else total > 10 && ok",
+                &options
+            ),
+            "let total = price * qty in\nif vip then\n    total > 0\nelse\n    total > 10\n        && ok"
+        );
+        assert_eq!(
+            round_trip_with("guard (x in [1, 2]) in x > 0", &options),
+            "guard (x in [1, 2]) in\nx > 0"
+        );
+        assert_eq!(round_trip_with("a + b", &options), "a + b");
+    }
+
+    #[test]
+    fn test_operator_spacing() {
+        let options = FormatOptions {
+            operator_spacing: false,
+            ..FormatOptions::default()
+        };
+        assert_eq!(
+            round_trip_with("(a + b) * c >= 10 && x in [1, 2]", &options),
+            "(a+b)*c>=10&&x in [1, 2]"
+        );
+        assert_eq!(round_trip_with("a - -1 ?? b", &options), "a - -1??b");
+        assert_eq!(
+            round_trip_with("xs |> sum() ?| 0", &options),
+            "xs|>sum()?|0"
+        );
+    }
+
+    #[test]
+    fn test_minimal_parentheses() {
+        assert_eq!(round_trip("(a - (b - c)) * 2"), "(a - (b - c)) * 2");
+        assert_eq!(
+            round_trip("(a ^ b) ^ c + a ^ (b ^ c)"),
+            "(a ^ b) ^ c + a ^ b ^ c"
+        );
+        assert_eq!(
+            round_trip("!(a && b) || -(-x) > 0"),
+            "!(a && b) || -(-x) > 0"
+        );
+        assert_eq!(
+            round_trip("(x ?? 1) + 2 in 0..=10"),
+            "(x ?? 1) + 2 in 0..=10"
+        );
+        assert_eq!(
+            round_trip("(a ?| b) ?| c || d ?| (e |> f())"),
+            "(a ?| b) ?| c || d ?| (e |> f())"
+        );
+        assert_eq!(
+            round_trip("(if a then 1 else 2) + 1"),
+            "(if a then 1 else 2) + 1"
+        );
+    }
+
+    #[test]
+    fn test_literals_and_forms() {
+        assert_eq!(
+            round_trip("{'first name': 'O\\'Brien', n: 1.5, w: 50kg}"),
+            "{'first name': 'O\\'Brien', n: 1.5, w: 50kg}"
+        );
+        assert_eq!(
+            round_trip("rate <= 15% && fee < 2.5% % 1 && w > 0.5kg"),
+            "rate <= 15% && fee < 2.5% % 1 && w > 0.5kg"
+        );
+        assert_eq!(
+            round_trip("stock.`in` > 0 && `guard`"),
+            "stock.`in` > 0 && `guard`"
+        );
+        assert_eq!(
+            round_trip("due - @duration( PT1H30M ) >= @date(2024-01-15)"),
+            "due - @duration(PT1H30M) >= @date(2024-01-15)"
+        );
+        assert_eq!(
+            round_trip("matrix[i][j + 1] > (-xs)[0].n"),
+            "matrix[i][j + 1] > (-xs)[0].n"
+        );
+        assert_eq!(
+            round_trip("any item in items satisfies item.qty > ${min}"),
+            "any(items, fn(item ~> item.qty > ${min}))"
+        );
+        assert_eq!(
+            round_trip("let ok = (x in [1, 2]) in ok && created < TODAY"),
+            "let ok = (x in [1, 2]) in ok && created < TODAY"
+        );
+        assert_eq!(
+            round_trip("require age >= 18 else 'adults only'"),
+            "require age >= 18 else 'adults only'"
+        );
+    }
+}
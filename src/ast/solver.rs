@@ -0,0 +1,743 @@
+//! Bounded search for inputs that violate or satisfy an expression
+//!
+//! Every field an expression reads is given a small set of candidate values
+//! derived from the constants it is compared with: `age >= 18` tries 17, 18
+//! and 19, `length(name) > 3` tries strings of length 2 to 4, `status ==
+//! 'active'` tries `'active'` and a different string. The expression is then
+//! evaluated for each combination of candidates.
+//!
+//! When every field is only compared with constants, the candidates cover
+//! every way the comparisons can turn out, so exhausting them proves that no
+//! violating input exists. Comparisons between fields or arithmetic on them
+//! make the search a heuristic, and the outcome is then
+//! [`SolverOutcome::Unknown`] instead of [`SolverOutcome::Valid`].
+//!
+//! Integers, floats, booleans and strings (through equality, membership and
+//! `length`) are supported; other expressions yield
+//! [`SolverOutcome::Unknown`].
+
+use super::{BinaryOperator, Expr, Literal, UnaryOperator};
+use crate::codegen::float::format_float;
+use crate::codegen::optimization::Optimizer;
+use crate::codegen::types::{RustType, TypeInfo};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Upper bound on the number of candidate inputs evaluated
+pub const MAX_CANDIDATE_INPUTS: usize = 100_000;
+
+/// Value of an input field in a [`Counterexample`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputValue {
+    /// Integer value
+    Integer(i64),
+    /// Float value
+    Float(f64),
+    /// Boolean value
+    Boolean(bool),
+    /// String value
+    String(String),
+}
+
+impl fmt::Display for InputValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputValue::Integer(n) => write!(f, "{}", n),
+            InputValue::Float(x) => write!(f, "{}", format_float(*x)),
+            InputValue::Boolean(b) => write!(f, "{}", b),
+            InputValue::String(s) => write!(f, "'{}'", s),
+        }
+    }
+}
+
+/// Field values, keyed by dotted field path, for which an expression has a
+/// given result
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Counterexample {
+    /// Value of every field the expression reads
+    pub fields: BTreeMap<String, InputValue>,
+}
+
+impl Counterexample {
+    /// Value assigned to a field
+    pub fn get(&self, path: &str) -> Option<&InputValue> {
+        self.fields.get(path)
+    }
+}
+
+impl fmt::Display for Counterexample {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.fields.is_empty() {
+            return write!(f, "any input");
+        }
+        let fields: Vec<String> = self
+            .fields
+            .iter()
+            .map(|(path, value)| format!("{} = {}", path, value))
+            .collect();
+        write!(f, "{}", fields.join(", "))
+    }
+}
+
+/// Result of a bounded search
+#[derive(Debug, Clone, PartialEq)]
+pub enum SolverOutcome {
+    /// An input for which the expression has the searched-for result
+    Counterexample(Counterexample),
+    /// No input gives the searched-for result
+    Valid,
+    /// The search was inconclusive
+    Unknown(String),
+}
+
+/// Find an input for which `expr` is false
+///
+/// `ctx` gives the types of the input's top-level fields; fields it does not
+/// declare are typed from how the expression uses them.
+///
+/// # Example
+///
+/// ```
+/// use elo_rust::ast::analysis::{find_counterexample, InputValue, SolverOutcome};
+/// use elo_rust::codegen::types::TypeInfo;
+/// use elo_rust::parser::Parser;
+///
+/// let expr = Parser::parse("age >= 18 && length(name) > 0").unwrap();
+/// match find_counterexample(&expr, &TypeInfo::new("User")) {
+///     SolverOutcome::Counterexample(input) => {
+///         assert_eq!(input.get("age"), Some(&InputValue::Integer(17)));
+///     }
+///     other => panic!("expected a counterexample, got {:?}", other),
+/// }
+///
+/// let tautology = Parser::parse("age > 5 || age <= 5").unwrap();
+/// assert_eq!(
+///     find_counterexample(&tautology, &TypeInfo::new("User")),
+///     SolverOutcome::Valid
+/// );
+/// ```
+pub fn find_counterexample(expr: &Expr, ctx: &TypeInfo) -> SolverOutcome {
+    search(expr, ctx, false)
+}
+
+/// Find an input for which `expr` is true
+///
+/// [`SolverOutcome::Valid`] means that no input passes the expression.
+pub fn find_example(expr: &Expr, ctx: &TypeInfo) -> SolverOutcome {
+    search(expr, ctx, true)
+}
+
+/// Type a field is given candidates for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FieldType {
+    Integer,
+    Float,
+    Boolean,
+    String,
+}
+
+/// What the expression reveals about one field
+#[derive(Debug, Default)]
+struct FieldUsage {
+    ty: Option<FieldType>,
+    /// Constants the field (or its length) is compared with
+    constants: Vec<Literal>,
+    strings: Vec<String>,
+    lengths: Vec<i64>,
+}
+
+/// Facts collected from the expression before searching
+#[derive(Debug, Default)]
+struct Analysis {
+    fields: BTreeMap<String, FieldUsage>,
+    /// Every field is only compared with constants
+    exhaustive: bool,
+    unsupported: Option<String>,
+}
+
+fn search(expr: &Expr, ctx: &TypeInfo, wanted: bool) -> SolverOutcome {
+    // Folding turns `-5` into a literal the candidates can be derived from
+    let folded = Optimizer::fold_constants(expr);
+    let expr = match &folded {
+        Expr::Require { condition, .. } => condition.as_ref(),
+        other => other,
+    };
+    let mut analysis = Analysis {
+        exhaustive: true,
+        ..Analysis::default()
+    };
+    analysis.visit(expr, Some(FieldType::Boolean));
+    if let Some(reason) = analysis.unsupported {
+        return SolverOutcome::Unknown(reason);
+    }
+
+    let mut fields = Vec::with_capacity(analysis.fields.len());
+    for (path, usage) in &analysis.fields {
+        let declared = if path.contains('.') {
+            None
+        } else {
+            ctx.get_field(path)
+        };
+        let ty = match declared.map(declared_type) {
+            Some(Ok(ty)) => ty,
+            Some(Err(ty)) => {
+                return SolverOutcome::Unknown(format!(
+                    "field '{}' has unsupported type {}",
+                    path,
+                    ty.to_rust_string()
+                ))
+            }
+            None => usage.ty.unwrap_or(FieldType::Integer),
+        };
+        fields.push((path.clone(), candidates(ty, usage)));
+    }
+
+    let total = fields
+        .iter()
+        .try_fold(1usize, |total, (_, values)| total.checked_mul(values.len()))
+        .filter(|&total| total <= MAX_CANDIDATE_INPUTS);
+    let Some(total) = total else {
+        return SolverOutcome::Unknown(format!(
+            "more than {} candidate inputs",
+            MAX_CANDIDATE_INPUTS
+        ));
+    };
+
+    let mut exhaustive = analysis.exhaustive;
+    let mut env = BTreeMap::new();
+    for index in 0..total {
+        // Mixed-radix decoding of `index`, first field varying slowest
+        let mut rest = index;
+        for (path, values) in fields.iter().rev() {
+            env.insert(path.clone(), values[rest % values.len()].clone());
+            rest /= values.len();
+        }
+        match evaluate(expr, &env) {
+            Some(InputValue::Boolean(result)) if result == wanted => {
+                return SolverOutcome::Counterexample(Counterexample { fields: env });
+            }
+            Some(InputValue::Boolean(_)) => {}
+            // Type errors and failed arithmetic leave this input undecided
+            _ => exhaustive = false,
+        }
+    }
+
+    if exhaustive {
+        SolverOutcome::Valid
+    } else {
+        SolverOutcome::Unknown(format!(
+            "no {} input among {} candidates",
+            if wanted { "passing" } else { "failing" },
+            total
+        ))
+    }
+}
+
+fn declared_type(ty: &RustType) -> Result<FieldType, &RustType> {
+    match ty {
+        RustType::Integer => Ok(FieldType::Integer),
+        RustType::Float => Ok(FieldType::Float),
+        RustType::Bool => Ok(FieldType::Boolean),
+        RustType::String => Ok(FieldType::String),
+        other => Err(other),
+    }
+}
+
+impl Analysis {
+    /// Record the fields read by `expr`, which is expected to have type `ty`
+    fn visit(&mut self, expr: &Expr, ty: Option<FieldType>) {
+        if self.unsupported.is_some() {
+            return;
+        }
+        if let Some(path) = field_path(expr) {
+            let usage = self.fields.entry(path).or_default();
+            if usage.ty.is_none() {
+                usage.ty = ty;
+            }
+            return;
+        }
+        match expr {
+            Expr::Literal(_) | Expr::String(_) => {}
+            Expr::BinaryOp { op, left, right } => self.visit_binary(*op, left, right),
+            Expr::UnaryOp { op, operand } => match op {
+                UnaryOperator::Not => self.visit(operand, Some(FieldType::Boolean)),
+                UnaryOperator::Neg | UnaryOperator::Plus => {
+                    self.exhaustive &= field_path(operand).is_none();
+                    self.visit(operand, Some(FieldType::Integer));
+                }
+            },
+            Expr::FunctionCall { name, args } if name == "length" && args.len() == 1 => {
+                self.exhaustive = false;
+                self.visit(&args[0], Some(FieldType::String));
+            }
+            Expr::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.visit(condition, Some(FieldType::Boolean));
+                self.visit(then_branch, ty);
+                self.visit(else_branch, ty);
+            }
+            Expr::FunctionCall { name, .. } => {
+                self.unsupported = Some(format!("unsupported function '{}'", name));
+            }
+            other => {
+                self.unsupported = Some(format!("unsupported expression: {}", kind(other)));
+            }
+        }
+    }
+
+    fn visit_binary(&mut self, op: BinaryOperator, left: &Expr, right: &Expr) {
+        match op {
+            BinaryOperator::And | BinaryOperator::Or => {
+                self.visit(left, Some(FieldType::Boolean));
+                self.visit(right, Some(FieldType::Boolean));
+            }
+            BinaryOperator::Eq
+            | BinaryOperator::Neq
+            | BinaryOperator::Lt
+            | BinaryOperator::Lte
+            | BinaryOperator::Gt
+            | BinaryOperator::Gte => {
+                if !self.compare_with_constant(op, left, right)
+                    && !self.compare_with_constant(op, right, left)
+                {
+                    // Comparing two computed values: candidates may miss cases
+                    self.exhaustive = false;
+                    let ty = literal_type(left).or_else(|| literal_type(right));
+                    self.visit(left, ty);
+                    self.visit(right, ty);
+                }
+            }
+            BinaryOperator::In => match right {
+                Expr::Array(elements) if field_path(left).is_some() => {
+                    for element in elements {
+                        self.visit(element, None);
+                        self.compare_with_constant(BinaryOperator::Eq, left, element);
+                    }
+                    if let Some(path) = field_path(left) {
+                        let usage = self.fields.entry(path).or_default();
+                        if usage.ty.is_none() {
+                            usage.ty = elements.first().and_then(literal_type);
+                        }
+                    }
+                }
+                _ => {
+                    self.unsupported = Some("unsupported membership test".to_string());
+                }
+            },
+            BinaryOperator::Add
+            | BinaryOperator::Sub
+            | BinaryOperator::Mul
+            | BinaryOperator::Div
+            | BinaryOperator::Mod => {
+                self.exhaustive = false;
+                let ty = literal_type(left)
+                    .or_else(|| literal_type(right))
+                    .or(Some(FieldType::Integer));
+                self.visit(left, ty);
+                self.visit(right, ty);
+            }
+            BinaryOperator::Pow | BinaryOperator::Like => {
+                self.unsupported = Some(format!("unsupported operator: {}", op));
+            }
+        }
+    }
+
+    /// Record `subject op constant` if `subject` is a field or the length of
+    /// one and `constant` a literal
+    fn compare_with_constant(
+        &mut self,
+        op: BinaryOperator,
+        subject: &Expr,
+        constant: &Expr,
+    ) -> bool {
+        let (path, is_length) = match subject {
+            Expr::FunctionCall { name, args } if name == "length" && args.len() == 1 => {
+                match field_path(&args[0]) {
+                    Some(path) => (path, true),
+                    None => return false,
+                }
+            }
+            _ => match field_path(subject) {
+                Some(path) => (path, false),
+                None => return false,
+            },
+        };
+        let usage = self.fields.entry(path).or_default();
+        match (constant, is_length) {
+            (Expr::Literal(Literal::Integer(n)), true) => {
+                usage.ty.get_or_insert(FieldType::String);
+                usage.lengths.push(*n);
+            }
+            (Expr::Literal(literal), false) => {
+                let ty = match literal {
+                    Literal::Integer(_) => FieldType::Integer,
+                    Literal::Float(_) => FieldType::Float,
+                    Literal::Boolean(_) => FieldType::Boolean,
+                };
+                usage.ty.get_or_insert(ty);
+                usage.constants.push(literal.clone());
+            }
+            (Expr::Percentage(percent), false) => {
+                usage.ty.get_or_insert(FieldType::Float);
+                usage.constants.push(Literal::Float(percent / 100.0));
+            }
+            (Expr::String(s), false) if matches!(op, BinaryOperator::Eq | BinaryOperator::Neq) => {
+                usage.ty.get_or_insert(FieldType::String);
+                usage.strings.push(s.clone());
+            }
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// Short name of an expression kind for diagnostics
+fn kind(expr: &Expr) -> &'static str {
+    match expr {
+        Expr::Null => "null",
+        Expr::Placeholder(_) => "placeholder",
+        Expr::Lambda { .. } => "lambda",
+        Expr::Let { .. } => "let",
+        Expr::Array(_) => "array",
+        Expr::Range { .. } => "range",
+        Expr::Object(_) => "object",
+        Expr::Pipe { .. } => "pipe",
+        Expr::Alternative { .. } => "?|",
+        Expr::NullDefault { .. } => "??",
+        Expr::Guard { .. } => "guard",
+        Expr::Require { .. } => "require",
+        Expr::WarnIf { .. } => "warn_if",
+        Expr::Quantity { .. } => "quantity",
+        Expr::Date(_) | Expr::DateTime(_) | Expr::Duration(_) | Expr::TemporalKeyword(_) => {
+            "temporal value"
+        }
+        _ => "expression",
+    }
+}
+
+fn literal_type(expr: &Expr) -> Option<FieldType> {
+    match expr {
+        Expr::Literal(Literal::Integer(_)) => Some(FieldType::Integer),
+        Expr::Literal(Literal::Float(_)) | Expr::Percentage(_) => Some(FieldType::Float),
+        Expr::Literal(Literal::Boolean(_)) => Some(FieldType::Boolean),
+        Expr::String(_) => Some(FieldType::String),
+        _ => None,
+    }
+}
+
+/// Candidate values of a field, most telling first
+fn candidates(ty: FieldType, usage: &FieldUsage) -> Vec<InputValue> {
+    let mut values = Vec::new();
+    let mut push = |value: InputValue| {
+        if !values.contains(&value) {
+            values.push(value);
+        }
+    };
+    match ty {
+        FieldType::Integer => {
+            for constant in &usage.constants {
+                let n = match constant {
+                    Literal::Integer(n) => *n,
+                    Literal::Float(x) => *x as i64,
+                    Literal::Boolean(_) => continue,
+                };
+                push(InputValue::Integer(n.saturating_sub(1)));
+                push(InputValue::Integer(n));
+                push(InputValue::Integer(n.saturating_add(1)));
+            }
+            push(InputValue::Integer(0));
+        }
+        FieldType::Float => {
+            let mut points: Vec<f64> = usage
+                .constants
+                .iter()
+                .filter_map(|constant| match constant {
+                    Literal::Integer(n) => Some(*n as f64),
+                    Literal::Float(x) if x.is_finite() => Some(*x),
+                    _ => None,
+                })
+                .collect();
+            points.sort_by(f64::total_cmp);
+            points.dedup();
+            // One value in every gap between constants, and the constants
+            for (i, &point) in points.iter().enumerate() {
+                let below = if i == 0 {
+                    point - 1.0
+                } else {
+                    (points[i - 1] + point) / 2.0
+                };
+                push(InputValue::Float(below));
+                push(InputValue::Float(point));
+            }
+            if let Some(&last) = points.last() {
+                push(InputValue::Float(last + 1.0));
+            }
+            push(InputValue::Float(0.0));
+        }
+        FieldType::Boolean => {
+            push(InputValue::Boolean(false));
+            push(InputValue::Boolean(true));
+        }
+        FieldType::String => {
+            for s in &usage.strings {
+                push(InputValue::String(s.clone()));
+            }
+            let mut lengths: Vec<i64> = usage
+                .lengths
+                .iter()
+                .flat_map(|&n| [n.saturating_sub(1), n, n.saturating_add(1)])
+                .filter(|&n| (0..=1024).contains(&n))
+                .collect();
+            // A string equal to no literal
+            lengths.push(0);
+            for n in lengths {
+                push(InputValue::String(fresh_string(n as usize, &usage.strings)));
+            }
+        }
+    }
+    values
+}
+
+/// A string of `length` characters that is not in `taken`
+fn fresh_string(length: usize, taken: &[String]) -> String {
+    ('a'..='z')
+        .map(|c| c.to_string().repeat(length))
+        .find(|s| !taken.contains(s))
+        .unwrap_or_else(|| "\u{0}".repeat(length))
+}
+
+/// Dotted path of a field access chain rooted at an identifier
+fn field_path(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Identifier(name) => Some(name.clone()),
+        Expr::FieldAccess { receiver, field } => {
+            field_path(receiver).map(|path| format!("{}.{}", path, field))
+        }
+        _ => None,
+    }
+}
+
+/// Evaluate `expr` with fields bound in `env`; `None` on type errors and
+/// failed arithmetic
+fn evaluate(expr: &Expr, env: &BTreeMap<String, InputValue>) -> Option<InputValue> {
+    if let Some(path) = field_path(expr) {
+        return env.get(&path).cloned();
+    }
+    match expr {
+        Expr::Literal(Literal::Integer(n)) => Some(InputValue::Integer(*n)),
+        Expr::Literal(Literal::Float(x)) => Some(InputValue::Float(*x)),
+        Expr::Percentage(percent) => Some(InputValue::Float(percent / 100.0)),
+        Expr::Literal(Literal::Boolean(b)) => Some(InputValue::Boolean(*b)),
+        Expr::String(s) => Some(InputValue::String(s.clone())),
+        Expr::UnaryOp { op, operand } => match (op, evaluate(operand, env)?) {
+            (UnaryOperator::Not, InputValue::Boolean(b)) => Some(InputValue::Boolean(!b)),
+            (UnaryOperator::Neg, InputValue::Integer(n)) => {
+                n.checked_neg().map(InputValue::Integer)
+            }
+            (UnaryOperator::Neg, InputValue::Float(x)) => Some(InputValue::Float(-x)),
+            (UnaryOperator::Plus, value @ (InputValue::Integer(_) | InputValue::Float(_))) => {
+                Some(value)
+            }
+            _ => None,
+        },
+        Expr::BinaryOp {
+            op: BinaryOperator::And,
+            left,
+            right,
+        } => match evaluate(left, env)? {
+            InputValue::Boolean(false) => Some(InputValue::Boolean(false)),
+            InputValue::Boolean(true) => as_boolean(evaluate(right, env)?),
+            _ => None,
+        },
+        Expr::BinaryOp {
+            op: BinaryOperator::Or,
+            left,
+            right,
+        } => match evaluate(left, env)? {
+            InputValue::Boolean(true) => Some(InputValue::Boolean(true)),
+            InputValue::Boolean(false) => as_boolean(evaluate(right, env)?),
+            _ => None,
+        },
+        Expr::BinaryOp {
+            op: BinaryOperator::In,
+            left,
+            right,
+        } => {
+            let value = evaluate(left, env)?;
+            let Expr::Array(elements) = right.as_ref() else {
+                return None;
+            };
+            let mut found = false;
+            for element in elements {
+                found |= compare(BinaryOperator::Eq, &value, &evaluate(element, env)?)?;
+            }
+            Some(InputValue::Boolean(found))
+        }
+        Expr::BinaryOp { op, left, right } => {
+            let (left, right) = (evaluate(left, env)?, evaluate(right, env)?);
+            match op {
+                BinaryOperator::Add
+                | BinaryOperator::Sub
+                | BinaryOperator::Mul
+                | BinaryOperator::Div
+                | BinaryOperator::Mod => arithmetic(*op, &left, &right),
+                _ => compare(*op, &left, &right).map(InputValue::Boolean),
+            }
+        }
+        Expr::FunctionCall { name, args } if name == "length" && args.len() == 1 => {
+            match evaluate(&args[0], env)? {
+                InputValue::String(s) => Some(InputValue::Integer(s.chars().count() as i64)),
+                _ => None,
+            }
+        }
+        Expr::If {
+            condition,
+            then_branch,
+            else_branch,
+        } => match evaluate(condition, env)? {
+            InputValue::Boolean(true) => evaluate(then_branch, env),
+            InputValue::Boolean(false) => evaluate(else_branch, env),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn as_boolean(value: InputValue) -> Option<InputValue> {
+    matches!(value, InputValue::Boolean(_)).then_some(value)
+}
+
+fn as_float(value: &InputValue) -> Option<f64> {
+    match value {
+        InputValue::Integer(n) => Some(*n as f64),
+        InputValue::Float(x) => Some(*x),
+        _ => None,
+    }
+}
+
+fn arithmetic(op: BinaryOperator, left: &InputValue, right: &InputValue) -> Option<InputValue> {
+    if let (InputValue::Integer(a), InputValue::Integer(b)) = (left, right) {
+        let result = match op {
+            BinaryOperator::Add => a.checked_add(*b),
+            BinaryOperator::Sub => a.checked_sub(*b),
+            BinaryOperator::Mul => a.checked_mul(*b),
+            BinaryOperator::Div => a.checked_div(*b),
+            BinaryOperator::Mod => a.checked_rem(*b),
+            _ => None,
+        };
+        return result.map(InputValue::Integer);
+    }
+    let (a, b) = (as_float(left)?, as_float(right)?);
+    let result = match op {
+        BinaryOperator::Add => a + b,
+        BinaryOperator::Sub => a - b,
+        BinaryOperator::Mul => a * b,
+        BinaryOperator::Div if b != 0.0 => a / b,
+        BinaryOperator::Mod if b != 0.0 => a % b,
+        _ => return None,
+    };
+    Some(InputValue::Float(result))
+}
+
+fn compare(op: BinaryOperator, left: &InputValue, right: &InputValue) -> Option<bool> {
+    let ordering = match (left, right) {
+        (InputValue::Boolean(a), InputValue::Boolean(b)) => a.cmp(b),
+        (InputValue::String(a), InputValue::String(b)) => a.cmp(b),
+        (InputValue::Integer(a), InputValue::Integer(b)) => a.cmp(b),
+        _ => as_float(left)?.partial_cmp(&as_float(right)?)?,
+    };
+    match op {
+        BinaryOperator::Eq => Some(ordering.is_eq()),
+        BinaryOperator::Neq => Some(ordering.is_ne()),
+        BinaryOperator::Lt => Some(ordering.is_lt()),
+        BinaryOperator::Lte => Some(ordering.is_le()),
+        BinaryOperator::Gt => Some(ordering.is_gt()),
+        BinaryOperator::Gte => Some(ordering.is_ge()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+
+    fn failing(source: &str) -> SolverOutcome {
+        find_counterexample(&Parser::parse(source).unwrap(), &TypeInfo::new("Input"))
+    }
+
+    fn counterexample(source: &str) -> String {
+        match failing(source) {
+            SolverOutcome::Counterexample(input) => input.to_string(),
+            other => panic!("expected a counterexample for {}, got {:?}", source, other),
+        }
+    }
+
+    #[test]
+    fn test_counterexample_at_boundaries() {
+        assert_eq!(counterexample("age >= 18"), "age = 17");
+        assert_eq!(counterexample("age >= 18 && age < 65"), "age = 17");
+        assert_eq!(
+            counterexample("age < 18 || age >= 18 && verified"),
+            "age = 18, verified = false"
+        );
+        assert_eq!(counterexample("price > 9.5"), "price = 8.5");
+        assert_eq!(counterexample("balance >= -5"), "balance = -6");
+        assert_eq!(counterexample("user.name != 'root'"), "user.name = 'root'");
+        assert_eq!(counterexample("length(code) == 3"), "code = 'aa'");
+        assert_eq!(counterexample("status in ['a', 'b']"), "status = ''");
+    }
+
+    #[test]
+    fn test_valid_for_bounded_domains() {
+        assert_eq!(failing("age > 5 || age <= 5"), SolverOutcome::Valid);
+        assert_eq!(failing("verified || !verified"), SolverOutcome::Valid);
+        assert_eq!(
+            failing("length(name) > 2 || length(name) <= 2"),
+            SolverOutcome::Valid
+        );
+
+        let never = Parser::parse("age > 10 && age < 5").unwrap();
+        assert_eq!(
+            find_example(&never, &TypeInfo::new("Input")),
+            SolverOutcome::Valid
+        );
+    }
+
+    #[test]
+    fn test_inconclusive_searches() {
+        // Comparing two fields is outside the complete fragment
+        assert!(matches!(
+            failing("a >= b || a < b"),
+            SolverOutcome::Unknown(_)
+        ));
+        assert_eq!(
+            failing("matches(email, '^a')"),
+            SolverOutcome::Unknown("unsupported function 'matches'".to_string())
+        );
+    }
+
+    #[test]
+    fn test_declared_field_types() {
+        let mut user = TypeInfo::new("User");
+        user.add_field("score", RustType::Float);
+        user.add_field("joined", RustType::Date);
+        let expr = Parser::parse("score > 1").unwrap();
+        assert_eq!(
+            find_counterexample(&expr, &user),
+            SolverOutcome::Counterexample(Counterexample {
+                fields: [("score".to_string(), InputValue::Float(0.0))].into(),
+            })
+        );
+        let expr = Parser::parse("joined == 1").unwrap();
+        assert_eq!(
+            find_counterexample(&expr, &user),
+            SolverOutcome::Unknown(
+                "field 'joined' has unsupported type chrono::NaiveDate".to_string()
+            )
+        );
+    }
+}
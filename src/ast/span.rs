@@ -0,0 +1,175 @@
+//! Source locations of AST nodes
+//!
+//! [`Expr`] itself carries no positions, so that trees built by hand, by the
+//! optimizer or by a [`Fold`](super::Fold) compare equal to parsed ones.
+//! [`Parser::parse_spanned`](crate::parser::Parser::parse_spanned) returns a
+//! [`Spanned`] instead: the expression plus a [`SpanTree`] of the same shape,
+//! whose children follow [`Expr::children`].
+//!
+//! # Example
+//!
+//! ```
+//! use elo_rust::ast::Expr;
+//! use elo_rust::parser::Parser;
+//!
+//! let source = "age >= 18 && name != ''";
+//! let spanned = Parser::parse_spanned(source).unwrap();
+//! let Expr::BinaryOp { right, .. } = &spanned.expr else { panic!() };
+//! let span = spanned.span_of(right).unwrap();
+//! assert_eq!(span.text(source), "name != ''");
+//! assert_eq!((span.line, span.column), (1, 14));
+//! ```
+
+use super::Expr;
+use std::fmt;
+
+/// A range of source text
+///
+/// `start..end` are byte offsets; `line` and `column` (both 1-based, columns
+/// counted in characters) locate `start`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Span {
+    /// Byte offset of the first character
+    pub start: usize,
+    /// Byte offset just past the last character
+    pub end: usize,
+    /// Line number of `start` (1-based)
+    pub line: usize,
+    /// Column number of `start` (1-based)
+    pub column: usize,
+}
+
+impl Span {
+    /// The smallest span covering both `self` and `other`
+    pub fn to(self, other: Span) -> Span {
+        if other.start < self.start {
+            return other.to(self);
+        }
+        Span {
+            end: self.end.max(other.end),
+            ..self
+        }
+    }
+
+    /// The source text this span covers
+    ///
+    /// `source` must be the text the span was taken from.
+    pub fn text(self, source: &str) -> &str {
+        &source[self.start..self.end]
+    }
+
+    /// Length in bytes
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Whether the span covers no text
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Spans of an expression and, recursively, of its children
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpanTree {
+    /// Span of the node itself
+    pub span: Span,
+    /// Spans of the node's children, in [`Expr::children`] order
+    pub children: Vec<SpanTree>,
+}
+
+/// A parsed expression with the source span of every node
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spanned {
+    /// The expression
+    pub expr: Expr,
+    /// Its spans, shaped like `expr`
+    pub spans: SpanTree,
+}
+
+impl Spanned {
+    /// Span of the whole expression
+    pub fn span(&self) -> Span {
+        self.spans.span
+    }
+
+    /// Span of `node`, which must be a reference into [`Spanned::expr`]
+    ///
+    /// Nodes are matched by address, so equal sub-expressions at different
+    /// places get their own spans. Returns `None` for any other reference.
+    pub fn span_of(&self, node: &Expr) -> Option<Span> {
+        self.nodes()
+            .find(|(expr, _)| std::ptr::eq(*expr, node))
+            .map(|(_, span)| span)
+    }
+
+    /// Every node with its span, parents before their children
+    pub fn nodes(&self) -> impl Iterator<Item = (&Expr, Span)> {
+        let mut stack = vec![(&self.expr, &self.spans)];
+        std::iter::from_fn(move || {
+            let (expr, tree) = stack.pop()?;
+            stack.extend(expr.children().into_iter().zip(&tree.children).rev());
+            Some((expr, tree.span))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(start: usize, end: usize, column: usize) -> Span {
+        Span {
+            start,
+            end,
+            line: 1,
+            column,
+        }
+    }
+
+    #[test]
+    fn test_span() {
+        let (a, b) = (span(2, 5, 3), span(7, 9, 8));
+        assert_eq!(a.to(b), span(2, 9, 3));
+        assert_eq!(b.to(a), span(2, 9, 3));
+        assert_eq!(a.text("a + bcd - e"), "+ b");
+        assert_eq!(a.len(), 3);
+        assert!(!a.is_empty());
+        assert_eq!(b.to_string(), "1:8");
+    }
+
+    #[test]
+    fn test_nodes_follow_children() {
+        let expr = Expr::Array(vec![Expr::Null, Expr::Identifier("x".to_string())]);
+        let spanned = Spanned {
+            spans: SpanTree {
+                span: span(0, 10, 1),
+                children: vec![
+                    SpanTree {
+                        span: span(1, 5, 2),
+                        children: Vec::new(),
+                    },
+                    SpanTree {
+                        span: span(7, 8, 8),
+                        children: Vec::new(),
+                    },
+                ],
+            },
+            expr,
+        };
+        let spans: Vec<Span> = spanned.nodes().map(|(_, span)| span).collect();
+        assert_eq!(spans, vec![span(0, 10, 1), span(1, 5, 2), span(7, 8, 8)]);
+
+        let Expr::Array(items) = &spanned.expr else {
+            unreachable!()
+        };
+        assert_eq!(spanned.span_of(&items[1]), Some(span(7, 8, 8)));
+        assert_eq!(spanned.span_of(&Expr::Null), None);
+    }
+}
@@ -2,6 +2,13 @@
 //!
 //! This module defines the Visitor trait, which implements the visitor pattern
 //! for AST traversal. Implementors can transform or analyze AST nodes.
+//!
+//! Passes that only care about a few node kinds can use the lighter traits,
+//! whose default methods traverse everything else depth-first:
+//!
+//! - [`Walker`] observes nodes on entry and exit ([`walk_expr`])
+//! - [`VisitorMut`] rewrites nodes in place ([`walk_expr_mut`])
+//! - [`Fold`] consumes a tree and builds a new one ([`fold_children`])
 
 use super::{BinaryOperator, Expr, Literal, TemporalKeyword, UnaryOperator};
 
@@ -45,6 +52,12 @@ pub trait Visitor<T> {
     /// Visit a field access expression
     fn visit_field_access(&mut self, receiver: &Expr, field: &str) -> T;
 
+    /// Visit an index access expression
+    fn visit_index(&mut self, collection: &Expr, index: &Expr) -> T;
+
+    /// Visit a parameter placeholder
+    fn visit_placeholder(&mut self, name: &str) -> T;
+
     /// Visit a binary operation
     fn visit_binary_op(&mut self, op: BinaryOperator, left: &Expr, right: &Expr) -> T;
 
@@ -78,6 +91,26 @@ pub trait Visitor<T> {
     /// Visit a guard expression
     fn visit_guard(&mut self, condition: &Expr, body: &Expr) -> T;
 
+    /// Visit a null-default expression (value ?? default)
+    fn visit_null_default(&mut self, value: &Expr, default: &Expr) -> T;
+
+    /// Visit a range literal
+    fn visit_range(&mut self, start: &Expr, end: &Expr, inclusive: bool) -> T;
+
+    /// Visit a numeric literal with a unit suffix
+    fn visit_quantity(&mut self, value: f64, unit: &str) -> T;
+
+    /// Visit a percentage literal, by default as the fraction it stands for
+    fn visit_percentage(&mut self, percent: f64) -> T {
+        self.visit_literal(&Literal::Float(percent / 100.0))
+    }
+
+    /// Visit a require expression (error on false condition)
+    fn visit_require(&mut self, condition: &Expr, message: &str) -> T;
+
+    /// Visit a warn_if expression (warning on true condition)
+    fn visit_warn_if(&mut self, condition: &Expr, message: &str) -> T;
+
     /// Visit a date literal
     fn visit_date(&mut self, date: &str) -> T;
 
@@ -105,8 +138,10 @@ pub trait DefaultVisitor<T>: Visitor<T> {
             Expr::Literal(lit) => self.visit_literal(lit),
             Expr::Null => self.visit_null(),
             Expr::Identifier(name) => self.visit_identifier(name),
+            Expr::Placeholder(name) => self.visit_placeholder(name),
             Expr::String(value) => self.visit_string(value),
             Expr::FieldAccess { receiver, field } => self.visit_field_access(receiver, field),
+            Expr::Index { collection, index } => self.visit_index(collection, index),
             Expr::BinaryOp { op, left, right } => self.visit_binary_op(*op, left, right),
             Expr::UnaryOp { op, operand } => self.visit_unary_op(*op, operand),
             Expr::FunctionCall { name, args } => self.visit_function_call(name, args),
@@ -125,6 +160,16 @@ pub trait DefaultVisitor<T>: Visitor<T> {
                 alternative,
             } => self.visit_alternative(primary, alternative),
             Expr::Guard { condition, body } => self.visit_guard(condition, body),
+            Expr::NullDefault { value, default } => self.visit_null_default(value, default),
+            Expr::Range {
+                start,
+                end,
+                inclusive,
+            } => self.visit_range(start, end, *inclusive),
+            Expr::Quantity { value, unit } => self.visit_quantity(*value, unit),
+            Expr::Percentage(percent) => self.visit_percentage(*percent),
+            Expr::Require { condition, message } => self.visit_require(condition, message),
+            Expr::WarnIf { condition, message } => self.visit_warn_if(condition, message),
             Expr::Date(date) => self.visit_date(date),
             Expr::DateTime(datetime) => self.visit_datetime(datetime),
             Expr::Duration(duration) => self.visit_duration(duration),
@@ -133,6 +178,114 @@ pub trait DefaultVisitor<T>: Visitor<T> {
     }
 }
 
+/// Read-only depth-first traversal with enter/leave hooks
+///
+/// # Example
+///
+/// ```
+/// use elo_rust::ast::{walk_expr, Expr, Walker};
+/// use elo_rust::parser::Parser;
+///
+/// struct Calls(Vec<String>);
+///
+/// impl Walker for Calls {
+///     fn enter(&mut self, expr: &Expr) -> bool {
+///         if let Expr::FunctionCall { name, .. } = expr {
+///             self.0.push(name.clone());
+///         }
+///         true
+///     }
+/// }
+///
+/// let mut calls = Calls(Vec::new());
+/// walk_expr(&mut calls, &Parser::parse("length(trim(name)) > 0").unwrap());
+/// assert_eq!(calls.0, vec!["length", "trim"]);
+/// ```
+pub trait Walker {
+    /// Called before a node's children; return `false` to skip them
+    fn enter(&mut self, expr: &Expr) -> bool {
+        let _ = expr;
+        true
+    }
+
+    /// Called after a node's children (also when they were skipped)
+    fn leave(&mut self, expr: &Expr) {
+        let _ = expr;
+    }
+}
+
+/// Walk an expression depth-first, calling the walker's hooks on every node
+pub fn walk_expr<W: Walker + ?Sized>(walker: &mut W, expr: &Expr) {
+    if walker.enter(expr) {
+        for child in expr.children() {
+            walk_expr(walker, child);
+        }
+    }
+    walker.leave(expr);
+}
+
+/// In-place transformation of an expression tree
+///
+/// Override [`visit_expr_mut`](VisitorMut::visit_expr_mut), handle the node
+/// kinds of interest, and call [`walk_expr_mut`] to continue into children.
+///
+/// # Example
+///
+/// ```
+/// use elo_rust::ast::{walk_expr_mut, Expr, VisitorMut};
+/// use elo_rust::parser::Parser;
+///
+/// struct Rename;
+///
+/// impl VisitorMut for Rename {
+///     fn visit_expr_mut(&mut self, expr: &mut Expr) {
+///         if let Expr::Identifier(name) = expr {
+///             if name == "dob" {
+///                 *name = "birth_date".to_string();
+///             }
+///         }
+///         walk_expr_mut(self, expr);
+///     }
+/// }
+///
+/// let mut expr = Parser::parse("age(dob) >= 18").unwrap();
+/// Rename.visit_expr_mut(&mut expr);
+/// assert_eq!(expr, Parser::parse("age(birth_date) >= 18").unwrap());
+/// ```
+pub trait VisitorMut {
+    /// Visit a node; the default visits every child
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        walk_expr_mut(self, expr);
+    }
+}
+
+/// Visit each direct child of an expression with a mutable visitor
+pub fn walk_expr_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expr: &mut Expr) {
+    for child in expr.children_mut() {
+        visitor.visit_expr_mut(child);
+    }
+}
+
+/// Owned, bottom-up transformation of an expression tree
+///
+/// Override [`fold_expr`](Fold::fold_expr), call [`fold_children`] first to
+/// transform the sub-expressions, then rewrite the node itself.
+pub trait Fold {
+    /// Transform a node; the default folds every child and keeps the node
+    fn fold_expr(&mut self, expr: Expr) -> Expr {
+        fold_children(self, expr)
+    }
+}
+
+/// Fold each direct child of an expression, keeping the node itself
+pub fn fold_children<F: Fold + ?Sized>(folder: &mut F, mut expr: Expr) -> Expr {
+    for child in expr.children_mut() {
+        let owned = std::mem::replace(child, Expr::Null);
+        *child = folder.fold_expr(owned);
+    }
+    expr
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -164,7 +317,9 @@ mod tests {
         fn visit_literal(&mut self, _lit: &Literal) {}
         fn visit_null(&mut self) {}
         fn visit_identifier(&mut self, _name: &str) {}
+        fn visit_placeholder(&mut self, _name: &str) {}
         fn visit_field_access(&mut self, _receiver: &Expr, _field: &str) {}
+        fn visit_index(&mut self, _collection: &Expr, _index: &Expr) {}
         fn visit_binary_op(&mut self, _op: BinaryOperator, _left: &Expr, _right: &Expr) {}
         fn visit_unary_op(&mut self, _op: UnaryOperator, _operand: &Expr) {}
         fn visit_function_call(&mut self, _name: &str, _args: &[Expr]) {}
@@ -176,6 +331,11 @@ mod tests {
         fn visit_pipe(&mut self, _value: &Expr, _functions: &[Expr]) {}
         fn visit_alternative(&mut self, _primary: &Expr, _alternative: &Expr) {}
         fn visit_guard(&mut self, _condition: &Expr, _body: &Expr) {}
+        fn visit_null_default(&mut self, _value: &Expr, _default: &Expr) {}
+        fn visit_range(&mut self, _start: &Expr, _end: &Expr, _inclusive: bool) {}
+        fn visit_quantity(&mut self, _value: f64, _unit: &str) {}
+        fn visit_require(&mut self, _condition: &Expr, _message: &str) {}
+        fn visit_warn_if(&mut self, _condition: &Expr, _message: &str) {}
         fn visit_date(&mut self, _date: &str) {}
         fn visit_datetime(&mut self, _datetime: &str) {}
         fn visit_duration(&mut self, _duration: &str) {}
@@ -183,6 +343,89 @@ mod tests {
         fn visit_string(&mut self, _value: &str) {}
     }
 
+    fn parse(source: &str) -> Expr {
+        crate::parser::Parser::parse(source).unwrap()
+    }
+
+    #[derive(Default)]
+    struct Trace {
+        events: Vec<String>,
+    }
+
+    impl Walker for Trace {
+        fn enter(&mut self, expr: &Expr) -> bool {
+            match expr {
+                Expr::Identifier(name) => self.events.push(format!("enter {}", name)),
+                Expr::Lambda { .. } => return false,
+                _ => {}
+            }
+            true
+        }
+
+        fn leave(&mut self, expr: &Expr) {
+            if let Expr::BinaryOp { op, .. } = expr {
+                self.events.push(format!("leave {}", op));
+            }
+        }
+    }
+
+    #[test]
+    fn test_walk_expr_order_and_pruning() {
+        let mut trace = Trace::default();
+        walk_expr(&mut trace, &parse("a + b > any(xs, fn(x ~> x > c))"));
+        assert_eq!(
+            trace.events,
+            vec!["enter a", "enter b", "leave +", "enter xs", "leave >"]
+        );
+    }
+
+    struct Negate;
+
+    impl VisitorMut for Negate {
+        fn visit_expr_mut(&mut self, expr: &mut Expr) {
+            walk_expr_mut(self, expr);
+            if let Expr::Literal(Literal::Boolean(b)) = expr {
+                *b = !*b;
+            }
+        }
+    }
+
+    #[test]
+    fn test_visitor_mut_rewrites_in_place() {
+        let mut expr = parse("if true then [false, x] else {a: true}");
+        Negate.visit_expr_mut(&mut expr);
+        assert_eq!(expr, parse("if false then [true, x] else {a: false}"));
+    }
+
+    struct DropDoubleNot;
+
+    impl Fold for DropDoubleNot {
+        fn fold_expr(&mut self, expr: Expr) -> Expr {
+            match fold_children(self, expr) {
+                Expr::UnaryOp {
+                    op: UnaryOperator::Not,
+                    operand,
+                } => match *operand {
+                    Expr::UnaryOp {
+                        op: UnaryOperator::Not,
+                        operand: inner,
+                    } => *inner,
+                    operand => Expr::UnaryOp {
+                        op: UnaryOperator::Not,
+                        operand: Box::new(operand),
+                    },
+                },
+                expr => expr,
+            }
+        }
+    }
+
+    #[test]
+    fn test_fold_rebuilds_bottom_up() {
+        let folded = DropDoubleNot.fold_expr(parse("!!!!a && f(!!b, !c)"));
+        assert_eq!(folded, parse("a && f(b, !c)"));
+    }
+
     #[test]
     fn test_visitor_basic() {
         let expr = Expr::Literal(Literal::Integer(42));
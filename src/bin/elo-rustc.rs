@@ -0,0 +1,249 @@
+//! Compiler driver for ELO validators
+//!
+//! Compiles the ELO expression in a file, or on stdin, to a Rust source
+//! file holding one validator function:
+//!
+//! ```text
+//! elo-rustc rules/adult.elo                  # writes rules/adult.rs
+//! elo-rustc rules/adult.elo -o src/adult.rs --name is_adult --type User
+//! echo 'age >= 18' | elo-rustc --type User   # prints the code
+//! elo-rustc schemas/user.toml                # every rule of a schema
+//! elo-rustc rules/adult.elo --out-dir "$OUT_DIR"   # writes $OUT_DIR/adult.rs
+//! elo-rustc rules/limit.elo --param max_amount=500  # binds ${max_amount}
+//! ```
+//!
+//! Files ending in `.toml` or `.json` are [schemas](elo_rust::schema): they
+//! name the type and rules themselves, so `--name`, `--type` and
+//! `--base-path` do not apply to them.
+//!
+//! Unlike `elo compile`, failures are reported on stderr with a non-zero
+//! exit status and never produce an output file.
+
+use elo_rust::codegen::audit::PanicPolicy;
+use elo_rust::params::Params;
+use elo_rust::schema::{Schema, SchemaError};
+use elo_rust::security::{
+    read_file_with_limit, read_stdin_with_limit, validate_expression_text, validate_file_path,
+    validate_file_path_in, write_file_safe,
+};
+use elo_rust::RustCodeGenerator;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+/// Command-line options
+#[derive(Debug)]
+struct Options {
+    /// Input file; `None` reads stdin
+    input: Option<String>,
+    /// Output file; `None` derives it from the input, or prints for stdin
+    output: Option<String>,
+    /// Directory the output path is relative to and must stay within;
+    /// `None` for the working directory
+    out_dir: Option<String>,
+    name: String,
+    input_type: String,
+    base_path: String,
+    deny_panics: bool,
+    /// Values of `${name}` placeholders
+    params: Params,
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let options = match parse_args(&args) {
+        Ok(Some(options)) => options,
+        Ok(None) => return ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            eprintln!("Run 'elo-rustc --help' for usage");
+            return ExitCode::from(2);
+        }
+    };
+    match compile(&options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Parse the arguments; `Ok(None)` when only help or the version was asked for
+fn parse_args(args: &[String]) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        input: None,
+        output: None,
+        out_dir: None,
+        name: "validate".to_string(),
+        input_type: "T".to_string(),
+        base_path: String::new(),
+        deny_panics: false,
+        params: Params::new(),
+    };
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| {
+            args.next()
+                .cloned()
+                .ok_or_else(|| format!("{} requires a value", flag))
+        };
+        match arg.as_str() {
+            "--output" | "-o" => options.output = Some(value(arg)?),
+            "--out-dir" => options.out_dir = Some(value(arg)?),
+            "--name" | "-n" => options.name = value(arg)?,
+            "--type" | "-t" => options.input_type = value(arg)?,
+            "--base-path" => options.base_path = value(arg)?,
+            "--deny-panics" => options.deny_panics = true,
+            "--param" | "-p" => options
+                .params
+                .insert_assignment(&value(arg)?)
+                .map_err(|e| format!("{}: {}", arg, e))?,
+            "--help" | "-h" => {
+                print_help();
+                return Ok(None);
+            }
+            "--version" | "-V" => {
+                println!("elo-rustc {}", env!("CARGO_PKG_VERSION"));
+                return Ok(None);
+            }
+            "-" => options.input = None,
+            flag if flag.starts_with('-') => return Err(format!("unknown option '{}'", flag)),
+            file => {
+                if options.input.is_some() {
+                    return Err(format!("unexpected argument '{}'", file));
+                }
+                options.input = Some(file.to_string());
+            }
+        }
+    }
+
+    for (what, ident) in [("--name", &options.name), ("--type", &options.input_type)] {
+        if syn::parse_str::<syn::Ident>(ident).is_err() {
+            return Err(format!(
+                "{} must be a Rust identifier, got '{}'",
+                what, ident
+            ));
+        }
+    }
+    Ok(Some(options))
+}
+
+/// Whether `file` is a schema rather than a single expression
+fn is_schema(file: &str) -> bool {
+    file.ends_with(".toml") || file.ends_with(".json")
+}
+
+/// Read, compile and write one validator, or the validators of a schema
+fn compile(options: &Options) -> Result<(), String> {
+    let policy = if options.deny_panics {
+        PanicPolicy::Deny
+    } else {
+        PanicPolicy::Warn
+    };
+    let generator = RustCodeGenerator::new()
+        .with_panic_policy(policy)
+        .with_params(options.params.clone());
+    let tokens = match &options.input {
+        Some(file) if is_schema(file) => Schema::load(file)
+            .and_then(|schema| schema.generate(&generator))
+            .map_err(|e| {
+                SchemaError {
+                    file: Some(file.clone()),
+                    ..e
+                }
+                .to_string()
+            })?,
+        _ => compile_expression(options, &generator)?,
+    };
+    let code = format!(
+        "// Generated by elo-rustc {}. Do not edit.\n\n{}\n",
+        env!("CARGO_PKG_VERSION"),
+        tokens
+    );
+
+    let output = match (&options.output, &options.input) {
+        (Some(output), _) => output.clone(),
+        (None, Some(input)) => {
+            let output = PathBuf::from(input).with_extension("rs");
+            // Under an output directory, the input's directories are dropped
+            let output = match (&options.out_dir, output.file_name()) {
+                (Some(_), Some(name)) => PathBuf::from(name),
+                _ => output,
+            };
+            output.to_string_lossy().into_owned()
+        }
+        (None, None) => {
+            print!("{}", code);
+            return Ok(());
+        }
+    };
+    if options.input.as_deref() == Some(output.as_str()) {
+        return Err(format!("output '{}' would overwrite the input", output));
+    }
+    // The output path is checked like the input path, and written without
+    // following a symlink planted there since
+    let path = match &options.out_dir {
+        Some(dir) => validate_file_path_in(Path::new(dir), &output),
+        None => validate_file_path(&output),
+    }
+    .map_err(|e| format!("invalid output path '{}': {}", output, e))?;
+    write_file_safe(&path, &code).map_err(|e| format!("cannot write '{}': {}", output, e))?;
+    eprintln!("Wrote {}", path.display());
+    Ok(())
+}
+
+/// Read the expression from the input file or stdin and compile it
+fn compile_expression(
+    options: &Options,
+    generator: &RustCodeGenerator,
+) -> Result<proc_macro2::TokenStream, String> {
+    let source = match &options.input {
+        Some(file) => {
+            // Validate file path to prevent directory traversal
+            let path = validate_file_path(file)
+                .map_err(|e| format!("invalid input path '{}': {}", file, e))?;
+            read_file_with_limit(&path).map_err(|e| format!("cannot read '{}': {}", file, e))?
+        }
+        None => read_stdin_with_limit().map_err(|e| format!("cannot read stdin: {}", e))?,
+    };
+    let expression = source.trim();
+    validate_expression_text(expression).map_err(|e| format!("invalid ELO expression: {}", e))?;
+    generator.generate_validator_with_errors(
+        &options.name,
+        expression,
+        &options.input_type,
+        &options.base_path,
+    )
+}
+
+fn print_help() {
+    println!("elo-rustc - Compile an ELO expression to a Rust validator");
+    println!();
+    println!("Usage: elo-rustc [options] [<file>]");
+    println!();
+    println!("Reads the expression from <file>, or from stdin if <file> is omitted or '-'.");
+    println!("A <file> ending in .toml or .json is a schema naming a type and its rules.");
+    println!();
+    println!("Options:");
+    println!("  -o, --output <file>   Write the code to <file> (default: <file> with an .rs");
+    println!("                        extension; stdout when reading stdin)");
+    println!("  --out-dir <dir>       Resolve the output path in <dir> and keep it there,");
+    println!("                        e.g. $OUT_DIR in a build script");
+    println!("  -n, --name <name>     Name of the validator function (default: validate)");
+    println!("  -t, --type <type>     Type of the validated input (default: T)");
+    println!("  --base-path <path>    Nest the paths of reported errors under <path>");
+    println!("  --deny-panics         Fail if the generated code could panic");
+    println!("  -p, --param <name=value>");
+    println!("                        Bind the placeholder ${{name}} (value read as JSON,");
+    println!("                        or else as a string)");
+    println!("  -h, --help            Show this help message");
+    println!("  -V, --version         Show version");
+    println!();
+    println!("Examples:");
+    println!("  elo-rustc rules/adult.elo");
+    println!("  elo-rustc rules/adult.elo -o src/adult.rs --name is_adult --type User");
+    println!("  echo 'age >= 18' | elo-rustc --type User");
+    println!("  elo-rustc schemas/user.toml -o src/user_rules.rs");
+    println!("  elo-rustc rules/adult.elo --out-dir \"$OUT_DIR\"");
+}
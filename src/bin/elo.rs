@@ -3,11 +3,23 @@
 //! Provides command-line interface for compiling ELO expressions to Rust
 
 use elo_rust::security::{
-    read_file_with_limit, read_stdin_with_limit, validate_expression, validate_file_path,
+    read_file_with_limit, read_stdin_with_limit, validate_expression, validate_expression_text,
+    validate_file_path, write_file_safe,
 };
 use std::io;
+use std::process::ExitCode;
 
-fn main() -> io::Result<()> {
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run() -> io::Result<()> {
     let args: Vec<String> = std::env::args().collect();
 
     if args.len() < 2 {
@@ -18,6 +30,11 @@ fn main() -> io::Result<()> {
     match args[1].as_str() {
         "compile" => compile_command(&args[2..]),
         "validate" => validate_command(&args[2..]),
+        "lint" => lint_command(&args[2..]),
+        "stats" => stats_command(&args[2..]),
+        "table" => table_command(&args[2..]),
+        "check" => check_command(&args[2..]),
+        "migrate" => migrate_command(&args[2..]),
         "--help" | "-h" | "help" => {
             print_help();
             Ok(())
@@ -38,6 +55,9 @@ fn compile_command(args: &[String]) -> io::Result<()> {
     let mut input_file: Option<String> = None;
     let mut output_file: Option<String> = None;
     let mut expression: Option<String> = None;
+    let mut config_file: Option<String> = None;
+    let mut deny_panics = false;
+    let mut params = elo_rust::params::Params::new();
 
     let mut i = 0;
     while i < args.len() {
@@ -69,6 +89,27 @@ fn compile_command(args: &[String]) -> io::Result<()> {
                     return Ok(());
                 }
             }
+            "--config" | "-c" => {
+                i += 1;
+                if i < args.len() {
+                    config_file = Some(args[i].clone());
+                } else {
+                    eprintln!("Error: --config requires a value");
+                    return Ok(());
+                }
+            }
+            "--deny-panics" => deny_panics = true,
+            "--param" | "-p" => {
+                i += 1;
+                let Some(assignment) = args.get(i) else {
+                    eprintln!("Error: --param requires a value");
+                    return Ok(());
+                };
+                if let Err(e) = params.insert_assignment(assignment) {
+                    eprintln!("Error: --param: {}", e);
+                    return Ok(());
+                }
+            }
             "--help" | "-h" => {
                 print_compile_help();
                 return Ok(());
@@ -78,6 +119,13 @@ fn compile_command(args: &[String]) -> io::Result<()> {
         i += 1;
     }
 
+    let config = load_config(config_file.as_deref())?.unwrap_or_default();
+    let panic_policy = if deny_panics || config.codegen.deny_panics {
+        elo_rust::codegen::audit::PanicPolicy::Deny
+    } else {
+        elo_rust::codegen::audit::PanicPolicy::Warn
+    };
+
     // Get ELO expression from either file or command line
     let elo_expr = if let Some(expr) = expression {
         expr
@@ -100,13 +148,13 @@ fn compile_command(args: &[String]) -> io::Result<()> {
     };
 
     // Validate the expression
-    if let Err(e) = validate_expression(&elo_expr) {
+    if let Err(e) = validate_expression_text(&elo_expr) {
         eprintln!("Error: Invalid ELO expression: {}", e);
         return Ok(());
     }
 
     // Generate code
-    let generated_code = generate_validator_code(&elo_expr);
+    let generated_code = generate_validator_code(&elo_expr, panic_policy, params);
 
     // Output result
     if let Some(out_file) = output_file {
@@ -130,7 +178,15 @@ fn compile_command(args: &[String]) -> io::Result<()> {
 }
 
 fn validate_command(args: &[String]) -> io::Result<()> {
+    use elo_rust::runtime::stream::RecordFormat;
+
     let mut input_file: Option<String> = None;
+    let mut rules_file: Option<String> = None;
+    let mut data_file: Option<String> = None;
+    #[cfg(feature = "serde-support")]
+    let mut format = RecordFormat::Ndjson;
+    #[cfg(not(feature = "serde-support"))]
+    let mut format = RecordFormat::Csv;
 
     let mut i = 0;
     while i < args.len() {
@@ -144,6 +200,34 @@ fn validate_command(args: &[String]) -> io::Result<()> {
                     return Ok(());
                 }
             }
+            "--rules" | "-r" => {
+                i += 1;
+                if i < args.len() {
+                    rules_file = Some(args[i].clone());
+                } else {
+                    eprintln!("Error: --rules requires a value");
+                    return Ok(());
+                }
+            }
+            "--data" | "-d" => {
+                i += 1;
+                if i < args.len() {
+                    data_file = Some(args[i].clone());
+                } else {
+                    eprintln!("Error: --data requires a value");
+                    return Ok(());
+                }
+            }
+            "--format" | "-f" => {
+                i += 1;
+                match args.get(i).and_then(|name| RecordFormat::from_name(name)) {
+                    Some(parsed) => format = parsed,
+                    None => {
+                        eprintln!("Error: --format must be 'ndjson' or 'csv'");
+                        return Ok(());
+                    }
+                }
+            }
             "--help" | "-h" => {
                 print_validate_help();
                 return Ok(());
@@ -153,6 +237,10 @@ fn validate_command(args: &[String]) -> io::Result<()> {
         i += 1;
     }
 
+    if let Some(rules_file) = rules_file {
+        return validate_records(&rules_file, data_file.as_deref(), format);
+    }
+
     let elo_expr = if let Some(file) = input_file {
         // Validate file path to prevent directory traversal
         let safe_path = validate_file_path(&file).map_err(|e| {
@@ -189,43 +277,728 @@ fn validate_command(args: &[String]) -> io::Result<()> {
     }
 }
 
-/// Writes file safely to prevent TOCTOU (Time of Check, Time of Use) attacks
-///
-/// Uses O_NOFOLLOW on Unix to prevent symlink races
-fn write_file_safe(path: &std::path::Path, content: &str) -> io::Result<()> {
-    #[cfg(unix)]
-    {
-        use std::io::Write;
-        use std::os::unix::fs::OpenOptionsExt;
-
-        // Open with O_NOFOLLOW to prevent symlink races
-        let mut file = std::fs::OpenOptions::new()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .custom_flags(libc::O_NOFOLLOW)
-            .open(path)?;
-
-        file.write_all(content.as_bytes())?;
+/// Stream NDJSON or CSV records through a rule set, one line at a time
+fn validate_records(
+    rules_file: &str,
+    data_file: Option<&str>,
+    format: elo_rust::runtime::stream::RecordFormat,
+) -> io::Result<()> {
+    use elo_rust::rules::RuleSet;
+    use elo_rust::runtime::stream::RecordReader;
+    use std::io::BufRead;
+
+    let safe_path = validate_file_path(rules_file).map_err(|e| {
+        eprintln!("Invalid rules file path: {}", e);
+        e
+    })?;
+    let source = read_file_with_limit(&safe_path).map_err(|e| {
+        eprintln!("Failed to read rules file '{}': {}", rules_file, e);
+        e
+    })?;
+    let rules = RuleSet::parse(&source).map_err(|e| {
+        eprintln!("✗ Invalid rule set: {}", e);
+        io::Error::new(io::ErrorKind::InvalidInput, "Invalid rule set")
+    })?;
+
+    let reader: Box<dyn BufRead> = match data_file {
+        Some(file) => {
+            let safe_path = validate_file_path(file).map_err(|e| {
+                eprintln!("Invalid data file path: {}", e);
+                e
+            })?;
+            let data = std::fs::File::open(&safe_path).map_err(|e| {
+                eprintln!("Failed to read data file '{}': {}", file, e);
+                e
+            })?;
+            Box::new(io::BufReader::new(data))
+        }
+        None => Box::new(io::stdin().lock()),
+    };
+
+    let (mut passed, mut failed, mut invalid) = (0usize, 0usize, 0usize);
+    for record in RecordReader::new(reader, format) {
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                invalid += 1;
+                println!("✗ {}", e);
+                continue;
+            }
+        };
+        let report = rules.check(&record.value).map_err(|e| {
+            eprintln!("✗ Invalid rule set: {}", e);
+            io::Error::new(io::ErrorKind::InvalidInput, "Invalid rule set")
+        })?;
+        for warning in report.warned().flat_map(|outcome| &outcome.errors) {
+            println!("⚠ line {}: {}", record.line, warning.message);
+        }
+        if report.is_ok() {
+            passed += 1;
+            println!("✓ line {}", record.line);
+        } else {
+            failed += 1;
+            let messages: Vec<&str> = report
+                .failed()
+                .flat_map(|outcome| &outcome.errors)
+                .map(|error| error.message.as_str())
+                .collect();
+            println!("✗ line {}: {}", record.line, messages.join("; "));
+        }
+    }
+
+    println!(
+        "{} records: {} passed, {} failed, {} invalid",
+        passed + failed + invalid,
+        passed,
+        failed,
+        invalid
+    );
+    if failed + invalid > 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Some records failed validation",
+        ));
+    }
+    Ok(())
+}
+
+fn table_command(args: &[String]) -> io::Result<()> {
+    use elo_rust::parser::Parser;
+    use elo_rust::rules::table::{DecisionTable, DecisionTableError, HitPolicy};
+
+    let mut input_file: Option<String> = None;
+    let mut default: Option<String> = None;
+    let mut hit_policy = HitPolicy::Unique;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--input" | "-i" => {
+                i += 1;
+                if i < args.len() {
+                    input_file = Some(args[i].clone());
+                } else {
+                    eprintln!("Error: --input requires a value");
+                    return Ok(());
+                }
+            }
+            "--default" => {
+                i += 1;
+                if i < args.len() {
+                    default = Some(args[i].clone());
+                } else {
+                    eprintln!("Error: --default requires a value");
+                    return Ok(());
+                }
+            }
+            "--first" => hit_policy = HitPolicy::First,
+            "--help" | "-h" => {
+                print_table_help();
+                return Ok(());
+            }
+            _ => eprintln!("Unknown argument: {}", args[i]),
+        }
+        i += 1;
+    }
+
+    let source = if let Some(file) = input_file {
+        // Validate file path to prevent directory traversal
+        let safe_path = validate_file_path(&file).map_err(|e| {
+            eprintln!("Invalid input file path: {}", e);
+            e
+        })?;
+
+        read_file_with_limit(&safe_path).map_err(|e| {
+            eprintln!("Failed to read input file '{}': {}", file, e);
+            e
+        })?
+    } else {
+        read_stdin_with_limit().map_err(|e| {
+            eprintln!("Failed to read from stdin: {}", e);
+            e
+        })?
+    };
+
+    // Markdown tables start with a pipe; anything else is CSV
+    let is_markdown = source
+        .lines()
+        .find(|line| !line.trim().is_empty())
+        .is_some_and(|line| line.trim_start().starts_with('|'));
+    let table = if is_markdown {
+        DecisionTable::from_markdown(&source)
+    } else {
+        DecisionTable::from_csv(&source)
+    };
+    let table = table.map(|table| {
+        let table = table.with_hit_policy(hit_policy);
+        match default {
+            Some(default) => table.with_default(default),
+            None => table,
+        }
+    });
+
+    let elo = table.and_then(|table| {
+        let elo = table.to_elo()?;
+        Parser::parse(&elo).map_err(DecisionTableError::Expression)?;
+        Ok(elo)
+    });
+    match elo {
+        Ok(elo) => {
+            println!("{}", elo);
+            Ok(())
+        }
+        Err(e) => {
+            eprintln!("✗ {}", e);
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Invalid decision table",
+            ))
+        }
+    }
+}
+
+fn lint_command(args: &[String]) -> io::Result<()> {
+    use elo_rust::lint::{LintConfig, LintKind, LintLevel};
+
+    let mut input_file: Option<String> = None;
+    let mut expression: Option<String> = None;
+    let mut config_file: Option<String> = None;
+    let mut levels: Vec<(LintKind, LintLevel)> = Vec::new();
+    let mut max_ternary_depth: Option<usize> = None;
+    let mut format = "text".to_string();
+    let mut fix = false;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--input" | "-i" => {
+                i += 1;
+                if i < args.len() {
+                    input_file = Some(args[i].clone());
+                } else {
+                    eprintln!("Error: --input requires a value");
+                    return Ok(());
+                }
+            }
+            "--expression" | "-e" => {
+                i += 1;
+                if i < args.len() {
+                    expression = Some(args[i].clone());
+                } else {
+                    eprintln!("Error: --expression requires a value");
+                    return Ok(());
+                }
+            }
+            flag @ ("--allow" | "--warn" | "--deny") => {
+                i += 1;
+                let level = LintLevel::from_name(&flag[2..]).unwrap_or(LintLevel::Warn);
+                match args.get(i).and_then(|name| LintKind::from_name(name)) {
+                    Some(kind) => levels.push((kind, level)),
+                    None => {
+                        eprintln!("Error: {} requires a lint name", flag);
+                        return Ok(());
+                    }
+                }
+            }
+            "--max-ternary-depth" => {
+                i += 1;
+                match args.get(i).and_then(|n| n.parse().ok()) {
+                    Some(depth) => max_ternary_depth = Some(depth),
+                    None => {
+                        eprintln!("Error: --max-ternary-depth requires a number");
+                        return Ok(());
+                    }
+                }
+            }
+            "--config" | "-c" => {
+                i += 1;
+                if i < args.len() {
+                    config_file = Some(args[i].clone());
+                } else {
+                    eprintln!("Error: --config requires a value");
+                    return Ok(());
+                }
+            }
+            "--format" | "-f" => {
+                i += 1;
+                match args.get(i).map(String::as_str) {
+                    Some(name @ ("text" | "json" | "sarif")) => format = name.to_string(),
+                    _ => {
+                        eprintln!("Error: --format must be 'text', 'json' or 'sarif'");
+                        return Ok(());
+                    }
+                }
+            }
+            "--fix" => fix = true,
+            "--help" | "-h" => {
+                print_lint_help();
+                return Ok(());
+            }
+            _ => eprintln!("Unknown argument: {}", args[i]),
+        }
+        i += 1;
+    }
+
+    // Command-line flags override the configuration file
+    let mut config = load_config(config_file.as_deref())?
+        .map(|config| config.lint)
+        .unwrap_or_else(LintConfig::new);
+    for (kind, level) in levels {
+        config = config.with_level(kind, level);
+    }
+    if let Some(depth) = max_ternary_depth {
+        config = config.with_max_ternary_depth(depth);
+    }
+
+    let source_name = match (&expression, &input_file) {
+        (Some(_), _) => "<expression>".to_string(),
+        (None, Some(file)) => file.clone(),
+        (None, None) => "<stdin>".to_string(),
+    };
+
+    let mut fix_target = None;
+    let mut elo_expr = if let Some(expr) = expression {
+        expr
+    } else if let Some(file) = input_file {
+        // Validate file path to prevent directory traversal
+        let safe_path = validate_file_path(&file).map_err(|e| {
+            eprintln!("Invalid input file path: {}", e);
+            e
+        })?;
+
+        let content = read_file_with_limit(&safe_path).map_err(|e| {
+            eprintln!("Failed to read input file '{}': {}", file, e);
+            e
+        })?;
+        fix_target = Some(safe_path);
+        content
+    } else {
+        read_stdin_with_limit().map_err(|e| {
+            eprintln!("Failed to read from stdin: {}", e);
+            e
+        })?
+    };
+
+    if let Err(e) = validate_expression_text(&elo_expr) {
+        eprintln!("Error: Invalid ELO expression: {}", e);
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Invalid ELO expression",
+        ));
+    }
+
+    if fix {
+        let (fixed, applied) = elo_rust::fix::fix_source(&config, &elo_expr);
+        match &fix_target {
+            // Rewrite the file in place; an expression is echoed instead
+            Some(path) if applied > 0 => {
+                write_file_safe(path, &fixed).map_err(|e| {
+                    eprintln!("Failed to write '{}': {}", source_name, e);
+                    e
+                })?;
+                eprintln!("✓ Applied {} fix(es) to {}", applied, source_name);
+            }
+            Some(_) => {}
+            None => {
+                println!("{}", fixed.trim());
+                eprintln!("✓ Applied {} fix(es)", applied);
+            }
+        }
+        elo_expr = fixed;
+    }
+
+    if format != "text" {
+        return lint_report(&config, elo_expr.trim(), &source_name, &format);
+    }
+
+    let lints = config.check_source(elo_expr.trim()).map_err(|e| {
+        eprintln!("✗ ELO expression is invalid: {}", e);
+        io::Error::new(io::ErrorKind::InvalidInput, "Invalid ELO expression")
+    })?;
+
+    for lint in &lints {
+        println!("{}", lint);
+    }
+    if lints.iter().any(|l| l.is_denied()) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Lint errors found",
+        ));
+    }
+    if lints.is_empty() {
+        println!("✓ No lints found");
+    }
+    Ok(())
+}
+
+/// Print lints and parse errors as a JSON or SARIF diagnostics document
+fn lint_report(
+    config: &elo_rust::lint::LintConfig,
+    source: &str,
+    source_name: &str,
+    format: &str,
+) -> io::Result<()> {
+    use elo_rust::diagnostics::{self, Diagnostic, DiagnosticLevel};
+
+    let diags: Vec<Diagnostic> = match config.check_source(source) {
+        Ok(lints) => lints.iter().map(Diagnostic::from_lint).collect(),
+        Err(e) => vec![Diagnostic::from_parse_error(&e, source)],
+    };
+    if format == "sarif" {
+        println!("{}", diagnostics::to_sarif(&diags, source_name));
+    } else {
+        println!("{}", diagnostics::to_json(&diags, source_name));
+    }
+    if diags.iter().any(|d| d.level == DiagnosticLevel::Error) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Lint errors found",
+        ));
+    }
+    Ok(())
+}
+
+fn stats_command(args: &[String]) -> io::Result<()> {
+    use elo_rust::parser::Parser;
+    use elo_rust::security::{analyze_expression_complexity, ComplexityLimits};
+
+    let mut input_file: Option<String> = None;
+    let mut expression: Option<String> = None;
+    let mut rules_file: Option<String> = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--input" | "-i" => {
+                i += 1;
+                if i < args.len() {
+                    input_file = Some(args[i].clone());
+                } else {
+                    eprintln!("Error: --input requires a value");
+                    return Ok(());
+                }
+            }
+            "--expression" | "-e" => {
+                i += 1;
+                if i < args.len() {
+                    expression = Some(args[i].clone());
+                } else {
+                    eprintln!("Error: --expression requires a value");
+                    return Ok(());
+                }
+            }
+            "--rules" | "-r" => {
+                i += 1;
+                if i < args.len() {
+                    rules_file = Some(args[i].clone());
+                } else {
+                    eprintln!("Error: --rules requires a value");
+                    return Ok(());
+                }
+            }
+            "--help" | "-h" => {
+                print_stats_help();
+                return Ok(());
+            }
+            _ => eprintln!("Unknown argument: {}", args[i]),
+        }
+        i += 1;
+    }
+
+    if let Some(rules_file) = rules_file {
+        return rule_code_sizes(&rules_file);
+    }
+
+    let elo_expr = if let Some(expr) = expression {
+        expr
+    } else if let Some(file) = input_file {
+        // Validate file path to prevent directory traversal
+        let safe_path = validate_file_path(&file).map_err(|e| {
+            eprintln!("Invalid input file path: {}", e);
+            e
+        })?;
+
+        read_file_with_limit(&safe_path).map_err(|e| {
+            eprintln!("Failed to read input file '{}': {}", file, e);
+            e
+        })?
+    } else {
+        read_stdin_with_limit().map_err(|e| {
+            eprintln!("Failed to read from stdin: {}", e);
+            e
+        })?
+    };
+
+    let expr = Parser::parse(elo_expr.trim()).map_err(|e| {
+        eprintln!("✗ ELO expression is invalid: {}", e);
+        io::Error::new(io::ErrorKind::InvalidInput, "Invalid ELO expression")
+    })?;
+
+    let report = analyze_expression_complexity(&expr);
+    println!("{}", report);
+    if let Err(e) = ComplexityLimits::default().check(&report) {
+        println!("⚠ {}", e);
+    }
+    Ok(())
+}
+
+/// Print the generated-code size of each rule of a rule file as a table
+fn rule_code_sizes(rules_file: &str) -> io::Result<()> {
+    use elo_rust::codegen::SizeReport;
+    use elo_rust::rules::RuleSet;
+
+    let safe_path = validate_file_path(rules_file).map_err(|e| {
+        eprintln!("Invalid rules file path: {}", e);
+        e
+    })?;
+    let source = read_file_with_limit(&safe_path).map_err(|e| {
+        eprintln!("Failed to read rules file '{}': {}", rules_file, e);
+        e
+    })?;
+    let rules = RuleSet::parse(&source).map_err(|e| {
+        eprintln!("✗ Invalid rule set: {}", e);
+        io::Error::new(io::ErrorKind::InvalidInput, "Invalid rule set")
+    })?;
+
+    let sizes = rules.code_sizes();
+    let width = sizes
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(0)
+        .max("total".len());
+    println!(
+        "{:<width$}  {:>7}  {:>9}  {:>6}  {:>7}  {:>5}  {:>7}",
+        "rule", "tokens", "functions", "macros", "methods", "depth", "cost"
+    );
+    let row = |name: &str, size: &SizeReport| {
+        println!(
+            "{:<width$}  {:>7}  {:>9}  {:>6}  {:>7}  {:>5}  {:>7}",
+            name,
+            size.tokens,
+            size.functions,
+            size.macro_calls,
+            size.method_calls,
+            size.max_depth,
+            size.compile_cost()
+        );
+    };
+    for (name, size) in &sizes {
+        row(name, size);
+    }
+    let total: SizeReport = sizes.iter().map(|(_, size)| *size).sum();
+    row("total", &total);
+
+    for (name, size) in sizes.iter().filter(|(_, size)| size.is_heavy()) {
+        println!(
+            "⚠ Rule '{}' is expensive to compile (cost {}); consider splitting it",
+            name,
+            size.compile_cost()
+        );
+    }
+    Ok(())
+}
+
+fn check_command(args: &[String]) -> io::Result<()> {
+    use elo_rust::ast::analysis::{find_counterexample, find_example, SolverOutcome};
+    use elo_rust::codegen::types::TypeInfo;
+    use elo_rust::parser::Parser;
+
+    let mut input_file: Option<String> = None;
+    let mut expression: Option<String> = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--input" | "-i" => {
+                i += 1;
+                if i < args.len() {
+                    input_file = Some(args[i].clone());
+                } else {
+                    eprintln!("Error: --input requires a value");
+                    return Ok(());
+                }
+            }
+            "--expression" | "-e" => {
+                i += 1;
+                if i < args.len() {
+                    expression = Some(args[i].clone());
+                } else {
+                    eprintln!("Error: --expression requires a value");
+                    return Ok(());
+                }
+            }
+            "--help" | "-h" => {
+                print_check_help();
+                return Ok(());
+            }
+            _ => eprintln!("Unknown argument: {}", args[i]),
+        }
+        i += 1;
+    }
+
+    let elo_expr = if let Some(expr) = expression {
+        expr
+    } else if let Some(file) = input_file {
+        // Validate file path to prevent directory traversal
+        let safe_path = validate_file_path(&file).map_err(|e| {
+            eprintln!("Invalid input file path: {}", e);
+            e
+        })?;
+
+        read_file_with_limit(&safe_path).map_err(|e| {
+            eprintln!("Failed to read input file '{}': {}", file, e);
+            e
+        })?
+    } else {
+        read_stdin_with_limit().map_err(|e| {
+            eprintln!("Failed to read from stdin: {}", e);
+            e
+        })?
+    };
+
+    let expr = Parser::parse(elo_expr.trim()).map_err(|e| {
+        eprintln!("✗ ELO expression is invalid: {}", e);
+        io::Error::new(io::ErrorKind::InvalidInput, "Invalid ELO expression")
+    })?;
+
+    let input = TypeInfo::new("Input");
+    let mut satisfiable = true;
+    match find_example(&expr, &input) {
+        SolverOutcome::Counterexample(example) => println!("✓ passes for: {}", example),
+        SolverOutcome::Valid => {
+            println!("✗ no input passes this expression");
+            satisfiable = false;
+        }
+        SolverOutcome::Unknown(reason) => println!("? no passing input found: {}", reason),
+    }
+    match find_counterexample(&expr, &input) {
+        SolverOutcome::Counterexample(example) => println!("✓ fails for: {}", example),
+        SolverOutcome::Valid => println!("⚠ no input fails this expression; it always holds"),
+        SolverOutcome::Unknown(reason) => println!("? no failing input found: {}", reason),
+    }
+
+    if satisfiable {
         Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Unsatisfiable ELO expression",
+        ))
     }
+}
+
+fn migrate_command(args: &[String]) -> io::Result<()> {
+    use elo_rust::migrate::{migrate, MigrationMode};
+
+    let mut input_file: Option<String> = None;
+    let mut expression: Option<String> = None;
+    let mut mode = MigrationMode::PreserveResults;
+    let mut in_place = false;
 
-    #[cfg(not(unix))]
-    {
-        // On non-Unix systems, use standard write
-        std::fs::write(path, content)
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--input" | "-i" => {
+                i += 1;
+                if i < args.len() {
+                    input_file = Some(args[i].clone());
+                } else {
+                    eprintln!("Error: --input requires a value");
+                    return Ok(());
+                }
+            }
+            "--expression" | "-e" => {
+                i += 1;
+                if i < args.len() {
+                    expression = Some(args[i].clone());
+                } else {
+                    eprintln!("Error: --expression requires a value");
+                    return Ok(());
+                }
+            }
+            "--adopt-fixes" => mode = MigrationMode::AdoptFixes,
+            "--in-place" => in_place = true,
+            "--help" | "-h" => {
+                print_migrate_help();
+                return Ok(());
+            }
+            _ => eprintln!("Unknown argument: {}", args[i]),
+        }
+        i += 1;
+    }
+
+    let mut target = None;
+    let elo_expr = if let Some(expr) = expression {
+        expr
+    } else if let Some(file) = input_file {
+        // Validate file path to prevent directory traversal
+        let safe_path = validate_file_path(&file).map_err(|e| {
+            eprintln!("Invalid input file path: {}", e);
+            e
+        })?;
+
+        let content = read_file_with_limit(&safe_path).map_err(|e| {
+            eprintln!("Failed to read input file '{}': {}", file, e);
+            e
+        })?;
+        target = Some((safe_path, file));
+        content
+    } else {
+        read_stdin_with_limit().map_err(|e| {
+            eprintln!("Failed to read from stdin: {}", e);
+            e
+        })?
+    };
+
+    let migration = migrate(&elo_expr, mode).map_err(|e| {
+        eprintln!("✗ ELO expression is invalid: {}", e);
+        io::Error::new(io::ErrorKind::InvalidInput, "Invalid ELO expression")
+    })?;
+
+    for note in &migration.notes {
+        eprintln!("⚠ {}", note);
     }
+    match (&target, in_place) {
+        (Some((path, file)), true) => {
+            if migration.source != elo_expr {
+                write_file_safe(path, &migration.source).map_err(|e| {
+                    eprintln!("Failed to write '{}': {}", file, e);
+                    e
+                })?;
+            }
+            eprintln!("✓ Migrated {}", file);
+        }
+        (None, true) => {
+            eprintln!("Error: --in-place requires --input");
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "--in-place requires --input",
+            ));
+        }
+        (_, false) => println!("{}", migration.source.trim()),
+    }
+    if migration.notes.is_empty() {
+        eprintln!("✓ No 0.x operator semantics found");
+    }
+    Ok(())
 }
 
 /// Generates a safe validator code template
 ///
 /// Does NOT embed user input in the generated code
 /// Expressions should be validated and stored separately
-fn generate_validator_code(elo_expr: &str) -> String {
+fn generate_validator_code(
+    elo_expr: &str,
+    panic_policy: elo_rust::codegen::audit::PanicPolicy,
+    params: elo_rust::params::Params,
+) -> String {
     use elo_rust::RustCodeGenerator;
 
     // Create a code generator
-    let generator = RustCodeGenerator::new();
+    let generator = RustCodeGenerator::new()
+        .with_panic_policy(panic_policy)
+        .with_params(params);
 
     // Generate the validator function
     match generator.generate_validator("validate", elo_expr, "T") {
@@ -238,18 +1011,38 @@ fn generate_validator_code(elo_expr: &str) -> String {
         Err(e) => {
             eprintln!("Codegen error: {}", e);
             format!(
-                "//! Codegen error: {}\npub fn validate(input: &impl std::any::Any) -> Result<(), Vec<String>> {{\n    Err(vec![\"{}\".to_string()])\n}}\n",
+                "//! Codegen error: {}\npub fn validate(input: &impl std::any::Any) -> Result<(), elo_rust::runtime::ValidationErrors> {{\n    let mut errors = elo_rust::runtime::ValidationErrors::new();\n    errors.push(elo_rust::runtime::ValidationError::new(\"validate\", {:?}, \"validate\"));\n    Err(errors)\n}}\n",
                 e, e
             )
         }
     }
 }
 
+/// Load the project configuration from `--config <file>`, or from
+/// `elo.toml` in the working directory if present
+fn load_config(config_file: Option<&str>) -> io::Result<Option<elo_rust::config::Config>> {
+    use elo_rust::config::Config;
+
+    let config = match config_file {
+        Some(file) => Config::load(file).map(Some),
+        None => Config::discover(),
+    };
+    config.map_err(|e| {
+        eprintln!("✗ {}", e);
+        io::Error::new(io::ErrorKind::InvalidInput, "Invalid configuration")
+    })
+}
+
 fn print_usage(program: &str) {
     println!("Usage: {} <command> [options]", program);
     println!("\nCommands:");
     println!("  compile     Compile ELO expression to Rust code");
     println!("  validate    Validate ELO expression");
+    println!("  lint        Check ELO expression for likely mistakes");
+    println!("  stats       Show size and estimated cost of an ELO expression");
+    println!("  table       Convert a CSV or Markdown decision table to ELO");
+    println!("  check       Find inputs that pass and fail an ELO expression");
+    println!("  migrate     Rewrite expressions relying on 0.x operator semantics");
     println!("  help        Show this help message");
     println!("\nOptions:");
     println!("  -h, --help      Show help for command");
@@ -265,6 +1058,12 @@ fn print_help() {
     println!("  elo compile --expression 'age >= 18'");
     println!("  elo compile --input rules.elo --output validator.rs");
     println!("  elo validate --input rules.elo");
+    println!("  elo validate --rules users.elo --format csv < users.csv");
+    println!("  elo lint --expression 'verified == true'");
+    println!("  elo stats --input rules.elo");
+    println!("  elo table --input discounts.csv");
+    println!("  elo check --expression 'age >= 18 && age < 16'");
+    println!("  elo migrate --input rules.elo --in-place");
 }
 
 fn print_compile_help() {
@@ -276,12 +1075,17 @@ fn print_compile_help() {
     println!("  -e, --expression <expr>  ELO expression to compile");
     println!("  -i, --input <file>       Read ELO expression from file");
     println!("  -o, --output <file>      Write generated code to file");
+    println!("  --deny-panics            Fail if the generated code could panic");
+    println!("  -p, --param <name=value> Bind the placeholder ${{name}} (value read as JSON,");
+    println!("                           or else as a string)");
+    println!("  -c, --config <file>      Read options from a config file (default: elo.toml)");
     println!("  -h, --help               Show this help message");
     println!();
     println!("Examples:");
     println!("  elo compile --expression 'age >= 18'");
     println!("  elo compile --input rules.elo --output validator.rs");
     println!("  elo compile --expression 'user.age >= 18 && user.verified == true'");
+    println!("  elo compile --expression 'amount <= ${{max_amount}}' --param max_amount=500");
 }
 
 fn print_validate_help() {
@@ -289,13 +1093,132 @@ fn print_validate_help() {
     println!();
     println!("Usage: elo validate [options]");
     println!();
+    println!("With --rules, checks each NDJSON or CSV record against a rule set and");
+    println!("prints one result per record and a summary. Records are read one line");
+    println!("at a time, so inputs of any size use bounded memory.");
+    println!();
     println!("Options:");
-    println!("  -i, --input <file>  Read ELO expression from file");
-    println!("  -h, --help          Show this help message");
+    println!("  -i, --input <file>      Read ELO expression from file");
+    println!("  -r, --rules <file>      Check data records against a rule set file");
+    println!("  -d, --data <file>       Read records from file (default: stdin)");
+    println!("  -f, --format <format>   Record format: ndjson (default) or csv");
+    println!("  -h, --help              Show this help message");
     println!();
     println!("Examples:");
     println!("  echo 'age >= 18' | elo validate");
     println!("  elo validate --input rules.elo");
+    println!("  elo validate --rules users.elo --format ndjson < users.ndjson");
+    println!("  elo validate --rules users.elo --format csv --data users.csv");
+}
+
+fn print_stats_help() {
+    println!("stats - Show size and estimated evaluation cost of ELO expressions");
+    println!();
+    println!("Usage: elo stats [options]");
+    println!();
+    println!("Options:");
+    println!("  -e, --expression <expr>  ELO expression to measure");
+    println!("  -i, --input <file>       Read ELO expression from file");
+    println!("  -r, --rules <file>       Show generated-code size and compile cost per rule");
+    println!("  -h, --help               Show this help message");
+    println!();
+    println!("Examples:");
+    println!("  elo stats --expression \"any(items, fn(i ~> matches(i.sku, '^A')))\"");
+    println!("  elo stats --input rules.elo");
+    println!("  elo stats --rules policy.elo");
+}
+
+fn print_check_help() {
+    println!("check - Find inputs that pass and fail an ELO expression");
+    println!();
+    println!("Usage: elo check [options]");
+    println!();
+    println!("Each field is tried with values around the constants it is compared");
+    println!("with. Fails if no input can pass the expression.");
+    println!();
+    println!("Options:");
+    println!("  -e, --expression <expr>  ELO expression to check");
+    println!("  -i, --input <file>       Read ELO expression from file");
+    println!("  -h, --help               Show this help message");
+    println!();
+    println!("Examples:");
+    println!("  elo check --expression 'age >= 18 && length(name) > 0'");
+    println!("  elo check --input rules.elo");
+}
+
+fn print_migrate_help() {
+    println!("migrate - Rewrite expressions relying on 0.x operator semantics");
+    println!();
+    println!("Usage: elo migrate [options]");
+    println!();
+    println!("Validators generated by 0.x compiled 'a ^ b' as 'a * b' and '+x' as '-x'.");
+    println!("By default these are rewritten to keep the 0.x results; with");
+    println!("--adopt-fixes the correct meaning is kept and the changed places are");
+    println!("reported.");
+    println!();
+    println!("Options:");
+    println!("  -e, --expression <expr>  ELO expression to migrate");
+    println!("  -i, --input <file>       Read ELO expression from file");
+    println!("  --adopt-fixes            Keep the correct semantics instead of the 0.x results");
+    println!("  --in-place               Rewrite the --input file instead of printing");
+    println!("  -h, --help               Show this help message");
+    println!();
+    println!("Examples:");
+    println!("  elo migrate --expression 'area <= width ^ 2'");
+    println!("  elo migrate --input rules.elo --in-place");
+}
+
+fn print_table_help() {
+    println!("table - Convert a decision table to an ELO if/else chain");
+    println!();
+    println!("Usage: elo table [options]");
+    println!();
+    println!("The last column holds the outcome; the others are conditions on the");
+    println!("expression in their header. Tables starting with '|' are read as");
+    println!("Markdown, anything else as CSV.");
+    println!();
+    println!("Options:");
+    println!("  -i, --input <file>    Read the table from file");
+    println!("  --first               First matching row wins (default: rows must not overlap)");
+    println!(
+        "  --default <expr>      Outcome when no row matches (default: table must be exhaustive)"
+    );
+    println!("  -h, --help            Show this help message");
+    println!();
+    println!("Examples:");
+    println!("  elo table --input discounts.csv");
+    println!("  elo table --input shipping.md --first");
+}
+
+fn print_lint_help() {
+    println!("lint - Check ELO expressions for likely mistakes");
+    println!();
+    println!("Usage: elo lint [options]");
+    println!();
+    println!("Options:");
+    println!("  -e, --expression <expr>      ELO expression to lint");
+    println!("  -i, --input <file>           Read ELO expression from file");
+    println!("  --allow <lint>               Do not report a lint");
+    println!("  --warn <lint>                Report a lint as a warning (default)");
+    println!("  --deny <lint>                Report a lint as an error and fail");
+    println!("  --max-ternary-depth <n>      Deepest allowed nesting of if expressions");
+    println!("  -f, --format <format>        Output format: text, json or sarif (default: text)");
+    println!("  --fix                        Apply automatic fixes (rewrites --input files)");
+    println!(
+        "  -c, --config <file>          Read lint levels from a config file (default: elo.toml)"
+    );
+    println!("  -h, --help                   Show this help message");
+    println!();
+    println!("Lints:");
+    for kind in elo_rust::lint::LintKind::ALL {
+        println!("  {}", kind);
+    }
+    println!();
+    println!("Examples:");
+    println!("  elo lint --expression 'price == 0.1' --deny float_equality");
+    println!("  echo 'age >= 18' | elo lint");
+    println!("  elo lint --input rule.elo --format sarif > lint.sarif");
+    println!("  elo lint --input rule.elo --fix");
 }
 
 fn print_version() {
@@ -3,25 +3,88 @@
 //! This module implements the Visitor trait to transform ELO AST nodes into
 //! Rust TokenStreams that can be compiled.
 
+use crate::ast::printer;
 use crate::ast::visitor::Visitor;
 use crate::ast::{BinaryOperator, Expr, Literal, TemporalKeyword, UnaryOperator};
+use crate::parser::operators::OperatorTable;
 use proc_macro2::TokenStream;
 use quote::quote;
+use std::collections::HashSet;
 
 use super::{
+    float::float_literal,
     functions::FunctionGenerator,
     operators::{BinaryOp, OperatorGenerator, UnaryOp},
+    optimization::{MembershipTable, CSE_PREFIX},
+    options::TemporalBackend,
+    rust_ident,
     temporal::TemporalGenerator,
+    type_inference::{AlternativeStrategy, InferredType, TypeInferenceVisitor, CONTEXT_ROOT},
+    types::TypeContext,
 };
 
+/// What generated code does when a `guard` condition does not hold
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GuardPolicy {
+    /// Fail the check the guard belongs to
+    ///
+    /// Validators report a failed guard like a failed `require`, with the
+    /// message `guard failed: <condition>`. A guard whose body is not a
+    /// check (`guard qty > 0 in total / qty`) then yields the default value
+    /// of the body's type.
+    #[default]
+    Error,
+    /// Panic with the `guard failed: <condition>` message
+    Panic,
+}
+
 /// Visitor that generates Rust code from ELO AST
 #[derive(Debug)]
 pub struct CodegenVisitor {
     operator_gen: OperatorGenerator,
     function_gen: FunctionGenerator,
     temporal_gen: TemporalGenerator,
+    /// Names of rules that identifiers may refer to (see `crate::rules`)
+    rule_refs: HashSet<String>,
+    /// Rule name under which `require`/`warn_if` push into the
+    /// `__elo_errors`/`__elo_warnings` collections of the enclosing validator
+    diagnostics_rule: Option<String>,
+    /// Path under which reported field paths are nested
+    base_path: String,
+    /// Rule name under which `require`/`warn_if` report into `__elo_sink`
+    sink_rule: Option<String>,
+    uses_require: bool,
+    uses_warn_if: bool,
+    /// Custom operators whose function calls are generated by the operator
+    operators: OperatorTable,
+    /// Whether free identifiers are fields of the validator's `input`
+    input_fields: bool,
+    /// Names bound by enclosing `let` expressions and lambdas
+    locals: Vec<String>,
+    /// Infers operand types where the generated code depends on them
+    types: TypeInferenceVisitor,
+    /// Nullable operands of the enclosing comparisons, with the code reading
+    /// the values the guarding `match` binds them to
+    optional_bindings: Vec<(Expr, TokenStream)>,
+    guard_policy: GuardPolicy,
+    /// Values of the enclosing `let`s binding shared calls (see
+    /// [`CSE_PREFIX`]), whose fields checks reading them report
+    shared_calls: Vec<(String, Expr)>,
 }
 
+/// Functions returning an `Option`, guarded like nullable fields when compared
+const NULLABLE_FUNCTIONS: &[&str] = &["min_by", "max_by"];
+
+/// Functions that take a possibly null value as it is
+const NULL_AWARE_FUNCTIONS: &[&str] = &[
+    "is_null",
+    "is_some",
+    "is_present",
+    "required_if",
+    "required_unless",
+    "requires_with",
+];
+
 impl CodegenVisitor {
     /// Create a new code generation visitor
     pub fn new() -> Self {
@@ -29,9 +92,115 @@ impl CodegenVisitor {
             operator_gen: OperatorGenerator::new(),
             function_gen: FunctionGenerator::new(),
             temporal_gen: TemporalGenerator::new(),
+            rule_refs: HashSet::new(),
+            diagnostics_rule: None,
+            base_path: String::new(),
+            sink_rule: None,
+            uses_require: false,
+            uses_warn_if: false,
+            operators: OperatorTable::new(),
+            input_fields: false,
+            locals: Vec::new(),
+            types: TypeInferenceVisitor::new(),
+            optional_bindings: Vec::new(),
+            guard_policy: GuardPolicy::default(),
+            shared_calls: Vec::new(),
         }
     }
 
+    /// Generate calls of custom operator functions with the operators' codegen
+    pub fn with_operators(mut self, operators: OperatorTable) -> Self {
+        self.operators = operators;
+        self
+    }
+
+    /// Emit `require`/`warn_if` failures into diagnostic collections
+    ///
+    /// The generated code expects `__elo_errors` and `__elo_warnings` (both
+    /// [`ValidationErrors`](crate::runtime::ValidationErrors)) to be in
+    /// scope; failures are reported under `rule`, see
+    /// [`validation_error`](Self::validation_error). Without this, `require`
+    /// compiles to its bare condition and `warn_if` to `true`.
+    pub fn with_diagnostics(mut self, rule: impl Into<String>) -> Self {
+        self.diagnostics_rule = Some(rule.into());
+        self
+    }
+
+    /// Nest the paths of reported errors under `path` (e.g. `"user"`)
+    pub fn with_base_path(mut self, path: impl Into<String>) -> Self {
+        self.base_path = path.into();
+        self
+    }
+
+    /// Infer the types of `input_type`'s fields from `context`
+    ///
+    /// Operators whose code depends on operand types (`^` on integers or
+    /// floats) then follow the declared field types; without a context only
+    /// literals are known, and unknown operands are taken as integers.
+    /// Comparisons reading `Option` fields are guarded by a `match` on them,
+    /// and `??`/`?|` unwrap such fields with their default.
+    pub fn with_type_context(mut self, context: TypeContext, input_type: &str) -> Self {
+        self.types = TypeInferenceVisitor::with_context(context, input_type);
+        self
+    }
+
+    /// Read free identifiers from the validator's `input` parameter
+    ///
+    /// `age >= 18` then compiles to `input.age >= 18`. Names bound by `let`
+    /// or a lambda, rule references and `input` itself are left as they are.
+    pub fn with_input_fields(mut self) -> Self {
+        self.input_fields = true;
+        self
+    }
+
+    /// Report `require`/`warn_if` messages into an error sink
+    ///
+    /// The generated code expects `__elo_sink` (an
+    /// [`ErrorSink`](crate::runtime::sink::ErrorSink)) and
+    /// `mut __elo_failed: bool` to be in scope; failures are reported under
+    /// `rule`. Takes precedence over [`with_diagnostics`](Self::with_diagnostics).
+    pub fn with_sink(mut self, rule: impl Into<String>) -> Self {
+        self.sink_rule = Some(rule.into());
+        self
+    }
+
+    /// Set what the generated code does when a `guard` fails
+    pub fn with_guard_policy(mut self, policy: GuardPolicy) -> Self {
+        self.guard_policy = policy;
+        self
+    }
+
+    /// Generate dates, times and durations for the given crate
+    pub fn with_temporal_backend(mut self, backend: TemporalBackend) -> Self {
+        self.function_gen = FunctionGenerator::new().with_temporal_backend(backend);
+        self.temporal_gen = TemporalGenerator::with_backend(backend);
+        self
+    }
+
+    /// Whether a `require` expression, or a guard reporting its failure,
+    /// was generated
+    pub fn uses_require(&self) -> bool {
+        self.uses_require
+    }
+
+    /// Whether a `warn_if` expression was generated
+    pub fn uses_warn_if(&self) -> bool {
+        self.uses_warn_if
+    }
+
+    /// Treat the given names as references to other rules
+    ///
+    /// Identifiers matching one of these names are emitted as calls to the
+    /// generated rule helper (`rule_<name>(input)`) instead of plain variables.
+    pub fn with_rule_references<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.rule_refs = names.into_iter().map(Into::into).collect();
+        self
+    }
+
     /// Convert AST BinaryOperator to codegen BinaryOp
     fn convert_binary_op(op: BinaryOperator) -> BinaryOp {
         match op {
@@ -40,7 +209,7 @@ impl CodegenVisitor {
             BinaryOperator::Mul => BinaryOp::Multiply,
             BinaryOperator::Div => BinaryOp::Divide,
             BinaryOperator::Mod => BinaryOp::Modulo,
-            BinaryOperator::Pow => BinaryOp::Multiply, // Fallback, would need special handling
+            BinaryOperator::Pow => BinaryOp::Power,
             BinaryOperator::Eq => BinaryOp::Equal,
             BinaryOperator::Neq => BinaryOp::NotEqual,
             BinaryOperator::Lt => BinaryOp::Less,
@@ -49,6 +218,8 @@ impl CodegenVisitor {
             BinaryOperator::Gte => BinaryOp::GreaterEqual,
             BinaryOperator::And => BinaryOp::And,
             BinaryOperator::Or => BinaryOp::Or,
+            BinaryOperator::In => BinaryOp::In,
+            BinaryOperator::Like => BinaryOp::Like,
         }
     }
 
@@ -57,8 +228,497 @@ impl CodegenVisitor {
         match op {
             UnaryOperator::Not => UnaryOp::Not,
             UnaryOperator::Neg => UnaryOp::Negate,
-            UnaryOperator::Plus => UnaryOp::Negate, // Identity, treat as no-op via negate
+            UnaryOperator::Plus => UnaryOp::Identity,
+        }
+    }
+
+    /// Whether `base ^ exponent` is computed on floats, if either operand is
+    /// a float; integer powers stay integers, even with negative exponents
+    fn float_power(&self, base: &Expr, exponent: &Expr) -> bool {
+        self.value_type(base) == InferredType::Float
+            || self.value_type(exponent) == InferredType::Float
+    }
+
+    /// Operands of an arithmetic or comparison operator with an integer
+    /// operand converted to `f64` when the other one is a float
+    ///
+    /// Integer literals are written as float literals, other integers cast.
+    fn coerce_to_float(
+        &self,
+        op: BinaryOperator,
+        (l, left): (TokenStream, &Expr),
+        (r, right): (TokenStream, &Expr),
+    ) -> (TokenStream, TokenStream) {
+        if !matches!(
+            op,
+            BinaryOperator::Add
+                | BinaryOperator::Sub
+                | BinaryOperator::Mul
+                | BinaryOperator::Div
+                | BinaryOperator::Mod
+                | BinaryOperator::Eq
+                | BinaryOperator::Neq
+                | BinaryOperator::Lt
+                | BinaryOperator::Lte
+                | BinaryOperator::Gt
+                | BinaryOperator::Gte
+        ) {
+            return (l, r);
+        }
+        let to_float = |code: TokenStream, operand: &Expr| match operand {
+            Expr::Literal(Literal::Integer(n)) => float_literal(*n as f64),
+            _ => quote! { (#code) as f64 },
+        };
+        match (self.value_type(left), self.value_type(right)) {
+            (InferredType::Float, InferredType::Integer) => (l, to_float(r, right)),
+            (InferredType::Integer, InferredType::Float) => (to_float(l, left), r),
+            _ => (l, r),
+        }
+    }
+
+    /// Inferred type of `expr`, or of its value if it is nullable
+    fn value_type(&self, expr: &Expr) -> InferredType {
+        match self.types.infer(expr) {
+            InferredType::Nullable(inner) => *inner,
+            ty => ty,
+        }
+    }
+
+    /// Inferred type of a field read, `Unknown` for reads of locals and of
+    /// the context, whose types are not tracked
+    fn field_type(&self, expr: &Expr) -> InferredType {
+        let mut root = expr;
+        while let Expr::FieldAccess { receiver, .. } = root {
+            root = receiver;
+        }
+        match root {
+            Expr::Identifier(name)
+                if name != CONTEXT_ROOT
+                    && !self.locals.contains(name)
+                    && !self.rule_refs.contains(name) =>
+            {
+                self.types.infer(expr)
+            }
+            _ => InferredType::Unknown,
+        }
+    }
+
+    /// Whether `expr` reads a field of a known type that cannot be null
+    fn is_present(&self, expr: &Expr) -> bool {
+        let ty = self.field_type(expr);
+        ty != InferredType::Unknown && !ty.is_nullable() && !ty.is_error()
+    }
+
+    /// Collect the nullable operands of a comparison in `expr`
+    ///
+    /// These are `Option` fields, indexed elements and the results of
+    /// `min_by`/`max_by`, which are null for an empty array. Null-aware forms
+    /// (`??`, `?|`, `is_null`, ...) and nested scopes are not searched.
+    fn collect_optionals(&self, expr: &Expr, optionals: &mut Vec<Expr>) {
+        if optionals.contains(expr) || self.optional_bindings.iter().any(|(e, _)| e == expr) {
+            return;
+        }
+        match expr {
+            Expr::Index { .. } => optionals.push(expr.clone()),
+            Expr::Identifier(_) | Expr::FieldAccess { .. }
+                if matches!(self.field_type(expr), InferredType::Nullable(_)) =>
+            {
+                optionals.push(expr.clone())
+            }
+            Expr::FunctionCall { name, .. } if NULLABLE_FUNCTIONS.contains(&name.as_str()) => {
+                optionals.push(expr.clone())
+            }
+            Expr::FunctionCall { name, .. } if NULL_AWARE_FUNCTIONS.contains(&name.as_str()) => {}
+            Expr::Lambda { .. }
+            | Expr::Let { .. }
+            | Expr::If { .. }
+            | Expr::Guard { .. }
+            | Expr::Require { .. }
+            | Expr::WarnIf { .. }
+            | Expr::Alternative { .. }
+            | Expr::NullDefault { .. } => {}
+            _ => {
+                for child in expr.children() {
+                    self.collect_optionals(child, optionals);
+                }
+            }
+        }
+    }
+
+    /// `value == null` as `value.is_none()`, `value != null` as `is_some()`
+    ///
+    /// Fields the input type declares as not optional are never null.
+    fn null_comparison(
+        &mut self,
+        op: BinaryOperator,
+        left: &Expr,
+        right: &Expr,
+    ) -> Option<TokenStream> {
+        let value = match (op, left, right) {
+            (BinaryOperator::Eq | BinaryOperator::Neq, Expr::Null, value)
+            | (BinaryOperator::Eq | BinaryOperator::Neq, value, Expr::Null) => value,
+            _ => return None,
+        };
+        let is_null = op == BinaryOperator::Eq;
+        let never_null = matches!(value, Expr::Identifier(_) | Expr::FieldAccess { .. })
+            && !matches!(
+                self.field_type(value),
+                InferredType::Nullable(_)
+                    | InferredType::Null
+                    | InferredType::Unknown
+                    | InferredType::Error(_)
+            );
+        if never_null {
+            let is_not_null = !is_null;
+            return Some(quote! { #is_not_null });
+        }
+        let value = self.visit_expr(value);
+        Some(if is_null {
+            quote! { (#value).is_none() }
+        } else {
+            quote! { (#value).is_some() }
+        })
+    }
+
+    /// Guard a comparison reading nullable operands with a `match`
+    ///
+    /// The comparison is generated on the operands' values; when one of them
+    /// is null it is false, except `!=`, which is true.
+    fn guarded_comparison(
+        &mut self,
+        op: BinaryOperator,
+        left: &Expr,
+        right: &Expr,
+        optionals: Vec<Expr>,
+    ) -> TokenStream {
+        let start = self.optional_bindings.len();
+        let mut scrutinees = Vec::new();
+        let mut names = Vec::new();
+        for (i, optional) in optionals.into_iter().enumerate() {
+            let name = quote::format_ident!("__elo_some{}", start + i);
+            let code = self.visit_expr(&optional);
+            // Values of `Copy` types and temporaries (indexed elements, call
+            // results) are matched by value, others are borrowed from the input
+            let (scrutinee, value) = if self.is_copied(&optional) {
+                (code, quote! { #name })
+            } else {
+                (quote! { &#code }, quote! { (*#name) })
+            };
+            scrutinees.push(scrutinee);
+            names.push(name);
+            self.optional_bindings.push((optional, value));
+        }
+        let comparison = self.visit_binary_op(op, left, right);
+        self.optional_bindings.truncate(start);
+
+        let if_null = op == BinaryOperator::Neq;
+        if let ([scrutinee], [name]) = (scrutinees.as_slice(), names.as_slice()) {
+            return quote! {
+                match #scrutinee {
+                    Some(#name) => #comparison,
+                    None => #if_null,
+                }
+            };
+        }
+        quote! {
+            match (#(#scrutinees),*) {
+                (#(Some(#names)),*) => #comparison,
+                _ => #if_null,
+            }
+        }
+    }
+
+    /// Whether the nullable operand `optional` is matched by value
+    fn is_copied(&self, optional: &Expr) -> bool {
+        let temporary = match optional {
+            Expr::Index { .. } => true,
+            Expr::FunctionCall { name, .. } => NULLABLE_FUNCTIONS.contains(&name.as_str()),
+            _ => false,
+        };
+        temporary
+            || matches!(
+                self.value_type(optional),
+                InferredType::Integer
+                    | InferredType::Float
+                    | InferredType::Boolean
+                    | InferredType::Date
+                    | InferredType::DateTime
+                    | InferredType::Duration
+            )
+    }
+
+    /// Code for the default of `??`/`?|` on a value of type `value_type`
+    ///
+    /// Integer literal defaults of float fields are written as floats.
+    fn visit_default(&mut self, value_type: &InferredType, default: &Expr) -> TokenStream {
+        match (value_type, default) {
+            (InferredType::Float, Expr::Literal(Literal::Integer(n))) => float_literal(*n as f64),
+            _ => self.visit_expr(default),
+        }
+    }
+
+    /// Code building the `ValidationError` reported when `check` fails
+    ///
+    /// The error's path is that of the one input field `check` reads, nested
+    /// under the base path, and the field's value is attached when its type
+    /// implements `Display` or `Debug` (see [`crate::runtime::inspect`]).
+    /// A check reading no field or several is reported at the base path, or
+    /// under the rule's name without one.
+    pub fn validation_error(&self, check: &Expr, message: &str, warning: bool) -> TokenStream {
+        let rule = self.diagnostics_rule.as_deref().unwrap_or_default();
+        let constructor = if warning {
+            quote! { warning }
+        } else {
+            quote! { new }
+        };
+        let Some(field) = self.checked_field(check) else {
+            let path = if self.base_path.is_empty() {
+                rule
+            } else {
+                &self.base_path
+            };
+            return quote! {
+                elo_rust::runtime::ValidationError::#constructor(#path, #message, #rule)
+            };
+        };
+        let mut segments = field.split('.');
+        let root = segments.next().unwrap_or_default();
+        let mut value = if root == "input" || !self.input_fields {
+            let ident = rust_ident(root);
+            quote! { #ident }
+        } else {
+            let ident = rust_ident(root);
+            quote! { input.#ident }
+        };
+        for segment in segments {
+            let ident = rust_ident(segment);
+            value = quote! { #value.#ident };
+        }
+        let field = field.strip_prefix("input.").unwrap_or(&field);
+        let path = if self.base_path.is_empty() {
+            field.to_string()
+        } else {
+            format!("{}.{}", self.base_path, field)
+        };
+        quote! {
+            {
+                #[allow(unused_imports)]
+                use elo_rust::runtime::inspect::{
+                    InspectDebug as _, InspectDisplay as _, InspectNone as _,
+                };
+                let error = elo_rust::runtime::ValidationError::#constructor(#path, #message, #rule);
+                match (&&&elo_rust::runtime::inspect::Inspect(&#value)).reported_value() {
+                    Some(value) => error.with_value(value),
+                    None => error,
+                }
+            }
+        }
+    }
+
+    /// The single input field `expr` reads, as a dotted path, if there is one
+    fn checked_field(&self, expr: &Expr) -> Option<String> {
+        let mut fields = self.read_fields(expr).into_iter().filter(|path| {
+            let root = path.split('.').next().unwrap_or_default();
+            root != "ctx"
+                && path != "input"
+                && !self.rule_refs.contains(root)
+                && !self.locals.iter().any(|l| l == root)
+        });
+        let field = fields.next()?;
+        fields.next().is_none().then_some(field)
+    }
+
+    /// Field paths `expr` reads, through the variables of shared calls
+    fn read_fields(&self, expr: &Expr) -> std::collections::BTreeSet<String> {
+        let mut fields = std::collections::BTreeSet::new();
+        for path in expr.referenced_fields() {
+            let root = path.split('.').next().unwrap_or_default();
+            match self
+                .shared_calls
+                .iter()
+                .rev()
+                .find(|(name, _)| name == root)
+            {
+                Some((_, call)) => fields.extend(self.read_fields(call)),
+                None => {
+                    fields.insert(path);
+                }
+            }
+        }
+        fields
+    }
+
+    /// Check every conjunct of a top-level `&&` chain, reporting each failure
+    ///
+    /// Unlike `&&`, a failed conjunct does not skip the ones after it; see
+    /// [`collect_failures`](Self::collect_failures). The `let`s that
+    /// [share calls](super::optimization::Optimizer::eliminate_common_subexpressions)
+    /// around the chain are kept, and other expressions are generated as
+    /// usual.
+    pub fn visit_collect_all(&mut self, expr: &Expr) -> TokenStream {
+        if let Expr::Let { name, value, body } = expr {
+            if name.starts_with(CSE_PREFIX) {
+                return self.scoped_let(name, value, body, Self::visit_collect_all);
+            }
+        }
+        let conjuncts = super::conjuncts(expr);
+        if conjuncts.len() < 2 {
+            return self.visit_expr(expr);
         }
+        let checks = conjuncts
+            .into_iter()
+            .map(|conjunct| (self.visit_expr(conjunct), conjunct))
+            .collect();
+        self.collect_failures(checks)
+    }
+
+    /// Evaluate every check, reporting each failed one that reported nothing
+    /// itself
+    ///
+    /// `checks` pairs the code of each check with the expression it
+    /// generates. A failed check that pushed no `require` message is
+    /// reported as "Validation failed" under the fields its expression
+    /// reads, see [`validation_error`](Self::validation_error). The code
+    /// needs `__elo_errors` in scope, as set up by
+    /// [`with_diagnostics`](Self::with_diagnostics), and is true if all
+    /// checks passed.
+    pub fn collect_failures(&mut self, checks: Vec<(TokenStream, &Expr)>) -> TokenStream {
+        self.uses_require = true;
+        let checks: Vec<TokenStream> = checks
+            .into_iter()
+            .map(|(code, check)| {
+                let failure = self.validation_error(check, "Validation failed", false);
+                quote! {
+                    {
+                        let __elo_mark = __elo_errors.len();
+                        let passed: bool = #code;
+                        if !passed && __elo_errors.len() == __elo_mark {
+                            __elo_errors.push(#failure);
+                        }
+                        __elo_passed &= passed;
+                    }
+                }
+            })
+            .collect();
+        quote! {
+            {
+                let mut __elo_passed = true;
+                #(#checks)*
+                __elo_passed
+            }
+        }
+    }
+
+    /// `let name = value in body`, with `body` generated by `visit_body`
+    /// while `name` is in scope
+    fn scoped_let(
+        &mut self,
+        name: &str,
+        value: &Expr,
+        body: &Expr,
+        visit_body: fn(&mut Self, &Expr) -> TokenStream,
+    ) -> TokenStream {
+        let var_ident = rust_ident(name);
+        let val = self.visit_expr(value);
+        let shadowed = self.rule_refs.remove(name);
+        let shared = name.starts_with(CSE_PREFIX);
+        if shared {
+            self.shared_calls.push((name.to_string(), value.clone()));
+        }
+        self.locals.push(name.to_string());
+        let bound = self.types.bind(name, self.types.infer(value));
+        let outer = std::mem::replace(&mut self.types, bound);
+        let bod = visit_body(self, body);
+        self.types = outer;
+        self.locals.pop();
+        if shared {
+            self.shared_calls.pop();
+        }
+        if shadowed {
+            self.rule_refs.insert(name.to_string());
+        }
+        let bod = block_tail(bod);
+        quote! {
+            {
+                let #var_ident = #val;
+                #bod
+            }
+        }
+    }
+
+    /// `primary ?| alternative` on validations: the alternative is only
+    /// evaluated when the primary fails, and the primary's `require`
+    /// messages are only reported when both fail
+    fn validation_fallback(&mut self, primary: &Expr, alternative: &Expr) -> TokenStream {
+        let uses_require = std::mem::take(&mut self.uses_require);
+        let prim = self.visit_expr(primary);
+        let primary_requires = self.uses_require;
+        self.uses_require |= uses_require;
+        let alt = self.visit_expr(alternative);
+        if self.sink_rule.is_some() {
+            // `__elo_failed` is only mutable when a `require` was generated
+            let save =
+                primary_requires.then(|| quote! { let __elo_was_failed: bool = __elo_failed; });
+            let restore = primary_requires.then(|| {
+                quote! {
+                    if passed {
+                        __elo_failed = __elo_was_failed;
+                    }
+                }
+            });
+            // Collect the primary's failures aside, then forward them
+            // (warnings always, errors only if the alternative fails too)
+            return quote! {
+                {
+                    #save
+                    let mut __elo_deferred: Vec<elo_rust::runtime::sink::Failure> = Vec::new();
+                    let passed: bool = {
+                        let __elo_sink = &mut __elo_deferred;
+                        #prim
+                    };
+                    let passed = passed || {
+                        let passed: bool = #alt;
+                        #restore
+                        passed
+                    };
+                    for failure in __elo_deferred {
+                        if !passed || failure.is_warning() {
+                            __elo_sink.push(failure);
+                        }
+                    }
+                    passed
+                }
+            };
+        }
+        // Only a `require` in the primary can leave errors to discard
+        if self.diagnostics_rule.is_some() && primary_requires {
+            return quote! {
+                {
+                    let __elo_mark = __elo_errors.len();
+                    let passed: bool = #prim;
+                    passed || {
+                        let passed: bool = #alt;
+                        if passed {
+                            __elo_errors.errors.truncate(__elo_mark);
+                        }
+                        passed
+                    }
+                }
+            };
+        }
+        quote! { (#prim || #alt) }
+    }
+}
+
+/// `expr` as the value of a block
+///
+/// An expression starting with a block, `if` or `match` followed by more
+/// (`{ .. } && ok`) would be read as a statement there, so it is
+/// parenthesized; other expressions are left bare.
+fn block_tail(expr: TokenStream) -> TokenStream {
+    match syn::parse2::<syn::Block>(quote! { { #expr } }) {
+        Ok(block) if block.stmts.len() <= 1 => expr,
+        _ => quote! { (#expr) },
     }
 }
 
@@ -70,12 +730,17 @@ impl Default for CodegenVisitor {
 
 impl Visitor<TokenStream> for CodegenVisitor {
     fn visit_expr(&mut self, expr: &Expr) -> TokenStream {
+        if let Some((_, value)) = self.optional_bindings.iter().find(|(e, _)| e == expr) {
+            return value.clone();
+        }
         match expr {
             Expr::Literal(lit) => self.visit_literal(lit),
             Expr::Null => self.visit_null(),
             Expr::Identifier(name) => self.visit_identifier(name),
+            Expr::Placeholder(name) => self.visit_placeholder(name),
             Expr::String(value) => self.visit_string(value),
             Expr::FieldAccess { receiver, field } => self.visit_field_access(receiver, field),
+            Expr::Index { collection, index } => self.visit_index(collection, index),
             Expr::BinaryOp { op, left, right } => self.visit_binary_op(*op, left, right),
             Expr::UnaryOp { op, operand } => self.visit_unary_op(*op, operand),
             Expr::FunctionCall { name, args } => self.visit_function_call(name, args),
@@ -94,6 +759,16 @@ impl Visitor<TokenStream> for CodegenVisitor {
                 alternative,
             } => self.visit_alternative(primary, alternative),
             Expr::Guard { condition, body } => self.visit_guard(condition, body),
+            Expr::NullDefault { value, default } => self.visit_null_default(value, default),
+            Expr::Range {
+                start,
+                end,
+                inclusive,
+            } => self.visit_range(start, end, *inclusive),
+            Expr::Quantity { value, unit } => self.visit_quantity(*value, unit),
+            Expr::Percentage(percent) => self.visit_percentage(*percent),
+            Expr::Require { condition, message } => self.visit_require(condition, message),
+            Expr::WarnIf { condition, message } => self.visit_warn_if(condition, message),
             Expr::Date(date) => self.visit_date(date),
             Expr::DateTime(datetime) => self.visit_datetime(datetime),
             Expr::Duration(duration) => self.visit_duration(duration),
@@ -104,7 +779,7 @@ impl Visitor<TokenStream> for CodegenVisitor {
     fn visit_literal(&mut self, lit: &Literal) -> TokenStream {
         match lit {
             Literal::Integer(n) => quote! { #n },
-            Literal::Float(f) => quote! { #f },
+            Literal::Float(f) => float_literal(*f),
             Literal::Boolean(b) => quote! { #b },
         }
     }
@@ -114,20 +789,105 @@ impl Visitor<TokenStream> for CodegenVisitor {
     }
 
     fn visit_identifier(&mut self, name: &str) -> TokenStream {
-        let ident = quote::format_ident!("{}", name);
+        if self.rule_refs.contains(name) {
+            let helper = crate::rules::rule_fn_ident(name);
+            return quote! { #helper(input) };
+        }
+        let ident = rust_ident(name);
+        if self.input_fields && name != "input" && !self.locals.iter().any(|l| l == name) {
+            return quote! { input.#ident };
+        }
         quote! { #ident }
     }
 
+    fn visit_placeholder(&mut self, name: &str) -> TokenStream {
+        // Placeholders are bound before code generation; see `crate::params`
+        let message = format!("unbound parameter ${{{}}}", name);
+        quote! { compile_error!(#message) }
+    }
+
     fn visit_field_access(&mut self, receiver: &Expr, field: &str) -> TokenStream {
         let recv = self.visit_expr(receiver);
-        let field_ident = quote::format_ident!("{}", field);
+        let field_ident = rust_ident(field);
         quote! { #recv.#field_ident }
     }
 
+    fn visit_index(&mut self, collection: &Expr, index: &Expr) -> TokenStream {
+        // Out-of-range and negative positions give `None` rather than
+        // panicking, so an element reads like a nullable field
+        let idx = self.visit_expr(index);
+        let position = quote! { usize::try_from(#idx).ok() };
+        if let Expr::Index {
+            collection: rows,
+            index: row,
+        } = collection
+        {
+            // `matrix[i][j]`: continue inside the optional row
+            let row = self.visit_index(rows, row);
+            return quote! {
+                #row.and_then(|__elo_row| #position.and_then(|__elo_i| __elo_row.get(__elo_i).cloned()))
+            };
+        }
+        let coll = self.visit_expr(collection);
+        quote! { #position.and_then(|__elo_i| #coll.get(__elo_i).cloned()) }
+    }
+
     fn visit_binary_op(&mut self, op: BinaryOperator, left: &Expr, right: &Expr) -> TokenStream {
+        if let Some(code) = self.null_comparison(op, left, right) {
+            return code;
+        }
+        if matches!(
+            op,
+            BinaryOperator::Eq
+                | BinaryOperator::Neq
+                | BinaryOperator::Lt
+                | BinaryOperator::Lte
+                | BinaryOperator::Gt
+                | BinaryOperator::Gte
+                | BinaryOperator::In
+                | BinaryOperator::Like
+        ) {
+            let mut optionals = Vec::new();
+            self.collect_optionals(left, &mut optionals);
+            self.collect_optionals(right, &mut optionals);
+            if !optionals.is_empty() {
+                return self.guarded_comparison(op, left, right, optionals);
+            }
+        }
         let l = self.visit_expr(left);
+        if let (BinaryOperator::Like, Expr::String(pattern)) = (op, right) {
+            return self.operator_gen.like_literal(l, pattern);
+        }
+        if op == BinaryOperator::In {
+            if let Some(table) = MembershipTable::from_array(right) {
+                return self.operator_gen.membership_table(l, &table);
+            }
+            if let Some(set) = MembershipTable::from_literals(right) {
+                return self.operator_gen.membership_pattern(l, &set);
+            }
+        }
         let r = self.visit_expr(right);
+        let (l, r) = self.coerce_to_float(op, (l, left), (r, right));
+        if op == BinaryOperator::Pow && self.float_power(left, right) {
+            let to_float = |code: TokenStream, operand: &Expr| {
+                if self.value_type(operand) == InferredType::Float {
+                    code
+                } else {
+                    // Parenthesized so the cast applies to the whole operand,
+                    // without tripping `unused_parens` in argument position
+                    quote! { (#code) as f64 }
+                }
+            };
+            let (l, r) = (to_float(l, left), to_float(r, right));
+            return self.operator_gen.power_float(l, r);
+        }
         let codegen_op = Self::convert_binary_op(op);
+        if matches!(op, BinaryOperator::Div | BinaryOperator::Mod)
+            && self.value_type(left) == InferredType::Integer
+            && self.value_type(right) == InferredType::Integer
+        {
+            return self.operator_gen.integer_division(codegen_op, l, r);
+        }
         self.operator_gen.binary(codegen_op, l, r)
     }
 
@@ -138,15 +898,160 @@ impl Visitor<TokenStream> for CodegenVisitor {
     }
 
     fn visit_function_call(&mut self, name: &str, args: &[Expr]) -> TokenStream {
+        // required_if/required_unless/requires_with are sugar for `require`
+        if let Some(expanded) = super::presence::expand_call(name, args) {
+            return self.visit_expr(&expanded);
+        }
+
+        if let (Some(op), [left, right]) = (self.operators.by_function(name).cloned(), args) {
+            let left = self.visit_expr(left);
+            let right = self.visit_expr(right);
+            return op.generate(left, right);
+        }
+
+        // Quantifiers with an explicit predicate bind the lambda parameter
+        // directly in the iterator closure
+        if let ("any" | "all", [collection, Expr::Lambda { param, body }]) = (name, args) {
+            let coll = self.visit_expr(collection);
+            let param_ident = rust_ident(param);
+            let element = self.types.element_type(collection);
+            let shadowed = self.rule_refs.remove(param);
+            self.locals.push(param.clone());
+            let bound = self.types.bind(param, element.clone());
+            let outer = std::mem::replace(&mut self.types, bound);
+            let body = self.visit_expr(body);
+            self.types = outer;
+            self.locals.pop();
+            if shadowed {
+                self.rule_refs.insert(param.clone());
+            }
+            // Ranges are iterators already and yield their values directly;
+            // numbers and booleans are copied so the predicate compares values
+            let iter = if matches!(collection, Expr::Range { .. }) {
+                quote! { #coll }
+            } else if matches!(
+                element,
+                InferredType::Integer | InferredType::Float | InferredType::Boolean
+            ) {
+                quote! { #coll.iter().copied() }
+            } else {
+                quote! { #coll.iter() }
+            };
+            return if name == "any" {
+                quote! { #iter.any(|#param_ident| #body) }
+            } else {
+                quote! { #iter.all(|#param_ident| #body) }
+            };
+        }
+
+        // between(value, low, high) is `value in low..=high`, so it shares the
+        // range check and the null guard of optional fields
+        if let ("between", [value, low, high]) = (name, args) {
+            let check = Expr::BinaryOp {
+                op: BinaryOperator::In,
+                left: Box::new(value.clone()),
+                right: Box::new(Expr::Range {
+                    start: Box::new(low.clone()),
+                    end: Box::new(high.clone()),
+                    inclusive: true,
+                }),
+            };
+            return self.visit_expr(&check);
+        }
+
+        // Collection functions bind their lambda parameter to an element,
+        // or for `reduce` to the accumulator and an element
+        if let (
+            "map" | "filter" | "reduce" | "sum" | "min_by" | "max_by",
+            [collection, .., Expr::Lambda { param, body }],
+        ) = (name, args)
+        {
+            let (values, function) = args.split_at(args.len() - 1);
+            let mut arg_tokens: Vec<TokenStream> =
+                values.iter().map(|arg| self.visit_expr(arg)).collect();
+            let element = self.types.element_type(collection);
+            let bound = match (name, body.as_ref(), values) {
+                ("reduce", Expr::Lambda { param: item, .. }, [_, init]) => self
+                    .types
+                    .bind(param, self.types.infer(init))
+                    .bind(item, element),
+                _ => self.types.bind(param, element),
+            };
+            let outer = std::mem::replace(&mut self.types, bound);
+            arg_tokens.push(self.visit_expr(&function[0]));
+            self.types = outer;
+            return self.function_gen.call(name, arg_tokens);
+        }
+
+        // clamp(value, low..=high) clamps to the range bounds
+        if let (
+            "clamp",
+            [value, Expr::Range {
+                start,
+                end,
+                inclusive,
+            }],
+        ) = (name, args)
+        {
+            // Floats have no largest value below an exclusive end; type
+            // inference reports this before code is generated
+            if !inclusive
+                && [value, start.as_ref(), end.as_ref()]
+                    .into_iter()
+                    .any(|expr| self.value_type(expr) == InferredType::Float)
+            {
+                return quote! { compile_error!("clamp() needs an inclusive range of floats") };
+            }
+            let value = self.visit_expr(value);
+            let low = self.visit_expr(start);
+            let high = self.visit_expr(end);
+            return if *inclusive {
+                quote! { (#value).clamp(#low, #high) }
+            } else {
+                quote! { (#value).clamp(#low, #high - 1) }
+            };
+        }
+        if let ("clamp", [value, low, high]) = (name, args) {
+            let value = self.visit_expr(value);
+            let low = self.visit_expr(low);
+            let high = self.visit_expr(high);
+            return quote! { (#value).clamp(#low, #high) };
+        }
+
+        // A value of a known type that cannot be null is never null
+        if let ("is_null" | "is_some", [value]) = (name, args) {
+            if self.is_present(value) {
+                let present = name == "is_some";
+                return quote! { #present };
+            }
+        }
+
         let arg_tokens: Vec<TokenStream> = args.iter().map(|a| self.visit_expr(a)).collect();
 
+        // `contains` and `length` take strings or arrays; `length` counts
+        // the characters of a string
+        if let ("contains" | "length", [subject, ..]) = (name, args) {
+            return match (name, self.value_type(subject)) {
+                (_, InferredType::Array(_)) | ("length", InferredType::Unknown) => {
+                    self.function_gen.array_function(name, arg_tokens)
+                }
+                _ => self.function_gen.string_function(name, arg_tokens),
+            };
+        }
+
         // Use the unified function generator interface
         self.function_gen.call(name, arg_tokens)
     }
 
     fn visit_lambda(&mut self, param: &str, body: &Expr) -> TokenStream {
-        let param_ident = quote::format_ident!("{}", param);
-        let body = self.visit_expr(body);
+        let param_ident = rust_ident(param);
+        let shadowed = self.rule_refs.remove(param);
+        self.locals.push(param.to_string());
+        let body = block_tail(self.visit_expr(body));
+        self.locals.pop();
+        if shadowed {
+            self.rule_refs.insert(param.to_string());
+        }
         quote! {
             |#param_ident| {
                 #body
@@ -155,15 +1060,7 @@ impl Visitor<TokenStream> for CodegenVisitor {
     }
 
     fn visit_let(&mut self, name: &str, value: &Expr, body: &Expr) -> TokenStream {
-        let var_ident = quote::format_ident!("{}", name);
-        let val = self.visit_expr(value);
-        let bod = self.visit_expr(body);
-        quote! {
-            {
-                let #var_ident = #val;
-                #bod
-            }
-        }
+        self.scoped_let(name, value, body, Self::visit_expr)
     }
 
     fn visit_if(
@@ -173,8 +1070,8 @@ impl Visitor<TokenStream> for CodegenVisitor {
         else_branch: &Expr,
     ) -> TokenStream {
         let cond = self.visit_expr(condition);
-        let then_b = self.visit_expr(then_branch);
-        let else_b = self.visit_expr(else_branch);
+        let then_b = block_tail(self.visit_expr(then_branch));
+        let else_b = block_tail(self.visit_expr(else_branch));
         quote! {
             if #cond { #then_b } else { #else_b }
         }
@@ -188,8 +1085,8 @@ impl Visitor<TokenStream> for CodegenVisitor {
     }
 
     fn visit_object(&mut self, fields: &[(String, Expr)]) -> TokenStream {
-        // For objects, we can't easily generate code without knowing the target type
-        // For now, generate a tuple struct representing the key-value pairs
+        // Objects become maps keyed by field name, which iterate in key
+        // order as the interpreter's objects do
         let pairs: Vec<TokenStream> = fields
             .iter()
             .map(|(k, v)| {
@@ -199,55 +1096,168 @@ impl Visitor<TokenStream> for CodegenVisitor {
             })
             .collect();
         quote! {
-            vec![#(#pairs),*]
+            ::std::collections::BTreeMap::from([#(#pairs),*])
         }
     }
 
     fn visit_pipe(&mut self, value: &Expr, functions: &[Expr]) -> TokenStream {
-        let mut result = self.visit_expr(value);
-
-        for func in functions {
-            // For each function in the pipe, we need to apply it to the previous result
-            // If it's a function call, inject result as first arg
-            // Otherwise, create a function call with result as argument
-            match func {
-                Expr::FunctionCall { name, args } => {
-                    // Insert result as first argument
-                    let mut new_args = vec![result.clone()];
-                    for arg in args {
-                        new_args.push(self.visit_expr(arg));
-                    }
+        // Stages become calls on the piped value and lambda stages `let`
+        // bindings, so they get the same code as when written out
+        match Expr::desugar_pipe(value, functions) {
+            Ok(desugared) => self.visit_expr(&desugared),
+            // Rejected before generation; see `RustCodeGenerator::prepare_ast`
+            Err(message) => quote! { compile_error!(#message) },
+        }
+    }
 
-                    // Generate the function call with the new arguments
-                    let arg_tokens: Vec<TokenStream> = new_args;
-                    result = self.function_gen.call(name, arg_tokens);
-                }
-                Expr::Identifier(name) => {
-                    // Simple identifier - treat as a function call with one argument
-                    result = self.function_gen.call(name, vec![result]);
-                }
-                _ => {
-                    // Other expressions - try to apply them
-                    result = self.visit_expr(func);
+    fn visit_alternative(&mut self, primary: &Expr, alternative: &Expr) -> TokenStream {
+        match self.types.infer_alternative_strategy(primary) {
+            AlternativeStrategy::NullCheck => {
+                let prim = self.visit_expr(primary);
+                let alt = self.visit_default(&self.value_type(primary), alternative);
+                // Borrow the primary so optional input fields are not moved
+                // out of, and only build the alternative when it is needed
+                if matches!(
+                    self.types.infer(alternative),
+                    InferredType::Nullable(_) | InferredType::Null
+                ) {
+                    quote! { #prim.as_ref().cloned().or_else(|| #alt) }
+                } else {
+                    quote! {
+                        #prim.as_ref().cloned().unwrap_or_else(|| ::core::convert::Into::into(#alt))
+                    }
                 }
             }
+            AlternativeStrategy::ValidationFallback => {
+                self.validation_fallback(primary, alternative)
+            }
+            AlternativeStrategy::PrimaryOnly => self.visit_expr(primary),
         }
-        result
     }
 
-    fn visit_alternative(&mut self, primary: &Expr, alternative: &Expr) -> TokenStream {
-        let prim = self.visit_expr(primary);
-        let alt = self.visit_expr(alternative);
+    fn visit_guard(&mut self, condition: &Expr, body: &Expr) -> TokenStream {
+        let cond = self.visit_expr(condition);
+        let bod = block_tail(self.visit_expr(body));
+        let message = format!("guard failed: {}", printer::format(condition));
+        if self.guard_policy == GuardPolicy::Panic {
+            return quote! {
+                if #cond { #bod } else { panic!("{}", #message) }
+            };
+        }
+        // A failed guard is reported like a failed `require`
+        let report = if let Some(rule) = &self.sink_rule {
+            self.uses_require = true;
+            quote! {
+                __elo_failed = true;
+                __elo_sink.push(elo_rust::runtime::sink::Failure::error(#rule, #message));
+            }
+        } else if self.diagnostics_rule.is_some() {
+            self.uses_require = true;
+            let error = self.validation_error(condition, &message, false);
+            quote! { __elo_errors.push(#error); }
+        } else {
+            TokenStream::new()
+        };
+        let fallback = if self.types.infer(body) == InferredType::Boolean {
+            quote! { false }
+        } else {
+            quote! { ::core::default::Default::default() }
+        };
         quote! {
-            #prim.or_else(|| #alt)
+            if #cond { #bod } else { #report #fallback }
         }
     }
 
-    fn visit_guard(&mut self, condition: &Expr, body: &Expr) -> TokenStream {
+    fn visit_null_default(&mut self, value: &Expr, default: &Expr) -> TokenStream {
+        let val = self.visit_expr(value);
+        if let InferredType::Nullable(inner) = self.field_type(value) {
+            // Borrow `Option` fields of the input rather than moving out
+            let def = self.visit_default(&inner, default);
+            return quote! {
+                #val.as_ref().cloned().unwrap_or_else(|| ::core::convert::Into::into(#def))
+            };
+        }
+        if self.is_present(value) {
+            return val;
+        }
+        let def = self.visit_expr(default);
+        // Only evaluate non-constant defaults when they are needed
+        if matches!(default, Expr::Literal(_) | Expr::String(_)) {
+            quote! { #val.unwrap_or(#def) }
+        } else {
+            quote! { #val.unwrap_or_else(|| #def) }
+        }
+    }
+
+    fn visit_range(&mut self, start: &Expr, end: &Expr, inclusive: bool) -> TokenStream {
+        let start = self.visit_expr(start);
+        let end = self.visit_expr(end);
+        if inclusive {
+            quote! { (#start..=#end) }
+        } else {
+            quote! { (#start..#end) }
+        }
+    }
+
+    fn visit_quantity(&mut self, value: f64, _unit: &str) -> TokenStream {
+        // Units are checked and normalised before codegen (see `units`)
+        float_literal(value)
+    }
+
+    fn visit_require(&mut self, condition: &Expr, message: &str) -> TokenStream {
+        self.uses_require = true;
         let cond = self.visit_expr(condition);
-        let bod = self.visit_expr(body);
+        if let Some(rule) = &self.sink_rule {
+            return quote! {
+                {
+                    let passed: bool = #cond;
+                    if !passed {
+                        __elo_failed = true;
+                        __elo_sink.push(elo_rust::runtime::sink::Failure::error(#rule, #message));
+                    }
+                    passed
+                }
+            };
+        }
+        if self.diagnostics_rule.is_none() {
+            return cond;
+        }
+        let error = self.validation_error(condition, message, false);
         quote! {
-            if #cond { #bod } else { panic!("Guard failed") }
+            {
+                let passed: bool = #cond;
+                if !passed {
+                    __elo_errors.push(#error);
+                }
+                passed
+            }
+        }
+    }
+
+    fn visit_warn_if(&mut self, condition: &Expr, message: &str) -> TokenStream {
+        self.uses_warn_if = true;
+        let cond = self.visit_expr(condition);
+        if let Some(rule) = &self.sink_rule {
+            return quote! {
+                {
+                    if #cond {
+                        __elo_sink.push(elo_rust::runtime::sink::Failure::warning(#rule, #message));
+                    }
+                    true
+                }
+            };
+        }
+        if self.diagnostics_rule.is_none() {
+            return quote! { true };
+        }
+        let warning = self.validation_error(condition, message, true);
+        quote! {
+            {
+                if #cond {
+                    __elo_warnings.push(#warning);
+                }
+                true
+            }
         }
     }
 
@@ -388,6 +1398,57 @@ mod tests {
         assert!(tokens_str.contains("hello"));
     }
 
+    #[test]
+    fn test_codegen_guard_fallback() {
+        let mut visitor = CodegenVisitor::new();
+        let expr = crate::parser::Parser::parse("guard qty > 0 in qty < 10").unwrap();
+        assert_eq!(
+            visitor.visit_expr(&expr).to_string(),
+            "if qty > 0i64 { qty < 10i64 } else { false }"
+        );
+        let expr = crate::parser::Parser::parse("guard qty > 0 in 10 / qty").unwrap();
+        assert_eq!(
+            visitor.visit_expr(&expr).to_string(),
+            "if qty > 0i64 { 10i64 / qty } else { :: core :: default :: Default :: default () }"
+        );
+    }
+
+    #[test]
+    fn test_codegen_quantifier() {
+        let mut visitor = CodegenVisitor::new();
+        let expr =
+            crate::parser::Parser::parse("any item in items satisfies item.price > 0").unwrap();
+        let tokens_str = visitor.visit_expr(&expr).to_string();
+        assert_eq!(
+            tokens_str,
+            "items . iter () . any (| item | item . price > 0i64)"
+        );
+    }
+
+    #[test]
+    fn test_codegen_quantifier_copies_numbers() {
+        use crate::codegen::types::{RustType, TypeInfo};
+        let mut order = TypeInfo::new("Order");
+        order.add_field("items", RustType::Array(Box::new(RustType::Integer)));
+        let mut context = TypeContext::new();
+        context.register_type("Order", order);
+        let code = |source: &str| {
+            CodegenVisitor::new()
+                .with_input_fields()
+                .with_type_context(context.clone(), "Order")
+                .visit_expr(&crate::parser::Parser::parse(source).unwrap())
+                .to_string()
+        };
+        assert_eq!(
+            code("any i in items satisfies i > 3"),
+            "input . items . iter () . copied () . any (| i | i > 3i64)"
+        );
+        assert_eq!(
+            code("all(items, fn(i ~> i > 3))"),
+            "input . items . iter () . copied () . all (| i | i > 3i64)"
+        );
+    }
+
     #[test]
     fn test_codegen_null() {
         let mut visitor = CodegenVisitor::new();
@@ -396,4 +1457,187 @@ mod tests {
         let tokens_str = tokens.to_string();
         assert!(tokens_str.contains("None"));
     }
+
+    #[test]
+    fn test_codegen_alternative_strategies() {
+        let alternative = |primary: &str, alt: &str| Expr::Alternative {
+            primary: Box::new(crate::parser::Parser::parse(primary).unwrap()),
+            alternative: Box::new(crate::parser::Parser::parse(alt).unwrap()),
+        };
+        let code = |expr: &Expr| CodegenVisitor::new().visit_expr(expr).to_string();
+
+        let nickname = code(&alternative("nickname", "'anon'"));
+        assert!(
+            nickname.contains(". as_ref () . cloned () . unwrap_or_else"),
+            "{}",
+            nickname
+        );
+        let lookup = code(&alternative("ctx.nickname", "ctx.name"));
+        assert!(lookup.contains("or_else"), "{}", lookup);
+        let fallback = code(&alternative("age >= 18", "guardian == true"));
+        assert!(fallback.contains("||"), "{}", fallback);
+        assert!(!fallback.contains("or_else"), "{}", fallback);
+        assert_eq!(
+            code(&alternative("length(name)", "0")),
+            code(&Expr::FunctionCall {
+                name: "length".to_string(),
+                args: vec![Expr::Identifier("name".to_string())],
+            })
+        );
+
+        let diagnostics = CodegenVisitor::new()
+            .with_diagnostics("adult")
+            .visit_expr(&alternative(
+                "require age >= 18 else 'adult'",
+                "guardian == true",
+            ))
+            .to_string();
+        assert!(
+            diagnostics.contains("truncate (__elo_mark)"),
+            "{}",
+            diagnostics
+        );
+        let sink = CodegenVisitor::new()
+            .with_sink("age")
+            .visit_expr(&alternative(
+                "require age >= 18 else 'adult'",
+                "guardian == true",
+            ))
+            .to_string();
+        assert!(sink.contains("__elo_deferred"), "{}", sink);
+    }
+
+    fn profile_visitor() -> CodegenVisitor {
+        use crate::codegen::types::{RustType, TypeInfo};
+        let mut profile = TypeInfo::new("Profile");
+        profile.add_field("age", RustType::Option(Box::new(RustType::Integer)));
+        profile.add_field("nick", RustType::Option(Box::new(RustType::String)));
+        profile.add_field("name", RustType::String);
+        let mut context = TypeContext::new();
+        context.register_type("Profile", profile);
+        CodegenVisitor::new()
+            .with_input_fields()
+            .with_type_context(context, "Profile")
+    }
+
+    #[test]
+    fn test_codegen_guards_optional_fields() {
+        let code = |source: &str| {
+            let expr = crate::parser::Parser::parse(source).unwrap();
+            profile_visitor().visit_expr(&expr).to_string()
+        };
+        // `Copy` values are matched by value, others borrowed
+        assert_eq!(
+            code("age >= 18"),
+            "match input . age { Some (__elo_some0) => __elo_some0 >= 18i64 , None => false , }"
+        );
+        assert_eq!(
+            code("nick != 'bob'"),
+            "match & input . nick { Some (__elo_some0) => (* __elo_some0) != \"bob\" , None => true , }"
+        );
+        assert_eq!(
+            code("age + 1 < length(nick)"),
+            "match (input . age , & input . nick) { (Some (__elo_some0) , Some (__elo_some1)) => __elo_some0 + 1i64 < ((* __elo_some1) . chars () . count () as i64) , _ => false , }"
+        );
+        // Non-optional fields and null-aware forms are not guarded
+        assert_eq!(code("name == 'bob'"), "input . name == \"bob\"");
+        assert_eq!(code("is_null(nick)"), "input . nick . is_none ()");
+    }
+
+    #[test]
+    fn test_codegen_null_comparisons() {
+        let code = |source: &str| {
+            let expr = crate::parser::Parser::parse(source).unwrap();
+            profile_visitor().visit_expr(&expr).to_string()
+        };
+        assert_eq!(code("nick == null"), "(input . nick) . is_none ()");
+        assert_eq!(code("null != age"), "(input . age) . is_some ()");
+        // A field that is not an `Option` is never null
+        assert_eq!(code("name == null"), "false");
+        assert_eq!(code("name != null"), "true");
+        assert_eq!(code("is_null(name) || !is_some(name)"), "false || ! true");
+    }
+
+    #[test]
+    fn test_codegen_defaults_of_optional_fields() {
+        let code = |source: &str| {
+            let expr = crate::parser::Parser::parse(source).unwrap();
+            profile_visitor().visit_expr(&expr).to_string()
+        };
+        let unwrapped = "input . nick . as_ref () . cloned () . unwrap_or_else (|| :: core :: convert :: Into :: into (\"anon\"))";
+        assert_eq!(code("nick ?? 'anon'"), unwrapped);
+        assert_eq!(code("nick ?| 'anon'"), unwrapped);
+        // A field that is not an `Option` has no default to fall back to
+        assert_eq!(code("name ?| 'anon'"), "input . name");
+        assert_eq!(code("name ?? 'anon'"), "input . name");
+    }
+
+    #[test]
+    fn test_codegen_unary_plus() {
+        let code = |source: &str| {
+            let expr = crate::parser::Parser::parse(source).unwrap();
+            CodegenVisitor::new().visit_expr(&expr).to_string()
+        };
+        assert_eq!(code("+5"), "5i64");
+        assert_eq!(code("-5"), "- 5i64");
+        assert_eq!(code("+x > 0"), "x > 0i64");
+    }
+
+    #[test]
+    fn test_codegen_power() {
+        let code = |source: &str| {
+            let expr = crate::parser::Parser::parse(source).unwrap();
+            CodegenVisitor::new().visit_expr(&expr).to_string()
+        };
+        assert_eq!(
+            code("x ^ 2"),
+            "elo_rust :: runtime :: value :: strict_power (x , 2i64)"
+        );
+        assert_eq!(
+            code("x ^ 0.5"),
+            "elo_rust :: runtime :: value :: strict_float_power ((x) as f64 , 0.5f64)"
+        );
+        assert_eq!(
+            code("2.5 ^ n"),
+            "elo_rust :: runtime :: value :: strict_float_power (2.5f64 , (n) as f64)"
+        );
+        assert_eq!(
+            code("x ^ -1"),
+            "elo_rust :: runtime :: value :: strict_power (x , - 1i64)"
+        );
+
+        let mut sample = crate::codegen::types::TypeInfo::new("Sample");
+        sample.add_field("weight", crate::codegen::types::RustType::Float);
+        let mut context = TypeContext::new();
+        context.register_type("Sample", sample);
+        let expr = crate::parser::Parser::parse("weight ^ 2").unwrap();
+        let typed = CodegenVisitor::new()
+            .with_input_fields()
+            .with_type_context(context, "Sample")
+            .visit_expr(&expr)
+            .to_string();
+        assert_eq!(
+            typed,
+            "elo_rust :: runtime :: value :: strict_float_power (input . weight , (2i64) as f64)"
+        );
+    }
+
+    #[test]
+    fn test_codegen_index() {
+        let code = |source: &str| {
+            CodegenVisitor::new()
+                .visit_expr(&crate::parser::Parser::parse(source).unwrap())
+                .to_string()
+        };
+        let element = code("items[0]");
+        assert!(element.contains("usize :: try_from (0i64)"), "{}", element);
+        assert!(
+            element.contains(". get (__elo_i) . cloned ()"),
+            "{}",
+            element
+        );
+        assert!(crate::codegen::audit::audit_panics(&element.parse().unwrap()).is_empty());
+        let nested = code("matrix[i][j]");
+        assert!(nested.contains("and_then (| __elo_row |"), "{}", nested);
+    }
 }
@@ -0,0 +1,239 @@
+//! Panic audit of generated code
+//!
+//! Scans emitted token streams for constructs that can panic at runtime:
+//! panicking macros (`panic!`, `unreachable!`, `todo!`, `unimplemented!`),
+//! `.unwrap()` / `.expect(..)` calls, unguarded indexing (`xs[i]`) and the
+//! runtime's strict arithmetic (`strict_divide(..)`, ...).
+//! With [`PanicPolicy::Deny`], [`RustCodeGenerator`](super::RustCodeGenerator)
+//! refuses to emit validators containing any of them, which backs the
+//! guarantee that generated validators report failures instead of panicking.
+//!
+//! The audit is syntactic: it flags every occurrence, including ones that
+//! cannot fail for the values the generator emits.
+//!
+//! # Example
+//!
+//! ```
+//! use elo_rust::codegen::audit::{audit_panics, PanicKind};
+//! use quote::quote;
+//!
+//! let sites = audit_panics(&quote! { if ok { true } else { panic!("failed") } });
+//! assert_eq!(sites[0].kind, PanicKind::Macro("panic".to_string()));
+//! assert!(audit_panics(&quote! { xs.get(0).is_some() }).is_empty());
+//! ```
+
+use proc_macro2::{Delimiter, TokenStream, TokenTree};
+use std::fmt;
+
+/// Macros that panic when reached
+const PANIC_MACROS: &[&str] = &["panic", "unreachable", "todo", "unimplemented"];
+
+/// Methods that panic on `None` / `Err`
+const PANIC_METHODS: &[&str] = &["unwrap", "expect", "unwrap_err", "expect_err"];
+
+/// Runtime functions generated code calls that panic on bad operands, such
+/// as a zero divisor
+const PANIC_FUNCTIONS: &[&str] = &[
+    "strict_divide",
+    "strict_remainder",
+    "strict_power",
+    "strict_float_power",
+];
+
+/// Keywords that may precede an array expression rather than an indexed value
+const NON_INDEX_KEYWORDS: &[&str] = &[
+    "in", "return", "break", "else", "match", "if", "while", "let", "mut", "move", "yield",
+];
+
+/// How the generator treats generated code that may panic
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PanicPolicy {
+    /// Do not audit generated code
+    #[default]
+    Allow,
+    /// Print a warning for every panicking construct
+    Warn,
+    /// Fail generation if any panicking construct is found
+    Deny,
+}
+
+/// Kind of panicking construct
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PanicKind {
+    /// A panicking macro such as `panic!`
+    Macro(String),
+    /// A panicking method such as `.unwrap()`
+    Method(String),
+    /// Indexing with `[]`, which panics when out of bounds
+    Index,
+    /// A call of a panicking runtime function such as `strict_divide`
+    Function(String),
+}
+
+/// A construct in generated code that can panic
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PanicSite {
+    /// What was found
+    pub kind: PanicKind,
+    /// The offending code, as emitted
+    pub snippet: String,
+}
+
+impl fmt::Display for PanicSite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            PanicKind::Macro(name) => write!(f, "`{}!` in `{}`", name, self.snippet),
+            PanicKind::Method(name) => write!(f, "`.{}()` in `{}`", name, self.snippet),
+            PanicKind::Index => write!(f, "unguarded indexing in `{}`", self.snippet),
+            PanicKind::Function(name) => write!(f, "`{}()` in `{}`", name, self.snippet),
+        }
+    }
+}
+
+/// Find every construct in `tokens` that can panic
+pub fn audit_panics(tokens: &TokenStream) -> Vec<PanicSite> {
+    let mut sites = Vec::new();
+    audit_stream(tokens.clone(), &mut sites);
+    sites
+}
+
+/// Apply a panic policy to generated code
+///
+/// Returns an error listing the panicking constructs under
+/// [`PanicPolicy::Deny`]; prints them under [`PanicPolicy::Warn`].
+pub fn enforce(policy: PanicPolicy, tokens: &TokenStream) -> Result<(), String> {
+    if policy == PanicPolicy::Allow {
+        return Ok(());
+    }
+    let sites = audit_panics(tokens);
+    if sites.is_empty() {
+        return Ok(());
+    }
+    let list: Vec<String> = sites.iter().map(|site| site.to_string()).collect();
+    match policy {
+        PanicPolicy::Deny => Err(format!("Generated code may panic: {}", list.join("; "))),
+        _ => {
+            for site in list {
+                eprintln!("⚠️  Warning: generated code may panic: {}", site);
+            }
+            Ok(())
+        }
+    }
+}
+
+fn audit_stream(tokens: TokenStream, sites: &mut Vec<PanicSite>) {
+    let trees: Vec<TokenTree> = tokens.into_iter().collect();
+    for (i, tree) in trees.iter().enumerate() {
+        let next = trees.get(i + 1);
+        match tree {
+            TokenTree::Ident(ident) => {
+                let name = ident.to_string();
+                let is_macro = matches!(next, Some(TokenTree::Punct(p)) if p.as_char() == '!');
+                let after_dot =
+                    i > 0 && matches!(&trees[i - 1], TokenTree::Punct(p) if p.as_char() == '.');
+                let is_call = matches!(
+                    next,
+                    Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Parenthesis
+                );
+                if is_macro && PANIC_MACROS.contains(&name.as_str()) {
+                    sites.push(PanicSite {
+                        kind: PanicKind::Macro(name),
+                        snippet: snippet(&trees[i..(i + 3).min(trees.len())]),
+                    });
+                } else if after_dot && is_call && PANIC_METHODS.contains(&name.as_str()) {
+                    sites.push(PanicSite {
+                        kind: PanicKind::Method(name),
+                        snippet: snippet(&trees[i.saturating_sub(2)..i + 2]),
+                    });
+                } else if is_call && PANIC_FUNCTIONS.contains(&name.as_str()) {
+                    sites.push(PanicSite {
+                        kind: PanicKind::Function(name),
+                        snippet: snippet(&trees[i..i + 2]),
+                    });
+                }
+            }
+            TokenTree::Group(group) => {
+                if group.delimiter() == Delimiter::Bracket && i > 0 && is_indexable(&trees[i - 1]) {
+                    sites.push(PanicSite {
+                        kind: PanicKind::Index,
+                        snippet: snippet(&trees[i - 1..=i]),
+                    });
+                }
+                audit_stream(group.stream(), sites);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Check if a `[...]` group after `prev` indexes a value
+///
+/// Array expressions follow operators, keywords or `!` (`vec![..]`), and
+/// attributes follow `#`; indexing follows a value.
+fn is_indexable(prev: &TokenTree) -> bool {
+    match prev {
+        TokenTree::Ident(ident) => !NON_INDEX_KEYWORDS.contains(&ident.to_string().as_str()),
+        TokenTree::Group(group) => group.delimiter() != Delimiter::Brace,
+        TokenTree::Literal(_) => true,
+        TokenTree::Punct(_) => false,
+    }
+}
+
+fn snippet(trees: &[TokenTree]) -> String {
+    trees.iter().cloned().collect::<TokenStream>().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::quote;
+
+    fn kinds(tokens: TokenStream) -> Vec<PanicKind> {
+        audit_panics(&tokens).into_iter().map(|s| s.kind).collect()
+    }
+
+    #[test]
+    fn test_finds_panicking_constructs() {
+        assert_eq!(
+            kinds(quote! { { let d = parse(s).expect("bad"); unreachable!() } }),
+            vec![
+                PanicKind::Method("expect".to_string()),
+                PanicKind::Macro("unreachable".to_string())
+            ]
+        );
+        assert_eq!(
+            kinds(quote! { input.items[0] > (a)[1] }),
+            vec![PanicKind::Index, PanicKind::Index]
+        );
+        assert_eq!(
+            kinds(quote! { elo_rust::runtime::value::strict_divide(a, 0i64) }),
+            vec![PanicKind::Function("strict_divide".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_ignores_safe_constructs() {
+        assert!(kinds(quote! {
+            #[inline]
+            fn f(x: Option<i64>) -> bool {
+                let v = vec![1, 2];
+                let a = [1, 2];
+                for i in [1, 2] { }
+                x.unwrap_or(0) > 0 && v.first().is_some() && unwrap(a)
+            }
+        })
+        .is_empty());
+    }
+
+    #[test]
+    fn test_enforce() {
+        let tokens = quote! { value.unwrap() };
+        assert!(enforce(PanicPolicy::Allow, &tokens).is_ok());
+        assert!(enforce(PanicPolicy::Warn, &tokens).is_ok());
+        let err = enforce(PanicPolicy::Deny, &tokens).unwrap_err();
+        assert_eq!(
+            err,
+            "Generated code may panic: `.unwrap()` in `value . unwrap ()`"
+        );
+    }
+}
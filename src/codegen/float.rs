@@ -0,0 +1,65 @@
+//! Deterministic float formatting
+//!
+//! Every float that ends up in generated code, SQL or a message is written
+//! with the shortest representation that parses back to the same value
+//! (via [`ryu`]), so `3.15` stays `3.15` and output does not churn between
+//! runs or platforms.
+
+use proc_macro2::{Literal, TokenStream};
+use quote::quote;
+
+/// Shortest round-trip text of a float: `3.15`, `3.0`, `1e21`
+///
+/// Non-finite values are written `NaN`, `inf` and `-inf`.
+pub fn format_float(value: f64) -> String {
+    ryu::Buffer::new().format(value).to_string()
+}
+
+/// A Rust expression for a float: a suffixed literal such as `3.15f64`, or
+/// the matching `f64` constant for non-finite values
+pub fn float_literal(value: f64) -> TokenStream {
+    if value.is_nan() {
+        return quote! { f64::NAN };
+    }
+    if value.is_infinite() {
+        return if value > 0.0 {
+            quote! { f64::INFINITY }
+        } else {
+            quote! { f64::NEG_INFINITY }
+        };
+    }
+    let text = format_float(value);
+    let digits = text.strip_suffix(".0").unwrap_or(&text);
+    match format!("{}f64", digits).parse::<Literal>() {
+        Ok(literal) => quote! { #literal },
+        // Unreachable for ryu output; keep proc-macro2's own rendering
+        Err(_) => quote! { #value },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_float() {
+        assert_eq!(format_float(3.15), "3.15");
+        assert_eq!(format_float(0.1 + 0.2), "0.30000000000000004");
+        assert_eq!(format_float(3.0), "3.0");
+        assert_eq!(format_float(-0.5), "-0.5");
+        assert_eq!(format_float(1e21), "1e21");
+        assert_eq!(format_float(f64::NAN), "NaN");
+    }
+
+    #[test]
+    fn test_float_literal() {
+        assert_eq!(float_literal(3.15).to_string(), "3.15f64");
+        assert_eq!(float_literal(10.0).to_string(), "10f64");
+        assert_eq!(float_literal(-2.5).to_string(), "- 2.5f64");
+        assert_eq!(float_literal(1e-7).to_string(), "1e-7f64");
+        assert_eq!(
+            float_literal(f64::NEG_INFINITY).to_string(),
+            "f64 :: NEG_INFINITY"
+        );
+    }
+}
@@ -1,5 +1,8 @@
 //! Standard library function call code generation
 
+use super::options::TemporalBackend;
+use crate::ast::Expr;
+use crate::parser::operators::OperatorTable;
 use proc_macro2::TokenStream;
 use quote::quote;
 
@@ -10,15 +13,82 @@ const REGEX_TIMEOUT_MS: u64 = 1000;
 
 /// Generates code for function calls
 #[derive(Debug)]
-pub struct FunctionGenerator;
+pub struct FunctionGenerator {
+    temporal_backend: TemporalBackend,
+}
+
+/// Check the literal patterns of the `matches` calls in `expr`
+///
+/// Each is run through
+/// [`validate_regex_pattern`](crate::security::validate_regex_pattern), so
+/// invalid or ReDoS-prone patterns are rejected before code is generated.
+/// Patterns computed at runtime are checked when they are used.
+pub fn check_regex_patterns(expr: &Expr) -> Result<(), String> {
+    if let Expr::FunctionCall { name, args } = expr {
+        if let (Some(Expr::String(pattern)), "matches") = (args.last(), name.as_str()) {
+            crate::security::validate_regex_pattern(pattern)
+                .map_err(|e| format!("matches('{}'): {}", pattern, e))?;
+        }
+    }
+    expr.children()
+        .into_iter()
+        .try_for_each(check_regex_patterns)
+}
+
+/// Check that the calls in `expr` are to standard library functions or
+/// custom operators, with as many arguments as one of their signatures takes
+///
+/// Pipes are checked once desugared, as their stages take the piped value.
+pub fn check_function_calls(expr: &Expr, operators: &OperatorTable) -> Result<(), String> {
+    match expr {
+        Expr::Pipe { value, functions } => {
+            // Invalid pipes are reported by `Expr::check_pipes`
+            return match Expr::desugar_pipe(value, functions) {
+                Ok(desugared) => check_function_calls(&desugared, operators),
+                Err(_) => Ok(()),
+            };
+        }
+        Expr::FunctionCall { name, args } if operators.by_function(name).is_none() => {
+            let mut signatures = crate::stdlib::lookup(name).peekable();
+            if signatures.peek().is_none() {
+                return Err(format!("unknown function {}()", name));
+            }
+            if !signatures.any(|f| f.accepts_arity(args.len())) {
+                return Err(format!(
+                    "{}() does not take {} argument{}",
+                    name,
+                    args.len(),
+                    if args.len() == 1 { "" } else { "s" }
+                ));
+            }
+        }
+        _ => {}
+    }
+    expr.children()
+        .into_iter()
+        .try_for_each(|child| check_function_calls(child, operators))
+}
 
 impl FunctionGenerator {
     /// Create a new function generator
     pub fn new() -> Self {
-        Self
+        Self {
+            temporal_backend: TemporalBackend::Chrono,
+        }
+    }
+
+    /// Generate date/time functions for the given crate
+    pub fn with_temporal_backend(mut self, backend: TemporalBackend) -> Self {
+        self.temporal_backend = backend;
+        self
     }
 
     /// Generate code for a function call
+    ///
+    /// A function without generated code, or a call with arguments it cannot
+    /// use, becomes a `compile_error!` naming the function, so the mistake
+    /// is reported where the code is compiled rather than left as an empty
+    /// expression. [`check_function_calls`] reports both before generation.
     pub fn call(&self, name: &str, args: Vec<TokenStream>) -> TokenStream {
         match name {
             // String functions
@@ -27,8 +97,21 @@ impl FunctionGenerator {
             // DateTime functions
             "today" | "now" | "age" | "days_since" | "date" => self.datetime_function(name, args),
             // Array functions
-            "any" | "all" => self.array_function(name, args),
-            _ => quote!(),
+            "any" | "all" | "map" | "filter" | "reduce" | "sum" | "min_by" | "max_by" => {
+                self.array_function(name, args)
+            }
+            // Null checks
+            "is_null" | "is_some" => self.array_function(name, args),
+            // Numeric functions
+            "abs" => self.numeric_function(name, args),
+            // Validation functions
+            "is_phone" | "normalize_phone" | "is_iso_country" | "is_iso_currency"
+            | "is_bcp47_lang" | "password_strength" | "is_card_number" | "card_brand"
+            | "is_email" | "is_url" | "is_uuid" | "is_ipv4" | "is_ipv6" | "has_extension"
+            | "is_mime" | "size_kb" | "size_mb" | "size_gb" | "is_present" => {
+                self.validation_function(name, args)
+            }
+            _ => unknown_function(name),
         }
     }
 
@@ -37,41 +120,32 @@ impl FunctionGenerator {
         match name {
             "matches" => {
                 if args.len() < 2 {
-                    return quote!();
+                    return invalid_call(name);
                 }
                 let subject = &args[0];
                 let pattern = &args[1];
+                if syn::parse2::<syn::LitStr>(pattern.clone()).is_err() {
+                    return quote!(elo_rust::runtime::pattern::is_match(&#subject, &#pattern));
+                }
+                // Literal patterns are checked by `check_regex_patterns` when
+                // the code is generated, and compiled once on first use
                 quote! {
                     {
-                        use regex::Regex;
-                        // Validate regex pattern and compile with timeout guard
-                        match Regex::new(#pattern) {
-                            Ok(re) => {
-                                // Rust's regex crate provides built-in ReDoS protection
-                                // by using a different matching algorithm for certain patterns
-                                match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                                    re.is_match(#subject)
-                                })) {
-                                    Ok(result) => result,
-                                    Err(_) => {
-                                        eprintln!(
-                                            "⚠️  Regex matching failed: pattern may cause performance issues"
-                                        );
-                                        false
-                                    }
-                                }
-                            }
-                            Err(_) => {
-                                eprintln!("⚠️  Invalid regex pattern: {}", #pattern);
-                                false
-                            }
+                        static __ELO_REGEX: ::std::sync::OnceLock<
+                            Option<elo_rust::runtime::pattern::Regex>,
+                        > = ::std::sync::OnceLock::new();
+                        match __ELO_REGEX
+                            .get_or_init(|| elo_rust::runtime::pattern::Regex::new(#pattern).ok())
+                        {
+                            Some(regex) => regex.is_match(&#subject),
+                            None => false,
                         }
                     }
                 }
             }
             "contains" => {
                 if args.len() < 2 {
-                    return quote!();
+                    return invalid_call(name);
                 }
                 let subject = &args[0];
                 let substring = &args[1];
@@ -79,35 +153,35 @@ impl FunctionGenerator {
             }
             "length" => {
                 if args.is_empty() {
-                    return quote!();
+                    return invalid_call(name);
                 }
                 let subject = &args[0];
-                quote!(#subject.len())
+                quote!((#subject.chars().count() as i64))
             }
             "uppercase" => {
                 if args.is_empty() {
-                    return quote!();
+                    return invalid_call(name);
                 }
                 let subject = &args[0];
                 quote!(#subject.to_uppercase())
             }
             "lowercase" => {
                 if args.is_empty() {
-                    return quote!();
+                    return invalid_call(name);
                 }
                 let subject = &args[0];
                 quote!(#subject.to_lowercase())
             }
             "trim" => {
                 if args.is_empty() {
-                    return quote!();
+                    return invalid_call(name);
                 }
                 let subject = &args[0];
                 quote!(#subject.trim())
             }
             "starts_with" => {
                 if args.len() < 2 {
-                    return quote!();
+                    return invalid_call(name);
                 }
                 let subject = &args[0];
                 let prefix = &args[1];
@@ -115,18 +189,21 @@ impl FunctionGenerator {
             }
             "ends_with" => {
                 if args.len() < 2 {
-                    return quote!();
+                    return invalid_call(name);
                 }
                 let subject = &args[0];
                 let suffix = &args[1];
                 quote!(#subject.ends_with(#suffix))
             }
-            _ => quote!(),
+            _ => unknown_function(name),
         }
     }
 
     /// Generate code for a date/time function
     pub fn datetime_function(&self, name: &str, args: Vec<TokenStream>) -> TokenStream {
+        if self.temporal_backend == TemporalBackend::Time {
+            return super::temporal::time_function(name, &args);
+        }
         match name {
             "today" => {
                 quote! {
@@ -146,7 +223,7 @@ impl FunctionGenerator {
             }
             "age" => {
                 if args.is_empty() {
-                    return quote!();
+                    return invalid_call(name);
                 }
                 let birth_date = &args[0];
                 quote! {
@@ -163,7 +240,7 @@ impl FunctionGenerator {
             }
             "days_since" => {
                 if args.is_empty() {
-                    return quote!();
+                    return invalid_call(name);
                 }
                 let date = &args[0];
                 quote! {
@@ -175,7 +252,7 @@ impl FunctionGenerator {
             }
             "date" => {
                 if args.is_empty() {
-                    return quote!();
+                    return invalid_call(name);
                 }
                 let date_str = &args[0];
                 quote! {
@@ -186,24 +263,195 @@ impl FunctionGenerator {
                     }
                 }
             }
-            _ => quote!(),
+            _ => unknown_function(name),
+        }
+    }
+
+    /// Generate code for a format validation function
+    ///
+    /// These call into `elo_rust::runtime` helpers rather than inlining a
+    /// regex, which keeps generated validators free of ReDoS-prone patterns.
+    pub fn validation_function(&self, name: &str, args: Vec<TokenStream>) -> TokenStream {
+        match name {
+            "is_phone" => {
+                if args.is_empty() {
+                    return invalid_call(name);
+                }
+                let subject = &args[0];
+                quote!(elo_rust::runtime::phone::is_phone(&#subject))
+            }
+            "normalize_phone" => {
+                if args.len() < 2 {
+                    return invalid_call(name);
+                }
+                let subject = &args[0];
+                let region = &args[1];
+                quote!(elo_rust::runtime::phone::normalize_phone(&#subject, &#region))
+            }
+            "is_iso_country" | "is_iso_currency" | "is_bcp47_lang" => {
+                if args.is_empty() {
+                    return invalid_call(name);
+                }
+                let subject = &args[0];
+                let func = syn::Ident::new(name, proc_macro2::Span::call_site());
+                quote!(elo_rust::runtime::codes::#func(&#subject))
+            }
+            "password_strength" => {
+                if args.is_empty() {
+                    return invalid_call(name);
+                }
+                let subject = &args[0];
+                quote!(elo_rust::runtime::password::password_strength(&#subject))
+            }
+            "is_card_number" | "card_brand" => {
+                if args.is_empty() {
+                    return invalid_call(name);
+                }
+                let subject = &args[0];
+                let func = syn::Ident::new(name, proc_macro2::Span::call_site());
+                quote!(elo_rust::runtime::card::#func(&#subject))
+            }
+            "is_email" | "is_url" | "is_uuid" | "is_ipv4" | "is_ipv6" => {
+                if args.is_empty() {
+                    return invalid_call(name);
+                }
+                let subject = &args[0];
+                let func = syn::Ident::new(name, proc_macro2::Span::call_site());
+                quote!(elo_rust::runtime::format::#func(&#subject))
+            }
+            "has_extension" | "is_mime" => {
+                if args.len() < 2 {
+                    return invalid_call(name);
+                }
+                let subject = &args[0];
+                let allowed = &args[1];
+                let func = syn::Ident::new(name, proc_macro2::Span::call_site());
+                quote!(elo_rust::runtime::file::#func(&#subject, &#allowed))
+            }
+            "is_present" => {
+                if args.is_empty() {
+                    return invalid_call(name);
+                }
+                let subject = &args[0];
+                quote!(elo_rust::runtime::presence::is_present(&#subject))
+            }
+            "size_kb" | "size_mb" | "size_gb" => {
+                if args.is_empty() {
+                    return invalid_call(name);
+                }
+                let bytes = &args[0];
+                let func = syn::Ident::new(name, proc_macro2::Span::call_site());
+                quote!(elo_rust::runtime::file::#func((#bytes) as f64))
+            }
+            _ => unknown_function(name),
+        }
+    }
+
+    /// Generate code for a numeric function
+    pub fn numeric_function(&self, name: &str, args: Vec<TokenStream>) -> TokenStream {
+        match name {
+            "abs" => {
+                if args.is_empty() {
+                    return invalid_call(name);
+                }
+                let value = &args[0];
+                quote!((#value).abs())
+            }
+            _ => unknown_function(name),
         }
     }
 
     /// Generate code for a collection function
+    ///
+    /// Lambda arguments arrive as generated `|param| { body }` closures.
+    /// `map`, `filter`, `reduce`, `sum`, `min_by` and `max_by` pass them
+    /// owned elements: ranges are iterated directly and arrays element by
+    /// element, cloned. `reduce` takes the curried closure of a two
+    /// parameter lambda, `|acc| { |item| { body } }`.
     pub fn array_function(&self, name: &str, args: Vec<TokenStream>) -> TokenStream {
         match name {
+            "map" => {
+                if args.len() < 2 {
+                    return invalid_call(name);
+                }
+                let items = elements(&args[0]);
+                let function = &args[1];
+                quote!(#items.map(#function).collect::<Vec<_>>())
+            }
+            "filter" => {
+                let Some((param, predicate)) = args.get(1).and_then(closure_parts) else {
+                    return invalid_call(name);
+                };
+                let items = elements(&args[0]);
+                quote! {
+                    #items
+                        .filter_map(|#param| {
+                            let __elo_keep: bool = #predicate;
+                            __elo_keep.then_some(#param)
+                        })
+                        .collect::<Vec<_>>()
+                }
+            }
+            "reduce" => {
+                let Some((acc, function)) = args.get(2).and_then(closure_parts) else {
+                    return invalid_call(name);
+                };
+                let Some((item, body)) = closure_parts(&quote!(#function)) else {
+                    return invalid_call(name);
+                };
+                let items = elements(&args[0]);
+                let init = &args[1];
+                quote!(#items.fold(#init, |#acc, #item| #body))
+            }
+            "sum" => {
+                if args.is_empty() {
+                    return invalid_call(name);
+                }
+                let items = elements(&args[0]);
+                let values = match args.get(1) {
+                    Some(function) => quote!(#items.map(#function)),
+                    None => items,
+                };
+                quote! {
+                    #values
+                        .reduce(|__elo_total, __elo_value| __elo_total + __elo_value)
+                        .unwrap_or_default()
+                }
+            }
+            "min_by" | "max_by" => {
+                let Some((param, key)) = args.get(1).and_then(closure_parts) else {
+                    return invalid_call(name);
+                };
+                let items = elements(&args[0]);
+                let select = syn::Ident::new(name, proc_macro2::Span::call_site());
+                // Keys are compared with `partial_cmp` so float keys work;
+                // the element with the first smallest or last largest key wins
+                quote! {
+                    #items
+                        .map(|#param| (::core::clone::Clone::clone(&(#key)), #param))
+                        .#select(|(__elo_a, _), (__elo_b, _)| {
+                            ::core::cmp::PartialOrd::partial_cmp(__elo_a, __elo_b)
+                                .unwrap_or(::core::cmp::Ordering::Equal)
+                        })
+                        .map(|(_, __elo_item)| __elo_item)
+                }
+            }
             "contains" => {
                 if args.len() < 2 {
-                    return quote!();
+                    return invalid_call(name);
                 }
                 let array = &args[0];
                 let value = &args[1];
-                quote!(#array.contains(&#value))
+                // Converted to the element type, so a `Vec<String>` takes `&str`
+                quote! {
+                    #array.contains(&::core::convert::Into::into(
+                        ::core::clone::Clone::clone(&#value),
+                    ))
+                }
             }
             "any" => {
                 if args.len() < 2 {
-                    return quote!();
+                    return invalid_call(name);
                 }
                 let array = &args[0];
                 let predicate = &args[1];
@@ -211,7 +459,7 @@ impl FunctionGenerator {
             }
             "all" => {
                 if args.len() < 2 {
-                    return quote!();
+                    return invalid_call(name);
                 }
                 let array = &args[0];
                 let predicate = &args[1];
@@ -219,14 +467,14 @@ impl FunctionGenerator {
             }
             "length" => {
                 if args.is_empty() {
-                    return quote!();
+                    return invalid_call(name);
                 }
                 let array = &args[0];
-                quote!(#array.len())
+                quote!((#array.len() as i64))
             }
             "is_empty" => {
                 if args.is_empty() {
-                    return quote!();
+                    return invalid_call(name);
                 }
                 let array = &args[0];
                 quote!(#array.is_empty())
@@ -234,23 +482,65 @@ impl FunctionGenerator {
             // Type checking functions
             "is_null" => {
                 if args.is_empty() {
-                    return quote!();
+                    return invalid_call(name);
                 }
                 let value = &args[0];
                 quote!(#value.is_none())
             }
             "is_some" => {
                 if args.is_empty() {
-                    return quote!();
+                    return invalid_call(name);
                 }
                 let value = &args[0];
                 quote!(#value.is_some())
             }
-            _ => quote!(),
+            _ => unknown_function(name),
         }
     }
 }
 
+/// A call the generator has no code for, reported when the code is compiled
+fn unknown_function(name: &str) -> TokenStream {
+    let message = format!("unknown function {}()", name);
+    quote!(::core::compile_error!(#message))
+}
+
+/// A call with arguments the generator cannot use, reported when the code
+/// is compiled
+fn invalid_call(name: &str) -> TokenStream {
+    let message = format!("invalid arguments to {}()", name);
+    quote!(::core::compile_error!(#message))
+}
+
+/// Owned elements of a collection: ranges are iterators already, arrays
+/// are cloned element by element
+fn elements(collection: &TokenStream) -> TokenStream {
+    match syn::parse2::<syn::Expr>(collection.clone()) {
+        Ok(syn::Expr::Paren(paren)) if matches!(*paren.expr, syn::Expr::Range(_)) => {
+            collection.clone()
+        }
+        _ => quote!(#collection.iter().cloned()),
+    }
+}
+
+/// Parameter and body of a generated one-parameter closure, unwrapping a
+/// body block that holds a single expression
+fn closure_parts(closure: &TokenStream) -> Option<(syn::Pat, syn::Expr)> {
+    let closure = syn::parse2::<syn::ExprClosure>(closure.clone()).ok()?;
+    let mut inputs = closure.inputs.into_iter();
+    let (Some(param), None) = (inputs.next(), inputs.next()) else {
+        return None;
+    };
+    let body = match *closure.body {
+        syn::Expr::Block(block) => match block.block.stmts.as_slice() {
+            [syn::Stmt::Expr(expr, None)] => expr.clone(),
+            _ => syn::Expr::Block(block),
+        },
+        body => body,
+    };
+    Some((param, body))
+}
+
 impl Default for FunctionGenerator {
     fn default() -> Self {
         Self::new()
@@ -265,4 +555,223 @@ mod tests {
     fn test_function_generator_creation() {
         let _gen = FunctionGenerator::new();
     }
+
+    #[test]
+    fn test_matches_function() {
+        let gen = FunctionGenerator::new();
+        let code = gen
+            .call("matches", vec![quote!(input.code), quote!("^[A-Z]{3}$")])
+            .to_string();
+        assert!(code.contains("static __ELO_REGEX"));
+        assert!(code.contains("Regex :: new (\"^[A-Z]{3}$\") . ok ()"));
+        assert!(code.contains("Some (regex) => regex . is_match (& input . code)"));
+
+        let code = gen
+            .call("matches", vec![quote!(input.code), quote!(ctx.pattern)])
+            .to_string();
+        assert_eq!(
+            code,
+            "elo_rust :: runtime :: pattern :: is_match (& input . code , & ctx . pattern)"
+        );
+    }
+
+    #[test]
+    fn test_check_regex_patterns() {
+        let check = |source| check_regex_patterns(&crate::parser::Parser::parse(source).unwrap());
+        assert!(check("matches(code, '^[A-Z]{3}$') && matches(name, ctx.pattern)").is_ok());
+        assert!(check("code |> matches('^[a-z]+$')").is_ok());
+
+        let error = check("age > 18 && matches(code, '(a+)+')").unwrap_err();
+        assert!(error.starts_with("matches('(a+)+'): "), "{}", error);
+        assert!(error.contains("nested quantifiers"));
+        assert!(check("code |> matches('[')").is_err());
+    }
+
+    #[test]
+    fn test_check_function_calls() {
+        let check = |source| {
+            check_function_calls(
+                &crate::parser::Parser::parse(source).unwrap(),
+                &OperatorTable::new(),
+            )
+        };
+        assert!(check("sum(items) > 0 && clamp(age, 1, 9) > 3").is_ok());
+        assert!(check("name |> trim |> starts_with('a')").is_ok());
+        assert!(check("requires_with(a, b, c)").is_ok());
+
+        assert_eq!(
+            check("sum() > 0").unwrap_err(),
+            "sum() does not take 0 arguments"
+        );
+        assert_eq!(
+            check("is_email(a, b)").unwrap_err(),
+            "is_email() does not take 2 arguments"
+        );
+        assert_eq!(
+            check("age > 1 && frobnicate(age)").unwrap_err(),
+            "unknown function frobnicate()"
+        );
+        assert_eq!(
+            check("name |> shout").unwrap_err(),
+            "unknown function shout()"
+        );
+    }
+
+    #[test]
+    fn test_collection_functions() {
+        let gen = FunctionGenerator::new();
+        let code = gen
+            .call("map", vec![quote!(input.prices), quote!(|p| { p * 2f64 })])
+            .to_string();
+        assert_eq!(
+            code,
+            "input . prices . iter () . cloned () . map (| p | { p * 2f64 }) . collect :: < Vec < _ >> ()"
+        );
+
+        let code = gen
+            .call(
+                "filter",
+                vec![quote!((1i64..10i64)), quote!(|i| { i % 2i64 == 0i64 })],
+            )
+            .to_string();
+        assert!(
+            code.starts_with("(1i64 .. 10i64) . filter_map (| i |"),
+            "{}",
+            code
+        );
+        assert!(code.contains("let __elo_keep : bool = i % 2i64 == 0i64 ;"));
+
+        let code = gen
+            .call(
+                "reduce",
+                vec![quote!(xs), quote!(0i64), quote!(|acc| { |x| { acc + x } })],
+            )
+            .to_string();
+        assert_eq!(
+            code,
+            "xs . iter () . cloned () . fold (0i64 , | acc , x | acc + x)"
+        );
+
+        let code = gen.call("sum", vec![quote!(xs)]).to_string();
+        assert!(code.starts_with("xs . iter () . cloned () . reduce ("));
+        let code = gen
+            .call("max_by", vec![quote!(items), quote!(|item| { item.qty })])
+            .to_string();
+        assert!(code.contains(
+            ". map (| item | (:: core :: clone :: Clone :: clone (& (item . qty)) , item))"
+        ));
+        assert!(code.contains(". max_by (|"));
+
+        // Lambdas are required where the element is named
+        assert_eq!(
+            gen.call("filter", vec![quote!(xs), quote!(keep)])
+                .to_string(),
+            invalid_call("filter").to_string()
+        );
+        assert_eq!(
+            gen.call("reduce", vec![quote!(xs), quote!(0i64)])
+                .to_string(),
+            invalid_call("reduce").to_string()
+        );
+    }
+
+    #[test]
+    fn test_phone_functions() {
+        let gen = FunctionGenerator::new();
+        let code = gen.call("is_phone", vec![quote!(input.phone)]).to_string();
+        assert_eq!(
+            code,
+            "elo_rust :: runtime :: phone :: is_phone (& input . phone)"
+        );
+        let code = gen
+            .call("normalize_phone", vec![quote!(phone), quote!("GB")])
+            .to_string();
+        assert!(code.contains("normalize_phone (& phone , & \"GB\")"));
+        assert_eq!(
+            gen.call("normalize_phone", vec![quote!(phone)]).to_string(),
+            invalid_call("normalize_phone").to_string()
+        );
+    }
+
+    #[test]
+    fn test_code_list_functions() {
+        let gen = FunctionGenerator::new();
+        let code = gen
+            .call("is_iso_currency", vec![quote!(input.currency)])
+            .to_string();
+        assert_eq!(
+            code,
+            "elo_rust :: runtime :: codes :: is_iso_currency (& input . currency)"
+        );
+        assert_eq!(
+            gen.call("is_bcp47_lang", vec![]).to_string(),
+            invalid_call("is_bcp47_lang").to_string()
+        );
+    }
+
+    #[test]
+    fn test_password_strength_function() {
+        let gen = FunctionGenerator::new();
+        let code = gen
+            .call("password_strength", vec![quote!(input.password)])
+            .to_string();
+        assert_eq!(
+            code,
+            "elo_rust :: runtime :: password :: password_strength (& input . password)"
+        );
+    }
+
+    #[test]
+    fn test_card_functions() {
+        let gen = FunctionGenerator::new();
+        let code = gen.call("card_brand", vec![quote!(card)]).to_string();
+        assert_eq!(code, "elo_rust :: runtime :: card :: card_brand (& card)");
+        assert_eq!(
+            gen.call("is_card_number", vec![]).to_string(),
+            invalid_call("is_card_number").to_string()
+        );
+    }
+
+    #[test]
+    fn test_format_functions() {
+        let gen = FunctionGenerator::new();
+        for name in ["is_email", "is_url", "is_uuid", "is_ipv4", "is_ipv6"] {
+            let code = gen.call(name, vec![quote!(input.contact)]).to_string();
+            assert_eq!(
+                code,
+                format!(
+                    "elo_rust :: runtime :: format :: {} (& input . contact)",
+                    name
+                )
+            );
+            assert_eq!(
+                gen.call(name, vec![]).to_string(),
+                invalid_call(name).to_string()
+            );
+        }
+    }
+
+    #[test]
+    fn test_file_functions() {
+        let gen = FunctionGenerator::new();
+        let code = gen
+            .call(
+                "is_mime",
+                vec![quote!(file.content_type), quote!(vec!["image/*"])],
+            )
+            .to_string();
+        assert_eq!(
+            code,
+            "elo_rust :: runtime :: file :: is_mime (& file . content_type , & vec ! [\"image/*\"])"
+        );
+        let code = gen.call("size_mb", vec![quote!(file.size)]).to_string();
+        assert_eq!(
+            code,
+            "elo_rust :: runtime :: file :: size_mb ((file . size) as f64)"
+        );
+        assert_eq!(
+            gen.call("has_extension", vec![quote!(name)]).to_string(),
+            invalid_call("has_extension").to_string()
+        );
+    }
 }
@@ -4,23 +4,34 @@
 //! into idiomatic Rust code via the `quote!` macro.
 
 pub mod ast_to_code;
+pub mod audit;
 pub mod errors;
 pub mod expressions;
+pub mod float;
 pub mod functions;
+pub mod no_std;
 pub mod operators;
 pub mod optimization;
+pub mod options;
+pub mod presence;
+pub mod size;
 pub mod temporal;
 pub mod type_inference;
 pub mod types;
+pub mod units;
 
 pub use errors::CodeGenError;
 pub use operators::{BinaryOp, OperatorGenerator, UnaryOp};
+pub use options::{CodegenOptions, ErrorStrategy, TemporalBackend};
+pub use size::{report, SizeReport};
 
+use crate::ast::printer;
 use crate::ast::visitor::Visitor;
 use proc_macro2::TokenStream;
 use quote::quote;
 
-pub use types::TypeContext;
+use type_inference::{InferredType, TypeInferenceVisitor};
+pub use types::{FrozenTypeContext, TypeContext};
 
 /// Main code generator for transforming ELO AST to Rust code
 ///
@@ -39,6 +50,14 @@ pub use types::TypeContext;
 pub struct RustCodeGenerator {
     /// Type context for resolving custom types
     type_context: TypeContext,
+    /// How unit mismatches between fields and literals are handled
+    unit_policy: units::UnitPolicy,
+    /// Embedder-defined infix operators
+    operators: crate::parser::operators::OperatorTable,
+    /// Values for `${name}` placeholders
+    params: crate::params::Params,
+    /// Style of the generated code
+    options: CodegenOptions,
 }
 
 impl RustCodeGenerator {
@@ -46,6 +65,10 @@ impl RustCodeGenerator {
     pub fn new() -> Self {
         Self {
             type_context: TypeContext::new(),
+            unit_policy: units::UnitPolicy::default(),
+            operators: Default::default(),
+            params: Default::default(),
+            options: CodegenOptions::default(),
         }
     }
 
@@ -55,7 +78,91 @@ impl RustCodeGenerator {
     ///
     /// * `type_context` - Pre-configured type context with custom types
     pub fn with_context(type_context: TypeContext) -> Self {
-        Self { type_context }
+        Self {
+            type_context,
+            unit_policy: units::UnitPolicy::default(),
+            operators: Default::default(),
+            params: Default::default(),
+            options: CodegenOptions::default(),
+        }
+    }
+
+    /// Set how literals in a different unit than their field are handled
+    pub fn with_unit_policy(mut self, policy: units::UnitPolicy) -> Self {
+        self.unit_policy = policy;
+        self
+    }
+
+    /// Accept custom infix operators in validator expressions
+    pub fn with_operators(mut self, operators: crate::parser::operators::OperatorTable) -> Self {
+        self.operators = operators;
+        self
+    }
+
+    /// Set how panicking constructs in generated validators are handled
+    ///
+    /// Shorthand for setting [`CodegenOptions::panic_policy`].
+    pub fn with_panic_policy(mut self, policy: audit::PanicPolicy) -> Self {
+        self.options.panic_policy = policy;
+        self
+    }
+
+    /// Set what generated validators do when a `guard` condition fails
+    ///
+    /// By default ([`GuardPolicy::Error`](ast_to_code::GuardPolicy::Error))
+    /// the validator reports `guard failed: <condition>` as a validation
+    /// error; [`GuardPolicy::Panic`](ast_to_code::GuardPolicy::Panic) panics
+    /// instead, which [`audit::PanicPolicy::Deny`] rejects.
+    ///
+    /// Shorthand for setting [`CodegenOptions::guard_policy`].
+    pub fn with_guard_policy(mut self, policy: ast_to_code::GuardPolicy) -> Self {
+        self.options.guard_policy = policy;
+        self
+    }
+
+    /// Split large validators into one helper function per top-level conjunct
+    ///
+    /// When the body generated by [`generate_validator`](Self::generate_validator)
+    /// exceeds `max_tokens` tokens and the expression is a chain of `&&`,
+    /// each conjunct is emitted as a private `__elo_<name>_part_<i>` function.
+    /// The conjuncts are still evaluated in order with short-circuiting, and
+    /// `require`/`warn_if` messages are collected exactly as before.
+    ///
+    /// Shorthand for setting [`CodegenOptions::max_function_tokens`].
+    pub fn with_max_function_tokens(mut self, max_tokens: usize) -> Self {
+        self.options.max_function_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Bind `${name}` placeholders in validator expressions to these values
+    ///
+    /// Generation fails if a placeholder is unbound or its value has the
+    /// wrong type for where it is used (see [`crate::params`]).
+    pub fn with_params(mut self, params: crate::params::Params) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Replace the type context, keeping every other setting
+    pub fn with_type_context(mut self, type_context: TypeContext) -> Self {
+        self.type_context = type_context;
+        self
+    }
+
+    /// Set the style of the generated code (see [`CodegenOptions`])
+    ///
+    /// This replaces every option, including those set before with
+    /// [`with_panic_policy`](Self::with_panic_policy),
+    /// [`with_guard_policy`](Self::with_guard_policy) or
+    /// [`with_max_function_tokens`](Self::with_max_function_tokens).
+    pub fn with_options(mut self, options: CodegenOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// The options the generated code is styled with
+    pub fn options(&self) -> &CodegenOptions {
+        &self.options
     }
 
     /// Check if the generator is in a valid state
@@ -91,11 +198,11 @@ impl RustCodeGenerator {
         name: &str,
         input_type: &str,
     ) -> Result<TokenStream, String> {
-        let fn_name = quote::format_ident!("{}", name);
+        let fn_name = self.function_ident(name);
         let input_ident = quote::format_ident!("{}", input_type);
 
         Ok(quote! {
-            pub fn #fn_name(input: &#input_ident) -> Result<(), Vec<String>>
+            pub fn #fn_name(input: &#input_ident) -> Result<(), elo_rust::runtime::ValidationErrors>
         })
     }
 
@@ -146,8 +253,8 @@ impl RustCodeGenerator {
         receiver: &str,
         field: &str,
     ) -> Result<TokenStream, String> {
-        let receiver_ident = quote::format_ident!("{}", receiver);
-        let field_ident = quote::format_ident!("{}", field);
+        let receiver_ident = rust_ident(receiver);
+        let field_ident = rust_ident(field);
 
         Ok(quote! {
             #receiver_ident.#field_ident
@@ -166,12 +273,67 @@ impl RustCodeGenerator {
 
     /// Generate a documentation comment
     ///
-    /// Note: Doc comments are handled at the token manipulation level, not in token streams.
-    /// This method is provided for future extensibility.
-    pub fn generate_doc_comment(&self, _text: &str) -> Result<TokenStream, String> {
-        // Doc comments are handled at the token level
-        // For now, just return empty - doc comments will be added via token manipulation
-        Ok(quote! {})
+    /// Each line of `text` becomes a `#[doc = "..."]` attribute, the form
+    /// `///` comments take in a token stream.
+    pub fn generate_doc_comment(&self, text: &str) -> Result<TokenStream, String> {
+        let lines = text.lines().map(|line| format!(" {}", line));
+        Ok(quote! { #(#[doc = #lines])* })
+    }
+
+    /// The doc comment of a generated validator, if they are emitted
+    ///
+    /// The expression is shown as the canonical source of its parsed form,
+    /// spread over indented lines.
+    fn validator_docs(&self, expr: &crate::ast::Expr) -> Result<TokenStream, String> {
+        if !self.options.doc_comments {
+            return Ok(TokenStream::new());
+        }
+        let layout = printer::FormatOptions {
+            indent: Some(4),
+            ..printer::FormatOptions::default()
+        };
+        self.generate_doc_comment(&format!(
+            "Validates the input against the ELO expression\n\n```text\n{}\n```",
+            printer::format_with(expr, &layout)
+        ))
+    }
+
+    /// The identifier of a generated function, with the identifier prefix
+    fn function_ident(&self, name: &str) -> proc_macro2::Ident {
+        quote::format_ident!("{}{}", self.options.identifier_prefix, name)
+    }
+
+    /// Apply the `no_std` option and the panic policy to generated code
+    fn finish(&self, tokens: TokenStream) -> Result<TokenStream, String> {
+        let tokens = if self.options.no_std {
+            no_std::to_no_std(&tokens)?
+        } else {
+            tokens
+        };
+        audit::enforce(self.options.panic_policy, &tokens)?;
+        Ok(tokens)
+    }
+
+    /// Generate the Rust expression computing the value of an ELO expression
+    ///
+    /// The code reads fields through `input: &input_type` like a validator
+    /// body, and has the Rust type of the value rather than `bool`. A
+    /// failed `guard` follows the [guard policy](Self::with_guard_policy).
+    pub fn generate_expression(
+        &self,
+        elo_expr: &str,
+        input_type: &str,
+    ) -> Result<TokenStream, String> {
+        let source = self.parse(elo_expr)?;
+        let ast = self.prepare_ast(&source, input_type)?;
+        let code = ast_to_code::CodegenVisitor::new()
+            .with_guard_policy(self.options.guard_policy)
+            .with_temporal_backend(self.options.temporal_backend)
+            .with_input_fields()
+            .with_type_context(self.type_context.clone(), input_type)
+            .with_operators(self.operators.clone())
+            .visit_expr(&self.share_calls(&ast, input_type));
+        self.finish(code)
     }
 
     /// Generate a complete validator function from an ELO expression
@@ -184,33 +346,347 @@ impl RustCodeGenerator {
     ///
     /// # Returns
     ///
-    /// A `TokenStream` representing the complete validator function
+    /// A `TokenStream` representing the complete validator function, which
+    /// returns `Result<(), ValidationErrors>`; see
+    /// [`generate_validator_with_errors`](Self::generate_validator_with_errors).
+    ///
+    /// Generation fails if the expression calls an unknown function, or a
+    /// function with an argument count none of its signatures takes (see
+    /// [`functions::check_function_calls`]). Under
+    /// [`audit::PanicPolicy::Deny`] it also fails if the validator could
+    /// panic at runtime.
     pub fn generate_validator(
         &self,
         name: &str,
         elo_expr: &str,
         input_type: &str,
     ) -> Result<TokenStream, String> {
-        // Parse the ELO expression
-        let ast =
-            crate::parser::Parser::parse(elo_expr).map_err(|e| format!("Parse error: {}", e))?;
+        self.generate_validator_with_errors(name, elo_expr, input_type, "")
+    }
+
+    /// Generate a validator whose error paths are nested under `base_path`
+    ///
+    /// The generated function has the signature
+    /// `fn name(input: &T) -> Result<(), elo_rust::runtime::ValidationErrors>`.
+    /// Every [`ValidationError`](crate::runtime::ValidationError) has `name`
+    /// as its rule. Its path is the input field the failed check reads,
+    /// prefixed with `base_path` (e.g. `"user.age"`), and its value is that
+    /// field's value when it implements `Display` or `Debug`. Checks reading
+    /// no field or several are reported at `base_path`, or under `name` if
+    /// `base_path` is empty.
+    ///
+    /// With [`ErrorStrategy::CollectAll`], every conjunct of a top-level
+    /// `&&` chain is checked and each failed one is reported on its own.
+    pub fn generate_validator_with_errors(
+        &self,
+        name: &str,
+        elo_expr: &str,
+        input_type: &str,
+        base_path: &str,
+    ) -> Result<TokenStream, String> {
+        let tokens = self.build_validator(name, elo_expr, input_type, base_path)?;
+        self.finish(tokens)
+    }
+
+    /// Generate a validator together with a batch validator for slices
+    ///
+    /// Besides the validator `name` of
+    /// [`generate_validator`](Self::generate_validator), the code holds
+    /// `fn name_all(items: &[T]) -> Result<(), Vec<(usize, ValidationErrors)>>`,
+    /// which checks every item and reports the errors of each failing one
+    /// with its index (see [`validate_all`](crate::runtime::validate_all)).
+    pub fn generate_batch_validator(
+        &self,
+        name: &str,
+        elo_expr: &str,
+        input_type: &str,
+    ) -> Result<TokenStream, String> {
+        let validator = self.build_validator(name, elo_expr, input_type, "")?;
+        let docs = self.validator_docs(&self.parse(elo_expr)?)?;
+        let fn_name = self.function_ident(name);
+        let all_name = self.function_ident(&format!("{}_all", name));
+        let input_ident = quote::format_ident!("{}", input_type);
+        let tokens = quote! {
+            #validator
+
+            #docs
+            pub fn #all_name(items: &[#input_ident]) -> Result<(), Vec<(usize, elo_rust::runtime::ValidationErrors)>> {
+                elo_rust::runtime::validate_all(items, #fn_name)
+            }
+        };
+        self.finish(tokens)
+    }
+
+    /// Generate an allocation-free validator that reports into an error sink
+    ///
+    /// The generated function has the signature
+    /// `fn name<S: ErrorSink>(input: &T, sink: &mut S) -> bool` and reports
+    /// each failed `require` (or a generic failure) as a
+    /// [`Failure`](crate::runtime::sink::Failure) under the rule `name`.
+    /// With a [`FixedErrorSink`](crate::runtime::sink::FixedErrorSink) the
+    /// reporting path does not allocate; the checks themselves allocate only
+    /// where the expression does (e.g. string functions).
+    pub fn generate_sink_validator(
+        &self,
+        name: &str,
+        elo_expr: &str,
+        input_type: &str,
+    ) -> Result<TokenStream, String> {
+        let source = self.parse(elo_expr)?;
+        let ast = self.prepare_ast(&source, input_type)?;
+        let mut visitor = ast_to_code::CodegenVisitor::new()
+            .with_sink(name)
+            .with_guard_policy(self.options.guard_policy)
+            .with_temporal_backend(self.options.temporal_backend)
+            .with_input_fields()
+            .with_type_context(self.type_context.clone(), input_type)
+            .with_operators(self.operators.clone());
+        let validation_code = visitor.visit_expr(&self.share_calls(&ast, input_type));
+
+        let docs = self.validator_docs(&source)?;
+        let fn_name = self.function_ident(name);
+        let input_ident = quote::format_ident!("{}", input_type);
+        let failed_flag = if visitor.uses_require() {
+            quote! { let mut __elo_failed = false; }
+        } else {
+            quote! { let __elo_failed = false; }
+        };
+        let tokens = quote! {
+            #docs
+            pub fn #fn_name<S: elo_rust::runtime::sink::ErrorSink>(
+                input: &#input_ident,
+                __elo_sink: &mut S,
+            ) -> bool {
+                #failed_flag
+                let result: bool = #validation_code;
+                if !result && !__elo_failed {
+                    __elo_sink.push(elo_rust::runtime::sink::Failure::error(
+                        #name,
+                        "Validation failed",
+                    ));
+                }
+                result
+            }
+        };
+        self.finish(tokens)
+    }
+
+    /// Parse an ELO expression with the generator's operators
+    fn parse(&self, elo_expr: &str) -> Result<crate::ast::Expr, String> {
+        crate::parser::Parser::parse_with_operators(elo_expr, &self.operators)
+            .map_err(|e| format!("Parse error: {}", e))
+    }
+
+    /// Check a parsed expression against the input type and optimize it
+    fn prepare_ast(
+        &self,
+        source: &crate::ast::Expr,
+        input_type: &str,
+    ) -> Result<crate::ast::Expr, String> {
+        let ast = self
+            .params
+            .bind(source)
+            .map_err(|e| format!("Parameter error: {}", e))?;
+        functions::check_regex_patterns(&ast).map_err(|e| format!("Pattern error: {}", e))?;
+        ast.check_pipes()
+            .map_err(|e| format!("Pipe error: {}", e))?;
+        functions::check_function_calls(&ast, &self.operators)
+            .map_err(|e| format!("Function error: {}", e))?;
+
+        // Check and normalise units against the input type's annotations
+        let ast = if self.has_type(input_type) {
+            let check = units::UnitChecker::new(&self.type_context, input_type)
+                .with_policy(self.unit_policy)
+                .check(&ast);
+            if check.has_errors() {
+                let messages: Vec<String> = check
+                    .diagnostics
+                    .into_iter()
+                    .filter(|d| d.severity == crate::runtime::Severity::Error)
+                    .map(|d| d.message)
+                    .collect();
+                return Err(format!("Unit error: {}", messages.join("; ")));
+            }
+            check.expr
+        } else {
+            ast
+        };
+        // Without a registered input type only literals have known types,
+        // which still catches `clamp(x, 1.0..2.0)` or `'a' - 1`
+        let types = if self.has_type(input_type) {
+            TypeInferenceVisitor::with_context(self.type_context.clone(), input_type)
+        } else {
+            TypeInferenceVisitor::new()
+        };
+        if let InferredType::Error(message) = types.infer(&ast) {
+            return Err(format!("Type error: {}", message));
+        }
+        // Calls are shared once the types are known, see `share_calls`
+        let passes: Vec<optimization::Pass> = optimization::Pass::ALL
+            .iter()
+            .copied()
+            .filter(|pass| *pass != optimization::Pass::CommonSubexpressions)
+            .collect();
+        Ok(optimization::Optimizer::optimize_with_passes(&ast, &passes))
+    }
+
+    /// Bind repeated pure calls to `let`s, except calls reading nullable
+    /// values, whose comparisons need the null guard around them
+    fn share_calls(&self, ast: &crate::ast::Expr, input_type: &str) -> crate::ast::Expr {
+        let types = TypeInferenceVisitor::with_context(self.type_context.clone(), input_type);
+        optimization::Optimizer::eliminate_common_subexpressions(ast, |call| {
+            !reads_nullable(&types, call)
+        })
+    }
+
+    fn build_validator(
+        &self,
+        name: &str,
+        elo_expr: &str,
+        input_type: &str,
+        base_path: &str,
+    ) -> Result<TokenStream, String> {
+        let source = self.parse(elo_expr)?;
+        let ast = self.prepare_ast(&source, input_type)?;
 
         // Generate code via visitor
-        let mut visitor = ast_to_code::CodegenVisitor::new();
-        let validation_code = visitor.visit_expr(&ast);
+        let new_visitor = || {
+            ast_to_code::CodegenVisitor::new()
+                .with_diagnostics(name)
+                .with_base_path(base_path)
+                .with_guard_policy(self.options.guard_policy)
+                .with_temporal_backend(self.options.temporal_backend)
+                .with_input_fields()
+                .with_type_context(self.type_context.clone(), input_type)
+                .with_operators(self.operators.clone())
+        };
+        let mut visitor = new_visitor();
+        let collect_all = self.options.error_strategy == ErrorStrategy::CollectAll;
+        let shared = self.share_calls(&ast, input_type);
+        let mut validation_code = if collect_all {
+            visitor.visit_collect_all(&shared)
+        } else {
+            visitor.visit_expr(&shared)
+        };
+        let uses_require = visitor.uses_require();
+        let uses_warn_if = visitor.uses_warn_if();
+        let failure = visitor.validation_error(&ast, "Validation failed", false);
 
         // Wrap in function
-        let fn_name = quote::format_ident!("{}", name);
+        let docs = self.validator_docs(&source)?;
+        let fn_name = self.function_ident(name);
         let input_ident = quote::format_ident!("{}", input_type);
 
+        // Move each top-level conjunct of an oversized body into its own function
+        let mut helpers = TokenStream::new();
+        let conjuncts = conjuncts(&ast);
+        let oversized = self
+            .options
+            .max_function_tokens
+            .is_some_and(|max| token_count(&validation_code) > max);
+        if oversized && conjuncts.len() > 1 {
+            let (mut params, mut args) = (TokenStream::new(), TokenStream::new());
+            if uses_require || uses_warn_if {
+                params.extend(quote! { , __elo_errors: &mut elo_rust::runtime::ValidationErrors });
+                args.extend(quote! { , &mut __elo_errors });
+            }
+            if uses_warn_if {
+                params
+                    .extend(quote! { , __elo_warnings: &mut elo_rust::runtime::ValidationErrors });
+                args.extend(quote! { , &mut __elo_warnings });
+            }
+            let mut calls = Vec::with_capacity(conjuncts.len());
+            for (i, conjunct) in conjuncts.iter().enumerate() {
+                let part = quote::format_ident!(
+                    "__elo_{}{}_part_{}",
+                    self.options.identifier_prefix,
+                    name,
+                    i
+                );
+                let code = new_visitor().visit_expr(conjunct);
+                helpers.extend(quote! {
+                    fn #part(input: &#input_ident #params) -> bool {
+                        #code
+                    }
+                });
+                calls.push(quote! { #part(input #args) });
+            }
+            validation_code = if collect_all {
+                visitor.collect_failures(calls.into_iter().zip(conjuncts).collect())
+            } else {
+                quote! { #(#calls)&&* }
+            };
+        }
+
+        if !uses_require && !uses_warn_if {
+            return Ok(quote! {
+                #helpers
+
+                #docs
+                pub fn #fn_name(input: &#input_ident) -> Result<(), elo_rust::runtime::ValidationErrors> {
+                    let result = #validation_code;
+                    if result {
+                        Ok(())
+                    } else {
+                        let mut errors = elo_rust::runtime::ValidationErrors::new();
+                        errors.push(#failure);
+                        Err(errors)
+                    }
+                }
+            });
+        }
+
+        // `require` collects custom messages; a plain `false` result still
+        // falls back to the generic message.
+        let body = quote! {
+            let mut __elo_errors = elo_rust::runtime::ValidationErrors::new();
+            let result: bool = #validation_code;
+            if !result && __elo_errors.is_empty() {
+                __elo_errors.push(#failure);
+            }
+        };
+
+        if !uses_warn_if {
+            return Ok(quote! {
+                #helpers
+
+                #docs
+                pub fn #fn_name(input: &#input_ident) -> Result<(), elo_rust::runtime::ValidationErrors> {
+                    #body
+                    if __elo_errors.is_empty() {
+                        Ok(())
+                    } else {
+                        Err(__elo_errors)
+                    }
+                }
+            });
+        }
+
+        // Warnings never fail validation; they are returned alongside the result
+        let with_warnings = self.function_ident(&format!("{}_with_warnings", name));
         Ok(quote! {
-            pub fn #fn_name(input: &#input_ident) -> Result<(), Vec<String>> {
-                let result = #validation_code;
-                if result {
+            #helpers
+
+            #docs
+            pub fn #with_warnings(
+                input: &#input_ident,
+            ) -> (
+                Result<(), elo_rust::runtime::ValidationErrors>,
+                elo_rust::runtime::ValidationErrors,
+            ) {
+                let mut __elo_warnings = elo_rust::runtime::ValidationErrors::new();
+                #body
+                let outcome = if __elo_errors.is_empty() {
                     Ok(())
                 } else {
-                    Err(vec!["Validation failed".to_string()])
-                }
+                    Err(__elo_errors)
+                };
+                (outcome, __elo_warnings)
+            }
+
+            #docs
+            pub fn #fn_name(input: &#input_ident) -> Result<(), elo_rust::runtime::ValidationErrors> {
+                #with_warnings(input).0
             }
         })
     }
@@ -233,12 +709,12 @@ impl RustCodeGenerator {
         input_type: &str,
     ) -> Result<TokenStream, String> {
         let struct_ident = quote::format_ident!("{}", struct_name);
-        let fn_ident = quote::format_ident!("{}", validator_fn_name);
+        let fn_ident = self.function_ident(validator_fn_name);
         let input_ident = quote::format_ident!("{}", input_type);
 
         Ok(quote! {
             impl #struct_ident {
-                pub fn #fn_ident(input: &#input_ident) -> Result<(), Vec<String>> {
+                pub fn #fn_ident(input: &#input_ident) -> Result<(), elo_rust::runtime::ValidationErrors> {
                     Ok(())
                 }
             }
@@ -246,8 +722,68 @@ impl RustCodeGenerator {
     }
 }
 
+/// Flatten a top-level `a && b && ...` chain into its operands
+pub(crate) fn conjuncts(expr: &crate::ast::Expr) -> Vec<&crate::ast::Expr> {
+    match expr {
+        crate::ast::Expr::BinaryOp {
+            op: crate::ast::BinaryOperator::And,
+            left,
+            right,
+        } => {
+            let mut parts = conjuncts(left);
+            parts.extend(conjuncts(right));
+            parts
+        }
+        expr => vec![expr],
+    }
+}
+
+/// Whether any part of `expr` may be null
+fn reads_nullable(types: &TypeInferenceVisitor, expr: &crate::ast::Expr) -> bool {
+    matches!(
+        types.infer(expr),
+        InferredType::Nullable(_) | InferredType::Null
+    ) || expr
+        .children()
+        .into_iter()
+        .any(|child| reads_nullable(types, child))
+}
+
+/// Number of tokens in a stream, counting the contents of groups
+fn token_count(tokens: &TokenStream) -> usize {
+    tokens
+        .clone()
+        .into_iter()
+        .map(|tree| match tree {
+            proc_macro2::TokenTree::Group(group) => 1 + token_count(&group.stream()),
+            _ => 1,
+        })
+        .sum()
+}
+
 impl Default for RustCodeGenerator {
     fn default() -> Self {
         Self::new()
     }
 }
+
+/// Rust keywords that need the raw identifier form (`r#in`) as field names
+const RUST_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+/// Rust identifier for an ELO identifier or field name
+///
+/// Names that are Rust keywords, such as a field escaped as `` `type` `` in
+/// ELO, become raw identifiers so they still refer to the struct field.
+pub(crate) fn rust_ident(name: &str) -> proc_macro2::Ident {
+    if RUST_KEYWORDS.contains(&name) {
+        proc_macro2::Ident::new_raw(name, proc_macro2::Span::call_site())
+    } else {
+        quote::format_ident!("{}", name)
+    }
+}
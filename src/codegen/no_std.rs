@@ -0,0 +1,186 @@
+//! Rewriting generated code for `#![no_std]` crates
+//!
+//! With [`CodegenOptions::no_std`](super::CodegenOptions::no_std), the
+//! generator passes its output through [`to_no_std`], which names `core`
+//! and `alloc` items instead of `std` ones: `::std::cmp::Ordering` becomes
+//! `::core::cmp::Ordering`, and `vec!`, `Vec` and `String`, which are not in
+//! the `core` prelude, are written as `::alloc` paths. The crate including
+//! the code needs `extern crate alloc;`.
+//!
+//! Code needing items that only `std` provides, such as the lazily compiled
+//! regex of `matches`, is rejected. The `elo_rust` runtime that validators
+//! report errors through is not rewritten.
+//!
+//! # Example
+//!
+//! ```
+//! use elo_rust::codegen::no_std::to_no_std;
+//! use quote::quote;
+//!
+//! let code = to_no_std(&quote! { let xs: Vec<i64> = vec![1, 2]; }).unwrap();
+//! assert_eq!(
+//!     code.to_string(),
+//!     "let xs : :: alloc :: vec :: Vec < i64 > = :: alloc :: vec ! [1 , 2] ;"
+//! );
+//! assert!(to_no_std(&quote! { ::std::sync::OnceLock::new() }).is_err());
+//! ```
+
+use proc_macro2::{Group, Ident, Punct, Spacing, Span, TokenStream, TokenTree};
+
+/// `std` modules that re-export a `core` module of the same name
+const CORE_MODULES: &[&str] = &[
+    "any", "cell", "char", "clone", "cmp", "convert", "default", "fmt", "hash", "iter", "marker",
+    "mem", "num", "ops", "option", "result", "slice", "str",
+];
+
+/// `std` modules that re-export an `alloc` module of the same name
+const ALLOC_MODULES: &[&str] = &["borrow", "boxed", "string", "vec"];
+
+/// Prelude names of `std` missing from the `core` prelude, with their `alloc` path
+const ALLOC_PRELUDE: &[(&str, &[&str])] = &[
+    ("Vec", &["vec", "Vec"]),
+    ("String", &["string", "String"]),
+    ("Box", &["boxed", "Box"]),
+];
+
+/// Macros of `std` missing from the `core` prelude, with their `alloc` path
+const ALLOC_MACROS: &[(&str, &[&str])] = &[("vec", &["vec"]), ("format", &["format"])];
+
+/// Rewrite generated code to name `core` and `alloc` items instead of `std` ones
+///
+/// Returns an error naming the first `std` module without a `core` or
+/// `alloc` counterpart.
+pub fn to_no_std(tokens: &TokenStream) -> Result<TokenStream, String> {
+    let trees: Vec<TokenTree> = tokens.clone().into_iter().collect();
+    let mut out = TokenStream::new();
+    for (i, tree) in trees.iter().enumerate() {
+        // Only the first segment of a path is rewritten, never a field or method
+        let qualified = follows_dot(&trees[..i]) || follows_path_segment(&trees[..i]);
+        match tree {
+            TokenTree::Group(group) => {
+                let mut rewritten = Group::new(group.delimiter(), to_no_std(&group.stream())?);
+                rewritten.set_span(group.span());
+                out.extend([TokenTree::Group(rewritten)]);
+            }
+            TokenTree::Ident(ident) if ident == "std" && !qualified => {
+                let module = match trees.get(i + 3) {
+                    Some(TokenTree::Ident(module)) => module.to_string(),
+                    _ => String::new(),
+                };
+                let root = if CORE_MODULES.contains(&module.as_str()) {
+                    "core"
+                } else if ALLOC_MODULES.contains(&module.as_str()) {
+                    "alloc"
+                } else {
+                    return Err(format!("no_std code cannot use `std::{}`", module));
+                };
+                out.extend([TokenTree::Ident(Ident::new(root, ident.span()))]);
+            }
+            TokenTree::Ident(ident) if !qualified => {
+                let name = ident.to_string();
+                let is_macro =
+                    matches!(trees.get(i + 1), Some(TokenTree::Punct(p)) if p.as_char() == '!');
+                let table = if is_macro {
+                    ALLOC_MACROS
+                } else {
+                    ALLOC_PRELUDE
+                };
+                match table.iter().find(|(item, _)| *item == name) {
+                    Some((_, path)) => out.extend(alloc_path(path, ident.span())),
+                    None => out.extend([tree.clone()]),
+                }
+            }
+            _ => out.extend([tree.clone()]),
+        }
+    }
+    Ok(out)
+}
+
+/// Whether the tokens end with a `.`
+fn follows_dot(before: &[TokenTree]) -> bool {
+    matches!(before.last(), Some(TokenTree::Punct(p)) if p.as_char() == '.')
+}
+
+/// Whether the tokens end with a path segment and `::`, unlike the leading
+/// `::` of an absolute path such as `::std::mem`
+fn follows_path_segment(before: &[TokenTree]) -> bool {
+    let is_punct = |tree: Option<&TokenTree>, c: char| matches!(tree, Some(TokenTree::Punct(p)) if p.as_char() == c);
+    match before {
+        [.., segment, TokenTree::Punct(first), second] if first.as_char() == ':' => {
+            is_punct(Some(second), ':')
+                && match segment {
+                    TokenTree::Ident(_) => true,
+                    // The `>` closing generics, not the one of `->`
+                    TokenTree::Punct(p) if p.as_char() == '>' => {
+                        !is_punct(before.len().checked_sub(4).map(|i| &before[i]), '-')
+                    }
+                    _ => false,
+                }
+        }
+        _ => false,
+    }
+}
+
+/// `::alloc::<segments>`
+fn alloc_path(segments: &[&str], span: Span) -> TokenStream {
+    let mut path = TokenStream::new();
+    for segment in std::iter::once(&"alloc").chain(segments) {
+        path.extend([
+            TokenTree::Punct(Punct::new(':', Spacing::Joint)),
+            TokenTree::Punct(Punct::new(':', Spacing::Alone)),
+            TokenTree::Ident(Ident::new(segment, span)),
+        ]);
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::quote;
+
+    fn rewrite(tokens: TokenStream) -> String {
+        to_no_std(&tokens).unwrap().to_string()
+    }
+
+    #[test]
+    fn test_std_paths() {
+        assert_eq!(
+            rewrite(quote! { ::std::cmp::Ordering::Less }),
+            ":: core :: cmp :: Ordering :: Less"
+        );
+        assert_eq!(
+            rewrite(quote! { { std::string::String::new() } }),
+            "{ alloc :: string :: String :: new () }"
+        );
+        assert_eq!(
+            rewrite(quote! { fn f() -> ::std::vec::Vec<u8> {} }),
+            "fn f () -> :: alloc :: vec :: Vec < u8 > { }"
+        );
+        let error = to_no_std(&quote! { x(::std::collections::HashSet::new()) }).unwrap_err();
+        assert!(error.contains("std::collections"));
+    }
+
+    #[test]
+    fn test_prelude_items() {
+        assert_eq!(
+            rewrite(quote! { xs.iter().collect::<Vec<_>>() }),
+            "xs . iter () . collect :: < :: alloc :: vec :: Vec < _ >> ()"
+        );
+        assert_eq!(
+            rewrite(quote! { format!("{}", n) }),
+            ":: alloc :: format ! (\"{}\" , n)"
+        );
+        // Already qualified paths and other identifiers are kept
+        assert_eq!(
+            rewrite(quote! { ::alloc::vec::Vec::new() }),
+            ":: alloc :: vec :: Vec :: new ()"
+        );
+        assert_eq!(rewrite(quote! { vec.len() }), "vec . len ()");
+        assert_eq!(
+            rewrite(quote! { let s: String = name; }),
+            "let s : :: alloc :: string :: String = name ;"
+        );
+        assert_eq!(rewrite(quote! { input.std > 0 }), "input . std > 0");
+    }
+}
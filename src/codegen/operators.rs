@@ -1,6 +1,7 @@
 //! Binary and unary operator code generation
 
-use proc_macro2::TokenStream;
+use super::optimization::MembershipTable;
+use proc_macro2::{Spacing, TokenStream, TokenTree};
 use quote::quote;
 
 /// Represents a binary operator
@@ -34,17 +35,25 @@ pub enum BinaryOp {
     Divide,
     /// Modulo (%)
     Modulo,
+    /// Power (^) of integers; see [`OperatorGenerator::power_float`] for floats
+    Power,
+    /// Membership (value in collection/range)
+    In,
+    /// Glob pattern match (text like pattern)
+    Like,
 }
 
 /// Represents a unary operator
 ///
-/// Supports logical negation and numeric negation.
+/// Supports logical negation, numeric negation and unary plus.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum UnaryOp {
     /// Logical NOT (!)
     Not,
     /// Negation (-)
     Negate,
+    /// Unary plus (+), which leaves its operand unchanged
+    Identity,
 }
 
 /// Generates code for operators
@@ -71,6 +80,30 @@ impl OperatorGenerator {
     ///
     /// A `TokenStream` representing the binary operation
     pub fn binary(&self, op: BinaryOp, left: TokenStream, right: TokenStream) -> TokenStream {
+        let strength = match op {
+            BinaryOp::Equal
+            | BinaryOp::NotEqual
+            | BinaryOp::Less
+            | BinaryOp::LessEqual
+            | BinaryOp::Greater
+            | BinaryOp::GreaterEqual => Some(precedence::COMPARE),
+            BinaryOp::Add | BinaryOp::Subtract => Some(precedence::ADD),
+            BinaryOp::Multiply | BinaryOp::Divide | BinaryOp::Modulo => Some(precedence::MUL),
+            BinaryOp::And => Some(precedence::AND),
+            BinaryOp::Or => Some(precedence::OR),
+            // Function arguments and borrows
+            BinaryOp::Power => Some(precedence::LOWEST),
+            BinaryOp::Like => Some(precedence::UNARY),
+            // The collection is the receiver of `contains`
+            BinaryOp::In => None,
+        };
+        let (left, right) = match strength {
+            Some(strength) => (
+                operand(left, strength, false),
+                operand(right, strength, true),
+            ),
+            None => (left, receiver(right)),
+        };
         match op {
             BinaryOp::Equal => quote! { #left == #right },
             BinaryOp::NotEqual => quote! { #left != #right },
@@ -83,8 +116,127 @@ impl OperatorGenerator {
             BinaryOp::Multiply => quote! { #left * #right },
             BinaryOp::Divide => quote! { #left / #right },
             BinaryOp::Modulo => quote! { #left % #right },
+            BinaryOp::Power => {
+                quote! { elo_rust::runtime::value::strict_power(#left, #right) }
+            }
             BinaryOp::And => quote! { #left && #right },
             BinaryOp::Or => quote! { #left || #right },
+            BinaryOp::In => quote! { #right.contains(&(#left)) },
+            BinaryOp::Like => quote! { elo_rust::runtime::glob::glob_match(&#left, &#right) },
+        }
+    }
+
+    /// Generate code for `text like 'pattern'` with a literal pattern
+    ///
+    /// Patterns made of a single literal with `*` only at the ends compile to
+    /// plain string comparisons (`==`, `starts_with`, `ends_with`, `contains`);
+    /// everything else falls back to the runtime glob matcher.
+    pub fn like_literal(&self, text: TokenStream, pattern: &str) -> TokenStream {
+        use crate::runtime::glob::{parse_glob, GlobToken};
+
+        let text = receiver(text);
+        let tokens = parse_glob(pattern);
+        let fallback = || quote! { elo_rust::runtime::glob::glob_match(&#text, #pattern) };
+        if tokens.contains(&GlobToken::AnyChar) {
+            return fallback();
+        }
+
+        let literals: Vec<&str> = tokens
+            .iter()
+            .filter_map(|t| match t {
+                GlobToken::Literal(s) => Some(s.as_str()),
+                _ => None,
+            })
+            .collect();
+        let leading_star = tokens.first() == Some(&GlobToken::AnySequence);
+        let trailing_star = tokens.last() == Some(&GlobToken::AnySequence);
+
+        match (literals.as_slice(), leading_star, trailing_star) {
+            ([], _, _) if tokens.is_empty() => quote! { #text.is_empty() },
+            ([], _, _) => quote! { true },
+            ([lit], false, false) => quote! { #text == #lit },
+            ([lit], true, false) => quote! { #text.ends_with(#lit) },
+            ([lit], false, true) => quote! { #text.starts_with(#lit) },
+            ([lit], true, true) => quote! { #text.contains(#lit) },
+            ([prefix, suffix], false, false) => {
+                let min_len = prefix.len() + suffix.len();
+                quote! {
+                    (#text.len() >= #min_len
+                        && #text.starts_with(#prefix)
+                        && #text.ends_with(#suffix))
+                }
+            }
+            _ => fallback(),
+        }
+    }
+
+    /// Generate code for `value in [...]` against a large literal set
+    ///
+    /// The set becomes a sorted `const` slice searched with binary search,
+    /// so lookups take O(log n) comparisons and never allocate.
+    pub fn membership_table(&self, value: TokenStream, table: &MembershipTable) -> TokenStream {
+        let value = operand(value, precedence::UNARY, false);
+        match table {
+            MembershipTable::Strings(values) => quote! {
+                {
+                    const TABLE: &[&str] = &[#(#values),*];
+                    TABLE
+                        .binary_search(&::core::convert::AsRef::<str>::as_ref(&#value))
+                        .is_ok()
+                }
+            },
+            MembershipTable::Integers(values) => quote! {
+                {
+                    const TABLE: &[i64] = &[#(#values),*];
+                    TABLE.binary_search(&(#value)).is_ok()
+                }
+            },
+        }
+    }
+
+    /// Generate code for `value in [...]` against a small literal set
+    ///
+    /// The set becomes a `matches!` pattern, so string fields are compared
+    /// as `&str` whether they hold a `String` or a `&str`, and nothing is
+    /// allocated.
+    pub fn membership_pattern(&self, value: TokenStream, set: &MembershipTable) -> TokenStream {
+        let value = operand(value, precedence::UNARY, false);
+        match set {
+            MembershipTable::Strings(values) => quote! {
+                matches!(::core::convert::AsRef::<str>::as_ref(&#value), #(#values)|*)
+            },
+            MembershipTable::Integers(values) => quote! {
+                matches!(#value, #(#values)|*)
+            },
+        }
+    }
+
+    /// Generate code for `base ^ exponent` on floats
+    ///
+    /// Both operands must be `f64`; integer operands are converted by the
+    /// caller. Integer powers are [`BinaryOp::Power`]. Both panic with the
+    /// interpreter's error when the result overflows or is not a real number.
+    pub fn power_float(&self, base: TokenStream, exponent: TokenStream) -> TokenStream {
+        quote! { elo_rust::runtime::value::strict_float_power(#base, #exponent) }
+    }
+
+    /// Generate code for `/` or `%` on two `i64` operands
+    ///
+    /// Dividing by zero panics with the interpreter's `Division by zero`
+    /// (`Modulo by zero`) instead of Rust's own message, and a constant zero
+    /// divisor does not trip rustc's `unconditional_panic` lint.
+    pub fn integer_division(
+        &self,
+        op: BinaryOp,
+        left: TokenStream,
+        right: TokenStream,
+    ) -> TokenStream {
+        match op {
+            BinaryOp::Divide => quote! { elo_rust::runtime::value::strict_divide(#left, #right) },
+            BinaryOp::Modulo => {
+                quote! { elo_rust::runtime::value::strict_remainder(#left, #right) }
+            }
+            _ => self.binary(op, left, right),
         }
     }
 
@@ -99,13 +251,127 @@ impl OperatorGenerator {
     ///
     /// A `TokenStream` representing the unary operation
     pub fn unary(&self, op: UnaryOp, operand: TokenStream) -> TokenStream {
+        let operand = self::operand(operand, precedence::UNARY, false);
         match op {
             UnaryOp::Not => quote! { !#operand },
             UnaryOp::Negate => quote! { -#operand },
+            UnaryOp::Identity => operand,
         }
     }
 }
 
+/// Binding strength of Rust operators, loosest first
+mod precedence {
+    pub(super) const LOWEST: u8 = 0;
+    pub(super) const RANGE: u8 = 1;
+    pub(super) const OR: u8 = 2;
+    pub(super) const AND: u8 = 3;
+    pub(super) const COMPARE: u8 = 4;
+    pub(super) const BIT_OR: u8 = 5;
+    pub(super) const BIT_XOR: u8 = 6;
+    pub(super) const BIT_AND: u8 = 7;
+    pub(super) const SHIFT: u8 = 8;
+    pub(super) const ADD: u8 = 9;
+    pub(super) const MUL: u8 = 10;
+    pub(super) const CAST: u8 = 11;
+    pub(super) const UNARY: u8 = 12;
+    pub(super) const PRIMARY: u8 = u8::MAX;
+}
+
+/// The loosest operator at the top level of `code`, and whether it contains
+/// an `as` cast
+fn loosest(code: &TokenStream) -> (u8, bool) {
+    use precedence::*;
+
+    let tokens: Vec<TokenTree> = code.clone().into_iter().collect();
+    let (mut loosest, mut cast) = (PRIMARY, false);
+    // At the start and after a binary operator, punctuation is a prefix
+    let mut expect_operand = true;
+    let mut i = 0;
+    while i < tokens.len() {
+        let strength = match &tokens[i] {
+            TokenTree::Punct(_) => {
+                // Joint punctuation forms a single operator: `&&`, `<=`, `::`
+                let mut op = String::new();
+                while let Some(TokenTree::Punct(punct)) = tokens.get(i) {
+                    op.push(punct.as_char());
+                    i += 1;
+                    if punct.spacing() == Spacing::Alone {
+                        break;
+                    }
+                }
+                match op.as_str() {
+                    "." | "::" | "?" => continue,
+                    // The bang of a macro call such as `matches!(..)`
+                    "!" if !expect_operand => continue,
+                    _ if expect_operand && op.starts_with('|') => LOWEST,
+                    _ if expect_operand => UNARY,
+                    _ => {
+                        expect_operand = true;
+                        match op.as_str() {
+                            "*" | "/" | "%" => MUL,
+                            "+" | "-" => ADD,
+                            "<<" | ">>" => SHIFT,
+                            "&" => BIT_AND,
+                            "^" => BIT_XOR,
+                            "|" => BIT_OR,
+                            "==" | "!=" | "<" | ">" | "<=" | ">=" => COMPARE,
+                            "&&" => AND,
+                            "||" => OR,
+                            ".." | "..=" => RANGE,
+                            _ => LOWEST,
+                        }
+                    }
+                }
+            }
+            TokenTree::Ident(ident) => {
+                i += 1;
+                match ident.to_string().as_str() {
+                    "as" => {
+                        cast = true;
+                        CAST
+                    }
+                    "if" | "match" | "let" | "move" | "return" | "loop" | "while" | "for" => LOWEST,
+                    _ => {
+                        expect_operand = false;
+                        continue;
+                    }
+                }
+            }
+            _ => {
+                i += 1;
+                expect_operand = false;
+                continue;
+            }
+        };
+        loosest = loosest.min(strength);
+    }
+    (loosest, cast)
+}
+
+/// `code` as an operand of an operator binding with `strength`, in
+/// parentheses if it would otherwise come apart
+///
+/// Token streams are spliced as is, so without them `(a + 1) * 2` would come
+/// out as `a + 1 * 2`. Comparisons do not chain, and a cast before `<` would
+/// read as the start of generic arguments.
+fn operand(code: TokenStream, strength: u8, right: bool) -> TokenStream {
+    let (inner, cast) = loosest(&code);
+    let wrap = inner < strength
+        || (inner == strength && (right || strength == precedence::COMPARE))
+        || (cast && matches!(strength, precedence::COMPARE | precedence::SHIFT));
+    if wrap {
+        quote! { (#code) }
+    } else {
+        code
+    }
+}
+
+/// `code` as the receiver of a method call
+fn receiver(code: TokenStream) -> TokenStream {
+    operand(code, precedence::PRIMARY, false)
+}
+
 impl Default for OperatorGenerator {
     fn default() -> Self {
         Self::new()
@@ -144,4 +410,56 @@ mod tests {
     fn test_operator_generator_creation() {
         let _gen = OperatorGenerator::new();
     }
+
+    #[test]
+    fn test_operands_keep_their_grouping() {
+        let gen = OperatorGenerator::new();
+        let sum = gen.binary(BinaryOp::Add, quote! { a }, quote! { 1i64 });
+        assert_eq!(
+            gen.binary(BinaryOp::Multiply, sum.clone(), quote! { 2i64 })
+                .to_string(),
+            "(a + 1i64) * 2i64"
+        );
+        assert_eq!(
+            gen.unary(UnaryOp::Negate, sum.clone()).to_string(),
+            "- (a + 1i64)"
+        );
+        assert_eq!(
+            gen.binary(BinaryOp::Subtract, quote! { a }, sum.clone())
+                .to_string(),
+            "a - (a + 1i64)"
+        );
+        // Operands binding tighter than the operator stay as they are
+        assert_eq!(
+            gen.binary(BinaryOp::Add, sum, quote! { input.b.len() })
+                .to_string(),
+            "a + 1i64 + input . b . len ()"
+        );
+
+        let comparison = gen.binary(BinaryOp::Greater, quote! { input.age }, quote! { 18i64 });
+        assert_eq!(
+            gen.unary(UnaryOp::Not, comparison.clone()).to_string(),
+            "! (input . age > 18i64)"
+        );
+        assert_eq!(
+            gen.binary(BinaryOp::Equal, comparison.clone(), quote! { true })
+                .to_string(),
+            "(input . age > 18i64) == true"
+        );
+        assert_eq!(
+            gen.binary(BinaryOp::And, comparison.clone(), comparison)
+                .to_string(),
+            "input . age > 18i64 && input . age > 18i64"
+        );
+        assert_eq!(
+            gen.binary(BinaryOp::Less, quote! { (x) as f64 }, quote! { y })
+                .to_string(),
+            "((x) as f64) < y"
+        );
+        assert_eq!(
+            gen.unary(UnaryOp::Not, quote! { matches!(x, 1i64 | 2i64) })
+                .to_string(),
+            "! matches ! (x , 1i64 | 2i64)"
+        );
+    }
 }
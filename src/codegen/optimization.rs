@@ -2,8 +2,245 @@
 //!
 //! Provides optimization strategies including constant folding,
 //! dead code elimination, and expression simplification.
+//!
+//! Large literal sets on the right of `in` are detected as
+//! [`MembershipTable`]s, which code generation turns into sorted `const`
+//! tables searched with binary search instead of a linear scan.
+//!
+//! After folding, small boolean sub-expressions are minimized
+//! Quine–McCluskey style (see [`Optimizer::minimize_booleans`]), so
+//! defensive redundancy such as `a && (a || b)` compiles to just `a`.
+//!
+//! Branches of `if` and `guard` with a constant condition are dropped, and
+//! boolean identities such as `x && true` and `!!x` are removed. Pure
+//! function calls repeated across a validator, such as `length(trim(name))`
+//! in several conjuncts, are bound once to a `let` (see
+//! [`Optimizer::eliminate_common_subexpressions`]). Each of these steps is a
+//! [`Pass`]; [`Optimizer::optimize_with_passes`] runs a chosen subset.
+//!
+//! [`Optimizer::optimize_with_report`] also returns an [`OptimizerReport`]
+//! recording which folds fired and why the others were skipped.
+
+use crate::ast::visitor::fold_children;
+use crate::ast::{BinaryOperator, Expr, Fold, Literal, UnaryOperator};
+use crate::codegen::float::format_float;
+use crate::runtime::value::{float_power, integer_power};
+use crate::runtime::EloValue;
+use std::fmt;
+
+/// Minimum number of distinct literals for `in` to use a lookup table
+pub const MEMBERSHIP_TABLE_THRESHOLD: usize = 16;
+
+/// Most distinct conditions a boolean expression may have to be minimized
+pub const DEFAULT_MINIMIZE_VARIABLES: usize = 8;
+
+/// Prefix of the `let` names that hold common subexpressions
+pub const CSE_PREFIX: &str = "__elo_cse_";
+
+/// Sorted, deduplicated literals of a large `x in [...]` set
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MembershipTable {
+    /// String literals
+    Strings(Vec<String>),
+    /// Integer literals
+    Integers(Vec<i64>),
+}
+
+impl MembershipTable {
+    /// Detect an array of at least [`MEMBERSHIP_TABLE_THRESHOLD`] distinct
+    /// literals of one type (after constant folding, so `-5` counts)
+    pub fn from_array(expr: &Expr) -> Option<Self> {
+        Self::from_literals(expr).filter(|table| table.len() >= MEMBERSHIP_TABLE_THRESHOLD)
+    }
+
+    /// Detect a non-empty array of literals of one type, whatever its size
+    ///
+    /// Sets below the table threshold compile to a `matches!` pattern.
+    pub fn from_literals(expr: &Expr) -> Option<Self> {
+        let Expr::Array(elements) = Optimizer::fold_constants(expr) else {
+            return None;
+        };
+        let table = match elements.first()? {
+            Expr::String(_) => {
+                let mut values = elements
+                    .into_iter()
+                    .map(|e| match e {
+                        Expr::String(s) => Some(s),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<String>>>()?;
+                values.sort();
+                values.dedup();
+                MembershipTable::Strings(values)
+            }
+            Expr::Literal(Literal::Integer(_)) => {
+                let mut values = elements
+                    .into_iter()
+                    .map(|e| match e {
+                        Expr::Literal(Literal::Integer(i)) => Some(i),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<i64>>>()?;
+                values.sort_unstable();
+                values.dedup();
+                MembershipTable::Integers(values)
+            }
+            _ => return None,
+        };
+        Some(table)
+    }
+
+    /// Number of distinct values
+    pub fn len(&self) -> usize {
+        match self {
+            MembershipTable::Strings(values) => values.len(),
+            MembershipTable::Integers(values) => values.len(),
+        }
+    }
+
+    /// Check if the table has no values
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Why a candidate fold was not applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The result does not fit in an `i64` or is not a finite float
+    Overflow,
+    /// Division or remainder by a literal zero, or a literal zero raised to
+    /// a negative power, is left for runtime
+    DivisionByZero,
+    /// At least one operand is only known at runtime
+    NonLiteralOperand,
+    /// The operator is not defined for these literal types
+    UnsupportedOperands,
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Overflow => write!(f, "overflow risk"),
+            Self::DivisionByZero => write!(f, "division by zero"),
+            Self::NonLiteralOperand => write!(f, "non-literal operand"),
+            Self::UnsupportedOperands => write!(f, "unsupported operand types"),
+        }
+    }
+}
+
+/// A fold the optimizer applied or considered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OptimizationStep {
+    /// The expression, as written before optimization
+    pub expression: String,
+    /// `None` if the fold fired, otherwise why it was skipped
+    pub skipped: Option<SkipReason>,
+}
+
+impl OptimizationStep {
+    /// Check if the fold was applied
+    pub fn fired(&self) -> bool {
+        self.skipped.is_none()
+    }
+}
+
+impl fmt::Display for OptimizationStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.skipped {
+            None => write!(f, "folded `{}`", self.expression),
+            Some(reason) => write!(f, "skipped `{}`: {}", self.expression, reason),
+        }
+    }
+}
+
+/// Record of the folds considered by [`Optimizer::optimize_with_report`]
+///
+/// # Example
+///
+/// ```
+/// use elo_rust::codegen::optimization::{Optimizer, SkipReason};
+/// use elo_rust::parser::Parser;
+///
+/// let expr = Parser::parse("age * (60 * 60) > 9223372036854775807 + 1").unwrap();
+/// let (_, report) = Optimizer::optimize_with_report(&expr);
+///
+/// assert_eq!(report.fired().count(), 1);
+/// let reasons: Vec<_> = report.skipped().filter_map(|step| step.skipped).collect();
+/// assert!(reasons.contains(&SkipReason::Overflow));
+/// assert!(reasons.contains(&SkipReason::NonLiteralOperand));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OptimizerReport {
+    steps: Vec<OptimizationStep>,
+}
 
-use crate::ast::{BinaryOperator, Expr, Literal, UnaryOperator};
+impl OptimizerReport {
+    /// All steps, in the order the optimizer visited them (innermost first)
+    pub fn steps(&self) -> &[OptimizationStep] {
+        &self.steps
+    }
+
+    /// Folds that were applied
+    pub fn fired(&self) -> impl Iterator<Item = &OptimizationStep> {
+        self.steps.iter().filter(|step| step.fired())
+    }
+
+    /// Folds that were considered but not applied
+    pub fn skipped(&self) -> impl Iterator<Item = &OptimizationStep> {
+        self.steps.iter().filter(|step| !step.fired())
+    }
+
+    /// Check if the optimizer considered nothing
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    fn record(&mut self, expression: String, skipped: Option<SkipReason>) {
+        self.steps.push(OptimizationStep {
+            expression,
+            skipped,
+        });
+    }
+}
+
+impl fmt::Display for OptimizerReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for step in &self.steps {
+            writeln!(f, "{}", step)?;
+        }
+        Ok(())
+    }
+}
+
+/// A step of [`Optimizer::optimize_with_passes`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Pass {
+    /// Evaluate operations on literals (`60 * 60` becomes `3600`)
+    ConstantFolding,
+    /// Keep only the taken branch of `if true`/`if false`, and the body of
+    /// a guard whose condition is `true`
+    DeadBranches,
+    /// Remove boolean identities: `x && true`, `x || false` and `!!x` become
+    /// `x`, while `false && x` and `true || x` become the literal
+    BooleanSimplification,
+    /// Minimize small `&&`/`||`/`!` trees (see [`Optimizer::minimize_booleans`])
+    BooleanMinimization,
+    /// Bind repeated pure calls to a `let` (see
+    /// [`Optimizer::eliminate_common_subexpressions`])
+    CommonSubexpressions,
+}
+
+impl Pass {
+    /// Every pass, in the order [`Optimizer::optimize`] runs them
+    pub const ALL: &'static [Pass] = &[
+        Pass::ConstantFolding,
+        Pass::DeadBranches,
+        Pass::BooleanSimplification,
+        Pass::BooleanMinimization,
+        Pass::CommonSubexpressions,
+    ];
+}
 
 /// Optimization context for code generation
 #[derive(Debug, Clone)]
@@ -17,114 +254,187 @@ impl Optimizer {
 
     /// Apply all optimizations to an expression
     pub fn optimize(expr: &Expr) -> Expr {
-        Self::fold_constants(expr)
+        Self::optimize_with_passes(expr, Pass::ALL)
     }
 
-    /// Constant folding: evaluate constant expressions at compile time
-    pub fn fold_constants(expr: &Expr) -> Expr {
-        match expr {
-            // Binary operations on literals can be folded
-            Expr::BinaryOp { op, left, right } => {
-                let left_folded = Self::fold_constants(left);
-                let right_folded = Self::fold_constants(right);
-
-                // Try to fold if both sides are literals
-                if let (Expr::Literal(left_lit), Expr::Literal(right_lit)) =
-                    (&left_folded, &right_folded)
-                {
-                    if let Some(folded) = Self::fold_binary_op(*op, left_lit, right_lit) {
-                        return folded;
-                    }
-                }
+    /// Apply the given passes, each once and in the given order
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use elo_rust::codegen::optimization::{Optimizer, Pass};
+    /// use elo_rust::parser::Parser;
+    ///
+    /// let expr = Parser::parse("if 1 < 2 then verified && true else false").unwrap();
+    /// assert_eq!(
+    ///     Optimizer::optimize_with_passes(&expr, &[Pass::ConstantFolding, Pass::DeadBranches]),
+    ///     Parser::parse("verified && true").unwrap()
+    /// );
+    /// assert_eq!(Optimizer::optimize(&expr), Parser::parse("verified").unwrap());
+    /// ```
+    pub fn optimize_with_passes(expr: &Expr, passes: &[Pass]) -> Expr {
+        passes
+            .iter()
+            .fold(expr.clone(), |expr, pass| Self::run_pass(*pass, expr, None))
+    }
 
-                Expr::BinaryOp {
-                    op: *op,
-                    left: Box::new(left_folded),
-                    right: Box::new(right_folded),
+    /// Apply all optimizations and report which folds fired or were skipped
+    pub fn optimize_with_report(expr: &Expr) -> (Expr, OptimizerReport) {
+        let mut report = OptimizerReport::default();
+        let optimized = Pass::ALL.iter().fold(expr.clone(), |expr, pass| {
+            Self::run_pass(*pass, expr, Some(&mut report))
+        });
+        (optimized, report)
+    }
+
+    /// Run one pass, recording constant folds into `report` if given
+    fn run_pass(pass: Pass, expr: Expr, report: Option<&mut OptimizerReport>) -> Expr {
+        match pass {
+            Pass::ConstantFolding => match report {
+                Some(report) => {
+                    let mut folder = ConstantFolder {
+                        report: Some(std::mem::take(report)),
+                    };
+                    let folded = folder.fold_expr(expr);
+                    *report = folder.report.unwrap_or_default();
+                    folded
                 }
+                None => ConstantFolder { report: None }.fold_expr(expr),
+            },
+            Pass::DeadBranches => DeadBranchEliminator.fold_expr(expr),
+            Pass::BooleanSimplification => BooleanSimplifier.fold_expr(expr),
+            Pass::BooleanMinimization => BooleanMinimizer {
+                max_variables: DEFAULT_MINIMIZE_VARIABLES,
             }
+            .fold_expr(expr),
+            Pass::CommonSubexpressions => Self::eliminate_common_subexpressions(&expr, |_| true),
+        }
+    }
 
-            // Unary operations on literals can be folded
-            Expr::UnaryOp { op, operand } => {
-                let operand_folded = Self::fold_constants(operand);
-
-                if let Expr::Literal(lit) = &operand_folded {
-                    if let Some(folded) = Self::fold_unary_op(*op, lit) {
-                        return folded;
+    /// Bind function calls that occur more than once to `let` variables
+    ///
+    /// A call is shared when it is pure: its functions are pure in the
+    /// [stdlib model](crate::stdlib::is_pure), it holds no `require`,
+    /// `warn_if`, `guard` or temporal keyword, and no division that could
+    /// fail. It must also not read names bound by an enclosing `let` or
+    /// lambda, and `eligible` must accept it. Repeated calls, largest
+    /// first, are bound to `__elo_cse_<n>` variables wrapping the whole
+    /// expression, so a shared call is evaluated once even where `&&` or
+    /// `||` would have skipped it. Pipes are desugared to calls first.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use elo_rust::ast::printer;
+    /// use elo_rust::codegen::optimization::Optimizer;
+    /// use elo_rust::parser::Parser;
+    ///
+    /// let expr = Parser::parse("length(trim(name)) >= 3 && length(trim(name)) <= 20").unwrap();
+    /// let shared = Optimizer::eliminate_common_subexpressions(&expr, |_| true);
+    /// assert_eq!(
+    ///     printer::format(&shared),
+    ///     "let `__elo_cse_0` = length(trim(name)) in `__elo_cse_0` >= 3 && `__elo_cse_0` <= 20"
+    /// );
+    /// ```
+    pub fn eliminate_common_subexpressions(expr: &Expr, eligible: impl Fn(&Expr) -> bool) -> Expr {
+        let mut expr = PipeDesugarer.fold_expr(expr.clone());
+        let mut next = 0;
+        loop {
+            let mut counts: Vec<(Expr, usize)> = Vec::new();
+            count_shared_calls(&expr, &mut Vec::new(), &eligible, &mut counts);
+            let Some(common) = counts
+                .into_iter()
+                .filter(|(_, count)| *count > 1)
+                .map(|(call, _)| call)
+                .reduce(|best, call| {
+                    if call.node_count() > best.node_count() {
+                        call
+                    } else {
+                        best
                     }
+                })
+            else {
+                return expr;
+            };
+            let name = loop {
+                let name = format!("{}{}", CSE_PREFIX, next);
+                next += 1;
+                if !binds_or_reads(&expr, &name) {
+                    break name;
                 }
-
-                Expr::UnaryOp {
-                    op: *op,
-                    operand: Box::new(operand_folded),
-                }
-            }
-
-            // Recursively fold expressions in containers
-            Expr::Array(elements) => {
-                let folded: Vec<Expr> = elements.iter().map(Self::fold_constants).collect();
-                Expr::Array(folded)
-            }
-
-            Expr::Object(fields) => {
-                let folded: Vec<(String, Expr)> = fields
-                    .iter()
-                    .map(|(k, v)| (k.clone(), Self::fold_constants(v)))
-                    .collect();
-                Expr::Object(folded)
+            };
+            let body = SharedCallReplacer {
+                common: &common,
+                name: &name,
+                bound: Vec::new(),
             }
+            .fold_expr(expr);
+            expr = Expr::Let {
+                name,
+                value: Box::new(common),
+                body: Box::new(body),
+            };
+        }
+    }
 
-            Expr::FieldAccess { receiver, field } => Expr::FieldAccess {
-                receiver: Box::new(Self::fold_constants(receiver)),
-                field: field.clone(),
-            },
-
-            Expr::FunctionCall { name, args } => Expr::FunctionCall {
-                name: name.clone(),
-                args: args.iter().map(Self::fold_constants).collect(),
-            },
-
-            Expr::Lambda { param, body } => Expr::Lambda {
-                param: param.clone(),
-                body: Box::new(Self::fold_constants(body)),
-            },
-
-            Expr::Let { name, value, body } => Expr::Let {
-                name: name.clone(),
-                value: Box::new(Self::fold_constants(value)),
-                body: Box::new(Self::fold_constants(body)),
-            },
-
-            Expr::If {
-                condition,
-                then_branch,
-                else_branch,
-            } => Expr::If {
-                condition: Box::new(Self::fold_constants(condition)),
-                then_branch: Box::new(Self::fold_constants(then_branch)),
-                else_branch: Box::new(Self::fold_constants(else_branch)),
-            },
-
-            Expr::Pipe { value, functions } => Expr::Pipe {
-                value: Box::new(Self::fold_constants(value)),
-                functions: functions.iter().map(Self::fold_constants).collect(),
-            },
-
-            Expr::Alternative {
-                primary,
-                alternative,
-            } => Expr::Alternative {
-                primary: Box::new(Self::fold_constants(primary)),
-                alternative: Box::new(Self::fold_constants(alternative)),
-            },
+    /// Simplify `&&` / `||` / `!` trees over at most `max_variables` conditions
+    ///
+    /// Each maximal boolean sub-expression is treated as a function of its
+    /// distinct conditions (comparisons, identifiers, calls, ...), which are
+    /// assumed to be free of side effects. The smaller of its minimal
+    /// sum-of-products and product-of-sums forms replaces it only if it
+    /// mentions fewer conditions; conditions keep their original order.
+    /// Trees containing `require`, `warn_if` or `guard` are left alone since
+    /// dropping them would drop their messages.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use elo_rust::codegen::optimization::Optimizer;
+    /// use elo_rust::parser::Parser;
+    ///
+    /// let expr = Parser::parse("age >= 18 && (age >= 18 || verified)").unwrap();
+    /// assert_eq!(
+    ///     Optimizer::minimize_booleans(&expr, 8),
+    ///     Parser::parse("age >= 18").unwrap()
+    /// );
+    /// ```
+    pub fn minimize_booleans(expr: &Expr, max_variables: usize) -> Expr {
+        BooleanMinimizer { max_variables }.fold_expr(expr.clone())
+    }
 
-            Expr::Guard { condition, body } => Expr::Guard {
-                condition: Box::new(Self::fold_constants(condition)),
-                body: Box::new(Self::fold_constants(body)),
-            },
+    /// Constant folding: evaluate constant expressions at compile time
+    pub fn fold_constants(expr: &Expr) -> Expr {
+        ConstantFolder { report: None }.fold_expr(expr.clone())
+    }
 
-            // Literals and identifiers cannot be folded further
-            expr => expr.clone(),
+    /// Explain why a binary operation on two literals did not fold
+    fn binary_skip_reason(op: BinaryOperator, left: &Literal, right: &Literal) -> SkipReason {
+        match (left, right, op) {
+            (
+                Literal::Integer(_),
+                Literal::Integer(0),
+                BinaryOperator::Div | BinaryOperator::Mod,
+            ) => SkipReason::DivisionByZero,
+            (Literal::Integer(0), Literal::Integer(r), BinaryOperator::Pow) if *r < 0 => {
+                SkipReason::DivisionByZero
+            }
+            (Literal::Float(_), Literal::Float(r), BinaryOperator::Div | BinaryOperator::Mod)
+                if *r == 0.0 =>
+            {
+                SkipReason::DivisionByZero
+            }
+            (
+                Literal::Integer(_),
+                Literal::Integer(_),
+                BinaryOperator::Add
+                | BinaryOperator::Sub
+                | BinaryOperator::Mul
+                | BinaryOperator::Div
+                | BinaryOperator::Pow,
+            ) => SkipReason::Overflow,
+            (Literal::Float(_), Literal::Float(_), BinaryOperator::Pow) => SkipReason::Overflow,
+            _ => SkipReason::UnsupportedOperands,
         }
     }
 
@@ -150,13 +460,13 @@ impl Optimizer {
                         Some(Expr::Literal(Literal::Integer(result)))
                     }
                     BinaryOperator::Mod if *r != 0 => Some(Expr::Literal(Literal::Integer(l % r))),
-                    BinaryOperator::Pow => {
-                        if *r < 0 || *r > 31 {
-                            return None; // Can't fold negative or large exponents
+                    // `0 ^ -1` divides by zero at runtime; left unfolded
+                    BinaryOperator::Pow => match integer_power(*l, *r) {
+                        Ok(EloValue::Integer(result)) => {
+                            Some(Expr::Literal(Literal::Integer(result)))
                         }
-                        let result = l.checked_pow(*r as u32)?;
-                        Some(Expr::Literal(Literal::Integer(result)))
-                    }
+                        _ => None,
+                    },
                     BinaryOperator::Eq => Some(Expr::Literal(Literal::Boolean(l == r))),
                     BinaryOperator::Neq => Some(Expr::Literal(Literal::Boolean(l != r))),
                     BinaryOperator::Lt => Some(Expr::Literal(Literal::Boolean(l < r))),
@@ -175,7 +485,7 @@ impl Optimizer {
                     BinaryOperator::Mul => l * r,
                     BinaryOperator::Div if *r != 0.0 => l / r,
                     BinaryOperator::Mod if *r != 0.0 => l % r,
-                    BinaryOperator::Pow => l.powf(*r),
+                    BinaryOperator::Pow => float_power(*l, *r).ok()?,
                     BinaryOperator::Eq => {
                         return Some(Expr::Literal(Literal::Boolean(
                             (l - r).abs() < f64::EPSILON,
@@ -218,7 +528,7 @@ impl Optimizer {
                 }
             }
             UnaryOperator::Neg => match lit {
-                Literal::Integer(n) => Some(Expr::Literal(Literal::Integer(-n))),
+                Literal::Integer(n) => Some(Expr::Literal(Literal::Integer(n.checked_neg()?))),
                 Literal::Float(f) => Some(Expr::Literal(Literal::Float(-f))),
                 _ => None,
             },
@@ -231,6 +541,594 @@ impl Optimizer {
     }
 }
 
+/// Bottom-up constant folding pass
+struct ConstantFolder {
+    /// Where to record folds, if the caller asked for a report
+    report: Option<OptimizerReport>,
+}
+
+impl ConstantFolder {
+    fn record(&mut self, describe: impl FnOnce() -> String, skipped: Option<SkipReason>) {
+        if let Some(report) = &mut self.report {
+            report.record(describe(), skipped);
+        }
+    }
+}
+
+impl Fold for ConstantFolder {
+    fn fold_expr(&mut self, expr: Expr) -> Expr {
+        match fold_children(self, expr) {
+            // Binary operations on literals can be folded
+            Expr::BinaryOp { op, left, right } => {
+                let (folded, skipped) = match (&*left, &*right) {
+                    (Expr::Literal(l), Expr::Literal(r)) => {
+                        match Optimizer::fold_binary_op(op, l, r) {
+                            Some(folded) => (Some(folded), None),
+                            None => (None, Some(Optimizer::binary_skip_reason(op, l, r))),
+                        }
+                    }
+                    _ => (None, Some(SkipReason::NonLiteralOperand)),
+                };
+                self.record(
+                    || format!("{} {} {}", describe(&left), op, describe(&right)),
+                    skipped,
+                );
+                folded.unwrap_or(Expr::BinaryOp { op, left, right })
+            }
+
+            // Unary operations on literals can be folded
+            Expr::UnaryOp { op, operand } => {
+                let (folded, skipped) = match &*operand {
+                    Expr::Literal(lit) => match Optimizer::fold_unary_op(op, lit) {
+                        Some(folded) => (Some(folded), None),
+                        None if matches!(lit, Literal::Integer(_)) => {
+                            (None, Some(SkipReason::Overflow))
+                        }
+                        None => (None, Some(SkipReason::UnsupportedOperands)),
+                    },
+                    _ => (None, Some(SkipReason::NonLiteralOperand)),
+                };
+                self.record(|| describe_unary(op, &operand), skipped);
+                folded.unwrap_or(Expr::UnaryOp { op, operand })
+            }
+
+            // A percentage is the fraction it stands for, which may fold further up
+            Expr::Percentage(percent) => Expr::Literal(Literal::Float(percent / 100.0)),
+
+            // A null value always falls back to the default
+            Expr::NullDefault { value, default } if *value == Expr::Null => {
+                self.record(|| format!("null ?? {}", describe(&default)), None);
+                *default
+            }
+
+            expr => expr,
+        }
+    }
+}
+
+/// Short rendering of an operand for reports
+fn describe(expr: &Expr) -> String {
+    match expr {
+        Expr::Literal(Literal::Integer(n)) => n.to_string(),
+        Expr::Literal(Literal::Float(f)) => format_float(*f),
+        Expr::Literal(Literal::Boolean(b)) => b.to_string(),
+        Expr::Identifier(name) => name.clone(),
+        Expr::FieldAccess { receiver, field } => format!("{}.{}", describe(receiver), field),
+        Expr::BinaryOp { op, left, right } => {
+            format!("({} {} {})", describe(left), op, describe(right))
+        }
+        Expr::UnaryOp { op, operand } => describe_unary(*op, operand),
+        _ => "...".to_string(),
+    }
+}
+
+fn describe_unary(op: UnaryOperator, operand: &Expr) -> String {
+    let operand = describe(operand);
+    if operand.starts_with('-') {
+        format!("{}({})", op, operand)
+    } else {
+        format!("{}{}", op, operand)
+    }
+}
+
+/// Bottom-up removal of branches behind constant conditions
+struct DeadBranchEliminator;
+
+impl Fold for DeadBranchEliminator {
+    fn fold_expr(&mut self, expr: Expr) -> Expr {
+        match fold_children(self, expr) {
+            Expr::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => match *condition {
+                Expr::Literal(Literal::Boolean(true)) => *then_branch,
+                Expr::Literal(Literal::Boolean(false)) => *else_branch,
+                condition => Expr::If {
+                    condition: Box::new(condition),
+                    then_branch,
+                    else_branch,
+                },
+            },
+            // A guard that always fails is kept: it reports its failure
+            Expr::Guard { condition, body } if *condition == TRUE => *body,
+            expr => expr,
+        }
+    }
+}
+
+/// Bottom-up removal of boolean identities
+///
+/// Operands are only dropped when they have no side effects, so a
+/// `require` in `require x else 'msg' && false` is still reported.
+struct BooleanSimplifier;
+
+impl Fold for BooleanSimplifier {
+    fn fold_expr(&mut self, expr: Expr) -> Expr {
+        match fold_children(self, expr) {
+            Expr::BinaryOp { op, left, right } => {
+                let absorbing = match op {
+                    BinaryOperator::And => FALSE,
+                    BinaryOperator::Or => TRUE,
+                    _ => return Expr::BinaryOp { op, left, right },
+                };
+                match (*left, *right) {
+                    // The identity element leaves the other operand
+                    (left, right) if left == identity(op) => right,
+                    (left, right) if right == identity(op) => left,
+                    // The right operand is never evaluated
+                    (left, _) if left == absorbing => absorbing,
+                    (left, right) if right == absorbing && !has_side_effects(&left) => right,
+                    (left, right) => Expr::BinaryOp {
+                        op,
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    },
+                }
+            }
+            Expr::UnaryOp {
+                op: UnaryOperator::Not,
+                operand,
+            } => match *operand {
+                Expr::UnaryOp {
+                    op: UnaryOperator::Not,
+                    operand,
+                } => *operand,
+                operand => Expr::UnaryOp {
+                    op: UnaryOperator::Not,
+                    operand: Box::new(operand),
+                },
+            },
+            expr => expr,
+        }
+    }
+}
+
+const TRUE: Expr = Expr::Literal(Literal::Boolean(true));
+const FALSE: Expr = Expr::Literal(Literal::Boolean(false));
+
+/// Operand of `&&` (`true`) or `||` (`false`) that leaves the other unchanged
+fn identity(op: BinaryOperator) -> Expr {
+    if op == BinaryOperator::And {
+        TRUE
+    } else {
+        FALSE
+    }
+}
+
+/// Top-down rewriting of pipes into the calls they stand for
+struct PipeDesugarer;
+
+impl Fold for PipeDesugarer {
+    fn fold_expr(&mut self, expr: Expr) -> Expr {
+        match expr {
+            // Stages are functions rather than values, so they are rewritten
+            // together with the piped value
+            Expr::Pipe { value, functions } => match Expr::desugar_pipe(&value, &functions) {
+                Ok(desugared) => self.fold_expr(desugared),
+                Err(_) => Expr::Pipe { value, functions },
+            },
+            expr => fold_children(self, expr),
+        }
+    }
+}
+
+/// Count the calls that may be shared, by structural equality
+fn count_shared_calls(
+    expr: &Expr,
+    bound: &mut Vec<String>,
+    eligible: &impl Fn(&Expr) -> bool,
+    counts: &mut Vec<(Expr, usize)>,
+) {
+    match expr {
+        Expr::Let { name, value, body } => {
+            count_shared_calls(value, bound, eligible, counts);
+            bound.push(name.clone());
+            count_shared_calls(body, bound, eligible, counts);
+            bound.pop();
+        }
+        Expr::Lambda { param, body } => {
+            bound.push(param.clone());
+            count_shared_calls(body, bound, eligible, counts);
+            bound.pop();
+        }
+        _ => {
+            if matches!(expr, Expr::FunctionCall { .. })
+                && is_pure(expr)
+                && reads_only_free(expr, bound)
+                && eligible(expr)
+            {
+                match counts.iter_mut().find(|(call, _)| call == expr) {
+                    Some((_, count)) => *count += 1,
+                    None => counts.push((expr.clone(), 1)),
+                }
+            }
+            for child in expr.children() {
+                count_shared_calls(child, bound, eligible, counts);
+            }
+        }
+    }
+}
+
+/// Whether an expression may be evaluated once, ahead of where it occurs
+fn is_pure(expr: &Expr) -> bool {
+    let pure = match expr {
+        Expr::Require { .. }
+        | Expr::WarnIf { .. }
+        | Expr::Guard { .. }
+        | Expr::TemporalKeyword(_) => false,
+        Expr::FunctionCall { name, .. } => crate::stdlib::is_pure(name),
+        // Division by zero would fail where a guard before it kept it from running
+        Expr::BinaryOp {
+            op: BinaryOperator::Div | BinaryOperator::Mod,
+            ..
+        } => false,
+        _ => true,
+    };
+    pure && expr.children().into_iter().all(is_pure)
+}
+
+/// Whether none of the names `expr` reads are bound by an enclosing scope
+fn reads_only_free(expr: &Expr, bound: &[String]) -> bool {
+    expr.free_variables()
+        .iter()
+        .all(|name| !bound.contains(name))
+}
+
+/// Whether `name` is read or bound anywhere in `expr`
+fn binds_or_reads(expr: &Expr, name: &str) -> bool {
+    let here = match expr {
+        Expr::Identifier(identifier) => identifier == name,
+        Expr::Let { name: bound, .. } | Expr::Lambda { param: bound, .. } => bound == name,
+        _ => false,
+    };
+    here || expr
+        .children()
+        .into_iter()
+        .any(|child| binds_or_reads(child, name))
+}
+
+/// Replaces the occurrences of a shared call outside the scopes that
+/// rebind the names it reads
+struct SharedCallReplacer<'a> {
+    common: &'a Expr,
+    name: &'a str,
+    bound: Vec<String>,
+}
+
+impl Fold for SharedCallReplacer<'_> {
+    fn fold_expr(&mut self, expr: Expr) -> Expr {
+        if expr == *self.common && reads_only_free(&expr, &self.bound) {
+            return Expr::Identifier(self.name.to_string());
+        }
+        match expr {
+            Expr::Let { name, value, body } => {
+                let value = self.fold_expr(*value);
+                self.bound.push(name.clone());
+                let body = self.fold_expr(*body);
+                self.bound.pop();
+                Expr::Let {
+                    name,
+                    value: Box::new(value),
+                    body: Box::new(body),
+                }
+            }
+            Expr::Lambda { param, body } => {
+                self.bound.push(param.clone());
+                let body = self.fold_expr(*body);
+                self.bound.pop();
+                Expr::Lambda {
+                    param,
+                    body: Box::new(body),
+                }
+            }
+            expr => fold_children(self, expr),
+        }
+    }
+}
+
+/// Quine–McCluskey minimization of boolean sub-expressions
+struct BooleanMinimizer {
+    max_variables: usize,
+}
+
+impl Fold for BooleanMinimizer {
+    fn fold_expr(&mut self, expr: Expr) -> Expr {
+        if !is_boolean_connective(&expr) {
+            return fold_children(self, expr);
+        }
+
+        // Minimize inside the conditions first, then the tree over them
+        let expr = fold_atoms(self, expr);
+        let mut atoms = Vec::new();
+        collect_atoms(&expr, &mut atoms);
+        if atoms.len() > self.max_variables || atoms.iter().any(has_side_effects) {
+            return expr;
+        }
+
+        let rows = 1u32 << atoms.len();
+        let (ones, zeros): (Vec<u32>, Vec<u32>) =
+            (0..rows).partition(|&row| evaluate(&expr, &atoms, row));
+        if zeros.is_empty() {
+            return Expr::Literal(Literal::Boolean(true));
+        }
+        if ones.is_empty() {
+            return Expr::Literal(Literal::Boolean(false));
+        }
+
+        let sum_of_products = minimal_cover(&ones, atoms.len());
+        let product_of_sums = minimal_cover(&zeros, atoms.len());
+        let (implicants, conjunctive) =
+            if literal_count(&product_of_sums) < literal_count(&sum_of_products) {
+                (product_of_sums, true)
+            } else {
+                (sum_of_products, false)
+            };
+        if literal_count(&implicants) >= occurrence_count(&expr) {
+            return expr;
+        }
+        build_expression(&implicants, &atoms, conjunctive)
+    }
+}
+
+fn is_boolean_connective(expr: &Expr) -> bool {
+    matches!(
+        expr,
+        Expr::BinaryOp {
+            op: BinaryOperator::And | BinaryOperator::Or,
+            ..
+        } | Expr::UnaryOp {
+            op: UnaryOperator::Not,
+            ..
+        }
+    )
+}
+
+/// Apply the minimizer to the conditions of a boolean tree
+fn fold_atoms(minimizer: &mut BooleanMinimizer, expr: Expr) -> Expr {
+    match expr {
+        Expr::BinaryOp { op, left, right } if is_boolean_connective_op(op) => Expr::BinaryOp {
+            op,
+            left: Box::new(fold_atoms(minimizer, *left)),
+            right: Box::new(fold_atoms(minimizer, *right)),
+        },
+        Expr::UnaryOp {
+            op: UnaryOperator::Not,
+            operand,
+        } => Expr::UnaryOp {
+            op: UnaryOperator::Not,
+            operand: Box::new(fold_atoms(minimizer, *operand)),
+        },
+        Expr::Literal(Literal::Boolean(_)) => expr,
+        atom => fold_children(minimizer, atom),
+    }
+}
+
+fn is_boolean_connective_op(op: BinaryOperator) -> bool {
+    matches!(op, BinaryOperator::And | BinaryOperator::Or)
+}
+
+/// Distinct conditions of a boolean tree, in order of first occurrence
+fn collect_atoms(expr: &Expr, atoms: &mut Vec<Expr>) {
+    match expr {
+        Expr::BinaryOp { op, left, right } if is_boolean_connective_op(*op) => {
+            collect_atoms(left, atoms);
+            collect_atoms(right, atoms);
+        }
+        Expr::UnaryOp {
+            op: UnaryOperator::Not,
+            operand,
+        } => collect_atoms(operand, atoms),
+        Expr::Literal(Literal::Boolean(_)) => {}
+        atom => {
+            if !atoms.contains(atom) {
+                atoms.push(atom.clone());
+            }
+        }
+    }
+}
+
+/// Number of condition occurrences in a boolean tree
+fn occurrence_count(expr: &Expr) -> usize {
+    match expr {
+        Expr::BinaryOp { op, left, right } if is_boolean_connective_op(*op) => {
+            occurrence_count(left) + occurrence_count(right)
+        }
+        Expr::UnaryOp {
+            op: UnaryOperator::Not,
+            operand,
+        } => occurrence_count(operand),
+        Expr::Literal(Literal::Boolean(_)) => 0,
+        _ => 1,
+    }
+}
+
+fn has_side_effects(expr: &Expr) -> bool {
+    matches!(
+        expr,
+        Expr::Require { .. } | Expr::WarnIf { .. } | Expr::Guard { .. }
+    ) || expr.children().into_iter().any(has_side_effects)
+}
+
+/// Value of a boolean tree when condition `i` has the value of bit `i` of `row`
+fn evaluate(expr: &Expr, atoms: &[Expr], row: u32) -> bool {
+    match expr {
+        Expr::BinaryOp {
+            op: BinaryOperator::And,
+            left,
+            right,
+        } => evaluate(left, atoms, row) && evaluate(right, atoms, row),
+        Expr::BinaryOp {
+            op: BinaryOperator::Or,
+            left,
+            right,
+        } => evaluate(left, atoms, row) || evaluate(right, atoms, row),
+        Expr::UnaryOp {
+            op: UnaryOperator::Not,
+            operand,
+        } => !evaluate(operand, atoms, row),
+        Expr::Literal(Literal::Boolean(b)) => *b,
+        atom => {
+            let index = atoms.iter().position(|a| a == atom).unwrap_or(0);
+            row & (1 << index) != 0
+        }
+    }
+}
+
+/// A product term: bits in `mask` are don't-cares, the others equal `value`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Implicant {
+    value: u32,
+    mask: u32,
+}
+
+impl Implicant {
+    fn covers(&self, row: u32) -> bool {
+        row & !self.mask == self.value
+    }
+
+    fn literals(&self, variables: usize) -> usize {
+        variables - self.mask.count_ones() as usize
+    }
+}
+
+fn literal_count(implicants: &[(Implicant, usize)]) -> usize {
+    implicants.iter().map(|(_, literals)| literals).sum()
+}
+
+/// Prime implicants of `rows`, then a small cover: essential primes first,
+/// then greedily the prime covering most remaining rows
+fn minimal_cover(rows: &[u32], variables: usize) -> Vec<(Implicant, usize)> {
+    let mut current: Vec<Implicant> = rows
+        .iter()
+        .map(|&value| Implicant { value, mask: 0 })
+        .collect();
+    let mut primes = Vec::new();
+    while !current.is_empty() {
+        let mut merged = vec![false; current.len()];
+        let mut next: Vec<Implicant> = Vec::new();
+        for i in 0..current.len() {
+            for j in i + 1..current.len() {
+                let (a, b) = (current[i], current[j]);
+                let diff = a.value ^ b.value;
+                if a.mask == b.mask && diff.count_ones() == 1 {
+                    merged[i] = true;
+                    merged[j] = true;
+                    let combined = Implicant {
+                        value: a.value & !diff,
+                        mask: a.mask | diff,
+                    };
+                    if !next.contains(&combined) {
+                        next.push(combined);
+                    }
+                }
+            }
+        }
+        primes.extend(
+            current
+                .iter()
+                .zip(&merged)
+                .filter(|(_, merged)| !**merged)
+                .map(|(implicant, _)| *implicant),
+        );
+        current = next;
+    }
+
+    let mut uncovered: Vec<u32> = rows.to_vec();
+    let mut cover: Vec<Implicant> = Vec::new();
+    for &row in rows {
+        let covering: Vec<&Implicant> = primes.iter().filter(|p| p.covers(row)).collect();
+        if let [only] = covering.as_slice() {
+            if !cover.contains(only) {
+                cover.push(**only);
+            }
+        }
+    }
+    uncovered.retain(|&row| !cover.iter().any(|p| p.covers(row)));
+    while !uncovered.is_empty() {
+        let best = primes
+            .iter()
+            .max_by_key(|p| {
+                let gain = uncovered.iter().filter(|&&row| p.covers(row)).count();
+                (gain, std::cmp::Reverse(p.literals(variables)))
+            })
+            .copied();
+        let Some(best) = best else { break };
+        uncovered.retain(|&row| !best.covers(row));
+        cover.push(best);
+    }
+
+    // Emit terms in the order their first condition appears
+    cover.sort_by_key(|p| (!p.mask).trailing_zeros());
+    cover
+        .into_iter()
+        .map(|p| (p, p.literals(variables)))
+        .collect()
+}
+
+/// Rebuild an expression from a cover
+///
+/// A sum of products covers the rows where the tree is true; with
+/// `conjunctive` the cover is of the false rows and is negated into a
+/// product of sums.
+fn build_expression(implicants: &[(Implicant, usize)], atoms: &[Expr], conjunctive: bool) -> Expr {
+    let (inner, outer) = if conjunctive {
+        (BinaryOperator::Or, BinaryOperator::And)
+    } else {
+        (BinaryOperator::And, BinaryOperator::Or)
+    };
+    let terms = implicants.iter().map(|(implicant, _)| {
+        let literals = atoms.iter().enumerate().filter_map(|(i, atom)| {
+            let bit = 1 << i;
+            if implicant.mask & bit != 0 {
+                return None;
+            }
+            // In a product of sums each literal is negated
+            let positive = (implicant.value & bit != 0) != conjunctive;
+            Some(if positive {
+                atom.clone()
+            } else {
+                Expr::UnaryOp {
+                    op: UnaryOperator::Not,
+                    operand: Box::new(atom.clone()),
+                }
+            })
+        });
+        join(literals, inner)
+    });
+    join(terms, outer)
+}
+
+fn join(exprs: impl Iterator<Item = Expr>, op: BinaryOperator) -> Expr {
+    exprs
+        .reduce(|left, right| Expr::BinaryOp {
+            op,
+            left: Box::new(left),
+            right: Box::new(right),
+        })
+        .unwrap_or(Expr::Literal(Literal::Boolean(op == BinaryOperator::And)))
+}
+
 impl Default for Optimizer {
     fn default() -> Self {
         Self::new()
@@ -435,6 +1333,42 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_membership_table_detection() {
+        let codes: Vec<String> = (0..20).rev().map(|i| format!("'C{:02}'", i)).collect();
+        let expr = crate::parser::Parser::parse(&format!("[{}, 'C00']", codes.join(", "))).unwrap();
+        match MembershipTable::from_array(&expr) {
+            Some(MembershipTable::Strings(values)) => {
+                assert_eq!(values.len(), 20);
+                assert_eq!(values[0], "C00");
+            }
+            other => panic!("expected string table, got {:?}", other),
+        }
+
+        let numbers: Vec<String> = (-10..10).map(|i| i.to_string()).collect();
+        let expr = crate::parser::Parser::parse(&format!("[{}]", numbers.join(", "))).unwrap();
+        assert_eq!(
+            MembershipTable::from_array(&expr),
+            Some(MembershipTable::Integers((-10..10).collect()))
+        );
+
+        // Small, mixed or non-literal sets keep the plain codegen
+        let small = crate::parser::Parser::parse("['b', 'a', 'b']").unwrap();
+        assert_eq!(MembershipTable::from_array(&small), None);
+        assert_eq!(
+            MembershipTable::from_literals(&small),
+            Some(MembershipTable::Strings(vec![
+                "a".to_string(),
+                "b".to_string()
+            ]))
+        );
+        let empty = crate::parser::Parser::parse("[]").unwrap();
+        assert_eq!(MembershipTable::from_literals(&empty), None);
+        let mixed = format!("[{}, x]", numbers.join(", "));
+        let mixed = crate::parser::Parser::parse(&mixed).unwrap();
+        assert_eq!(MembershipTable::from_array(&mixed), None);
+    }
+
     #[test]
     fn test_fold_division_by_zero() {
         let expr = Expr::BinaryOp {
@@ -447,4 +1381,214 @@ mod tests {
         // Should not fold division by zero
         matches!(folded, Expr::BinaryOp { .. });
     }
+
+    #[test]
+    fn test_fold_negate_min_integer() {
+        let expr = Expr::UnaryOp {
+            op: UnaryOperator::Neg,
+            operand: Box::new(Expr::Literal(Literal::Integer(i64::MIN))),
+        };
+        let (folded, report) = Optimizer::optimize_with_report(&expr);
+        assert_eq!(folded, expr);
+        assert_eq!(
+            report.steps()[0].to_string(),
+            "skipped `-(-9223372036854775808)`: overflow risk"
+        );
+    }
+
+    #[test]
+    fn test_fold_power_follows_runtime_policy() {
+        let fold =
+            |source: &str| Optimizer::optimize(&crate::parser::Parser::parse(source).unwrap());
+        assert_eq!(fold("2 ^ 40"), Expr::Literal(Literal::Integer(1 << 40)));
+        assert_eq!(fold("1 ^ 5000000000"), Expr::Literal(Literal::Integer(1)));
+        assert_eq!(fold("2 ^ -1"), Expr::Literal(Literal::Integer(0)));
+        let (_, report) =
+            Optimizer::optimize_with_report(&crate::parser::Parser::parse("0 ^ -1").unwrap());
+        assert_eq!(
+            report.skipped().last().unwrap().skipped,
+            Some(SkipReason::DivisionByZero)
+        );
+        for unfolded in ["2 ^ 63", "10.0 ^ 400.0", "(0.0 - 8.0) ^ 0.5"] {
+            let (folded, report) =
+                Optimizer::optimize_with_report(&crate::parser::Parser::parse(unfolded).unwrap());
+            assert!(matches!(folded, Expr::BinaryOp { .. }), "{}", unfolded);
+            assert_eq!(
+                report.skipped().last().unwrap().skipped,
+                Some(SkipReason::Overflow)
+            );
+        }
+    }
+
+    #[test]
+    fn test_optimizer_report() {
+        let expr = Expr::BinaryOp {
+            op: BinaryOperator::And,
+            left: Box::new(Expr::BinaryOp {
+                op: BinaryOperator::Div,
+                left: Box::new(Expr::Literal(Literal::Integer(1))),
+                right: Box::new(Expr::Literal(Literal::Integer(0))),
+            }),
+            right: Box::new(Expr::BinaryOp {
+                op: BinaryOperator::Add,
+                left: Box::new(Expr::Literal(Literal::Integer(2))),
+                right: Box::new(Expr::Literal(Literal::Integer(3))),
+            }),
+        };
+        let (_, report) = Optimizer::optimize_with_report(&expr);
+        assert_eq!(
+            report.to_string(),
+            "skipped `1 / 0`: division by zero\n\
+             folded `2 + 3`\n\
+             skipped `(1 / 0) && 5`: non-literal operand\n"
+        );
+        assert!(!report.is_empty());
+        assert_eq!(report.fired().count(), 1);
+    }
+
+    #[test]
+    fn test_minimize_absorption() {
+        let parse = |source| crate::parser::Parser::parse(source).unwrap();
+        assert_eq!(Optimizer::optimize(&parse("a && (a || b)")), parse("a"));
+        assert_eq!(
+            Optimizer::optimize(&parse("(a && b) || (a && !b)")),
+            parse("a")
+        );
+        assert_eq!(
+            Optimizer::optimize(&parse("x > 1 || !(x > 1)")),
+            Expr::Literal(Literal::Boolean(true))
+        );
+        // Already minimal trees keep their shape
+        assert_eq!(
+            Optimizer::optimize(&parse("a && b || c")),
+            parse("a && b || c")
+        );
+    }
+
+    #[test]
+    fn test_eliminate_dead_branches() {
+        let parse = |source| crate::parser::Parser::parse(source).unwrap();
+        let dead = |source| Optimizer::optimize_with_passes(&parse(source), &[Pass::DeadBranches]);
+        assert_eq!(dead("if true then a else b"), parse("a"));
+        assert_eq!(
+            dead("if false then a else if true then b else c"),
+            parse("b")
+        );
+        assert_eq!(dead("guard true in age > 18"), parse("age > 18"));
+        // Conditions are only taken as literals; folding them is another pass
+        assert_eq!(
+            dead("if 1 < 2 then a else b"),
+            parse("if 1 < 2 then a else b")
+        );
+        assert_eq!(
+            Optimizer::optimize(&parse("if 1 < 2 then a else b")),
+            parse("a")
+        );
+        // A guard that always fails still reports its failure
+        assert_eq!(dead("guard false in a"), parse("guard false in a"));
+    }
+
+    #[test]
+    fn test_simplify_booleans() {
+        let parse = |source| crate::parser::Parser::parse(source).unwrap();
+        let simplify = |source| {
+            Optimizer::optimize_with_passes(&parse(source), &[Pass::BooleanSimplification])
+        };
+        assert_eq!(simplify("verified && true"), parse("verified"));
+        assert_eq!(simplify("true && verified"), parse("verified"));
+        assert_eq!(simplify("verified || false"), parse("verified"));
+        assert_eq!(simplify("!!verified"), parse("verified"));
+        assert_eq!(simplify("!!!verified"), parse("!verified"));
+        assert_eq!(simplify("false && age > 18"), parse("false"));
+        assert_eq!(simplify("age > 18 || true"), parse("true"));
+        assert_eq!(simplify("(a || false) && (b && true)"), parse("a && b"));
+        // Operands with messages are kept even when the result is known
+        assert_eq!(
+            simplify("require a else 'needed' && false"),
+            parse("require a else 'needed' && false")
+        );
+    }
+
+    #[test]
+    fn test_eliminate_common_subexpressions() {
+        let parse = |source| crate::parser::Parser::parse(source).unwrap();
+        let share = |source| {
+            crate::ast::printer::format(&Optimizer::eliminate_common_subexpressions(
+                &parse(source),
+                |_| true,
+            ))
+        };
+        // The largest repeated call is bound first, then the calls inside it
+        assert_eq!(
+            share("length(trim(name)) > 3 && trim(name) != 'root' && length(trim(name)) < 9"),
+            "let `__elo_cse_1` = trim(name) in let `__elo_cse_0` = length(`__elo_cse_1`) in \
+             `__elo_cse_0` > 3 && `__elo_cse_1` != 'root' && `__elo_cse_0` < 9"
+        );
+        assert_eq!(
+            share("(name |> trim |> length()) > 3 && length(trim(name)) < 9"),
+            "let `__elo_cse_0` = length(trim(name)) in `__elo_cse_0` > 3 && `__elo_cse_0` < 9"
+        );
+        // Calls reading local names, impure calls and single calls stay put
+        for source in [
+            "let name = nick in length(name) > 3 && length(name) < 9",
+            "all(items, fn(i ~> length(i) > 3 && length(i) < 9))",
+            "age(birth) >= 18 && age(birth) < 65",
+            "length(name) > 3",
+            "abs(total / count) > 1 && abs(total / count) < 9",
+        ] {
+            assert_eq!(share(source), source);
+        }
+        // A call rebound in one place is only shared where it is not
+        assert_eq!(
+            share("length(name) > 3 && length(name) < 9 && (let name = nick in length(name) < 5)"),
+            "let `__elo_cse_0` = length(name) in \
+             `__elo_cse_0` > 3 && `__elo_cse_0` < 9 && (let name = nick in length(name) < 5)"
+        );
+        // Sharing again picks names that are not in use yet
+        let shared = Optimizer::eliminate_common_subexpressions(
+            &parse(
+                "trim(a) == 'x' && trim(a) != 'y' && (lowercase(b) == 'z' || lowercase(b) == 'w')",
+            ),
+            |call| matches!(call, Expr::FunctionCall { name, .. } if name == "trim"),
+        );
+        assert_eq!(
+            crate::ast::printer::format(&Optimizer::eliminate_common_subexpressions(&shared, |_| true)),
+            "let `__elo_cse_1` = lowercase(b) in let `__elo_cse_0` = trim(a) in \
+             `__elo_cse_0` == 'x' && `__elo_cse_0` != 'y' && (`__elo_cse_1` == 'z' || `__elo_cse_1` == 'w')"
+        );
+        let expr = parse("trim(a) == trim(a)");
+        assert_eq!(
+            Optimizer::eliminate_common_subexpressions(&expr, |_| false),
+            expr
+        );
+    }
+
+    #[test]
+    fn test_optimize_with_passes() {
+        let expr = crate::parser::Parser::parse("if 2 > 1 then a && (a || b) else c").unwrap();
+        assert_eq!(Optimizer::optimize_with_passes(&expr, &[]), expr);
+        assert_eq!(
+            Optimizer::optimize_with_passes(&expr, &[Pass::DeadBranches, Pass::ConstantFolding]),
+            crate::parser::Parser::parse("if true then a && (a || b) else c").unwrap()
+        );
+        assert_eq!(
+            Optimizer::optimize_with_passes(&expr, Pass::ALL),
+            Optimizer::optimize(&expr)
+        );
+        assert_eq!(
+            Optimizer::optimize(&expr),
+            crate::parser::Parser::parse("a").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_minimize_variable_limit() {
+        let source = "a && (a || b) && c";
+        let expr = crate::parser::Parser::parse(source).unwrap();
+        assert_eq!(Optimizer::minimize_booleans(&expr, 2), expr);
+        assert_eq!(
+            Optimizer::minimize_booleans(&expr, 3),
+            crate::parser::Parser::parse("a && c").unwrap()
+        );
+    }
 }
@@ -0,0 +1,87 @@
+//! Options for the style of generated validators
+//!
+//! [`CodegenOptions`] gathers the choices that shape generated code, passed
+//! to [`RustCodeGenerator::with_options`](super::RustCodeGenerator::with_options):
+//! how failures are reported, which date/time crate the code uses, whether
+//! it may name `std`, how panicking constructs and failed guards are
+//! treated, when large validators are split, how generated functions are
+//! named and whether they are documented. Every option defaults to the
+//! behaviour of a generator without options.
+//!
+//! # Example
+//!
+//! ```
+//! use elo_rust::codegen::{CodegenOptions, ErrorStrategy};
+//! use elo_rust::RustCodeGenerator;
+//!
+//! let generator = RustCodeGenerator::new().with_options(CodegenOptions {
+//!     error_strategy: ErrorStrategy::CollectAll,
+//!     ..CodegenOptions::default()
+//! });
+//! let code = generator
+//!     .generate_validator("check_user", "age >= 18 && verified", "User")
+//!     .unwrap()
+//!     .to_string();
+//! assert!(code.contains("ValidationError :: new (\"age\""));
+//! assert!(code.contains("ValidationError :: new (\"verified\""));
+//! ```
+
+use super::ast_to_code::GuardPolicy;
+use super::audit::PanicPolicy;
+
+/// How a validator whose expression is a chain of `&&` reports failures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorStrategy {
+    /// Stop at the first failed conjunct, like `&&`
+    ///
+    /// Without `require` messages, a failed validation reports one
+    /// "Validation failed" error for the whole expression.
+    #[default]
+    FailFast,
+    /// Check every conjunct and report each failed one
+    ///
+    /// A conjunct failing without a `require` message of its own is
+    /// reported as "Validation failed" under the field it reads, so
+    /// `age >= 18 && verified` can report both `age` and `verified`.
+    CollectAll,
+}
+
+/// The date/time crate generated code is written against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TemporalBackend {
+    /// `chrono`: dates are `NaiveDate`, timestamps `DateTime<Utc>`
+    #[default]
+    Chrono,
+    /// `time`: dates are `Date`, timestamps `OffsetDateTime`
+    ///
+    /// The current date is read in UTC, and `date(...)` needs the `parsing`
+    /// feature of `time`.
+    Time,
+}
+
+/// Code generation options of a [`RustCodeGenerator`](super::RustCodeGenerator)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CodegenOptions {
+    /// How failures of a top-level `&&` chain are reported
+    pub error_strategy: ErrorStrategy,
+    /// Which crate dates, times and durations are generated for
+    pub temporal_backend: TemporalBackend,
+    /// Name `core` and `alloc` items instead of `std` ones (see [`super::no_std`])
+    ///
+    /// Generation fails for expressions that need `std`, such as `matches`.
+    pub no_std: bool,
+    /// Whether generated code may contain panicking constructs (see [`super::audit`])
+    pub panic_policy: PanicPolicy,
+    /// What generated validators do when a `guard` condition fails
+    pub guard_policy: GuardPolicy,
+    /// Token count above which validators are split into helper functions
+    ///
+    /// `None` never splits them.
+    pub max_function_tokens: Option<usize>,
+    /// Prepended to the name of every generated function, e.g. `user_`
+    ///
+    /// Rule names in reported errors are not prefixed.
+    pub identifier_prefix: String,
+    /// Document each generated public function with its ELO expression
+    pub doc_comments: bool,
+}
@@ -0,0 +1,163 @@
+//! Conditional requiredness helpers
+//!
+//! Rewrites the cross-field presence sugar into `require` expressions built on
+//! the `is_present` runtime check:
+//!
+//! - `required_if(field, condition)`: `field` must be present when `condition` holds
+//! - `required_unless(field, condition)`: `field` must be present unless `condition` holds
+//! - `requires_with(field, other, ...)`: `field` must be present when any of the others is
+//!
+//! Each expansion carries a message naming the field and the triggering
+//! condition, e.g. "spouse_name is required when marital_status == 'married'".
+
+use super::float::format_float;
+use crate::ast::{BinaryOperator, Expr, Literal, UnaryOperator};
+
+/// Names of the presence sugar functions
+pub const PRESENCE_HELPERS: &[&str] = &["required_if", "required_unless", "requires_with"];
+
+/// Expand a presence helper call into a `require` expression
+///
+/// Returns `None` if `name` is not a presence helper or the arguments do not
+/// fit its signature.
+pub fn expand_call(name: &str, args: &[Expr]) -> Option<Expr> {
+    match (name, args) {
+        ("required_if", [field, condition]) => Some(require(
+            or(not(condition.clone()), present(field)),
+            format!(
+                "{} is required when {}",
+                describe(field),
+                describe(condition)
+            ),
+        )),
+        ("required_unless", [field, condition]) => Some(require(
+            or(condition.clone(), present(field)),
+            format!(
+                "{} is required unless {}",
+                describe(field),
+                describe(condition)
+            ),
+        )),
+        ("requires_with", [field, others @ ..]) => {
+            let any_present = others.iter().map(present).reduce(or)?;
+            let names: Vec<String> = others.iter().map(describe).collect();
+            let verb = if others.len() == 1 { "is" } else { "are" };
+            Some(require(
+                or(not(any_present), present(field)),
+                format!(
+                    "{} is required when {} {} present",
+                    describe(field),
+                    names.join(" or "),
+                    verb
+                ),
+            ))
+        }
+        _ => None,
+    }
+}
+
+fn present(expr: &Expr) -> Expr {
+    Expr::FunctionCall {
+        name: "is_present".to_string(),
+        args: vec![expr.clone()],
+    }
+}
+
+fn not(expr: Expr) -> Expr {
+    Expr::UnaryOp {
+        op: UnaryOperator::Not,
+        operand: Box::new(expr),
+    }
+}
+
+fn or(left: Expr, right: Expr) -> Expr {
+    Expr::BinaryOp {
+        op: BinaryOperator::Or,
+        left: Box::new(left),
+        right: Box::new(right),
+    }
+}
+
+fn require(condition: Expr, message: String) -> Expr {
+    Expr::Require {
+        condition: Box::new(condition),
+        message,
+    }
+}
+
+/// Render an expression for an error message, falling back to "condition"
+fn describe(expr: &Expr) -> String {
+    match expr {
+        Expr::Identifier(name) => name.clone(),
+        Expr::FieldAccess { receiver, field } => format!("{}.{}", describe(receiver), field),
+        Expr::String(s) => format!("'{}'", s),
+        Expr::Null => "null".to_string(),
+        Expr::Literal(Literal::Integer(n)) => n.to_string(),
+        Expr::Literal(Literal::Float(f)) => format_float(*f),
+        Expr::Percentage(percent) => format!("{}%", percent),
+        Expr::Literal(Literal::Boolean(b)) => b.to_string(),
+        Expr::BinaryOp { op, left, right } => {
+            format!("{} {} {}", describe(left), op, describe(right))
+        }
+        Expr::UnaryOp { op, operand } => format!("{}{}", op, describe(operand)),
+        _ => "condition".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+
+    fn expand(source: &str) -> Expr {
+        match Parser::parse(source).unwrap() {
+            Expr::FunctionCall { name, args } => expand_call(&name, &args).unwrap(),
+            other => panic!("expected a call, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_required_if_message() {
+        match expand("required_if(spouse_name, marital_status == 'married')") {
+            Expr::Require { message, .. } => assert_eq!(
+                message,
+                "spouse_name is required when marital_status == 'married'"
+            ),
+            other => panic!("expected require, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_required_unless_condition() {
+        match expand("required_unless(user.phone, user.has_email)") {
+            Expr::Require { condition, message } => {
+                assert_eq!(message, "user.phone is required unless user.has_email");
+                assert!(matches!(
+                    *condition,
+                    Expr::BinaryOp {
+                        op: BinaryOperator::Or,
+                        ..
+                    }
+                ));
+            }
+            other => panic!("expected require, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_requires_with_several_fields() {
+        match expand("requires_with(zip, street, city)") {
+            Expr::Require { message, .. } => {
+                assert_eq!(message, "zip is required when street or city are present")
+            }
+            other => panic!("expected require, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_invalid_arity_is_not_expanded() {
+        assert!(expand_call("required_if", &[Expr::Identifier("a".into())]).is_none());
+        assert!(expand_call("requires_with", &[Expr::Identifier("a".into())]).is_none());
+        assert!(expand_call("length", &[]).is_none());
+    }
+}
@@ -0,0 +1,160 @@
+//! Size and compile-cost estimates for generated code
+//!
+//! [`report`] measures a generated token stream: how many tokens and
+//! functions it contains, how many macro and method calls rustc has to
+//! expand or resolve, and how deeply it nests. These are combined into a
+//! [`compile_cost`](SizeReport::compile_cost) in arbitrary units, which is
+//! only meant for ranking rules against each other: rules far above the
+//! rest are the ones worth splitting with
+//! [`with_max_function_tokens`](super::RustCodeGenerator::with_max_function_tokens).
+//!
+//! # Example
+//!
+//! ```
+//! use elo_rust::codegen::report;
+//! use quote::quote;
+//!
+//! let size = report(&quote! { fn check(x: i64) -> bool { x > 0 && format!("{}", x).len() < 5 } });
+//! assert_eq!(size.functions, 1);
+//! assert_eq!(size.macro_calls, 1);
+//! assert_eq!(size.method_calls, 1);
+//! ```
+
+use proc_macro2::{TokenStream, TokenTree};
+use std::fmt;
+
+/// Compile cost above which a piece of generated code is reported as heavy
+pub const HIGH_COMPILE_COST: usize = 2_000;
+
+/// Measurements of a generated token stream
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SizeReport {
+    /// Tokens, counting the contents of every group
+    pub tokens: usize,
+    /// `fn` items and associated functions
+    pub functions: usize,
+    /// Macro invocations (`format!`, `vec!`, ...)
+    pub macro_calls: usize,
+    /// Method calls (`.len()`, `.iter()`, ...), each needing trait resolution
+    pub method_calls: usize,
+    /// Deepest nesting of delimited groups
+    pub max_depth: usize,
+}
+
+impl SizeReport {
+    /// Heuristic compile cost in arbitrary units
+    ///
+    /// Every token costs one unit; functions, macro expansions and method
+    /// resolution cost more, and deep nesting grows type inference work
+    /// quadratically.
+    pub fn compile_cost(&self) -> usize {
+        self.tokens
+            + 25 * self.functions
+            + 15 * self.macro_calls
+            + 5 * self.method_calls
+            + self.max_depth * self.max_depth
+    }
+
+    /// Whether the cost exceeds [`HIGH_COMPILE_COST`]
+    pub fn is_heavy(&self) -> bool {
+        self.compile_cost() > HIGH_COMPILE_COST
+    }
+
+    fn add(&mut self, other: SizeReport) {
+        self.tokens += other.tokens;
+        self.functions += other.functions;
+        self.macro_calls += other.macro_calls;
+        self.method_calls += other.method_calls;
+        self.max_depth = self.max_depth.max(other.max_depth);
+    }
+}
+
+impl std::ops::Add for SizeReport {
+    type Output = SizeReport;
+
+    fn add(mut self, other: SizeReport) -> SizeReport {
+        SizeReport::add(&mut self, other);
+        self
+    }
+}
+
+impl std::iter::Sum for SizeReport {
+    fn sum<I: Iterator<Item = SizeReport>>(iter: I) -> SizeReport {
+        iter.fold(SizeReport::default(), |total, size| total + size)
+    }
+}
+
+impl fmt::Display for SizeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "tokens: {}, functions: {}, macro calls: {}, method calls: {}, max depth: {}, compile cost: {}",
+            self.tokens,
+            self.functions,
+            self.macro_calls,
+            self.method_calls,
+            self.max_depth,
+            self.compile_cost()
+        )
+    }
+}
+
+/// Measure a generated token stream
+pub fn report(tokens: &TokenStream) -> SizeReport {
+    let trees: Vec<TokenTree> = tokens.clone().into_iter().collect();
+    let mut size = SizeReport::default();
+    for (i, tree) in trees.iter().enumerate() {
+        size.tokens += 1;
+        match tree {
+            TokenTree::Group(group) => {
+                let inner = report(&group.stream());
+                size.add(SizeReport {
+                    max_depth: inner.max_depth + 1,
+                    ..inner
+                });
+            }
+            TokenTree::Ident(ident) if ident == "fn" => size.functions += 1,
+            TokenTree::Ident(_) => {
+                let next = trees.get(i + 1);
+                let is_macro = matches!(next, Some(TokenTree::Punct(p)) if p.as_char() == '!')
+                    && matches!(trees.get(i + 2), Some(TokenTree::Group(_)));
+                let is_method = i > 0
+                    && matches!(&trees[i - 1], TokenTree::Punct(p) if p.as_char() == '.')
+                    && matches!(next, Some(TokenTree::Group(g)) if g.delimiter() == proc_macro2::Delimiter::Parenthesis);
+                size.macro_calls += usize::from(is_macro);
+                size.method_calls += usize::from(is_method);
+            }
+            _ => {}
+        }
+    }
+    size
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::quote;
+
+    #[test]
+    fn test_report_counts() {
+        let size = report(&quote! {
+            fn a(input: &Input) -> bool { input.name.len() > 3 }
+            fn b(input: &Input) -> Vec<String> { vec![format!("{}", a(input))] }
+        });
+        assert_eq!(size.functions, 2);
+        assert_eq!(size.macro_calls, 2);
+        assert_eq!(size.method_calls, 1);
+        assert_eq!(size.max_depth, 4);
+        assert!(!size.is_heavy());
+        assert_eq!(report(&TokenStream::new()), SizeReport::default());
+    }
+
+    #[test]
+    fn test_cost_grows_with_size() {
+        let small = report(&quote! { x > 0 });
+        let large = report(&quote! { x > 0 && y.iter().all(|v| v.len() > 0) && z.is_some() });
+        assert!(large.compile_cost() > small.compile_cost());
+        let total: SizeReport = [small, large].into_iter().sum();
+        assert_eq!(total.tokens, small.tokens + large.tokens);
+    }
+}
@@ -1,20 +1,52 @@
 //! Temporal value code generation for dates, times, and durations
+//!
+//! Code is generated for the `chrono` crate by default, or for the `time`
+//! crate with [`TemporalBackend::Time`]. The `time` backend reads the
+//! current date in UTC, as `time` only determines the local offset behind
+//! its `local-offset` feature, and `date(...)` needs its `parsing` feature.
 
+use super::options::TemporalBackend;
 use proc_macro2::TokenStream;
-use quote::quote;
+use quote::{format_ident, quote};
+
+/// Month names of `time::Month`, from January
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
 
 /// Generates code for temporal operations
 #[derive(Debug)]
-pub struct TemporalGenerator;
+pub struct TemporalGenerator {
+    backend: TemporalBackend,
+}
 
 impl TemporalGenerator {
-    /// Create a new temporal generator
+    /// Create a new temporal generator for `chrono`
     pub fn new() -> Self {
-        Self
+        Self::with_backend(TemporalBackend::Chrono)
+    }
+
+    /// Create a temporal generator for the given date/time crate
+    pub fn with_backend(backend: TemporalBackend) -> Self {
+        Self { backend }
     }
 
     /// Generate code for a date literal (ISO8601)
     pub fn date(&self, date_str: &str) -> TokenStream {
+        if self.backend == TemporalBackend::Time {
+            return time_date(date_str);
+        }
         quote! {
             {
                 use chrono::NaiveDate;
@@ -26,6 +58,9 @@ impl TemporalGenerator {
 
     /// Generate code for a datetime literal (RFC3339)
     pub fn datetime(&self, datetime_str: &str) -> TokenStream {
+        if self.backend == TemporalBackend::Time {
+            return time_datetime(datetime_str);
+        }
         quote! {
             {
                 use chrono::DateTime;
@@ -38,6 +73,9 @@ impl TemporalGenerator {
 
     /// Generate code for a duration literal (ISO8601)
     pub fn duration(&self, duration_str: &str) -> TokenStream {
+        if self.backend == TemporalBackend::Time {
+            return time_duration(duration_str);
+        }
         quote! {
             {
                 use chrono::Duration;
@@ -54,6 +92,9 @@ impl TemporalGenerator {
 
     /// Generate code for a temporal keyword
     pub fn keyword(&self, keyword: &str) -> TokenStream {
+        if self.backend == TemporalBackend::Time {
+            return time_keyword(keyword);
+        }
         match keyword {
             "NOW" => quote! {
                 {
@@ -226,6 +267,153 @@ impl Default for TemporalGenerator {
     }
 }
 
+/// A `time::Date` for an ISO8601 date literal
+fn time_date(date_str: &str) -> TokenStream {
+    use chrono::Datelike;
+    let Ok(date) = chrono::NaiveDate::parse_from_str(date_str, "%Y-%m-%d") else {
+        let message = format!("invalid date '{}'", date_str);
+        return quote!(::core::compile_error!(#message));
+    };
+    let (year, day) = (date.year(), date.day() as u8);
+    let month = format_ident!("{}", MONTHS[date.month0() as usize]);
+    quote! {
+        ::time::Date::from_calendar_date(#year, ::time::Month::#month, #day)
+            .expect("Invalid date format")
+    }
+}
+
+/// A UTC `time::OffsetDateTime` for an RFC3339 datetime literal
+fn time_datetime(datetime_str: &str) -> TokenStream {
+    let Ok(datetime) = chrono::DateTime::parse_from_rfc3339(datetime_str) else {
+        let message = format!("invalid datetime '{}'", datetime_str);
+        return quote!(::core::compile_error!(#message));
+    };
+    let seconds = datetime.timestamp();
+    let nanos = i64::from(datetime.timestamp_subsec_nanos());
+    quote! {
+        ::time::OffsetDateTime::from_unix_timestamp(#seconds)
+            .map(|dt| dt + ::time::Duration::nanoseconds(#nanos))
+            .expect("Invalid datetime format")
+    }
+}
+
+/// A `time::Duration` for an ISO8601 duration literal
+fn time_duration(duration_str: &str) -> TokenStream {
+    use crate::runtime::TemporalValue;
+    let seconds = match TemporalValue::parse_duration(duration_str) {
+        Ok(TemporalValue::Duration(duration)) => duration.num_seconds(),
+        _ => 0,
+    };
+    quote!(::time::Duration::seconds(#seconds))
+}
+
+/// Today's `time::Date`, in UTC
+fn time_today() -> TokenStream {
+    quote!(::time::OffsetDateTime::now_utc().date())
+}
+
+/// A temporal keyword for the `time` crate
+fn time_keyword(keyword: &str) -> TokenStream {
+    let today = time_today();
+    match keyword {
+        "NOW" => quote!(::time::OffsetDateTime::now_utc()),
+        "TODAY" => today,
+        "TOMORROW" => quote!((#today + ::time::Duration::DAY)),
+        "YESTERDAY" => quote!((#today - ::time::Duration::DAY)),
+        "START_OF_DAY" => quote!(#today.midnight()),
+        "END_OF_DAY" => quote! {
+            (#today.midnight() + (::time::Duration::DAY - ::time::Duration::SECOND))
+        },
+        "START_OF_WEEK" => quote! {
+            {
+                let today = #today;
+                today - ::time::Duration::days(today.weekday().number_days_from_monday() as i64)
+            }
+        },
+        "END_OF_WEEK" => quote! {
+            {
+                let today = #today;
+                today + ::time::Duration::days(6 - today.weekday().number_days_from_monday() as i64)
+            }
+        },
+        "START_OF_MONTH" => quote! {
+            {
+                let today = #today;
+                today - ::time::Duration::days(today.day() as i64 - 1)
+            }
+        },
+        "END_OF_MONTH" => quote! {
+            {
+                let today = #today;
+                let length = today.month().length(today.year());
+                today + ::time::Duration::days((length - today.day()) as i64)
+            }
+        },
+        "START_OF_QUARTER" => quote! {
+            {
+                let today = #today;
+                let month = ::time::Month::try_from((today.month() as u8 - 1) / 3 * 3 + 1)
+                    .unwrap_or(::time::Month::January);
+                ::time::Date::from_calendar_date(today.year(), month, 1).unwrap_or(today)
+            }
+        },
+        "END_OF_QUARTER" => quote! {
+            {
+                let today = #today;
+                let month = ::time::Month::try_from((today.month() as u8 - 1) / 3 * 3 + 3)
+                    .unwrap_or(::time::Month::December);
+                ::time::Date::from_calendar_date(today.year(), month, month.length(today.year()))
+                    .unwrap_or(today)
+            }
+        },
+        "START_OF_YEAR" => quote! {
+            {
+                let today = #today;
+                ::time::Date::from_ordinal_date(today.year(), 1).unwrap_or(today)
+            }
+        },
+        "END_OF_YEAR" => quote! {
+            {
+                let today = #today;
+                ::time::Date::from_calendar_date(today.year(), ::time::Month::December, 31)
+                    .unwrap_or(today)
+            }
+        },
+        "BEGINNING_OF_TIME" => quote!(::time::OffsetDateTime::UNIX_EPOCH.date()),
+        "END_OF_TIME" => quote! {
+            ::time::Date::from_calendar_date(9999, ::time::Month::December, 31)
+                .unwrap_or(::time::Date::MAX)
+        },
+        _ => quote!(),
+    }
+}
+
+/// A date/time stdlib function for the `time` crate
+pub(crate) fn time_function(name: &str, args: &[TokenStream]) -> TokenStream {
+    let today = time_today();
+    match (name, args) {
+        ("today", _) => today,
+        ("now", _) => quote!(::time::OffsetDateTime::now_utc()),
+        ("age", [birth_date, ..]) => quote! {
+            {
+                let today = #today;
+                let birth_date = #birth_date;
+                let mut age = today.year() - birth_date.year();
+                if (today.month() as u8, today.day()) < (birth_date.month() as u8, birth_date.day()) {
+                    age -= 1;
+                }
+                i64::from(age)
+            }
+        },
+        ("days_since", [date, ..]) => quote!((#today - #date).whole_days()),
+        ("date", [date_str, ..]) => quote! {
+            ::time::Date::parse(#date_str, &::time::format_description::well_known::Iso8601::DATE)
+                .expect("Invalid date format")
+        },
+        _ => quote!(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -284,6 +472,20 @@ mod tests {
         assert!(token_str.contains("0"));
     }
 
+    #[test]
+    fn test_time_backend() {
+        let gen = TemporalGenerator::with_backend(TemporalBackend::Time);
+        let date = gen.date("2024-03-15").to_string();
+        assert!(date.contains("from_calendar_date (2024i32 , :: time :: Month :: March , 15u8)"));
+        let datetime = gen.datetime("2024-01-15T10:30:00+02:00").to_string();
+        assert!(datetime.contains("from_unix_timestamp (1705307400i64)"));
+        let duration = gen.duration("PT1H30M").to_string();
+        assert_eq!(duration, ":: time :: Duration :: seconds (5400i64)");
+        let today = gen.keyword("TODAY").to_string();
+        assert_eq!(today, ":: time :: OffsetDateTime :: now_utc () . date ()");
+        assert!(!gen.keyword("END_OF_QUARTER").to_string().contains("chrono"));
+    }
+
     #[test]
     fn test_date_comparison() {
         let gen = TemporalGenerator::new();
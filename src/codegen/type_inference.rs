@@ -2,10 +2,22 @@
 //!
 //! Infers types for expressions to enable better error checking and code generation.
 //! Uses a simple bidirectional type inference approach.
+//!
+//! Without type information identifiers and field accesses are `Unknown`.
+//! [`TypeInferenceVisitor::with_context`] declares the input type instead:
+//! free identifiers are then its fields, `user.age` follows the registered
+//! [`TypeInfo`](super::types::TypeInfo) of each record, and a field the type
+//! does not declare is an [`InferredType::Error`].
 
+use super::types::{RustType, TypeContext};
 use crate::ast::{BinaryOperator, Expr, Literal, TemporalKeyword, UnaryOperator, Visitor};
 use std::fmt;
 
+/// Root identifier of context lookups (`ctx.limits.daily_max`)
+///
+/// Context fields may be absent, so lookups through it are inferred as nullable.
+pub const CONTEXT_ROOT: &str = "ctx";
+
 /// Inferred type of an ELO expression
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum InferredType {
@@ -27,9 +39,18 @@ pub enum InferredType {
     /// Array with element type
     Array(Box<InferredType>),
 
+    /// Range with bound type
+    Range(Box<InferredType>),
+
+    /// Value that may be null or missing
+    Nullable(Box<InferredType>),
+
     /// Object with field types (simplified - just track it's an object)
     Object,
 
+    /// Value of a type registered in a [`TypeContext`], by name
+    Record(String),
+
     /// Date type
     Date,
 
@@ -58,7 +79,10 @@ impl fmt::Display for InferredType {
             Self::Boolean => write!(f, "boolean"),
             Self::Null => write!(f, "null"),
             Self::Array(elem_type) => write!(f, "[{}]", elem_type),
+            Self::Range(bound_type) => write!(f, "range<{}>", bound_type),
+            Self::Nullable(inner) => write!(f, "{}?", inner),
             Self::Object => write!(f, "object"),
+            Self::Record(name) => write!(f, "{}", name),
             Self::Date => write!(f, "date"),
             Self::DateTime => write!(f, "datetime"),
             Self::Duration => write!(f, "duration"),
@@ -70,6 +94,50 @@ impl fmt::Display for InferredType {
 }
 
 impl InferredType {
+    /// Parse a type name as printed by `Display` (`integer`, `[string]`,
+    /// `string?`, ...); names without a counterpart, such as `any`, are
+    /// `Unknown`
+    pub fn from_type_name(name: &str) -> Self {
+        if let Some(inner) = name.strip_suffix('?') {
+            return Self::Nullable(Box::new(Self::from_type_name(inner)));
+        }
+        if let Some(elem) = name.strip_prefix('[').and_then(|n| n.strip_suffix(']')) {
+            return Self::Array(Box::new(Self::from_type_name(elem)));
+        }
+        match name {
+            "integer" => Self::Integer,
+            "float" => Self::Float,
+            "string" => Self::String,
+            "boolean" => Self::Boolean,
+            "null" => Self::Null,
+            "object" => Self::Object,
+            "date" => Self::Date,
+            "datetime" => Self::DateTime,
+            "duration" => Self::Duration,
+            "number" => Self::Numeric,
+            _ => Self::Unknown,
+        }
+    }
+
+    /// The inferred counterpart of a declared field type
+    ///
+    /// Custom types become records; `Unknown` stays unknown.
+    pub fn from_rust_type(rust_type: &RustType) -> Self {
+        match rust_type {
+            RustType::String => Self::String,
+            RustType::Integer => Self::Integer,
+            RustType::Float => Self::Float,
+            RustType::Bool => Self::Boolean,
+            RustType::Date => Self::Date,
+            RustType::Time => Self::DateTime,
+            RustType::Duration => Self::Duration,
+            RustType::Option(inner) => Self::Nullable(Box::new(Self::from_rust_type(inner))),
+            RustType::Array(inner) => Self::Array(Box::new(Self::from_rust_type(inner))),
+            RustType::Custom(name) => Self::Record(name.clone()),
+            RustType::Unknown => Self::Unknown,
+        }
+    }
+
     /// Check if this is a numeric type
     pub fn is_numeric(&self) -> bool {
         matches!(self, Self::Integer | Self::Float | Self::Numeric)
@@ -83,6 +151,11 @@ impl InferredType {
         )
     }
 
+    /// Check if this type may be null or missing
+    pub fn is_nullable(&self) -> bool {
+        matches!(self, Self::Nullable(_) | Self::Null)
+    }
+
     /// Check if this is an error type
     pub fn is_error(&self) -> bool {
         matches!(self, Self::Error(_))
@@ -111,6 +184,15 @@ impl InferredType {
             // Unknown can unify with anything
             (InferredType::Unknown, t) | (t, InferredType::Unknown) => t.clone(),
 
+            // Nullable unifies on the inner type and stays nullable
+            (InferredType::Nullable(inner), other) | (other, InferredType::Nullable(inner)) => {
+                let other = match other {
+                    InferredType::Nullable(o) => o.as_ref(),
+                    o => o,
+                };
+                InferredType::Nullable(Box::new(Self::common_type(inner, other)))
+            }
+
             // Array types unify on element type
             (InferredType::Array(a), InferredType::Array(b)) => {
                 let elem_type = Self::common_type(a, b);
@@ -123,26 +205,79 @@ impl InferredType {
     }
 }
 
+/// How `primary ?| alternative` falls back to its alternative, chosen from
+/// the inferred type of the primary
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlternativeStrategy {
+    /// The primary may be null (a nullable field or context lookup): its
+    /// value when present, otherwise the alternative
+    NullCheck,
+    /// The primary is a validation: passes when either side passes, and
+    /// failures of the primary are dropped when the alternative passes
+    ValidationFallback,
+    /// The primary always has a value, so the alternative is never used
+    PrimaryOnly,
+}
+
 /// Type inference visitor
 ///
 /// Analyzes expressions and infers their types.
 /// Returns the inferred type for each expression.
-#[derive(Debug)]
-pub struct TypeInferenceVisitor;
+#[derive(Debug, Clone)]
+pub struct TypeInferenceVisitor {
+    /// Registered types, for resolving fields
+    context: TypeContext,
+    /// Type whose fields the free identifiers are
+    input_type: Option<String>,
+    /// Names bound by `let` and lambdas, innermost last
+    locals: Vec<(String, InferredType)>,
+}
 
 impl TypeInferenceVisitor {
     /// Create a new type inference visitor
     pub fn new() -> Self {
-        TypeInferenceVisitor
+        TypeInferenceVisitor {
+            context: TypeContext::new(),
+            input_type: None,
+            locals: Vec::new(),
+        }
     }
 
-    /// Infer the type of an expression
-    pub fn infer(&self, expr: &Expr) -> InferredType {
-        Self::infer_expr(expr)
+    /// Create a visitor resolving identifiers as fields of `input_type`
+    ///
+    /// Field types come from the types registered in `context`. Records
+    /// whose type is not registered, or registered without fields, are not
+    /// checked: their fields are `Unknown`.
+    pub fn with_context(context: TypeContext, input_type: &str) -> Self {
+        TypeInferenceVisitor {
+            context,
+            input_type: Some(input_type.to_string()),
+            locals: Vec::new(),
+        }
+    }
+
+    /// Choose how `primary ?| alternative` is evaluated
+    ///
+    /// Primaries of unknown type are assumed to be nullable fields.
+    pub fn alternative_strategy(primary: &Expr) -> AlternativeStrategy {
+        Self::new().infer_alternative_strategy(primary)
+    }
+
+    /// Choose how `primary ?| alternative` is evaluated, knowing the types
+    /// of the input's fields
+    pub fn infer_alternative_strategy(&self, primary: &Expr) -> AlternativeStrategy {
+        match self.infer(primary) {
+            InferredType::Boolean => AlternativeStrategy::ValidationFallback,
+            InferredType::Nullable(_)
+            | InferredType::Null
+            | InferredType::Unknown
+            | InferredType::Error(_) => AlternativeStrategy::NullCheck,
+            _ => AlternativeStrategy::PrimaryOnly,
+        }
     }
 
-    /// Helper function to infer expression type without mut self
-    fn infer_expr(expr: &Expr) -> InferredType {
+    /// Infer the type of an expression
+    pub fn infer(&self, expr: &Expr) -> InferredType {
         match expr {
             Expr::Literal(lit) => match lit {
                 Literal::Integer(_) => InferredType::Integer,
@@ -150,31 +285,39 @@ impl TypeInferenceVisitor {
                 Literal::Boolean(_) => InferredType::Boolean,
             },
             Expr::Null => InferredType::Null,
-            Expr::Identifier(_) => InferredType::Unknown,
+            Expr::Identifier(name) => self.infer_identifier(name),
+            Expr::Placeholder(_) => InferredType::Unknown,
             Expr::String(_) => InferredType::String,
-            Expr::FieldAccess { .. } => InferredType::Unknown,
-            Expr::BinaryOp { op, left, right } => Self::infer_binary_op(*op, left, right),
-            Expr::UnaryOp { op, operand } => Self::infer_unary_op(*op, operand),
-            Expr::FunctionCall { name, args } => Self::infer_function_call(name, args),
+            Expr::FieldAccess { receiver, field } => self.infer_field_access(receiver, field),
+            Expr::Index { collection, index } => self.infer_index(collection, index),
+            Expr::BinaryOp { op, left, right } => self.infer_binary_op(*op, left, right),
+            Expr::UnaryOp { op, operand } => self.infer_unary_op(*op, operand),
+            Expr::FunctionCall { name, args } => self.infer_function_call(name, args),
             Expr::Lambda { .. } => InferredType::Unknown,
-            Expr::Let { body, .. } => Self::infer_expr(body),
+            Expr::Let { name, value, body } => match self.infer(value) {
+                error @ InferredType::Error(_) => error,
+                value_type => self.bind(name, value_type).infer(body),
+            },
             Expr::If {
+                condition,
                 then_branch,
                 else_branch,
-                ..
             } => {
-                let then_type = Self::infer_expr(then_branch);
-                let else_type = Self::infer_expr(else_branch);
+                if let error @ InferredType::Error(_) = self.infer(condition) {
+                    return error;
+                }
+                let then_type = self.infer(then_branch);
+                let else_type = self.infer(else_branch);
                 InferredType::common_type(&then_type, &else_type)
             }
             Expr::Array(elements) => {
                 if elements.is_empty() {
                     InferredType::Array(Box::new(InferredType::Unknown))
                 } else {
-                    let first_type = Self::infer_expr(&elements[0]);
+                    let first_type = self.infer(&elements[0]);
                     let mut common = first_type;
                     for elem in &elements[1..] {
-                        let elem_type = Self::infer_expr(elem);
+                        let elem_type = self.infer(elem);
                         common = InferredType::common_type(&common, &elem_type);
                         if common.is_error() {
                             break;
@@ -183,23 +326,44 @@ impl TypeInferenceVisitor {
                     InferredType::Array(Box::new(common))
                 }
             }
-            Expr::Object(_) => InferredType::Object,
-            Expr::Pipe { functions, .. } => {
-                if functions.is_empty() {
-                    InferredType::Unknown
-                } else {
-                    Self::infer_expr(functions.last().unwrap())
-                }
-            }
+            Expr::Object(fields) => fields
+                .iter()
+                .map(|(_, value)| self.infer(value))
+                .find(InferredType::is_error)
+                .unwrap_or(InferredType::Object),
+            Expr::Pipe { value, functions } => match Expr::desugar_pipe(value, functions) {
+                Ok(desugared) => self.infer(&desugared),
+                Err(message) => InferredType::Error(message),
+            },
             Expr::Alternative {
                 primary,
                 alternative,
-            } => {
-                let primary_type = Self::infer_expr(primary);
-                let alt_type = Self::infer_expr(alternative);
-                InferredType::common_type(&primary_type, &alt_type)
+            } => match self.infer_alternative_strategy(primary) {
+                AlternativeStrategy::NullCheck => match self.infer(alternative) {
+                    // Both sides may be null, so the result may be too
+                    alt_type @ (InferredType::Nullable(_) | InferredType::Null) => {
+                        InferredType::common_type(&self.infer(primary), &alt_type)
+                    }
+                    _ => self.infer_null_default(primary, alternative),
+                },
+                AlternativeStrategy::ValidationFallback => InferredType::Boolean,
+                AlternativeStrategy::PrimaryOnly => self.infer(primary),
+            },
+            Expr::Guard { condition, body } => match self.infer(condition) {
+                error @ InferredType::Error(_) => error,
+                _ => self.infer(body),
+            },
+            Expr::NullDefault { value, default } => self.infer_null_default(value, default),
+            Expr::Range { start, end, .. } => InferredType::Range(Box::new(
+                InferredType::common_type(&self.infer(start), &self.infer(end)),
+            )),
+            Expr::Quantity { .. } | Expr::Percentage(_) => InferredType::Float,
+            Expr::Require { condition, .. } | Expr::WarnIf { condition, .. } => {
+                match self.infer(condition) {
+                    error @ InferredType::Error(_) => error,
+                    _ => InferredType::Boolean,
+                }
             }
-            Expr::Guard { body, .. } => Self::infer_expr(body),
             Expr::Date(_) => InferredType::Date,
             Expr::DateTime(_) => InferredType::DateTime,
             Expr::Duration(_) => InferredType::Duration,
@@ -213,10 +377,62 @@ impl TypeInferenceVisitor {
         }
     }
 
-    fn infer_binary_op(op: BinaryOperator, left: &Expr, right: &Expr) -> InferredType {
-        let left_type = Self::infer_expr(left);
-        let right_type = Self::infer_expr(right);
+    /// Element type of `collection[index]`, nullable since the position
+    /// may be out of range
+    fn infer_index(&self, collection: &Expr, index: &Expr) -> InferredType {
+        match self.infer(index) {
+            InferredType::Integer | InferredType::Unknown => {}
+            error @ InferredType::Error(_) => return error,
+            other => return InferredType::Error(format!("Cannot index with {}", other)),
+        }
+        let element = match self.infer(collection) {
+            InferredType::Array(element) => *element,
+            InferredType::Nullable(inner) => match *inner {
+                InferredType::Array(element) => *element,
+                InferredType::Unknown => InferredType::Unknown,
+                other => return InferredType::Error(format!("Cannot index {}?", other)),
+            },
+            InferredType::Unknown | InferredType::Null => InferredType::Unknown,
+            error @ InferredType::Error(_) => return error,
+            other => return InferredType::Error(format!("Cannot index {}", other)),
+        };
+        match element {
+            InferredType::Nullable(_) => element,
+            element => InferredType::Nullable(Box::new(element)),
+        }
+    }
+
+    fn infer_binary_op(&self, op: BinaryOperator, left: &Expr, right: &Expr) -> InferredType {
+        let left_type = self.infer(left);
+        let right_type = self.infer(right);
+        for operand in [&left_type, &right_type] {
+            if operand.is_error() {
+                return operand.clone();
+            }
+        }
 
+        // Operators apply to the value of a nullable operand, which generated
+        // code guards with a match; arithmetic on it may then be null too
+        let nullable = matches!(left_type, InferredType::Nullable(_))
+            || matches!(right_type, InferredType::Nullable(_));
+        let unwrap = |ty: InferredType| match ty {
+            InferredType::Nullable(inner) => *inner,
+            ty => ty,
+        };
+        match Self::binary_op_type(op, unwrap(left_type), unwrap(right_type)) {
+            InferredType::Error(message) => self.located(message, &[left, right]),
+            result @ InferredType::Boolean => result,
+            result if nullable => InferredType::Nullable(Box::new(result)),
+            result => result,
+        }
+    }
+
+    /// Type of `op` applied to operands of the given types
+    fn binary_op_type(
+        op: BinaryOperator,
+        left_type: InferredType,
+        right_type: InferredType,
+    ) -> InferredType {
         match op {
             BinaryOperator::Add => match (&left_type, &right_type) {
                 (InferredType::Integer, InferredType::Integer) => InferredType::Integer,
@@ -284,6 +500,21 @@ impl TypeInferenceVisitor {
                     )),
                 }
             }
+            BinaryOperator::Pow
+                if left_type == InferredType::Float || right_type == InferredType::Float =>
+            {
+                if left_type.is_numeric() && right_type.is_numeric()
+                    || left_type == InferredType::Unknown
+                    || right_type == InferredType::Unknown
+                {
+                    InferredType::Float
+                } else {
+                    InferredType::Error(format!(
+                        "Cannot apply operator to {} and {}",
+                        left_type, right_type
+                    ))
+                }
+            }
             BinaryOperator::Mod | BinaryOperator::Pow => {
                 if left_type.is_numeric() && right_type.is_numeric() {
                     InferredType::Integer
@@ -303,40 +534,347 @@ impl TypeInferenceVisitor {
             | BinaryOperator::Lt
             | BinaryOperator::Lte
             | BinaryOperator::Gt
-            | BinaryOperator::Gte => InferredType::Boolean,
+            | BinaryOperator::Gte => {
+                if Self::comparable(&left_type, &right_type) {
+                    InferredType::Boolean
+                } else {
+                    InferredType::Error(format!("Cannot compare {} with {}", left_type, right_type))
+                }
+            }
+            BinaryOperator::In => InferredType::Boolean,
+            BinaryOperator::Like => {
+                let text_ok = matches!(left_type, InferredType::String | InferredType::Unknown);
+                let pattern_ok = matches!(right_type, InferredType::String | InferredType::Unknown);
+                if text_ok && pattern_ok {
+                    InferredType::Boolean
+                } else {
+                    InferredType::Error(format!(
+                        "'like' requires string operands, got {} and {}",
+                        left_type, right_type
+                    ))
+                }
+            }
             BinaryOperator::And | BinaryOperator::Or => InferredType::Boolean,
         }
     }
 
-    fn infer_unary_op(op: UnaryOperator, operand: &Expr) -> InferredType {
-        let operand_type = Self::infer_expr(operand);
+    /// Whether values of the two types can be compared
+    ///
+    /// Scalars compare with scalars of the same kind only: a string is not
+    /// read as a date, as generated code has no such conversion. Other
+    /// types are left to the generated code.
+    fn comparable(left: &InferredType, right: &InferredType) -> bool {
+        fn kind(ty: &InferredType) -> Option<u8> {
+            match ty {
+                InferredType::Integer | InferredType::Float | InferredType::Numeric => Some(0),
+                InferredType::String => Some(1),
+                InferredType::Boolean => Some(2),
+                InferredType::Date | InferredType::DateTime => Some(3),
+                InferredType::Duration => Some(4),
+                _ => None,
+            }
+        }
+        match (kind(left), kind(right)) {
+            (Some(left), Some(right)) => left == right,
+            _ => true,
+        }
+    }
+
+    /// A copy of this visitor with `name` bound to a value of type `ty`
+    pub fn bind(&self, name: &str, ty: InferredType) -> Self {
+        let mut scoped = self.clone();
+        scoped.locals.push((name.to_string(), ty));
+        scoped
+    }
+
+    /// Type of a local, or else of a field of the input type
+    fn infer_identifier(&self, name: &str) -> InferredType {
+        if let Some((_, ty)) = self.locals.iter().rev().find(|(local, _)| local == name) {
+            return ty.clone();
+        }
+        match &self.input_type {
+            Some(_) if name == CONTEXT_ROOT => InferredType::Unknown,
+            Some(input_type) if name == "input" => InferredType::Record(input_type.clone()),
+            Some(input_type) => self.infer_field(input_type, name),
+            None => InferredType::Unknown,
+        }
+    }
+
+    fn infer_field_access(&self, receiver: &Expr, field: &str) -> InferredType {
+        if Self::is_context_lookup(receiver) {
+            return InferredType::Nullable(Box::new(InferredType::Unknown));
+        }
+        match self.infer(receiver) {
+            InferredType::Record(name) => self.infer_field(&name, field),
+            InferredType::Nullable(inner) => match *inner {
+                InferredType::Record(name) => match self.infer_field(&name, field) {
+                    ty @ (InferredType::Nullable(_) | InferredType::Error(_)) => ty,
+                    ty => InferredType::Nullable(Box::new(ty)),
+                },
+                _ => InferredType::Unknown,
+            },
+            error @ InferredType::Error(_) => error,
+            _ => InferredType::Unknown,
+        }
+    }
+
+    /// Declared type of `field` on the registered type `type_name`
+    fn infer_field(&self, type_name: &str, field: &str) -> InferredType {
+        match self.context.get_type(type_name) {
+            Some(info) if !info.fields().is_empty() => match info.get_field(field) {
+                Some(field_type) => InferredType::from_rust_type(field_type),
+                None => InferredType::Error(format!("Unknown field '{}' on {}", field, type_name)),
+            },
+            _ => InferredType::Unknown,
+        }
+    }
+
+    /// Path of the input field `expr` reads (`order.total`, `items[0]`), if
+    /// it reads one
+    fn path_of(&self, expr: &Expr) -> Option<String> {
+        match expr {
+            Expr::Identifier(name)
+                if name != CONTEXT_ROOT && !self.locals.iter().any(|(local, _)| local == name) =>
+            {
+                Some(name.clone())
+            }
+            Expr::FieldAccess { receiver, field } => self
+                .path_of(receiver)
+                .map(|receiver| format!("{}.{}", receiver, field)),
+            Expr::Index { collection, index } => match **index {
+                Expr::Literal(Literal::Integer(n)) => self
+                    .path_of(collection)
+                    .map(|collection| format!("{}[{}]", collection, n)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// A type error raised by an operation on `operands`, prefixed with the
+    /// path of the first operand read from the input as the interpreter
+    /// reports it
+    fn located(&self, message: String, operands: &[&Expr]) -> InferredType {
+        match operands.iter().find_map(|operand| self.path_of(operand)) {
+            Some(path) => InferredType::Error(format!("{}: {}", path, message)),
+            None => InferredType::Error(message),
+        }
+    }
+
+    /// Why no overload of the standard library function `name` accepts
+    /// arguments of the inferred types, if none does
+    ///
+    /// Only arguments of known types are checked, so calls the table does
+    /// not describe precisely are left to the generated code.
+    fn argument_mismatch(&self, name: &str, args: &[Expr]) -> Option<String> {
+        let types: Vec<InferredType> = args.iter().map(|arg| self.infer(arg)).collect();
+        let mut overloads = crate::stdlib::lookup(name)
+            .filter(|f| f.accepts_arity(args.len()))
+            .peekable();
+        overloads.peek()?;
+        let accepted = overloads.any(|f| {
+            f.params.iter().zip(&types).all(|(param, ty)| {
+                let param = InferredType::from_type_name(param.trim_end_matches("..."));
+                accepts(&param, ty)
+            })
+        });
+        if accepted {
+            return None;
+        }
+        let names: Vec<String> = types.iter().map(ToString::to_string).collect();
+        Some(format!("{}() does not accept ({})", name, names.join(", ")))
+    }
+
+    /// Check whether an expression is rooted at the context identifier
+    fn is_context_lookup(expr: &Expr) -> bool {
+        match expr {
+            Expr::Identifier(name) => name == CONTEXT_ROOT,
+            Expr::FieldAccess { receiver, .. } => Self::is_context_lookup(receiver),
+            _ => false,
+        }
+    }
+
+    fn infer_null_default(&self, value: &Expr, default: &Expr) -> InferredType {
+        let value_type = match self.infer(value) {
+            InferredType::Nullable(inner) => *inner,
+            InferredType::Null => InferredType::Unknown,
+            other => other,
+        };
+        InferredType::common_type(&value_type, &self.infer(default))
+    }
+
+    fn infer_unary_op(&self, op: UnaryOperator, operand: &Expr) -> InferredType {
+        let operand_type = self.infer(operand);
+        if operand_type.is_error() {
+            return operand_type;
+        }
         match op {
             UnaryOperator::Not => InferredType::Boolean,
             UnaryOperator::Neg | UnaryOperator::Plus => operand_type,
         }
     }
 
-    fn infer_function_call(name: &str, args: &[Expr]) -> InferredType {
+    fn infer_function_call(&self, name: &str, args: &[Expr]) -> InferredType {
+        if let Some(error) = args
+            .iter()
+            .map(|arg| self.infer(arg))
+            .find(InferredType::is_error)
+        {
+            return error;
+        }
+        if let Some(ty) = self.infer_collection_call(name, args) {
+            return ty;
+        }
+        if let Some(message) = self.argument_mismatch(name, args) {
+            return self.located(message, &args.iter().collect::<Vec<_>>());
+        }
         match name {
-            "length" | "uppercase" | "lowercase" | "trim" | "contains" | "starts_with"
-            | "ends_with" => InferredType::String,
-            "map" | "filter" | "sort" => InferredType::Array(Box::new(InferredType::Unknown)),
+            "sort" => return InferredType::Array(Box::new(InferredType::Unknown)),
             "abs" | "min" | "max" | "round" | "floor" | "ceil" => {
-                if args.is_empty() {
+                return if args.is_empty() {
                     InferredType::Unknown
                 } else {
-                    let arg_type = Self::infer_expr(&args[0]);
+                    let arg_type = self.infer(&args[0]);
                     if arg_type.is_numeric() {
                         arg_type
                     } else {
                         InferredType::Error(format!("Expected numeric argument, got {}", arg_type))
                     }
-                }
+                };
+            }
+            "clamp" => return self.infer_clamp(args),
+            _ => {}
+        }
+        // Standard library functions: the return type from the canonical
+        // table, when all overloads agree on it
+        let mut returns = crate::stdlib::lookup(name).map(|f| f.return_type.as_str());
+        if let Some(first) = returns.next() {
+            if returns.all(|other| other == first) {
+                return InferredType::from_type_name(first);
             }
-            "all" | "any" => InferredType::Boolean,
+        }
+        InferredType::Unknown
+    }
+
+    /// Type of `clamp(value, low..=high)` or `clamp(value, low, high)`: the
+    /// type of the value
+    ///
+    /// An exclusive range has no largest float below its end, so
+    /// `clamp(x, 1.0..2.0)` is an error, as in the interpreter.
+    fn infer_clamp(&self, args: &[Expr]) -> InferredType {
+        let Some(value) = args.first() else {
+            return InferredType::Unknown;
+        };
+        if let [_, Expr::Range {
+            start,
+            end,
+            inclusive: false,
+        }] = args
+        {
+            let is_float = |expr: &Expr| match self.infer(expr) {
+                InferredType::Nullable(inner) => *inner == InferredType::Float,
+                ty => ty == InferredType::Float,
+            };
+            if [value, start.as_ref(), end.as_ref()]
+                .into_iter()
+                .any(is_float)
+            {
+                return self.located(
+                    "clamp() needs an inclusive range of floats".to_string(),
+                    &[value],
+                );
+            }
+        }
+        self.infer(value)
+    }
+}
+
+/// Whether a parameter declared as `param` takes an argument of type `arg`
+///
+/// Unknown types, records and objects match anything, integers are taken
+/// where floats are and ranges where arrays are.
+fn accepts(param: &InferredType, arg: &InferredType) -> bool {
+    match (param, arg) {
+        (InferredType::Unknown | InferredType::Object | InferredType::Record(_), _)
+        | (
+            _,
+            InferredType::Unknown
+            | InferredType::Object
+            | InferredType::Record(_)
+            | InferredType::Error(_),
+        ) => true,
+        (_, InferredType::Nullable(inner)) => accepts(param, inner),
+        (InferredType::Nullable(inner), arg) => *arg == InferredType::Null || accepts(inner, arg),
+        (InferredType::Numeric, arg) => arg.is_numeric(),
+        (InferredType::Float, InferredType::Integer | InferredType::Numeric) => true,
+        (
+            InferredType::Array(param),
+            InferredType::Array(element) | InferredType::Range(element),
+        ) => accepts(param, element),
+        (param, arg) => param == arg,
+    }
+}
+
+impl TypeInferenceVisitor {
+    /// Type of the elements of an array or a range, `Unknown` otherwise
+    pub fn element_type(&self, collection: &Expr) -> InferredType {
+        match self.infer(collection) {
+            InferredType::Array(element) | InferredType::Range(element) => *element,
             _ => InferredType::Unknown,
         }
     }
+
+    /// Type of `map`, `filter`, `reduce`, `sum`, `min_by` and `max_by`
+    /// called with a lambda, whose parameter is an element of the collection
+    ///
+    /// `reduce` takes a lambda of two parameters, the accumulator (typed
+    /// like the initial value) and the element.
+    fn infer_collection_call(&self, name: &str, args: &[Expr]) -> Option<InferredType> {
+        let (collection, rest) = args.split_first()?;
+        let element = self.element_type(collection);
+        let lambda = |function: &Expr| match function {
+            Expr::Lambda { param, body } => Some(self.bind(param, element.clone()).infer(body)),
+            _ => None,
+        };
+        let ty = match (name, rest) {
+            ("map", [function]) => match lambda(function)? {
+                error @ InferredType::Error(_) => error,
+                mapped => InferredType::Array(Box::new(mapped)),
+            },
+            ("filter", [function]) => match lambda(function)? {
+                error @ InferredType::Error(_) => error,
+                _ => InferredType::Array(Box::new(element)),
+            },
+            ("reduce", [init, Expr::Lambda { param, body }]) => {
+                let Expr::Lambda { param: item, body } = body.as_ref() else {
+                    return None;
+                };
+                let acc = self.infer(init);
+                let folded = self
+                    .bind(param, acc.clone())
+                    .bind(item, element)
+                    .infer(body);
+                InferredType::common_type(&acc, &folded)
+            }
+            ("sum", []) => Self::summed(element),
+            ("sum", [function]) => Self::summed(lambda(function)?),
+            ("min_by" | "max_by", [function]) => match lambda(function)? {
+                error @ InferredType::Error(_) => error,
+                _ => InferredType::Nullable(Box::new(element)),
+            },
+            _ => return None,
+        };
+        Some(ty)
+    }
+
+    /// Type of the sum of values of type `ty`
+    fn summed(ty: InferredType) -> InferredType {
+        match ty {
+            InferredType::Unknown | InferredType::Error(_) => ty,
+            ty if ty.is_numeric() => ty,
+            other => InferredType::Error(format!("Cannot sum {}", other)),
+        }
+    }
 }
 
 impl Default for TypeInferenceVisitor {
@@ -347,7 +885,7 @@ impl Default for TypeInferenceVisitor {
 
 impl Visitor<InferredType> for TypeInferenceVisitor {
     fn visit_expr(&mut self, expr: &Expr) -> InferredType {
-        Self::infer_expr(expr)
+        self.infer(expr)
     }
 
     fn visit_literal(&mut self, lit: &Literal) -> InferredType {
@@ -362,32 +900,43 @@ impl Visitor<InferredType> for TypeInferenceVisitor {
         InferredType::Null
     }
 
-    fn visit_identifier(&mut self, _name: &str) -> InferredType {
-        InferredType::Unknown
+    fn visit_identifier(&mut self, name: &str) -> InferredType {
+        self.infer_identifier(name)
     }
 
-    fn visit_field_access(&mut self, _receiver: &Expr, _field: &str) -> InferredType {
+    fn visit_placeholder(&mut self, _name: &str) -> InferredType {
         InferredType::Unknown
     }
 
+    fn visit_field_access(&mut self, receiver: &Expr, field: &str) -> InferredType {
+        self.infer_field_access(receiver, field)
+    }
+
+    fn visit_index(&mut self, collection: &Expr, index: &Expr) -> InferredType {
+        self.infer_index(collection, index)
+    }
+
     fn visit_binary_op(&mut self, op: BinaryOperator, left: &Expr, right: &Expr) -> InferredType {
-        Self::infer_binary_op(op, left, right)
+        self.infer_binary_op(op, left, right)
     }
 
     fn visit_unary_op(&mut self, op: UnaryOperator, operand: &Expr) -> InferredType {
-        Self::infer_unary_op(op, operand)
+        self.infer_unary_op(op, operand)
     }
 
     fn visit_function_call(&mut self, name: &str, args: &[Expr]) -> InferredType {
-        Self::infer_function_call(name, args)
+        self.infer_function_call(name, args)
     }
 
     fn visit_lambda(&mut self, _param: &str, _body: &Expr) -> InferredType {
         InferredType::Unknown
     }
 
-    fn visit_let(&mut self, _name: &str, _value: &Expr, body: &Expr) -> InferredType {
-        Self::infer_expr(body)
+    fn visit_let(&mut self, name: &str, value: &Expr, body: &Expr) -> InferredType {
+        match self.infer(value) {
+            error @ InferredType::Error(_) => error,
+            value_type => self.bind(name, value_type).infer(body),
+        }
     }
 
     fn visit_if(
@@ -396,8 +945,8 @@ impl Visitor<InferredType> for TypeInferenceVisitor {
         then_branch: &Expr,
         else_branch: &Expr,
     ) -> InferredType {
-        let then_type = Self::infer_expr(then_branch);
-        let else_type = Self::infer_expr(else_branch);
+        let then_type = self.infer(then_branch);
+        let else_type = self.infer(else_branch);
         InferredType::common_type(&then_type, &else_type)
     }
 
@@ -405,10 +954,10 @@ impl Visitor<InferredType> for TypeInferenceVisitor {
         if elements.is_empty() {
             InferredType::Array(Box::new(InferredType::Unknown))
         } else {
-            let first_type = Self::infer_expr(&elements[0]);
+            let first_type = self.infer(&elements[0]);
             let mut common = first_type;
             for elem in &elements[1..] {
-                let elem_type = Self::infer_expr(elem);
+                let elem_type = self.infer(elem);
                 common = InferredType::common_type(&common, &elem_type);
                 if common.is_error() {
                     break;
@@ -424,20 +973,43 @@ impl Visitor<InferredType> for TypeInferenceVisitor {
 
     fn visit_pipe(&mut self, value: &Expr, functions: &[Expr]) -> InferredType {
         if functions.is_empty() {
-            Self::infer_expr(value)
+            self.infer(value)
         } else {
-            Self::infer_expr(functions.last().unwrap())
+            self.infer(functions.last().unwrap())
         }
     }
 
     fn visit_alternative(&mut self, primary: &Expr, alternative: &Expr) -> InferredType {
-        let primary_type = Self::infer_expr(primary);
-        let alt_type = Self::infer_expr(alternative);
+        let primary_type = self.infer(primary);
+        let alt_type = self.infer(alternative);
         InferredType::common_type(&primary_type, &alt_type)
     }
 
     fn visit_guard(&mut self, _condition: &Expr, body: &Expr) -> InferredType {
-        Self::infer_expr(body)
+        self.infer(body)
+    }
+
+    fn visit_null_default(&mut self, value: &Expr, default: &Expr) -> InferredType {
+        self.infer_null_default(value, default)
+    }
+
+    fn visit_range(&mut self, start: &Expr, end: &Expr, _inclusive: bool) -> InferredType {
+        InferredType::Range(Box::new(InferredType::common_type(
+            &self.infer(start),
+            &self.infer(end),
+        )))
+    }
+
+    fn visit_quantity(&mut self, _value: f64, _unit: &str) -> InferredType {
+        InferredType::Float
+    }
+
+    fn visit_require(&mut self, _condition: &Expr, _message: &str) -> InferredType {
+        InferredType::Boolean
+    }
+
+    fn visit_warn_if(&mut self, _condition: &Expr, _message: &str) -> InferredType {
+        InferredType::Boolean
     }
 
     fn visit_date(&mut self, _date: &str) -> InferredType {
@@ -469,126 +1041,137 @@ mod tests {
     #[test]
     fn test_infer_integer_literal() {
         let expr = Parser::parse("42").unwrap();
-        let ty = TypeInferenceVisitor::infer_expr(&expr);
+        let ty = TypeInferenceVisitor::new().infer(&expr);
         assert_eq!(ty, InferredType::Integer);
     }
 
     #[test]
     fn test_infer_float_literal() {
         let expr = Parser::parse("3.14").unwrap();
-        let ty = TypeInferenceVisitor::infer_expr(&expr);
+        let ty = TypeInferenceVisitor::new().infer(&expr);
         assert_eq!(ty, InferredType::Float);
     }
 
     #[test]
     fn test_infer_string_literal() {
         let expr = Parser::parse("'hello'").unwrap();
-        let ty = TypeInferenceVisitor::infer_expr(&expr);
+        let ty = TypeInferenceVisitor::new().infer(&expr);
         assert_eq!(ty, InferredType::String);
     }
 
     #[test]
     fn test_infer_boolean_literal() {
         let expr = Parser::parse("true").unwrap();
-        let ty = TypeInferenceVisitor::infer_expr(&expr);
+        let ty = TypeInferenceVisitor::new().infer(&expr);
         assert_eq!(ty, InferredType::Boolean);
     }
 
     #[test]
     fn test_infer_null_literal() {
         let expr = Parser::parse("null").unwrap();
-        let ty = TypeInferenceVisitor::infer_expr(&expr);
+        let ty = TypeInferenceVisitor::new().infer(&expr);
         assert_eq!(ty, InferredType::Null);
     }
 
     #[test]
     fn test_infer_integer_addition() {
         let expr = Parser::parse("1 + 2").unwrap();
-        let ty = TypeInferenceVisitor::infer_expr(&expr);
+        let ty = TypeInferenceVisitor::new().infer(&expr);
         assert_eq!(ty, InferredType::Integer);
     }
 
     #[test]
     fn test_infer_float_arithmetic() {
         let expr = Parser::parse("3.0 + 2.0").unwrap();
-        let ty = TypeInferenceVisitor::infer_expr(&expr);
+        let ty = TypeInferenceVisitor::new().infer(&expr);
         assert_eq!(ty, InferredType::Float);
     }
 
     #[test]
     fn test_infer_mixed_numeric() {
         let expr = Parser::parse("1 + 2.0").unwrap();
-        let ty = TypeInferenceVisitor::infer_expr(&expr);
+        let ty = TypeInferenceVisitor::new().infer(&expr);
         assert_eq!(ty, InferredType::Float);
     }
 
     #[test]
     fn test_infer_comparison() {
         let expr = Parser::parse("5 > 3").unwrap();
-        let ty = TypeInferenceVisitor::infer_expr(&expr);
+        let ty = TypeInferenceVisitor::new().infer(&expr);
         assert_eq!(ty, InferredType::Boolean);
     }
 
+    #[test]
+    fn test_infer_comparison_of_different_kinds() {
+        let infer = |source| TypeInferenceVisitor::new().infer(&Parser::parse(source).unwrap());
+        assert_eq!(infer("1 < 2.5"), InferredType::Boolean);
+        assert_eq!(infer("@date(2024-01-01) < NOW"), InferredType::Boolean);
+        assert_eq!(infer("x == @date(2024-01-01)"), InferredType::Boolean);
+        assert!(infer("'2024-01-01' < @date(2024-01-02)").is_error());
+        assert!(infer("'a' == 1").is_error());
+        assert!(infer("true != @duration(P1D)").is_error());
+    }
+
     #[test]
     fn test_infer_logical_and() {
         let expr = Parser::parse("true && false").unwrap();
-        let ty = TypeInferenceVisitor::infer_expr(&expr);
+        let ty = TypeInferenceVisitor::new().infer(&expr);
         assert_eq!(ty, InferredType::Boolean);
     }
 
     #[test]
     fn test_infer_array_integers() {
         let expr = Parser::parse("[1, 2, 3]").unwrap();
-        let ty = TypeInferenceVisitor::infer_expr(&expr);
+        let ty = TypeInferenceVisitor::new().infer(&expr);
         assert_eq!(ty, InferredType::Array(Box::new(InferredType::Integer)));
     }
 
     #[test]
     fn test_infer_array_mixed_numeric() {
         let expr = Parser::parse("[1, 2.0, 3]").unwrap();
-        let ty = TypeInferenceVisitor::infer_expr(&expr);
+        let ty = TypeInferenceVisitor::new().infer(&expr);
         assert_eq!(ty, InferredType::Array(Box::new(InferredType::Float)));
     }
 
     #[test]
     fn test_infer_empty_array() {
         let expr = Parser::parse("[]").unwrap();
-        let ty = TypeInferenceVisitor::infer_expr(&expr);
+        let ty = TypeInferenceVisitor::new().infer(&expr);
         assert_eq!(ty, InferredType::Array(Box::new(InferredType::Unknown)));
     }
 
     #[test]
     fn test_infer_if_same_types() {
         let expr = Parser::parse("if true then 1 else 2").unwrap();
-        let ty = TypeInferenceVisitor::infer_expr(&expr);
+        let ty = TypeInferenceVisitor::new().infer(&expr);
         assert_eq!(ty, InferredType::Integer);
     }
 
     #[test]
     fn test_infer_if_different_numeric_types() {
         let expr = Parser::parse("if true then 1 else 2.0").unwrap();
-        let ty = TypeInferenceVisitor::infer_expr(&expr);
+        let ty = TypeInferenceVisitor::new().infer(&expr);
         assert_eq!(ty, InferredType::Float);
     }
 
     #[test]
     fn test_infer_let_expression() {
         let expr = Parser::parse("let x = 5 in x + 3").unwrap();
-        let ty = TypeInferenceVisitor::infer_expr(&expr);
+        let ty = TypeInferenceVisitor::new().infer(&expr);
         assert_eq!(ty, InferredType::Integer);
     }
 
     #[test]
     fn test_infer_unary_not() {
         let expr = Parser::parse("!true").unwrap();
-        let ty = TypeInferenceVisitor::infer_expr(&expr);
+        let ty = TypeInferenceVisitor::new().infer(&expr);
         assert_eq!(ty, InferredType::Boolean);
     }
 
     #[test]
     fn test_infer_string_concat() {
         let expr = Parser::parse("'hello' + ' world'").unwrap();
-        let ty = TypeInferenceVisitor::infer_expr(&expr);
+        let ty = TypeInferenceVisitor::new().infer(&expr);
         assert_eq!(ty, InferredType::String);
     }
 
@@ -616,6 +1199,56 @@ mod tests {
         assert_eq!(common, InferredType::Integer);
     }
 
+    #[test]
+    fn test_infer_collection_functions() {
+        let mut order = crate::codegen::types::TypeInfo::new("Order");
+        order.add_field("qty", RustType::Integer);
+        order.add_field("prices", RustType::Array(Box::new(RustType::Float)));
+        order.add_field("tags", RustType::Array(Box::new(RustType::String)));
+        let mut context = TypeContext::new();
+        context.register_type("Order", order);
+        let visitor = TypeInferenceVisitor::with_context(context, "Order");
+        let infer = |source: &str| visitor.infer(&Parser::parse(source).unwrap());
+        let floats = InferredType::Array(Box::new(InferredType::Float));
+
+        assert_eq!(infer("map(prices, fn(p ~> p * 2.0))"), floats);
+        assert_eq!(
+            infer("map(tags, fn(t ~> length(t)))"),
+            InferredType::Array(Box::new(InferredType::Integer))
+        );
+        assert_eq!(infer("filter(prices, fn(p ~> p > 1.5))"), floats);
+        assert_eq!(infer("sum(prices)"), InferredType::Float);
+        assert_eq!(infer("sum(1..=qty)"), InferredType::Integer);
+        assert_eq!(
+            infer("sum(tags, fn(t ~> length(t)))"),
+            InferredType::Integer
+        );
+        assert_eq!(
+            infer("reduce(prices, 0.0, fn(acc, p ~> acc + p))"),
+            InferredType::Float
+        );
+        assert_eq!(
+            infer("max_by(tags, fn(t ~> length(t)))"),
+            InferredType::Nullable(Box::new(InferredType::String))
+        );
+        assert!(infer("sum(tags)").is_error());
+        assert!(infer("map(tags, fn(t ~> t - 1))").is_error());
+    }
+
+    #[test]
+    fn test_infer_clamp_and_abs() {
+        let infer =
+            |source: &str| TypeInferenceVisitor::new().infer(&Parser::parse(source).unwrap());
+        assert_eq!(infer("clamp(5, 1..10)"), InferredType::Integer);
+        assert_eq!(infer("clamp(1.5, 1.0..=2.0)"), InferredType::Float);
+        assert_eq!(infer("abs(-4)"), InferredType::Integer);
+        assert_eq!(
+            infer("clamp(x, 1.0..2.0)"),
+            InferredType::Error("x: clamp() needs an inclusive range of floats".to_string())
+        );
+        assert!(infer("abs('a')").is_error());
+    }
+
     #[test]
     fn test_type_is_numeric() {
         assert!(InferredType::Integer.is_numeric());
@@ -624,6 +1257,195 @@ mod tests {
         assert!(!InferredType::String.is_numeric());
     }
 
+    #[test]
+    fn test_stdlib_return_types() {
+        for (source, expected) in [
+            ("length(name)", InferredType::Integer),
+            ("contains(tags, 'x')", InferredType::Boolean),
+            ("days_since(joined)", InferredType::Integer),
+            ("size_mb(bytes)", InferredType::Float),
+            (
+                "normalize_phone(phone, 'GB')",
+                InferredType::Nullable(Box::new(InferredType::String)),
+            ),
+            ("abs(-3)", InferredType::Integer),
+        ] {
+            let expr = Parser::parse(source).unwrap();
+            assert_eq!(
+                TypeInferenceVisitor::new().infer(&expr),
+                expected,
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn test_infer_index() {
+        let infer = |source| TypeInferenceVisitor::new().infer(&Parser::parse(source).unwrap());
+        let nullable = |ty| InferredType::Nullable(Box::new(ty));
+        assert_eq!(infer("[1, 2][0]"), nullable(InferredType::Integer));
+        assert_eq!(infer("[[1], [2]][0][0]"), nullable(InferredType::Integer));
+        assert_eq!(infer("items[0]"), nullable(InferredType::Unknown));
+        assert!(matches!(infer("'abc'[0]"), InferredType::Error(_)));
+        assert!(matches!(infer("[1][true]"), InferredType::Error(_)));
+    }
+
+    #[test]
+    fn test_type_from_type_name() {
+        for ty in [
+            InferredType::Integer,
+            InferredType::Array(Box::new(InferredType::String)),
+            InferredType::Nullable(Box::new(InferredType::Date)),
+        ] {
+            assert_eq!(InferredType::from_type_name(&ty.to_string()), ty);
+        }
+        assert_eq!(InferredType::from_type_name("any"), InferredType::Unknown);
+    }
+
+    #[test]
+    fn test_alternative_strategy() {
+        let alternative = |primary: Expr, alt: Expr| Expr::Alternative {
+            primary: Box::new(primary),
+            alternative: Box::new(alt),
+        };
+        let parse = |source| Parser::parse(source).unwrap();
+
+        let nickname = alternative(parse("ctx.nickname"), Expr::String("anon".to_string()));
+        assert_eq!(
+            TypeInferenceVisitor::new().infer(&nickname),
+            InferredType::String
+        );
+        let lookup = alternative(parse("ctx.nickname"), parse("ctx.name"));
+        assert_eq!(
+            TypeInferenceVisitor::new().infer(&lookup),
+            InferredType::Nullable(Box::new(InferredType::Unknown))
+        );
+        let fallback = alternative(parse("age >= 18"), parse("guardian == true"));
+        assert_eq!(
+            TypeInferenceVisitor::alternative_strategy(&parse("age >= 18")),
+            AlternativeStrategy::ValidationFallback
+        );
+        assert_eq!(
+            TypeInferenceVisitor::new().infer(&fallback),
+            InferredType::Boolean
+        );
+        assert_eq!(
+            TypeInferenceVisitor::alternative_strategy(&parse("length(name)")),
+            AlternativeStrategy::PrimaryOnly
+        );
+        assert_eq!(
+            TypeInferenceVisitor::alternative_strategy(&parse("nickname")),
+            AlternativeStrategy::NullCheck
+        );
+    }
+
+    fn user_context() -> TypeContext {
+        let mut address = crate::codegen::types::TypeInfo::new("Address");
+        address.add_field("city", RustType::String);
+        let mut user = crate::codegen::types::TypeInfo::new("User");
+        user.add_field("age", RustType::Integer);
+        user.add_field("tags", RustType::Array(Box::new(RustType::String)));
+        user.add_field(
+            "address",
+            RustType::Option(Box::new(RustType::Custom("Address".to_string()))),
+        );
+        user.add_field("manager", RustType::Custom("Employee".to_string()));
+        let mut account = crate::codegen::types::TypeInfo::new("Account");
+        account.add_field("user", RustType::Custom("User".to_string()));
+        let mut context = TypeContext::new();
+        context.register_type("Address", address);
+        context.register_type("User", user);
+        context.register_type("Account", account);
+        context
+    }
+
+    #[test]
+    fn test_infer_with_context() {
+        let visitor = TypeInferenceVisitor::with_context(user_context(), "Account");
+        let infer = |source| visitor.infer(&Parser::parse(source).unwrap());
+        let nullable = |ty| InferredType::Nullable(Box::new(ty));
+
+        assert_eq!(infer("user.age"), InferredType::Integer);
+        assert_eq!(infer("user.age + 1.5"), InferredType::Float);
+        assert_eq!(infer("user"), InferredType::Record("User".to_string()));
+        assert_eq!(infer("input.user.age"), InferredType::Integer);
+        assert_eq!(
+            infer("user.tags"),
+            InferredType::Array(Box::new(InferredType::String))
+        );
+        assert_eq!(infer("user.address.city"), nullable(InferredType::String));
+        // Unregistered records are not checked
+        assert_eq!(infer("user.manager.name"), InferredType::Unknown);
+        assert_eq!(infer("ctx.limit"), nullable(InferredType::Unknown));
+        assert_eq!(infer("let user = 5 in user + 1"), InferredType::Integer);
+        assert_eq!(infer("user.tags |> length"), InferredType::Integer);
+    }
+
+    #[test]
+    fn test_infer_nullable_operands() {
+        let mut profile = crate::codegen::types::TypeInfo::new("Profile");
+        profile.add_field("age", RustType::Option(Box::new(RustType::Integer)));
+        profile.add_field("nick", RustType::Option(Box::new(RustType::String)));
+        let mut context = TypeContext::new();
+        context.register_type("Profile", profile);
+        let visitor = TypeInferenceVisitor::with_context(context, "Profile");
+        let infer = |source| visitor.infer(&Parser::parse(source).unwrap());
+        let nullable = |ty| InferredType::Nullable(Box::new(ty));
+
+        // Operators apply to the value, and arithmetic stays nullable
+        assert_eq!(infer("age + 1"), nullable(InferredType::Integer));
+        assert_eq!(infer("age * 1.5"), nullable(InferredType::Float));
+        assert_eq!(infer("age + 1 >= 18"), InferredType::Boolean);
+        assert_eq!(infer("nick like 'a*'"), InferredType::Boolean);
+        assert_eq!(infer("(age ?? 0) + 1"), InferredType::Integer);
+        assert!(infer("nick - 1").is_error());
+        assert_eq!(
+            visitor.infer_alternative_strategy(&Parser::parse("nick").unwrap()),
+            AlternativeStrategy::NullCheck
+        );
+    }
+
+    #[test]
+    fn test_infer_unknown_fields() {
+        let visitor = TypeInferenceVisitor::with_context(user_context(), "Account");
+        let infer = |source| visitor.infer(&Parser::parse(source).unwrap());
+        let error = |message: &str| InferredType::Error(message.to_string());
+
+        assert_eq!(infer("user.agee"), error("Unknown field 'agee' on User"));
+        assert_eq!(infer("usr.age"), error("Unknown field 'usr' on Account"));
+        assert_eq!(
+            infer("user.age >= 18 && user.address.zip == ''"),
+            error("Unknown field 'zip' on Address")
+        );
+        assert_eq!(
+            infer("if user.nme == '' then 1 else 2"),
+            error("Unknown field 'nme' on User")
+        );
+        assert_eq!(
+            infer("length(user.tgs) > 0"),
+            error("Unknown field 'tgs' on User")
+        );
+
+        // Without a context every field is unknown, never an error
+        assert_eq!(
+            TypeInferenceVisitor::new().infer(&Parser::parse("user.agee").unwrap()),
+            InferredType::Unknown
+        );
+    }
+
+    #[test]
+    fn test_type_from_rust_type() {
+        assert_eq!(
+            InferredType::from_rust_type(&RustType::Option(Box::new(RustType::Time))),
+            InferredType::Nullable(Box::new(InferredType::DateTime))
+        );
+        assert_eq!(
+            InferredType::from_rust_type(&RustType::Custom("User".to_string())).to_string(),
+            "User"
+        );
+    }
+
     #[test]
     fn test_type_is_scalar() {
         assert!(InferredType::Integer.is_scalar());
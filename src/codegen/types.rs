@@ -1,6 +1,8 @@
 //! Type system mapping between ELO and Rust types
 
 use std::collections::HashMap;
+use std::ops::Deref;
+use std::sync::Arc;
 
 /// Represents the Rust type equivalent of an ELO type
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -47,6 +49,22 @@ impl RustType {
         }
     }
 
+    /// Resolve an ELO type name as written in a field declaration
+    ///
+    /// Unrecognised names are treated as custom types.
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "String" => Self::String,
+            "Integer" | "Int" => Self::Integer,
+            "Float" | "Number" => Self::Float,
+            "Bool" | "Boolean" => Self::Bool,
+            "Date" => Self::Date,
+            "Time" | "DateTime" => Self::Time,
+            "Duration" => Self::Duration,
+            other => Self::Custom(other.to_string()),
+        }
+    }
+
     /// Check if this type is compatible with another type
     pub fn is_compatible_with(&self, other: &RustType) -> bool {
         match (self, other) {
@@ -83,6 +101,8 @@ pub struct TypeInfo {
     pub name: String,
     /// Field names and their types
     fields: HashMap<String, RustType>,
+    /// Unit of measure annotations for numeric fields (field -> unit symbol)
+    units: HashMap<String, String>,
 }
 
 impl TypeInfo {
@@ -91,6 +111,7 @@ impl TypeInfo {
         Self {
             name: name.to_string(),
             fields: HashMap::new(),
+            units: HashMap::new(),
         }
     }
 
@@ -99,6 +120,58 @@ impl TypeInfo {
         self.fields.insert(name.to_string(), field_type);
     }
 
+    /// Add a numeric field annotated with a unit of measure (e.g., "kg")
+    pub fn add_field_with_unit(&mut self, name: &str, field_type: RustType, unit: &str) {
+        self.fields.insert(name.to_string(), field_type);
+        self.units.insert(name.to_string(), unit.to_string());
+    }
+
+    /// Add a field from a declaration such as `weight: Float (kg)`
+    ///
+    /// The parenthesised unit is optional.
+    pub fn add_field_declaration(&mut self, declaration: &str) -> Result<(), String> {
+        let (name, rest) = declaration.split_once(':').ok_or_else(|| {
+            format!(
+                "Expected 'name: Type' in field declaration '{}'",
+                declaration
+            )
+        })?;
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(format!("Missing field name in '{}'", declaration));
+        }
+
+        let rest = rest.trim();
+        let (type_name, unit) = match rest.split_once('(') {
+            Some((type_name, unit)) => {
+                let unit = unit
+                    .strip_suffix(')')
+                    .ok_or_else(|| format!("Unclosed unit annotation in '{}'", declaration))?
+                    .trim();
+                if unit.is_empty() {
+                    return Err(format!("Empty unit annotation in '{}'", declaration));
+                }
+                (type_name.trim(), Some(unit))
+            }
+            None => (rest, None),
+        };
+        if type_name.is_empty() {
+            return Err(format!("Missing type in '{}'", declaration));
+        }
+
+        let field_type = RustType::from_name(type_name);
+        match unit {
+            Some(unit) => self.add_field_with_unit(name, field_type, unit),
+            None => self.add_field(name, field_type),
+        }
+        Ok(())
+    }
+
+    /// Get the unit annotation of a field, if any
+    pub fn get_field_unit(&self, name: &str) -> Option<&str> {
+        self.units.get(name).map(String::as_str)
+    }
+
     /// Get the type of a field
     pub fn get_field(&self, name: &str) -> Option<&RustType> {
         self.fields.get(name)
@@ -134,20 +207,36 @@ impl TypeInfo {
 ///     Some(&RustType::String)
 /// );
 /// ```
+///
+/// Clones share the registry and copy it on the first `register_type`, so
+/// cloning a context per compilation unit is cheap.
 #[derive(Debug, Clone, Default)]
 pub struct TypeContext {
-    /// Registered user-defined types (name -> type info)
-    types: HashMap<String, TypeInfo>,
+    /// Registered user-defined types (name -> type info), shared between clones
+    types: Arc<HashMap<String, TypeInfo>>,
 }
 
 impl TypeContext {
     /// Create a new empty type context
     pub fn new() -> Self {
-        Self {
-            types: HashMap::new(),
+        Self::default()
+    }
+
+    /// Take an immutable snapshot that can be shared across threads
+    ///
+    /// The snapshot shares storage with this context; later registrations
+    /// on either side copy the registry instead of affecting the other.
+    pub fn freeze(&self) -> FrozenTypeContext {
+        FrozenTypeContext {
+            context: self.clone(),
         }
     }
 
+    /// Check if two contexts share the same registry storage
+    pub fn shares_storage_with(&self, other: &TypeContext) -> bool {
+        Arc::ptr_eq(&self.types, &other.types)
+    }
+
     /// Register a new custom type in the context
     ///
     /// # Arguments
@@ -155,7 +244,12 @@ impl TypeContext {
     /// * `name` - The name of the type (e.g., "User", "Product")
     /// * `type_info` - The type information including field definitions
     pub fn register_type(&mut self, name: &str, type_info: TypeInfo) {
-        self.types.insert(name.to_string(), type_info);
+        Arc::make_mut(&mut self.types).insert(name.to_string(), type_info);
+    }
+
+    /// Look up a registered type
+    pub fn get_type(&self, name: &str) -> Option<&TypeInfo> {
+        self.types.get(name)
     }
 
     /// Look up the type of a field in a registered type
@@ -174,6 +268,13 @@ impl TypeContext {
             .and_then(|t| t.get_field(field_name))
     }
 
+    /// Look up the unit annotation of a field in a registered type
+    pub fn get_field_unit(&self, type_name: &str, field_name: &str) -> Option<&str> {
+        self.types
+            .get(type_name)
+            .and_then(|t| t.get_field_unit(field_name))
+    }
+
     /// Check if this context has any registered types
     pub fn is_empty(&self) -> bool {
         self.types.is_empty()
@@ -226,6 +327,34 @@ impl TypeContext {
     }
 }
 
+/// Immutable, thread-safe handle to a [`TypeContext`]
+///
+/// Produced by [`TypeContext::freeze`]. Cloning is a reference count bump,
+/// so parallel compilation workers and long-lived servers can each hold
+/// one. Lookups go through `Deref`; use [`FrozenTypeContext::to_context`]
+/// to extend a copy with more types.
+#[derive(Debug, Clone, Default)]
+pub struct FrozenTypeContext {
+    context: TypeContext,
+}
+
+impl FrozenTypeContext {
+    /// Get a mutable context that starts from this snapshot
+    ///
+    /// The returned context shares storage until a type is registered.
+    pub fn to_context(&self) -> TypeContext {
+        self.context.clone()
+    }
+}
+
+impl Deref for FrozenTypeContext {
+    type Target = TypeContext;
+
+    fn deref(&self) -> &TypeContext {
+        &self.context
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -237,6 +366,63 @@ mod tests {
         assert_eq!(RustType::Bool.to_rust_string(), "bool");
     }
 
+    #[test]
+    fn test_field_declaration_with_unit() {
+        let mut info = TypeInfo::new("Parcel");
+        info.add_field_declaration("weight: Float (kg)").unwrap();
+        info.add_field_declaration("label: String").unwrap();
+        assert_eq!(info.get_field("weight"), Some(&RustType::Float));
+        assert_eq!(info.get_field_unit("weight"), Some("kg"));
+        assert_eq!(info.get_field_unit("label"), None);
+    }
+
+    #[test]
+    fn test_field_declaration_errors() {
+        let mut info = TypeInfo::new("Parcel");
+        assert!(info.add_field_declaration("weight Float").is_err());
+        assert!(info.add_field_declaration("weight: Float (kg").is_err());
+        assert!(info.add_field_declaration("weight: Float ()").is_err());
+    }
+
+    #[test]
+    fn test_clone_is_copy_on_write() {
+        let mut base = TypeContext::new();
+        base.register_type("User", TypeInfo::new("User"));
+
+        let mut extended = base.clone();
+        assert!(extended.shares_storage_with(&base));
+
+        extended.register_type("Order", TypeInfo::new("Order"));
+        assert!(!extended.shares_storage_with(&base));
+        assert_eq!(base.list_all_type_names(), vec!["User".to_string()]);
+        assert_eq!(extended.list_all_type_names().len(), 2);
+    }
+
+    #[test]
+    fn test_frozen_context_across_threads() {
+        let mut info = TypeInfo::new("User");
+        info.add_field("age", RustType::Integer);
+        let mut context = TypeContext::new();
+        context.register_type("User", info);
+        let frozen = context.freeze();
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let frozen = frozen.clone();
+                std::thread::spawn(move || frozen.get_field_type("User", "age").cloned())
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), Some(RustType::Integer));
+        }
+
+        // Extending a thawed copy leaves the snapshot untouched
+        let mut thawed = frozen.to_context();
+        assert!(thawed.shares_storage_with(&frozen));
+        thawed.register_type("Order", TypeInfo::new("Order"));
+        assert_eq!(frozen.list_all_type_names().len(), 1);
+    }
+
     #[test]
     fn test_option_type_string() {
         let opt_string = RustType::Option(Box::new(RustType::String));
@@ -0,0 +1,525 @@
+//! Units of measure for numeric fields and literals
+//!
+//! Fields can be annotated with a unit in their [`TypeInfo`](super::types::TypeInfo)
+//! declaration (`weight: Float (kg)`) and rule literals can carry a unit suffix
+//! (`weight < 50kg`). The [`UnitChecker`] compares both sides of comparisons and
+//! additive arithmetic, flagging mismatches and normalising compatible literals
+//! to the unit of the field they are compared with.
+
+use crate::ast::visitor::fold_children;
+use crate::ast::{BinaryOperator, Expr, Fold, Literal};
+use crate::runtime::Severity;
+
+use super::types::TypeContext;
+
+/// Physical dimension a unit measures
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dimension {
+    /// Mass (base unit: kilogram)
+    Mass,
+    /// Length (base unit: metre)
+    Length,
+    /// Time (base unit: second)
+    Time,
+    /// Volume (base unit: litre)
+    Volume,
+}
+
+/// A known unit of measure
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Unit {
+    /// Unit symbol as written in rules (e.g., "kg")
+    pub symbol: &'static str,
+    /// Dimension the unit measures
+    pub dimension: Dimension,
+    /// Multiplier converting a value in this unit to the dimension's base unit
+    pub factor: f64,
+}
+
+const UNITS: &[Unit] = &[
+    Unit {
+        symbol: "kg",
+        dimension: Dimension::Mass,
+        factor: 1.0,
+    },
+    Unit {
+        symbol: "g",
+        dimension: Dimension::Mass,
+        factor: 0.001,
+    },
+    Unit {
+        symbol: "mg",
+        dimension: Dimension::Mass,
+        factor: 0.000_001,
+    },
+    Unit {
+        symbol: "t",
+        dimension: Dimension::Mass,
+        factor: 1000.0,
+    },
+    Unit {
+        symbol: "lb",
+        dimension: Dimension::Mass,
+        factor: 0.453_592_37,
+    },
+    Unit {
+        symbol: "oz",
+        dimension: Dimension::Mass,
+        factor: 0.028_349_523_125,
+    },
+    Unit {
+        symbol: "m",
+        dimension: Dimension::Length,
+        factor: 1.0,
+    },
+    Unit {
+        symbol: "km",
+        dimension: Dimension::Length,
+        factor: 1000.0,
+    },
+    Unit {
+        symbol: "cm",
+        dimension: Dimension::Length,
+        factor: 0.01,
+    },
+    Unit {
+        symbol: "mm",
+        dimension: Dimension::Length,
+        factor: 0.001,
+    },
+    Unit {
+        symbol: "ft",
+        dimension: Dimension::Length,
+        factor: 0.3048,
+    },
+    Unit {
+        symbol: "yd",
+        dimension: Dimension::Length,
+        factor: 0.9144,
+    },
+    Unit {
+        symbol: "mi",
+        dimension: Dimension::Length,
+        factor: 1609.344,
+    },
+    Unit {
+        symbol: "s",
+        dimension: Dimension::Time,
+        factor: 1.0,
+    },
+    Unit {
+        symbol: "ms",
+        dimension: Dimension::Time,
+        factor: 0.001,
+    },
+    Unit {
+        symbol: "min",
+        dimension: Dimension::Time,
+        factor: 60.0,
+    },
+    Unit {
+        symbol: "h",
+        dimension: Dimension::Time,
+        factor: 3600.0,
+    },
+    Unit {
+        symbol: "l",
+        dimension: Dimension::Volume,
+        factor: 1.0,
+    },
+    Unit {
+        symbol: "ml",
+        dimension: Dimension::Volume,
+        factor: 0.001,
+    },
+];
+
+/// Look up a unit by its symbol
+pub fn lookup_unit(symbol: &str) -> Option<&'static Unit> {
+    UNITS.iter().find(|u| u.symbol == symbol)
+}
+
+/// Convert a value between two units of the same dimension
+///
+/// Returns `None` if either unit is unknown or the dimensions differ.
+pub fn convert(value: f64, from: &str, to: &str) -> Option<f64> {
+    let from = lookup_unit(from)?;
+    let to = lookup_unit(to)?;
+    if from.dimension != to.dimension {
+        return None;
+    }
+    Some(value * from.factor / to.factor)
+}
+
+/// How the checker treats values of the same dimension in different units
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnitPolicy {
+    /// Convert literals (and scale fields) to the unit they are compared with
+    #[default]
+    Convert,
+    /// Report differing units as an error
+    Reject,
+}
+
+/// A problem found while checking units
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnitDiagnostic {
+    /// Whether the problem prevents code generation
+    pub severity: Severity,
+    /// Human-readable description
+    pub message: String,
+}
+
+impl UnitDiagnostic {
+    fn error(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            message: message.into(),
+        }
+    }
+
+    fn warning(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message: message.into(),
+        }
+    }
+}
+
+/// Result of a unit check: the normalised expression plus any diagnostics
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnitCheck {
+    /// Expression with compatible literals converted to their field's unit
+    pub expr: Expr,
+    /// Warnings and errors found during the check
+    pub diagnostics: Vec<UnitDiagnostic>,
+}
+
+impl UnitCheck {
+    /// Check if any diagnostic is an error
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics
+            .iter()
+            .any(|d| d.severity == Severity::Error)
+    }
+}
+
+/// Unit carried by one operand of a comparison or sum
+enum Operand<'e> {
+    /// A literal with a unit suffix
+    Quantity(f64, &'e str),
+    /// A field annotated with a unit
+    Field(&'e str, &'e str),
+    /// A plain numeric literal
+    Unitless,
+    /// Anything the checker cannot reason about
+    Unknown,
+}
+
+/// Bottom-up pass reconciling the units of operands
+struct UnitRewriter<'c, 'a> {
+    checker: &'c UnitChecker<'a>,
+    diagnostics: Vec<UnitDiagnostic>,
+}
+
+impl Fold for UnitRewriter<'_, '_> {
+    fn fold_expr(&mut self, expr: Expr) -> Expr {
+        match fold_children(self, expr) {
+            Expr::Quantity { value, unit } => {
+                if lookup_unit(&unit).is_none() {
+                    self.diagnostics
+                        .push(UnitDiagnostic::error(format!("Unknown unit '{}'", unit)));
+                }
+                Expr::Quantity { value, unit }
+            }
+            Expr::BinaryOp { op, left, right } if UnitChecker::is_unit_sensitive(op) => {
+                let (left, right) = self.checker.reconcile(*left, *right, &mut self.diagnostics);
+                Expr::BinaryOp {
+                    op,
+                    left: Box::new(left),
+                    right: Box::new(right),
+                }
+            }
+            Expr::NullDefault { value, default } => {
+                let (value, default) =
+                    self.checker
+                        .reconcile(*value, *default, &mut self.diagnostics);
+                Expr::NullDefault {
+                    value: Box::new(value),
+                    default: Box::new(default),
+                }
+            }
+            expr => expr,
+        }
+    }
+}
+
+/// Checks unit consistency of an expression against a type's field annotations
+#[derive(Debug)]
+pub struct UnitChecker<'a> {
+    context: &'a TypeContext,
+    type_name: String,
+    policy: UnitPolicy,
+}
+
+impl<'a> UnitChecker<'a> {
+    /// Create a checker resolving fields of `type_name` in `context`
+    pub fn new(context: &'a TypeContext, type_name: &str) -> Self {
+        Self {
+            context,
+            type_name: type_name.to_string(),
+            policy: UnitPolicy::default(),
+        }
+    }
+
+    /// Set how differing units of the same dimension are handled
+    pub fn with_policy(mut self, policy: UnitPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Check an expression, returning the normalised expression and diagnostics
+    pub fn check(&self, expr: &Expr) -> UnitCheck {
+        let mut rewriter = UnitRewriter {
+            checker: self,
+            diagnostics: Vec::new(),
+        };
+        let expr = rewriter.fold_expr(expr.clone());
+        UnitCheck {
+            expr,
+            diagnostics: rewriter.diagnostics,
+        }
+    }
+
+    fn is_unit_sensitive(op: BinaryOperator) -> bool {
+        matches!(
+            op,
+            BinaryOperator::Add
+                | BinaryOperator::Sub
+                | BinaryOperator::Eq
+                | BinaryOperator::Neq
+                | BinaryOperator::Lt
+                | BinaryOperator::Lte
+                | BinaryOperator::Gt
+                | BinaryOperator::Gte
+        )
+    }
+
+    fn operand<'e>(&'e self, expr: &'e Expr) -> Operand<'e> {
+        match expr {
+            Expr::Quantity { value, unit } => Operand::Quantity(*value, unit),
+            Expr::Literal(Literal::Integer(_))
+            | Expr::Literal(Literal::Float(_))
+            | Expr::Percentage(_) => Operand::Unitless,
+            Expr::Identifier(field) => self.field_operand(field),
+            Expr::FieldAccess { receiver, field } if matches!(**receiver, Expr::Identifier(_)) => {
+                self.field_operand(field)
+            }
+            _ => Operand::Unknown,
+        }
+    }
+
+    fn field_operand<'e>(&'e self, field: &'e str) -> Operand<'e> {
+        match self.context.get_field_unit(&self.type_name, field) {
+            Some(unit) => Operand::Field(field, unit),
+            None => Operand::Unknown,
+        }
+    }
+
+    /// Bring both operands of a comparison or sum into the same unit
+    fn reconcile(&self, left: Expr, right: Expr, diags: &mut Vec<UnitDiagnostic>) -> (Expr, Expr) {
+        let (new_left, new_right) = match (self.operand(&left), self.operand(&right)) {
+            (Operand::Field(field, unit), Operand::Unitless)
+            | (Operand::Unitless, Operand::Field(field, unit)) => {
+                diags.push(UnitDiagnostic::warning(format!(
+                    "'{}' is measured in {} but is combined with a value without a unit",
+                    field, unit
+                )));
+                (None, None)
+            }
+            (Operand::Field(field, target), Operand::Quantity(value, unit)) => (
+                None,
+                self.convert_literal(field, target, value, unit, diags),
+            ),
+            (Operand::Quantity(value, unit), Operand::Field(field, target)) => (
+                self.convert_literal(field, target, value, unit, diags),
+                None,
+            ),
+            (Operand::Quantity(_, target), Operand::Quantity(value, unit)) => (
+                None,
+                self.convert_literal("literal", target, value, unit, diags),
+            ),
+            (Operand::Field(_, target), Operand::Field(field, unit)) => {
+                (None, self.scale_field(&right, field, target, unit, diags))
+            }
+            _ => (None, None),
+        };
+        (new_left.unwrap_or(left), new_right.unwrap_or(right))
+    }
+
+    /// Convert a literal to `target`, returning the replacement if one is needed
+    fn convert_literal(
+        &self,
+        subject: &str,
+        target: &str,
+        value: f64,
+        unit: &str,
+        diags: &mut Vec<UnitDiagnostic>,
+    ) -> Option<Expr> {
+        if unit == target {
+            return None;
+        }
+        let converted = self.compatible(subject, target, unit, diags)?;
+        Some(Expr::Quantity {
+            value: converted * value,
+            unit: target.to_string(),
+        })
+    }
+
+    /// Scale a field expression in `unit` so it is expressed in `target`
+    fn scale_field(
+        &self,
+        expr: &Expr,
+        field: &str,
+        target: &str,
+        unit: &str,
+        diags: &mut Vec<UnitDiagnostic>,
+    ) -> Option<Expr> {
+        if unit == target {
+            return None;
+        }
+        let factor = self.compatible(field, target, unit, diags)?;
+        Some(Expr::BinaryOp {
+            op: BinaryOperator::Mul,
+            left: Box::new(expr.clone()),
+            right: Box::new(Expr::Literal(Literal::Float(factor))),
+        })
+    }
+
+    /// Conversion factor from `unit` to `target`, or a diagnostic explaining why not
+    fn compatible(
+        &self,
+        subject: &str,
+        target: &str,
+        unit: &str,
+        diags: &mut Vec<UnitDiagnostic>,
+    ) -> Option<f64> {
+        let (Some(from), Some(to)) = (lookup_unit(unit), lookup_unit(target)) else {
+            // Unknown units are reported where the literal or annotation appears
+            if lookup_unit(target).is_none() {
+                diags.push(UnitDiagnostic::error(format!(
+                    "Unknown unit '{}' on '{}'",
+                    target, subject
+                )));
+            }
+            return None;
+        };
+        if from.dimension != to.dimension {
+            diags.push(UnitDiagnostic::error(format!(
+                "Incompatible units: '{}' is in {} but is combined with {}",
+                subject, target, unit
+            )));
+            return None;
+        }
+        if self.policy == UnitPolicy::Reject {
+            diags.push(UnitDiagnostic::error(format!(
+                "Unit mismatch: '{}' is in {} but is combined with {}",
+                subject, target, unit
+            )));
+            return None;
+        }
+        Some(from.factor / to.factor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegen::types::TypeInfo;
+    use crate::parser::Parser;
+
+    fn parcel_context() -> TypeContext {
+        let mut parcel = TypeInfo::new("Parcel");
+        parcel.add_field_declaration("weight: Float (kg)").unwrap();
+        parcel.add_field_declaration("length: Float (m)").unwrap();
+        parcel.add_field_declaration("width: Float (cm)").unwrap();
+        let mut context = TypeContext::new();
+        context.register_type("Parcel", parcel);
+        context
+    }
+
+    fn check(source: &str, policy: UnitPolicy) -> UnitCheck {
+        let context = parcel_context();
+        let expr = Parser::parse(source).unwrap();
+        UnitChecker::new(&context, "Parcel")
+            .with_policy(policy)
+            .check(&expr)
+    }
+
+    #[test]
+    fn test_convert_between_units() {
+        assert!((convert(1.0, "lb", "kg").unwrap() - 0.453_592_37).abs() < 1e-12);
+        assert_eq!(convert(2.0, "km", "m"), Some(2000.0));
+        assert_eq!(convert(1.0, "kg", "m"), None);
+        assert_eq!(convert(1.0, "parsec", "m"), None);
+    }
+
+    #[test]
+    fn test_matching_unit_is_clean() {
+        let result = check("weight < 50kg", UnitPolicy::Convert);
+        assert!(result.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_unitless_literal_warns() {
+        let result = check("weight < 50", UnitPolicy::Convert);
+        assert_eq!(result.diagnostics.len(), 1);
+        assert_eq!(result.diagnostics[0].severity, Severity::Warning);
+        assert!(!result.has_errors());
+    }
+
+    #[test]
+    fn test_compatible_unit_is_converted() {
+        let result = check("input.weight < 100lb", UnitPolicy::Convert);
+        assert!(result.diagnostics.is_empty());
+        match result.expr {
+            Expr::BinaryOp { right, .. } => match *right {
+                Expr::Quantity { value, unit } => {
+                    assert_eq!(unit, "kg");
+                    assert!((value - 45.359_237).abs() < 1e-9);
+                }
+                other => panic!("Expected converted quantity, got {:?}", other),
+            },
+            _ => panic!("Expected binary op"),
+        }
+    }
+
+    #[test]
+    fn test_compatible_unit_rejected_by_policy() {
+        let result = check("weight < 100lb", UnitPolicy::Reject);
+        assert!(result.has_errors());
+    }
+
+    #[test]
+    fn test_incompatible_dimension_is_error() {
+        let result = check("weight < 5m", UnitPolicy::Convert);
+        assert!(result.has_errors());
+    }
+
+    #[test]
+    fn test_unknown_unit_is_error() {
+        let result = check("weight < 5stone", UnitPolicy::Convert);
+        assert!(result.has_errors());
+        assert!(result.diagnostics[0].message.contains("stone"));
+    }
+
+    #[test]
+    fn test_fields_in_different_units_are_scaled() {
+        let result = check("length > width", UnitPolicy::Convert);
+        assert!(result.diagnostics.is_empty());
+        assert!(matches!(
+            result.expr,
+            Expr::BinaryOp { ref right, .. } if matches!(**right, Expr::BinaryOp { op: BinaryOperator::Mul, .. })
+        ));
+    }
+}
@@ -0,0 +1,698 @@
+//! Project configuration (`elo.toml`)
+//!
+//! A team keeps its rule directories, type sources, code generation options,
+//! lint levels and output layout in one `elo.toml` at the project root
+//! instead of repeating CLI flags. The CLI reads it automatically when
+//! present (or from `--config <file>`); flags given on the command line
+//! override it. Build scripts can load it with [`Config::load`].
+//!
+//! ```toml
+//! [rules]
+//! dirs = ["rules", "shared/rules"]
+//!
+//! [types]
+//! sources = ["src/models.rs"]
+//!
+//! [codegen]
+//! validator_name = "validate"
+//! deny_panics = true
+//! timing = false
+//! fast_path = true
+//!
+//! [lint]
+//! max_ternary_depth = 3
+//!
+//! [lint.levels]
+//! float_equality = "deny"
+//! redundant_parens = "allow"
+//!
+//! [output]
+//! dir = "src/generated"
+//! layout = "per-file"
+//! ```
+//!
+//! Every section and key is optional. The file is checked against this
+//! schema: unknown sections, keys and lint names, values of the wrong type
+//! and paths leaving the project are reported with their line number and,
+//! for misspellings, the closest valid name. Only the subset of TOML used
+//! above is accepted (tables, strings, integers, booleans and single-line
+//! arrays).
+//!
+//! # Example
+//!
+//! ```
+//! use elo_rust::config::{Config, OutputLayout};
+//! use elo_rust::lint::{LintKind, LintLevel};
+//!
+//! let config = Config::parse(
+//!     "[lint.levels]\nfloat_equality = \"deny\"\n\n[output]\nlayout = \"per-file\"",
+//! )
+//! .unwrap();
+//! assert_eq!(config.lint.level(LintKind::FloatEquality), LintLevel::Deny);
+//! assert_eq!(config.output.layout, OutputLayout::PerFile);
+//!
+//! let err = Config::parse("[codegen]\nfastpath = true").unwrap_err();
+//! assert_eq!(
+//!     err.to_string(),
+//!     "elo.toml line 2: unknown key 'fastpath' in [codegen] (did you mean 'fast_path'?)"
+//! );
+//! ```
+
+use crate::lint::{LintConfig, LintKind, LintLevel};
+use crate::security::{read_file_with_limit, validate_file_path};
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+
+/// Name of the configuration file looked up in the working directory
+pub const CONFIG_FILE: &str = "elo.toml";
+
+/// Code generation options
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodegenSettings {
+    /// Name of the generated validator function
+    pub validator_name: String,
+    /// Fail code generation if the generated code could panic
+    pub deny_panics: bool,
+    /// Record per-rule durations in reports (see [`RuleSet::with_timing`](crate::rules::RuleSet::with_timing))
+    pub timing: bool,
+    /// Generate consolidated range checks (see [`RuleSet::with_fast_path`](crate::rules::RuleSet::with_fast_path))
+    pub fast_path: bool,
+}
+
+impl Default for CodegenSettings {
+    fn default() -> Self {
+        Self {
+            validator_name: "validate".to_string(),
+            deny_panics: false,
+            timing: false,
+            fast_path: false,
+        }
+    }
+}
+
+/// How generated files are laid out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputLayout {
+    /// All validators in one `validators.rs`
+    #[default]
+    Single,
+    /// One file per rule file, named after it
+    PerFile,
+}
+
+/// Where generated code is written
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputSettings {
+    /// Output directory, relative to the project root
+    pub dir: PathBuf,
+    /// File layout inside the directory
+    pub layout: OutputLayout,
+}
+
+impl Default for OutputSettings {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from("src/generated"),
+            layout: OutputLayout::Single,
+        }
+    }
+}
+
+/// Parsed `elo.toml`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    /// Directories searched for `.elo` rule files
+    pub rule_dirs: Vec<PathBuf>,
+    /// Rust files declaring the input types of validators
+    pub type_sources: Vec<PathBuf>,
+    /// Code generation options
+    pub codegen: CodegenSettings,
+    /// Lint levels and thresholds
+    pub lint: LintConfig,
+    /// Output layout
+    pub output: OutputSettings,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            rule_dirs: vec![PathBuf::from("rules")],
+            type_sources: Vec::new(),
+            codegen: CodegenSettings::default(),
+            lint: LintConfig::default(),
+            output: OutputSettings::default(),
+        }
+    }
+}
+
+/// An error in a configuration file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    /// Name of the configuration file
+    pub file: String,
+    /// Line number (1-based) of the problem; 0 if it concerns the whole file
+    pub line: usize,
+    /// Description of the problem
+    pub message: String,
+    /// Closest valid name, for misspelled sections, keys and lints
+    pub suggestion: Option<String>,
+}
+
+impl ConfigError {
+    fn new(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            file: CONFIG_FILE.to_string(),
+            line,
+            message: message.into(),
+            suggestion: None,
+        }
+    }
+
+    fn unknown(line: usize, what: String, name: &str, candidates: &[&str]) -> Self {
+        Self {
+            file: CONFIG_FILE.to_string(),
+            line,
+            message: what,
+            suggestion: closest(name, candidates).map(str::to_string),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line > 0 {
+            write!(f, "{} line {}: {}", self.file, self.line, self.message)?;
+        } else {
+            write!(f, "{}: {}", self.file, self.message)?;
+        }
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, " (did you mean '{}'?)", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+const SECTIONS: [&str; 6] = ["rules", "types", "codegen", "lint", "lint.levels", "output"];
+
+/// A parsed TOML value
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+    String(String),
+    Integer(i64),
+    Boolean(bool),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    pub(crate) fn type_name(&self) -> &'static str {
+        match self {
+            Value::String(_) => "a string",
+            Value::Integer(_) => "an integer",
+            Value::Boolean(_) => "a boolean",
+            Value::Array(_) => "an array",
+        }
+    }
+}
+
+impl Config {
+    /// Parse and validate the contents of an `elo.toml`
+    pub fn parse(source: &str) -> Result<Config, ConfigError> {
+        let mut config = Config::default();
+        let mut section: Option<String> = None;
+        let mut seen: Vec<String> = Vec::new();
+
+        for (index, raw) in source.lines().enumerate() {
+            let line = index + 1;
+            let text = strip_comment(raw).trim();
+            if text.is_empty() {
+                continue;
+            }
+            if let Some(header) = text.strip_prefix('[') {
+                let name = header
+                    .strip_suffix(']')
+                    .ok_or_else(|| ConfigError::new(line, "unterminated section header"))?
+                    .trim();
+                if !SECTIONS.contains(&name) {
+                    return Err(ConfigError::unknown(
+                        line,
+                        format!("unknown section [{}]", name),
+                        name,
+                        &SECTIONS,
+                    ));
+                }
+                if seen.iter().any(|s| s == name) {
+                    return Err(ConfigError::new(
+                        line,
+                        format!("duplicate section [{}]", name),
+                    ));
+                }
+                seen.push(name.to_string());
+                section = Some(name.to_string());
+                continue;
+            }
+
+            let (key, value) = text
+                .split_once('=')
+                .ok_or_else(|| ConfigError::new(line, "expected 'key = value'"))?;
+            let key = unquote_key(key.trim())
+                .ok_or_else(|| ConfigError::new(line, format!("invalid key '{}'", key.trim())))?;
+            let value =
+                parse_value(value.trim()).map_err(|message| ConfigError::new(line, message))?;
+            let Some(section) = &section else {
+                return Err(ConfigError::new(
+                    line,
+                    format!("key '{}' must be inside a section such as [codegen]", key),
+                ));
+            };
+            if seen.iter().any(|s| *s == format!("{}.{}", section, key)) {
+                return Err(ConfigError::new(line, format!("duplicate key '{}'", key)));
+            }
+            seen.push(format!("{}.{}", section, key));
+            config.apply(section, &key, value, line)?;
+        }
+        Ok(config)
+    }
+
+    /// Read and parse a configuration file inside the working directory
+    pub fn load(path: &str) -> Result<Config, ConfigError> {
+        validate_file_path(path)
+            .and_then(|safe_path| read_file_with_limit(&safe_path))
+            .map_err(|e| ConfigError::new(0, format!("cannot read file: {}", e)))
+            .and_then(|source| Config::parse(&source))
+            .map_err(|error| ConfigError {
+                file: path.to_string(),
+                ..error
+            })
+    }
+
+    /// Load [`CONFIG_FILE`] from the working directory if it exists
+    pub fn discover() -> Result<Option<Config>, ConfigError> {
+        if Path::new(CONFIG_FILE).is_file() {
+            Config::load(CONFIG_FILE).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// The `.elo` files in the rule directories, sorted by path
+    ///
+    /// Missing directories are skipped.
+    pub fn rule_files(&self) -> std::io::Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        for dir in &self.rule_dirs {
+            if !dir.is_dir() {
+                continue;
+            }
+            for entry in std::fs::read_dir(dir)? {
+                let path = entry?.path();
+                if path.extension().is_some_and(|ext| ext == "elo") && path.is_file() {
+                    files.push(path);
+                }
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+
+    fn apply(
+        &mut self,
+        section: &str,
+        key: &str,
+        value: Value,
+        line: usize,
+    ) -> Result<(), ConfigError> {
+        let keys: &[&str] = match section {
+            "rules" => &["dirs"],
+            "types" => &["sources"],
+            "codegen" => &["validator_name", "deny_panics", "timing", "fast_path"],
+            "lint" => &["max_ternary_depth"],
+            "output" => &["dir", "layout"],
+            _ => &[],
+        };
+        if section == "lint.levels" {
+            let names: Vec<&str> = LintKind::ALL.iter().map(LintKind::name).collect();
+            let kind = LintKind::from_name(key).ok_or_else(|| {
+                ConfigError::unknown(line, format!("unknown lint '{}'", key), key, &names)
+            })?;
+            let level = expect_string(key, value, line)?;
+            let level = LintLevel::from_name(&level).ok_or_else(|| {
+                ConfigError::new(
+                    line,
+                    format!("level of '{}' must be \"allow\", \"warn\" or \"deny\"", key),
+                )
+            })?;
+            self.lint = std::mem::take(&mut self.lint).with_level(kind, level);
+            return Ok(());
+        }
+        if !keys.contains(&key) {
+            return Err(ConfigError::unknown(
+                line,
+                format!("unknown key '{}' in [{}]", key, section),
+                key,
+                keys,
+            ));
+        }
+
+        match (section, key) {
+            ("rules", "dirs") => self.rule_dirs = expect_paths(key, value, line)?,
+            ("types", "sources") => self.type_sources = expect_paths(key, value, line)?,
+            ("codegen", "validator_name") => {
+                let name = expect_string(key, value, line)?;
+                if syn::parse_str::<syn::Ident>(&name).is_err() {
+                    return Err(ConfigError::new(
+                        line,
+                        format!("'{}' is not a valid Rust function name", name),
+                    ));
+                }
+                self.codegen.validator_name = name;
+            }
+            ("codegen", "deny_panics") => self.codegen.deny_panics = expect_bool(key, value, line)?,
+            ("codegen", "timing") => self.codegen.timing = expect_bool(key, value, line)?,
+            ("codegen", "fast_path") => self.codegen.fast_path = expect_bool(key, value, line)?,
+            ("lint", "max_ternary_depth") => match value {
+                Value::Integer(depth) if depth >= 0 => {
+                    self.lint =
+                        std::mem::take(&mut self.lint).with_max_ternary_depth(depth as usize)
+                }
+                other => return Err(wrong_type(key, "a non-negative integer", &other, line)),
+            },
+            ("output", "dir") => {
+                self.output.dir = check_path(expect_string(key, value, line)?, line)?;
+            }
+            ("output", "layout") => {
+                self.output.layout = match expect_string(key, value, line)?.as_str() {
+                    "single" => OutputLayout::Single,
+                    "per-file" => OutputLayout::PerFile,
+                    _ => {
+                        return Err(ConfigError::new(
+                            line,
+                            "layout must be \"single\" or \"per-file\"",
+                        ))
+                    }
+                };
+            }
+            _ => unreachable!("keys are checked above"),
+        }
+        Ok(())
+    }
+}
+
+fn wrong_type(key: &str, expected: &str, found: &Value, line: usize) -> ConfigError {
+    ConfigError::new(
+        line,
+        format!(
+            "'{}' must be {}, found {}",
+            key,
+            expected,
+            found.type_name()
+        ),
+    )
+}
+
+fn expect_bool(key: &str, value: Value, line: usize) -> Result<bool, ConfigError> {
+    match value {
+        Value::Boolean(b) => Ok(b),
+        other => Err(wrong_type(key, "a boolean", &other, line)),
+    }
+}
+
+fn expect_string(key: &str, value: Value, line: usize) -> Result<String, ConfigError> {
+    match value {
+        Value::String(s) => Ok(s),
+        other => Err(wrong_type(key, "a string", &other, line)),
+    }
+}
+
+fn expect_paths(key: &str, value: Value, line: usize) -> Result<Vec<PathBuf>, ConfigError> {
+    match value {
+        Value::Array(items) => items
+            .into_iter()
+            .map(|item| match item {
+                Value::String(path) => check_path(path, line),
+                other => Err(wrong_type(key, "an array of strings", &other, line)),
+            })
+            .collect(),
+        other => Err(wrong_type(key, "an array of strings", &other, line)),
+    }
+}
+
+/// Paths must stay inside the project, like CLI file arguments
+fn check_path(path: String, line: usize) -> Result<PathBuf, ConfigError> {
+    let path_buf = PathBuf::from(&path);
+    if path.trim().is_empty()
+        || path_buf.is_absolute()
+        || path_buf
+            .components()
+            .any(|c| matches!(c, Component::ParentDir))
+    {
+        return Err(ConfigError::new(
+            line,
+            format!(
+                "path '{}' must be relative to the project and must not contain '..'",
+                path
+            ),
+        ));
+    }
+    Ok(path_buf)
+}
+
+/// Remove a `#` comment that is not inside a string
+pub(crate) fn strip_comment(line: &str) -> &str {
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for (i, ch) in line.char_indices() {
+        match (quote, ch) {
+            (Some('"'), '\\') if !escaped => {
+                escaped = true;
+                continue;
+            }
+            (Some(q), c) if c == q && !escaped => quote = None,
+            (None, '"' | '\'') => quote = Some(ch),
+            (None, '#') => return &line[..i],
+            _ => {}
+        }
+        escaped = false;
+    }
+    line
+}
+
+pub(crate) fn unquote_key(key: &str) -> Option<String> {
+    if let Some(inner) = key.strip_prefix('"').and_then(|k| k.strip_suffix('"')) {
+        return Some(inner.to_string());
+    }
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    bare.then(|| key.to_string())
+}
+
+pub(crate) fn parse_value(text: &str) -> Result<Value, String> {
+    let mut chars = text.chars().peekable();
+    let value = parse_item(&mut chars)?;
+    if chars.any(|c| !c.is_whitespace()) {
+        return Err(format!("unexpected text after value '{}'", text));
+    }
+    Ok(value)
+}
+
+fn parse_item(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) -> Result<Value, String> {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+    match chars.peek() {
+        Some('"') => {
+            chars.next();
+            let mut s = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') => return Ok(Value::String(s)),
+                    Some('\\') => match chars.next() {
+                        Some('"') => s.push('"'),
+                        Some('\\') => s.push('\\'),
+                        Some('n') => s.push('\n'),
+                        Some('t') => s.push('\t'),
+                        other => {
+                            return Err(format!(
+                                "unsupported escape '\\{}'",
+                                other.map(String::from).unwrap_or_default()
+                            ))
+                        }
+                    },
+                    Some(c) => s.push(c),
+                    None => return Err("unterminated string".to_string()),
+                }
+            }
+        }
+        Some('\'') => {
+            chars.next();
+            let s: String = chars.by_ref().take_while(|c| *c != '\'').collect();
+            Ok(Value::String(s))
+        }
+        Some('[') => {
+            chars.next();
+            let mut items = Vec::new();
+            loop {
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                if chars.next_if_eq(&']').is_some() {
+                    return Ok(Value::Array(items));
+                }
+                if chars.peek().is_none() {
+                    return Err("arrays must be closed on the same line".to_string());
+                }
+                items.push(parse_item(chars)?);
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                match chars.next() {
+                    Some(',') => {}
+                    Some(']') => return Ok(Value::Array(items)),
+                    None => return Err("arrays must be closed on the same line".to_string()),
+                    Some(c) => return Err(format!("expected ',' or ']' in array, found '{}'", c)),
+                }
+            }
+        }
+        _ => {
+            let mut word = String::new();
+            while let Some(c) = chars.next_if(|c| !matches!(c, ',' | ']') && !c.is_whitespace()) {
+                word.push(c);
+            }
+            match word.as_str() {
+                "true" => Ok(Value::Boolean(true)),
+                "false" => Ok(Value::Boolean(false)),
+                _ => word
+                    .replace('_', "")
+                    .parse()
+                    .map(Value::Integer)
+                    .map_err(|_| format!("invalid value '{}' (strings must be quoted)", word)),
+            }
+        }
+    }
+}
+
+/// The candidate closest to `name`, if it is a plausible misspelling
+pub(crate) fn closest<'a>(name: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    candidates
+        .iter()
+        .map(|c| (edit_distance(name, c), *c))
+        .filter(|(distance, candidate)| *distance <= 2.max(candidate.len() / 3))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_full_config() {
+        let config = Config::parse(
+            r#"
+# Team defaults
+[rules]
+dirs = ["rules", 'shared/rules'] # trailing comment
+
+[types]
+sources = ["src/models.rs"]
+
+[codegen]
+validator_name = "check_order"
+deny_panics = true
+fast_path = true
+
+[lint]
+max_ternary_depth = 3
+
+[lint.levels]
+float_equality = "deny"
+redundant_parens = "allow"
+
+[output]
+dir = "src/generated"
+layout = "per-file"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.rule_dirs,
+            vec![PathBuf::from("rules"), PathBuf::from("shared/rules")]
+        );
+        assert_eq!(config.type_sources, vec![PathBuf::from("src/models.rs")]);
+        assert_eq!(config.codegen.validator_name, "check_order");
+        assert!(config.codegen.deny_panics && config.codegen.fast_path);
+        assert!(!config.codegen.timing);
+        assert_eq!(config.lint.level(LintKind::FloatEquality), LintLevel::Deny);
+        assert_eq!(
+            config.lint.level(LintKind::RedundantParens),
+            LintLevel::Allow
+        );
+        assert_eq!(config.lint.level(LintKind::NestedTernary), LintLevel::Warn);
+        assert_eq!(
+            config.lint,
+            LintConfig::new()
+                .with_level(LintKind::FloatEquality, LintLevel::Deny)
+                .with_level(LintKind::RedundantParens, LintLevel::Allow)
+                .with_max_ternary_depth(3)
+        );
+        assert_eq!(config.output.layout, OutputLayout::PerFile);
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn test_schema_errors() {
+        let error = |source: &str| Config::parse(source).unwrap_err().to_string();
+        assert_eq!(
+            error("[codgen]"),
+            "elo.toml line 1: unknown section [codgen] (did you mean 'codegen'?)"
+        );
+        assert_eq!(
+            error("[lint.levels]\nfloat_equalty = \"deny\""),
+            "elo.toml line 2: unknown lint 'float_equalty' (did you mean 'float_equality'?)"
+        );
+        assert_eq!(
+            error("[lint.levels]\nfloat_equality = \"forbid\""),
+            "elo.toml line 2: level of 'float_equality' must be \"allow\", \"warn\" or \"deny\""
+        );
+        assert_eq!(
+            error("[codegen]\ntiming = \"yes\""),
+            "elo.toml line 2: 'timing' must be a boolean, found a string"
+        );
+        assert_eq!(
+            error("[rules]\ndirs = [\"../outside\"]"),
+            "elo.toml line 2: path '../outside' must be relative to the project and must not contain '..'"
+        );
+        assert_eq!(
+            error("[output]\nlayout = per-file"),
+            "elo.toml line 2: invalid value 'per-file' (strings must be quoted)"
+        );
+        assert_eq!(
+            error("timing = true"),
+            "elo.toml line 1: key 'timing' must be inside a section such as [codegen]"
+        );
+        assert_eq!(
+            error("[codegen]\ntiming = true\ntiming = false"),
+            "elo.toml line 3: duplicate key 'timing'"
+        );
+        assert_eq!(
+            error("[rules]\ndirs = [\"a\","),
+            "elo.toml line 2: arrays must be closed on the same line"
+        );
+        assert_eq!(
+            error("[codegen]\nvalidator_name = \"not valid\""),
+            "elo.toml line 2: 'not valid' is not a valid Rust function name"
+        );
+    }
+}
@@ -0,0 +1,972 @@
+//! Conformance fixtures for ELO backends
+//!
+//! A fixture pairs an expression and an input with the result this crate
+//! defines for it, either a value or an error. The fixtures live in
+//! `tests/conformance/*.fixture` and are embedded in the crate, so any
+//! backend (the evaluator, generated code, or an implementation outside
+//! this crate) can be checked against the same semantics by implementing
+//! [`Backend`] and calling [`run`].
+//!
+//! # Fixture format
+//!
+//! ```text
+//! # Comments start with '#'
+//! test adult by age
+//! expr: age >= 18
+//! input: {"age": 20}
+//! expect: true
+//!
+//! test division by zero
+//! expr: age / 0
+//! input: {"age": 20}
+//! error: Division by zero
+//! ```
+//!
+//! Each fixture starts with a `test <name>` line and needs `expr:` and
+//! exactly one of `expect:` (a JSON value) or `error:` (text the error
+//! message must contain). `input:` defaults to `{}`; `context:` is a JSON
+//! object visible to the expression as `ctx`.
+//!
+//! Only available with the `conformance` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use elo_rust::conformance::{self, InterpreterBackend};
+//!
+//! let report = conformance::run(&InterpreterBackend, &conformance::fixtures());
+//! assert!(report.is_success(), "{}", report);
+//! ```
+
+use crate::ast::analysis::field_dependencies;
+use crate::codegen::ast_to_code::GuardPolicy;
+use crate::codegen::float::float_literal;
+use crate::codegen::type_inference::CONTEXT_ROOT;
+use crate::codegen::types::{RustType, TypeContext, TypeInfo};
+use crate::codegen::{rust_ident, RustCodeGenerator};
+use crate::parser::Parser;
+use crate::runtime::interpreter::{EvalError, Evaluator};
+use crate::runtime::stream::parse_json;
+use crate::runtime::EloValue;
+use proc_macro2::TokenStream;
+use quote::quote;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+/// Fixture files shipped with the crate, in file name order
+const BUILTIN: &[(&str, &str)] = &[
+    (
+        "collections.fixture",
+        include_str!("../tests/conformance/collections.fixture"),
+    ),
+    (
+        "errors.fixture",
+        include_str!("../tests/conformance/errors.fixture"),
+    ),
+    (
+        "functions.fixture",
+        include_str!("../tests/conformance/functions.fixture"),
+    ),
+    (
+        "nulls.fixture",
+        include_str!("../tests/conformance/nulls.fixture"),
+    ),
+    (
+        "operators.fixture",
+        include_str!("../tests/conformance/operators.fixture"),
+    ),
+];
+
+/// Result a fixture expects
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expected {
+    /// The expression evaluates to this value
+    Value(EloValue),
+    /// Parsing or evaluation fails with a message containing this text
+    Error(String),
+}
+
+/// One expression, its input and the result it must produce
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fixture {
+    /// Name from the `test` line
+    pub name: String,
+    /// File and line of the `test` line (e.g. `operators.fixture:12`)
+    pub location: String,
+    /// ELO source of the expression
+    pub expr: String,
+    /// Input object; `context` is available under `ctx`
+    pub input: EloValue,
+    /// Expected result
+    pub expected: Expected,
+}
+
+/// Error in a fixture file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixtureError {
+    /// File and line of the error
+    pub location: String,
+    /// What is wrong
+    pub message: String,
+}
+
+impl fmt::Display for FixtureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.message)
+    }
+}
+
+impl std::error::Error for FixtureError {}
+
+/// Fixture being read, before its fields are checked
+struct Draft {
+    name: String,
+    location: String,
+    expr: Option<String>,
+    input: Option<EloValue>,
+    context: Option<EloValue>,
+    expected: Option<Expected>,
+}
+
+impl Draft {
+    fn finish(self) -> Result<Fixture, FixtureError> {
+        let error = |message: &str| FixtureError {
+            location: self.location.clone(),
+            message: format!("test '{}' {}", self.name, message),
+        };
+        let expr = self.expr.clone().ok_or_else(|| error("has no expr"))?;
+        let expected = self
+            .expected
+            .clone()
+            .ok_or_else(|| error("has neither expect nor error"))?;
+        let mut input = self
+            .input
+            .clone()
+            .unwrap_or_else(|| EloValue::Object(BTreeMap::new()));
+        if let Some(context) = self.context.clone() {
+            match &mut input {
+                EloValue::Object(fields) => {
+                    fields.insert(CONTEXT_ROOT.to_string(), context);
+                }
+                _ => return Err(error("has context but its input is not an object")),
+            }
+        }
+        Ok(Fixture {
+            name: self.name,
+            location: self.location,
+            expr,
+            input,
+            expected,
+        })
+    }
+}
+
+/// Parse fixtures from the text of a fixture file named `file`
+pub fn parse_fixtures(file: &str, source: &str) -> Result<Vec<Fixture>, FixtureError> {
+    let mut fixtures = Vec::new();
+    let mut draft: Option<Draft> = None;
+    for (index, line) in source.lines().enumerate() {
+        let location = format!("{}:{}", file, index + 1);
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let error = |message: String| FixtureError {
+            location: location.clone(),
+            message,
+        };
+        if let Some(name) = line.strip_prefix("test ") {
+            if let Some(done) = draft.take() {
+                fixtures.push(done.finish()?);
+            }
+            draft = Some(Draft {
+                name: name.trim().to_string(),
+                location,
+                expr: None,
+                input: None,
+                context: None,
+                expected: None,
+            });
+            continue;
+        }
+        let current = draft
+            .as_mut()
+            .ok_or_else(|| error(format!("expected 'test <name>', found '{}'", line)))?;
+        let (key, value) = line
+            .split_once(':')
+            .ok_or_else(|| error(format!("expected 'key: value', found '{}'", line)))?;
+        let value = value.trim();
+        let json = |value: &str| parse_json(value).map_err(|e| error(format!("{}: {}", key, e)));
+        let slot_taken = match key.trim() {
+            "expr" => current.expr.replace(value.to_string()).is_some(),
+            "input" => current.input.replace(json(value)?).is_some(),
+            "context" => current.context.replace(json(value)?).is_some(),
+            "expect" => current
+                .expected
+                .replace(Expected::Value(json(value)?))
+                .is_some(),
+            "error" => current
+                .expected
+                .replace(Expected::Error(value.to_string()))
+                .is_some(),
+            other => return Err(error(format!("unknown key '{}'", other))),
+        };
+        if slot_taken {
+            return Err(error(format!(
+                "test '{}' sets {} twice",
+                current.name,
+                key.trim()
+            )));
+        }
+    }
+    if let Some(done) = draft {
+        fixtures.push(done.finish()?);
+    }
+    Ok(fixtures)
+}
+
+/// Read the fixtures of every `.fixture` file in `dir`, in file name order
+pub fn load_dir(dir: &Path) -> Result<Vec<Fixture>, FixtureError> {
+    let io_error = |error: std::io::Error| FixtureError {
+        location: dir.display().to_string(),
+        message: error.to_string(),
+    };
+    let mut paths: Vec<_> = std::fs::read_dir(dir)
+        .map_err(io_error)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "fixture"))
+        .collect();
+    paths.sort();
+    let mut fixtures = Vec::new();
+    for path in paths {
+        let source = std::fs::read_to_string(&path).map_err(io_error)?;
+        let file = path.file_name().unwrap_or_default().to_string_lossy();
+        fixtures.extend(parse_fixtures(&file, &source)?);
+    }
+    Ok(fixtures)
+}
+
+/// The fixtures shipped with this crate
+pub fn fixtures() -> Vec<Fixture> {
+    BUILTIN
+        .iter()
+        .flat_map(|(file, source)| {
+            parse_fixtures(file, source).expect("built-in fixtures are well-formed")
+        })
+        .collect()
+}
+
+/// What a backend produced for a fixture
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    /// The expression evaluated to a value
+    Value(EloValue),
+    /// Parsing or evaluation failed with this message
+    Error(String),
+    /// The backend compiled the expression without evaluating it
+    ///
+    /// The fixture is reported as unsupported, since neither its value nor a
+    /// runtime error shows before the code runs.
+    Compiled,
+    /// The backend does not support the expression or its input
+    Unsupported(String),
+}
+
+/// An implementation of ELO semantics under test
+pub trait Backend {
+    /// Name shown in reports
+    fn name(&self) -> &str;
+
+    /// Called by [`run`] with every fixture before they run one by one,
+    /// so a backend can compile them together
+    fn prepare(&self, _fixtures: &[Fixture]) {}
+
+    /// Parse, compile and/or evaluate the fixture's expression
+    fn run(&self, fixture: &Fixture) -> Outcome;
+}
+
+/// Parses with [`Parser`] and evaluates with [`Evaluator`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct InterpreterBackend;
+
+impl Backend for InterpreterBackend {
+    fn name(&self) -> &str {
+        "interpreter"
+    }
+
+    fn run(&self, fixture: &Fixture) -> Outcome {
+        let expr = match Parser::parse(&fixture.expr) {
+            Ok(expr) => expr,
+            Err(error) => return Outcome::Error(error.to_string()),
+        };
+        match Evaluator::new().evaluate(&expr, &fixture.input) {
+            Ok(value) => Outcome::Value(value),
+            Err(EvalError::Unsupported(what)) => Outcome::Unsupported(what),
+            Err(error) => Outcome::Error(error.to_string()),
+        }
+    }
+}
+
+/// Generates code with [`RustCodeGenerator`], compiles it and runs it
+///
+/// Each fixture becomes a module of one generated crate, built with
+/// `cargo` in a work directory (by default `elo-rust-conformance` in the
+/// system's temporary directory) when [`run`] prepares the fixtures. The
+/// module builds the input from structs derived from its JSON, computes the
+/// value with the code of [`RustCodeGenerator::generate_expression`] and
+/// converts it back to an [`EloValue`]. Code that does not compile reports
+/// the compiler's error, and code that panics the panic message.
+///
+/// Rust needs a type for every input field, so fixtures whose input has an
+/// empty array or a `null`, or lacks a field the expression reads, are
+/// reported as unsupported.
+#[derive(Debug)]
+pub struct CodegenBackend {
+    generator: RustCodeGenerator,
+    work_dir: PathBuf,
+    /// Outcomes of the fixtures built so far
+    outcomes: Mutex<Vec<(Fixture, Outcome)>>,
+}
+
+impl Default for CodegenBackend {
+    /// A generator whose failed guards panic, so they show as errors
+    fn default() -> Self {
+        Self::new(RustCodeGenerator::new().with_guard_policy(GuardPolicy::Panic))
+    }
+}
+
+impl CodegenBackend {
+    /// Check code from `generator`
+    pub fn new(generator: RustCodeGenerator) -> Self {
+        Self {
+            generator,
+            work_dir: std::env::temp_dir().join("elo-rust-conformance"),
+            outcomes: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Build the generated crate in `dir`, which is reused between runs
+    pub fn with_work_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.work_dir = dir.into();
+        self
+    }
+
+    /// Source of the module computing `fixture`, or its outcome if it has
+    /// no code to run
+    fn module(&self, index: usize, fixture: &Fixture) -> Result<String, Outcome> {
+        let expr = Parser::parse(&fixture.expr).map_err(|e| Outcome::Error(e.to_string()))?;
+        for path in field_dependencies(&expr) {
+            let present = path
+                .segments()
+                .iter()
+                .try_fold(&fixture.input, |value, segment| match value {
+                    EloValue::Object(fields) => fields.get(segment),
+                    other => Some(other),
+                })
+                .is_some();
+            if !present {
+                return Err(Outcome::Unsupported(format!(
+                    "the input has no field '{}' to take its type from",
+                    path
+                )));
+            }
+        }
+
+        let input_type = format!("Input{}", index);
+        let mut shapes = Shapes::default();
+        let (_, _, input) = shapes
+            .shape(&input_type, &fixture.input)
+            .map_err(Outcome::Unsupported)?;
+        let code = self
+            .generator
+            .clone()
+            .with_type_context(shapes.context)
+            .generate_expression(&fixture.expr, &input_type)
+            .map_err(Outcome::Error)?;
+        let structs = shapes.structs;
+        Ok(quote! {
+            use super::ToElo;
+            #(#structs)*
+            pub fn run() -> elo_rust::runtime::EloValue {
+                let input = &#input;
+                ToElo::to_elo(&(#code))
+            }
+        }
+        .to_string())
+    }
+
+    /// Build the modules into one crate and run it, dropping modules that
+    /// do not compile until the rest does
+    fn build_and_run(
+        &self,
+        mut modules: BTreeMap<usize, String>,
+    ) -> Result<BTreeMap<usize, Outcome>, String> {
+        let mut outcomes = BTreeMap::new();
+        let executable = loop {
+            if modules.is_empty() {
+                return Ok(outcomes);
+            }
+            self.write_crate(&modules).map_err(|e| e.to_string())?;
+            let output = Command::new(std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into()))
+                .args(["build", "--offline", "--message-format=json"])
+                .current_dir(&self.work_dir)
+                .env("CARGO_TARGET_DIR", self.work_dir.join("target"))
+                .output()
+                .map_err(|e| format!("cannot run cargo: {}", e))?;
+            let mut executable = None;
+            let mut failed = BTreeMap::new();
+            for message in String::from_utf8_lossy(&output.stdout).lines() {
+                let Ok(message) = serde_json::from_str::<serde_json::Value>(message) else {
+                    continue;
+                };
+                if let Some(path) = message["executable"].as_str() {
+                    executable = Some(PathBuf::from(path));
+                }
+                let diagnostic = &message["message"];
+                if diagnostic["level"] != "error" {
+                    continue;
+                }
+                let text = diagnostic["message"].as_str().unwrap_or_default();
+                let files = diagnostic["spans"].as_array().into_iter().flatten();
+                for file in files.filter_map(|span| span["file_name"].as_str()) {
+                    if let Some(index) = module_index(file) {
+                        failed.entry(index).or_insert_with(|| text.to_string());
+                    }
+                }
+            }
+            if output.status.success() {
+                break executable.ok_or("cargo built no executable")?;
+            }
+            if failed.is_empty() {
+                return Err(format!(
+                    "cannot build the generated crate: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
+            }
+            for (index, error) in failed {
+                modules.remove(&index);
+                let message = format!("generated code does not compile: {}", error);
+                outcomes.insert(index, Outcome::Error(message));
+            }
+        };
+
+        let output = Command::new(executable)
+            .output()
+            .map_err(|e| format!("cannot run the generated crate: {}", e))?;
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            let mut parts = line.splitn(3, '\t');
+            let (Some(index), Some(kind), Some(text)) = (parts.next(), parts.next(), parts.next())
+            else {
+                continue;
+            };
+            let Ok(index) = index.parse::<usize>() else {
+                continue;
+            };
+            let outcome = match (kind, parse_json(text)) {
+                ("value", Ok(value)) => Outcome::Value(value),
+                _ => Outcome::Error(text.to_string()),
+            };
+            outcomes.insert(index, outcome);
+        }
+        for index in modules.keys() {
+            outcomes
+                .entry(*index)
+                .or_insert_with(|| Outcome::Error("generated code did not finish".to_string()));
+        }
+        Ok(outcomes)
+    }
+
+    /// Write the manifest, `main.rs` and one file per module
+    fn write_crate(&self, modules: &BTreeMap<usize, String>) -> std::io::Result<()> {
+        let src = self.work_dir.join("src");
+        if src.exists() {
+            std::fs::remove_dir_all(&src)?;
+        }
+        std::fs::create_dir_all(&src)?;
+        let manifest_dir = env!("CARGO_MANIFEST_DIR");
+        std::fs::write(
+            self.work_dir.join("Cargo.toml"),
+            format!(
+                "[package]\nname = \"elo-conformance\"\nversion = \"0.0.0\"\nedition = \"2021\"\n\
+                 publish = false\n\n[dependencies]\nelo-rust = {{ path = {:?}, features = [\"serde-support\"] }}\n\
+                 chrono = \"0.4\"\n\n[workspace]\n",
+                manifest_dir
+            ),
+        )?;
+        // The versions this crate was built with are known to be available
+        let lock = Path::new(manifest_dir).join("Cargo.lock");
+        if lock.exists() {
+            std::fs::copy(lock, self.work_dir.join("Cargo.lock"))?;
+        }
+
+        let names: Vec<_> = modules
+            .keys()
+            .map(|index| quote::format_ident!("f{}", index))
+            .collect();
+        let indices = modules.keys();
+        let main = quote! {
+            #![allow(dead_code, non_camel_case_types, unused_parens, unused_braces, clippy::all)]
+            #(mod #names;)*
+            fn main() {
+                let runs: &[(usize, fn() -> elo_rust::runtime::EloValue)] =
+                    &[#((#indices, #names::run)),*];
+                report(runs);
+            }
+        };
+        let main = format!("{}\n{}", main, RUNNER);
+        std::fs::write(src.join("main.rs"), main)?;
+        for (index, module) in modules {
+            std::fs::write(src.join(format!("f{}.rs", index)), module)?;
+        }
+        Ok(())
+    }
+}
+
+/// Index of the fixture module in `file` (`src/f12.rs` is fixture 12)
+fn module_index(file: &str) -> Option<usize> {
+    file.strip_prefix("src/f")?
+        .strip_suffix(".rs")?
+        .parse()
+        .ok()
+}
+
+/// Conversion of generated values to `EloValue` and the loop printing one
+/// `index<TAB>value<TAB>json` or `index<TAB>error<TAB>message` line per
+/// fixture
+const RUNNER: &str = r#"
+use elo_rust::runtime::EloValue;
+
+trait ToElo {
+    fn to_elo(&self) -> EloValue;
+}
+
+impl<T: ToElo + ?Sized> ToElo for &T {
+    fn to_elo(&self) -> EloValue {
+        (**self).to_elo()
+    }
+}
+
+macro_rules! integers {
+    ($($ty:ty),*) => {
+        $(impl ToElo for $ty {
+            fn to_elo(&self) -> EloValue {
+                EloValue::Integer(*self as i64)
+            }
+        })*
+    };
+}
+
+integers!(i32, i64, u32, usize);
+
+impl ToElo for f64 {
+    fn to_elo(&self) -> EloValue {
+        EloValue::Float(*self)
+    }
+}
+
+impl ToElo for bool {
+    fn to_elo(&self) -> EloValue {
+        EloValue::Boolean(*self)
+    }
+}
+
+impl ToElo for str {
+    fn to_elo(&self) -> EloValue {
+        EloValue::String(self.to_string())
+    }
+}
+
+impl ToElo for String {
+    fn to_elo(&self) -> EloValue {
+        EloValue::String(self.clone())
+    }
+}
+
+impl ToElo for () {
+    fn to_elo(&self) -> EloValue {
+        EloValue::Null
+    }
+}
+
+impl<T: ToElo> ToElo for Option<T> {
+    fn to_elo(&self) -> EloValue {
+        self.as_ref().map_or(EloValue::Null, ToElo::to_elo)
+    }
+}
+
+impl<T: ToElo> ToElo for [T] {
+    fn to_elo(&self) -> EloValue {
+        EloValue::Array(self.iter().map(ToElo::to_elo).collect())
+    }
+}
+
+impl<T: ToElo> ToElo for Vec<T> {
+    fn to_elo(&self) -> EloValue {
+        self.as_slice().to_elo()
+    }
+}
+
+impl<K: ToString, T: ToElo> ToElo for std::collections::BTreeMap<K, T> {
+    fn to_elo(&self) -> EloValue {
+        EloValue::Object(self.iter().map(|(k, v)| (k.to_string(), v.to_elo())).collect())
+    }
+}
+
+fn report(runs: &[(usize, fn() -> EloValue)]) {
+    std::panic::set_hook(Box::new(|_| {}));
+    for (index, run) in runs {
+        match std::panic::catch_unwind(run) {
+            Ok(value) => println!("{}\tvalue\t{}", index, value.to_json_string()),
+            Err(payload) => {
+                let message = payload
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| payload.downcast_ref::<String>().cloned())
+                    .unwrap_or_default();
+                println!("{}\terror\t{}", index, message.replace('\n', " "));
+            }
+        }
+    }
+}
+"#;
+
+/// Rust types of fixture inputs: a struct per JSON object and the type
+/// context describing them to the generator
+#[derive(Default)]
+struct Shapes {
+    structs: Vec<TokenStream>,
+    context: TypeContext,
+}
+
+impl Shapes {
+    /// Rust type, ELO type and literal of `value`; objects become structs
+    /// named `name`
+    fn shape(
+        &mut self,
+        name: &str,
+        value: &EloValue,
+    ) -> Result<(TokenStream, RustType, TokenStream), String> {
+        Ok(match value {
+            EloValue::Integer(n) => (quote!(i64), RustType::Integer, quote!(#n)),
+            EloValue::Float(f) => (quote!(f64), RustType::Float, float_literal(*f)),
+            EloValue::Boolean(b) => (quote!(bool), RustType::Bool, quote!(#b)),
+            EloValue::String(s) => (quote!(String), RustType::String, quote!(#s.to_string())),
+            EloValue::Array(items) => {
+                let element = format!("{}_item", name);
+                let first = items.first().ok_or("an empty array has no element type")?;
+                // Every element needs the type of the first
+                let signature = |item: &EloValue| {
+                    let mut scratch = Shapes::default();
+                    let (ty, _, literal) = scratch.shape(&element, item)?;
+                    let structs = scratch.structs;
+                    Ok::<_, String>((quote!(#ty #(#structs)*).to_string(), literal))
+                };
+                let (expected, _) = signature(first)?;
+                let mut literals = Vec::with_capacity(items.len());
+                for item in items {
+                    let (found, literal) = signature(item)?;
+                    if found != expected {
+                        return Err("the array's elements have different types".to_string());
+                    }
+                    literals.push(literal);
+                }
+                let (ty, rust_type, _) = self.shape(&element, first)?;
+                (
+                    quote!(Vec<#ty>),
+                    RustType::Array(Box::new(rust_type)),
+                    quote!(vec![#(#literals),*]),
+                )
+            }
+            EloValue::Object(fields) => {
+                let ident = quote::format_ident!("{}", name);
+                let mut info = TypeInfo::new(name);
+                let (mut idents, mut types, mut literals) = (Vec::new(), Vec::new(), Vec::new());
+                for (key, value) in fields {
+                    let (ty, rust_type, literal) =
+                        self.shape(&format!("{}_{}", name, key), value)?;
+                    info.add_field(key, rust_type);
+                    idents.push(rust_ident(key));
+                    types.push(ty);
+                    literals.push(literal);
+                }
+                self.context.register_type(name, info);
+                let keys = fields.keys();
+                self.structs.push(quote! {
+                    #[derive(Debug, Clone, PartialEq)]
+                    pub struct #ident {
+                        #(pub #idents: #types),*
+                    }
+
+                    impl ToElo for #ident {
+                        fn to_elo(&self) -> elo_rust::runtime::EloValue {
+                            elo_rust::runtime::EloValue::Object(
+                                [#((#keys.to_string(), self.#idents.to_elo())),*]
+                                    .into_iter()
+                                    .collect(),
+                            )
+                        }
+                    }
+                });
+                (
+                    quote!(#ident),
+                    RustType::Custom(name.to_string()),
+                    quote!(#ident { #(#idents: #literals),* }),
+                )
+            }
+            other => return Err(format!("a {} input has no Rust type", other.type_name())),
+        })
+    }
+}
+
+impl Backend for CodegenBackend {
+    fn name(&self) -> &str {
+        "codegen"
+    }
+
+    fn prepare(&self, fixtures: &[Fixture]) {
+        let mut outcomes = BTreeMap::new();
+        let mut modules = BTreeMap::new();
+        for (index, fixture) in fixtures.iter().enumerate() {
+            match self.module(index, fixture) {
+                Ok(module) => {
+                    modules.insert(index, module);
+                }
+                Err(outcome) => {
+                    outcomes.insert(index, outcome);
+                }
+            }
+        }
+        match self.build_and_run(modules.clone()) {
+            Ok(ran) => outcomes.extend(ran),
+            Err(error) => {
+                for index in modules.keys() {
+                    outcomes.insert(*index, Outcome::Error(error.clone()));
+                }
+            }
+        }
+        let mut known = self.outcomes.lock().unwrap_or_else(|e| e.into_inner());
+        for (index, outcome) in outcomes {
+            known.push((fixtures[index].clone(), outcome));
+        }
+    }
+
+    fn run(&self, fixture: &Fixture) -> Outcome {
+        let known = |outcomes: &[(Fixture, Outcome)]| {
+            outcomes
+                .iter()
+                .rev()
+                .find(|(done, _)| done == fixture)
+                .map(|(_, outcome)| outcome.clone())
+        };
+        if let Some(outcome) = known(&self.outcomes.lock().unwrap_or_else(|e| e.into_inner())) {
+            return outcome;
+        }
+        self.prepare(std::slice::from_ref(fixture));
+        known(&self.outcomes.lock().unwrap_or_else(|e| e.into_inner()))
+            .unwrap_or_else(|| Outcome::Error("the fixture was not built".to_string()))
+    }
+}
+
+/// A fixture whose outcome did not match
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    /// The fixture
+    pub fixture: Fixture,
+    /// What the backend produced
+    pub actual: Outcome,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}): `{}` expected ",
+            self.fixture.location, self.fixture.name, self.fixture.expr
+        )?;
+        match &self.fixture.expected {
+            Expected::Value(value) => write!(f, "{}", value)?,
+            Expected::Error(text) => write!(f, "an error containing '{}'", text)?,
+        }
+        match &self.actual {
+            Outcome::Value(value) => write!(f, ", got {}", value),
+            Outcome::Error(message) => write!(f, ", got error: {}", message),
+            Outcome::Compiled => write!(f, ", but it compiled"),
+            Outcome::Unsupported(what) => write!(f, ", but {} is unsupported", what),
+        }
+    }
+}
+
+/// Results of running fixtures through one backend
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    /// Backend name
+    pub backend: String,
+    /// Fixtures that matched
+    pub passed: usize,
+    /// Fixtures the backend could not check, with the reason; they count as
+    /// neither passed nor failed
+    pub unsupported: Vec<(String, String)>,
+    /// Fixtures that did not match
+    pub mismatches: Vec<Mismatch>,
+}
+
+impl Report {
+    /// Whether no fixture mismatched
+    pub fn is_success(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{}: {} passed, {} failed, {} unsupported",
+            self.backend,
+            self.passed,
+            self.mismatches.len(),
+            self.unsupported.len()
+        )?;
+        for mismatch in &self.mismatches {
+            writeln!(f, "  ✗ {}", mismatch)?;
+        }
+        for (name, reason) in &self.unsupported {
+            writeln!(f, "  - {}: {}", name, reason)?;
+        }
+        Ok(())
+    }
+}
+
+/// Run every fixture through `backend`
+pub fn run(backend: &dyn Backend, fixtures: &[Fixture]) -> Report {
+    let mut report = Report {
+        backend: backend.name().to_string(),
+        passed: 0,
+        unsupported: Vec::new(),
+        mismatches: Vec::new(),
+    };
+    backend.prepare(fixtures);
+    for fixture in fixtures {
+        let actual = backend.run(fixture);
+        let matched = match (&fixture.expected, &actual) {
+            (_, Outcome::Unsupported(what)) => {
+                report
+                    .unsupported
+                    .push((fixture.name.clone(), what.clone()));
+                continue;
+            }
+            (_, Outcome::Compiled) => {
+                report.unsupported.push((
+                    fixture.name.clone(),
+                    "the code compiled but did not run".to_string(),
+                ));
+                continue;
+            }
+            (Expected::Value(expected), Outcome::Value(value)) => same_value(expected, value),
+            (Expected::Error(text), Outcome::Error(message)) => message.contains(text.as_str()),
+            _ => false,
+        };
+        if matched {
+            report.passed += 1;
+        } else {
+            report.mismatches.push(Mismatch {
+                fixture: fixture.clone(),
+                actual,
+            });
+        }
+    }
+    report
+}
+
+/// Equality that treats a JSON number like `2.0` and an integer `2` as
+/// different, but compares floats with a small tolerance
+fn same_value(expected: &EloValue, actual: &EloValue) -> bool {
+    match (expected, actual) {
+        (EloValue::Float(a), EloValue::Float(b)) => (a - b).abs() <= 1e-9 * a.abs().max(1.0),
+        (EloValue::Array(a), EloValue::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same_value(a, b))
+        }
+        (EloValue::Object(a), EloValue::Object(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .zip(b)
+                    .all(|((ka, a), (kb, b))| ka == kb && same_value(a, b))
+        }
+        _ => expected == actual,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_fixtures() {
+        let fixtures = parse_fixtures(
+            "demo.fixture",
+            "# demo\ntest adult\nexpr: age >= 18\ninput: {\"age\": 20}\nexpect: true\n\n\
+             test lookup\nexpr: ctx.limit\ncontext: {\"limit\": 3}\nexpect: 3\n",
+        )
+        .unwrap();
+        assert_eq!(fixtures.len(), 2);
+        assert_eq!(fixtures[0].location, "demo.fixture:2");
+        assert_eq!(
+            fixtures[0].expected,
+            Expected::Value(EloValue::Boolean(true))
+        );
+        assert_eq!(fixtures[1].input.to_string(), "{ctx: {limit: 3}}");
+
+        let error = parse_fixtures("bad.fixture", "test x\nexpr: 1\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "bad.fixture:1: test 'x' has neither expect nor error"
+        );
+        let error = parse_fixtures("bad.fixture", "expr: 1\n").unwrap_err();
+        assert_eq!(error.location, "bad.fixture:1");
+        let error = parse_fixtures("bad.fixture", "test x\nexpr: 1\nexpr: 2\n").unwrap_err();
+        assert_eq!(error.message, "test 'x' sets expr twice");
+    }
+
+    #[test]
+    fn test_run_reports_mismatches() {
+        let fixtures =
+            parse_fixtures("demo.fixture", "test wrong\nexpr: 1 + 1\nexpect: 3\n").unwrap();
+        let report = run(&InterpreterBackend, &fixtures);
+        assert!(!report.is_success());
+        assert_eq!(
+            report.to_string(),
+            "interpreter: 0 passed, 1 failed, 0 unsupported\n  \
+             ✗ demo.fixture:1 (wrong): `1 + 1` expected 3, got 2\n"
+        );
+    }
+
+    #[test]
+    fn test_run_reports_unsupported() {
+        struct Unsupported;
+        impl Backend for Unsupported {
+            fn name(&self) -> &str {
+                "partial"
+            }
+            fn run(&self, _fixture: &Fixture) -> Outcome {
+                Outcome::Unsupported("nothing".to_string())
+            }
+        }
+        let fixtures = parse_fixtures("demo.fixture", "test any\nexpr: 1\nexpect: 1\n").unwrap();
+        let report = run(&Unsupported, &fixtures);
+        assert!(report.is_success());
+        assert_eq!(report.passed, 0);
+        assert_eq!(
+            report.to_string(),
+            "partial: 0 passed, 0 failed, 1 unsupported\n  - any: nothing\n"
+        );
+    }
+
+    #[test]
+    fn test_builtin_fixtures_load() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/conformance");
+        assert_eq!(load_dir(&dir).unwrap(), fixtures());
+    }
+}
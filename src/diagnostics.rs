@@ -0,0 +1,348 @@
+//! Machine-readable diagnostics
+//!
+//! Lints and parse errors are converted into [`Diagnostic`]s, which can be
+//! rendered as a stable JSON document ([`to_json`]) or as
+//! [SARIF 2.1.0](https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html)
+//! ([`to_sarif`]) for code-review tools and CI annotations.
+//!
+//! The JSON format is versioned; fields are only ever added:
+//!
+//! ```json
+//! {"version":1,"diagnostics":[
+//!   {"code":"redundant_parens","severity":"warning","file":"rules.elo",
+//!    "span":{"line":1,"column":1,"start":0,"end":5},"message":"...",
+//!    "suggestion":"remove unnecessary parentheses",
+//!    "fix":{"start":0,"end":5,"replacement":"age"}}
+//! ]}
+//! ```
+//!
+//! `start` and `end` are byte offsets. `span` is `null` when the finding has
+//! no position (lints of an expression that was not parsed from source), and
+//! `fix` when there is no automatic [`Fix`].
+//!
+//! # Example
+//!
+//! ```
+//! use elo_rust::diagnostics::{self, Diagnostic};
+//! use elo_rust::parser::Parser;
+//!
+//! let source = "age = 18";
+//! let error = Parser::parse(source).unwrap_err();
+//! let diagnostic = Diagnostic::from_parse_error(&error, source);
+//! assert_eq!(diagnostic.code, "parse_error");
+//! assert_eq!(diagnostic.fix.as_ref().unwrap().replacement, "==");
+//!
+//! let json = diagnostics::to_json(&[diagnostic], "rules.elo");
+//! assert!(json.starts_with(r#"{"version":1,"diagnostics":[{"code":"parse_error","severity":"error""#));
+//! ```
+
+use crate::ast::span::Span;
+use crate::fix::{self, Fix};
+use crate::lint::{Lint, LintLevel};
+use crate::parser::{Lexer, ParseError};
+use crate::runtime::json_string;
+use std::fmt;
+
+/// Version of the JSON diagnostics format
+pub const JSON_FORMAT_VERSION: u32 = 1;
+
+/// How serious a diagnostic is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DiagnosticLevel {
+    /// Fails the run
+    Error,
+    /// Reported, but does not fail the run
+    Warning,
+}
+
+impl DiagnosticLevel {
+    /// Name used in JSON output ("error" or "warning")
+    pub fn name(&self) -> &'static str {
+        match self {
+            DiagnosticLevel::Error => "error",
+            DiagnosticLevel::Warning => "warning",
+        }
+    }
+}
+
+impl fmt::Display for DiagnosticLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// A single finding in a uniform shape
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Stable identifier: a lint name or `parse_error`
+    pub code: String,
+    /// How serious the finding is
+    pub level: DiagnosticLevel,
+    /// Where the finding is, if known
+    pub span: Option<Span>,
+    /// Human-readable explanation
+    pub message: String,
+    /// Suggested fix, if any
+    pub suggestion: Option<String>,
+    /// Edit applying the suggestion, if it can be applied automatically
+    pub fix: Option<Fix>,
+}
+
+impl Diagnostic {
+    /// Convert a lint finding, keeping its span and fix; denied lints are
+    /// errors
+    pub fn from_lint(lint: &Lint) -> Self {
+        Self {
+            code: lint.kind.name().to_string(),
+            level: if lint.level == LintLevel::Deny {
+                DiagnosticLevel::Error
+            } else {
+                DiagnosticLevel::Warning
+            },
+            span: lint.span,
+            message: lint.message.clone(),
+            suggestion: lint.fix.as_ref().map(|fix| fix.message.clone()),
+            fix: lint.fix.clone(),
+        }
+    }
+
+    /// Convert an error from parsing `source`, keeping its position and
+    /// hint and adding the [`fix`](crate::fix) for it, if any
+    pub fn from_parse_error(error: &ParseError, source: &str) -> Self {
+        let fix = fix::parse_error_fix(source, error);
+        Self {
+            code: "parse_error".to_string(),
+            level: DiagnosticLevel::Error,
+            span: parse_error_span(error, source),
+            message: error.message.clone(),
+            suggestion: error
+                .hint
+                .clone()
+                .or_else(|| fix.as_ref().map(|fix| fix.message.clone())),
+            fix,
+        }
+    }
+}
+
+/// Span of the token a parse error points at, or of the character there
+/// when the source does not lex
+fn parse_error_span(error: &ParseError, source: &str) -> Option<Span> {
+    let tokens = Lexer::new(source).tokenize_spanned().unwrap_or_default();
+    if let Some(token) = tokens
+        .iter()
+        .find(|t| (t.line, t.column) == (error.line, error.column))
+    {
+        return Some(Span {
+            start: token.start,
+            end: token.end,
+            line: token.line,
+            column: token.column,
+        });
+    }
+    let line_start = match error.line {
+        0 => return None,
+        1 => 0,
+        line => source.match_indices('\n').nth(line - 2)?.0 + 1,
+    };
+    let rest = &source[line_start..];
+    let (offset, width) = rest
+        .char_indices()
+        .map(|(i, c)| (i, c.len_utf8()))
+        .chain([(rest.len(), 0)])
+        .nth(error.column.checked_sub(1)?)?;
+    Some(Span {
+        start: line_start + offset,
+        end: line_start + offset + width,
+        line: error.line,
+        column: error.column,
+    })
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}[{}]: {}", self.level, self.code, self.message)?;
+        if let Some(span) = self.span {
+            write!(f, " at line {}, column {}", span.line, span.column)?;
+        }
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, " ({})", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+/// Render diagnostics for `file` in the stable JSON format
+pub fn to_json(diagnostics: &[Diagnostic], file: &str) -> String {
+    let entries: Vec<String> = diagnostics
+        .iter()
+        .map(|d| {
+            let span = d.span.map_or("null".to_string(), |span| {
+                format!(
+                    r#"{{"line":{},"column":{},"start":{},"end":{}}}"#,
+                    span.line, span.column, span.start, span.end
+                )
+            });
+            let fix = d.fix.as_ref().map_or("null".to_string(), |fix| {
+                format!(
+                    r#"{{"start":{},"end":{},"replacement":{}}}"#,
+                    fix.span.start,
+                    fix.span.end,
+                    json_string(&fix.replacement)
+                )
+            });
+            format!(
+                r#"{{"code":{},"severity":"{}","file":{},"span":{},"message":{},"suggestion":{},"fix":{}}}"#,
+                json_string(&d.code),
+                d.level,
+                json_string(file),
+                span,
+                json_string(&d.message),
+                d.suggestion
+                    .as_deref()
+                    .map_or("null".to_string(), json_string),
+                fix
+            )
+        })
+        .collect();
+    format!(
+        r#"{{"version":{},"diagnostics":[{}]}}"#,
+        JSON_FORMAT_VERSION,
+        entries.join(",")
+    )
+}
+
+/// Render diagnostics for `file` as a SARIF 2.1.0 log with a single run
+///
+/// Each distinct code becomes a reporting rule of the `elo` tool driver.
+/// Suggestions are appended to the result message, and fixes become SARIF
+/// `fixes` replacing byte regions of `file`.
+pub fn to_sarif(diagnostics: &[Diagnostic], file: &str) -> String {
+    let mut codes: Vec<&str> = diagnostics.iter().map(|d| d.code.as_str()).collect();
+    codes.sort_unstable();
+    codes.dedup();
+    let rules: Vec<String> = codes
+        .iter()
+        .map(|code| format!(r#"{{"id":{}}}"#, json_string(code)))
+        .collect();
+
+    let results: Vec<String> = diagnostics
+        .iter()
+        .map(|d| {
+            let region = d.span.map_or(String::new(), |span| {
+                format!(
+                    r#","region":{{"startLine":{},"startColumn":{},"byteOffset":{},"byteLength":{}}}"#,
+                    span.line,
+                    span.column,
+                    span.start,
+                    span.len()
+                )
+            });
+            let text = match &d.suggestion {
+                Some(suggestion) => format!("{} ({})", d.message, suggestion),
+                None => d.message.clone(),
+            };
+            let fixes = d.fix.as_ref().map_or(String::new(), |fix| {
+                format!(
+                    r#","fixes":[{{"description":{{"text":{}}},"artifactChanges":[{{"artifactLocation":{{"uri":{}}},"replacements":[{{"deletedRegion":{{"byteOffset":{},"byteLength":{}}},"insertedContent":{{"text":{}}}}}]}}]}}]"#,
+                    json_string(&fix.message),
+                    json_string(file),
+                    fix.span.start,
+                    fix.span.len(),
+                    json_string(&fix.replacement)
+                )
+            });
+            format!(
+                r#"{{"ruleId":{},"level":"{}","message":{{"text":{}}},"locations":[{{"physicalLocation":{{"artifactLocation":{{"uri":{}}}{}}}}}]{}}}"#,
+                json_string(&d.code),
+                d.level,
+                json_string(&text),
+                json_string(file),
+                region,
+                fixes
+            )
+        })
+        .collect();
+
+    format!(
+        r#"{{"$schema":"https://json.schemastore.org/sarif-2.1.0.json","version":"2.1.0","runs":[{{"tool":{{"driver":{{"name":"elo","version":"{}","informationUri":"https://github.com/evoludigit/elo-rust","rules":[{}]}}}},"results":[{}]}}]}}"#,
+        env!("CARGO_PKG_VERSION"),
+        rules.join(","),
+        results.join(",")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lint::LintConfig;
+    use crate::lint::LintKind;
+    use crate::parser::Parser;
+
+    fn diagnostics() -> Vec<Diagnostic> {
+        let lints = LintConfig::new()
+            .with_level(LintKind::FloatEquality, LintLevel::Deny)
+            .check_source("(price) == 0.1")
+            .unwrap();
+        let mut diagnostics: Vec<Diagnostic> = lints.iter().map(Diagnostic::from_lint).collect();
+        diagnostics.push(Diagnostic::from_parse_error(
+            &Parser::parse("a & b").unwrap_err(),
+            "a & b",
+        ));
+        diagnostics
+    }
+
+    #[test]
+    fn test_json_format() {
+        let diagnostics = diagnostics();
+        assert_eq!(diagnostics[0].level, DiagnosticLevel::Warning);
+        assert_eq!(diagnostics[1].level, DiagnosticLevel::Error);
+        assert_eq!(
+            to_json(&diagnostics, "rules.elo"),
+            format!(
+                concat!(
+                    r#"{{"version":1,"diagnostics":["#,
+                    r#"{{"code":"redundant_parens","severity":"warning","file":"rules.elo","span":{{"line":1,"column":1,"start":0,"end":7}},"message":"unnecessary parentheses","suggestion":"remove unnecessary parentheses","fix":{{"start":0,"end":7,"replacement":"price"}}}},"#,
+                    r#"{{"code":"float_equality","severity":"error","file":"rules.elo","span":{{"line":1,"column":1,"start":0,"end":14}},"message":{},"suggestion":null,"fix":null}},"#,
+                    r#"{{"code":"parse_error","severity":"error","file":"rules.elo","span":{{"line":1,"column":3,"start":2,"end":3}},"message":"Unexpected '&'","suggestion":"did you mean '&&'?","fix":null}}"#,
+                    "]}}"
+                ),
+                json_string(&diagnostics[1].message)
+            )
+        );
+        assert_eq!(to_json(&[], "x"), r#"{"version":1,"diagnostics":[]}"#);
+    }
+
+    #[test]
+    fn test_parse_error_fix() {
+        let source = "a == 1 &&\nb = 2";
+        let diagnostic = Diagnostic::from_parse_error(&Parser::parse(source).unwrap_err(), source);
+        let span = diagnostic.span.unwrap();
+        assert_eq!((span.line, span.column, span.text(source)), (2, 3, "="));
+        assert_eq!(
+            diagnostic.suggestion.as_deref(),
+            Some("use '==' to compare values")
+        );
+        assert_eq!(
+            diagnostic.fix.map(|fix| (fix.span, fix.replacement)),
+            Some((12..13, "==".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_sarif_format() {
+        let sarif = to_sarif(&diagnostics(), "rules.elo");
+        assert!(sarif.starts_with(
+            r#"{"$schema":"https://json.schemastore.org/sarif-2.1.0.json","version":"2.1.0","runs":[{"tool":{"driver":{"name":"elo""#
+        ));
+        assert!(sarif.contains(
+            r#""rules":[{"id":"float_equality"},{"id":"parse_error"},{"id":"redundant_parens"}]"#
+        ));
+        assert!(sarif.contains(
+            r#"{"ruleId":"parse_error","level":"error","message":{"text":"Unexpected '&' (did you mean '&&'?)"},"locations":[{"physicalLocation":{"artifactLocation":{"uri":"rules.elo"},"region":{"startLine":1,"startColumn":3,"byteOffset":2,"byteLength":1}}}]}"#
+        ));
+        assert!(sarif.contains(
+            r#""fixes":[{"description":{"text":"remove unnecessary parentheses"},"artifactChanges":[{"artifactLocation":{"uri":"rules.elo"},"replacements":[{"deletedRegion":{"byteOffset":0,"byteLength":7},"insertedContent":{"text":"price"}}]}]}]"#
+        ));
+        assert!(sarif.contains(r#"{"ruleId":"float_equality","level":"error","message":{"text":"#));
+    }
+}
@@ -0,0 +1,237 @@
+//! Automatic fixes
+//!
+//! Some findings have an unambiguous repair, described as a [`Fix`]: a byte
+//! range of the source and the text that replaces it. Fixes are offered for
+//!
+//! - a single `=` where a comparison is expected (`age = 18` → `age == 18`)
+//! - an `if` without `else` whose `then` branch is a condition
+//!   (`if a then b > 1` → `if a then b > 1 else false`); the inserted branch
+//!   fails closed, so review it. Other branches have no default of their
+//!   type that is safe to assume, so they get no fix
+//! - redundant parentheses (`(age) > 1` → `age > 1`), unless the
+//!   `redundant_parens` lint is allowed
+//!
+//! [`fix_source`] applies fixes repeatedly until none are left, in the
+//! manner of rustfix.
+//!
+//! # Example
+//!
+//! ```
+//! use elo_rust::fix;
+//! use elo_rust::lint::LintConfig;
+//!
+//! let (fixed, applied) = fix::fix_source(&LintConfig::new(), "(age = 18)");
+//! assert_eq!(fixed, "age == 18");
+//! assert_eq!(applied, 2);
+//! ```
+
+use crate::ast::span::Span;
+use crate::codegen::type_inference::{InferredType, TypeInferenceVisitor};
+use crate::lint::LintConfig;
+use crate::parser::{Lexer, ParseError, Parser, SpannedToken, Token};
+use std::ops::Range;
+
+/// Upper bound on fix-and-recheck passes in [`fix_source`]
+pub const MAX_FIX_PASSES: usize = 16;
+
+/// A source edit that resolves a finding
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fix {
+    /// Byte range of the source to replace (empty for insertions)
+    pub span: Range<usize>,
+    /// Text to put in place of the range
+    pub replacement: String,
+    /// What the fix does
+    pub message: String,
+}
+
+/// Fixes for the findings in `source`
+///
+/// A source that does not parse gets at most one fix, for the parse error;
+/// lint fixes are only offered once it parses.
+pub fn suggest(config: &LintConfig, source: &str) -> Vec<Fix> {
+    match config.check_source(source) {
+        Ok(lints) => lints.into_iter().filter_map(|lint| lint.fix).collect(),
+        Err(error) => parse_error_fix(source, &error).into_iter().collect(),
+    }
+}
+
+/// Fix for a parse error of `source`, if it has an unambiguous one
+pub fn parse_error_fix(source: &str, error: &ParseError) -> Option<Fix> {
+    let tokens = Lexer::new(source).tokenize_spanned().ok()?;
+    let index = tokens
+        .iter()
+        .position(|t| (t.line, t.column) == (error.line, error.column))?;
+    parse_fix(source, &tokens, index, &error.message)
+}
+
+/// Apply non-overlapping fixes, returning the new source and how many were
+/// applied
+///
+/// When fixes overlap, the one starting first wins; run [`suggest`] again on
+/// the result to pick up the rest.
+pub fn apply(source: &str, fixes: &[Fix]) -> (String, usize) {
+    let mut sorted: Vec<&Fix> = fixes.iter().collect();
+    sorted.sort_by_key(|fix| (fix.span.start, fix.span.end));
+
+    let mut output = String::with_capacity(source.len());
+    let mut cursor = 0;
+    let mut applied = 0;
+    for fix in sorted {
+        if fix.span.start < cursor || fix.span.end > source.len() {
+            continue;
+        }
+        output.push_str(&source[cursor..fix.span.start]);
+        output.push_str(&fix.replacement);
+        cursor = fix.span.end;
+        applied += 1;
+    }
+    output.push_str(&source[cursor..]);
+    (output, applied)
+}
+
+/// Apply fixes until none are left (or [`MAX_FIX_PASSES`] is reached)
+pub fn fix_source(config: &LintConfig, source: &str) -> (String, usize) {
+    let mut current = source.to_string();
+    let mut total = 0;
+    for _ in 0..MAX_FIX_PASSES {
+        let fixes = suggest(config, &current);
+        let (fixed, applied) = apply(&current, &fixes);
+        if applied == 0 {
+            break;
+        }
+        current = fixed;
+        total += applied;
+    }
+    (current, total)
+}
+
+/// Fix for the parse error reported at `tokens[index]`
+fn parse_fix(source: &str, tokens: &[SpannedToken], index: usize, message: &str) -> Option<Fix> {
+    let start = tokens[index].start;
+    if tokens[index].token == Token::Equal {
+        return Some(Fix {
+            span: start..start + 1,
+            replacement: "==".to_string(),
+            message: "use '==' to compare values".to_string(),
+        });
+    }
+    if message.starts_with("Expected else,") {
+        // Insert right after the `then` branch, before any whitespace
+        let at = source[..start].trim_end().len();
+        let then = matching_then(&tokens[..index])?;
+        let branch = Parser::parse(&source[tokens[then].end..at]).ok()?;
+        if TypeInferenceVisitor::new().infer(&branch) != InferredType::Boolean {
+            return None;
+        }
+        return Some(Fix {
+            span: at..at,
+            replacement: " else false".to_string(),
+            message: "add the missing 'else' branch".to_string(),
+        });
+    }
+    None
+}
+
+/// Index of the `then` whose branch ends where `tokens` end, skipping
+/// complete `if`s nested in that branch
+fn matching_then(tokens: &[SpannedToken]) -> Option<usize> {
+    let mut open_elses = 0;
+    for (i, token) in tokens.iter().enumerate().rev() {
+        match token.token {
+            Token::Else => open_elses += 1,
+            Token::Then if open_elses == 0 => return Some(i),
+            Token::Then => open_elses -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Fix removing the pair of parentheses that `span` covers
+pub(crate) fn remove_parens(source: &str, span: Span) -> Fix {
+    let (start, end) = (span.start, span.end);
+    let inner = source[start + 1..end - 1].trim();
+    // Keep neighbouring words apart: `if(x)then` → `if x then`
+    let joins = |a: Option<char>, b: Option<char>| matches!((a, b), (Some(a), Some(b)) if is_word(a) && is_word(b));
+    let mut replacement = String::new();
+    if joins(source[..start].chars().next_back(), inner.chars().next()) {
+        replacement.push(' ');
+    }
+    replacement.push_str(inner);
+    if joins(inner.chars().next_back(), source[end..].chars().next()) {
+        replacement.push(' ');
+    }
+    Fix {
+        span: start..end,
+        replacement,
+        message: "remove unnecessary parentheses".to_string(),
+    }
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lint::{LintKind, LintLevel};
+
+    fn fixed(source: &str) -> String {
+        fix_source(&LintConfig::new(), source).0
+    }
+
+    #[test]
+    fn test_parse_error_fixes() {
+        assert_eq!(fixed("age = 18"), "age == 18");
+        assert_eq!(fixed("a == 1 &&\nb = 'é'"), "a == 1 &&\nb == 'é'");
+        assert_eq!(fixed("if a then b > 1"), "if a then b > 1 else false");
+        assert_eq!(
+            fixed("(if a then b == 'x') && c"),
+            "(if a then b == 'x' else false) && c"
+        );
+        assert_eq!(
+            fixed("if a then if b then true else false"),
+            "if a then if b then true else false else false"
+        );
+        // `false` would not match the type of the `then` branch
+        for source in ["if a then 1", "if a then 'x'", "if a then b"] {
+            assert!(suggest(&LintConfig::new(), source).is_empty(), "{}", source);
+        }
+        // No unambiguous repair
+        assert!(suggest(&LintConfig::new(), "let x = 1 x").is_empty());
+    }
+
+    #[test]
+    fn test_paren_fixes() {
+        assert_eq!(fixed("(age) > 1"), "age > 1");
+        assert_eq!(fixed("((a + b)) * 2"), "(a + b) * 2");
+        assert_eq!(fixed("((x)) > 1"), "x > 1");
+        assert_eq!(fixed("if(x)then 1 else 2"), "if x then 1 else 2");
+        assert_eq!(fixed("length(x) > (a - b) * 2"), "length(x) > (a - b) * 2");
+
+        let allowed = LintConfig::new().with_level(LintKind::RedundantParens, LintLevel::Allow);
+        assert_eq!(
+            fix_source(&allowed, "(age) > 1"),
+            ("(age) > 1".to_string(), 0)
+        );
+    }
+
+    #[test]
+    fn test_apply_skips_overlaps() {
+        let fixes = [
+            Fix {
+                span: 0..3,
+                replacement: "x".to_string(),
+                message: String::new(),
+            },
+            Fix {
+                span: 2..4,
+                replacement: "y".to_string(),
+                message: String::new(),
+            },
+        ];
+        assert_eq!(apply("abcd", &fixes), ("xd".to_string(), 1));
+    }
+}
@@ -14,12 +14,23 @@
 
 pub mod ast;
 pub mod codegen;
+pub mod config;
+#[cfg(feature = "conformance")]
+pub mod conformance;
+pub mod diagnostics;
+pub mod fix;
+pub mod lint;
+pub mod migrate;
+pub mod params;
 pub mod parser;
+pub mod rules;
 pub mod runtime;
+pub mod schema;
 pub mod security;
 pub mod stdlib;
 
 pub use codegen::RustCodeGenerator;
+pub use rules::RuleSet;
 pub use runtime::{ValidationError, ValidationErrors};
 
 /// Result type for validation operations
@@ -0,0 +1,690 @@
+//! Rule linter
+//!
+//! Flags expressions that are valid but almost certainly not what the author
+//! meant: comparisons with a constant outcome, `x == true`, repeated operands
+//! in `&&`/`||` chains, exact float equality, deeply nested `if` expressions,
+//! redundant parentheses and identifiers spelled like a temporal keyword. Each lint has a level ([`LintLevel`]) that can
+//! be changed per lint through a [`LintConfig`].
+//!
+//! [`LintConfig::check_rule_set`] also flags rules of a
+//! [`RuleSet`] that duplicate an earlier rule.
+//!
+//! # Example
+//!
+//! ```
+//! use elo_rust::lint::{self, LintKind};
+//! use elo_rust::parser::Parser;
+//!
+//! let expr = Parser::parse("verified == true && age >= 18").unwrap();
+//! let lints = lint::check(&expr);
+//! assert_eq!(lints[0].kind, LintKind::BoolComparison);
+//! ```
+
+use crate::ast::span::{Span, SpanTree};
+use crate::ast::{BinaryOperator, Expr, Literal};
+use crate::fix::{self, Fix};
+use crate::parser::lexer::temporal_keyword;
+use crate::parser::{Lexer, ParseError, Parser, Token};
+use crate::rules::RuleSet;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Default maximum nesting of `if` expressions before `nested_ternary` fires
+pub const DEFAULT_MAX_TERNARY_DEPTH: usize = 2;
+
+/// The individual checks performed by the linter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LintKind {
+    /// A comparison whose outcome is known statically (`x == x`, `1 < 2`)
+    ConstantComparison,
+    /// Parentheses that do not change the meaning (`(x)`, `((a + b))`)
+    RedundantParens,
+    /// Comparing with a boolean literal (`verified == true`)
+    BoolComparison,
+    /// The same operand repeated in an `&&` or `||` chain
+    DuplicateConjunct,
+    /// Exact equality on floating point values (`price == 0.1`)
+    FloatEquality,
+    /// `if` expressions nested deeper than the configured limit
+    NestedTernary,
+    /// An identifier that is a temporal keyword in another case (`today`)
+    ShadowedKeyword,
+    /// A rule identical to an earlier rule of the same set
+    DuplicateRule,
+}
+
+impl LintKind {
+    /// All lints, in reporting order
+    pub const ALL: [LintKind; 8] = [
+        LintKind::ConstantComparison,
+        LintKind::RedundantParens,
+        LintKind::BoolComparison,
+        LintKind::DuplicateConjunct,
+        LintKind::FloatEquality,
+        LintKind::NestedTernary,
+        LintKind::ShadowedKeyword,
+        LintKind::DuplicateRule,
+    ];
+
+    /// Name used in configuration and diagnostics (e.g. "float_equality")
+    pub fn name(&self) -> &'static str {
+        match self {
+            LintKind::ConstantComparison => "constant_comparison",
+            LintKind::RedundantParens => "redundant_parens",
+            LintKind::BoolComparison => "bool_comparison",
+            LintKind::DuplicateConjunct => "duplicate_conjunct",
+            LintKind::FloatEquality => "float_equality",
+            LintKind::NestedTernary => "nested_ternary",
+            LintKind::ShadowedKeyword => "shadowed_keyword",
+            LintKind::DuplicateRule => "duplicate_rule",
+        }
+    }
+
+    /// Look up a lint by name
+    pub fn from_name(name: &str) -> Option<LintKind> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+}
+
+impl fmt::Display for LintKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// How a lint is reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LintLevel {
+    /// Not reported
+    Allow,
+    /// Reported, but does not fail
+    Warn,
+    /// Reported as an error
+    Deny,
+}
+
+impl LintLevel {
+    /// Parse "allow", "warn" or "deny"
+    pub fn from_name(name: &str) -> Option<LintLevel> {
+        match name {
+            "allow" => Some(LintLevel::Allow),
+            "warn" => Some(LintLevel::Warn),
+            "deny" => Some(LintLevel::Deny),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for LintLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LintLevel::Allow => write!(f, "allow"),
+            LintLevel::Warn => write!(f, "warning"),
+            LintLevel::Deny => write!(f, "error"),
+        }
+    }
+}
+
+/// A single finding
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lint {
+    /// Which check fired
+    pub kind: LintKind,
+    /// Configured level of the check
+    pub level: LintLevel,
+    /// Human-readable explanation
+    pub message: String,
+    /// Where the finding is; `None` for expressions linted without source
+    pub span: Option<Span>,
+    /// Edit resolving the finding, if it has an unambiguous one
+    pub fix: Option<Fix>,
+}
+
+impl Lint {
+    /// Check if this finding should fail the lint run
+    pub fn is_denied(&self) -> bool {
+        self.level == LintLevel::Deny
+    }
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}[{}]: {}", self.level, self.kind, self.message)
+    }
+}
+
+/// Lint levels and thresholds
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintConfig {
+    levels: HashMap<LintKind, LintLevel>,
+    max_ternary_depth: usize,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            levels: HashMap::new(),
+            max_ternary_depth: DEFAULT_MAX_TERNARY_DEPTH,
+        }
+    }
+}
+
+impl LintConfig {
+    /// Create a configuration with every lint at [`LintLevel::Warn`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the level of a lint
+    pub fn with_level(mut self, kind: LintKind, level: LintLevel) -> Self {
+        self.levels.insert(kind, level);
+        self
+    }
+
+    /// Set the deepest allowed nesting of `if` expressions
+    pub fn with_max_ternary_depth(mut self, depth: usize) -> Self {
+        self.max_ternary_depth = depth;
+        self
+    }
+
+    /// Get the level of a lint
+    pub fn level(&self, kind: LintKind) -> LintLevel {
+        self.levels.get(&kind).copied().unwrap_or(LintLevel::Warn)
+    }
+
+    /// Lint a parsed expression
+    ///
+    /// Redundant parentheses are not visible in the AST; use
+    /// [`check_source`](Self::check_source) to include that lint.
+    pub fn check(&self, expr: &Expr) -> Vec<Lint> {
+        let mut lints = Vec::new();
+        self.walk(expr, None, 0, None, &mut lints);
+        lints
+    }
+
+    /// Parse and lint an expression, including token-level lints
+    ///
+    /// The findings carry their source spans, and redundant parentheses
+    /// the [`Fix`] removing them.
+    pub fn check_source(&self, source: &str) -> Result<Vec<Lint>, ParseError> {
+        let spanned = Parser::parse_spanned(source)?;
+        let mut lints = Vec::new();
+        if let Ok(tokens) = Lexer::new(source).tokenize_spanned() {
+            let plain: Vec<Token> = tokens.iter().map(|t| t.token.clone()).collect();
+            for (open, close) in redundant_parens(&plain) {
+                let span = Span {
+                    start: tokens[open].start,
+                    end: tokens[close].end,
+                    line: tokens[open].line,
+                    column: tokens[open].column,
+                };
+                self.report(
+                    LintKind::RedundantParens,
+                    "unnecessary parentheses".to_string(),
+                    Some(span),
+                    Some(fix::remove_parens(source, span)),
+                    &mut lints,
+                );
+            }
+        }
+        self.walk(&spanned.expr, Some(&spanned.spans), 0, None, &mut lints);
+        Ok(lints)
+    }
+
+    /// Lint every rule of a set, then the set as a whole
+    pub fn check_rule_set(&self, rules: &RuleSet) -> Vec<Lint> {
+        let mut lints = Vec::new();
+        for rule in rules.rules() {
+            lints.extend(self.check(&rule.expr));
+        }
+        for duplicate in rules.duplicate_rules() {
+            self.report(
+                LintKind::DuplicateRule,
+                duplicate.to_string(),
+                None,
+                None,
+                &mut lints,
+            );
+        }
+        lints
+    }
+
+    fn report(
+        &self,
+        kind: LintKind,
+        message: String,
+        span: Option<Span>,
+        fix: Option<Fix>,
+        lints: &mut Vec<Lint>,
+    ) {
+        let level = self.level(kind);
+        if level != LintLevel::Allow {
+            lints.push(Lint {
+                kind,
+                level,
+                message,
+                span,
+                fix,
+            });
+        }
+    }
+
+    /// `spans` are the spans of `expr`, if it was parsed from source;
+    /// `parent` is the operator of the enclosing binary operation, used to
+    /// lint each `&&`/`||` chain once from its top node
+    fn walk(
+        &self,
+        expr: &Expr,
+        spans: Option<&SpanTree>,
+        if_depth: usize,
+        parent: Option<BinaryOperator>,
+        lints: &mut Vec<Lint>,
+    ) {
+        let span = spans.map(|tree| tree.span);
+        let mut child_depth = if_depth;
+        let mut child_parent = None;
+        match expr {
+            Expr::BinaryOp { op, left, right } => {
+                self.check_binary(*op, left, right, span, lints);
+                if matches!(op, BinaryOperator::And | BinaryOperator::Or) && parent != Some(*op) {
+                    self.check_chain(*op, expr, spans, lints);
+                }
+                child_parent = Some(*op);
+            }
+            Expr::If { .. } => {
+                child_depth += 1;
+                if child_depth == self.max_ternary_depth + 1 {
+                    self.report(
+                        LintKind::NestedTernary,
+                        format!(
+                            "`if` expressions nested {} levels deep; consider splitting into rules",
+                            child_depth
+                        ),
+                        span,
+                        None,
+                        lints,
+                    );
+                }
+            }
+            Expr::Identifier(name) => {
+                let upper = name.to_ascii_uppercase();
+                if temporal_keyword(&upper).is_some() {
+                    self.report(
+                        LintKind::ShadowedKeyword,
+                        format!(
+                            "`{}` is an identifier, not the temporal keyword; did you mean `{}`?",
+                            name, upper
+                        ),
+                        span,
+                        None,
+                        lints,
+                    );
+                }
+            }
+            _ => {}
+        }
+        for (i, child) in expr.children().into_iter().enumerate() {
+            let child_spans = spans.and_then(|tree| tree.children.get(i));
+            self.walk(child, child_spans, child_depth, child_parent, lints);
+        }
+    }
+
+    fn check_binary(
+        &self,
+        op: BinaryOperator,
+        left: &Expr,
+        right: &Expr,
+        span: Option<Span>,
+        lints: &mut Vec<Lint>,
+    ) {
+        if !is_comparison(op) {
+            return;
+        }
+
+        if left == right && is_pure(left) {
+            let outcome = matches!(
+                op,
+                BinaryOperator::Eq | BinaryOperator::Lte | BinaryOperator::Gte
+            );
+            self.report(
+                LintKind::ConstantComparison,
+                format!(
+                    "comparing an expression with itself using `{}` is always {}",
+                    op, outcome
+                ),
+                span,
+                None,
+                lints,
+            );
+        } else if is_constant(left) && is_constant(right) {
+            self.report(
+                LintKind::ConstantComparison,
+                format!("comparison `{}` between constants has a fixed result", op),
+                span,
+                None,
+                lints,
+            );
+        }
+
+        if matches!(op, BinaryOperator::Eq | BinaryOperator::Neq) {
+            for (side, other) in [(left, right), (right, left)] {
+                if let Expr::Literal(Literal::Boolean(b)) = side {
+                    if !matches!(other, Expr::Literal(_)) {
+                        let negated = (*b) == (op == BinaryOperator::Neq);
+                        let suggestion = if negated {
+                            "negate the operand with `!`"
+                        } else {
+                            "use the operand directly"
+                        };
+                        self.report(
+                            LintKind::BoolComparison,
+                            format!("comparison with `{}` is redundant; {}", b, suggestion),
+                            span,
+                            None,
+                            lints,
+                        );
+                    }
+                }
+            }
+            if is_float(left) || is_float(right) {
+                self.report(
+                    LintKind::FloatEquality,
+                    format!(
+                        "`{}` on floating point values is unreliable; compare within a tolerance",
+                        op
+                    ),
+                    span,
+                    None,
+                    lints,
+                );
+            }
+        }
+    }
+
+    fn check_chain(
+        &self,
+        op: BinaryOperator,
+        expr: &Expr,
+        spans: Option<&SpanTree>,
+        lints: &mut Vec<Lint>,
+    ) {
+        let mut operands = Vec::new();
+        flatten_chain(op, expr, spans, &mut operands);
+        for (i, (operand, span)) in operands.iter().enumerate() {
+            if operands[..i].iter().any(|(earlier, _)| earlier == operand) {
+                let what = if op == BinaryOperator::And {
+                    "conjunct"
+                } else {
+                    "disjunct"
+                };
+                self.report(
+                    LintKind::DuplicateConjunct,
+                    format!("duplicated {} in `{}` chain", what, op),
+                    *span,
+                    None,
+                    lints,
+                );
+            }
+        }
+    }
+}
+
+/// Lint an expression with the default configuration
+pub fn check(expr: &Expr) -> Vec<Lint> {
+    LintConfig::default().check(expr)
+}
+
+/// Parse and lint an expression with the default configuration
+pub fn check_source(source: &str) -> Result<Vec<Lint>, ParseError> {
+    LintConfig::default().check_source(source)
+}
+
+fn is_comparison(op: BinaryOperator) -> bool {
+    matches!(
+        op,
+        BinaryOperator::Eq
+            | BinaryOperator::Neq
+            | BinaryOperator::Lt
+            | BinaryOperator::Lte
+            | BinaryOperator::Gt
+            | BinaryOperator::Gte
+    )
+}
+
+/// Operands of an `op` chain with their spans, if known
+fn flatten_chain<'a>(
+    op: BinaryOperator,
+    expr: &'a Expr,
+    spans: Option<&SpanTree>,
+    out: &mut Vec<(&'a Expr, Option<Span>)>,
+) {
+    match expr {
+        Expr::BinaryOp {
+            op: inner,
+            left,
+            right,
+        } if *inner == op => {
+            let child = |i: usize| spans.and_then(|tree| tree.children.get(i));
+            flatten_chain(op, left, child(0), out);
+            flatten_chain(op, right, child(1), out);
+        }
+        other => out.push((other, spans.map(|tree| tree.span))),
+    }
+}
+
+/// Literals whose value is known without evaluating anything
+fn is_constant(expr: &Expr) -> bool {
+    matches!(
+        expr,
+        Expr::Literal(_)
+            | Expr::String(_)
+            | Expr::Null
+            | Expr::Quantity { .. }
+            | Expr::Percentage(_)
+    )
+}
+
+/// Expressions that evaluate to the same value each time (no calls or clocks)
+fn is_pure(expr: &Expr) -> bool {
+    !matches!(expr, Expr::FunctionCall { .. } | Expr::TemporalKeyword(_))
+        && expr.children().into_iter().all(is_pure)
+}
+
+fn is_float(expr: &Expr) -> bool {
+    matches!(
+        expr,
+        Expr::Literal(Literal::Float(_)) | Expr::Quantity { .. } | Expr::Percentage(_)
+    )
+}
+
+/// Token indices of the parentheses of groups that do not affect parsing
+///
+/// A group is redundant when it wraps a single token, directly wraps another
+/// group, or wraps the whole expression. Parentheses of calls are skipped.
+pub(crate) fn redundant_parens(tokens: &[Token]) -> Vec<(usize, usize)> {
+    let mut matching = vec![None; tokens.len()];
+    let mut stack = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            Token::LeftParen => stack.push(i),
+            Token::RightParen => {
+                if let Some(open) = stack.pop() {
+                    matching[open] = Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let last = tokens
+        .iter()
+        .rposition(|t| *t != Token::Eof)
+        .unwrap_or_default();
+    let mut redundant = Vec::new();
+    for (open, close) in matching
+        .iter()
+        .enumerate()
+        .filter_map(|(open, close)| close.map(|c| (open, c)))
+    {
+        let is_call = open > 0 && matches!(tokens[open - 1], Token::Identifier(_) | Token::Fn);
+        if is_call {
+            continue;
+        }
+        let single_token = close == open + 2;
+        let double_wrapped =
+            tokens[open + 1] == Token::LeftParen && matching[open + 1] == Some(close - 1);
+        let whole_expression = open == 0 && close == last;
+        if single_token || double_wrapped || whole_expression {
+            redundant.push((open, close));
+        }
+    }
+    redundant
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(source: &str) -> Vec<LintKind> {
+        check_source(source)
+            .unwrap()
+            .into_iter()
+            .map(|l| l.kind)
+            .collect()
+    }
+
+    #[test]
+    fn test_clean_expression() {
+        assert!(kinds("age >= 18 && (status == 'active' || admin)").is_empty());
+        assert!(kinds("length(name) > 0").is_empty());
+    }
+
+    #[test]
+    fn test_constant_comparison() {
+        assert_eq!(kinds("age == age"), vec![LintKind::ConstantComparison]);
+        assert_eq!(kinds("1 < 2"), vec![LintKind::ConstantComparison]);
+        // Calls may return different values
+        assert!(kinds("random() == random()").is_empty());
+    }
+
+    #[test]
+    fn test_bool_comparison() {
+        let lints = check_source("verified != false").unwrap();
+        assert_eq!(lints.len(), 1);
+        assert_eq!(lints[0].kind, LintKind::BoolComparison);
+        assert!(lints[0].message.contains("directly"));
+        assert!(check_source("verified == false").unwrap()[0]
+            .message
+            .contains("negate"));
+    }
+
+    #[test]
+    fn test_duplicate_conjunct() {
+        assert_eq!(
+            kinds("a > 1 && b && a > 1"),
+            vec![LintKind::DuplicateConjunct]
+        );
+        assert_eq!(kinds("a || b || a"), vec![LintKind::DuplicateConjunct]);
+        assert!(kinds("a && b || a").is_empty());
+    }
+
+    #[test]
+    fn test_lint_spans() {
+        let source = "a > 1 && (b) && a > 1";
+        let lints = check_source(source).unwrap();
+        let spans: Vec<(LintKind, &str)> = lints
+            .iter()
+            .map(|l| (l.kind, l.span.unwrap().text(source)))
+            .collect();
+        assert_eq!(
+            spans,
+            vec![
+                (LintKind::RedundantParens, "(b)"),
+                (LintKind::DuplicateConjunct, "a > 1"),
+            ]
+        );
+        assert_eq!(lints[1].span.unwrap().column, 17);
+        assert_eq!(lints[0].fix.as_ref().unwrap().replacement, "b");
+        assert!(lints[1].fix.is_none());
+
+        // Without source there is nothing to point at
+        let expr = Parser::parse("age == age").unwrap();
+        assert_eq!(check(&expr)[0].span, None);
+    }
+
+    #[test]
+    fn test_float_equality() {
+        assert_eq!(kinds("price == 0.1"), vec![LintKind::FloatEquality]);
+        assert!(kinds("count == 1").is_empty());
+    }
+
+    #[test]
+    fn test_nested_ternary() {
+        assert!(kinds("if a then 1 else if b then 2 else 3").is_empty());
+        assert_eq!(
+            kinds("if a then 1 else if b then 2 else if c then 3 else 4"),
+            vec![LintKind::NestedTernary]
+        );
+        let config = LintConfig::new().with_max_ternary_depth(1);
+        let lints = config
+            .check_source("if a then 1 else if b then 2 else 3")
+            .unwrap();
+        assert_eq!(lints.len(), 1);
+    }
+
+    #[test]
+    fn test_shadowed_keyword() {
+        assert_eq!(kinds("created_at < today"), vec![LintKind::ShadowedKeyword]);
+        assert!(kinds("created_at < TODAY").is_empty());
+        assert!(kinds("created_at < today_start").is_empty());
+    }
+
+    #[test]
+    fn test_redundant_parens() {
+        assert_eq!(kinds("(age) > 1"), vec![LintKind::RedundantParens]);
+        assert_eq!(kinds("((a + b)) * 2"), vec![LintKind::RedundantParens]);
+        assert_eq!(kinds("(a > 1 && b)"), vec![LintKind::RedundantParens]);
+        assert!(kinds("(a + b) * 2").is_empty());
+        assert!(kinds("length(x) > (a - b) * 2").is_empty());
+    }
+
+    #[test]
+    fn test_levels() {
+        let config = LintConfig::new()
+            .with_level(LintKind::FloatEquality, LintLevel::Deny)
+            .with_level(LintKind::BoolComparison, LintLevel::Allow);
+        let lints = config
+            .check_source("price == 0.1 && verified == true")
+            .unwrap();
+        assert_eq!(lints.len(), 1);
+        assert!(lints[0].is_denied());
+        assert_eq!(
+            lints[0].to_string(),
+            "error[float_equality]: `==` on floating point values is unreliable; compare within a tolerance"
+        );
+    }
+
+    #[test]
+    fn test_duplicate_rule() {
+        let rules = RuleSet::parse(
+            "rule is_adult: age >= 18\n\
+             rule of_age: age >= 18",
+        )
+        .unwrap();
+        let lints = LintConfig::new().check_rule_set(&rules);
+        assert_eq!(lints.len(), 1);
+        assert_eq!(
+            lints[0].to_string(),
+            "warning[duplicate_rule]: rule 'of_age' is identical to rule 'is_adult'"
+        );
+    }
+
+    #[test]
+    fn test_lint_names_round_trip() {
+        for kind in LintKind::ALL {
+            assert_eq!(LintKind::from_name(kind.name()), Some(kind));
+        }
+        assert_eq!(LintLevel::from_name("deny"), Some(LintLevel::Deny));
+        assert_eq!(LintKind::from_name("unknown"), None);
+    }
+}
@@ -0,0 +1,210 @@
+//! Migration of expressions relying on 0.x code generation
+//!
+//! Validators generated by 0.x releases translated two operators
+//! incorrectly:
+//!
+//! - `a ^ b` compiled to `a * b`
+//! - unary `+x` compiled to `-x`
+//!
+//! [`migrate`] finds every use of them and rewrites the expression in an
+//! explicit form, so that its result is the same before and after the
+//! operator fixes land. With [`MigrationMode::AdoptFixes`] it instead keeps
+//! the correct meaning and only reports the places whose results change.
+//!
+//! # Example
+//!
+//! ```
+//! use elo_rust::migrate::{migrate, MigrationMode};
+//!
+//! let migration = migrate("area <= width ^ 2 && +delta > 0", MigrationMode::PreserveResults).unwrap();
+//! assert_eq!(migration.source, "area <= (width * 2) && -delta > 0");
+//! assert_eq!(migration.notes.len(), 2);
+//! ```
+
+use crate::fix::{self, Fix};
+use crate::parser::{LegacyOperator, ParseError, Parser, SpannedToken, Token};
+use std::fmt;
+
+/// An operator whose generated code changed meaning
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LegacyBehavior {
+    /// `a ^ b` compiled to `a * b`
+    PowerAsMultiply,
+    /// `+x` compiled to `-x`
+    UnaryPlusNegates,
+}
+
+/// What the rewritten expression should compute
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MigrationMode {
+    /// Spell out what 0.x validators computed, so results do not change
+    #[default]
+    PreserveResults,
+    /// Keep the documented meaning; results of the reported places change
+    AdoptFixes,
+}
+
+/// A place in the expression that relied on 0.x behavior
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationNote {
+    /// The behavior relied on
+    pub behavior: LegacyBehavior,
+    /// Line number (1-based) of the operator
+    pub line: usize,
+    /// Column number (1-based) of the operator
+    pub column: usize,
+    /// What was done, and whether results change
+    pub message: String,
+}
+
+impl fmt::Display for MigrationNote {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+/// A migrated expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Migration {
+    /// The rewritten source
+    pub source: String,
+    /// Every use of a legacy operator, in source order
+    pub notes: Vec<MigrationNote>,
+    /// The mode the expression was migrated in
+    pub mode: MigrationMode,
+}
+
+impl Migration {
+    /// Whether the rewritten expression computes different results than
+    /// 0.x validators did
+    pub fn changes_results(&self) -> bool {
+        self.mode == MigrationMode::AdoptFixes && !self.notes.is_empty()
+    }
+}
+
+/// Rewrite `source` so it no longer depends on 0.x operator translation
+pub fn migrate(source: &str, mode: MigrationMode) -> Result<Migration, ParseError> {
+    let (tokens, legacy) = Parser::legacy_operators(source)?;
+    let mut edits = Vec::new();
+    let mut notes = Vec::new();
+
+    for operator in legacy {
+        let (behavior, op) = match operator {
+            LegacyOperator::Pow { op, .. } => (LegacyBehavior::PowerAsMultiply, op),
+            LegacyOperator::UnaryPlus { op } => (LegacyBehavior::UnaryPlusNegates, op),
+        };
+        let message = match (operator, mode) {
+            (LegacyOperator::Pow { start, op, end }, MigrationMode::PreserveResults) => {
+                edits.push(replace(&tokens[op], "*"));
+                if !is_parenthesized(&tokens, start, end) {
+                    let open = tokens[start].start;
+                    let close = source[..tokens[end].start].trim_end().len();
+                    edits.push(insert(open, "("));
+                    edits.push(insert(close, ")"));
+                }
+                "'^' compiled to '*' in 0.x; rewritten as a multiplication to keep the result"
+            }
+            (LegacyOperator::UnaryPlus { op }, MigrationMode::PreserveResults) => {
+                edits.push(replace(&tokens[op], "-"));
+                "unary '+' negated its operand in 0.x; rewritten as '-' to keep the result"
+            }
+            (LegacyOperator::Pow { .. }, MigrationMode::AdoptFixes) => {
+                "'^' compiled to '*' in 0.x; result changes to exponentiation"
+            }
+            (LegacyOperator::UnaryPlus { op }, MigrationMode::AdoptFixes) => {
+                edits.push(replace(&tokens[op], ""));
+                "unary '+' negated its operand in 0.x; removed, so the result changes sign"
+            }
+        };
+        notes.push(MigrationNote {
+            behavior,
+            line: tokens[op].line,
+            column: tokens[op].column,
+            message: message.to_string(),
+        });
+    }
+
+    notes.sort_by_key(|note| (note.line, note.column));
+    Ok(Migration {
+        source: fix::apply(source, &edits).0,
+        notes,
+        mode,
+    })
+}
+
+/// Whether tokens `start..end` need no parentheses to keep their grouping:
+/// they form the whole expression or are already enclosed
+fn is_parenthesized(tokens: &[SpannedToken], start: usize, end: usize) -> bool {
+    let after = tokens.get(end).map(|t| &t.token);
+    match start.checked_sub(1).map(|before| &tokens[before].token) {
+        None => after == Some(&Token::Eof),
+        Some(before) => *before == Token::LeftParen && after == Some(&Token::RightParen),
+    }
+}
+
+/// Replace an operator token
+fn replace(token: &SpannedToken, text: &str) -> Fix {
+    Fix {
+        span: token.start..token.end,
+        replacement: text.to_string(),
+        message: String::new(),
+    }
+}
+
+fn insert(at: usize, text: &str) -> Fix {
+    Fix {
+        span: at..at,
+        replacement: text.to_string(),
+        message: String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preserved(source: &str) -> String {
+        migrate(source, MigrationMode::PreserveResults)
+            .unwrap()
+            .source
+    }
+
+    #[test]
+    fn test_preserve_power() {
+        assert_eq!(preserved("x ^ 2"), "x * 2");
+        assert_eq!(preserved("a / b ^ c > 1"), "a / (b * c) > 1");
+        assert_eq!(preserved("a ^ b ^ c"), "a * (b * c)");
+        assert_eq!(preserved("f(x ^ 2) + (y ^ 3)"), "f(x * 2) + (y * 3)");
+        assert_eq!(preserved("-x ^ 2 < 10"), "(-x * 2) < 10");
+        assert_eq!(preserved("age >= 18"), "age >= 18");
+    }
+
+    #[test]
+    fn test_preserve_unary_plus() {
+        let migration = migrate("a > 0 &&\n+b < 5", MigrationMode::PreserveResults).unwrap();
+        assert_eq!(migration.source, "a > 0 &&\n-b < 5");
+        assert_eq!(
+            migration.notes[0].behavior,
+            LegacyBehavior::UnaryPlusNegates
+        );
+        assert_eq!((migration.notes[0].line, migration.notes[0].column), (2, 1));
+        assert!(!migration.changes_results());
+    }
+
+    #[test]
+    fn test_adopt_fixes() {
+        let migration = migrate("+x ^ 2 > y", MigrationMode::AdoptFixes).unwrap();
+        assert_eq!(migration.source, "x ^ 2 > y");
+        assert_eq!(migration.notes.len(), 2);
+        assert_eq!(
+            migration.notes[1].to_string(),
+            "line 1, column 4: '^' compiled to '*' in 0.x; result changes to exponentiation"
+        );
+        assert!(migration.changes_results());
+        assert!(migrate("x ^", MigrationMode::AdoptFixes).is_err());
+    }
+}
@@ -0,0 +1,456 @@
+//! Tenant parameters for `${name}` placeholders
+//!
+//! A rule can leave values open with placeholders, e.g.
+//! `amount <= ${max_amount}`, and each tenant supplies its own values in a
+//! [`Params`] map. Binding replaces every placeholder with a literal, after
+//! checking that all placeholders are bound and that each value fits the
+//! way it is used (a number next to `<=`, a boolean next to `&&`, ...).
+//!
+//! Bind once per tenant before code generation
+//! ([`RustCodeGenerator::with_params`](crate::RustCodeGenerator::with_params),
+//! [`RuleSet::bind_params`](crate::RuleSet::bind_params)), or bind a parsed
+//! expression at runtime with [`Params::bind`].
+//!
+//! # Example
+//!
+//! ```
+//! use elo_rust::params::Params;
+//! use elo_rust::parser::Parser;
+//! use elo_rust::runtime::EloValue;
+//!
+//! let expr = Parser::parse("amount <= ${max_amount}").unwrap();
+//!
+//! let mut params = Params::new();
+//! params.insert("max_amount", EloValue::Integer(500));
+//! assert_eq!(params.bind(&expr).unwrap(), Parser::parse("amount <= 500").unwrap());
+//!
+//! params.insert("max_amount", EloValue::Boolean(true));
+//! assert_eq!(
+//!     params.bind(&expr).unwrap_err().to_string(),
+//!     "Parameter ${max_amount} is a boolean, expected a number or string"
+//! );
+//! ```
+
+use crate::ast::visitor::{fold_children, walk_expr, Walker};
+use crate::ast::{BinaryOperator, Expr, Fold, Literal, UnaryOperator};
+use crate::runtime::{EloValue, TemporalValue};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+/// Errors raised while binding placeholders
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParamError {
+    /// Placeholders without a value, sorted
+    Unbound(Vec<String>),
+
+    /// A value does not fit the way its placeholder is used
+    TypeMismatch {
+        /// Parameter name
+        name: String,
+        /// What the placeholder's context accepts (e.g. "a number")
+        expected: &'static str,
+        /// Type of the supplied value
+        found: &'static str,
+    },
+}
+
+impl fmt::Display for ParamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unbound(names) => {
+                let names: Vec<String> = names.iter().map(|n| format!("${{{}}}", n)).collect();
+                write!(f, "Unbound parameters: {}", names.join(", "))
+            }
+            Self::TypeMismatch {
+                name,
+                expected,
+                found,
+            } => {
+                let article = if found.starts_with(['a', 'e', 'i', 'o', 'u']) {
+                    "an"
+                } else {
+                    "a"
+                };
+                write!(
+                    f,
+                    "Parameter ${{{}}} is {} {}, expected {}",
+                    name, article, found, expected
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParamError {}
+
+/// Typed values for placeholders, keyed by parameter name
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Params {
+    values: BTreeMap<String, EloValue>,
+}
+
+impl Params {
+    /// Create an empty parameter map
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set a parameter, returning its previous value
+    pub fn insert(&mut self, name: impl Into<String>, value: EloValue) -> Option<EloValue> {
+        self.values.insert(name.into(), value)
+    }
+
+    /// Look up a parameter
+    pub fn get(&self, name: &str) -> Option<&EloValue> {
+        self.values.get(name)
+    }
+
+    /// Number of parameters
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Check if no parameter is set
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Set a parameter from a `name=value` assignment, as given to the
+    /// `--param` option of the command-line tools
+    ///
+    /// The value is read as JSON (`500`, `true`, `["FR", "DE"]`), or else
+    /// taken as a string, so `region=EU` needs no quotes. Without the
+    /// `serde-support` feature only numbers, booleans and `null` are read;
+    /// anything else is a string.
+    pub fn insert_assignment(&mut self, assignment: &str) -> Result<(), String> {
+        let (name, value) = assignment
+            .split_once('=')
+            .ok_or_else(|| format!("expected name=value, got '{}'", assignment))?;
+        let name = name.trim();
+        if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return Err(format!("invalid parameter name '{}'", name));
+        }
+        self.insert(name, assignment_value(value));
+        Ok(())
+    }
+
+    /// Replace every placeholder in `expr` with its value
+    ///
+    /// Fails if a placeholder has no value or a value's type does not fit
+    /// the operator or construct it is used in.
+    pub fn bind(&self, expr: &Expr) -> Result<Expr, ParamError> {
+        let unbound: Vec<String> = placeholders(expr)
+            .into_iter()
+            .filter(|name| !self.values.contains_key(name))
+            .collect();
+        if !unbound.is_empty() {
+            return Err(ParamError::Unbound(unbound));
+        }
+        self.check_types(expr)?;
+        Ok(Binder { params: self }.fold_expr(expr.clone()))
+    }
+
+    /// Check the values of placeholders directly used by `expr` and its children
+    fn check_types(&self, expr: &Expr) -> Result<(), ParamError> {
+        let expectations: Vec<(&Expr, Expected)> = match expr {
+            Expr::BinaryOp { op, left, right } => {
+                let (left_ok, right_ok) = binary_expectations(*op);
+                vec![(&**left, left_ok), (&**right, right_ok)]
+            }
+            Expr::UnaryOp { op, operand } => match op {
+                UnaryOperator::Not => vec![(&**operand, Expected::Boolean)],
+                UnaryOperator::Neg | UnaryOperator::Plus => vec![(&**operand, Expected::Number)],
+            },
+            Expr::If { condition, .. }
+            | Expr::Guard { condition, .. }
+            | Expr::Require { condition, .. }
+            | Expr::WarnIf { condition, .. } => vec![(&**condition, Expected::Boolean)],
+            _ => Vec::new(),
+        };
+
+        for (operand, expected) in expectations {
+            if let Expr::Placeholder(name) = operand {
+                if let Some(value) = self.values.get(name) {
+                    if !expected.accepts(value) {
+                        return Err(ParamError::TypeMismatch {
+                            name: name.clone(),
+                            expected: expected.description(),
+                            found: value.type_name(),
+                        });
+                    }
+                }
+            }
+        }
+
+        expr.children()
+            .into_iter()
+            .try_for_each(|child| self.check_types(child))
+    }
+}
+
+/// Read the value of a `name=value` assignment as JSON, or else as a string
+#[cfg(feature = "serde-support")]
+fn assignment_value(value: &str) -> EloValue {
+    serde_json::from_str::<serde_json::Value>(value)
+        .map(EloValue::from)
+        .unwrap_or_else(|_| EloValue::String(value.to_string()))
+}
+
+/// Read the value of a `name=value` assignment as a number, boolean or
+/// `null`, or else as a string
+#[cfg(not(feature = "serde-support"))]
+fn assignment_value(value: &str) -> EloValue {
+    if let Ok(n) = value.parse::<i64>() {
+        EloValue::Integer(n)
+    } else if let Some(f) = value.parse::<f64>().ok().filter(|f| f.is_finite()) {
+        EloValue::Float(f)
+    } else {
+        match value {
+            "true" => EloValue::Boolean(true),
+            "false" => EloValue::Boolean(false),
+            "null" => EloValue::Null,
+            _ => EloValue::String(value.to_string()),
+        }
+    }
+}
+
+/// Names of all placeholders in an expression, sorted
+pub fn placeholders(expr: &Expr) -> BTreeSet<String> {
+    struct Collect(BTreeSet<String>);
+
+    impl Walker for Collect {
+        fn enter(&mut self, expr: &Expr) -> bool {
+            if let Expr::Placeholder(name) = expr {
+                self.0.insert(name.clone());
+            }
+            true
+        }
+    }
+
+    let mut collect = Collect(BTreeSet::new());
+    walk_expr(&mut collect, expr);
+    collect.0
+}
+
+/// Values a placeholder's context accepts
+#[derive(Debug, Clone, Copy)]
+enum Expected {
+    Any,
+    Number,
+    Boolean,
+    String,
+    Ordered,
+    Collection,
+}
+
+impl Expected {
+    fn accepts(self, value: &EloValue) -> bool {
+        let number = matches!(value, EloValue::Integer(_) | EloValue::Float(_));
+        match self {
+            Self::Any => true,
+            Self::Number => number,
+            Self::Boolean => matches!(value, EloValue::Boolean(_)),
+            Self::String => matches!(value, EloValue::String(_)),
+            Self::Ordered => number || matches!(value, EloValue::String(_)),
+            Self::Collection => matches!(value, EloValue::Array(_) | EloValue::Range { .. }),
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Self::Any => "any value",
+            Self::Number => "a number",
+            Self::Boolean => "a boolean",
+            Self::String => "a string",
+            Self::Ordered => "a number or string",
+            Self::Collection => "an array or range",
+        }
+    }
+}
+
+/// What the left and right operands of `op` accept
+fn binary_expectations(op: BinaryOperator) -> (Expected, Expected) {
+    match op {
+        BinaryOperator::Add
+        | BinaryOperator::Lt
+        | BinaryOperator::Lte
+        | BinaryOperator::Gt
+        | BinaryOperator::Gte => (Expected::Ordered, Expected::Ordered),
+        BinaryOperator::Sub
+        | BinaryOperator::Mul
+        | BinaryOperator::Div
+        | BinaryOperator::Mod
+        | BinaryOperator::Pow => (Expected::Number, Expected::Number),
+        BinaryOperator::And | BinaryOperator::Or => (Expected::Boolean, Expected::Boolean),
+        BinaryOperator::Like => (Expected::String, Expected::String),
+        BinaryOperator::In => (Expected::Any, Expected::Collection),
+        BinaryOperator::Eq | BinaryOperator::Neq => (Expected::Any, Expected::Any),
+    }
+}
+
+/// Replaces placeholders with literal expressions
+struct Binder<'a> {
+    params: &'a Params,
+}
+
+impl Fold for Binder<'_> {
+    fn fold_expr(&mut self, expr: Expr) -> Expr {
+        match fold_children(self, expr) {
+            Expr::Placeholder(name) => match self.params.get(&name) {
+                Some(value) => value_to_expr(value),
+                None => Expr::Placeholder(name),
+            },
+            expr => expr,
+        }
+    }
+}
+
+/// Literal expression for a parameter value
+fn value_to_expr(value: &EloValue) -> Expr {
+    match value {
+        EloValue::Integer(n) => Expr::Literal(Literal::Integer(*n)),
+        EloValue::Float(f) => Expr::Literal(Literal::Float(*f)),
+        EloValue::Boolean(b) => Expr::Literal(Literal::Boolean(*b)),
+        EloValue::String(s) => Expr::String(s.clone()),
+        EloValue::Null => Expr::Null,
+        EloValue::Array(items) => Expr::Array(items.iter().map(value_to_expr).collect()),
+        EloValue::Object(fields) => Expr::Object(
+            fields
+                .iter()
+                .map(|(key, value)| (key.clone(), value_to_expr(value)))
+                .collect(),
+        ),
+        EloValue::Range {
+            start,
+            end,
+            inclusive,
+        } => Expr::Range {
+            start: Box::new(value_to_expr(start)),
+            end: Box::new(value_to_expr(end)),
+            inclusive: *inclusive,
+        },
+        EloValue::Temporal(TemporalValue::Date(date)) => Expr::Date(date.to_string()),
+        EloValue::Temporal(TemporalValue::DateTime(datetime)) => {
+            Expr::DateTime(datetime.to_rfc3339())
+        }
+        EloValue::Temporal(TemporalValue::Duration(duration)) => {
+            Expr::Duration(format!("PT{}S", duration.num_seconds()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+
+    fn tenant() -> Params {
+        let mut params = Params::new();
+        params.insert("max_amount", EloValue::Integer(500));
+        params.insert("strict", EloValue::Boolean(true));
+        params.insert(
+            "countries",
+            EloValue::Array(vec![
+                EloValue::String("FR".to_string()),
+                EloValue::String("DE".to_string()),
+            ]),
+        );
+        params
+    }
+
+    #[test]
+    fn test_bind_all_placeholders() {
+        let expr =
+            Parser::parse("amount <= ${max_amount} && (!${strict} || country in ${countries})")
+                .unwrap();
+        assert_eq!(
+            placeholders(&expr).into_iter().collect::<Vec<_>>(),
+            vec!["countries", "max_amount", "strict"]
+        );
+        assert_eq!(
+            tenant().bind(&expr).unwrap(),
+            Parser::parse("amount <= 500 && (!true || country in ['FR', 'DE'])").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_insert_assignment() {
+        let mut params = Params::new();
+        params.insert_assignment("max_amount=500").unwrap();
+        params.insert_assignment("region=EU").unwrap();
+        assert_eq!(params.get("max_amount"), Some(&EloValue::Integer(500)));
+        assert_eq!(
+            params.get("region"),
+            Some(&EloValue::String("EU".to_string()))
+        );
+        #[cfg(feature = "serde-support")]
+        {
+            params.insert_assignment("countries=[\"FR\"]").unwrap();
+            assert_eq!(
+                params.get("countries"),
+                Some(&EloValue::Array(vec![EloValue::String("FR".to_string())]))
+            );
+        }
+        assert_eq!(
+            params.insert_assignment("max_amount"),
+            Err("expected name=value, got 'max_amount'".to_string())
+        );
+        assert!(params.insert_assignment("a-b=1").is_err());
+    }
+
+    #[test]
+    fn test_unbound_placeholders() {
+        let expr = Parser::parse("a < ${max_b} && b < ${max_a} && c < ${max_amount}").unwrap();
+        let err = tenant().bind(&expr).unwrap_err();
+        assert_eq!(
+            err,
+            ParamError::Unbound(vec!["max_a".to_string(), "max_b".to_string()])
+        );
+        assert_eq!(err.to_string(), "Unbound parameters: ${max_a}, ${max_b}");
+    }
+
+    #[test]
+    fn test_type_mismatch() {
+        let params = tenant();
+        for (source, name) in [
+            ("${strict} + 1 > 2", "strict"),
+            ("x && ${max_amount}", "max_amount"),
+            ("name like ${max_amount}", "max_amount"),
+            ("country in ${max_amount}", "max_amount"),
+            ("require ${countries} else 'no'", "countries"),
+        ] {
+            let err = params.bind(&Parser::parse(source).unwrap()).unwrap_err();
+            assert!(
+                matches!(&err, ParamError::TypeMismatch { name: n, .. } if n == name),
+                "{}: {}",
+                source,
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn test_type_mismatch_display() {
+        let err = tenant()
+            .bind(&Parser::parse("-${strict} < 0 || !${max_amount}").unwrap())
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Parameter ${strict} is a boolean, expected a number"
+        );
+        let err = tenant()
+            .bind(&Parser::parse("!${max_amount}").unwrap())
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Parameter ${max_amount} is an integer, expected a boolean"
+        );
+    }
+
+    #[test]
+    fn test_lexer_rejects_malformed_placeholder() {
+        assert!(Parser::parse("amount <= $max").is_err());
+        assert!(Parser::parse("amount <= ${max").is_err());
+        assert!(Parser::parse("amount <= ${}").is_err());
+    }
+}
@@ -0,0 +1,76 @@
+//! Parser configuration
+//!
+//! [`ParserConfig`] collects the options that change how source text is
+//! read, for use with [`Parser::parse_with_config`](super::Parser::parse_with_config).
+
+use super::edition::Edition;
+use super::operators::OperatorTable;
+
+/// Options for parsing ELO expressions
+///
+/// # Example
+///
+/// ```
+/// use elo_rust::ast::{Expr, TemporalKeyword};
+/// use elo_rust::parser::{Parser, ParserConfig};
+///
+/// let config = ParserConfig::new().with_case_insensitive_temporal(true);
+/// let expr = Parser::parse_with_config("today", &config).unwrap();
+/// assert_eq!(expr, Expr::TemporalKeyword(TemporalKeyword::Today));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ParserConfig {
+    /// Embedder-defined infix operators
+    pub operators: OperatorTable,
+    /// Accept `today`, `Now`, `eom`, ... as temporal keywords
+    ///
+    /// Off by default: lowercase spellings are identifiers, and the
+    /// `shadowed_keyword` lint flags them.
+    pub case_insensitive_temporal: bool,
+    /// Grammar edition; syntax from later editions is rejected
+    pub edition: Edition,
+    /// What to do when an object literal repeats a key
+    pub duplicate_keys: DuplicateKeys,
+}
+
+/// Handling of repeated keys in object literals such as `{a: 1, a: 2}`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateKeys {
+    /// Reject the literal with a parse error at the repeated key
+    #[default]
+    Error,
+    /// Keep the last value; the field takes the position of its last
+    /// occurrence in the parsed [`Expr::Object`](crate::ast::Expr::Object)
+    LastWins,
+}
+
+impl ParserConfig {
+    /// Create the default configuration
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use custom infix operators
+    pub fn with_operators(mut self, operators: OperatorTable) -> Self {
+        self.operators = operators;
+        self
+    }
+
+    /// Accept temporal keywords in any case
+    pub fn with_case_insensitive_temporal(mut self, enabled: bool) -> Self {
+        self.case_insensitive_temporal = enabled;
+        self
+    }
+
+    /// Parse with the grammar of `edition`
+    pub fn with_edition(mut self, edition: Edition) -> Self {
+        self.edition = edition;
+        self
+    }
+
+    /// Choose how repeated object literal keys are handled
+    pub fn with_duplicate_keys(mut self, duplicate_keys: DuplicateKeys) -> Self {
+        self.duplicate_keys = duplicate_keys;
+        self
+    }
+}
@@ -0,0 +1,163 @@
+//! Language editions
+//!
+//! An [`Edition`] fixes the syntax a rule corpus is written against. Syntax
+//! that would change the meaning of existing rules ships in a new edition,
+//! so old corpora keep parsing the same way until they opt in. Rule files
+//! choose their edition with a header line before the first rule:
+//!
+//! ```text
+//! edition = "2024"
+//!
+//! rule is_adult: age >= 18
+//! ```
+//!
+//! Embedders parsing single expressions set it with
+//! [`ParserConfig::with_edition`](super::ParserConfig::with_edition). Without
+//! either, the latest edition is used.
+
+use std::fmt;
+
+/// A version of the ELO grammar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum Edition {
+    /// The original grammar: arithmetic, comparisons, logic, `let`, `if`,
+    /// `guard`, lambdas, pipes and temporal keywords
+    Edition2023,
+    /// Adds the syntax listed in [`SyntaxFeature`]
+    #[default]
+    Edition2024,
+}
+
+impl Edition {
+    /// The newest edition
+    pub const LATEST: Edition = Edition::Edition2024;
+
+    /// All editions, oldest first
+    pub const ALL: [Edition; 2] = [Edition::Edition2023, Edition::Edition2024];
+
+    /// The year naming the edition (e.g. "2024")
+    pub fn name(&self) -> &'static str {
+        match self {
+            Edition::Edition2023 => "2023",
+            Edition::Edition2024 => "2024",
+        }
+    }
+
+    /// Look up an edition by its year
+    pub fn from_name(name: &str) -> Option<Edition> {
+        Self::ALL.into_iter().find(|edition| edition.name() == name)
+    }
+
+    /// Whether `feature` may be used in this edition
+    pub fn supports(&self, feature: SyntaxFeature) -> bool {
+        *self >= feature.edition()
+    }
+
+    /// Parse an `edition = "2024"` header line
+    ///
+    /// Returns `None` for lines that are not edition headers, and an error
+    /// for headers naming an unknown edition.
+    pub fn from_header(line: &str) -> Option<Result<Edition, String>> {
+        let (key, value) = line.split_once('=')?;
+        if key.trim() != "edition" {
+            return None;
+        }
+        let value = value.trim();
+        let name = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .unwrap_or(value);
+        Some(Edition::from_name(name).ok_or_else(|| {
+            let known: Vec<&str> = Self::ALL.iter().map(Edition::name).collect();
+            format!(
+                "unknown edition '{}'; expected one of {}",
+                name,
+                known.join(", ")
+            )
+        }))
+    }
+}
+
+impl fmt::Display for Edition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Syntax that is only available from some edition on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyntaxFeature {
+    /// `any x in xs satisfies cond`, `all x in xs satisfies cond`
+    Quantifiers,
+    /// `require cond else 'msg'`, `warn_if cond : 'msg'`
+    Assertions,
+    /// `cond message 'msg'`
+    Messages,
+    /// `value ?? default`
+    NullDefault,
+    /// `value ?| alternative`
+    Alternative,
+    /// `start..end`, `start..=end`
+    Ranges,
+    /// `name like 'a*'`
+    Like,
+    /// `15%`
+    Percentages,
+    /// `50kg`
+    Quantities,
+}
+
+impl SyntaxFeature {
+    /// First edition accepting the feature
+    pub fn edition(&self) -> Edition {
+        Edition::Edition2024
+    }
+
+    /// Description used in error messages
+    pub fn description(&self) -> &'static str {
+        match self {
+            SyntaxFeature::Quantifiers => "quantified expressions",
+            SyntaxFeature::Assertions => "require/warn_if expressions",
+            SyntaxFeature::Messages => "message clauses",
+            SyntaxFeature::NullDefault => "the '??' operator",
+            SyntaxFeature::Alternative => "the '?|' operator",
+            SyntaxFeature::Ranges => "range expressions",
+            SyntaxFeature::Like => "the 'like' operator",
+            SyntaxFeature::Percentages => "percentage literals",
+            SyntaxFeature::Quantities => "quantity literals",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edition_names() {
+        assert_eq!(Edition::default(), Edition::LATEST);
+        assert_eq!(Edition::from_name("2023"), Some(Edition::Edition2023));
+        assert_eq!(Edition::from_name("2022"), None);
+        assert!(Edition::Edition2024.supports(SyntaxFeature::Ranges));
+        assert!(!Edition::Edition2023.supports(SyntaxFeature::Ranges));
+    }
+
+    #[test]
+    fn test_header() {
+        assert_eq!(
+            Edition::from_header(r#"edition = "2023""#),
+            Some(Ok(Edition::Edition2023))
+        );
+        assert_eq!(
+            Edition::from_header("edition=2024"),
+            Some(Ok(Edition::Edition2024))
+        );
+        assert_eq!(Edition::from_header("rule a: x = 1"), None);
+        assert_eq!(
+            Edition::from_header(r#"edition = "2030""#),
+            Some(Err(
+                "unknown edition '2030'; expected one of 2023, 2024".to_string()
+            ))
+        );
+    }
+}
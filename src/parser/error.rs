@@ -13,6 +13,8 @@ pub struct ParseError {
     pub column: usize,
     /// Optional source context showing the problematic line
     pub context: Option<String>,
+    /// Suggested fix for common mistakes
+    pub hint: Option<String>,
 }
 
 impl ParseError {
@@ -23,6 +25,7 @@ impl ParseError {
             line,
             column,
             context: None,
+            hint: None,
         }
     }
 
@@ -35,6 +38,7 @@ impl ParseError {
             line,
             column,
             context,
+            hint: None,
         }
     }
 
@@ -50,9 +54,22 @@ impl ParseError {
             line,
             column,
             context: Some(context.into()),
+            hint: None,
         }
     }
 
+    /// Attach a suggested fix
+    pub fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+
+    /// Attach the offending line of `input`, with a caret at the error column
+    pub fn with_source(mut self, input: &str) -> Self {
+        self.context = Self::extract_context(input, self.line, self.column);
+        self
+    }
+
     /// Convert a string position to line and column numbers
     fn position_to_line_col(input: &str, position: usize) -> (usize, usize) {
         let mut line = 1;
@@ -74,6 +91,15 @@ impl ParseError {
     }
 
     /// Extract source context for an error (the problematic line with a caret pointer)
+    ///
+    /// The line is shown after its number, between empty gutter lines, as
+    /// rustc does:
+    ///
+    /// ```text
+    ///   |
+    /// 1 | a & b
+    ///   |   ^
+    /// ```
     fn extract_context(input: &str, line: usize, column: usize) -> Option<String> {
         let lines: Vec<&str> = input.lines().collect();
         if line == 0 || line > lines.len() {
@@ -82,12 +108,19 @@ impl ParseError {
 
         let error_line = lines[line - 1];
         let pointer = " ".repeat(column.saturating_sub(1)) + "^";
+        let gutter = Self::gutter(line);
 
         Some(format!(
-            "  {} |\n  {} | {}\n  {} | {}",
-            line, "|", error_line, "|", pointer
+            "{} |\n{} | {}\n{} | {}",
+            gutter, line, error_line, gutter, pointer
         ))
     }
+
+    /// Blank space as wide as the number of `line`, aligning the context
+    /// and help lines under it
+    fn gutter(line: usize) -> String {
+        " ".repeat(line.to_string().len())
+    }
 }
 
 impl fmt::Display for ParseError {
@@ -102,6 +135,10 @@ impl fmt::Display for ParseError {
             write!(f, "\n{}", context)?;
         }
 
+        if let Some(hint) = &self.hint {
+            write!(f, "\n{} = help: {}", Self::gutter(self.line), hint)?;
+        }
+
         Ok(())
     }
 }
@@ -157,6 +194,29 @@ mod tests {
         assert!(err.context.is_some());
     }
 
+    #[test]
+    fn test_parse_error_with_hint_and_source() {
+        let err = ParseError::new("Unexpected '&'", 1, 3)
+            .with_source("a & b")
+            .with_hint("did you mean '&&'?");
+        assert_eq!(
+            err.to_string(),
+            "Parse error at line 1, column 3: Unexpected '&'\n  |\n1 | a & b\n  |   ^\n  = help: did you mean '&&'?"
+        );
+    }
+
+    #[test]
+    fn test_context_gutter_fits_the_line_number() {
+        let input = format!("{}a = 1", "x &&\n".repeat(11));
+        let err = ParseError::new("Unexpected '='", 12, 3)
+            .with_source(&input)
+            .with_hint("use '==' to compare values");
+        assert_eq!(
+            err.to_string(),
+            "Parse error at line 12, column 3: Unexpected '='\n   |\n12 | a = 1\n   |   ^\n   = help: use '==' to compare values"
+        );
+    }
+
     #[test]
     fn test_position_to_line_col_first_line() {
         let input = "hello world";
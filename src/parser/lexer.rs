@@ -3,6 +3,7 @@
 //! Converts a string of ELO code into a stream of tokens.
 //! Handles all ELO token types including literals, operators, keywords, and punctuation.
 
+use crate::codegen::float::format_float;
 use std::fmt;
 
 /// A single token in an ELO expression
@@ -13,6 +14,10 @@ pub enum Token {
     Integer(i64),
     /// Float literal (e.g., 3.14, -2.5)
     Float(f64),
+    /// Percentage literal (e.g., 15% is 15.0, meaning 0.15)
+    Percentage(f64),
+    /// Number with a unit suffix (e.g., 50kg, 2.5km)
+    Quantity(f64, String),
     /// String literal (single-quoted, e.g., 'hello')
     String(String),
     /// Boolean true
@@ -75,6 +80,12 @@ pub enum Token {
     Fn,
     /// guard keyword
     Guard,
+    /// require keyword
+    Require,
+    /// warn_if keyword
+    WarnIf,
+    /// like keyword (glob pattern match)
+    Like,
 
     // Temporal keywords
     /// NOW keyword
@@ -115,6 +126,10 @@ pub enum Token {
     Identifier(String),
     /// Dot operator: .
     Dot,
+    /// Exclusive range operator: ..
+    DotDot,
+    /// Inclusive range operator: ..=
+    DotDotEqual,
     /// Comma: ,
     Comma,
     /// Left parenthesis: (
@@ -143,6 +158,18 @@ pub enum Token {
     LambdaArrow,
     /// Alternative operator: ?|
     Alternative,
+    /// Null-default operator: ??
+    NullDefault,
+    /// Embedder-defined operator (see `crate::parser::operators`)
+    Custom(String),
+    /// Parameter placeholder: ${name}
+    Placeholder(String),
+    /// Date literal: @date(2024-01-15), holding the text in parentheses
+    Date(String),
+    /// DateTime literal: @datetime(2024-01-15T10:30:00Z)
+    DateTime(String),
+    /// Duration literal: @duration(P1D)
+    Duration(String),
     /// Pipe union: ||
     // (Note: OrOr handles this dual-purpose token)
 
@@ -155,7 +182,9 @@ impl fmt::Display for Token {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Token::Integer(n) => write!(f, "{}", n),
-            Token::Float(x) => write!(f, "{}", x),
+            Token::Float(x) => write!(f, "{}", format_float(*x)),
+            Token::Percentage(x) => write!(f, "{}%", x),
+            Token::Quantity(x, unit) => write!(f, "{}{}", x, unit),
             Token::String(s) => write!(f, "'{}'", s),
             Token::True => write!(f, "true"),
             Token::False => write!(f, "false"),
@@ -183,6 +212,9 @@ impl fmt::Display for Token {
             Token::Else => write!(f, "else"),
             Token::Fn => write!(f, "fn"),
             Token::Guard => write!(f, "guard"),
+            Token::Require => write!(f, "require"),
+            Token::WarnIf => write!(f, "warn_if"),
+            Token::Like => write!(f, "like"),
             Token::Now => write!(f, "NOW"),
             Token::Today => write!(f, "TODAY"),
             Token::Tomorrow => write!(f, "TOMORROW"),
@@ -200,7 +232,13 @@ impl fmt::Display for Token {
             Token::BeginningOfTime => write!(f, "BOT"),
             Token::EndOfTime => write!(f, "EOT"),
             Token::Identifier(name) => write!(f, "{}", name),
+            Token::Placeholder(name) => write!(f, "${{{}}}", name),
+            Token::Date(text) => write!(f, "@date({})", text),
+            Token::DateTime(text) => write!(f, "@datetime({})", text),
+            Token::Duration(text) => write!(f, "@duration({})", text),
             Token::Dot => write!(f, "."),
+            Token::DotDot => write!(f, ".."),
+            Token::DotDotEqual => write!(f, "..="),
             Token::Comma => write!(f, ","),
             Token::LeftParen => write!(f, "("),
             Token::RightParen => write!(f, ")"),
@@ -214,11 +252,28 @@ impl fmt::Display for Token {
             Token::Pipe => write!(f, "|>"),
             Token::LambdaArrow => write!(f, "~>"),
             Token::Alternative => write!(f, "?|"),
+            Token::NullDefault => write!(f, "??"),
+            Token::Custom(symbol) => write!(f, "{}", symbol),
             Token::Eof => write!(f, "EOF"),
         }
     }
 }
 
+/// A token with its location in the source
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpannedToken {
+    /// The token
+    pub token: Token,
+    /// Line number of the first character (1-based)
+    pub line: usize,
+    /// Column number of the first character (1-based)
+    pub column: usize,
+    /// Byte offset of the first character
+    pub start: usize,
+    /// Byte offset just past the last character
+    pub end: usize,
+}
+
 /// Parse error with location information
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct LexError {
@@ -228,6 +283,8 @@ pub struct LexError {
     pub line: usize,
     /// Column number (1-based)
     pub column: usize,
+    /// Suggested fix, e.g. "did you mean '&&'?"
+    pub hint: Option<String>,
 }
 
 impl fmt::Display for LexError {
@@ -245,13 +302,16 @@ impl std::error::Error for LexError {}
 /// Lexer for ELO expressions
 #[derive(Debug)]
 pub struct Lexer<'a> {
-    #[allow(dead_code)]
     input: &'a str,
     position: usize,
     line: usize,
     column: usize,
     chars: std::str::Chars<'a>,
     current_char: Option<char>,
+    /// Custom operator symbols, longest first
+    custom_symbols: Vec<String>,
+    /// Accept temporal keywords in any case (`today`, `Now`)
+    case_insensitive_temporal: bool,
 }
 
 impl<'a> Lexer<'a> {
@@ -264,11 +324,49 @@ impl<'a> Lexer<'a> {
             column: 1,
             chars: input.chars(),
             current_char: None,
+            custom_symbols: Vec::new(),
+            case_insensitive_temporal: false,
         };
         lexer.current_char = lexer.chars.next();
         lexer
     }
 
+    /// Recognise custom operator symbols, which take priority over built-in
+    /// operators sharing a prefix
+    pub fn with_operators(mut self, mut symbols: Vec<String>) -> Self {
+        symbols.sort_by_key(|symbol| std::cmp::Reverse(symbol.len()));
+        self.custom_symbols = symbols;
+        self
+    }
+
+    /// Accept temporal keywords regardless of case
+    ///
+    /// With this enabled `today` and `Today` lex as `TODAY` instead of as
+    /// identifiers.
+    pub fn with_case_insensitive_temporal(mut self, enabled: bool) -> Self {
+        self.case_insensitive_temporal = enabled;
+        self
+    }
+
+    /// Match a custom operator symbol at the current position
+    fn read_custom_operator(&mut self) -> Option<Token> {
+        let current = self.current_char?;
+        let rest = self.chars.as_str();
+        let symbol = self
+            .custom_symbols
+            .iter()
+            .find(|symbol| {
+                symbol
+                    .strip_prefix(current)
+                    .is_some_and(|tail| rest.starts_with(tail))
+            })?
+            .clone();
+        for _ in symbol.chars() {
+            self.advance();
+        }
+        Some(Token::Custom(symbol))
+    }
+
     /// Advance to next character
     fn advance(&mut self) {
         if let Some('\n') = self.current_char {
@@ -281,6 +379,12 @@ impl<'a> Lexer<'a> {
         self.current_char = self.chars.next();
     }
 
+    /// Byte offset of the current character
+    fn offset(&self) -> usize {
+        let current = self.current_char.map_or(0, char::len_utf8);
+        self.input.len() - self.chars.as_str().len() - current
+    }
+
     /// Skip whitespace
     fn skip_whitespace(&mut self) {
         while let Some(ch) = self.current_char {
@@ -304,8 +408,8 @@ impl<'a> Lexer<'a> {
             if ch.is_ascii_digit() {
                 num_str.push(ch);
                 self.advance();
-            } else if ch == '.' && !is_float {
-                // Check if next char is a digit
+            } else if ch == '.' && !is_float && self.chars.clone().next() != Some('.') {
+                // A second '.' starts a range operator (1..10), not a fraction
                 is_float = true;
                 num_str.push(ch);
                 self.advance();
@@ -314,27 +418,81 @@ impl<'a> Lexer<'a> {
             }
         }
 
-        if is_float {
-            num_str
+        // A `%` directly attached to the number is a percentage unless an
+        // operand follows it, in which case it is the modulo operator:
+        // `15%` and `15% + x` are percentages, `15%4` and `15% x` are modulo.
+        if self.current_char == Some('%') && !self.operand_follows_percent() {
+            self.advance();
+            return num_str
                 .parse::<f64>()
-                .map(Token::Float)
+                .map(Token::Percentage)
                 .map_err(|_| LexError {
-                    message: format!("Invalid float: {}", num_str),
+                    message: format!("Invalid percentage: {}", num_str),
                     line: start_line,
                     column: start_col,
-                })
+                    hint: None,
+                });
+        }
+
+        // A unit suffix directly attached to the number: 50kg, 2.5km
+        if matches!(self.current_char, Some(ch) if ch.is_ascii_alphabetic()) {
+            let mut unit = String::new();
+            while let Some(ch) = self.current_char {
+                if ch.is_ascii_alphanumeric() || ch == '_' {
+                    unit.push(ch);
+                    self.advance();
+                } else {
+                    break;
+                }
+            }
+            return num_str
+                .parse::<f64>()
+                .map(|value| Token::Quantity(value, unit))
+                .map_err(|_| LexError {
+                    message: format!("Invalid quantity: {}", num_str),
+                    line: start_line,
+                    column: start_col,
+                    hint: None,
+                });
+        }
+
+        if is_float {
+            match num_str.parse::<f64>() {
+                Ok(value) if value.is_finite() => Ok(Token::Float(value)),
+                _ => Err(LexError {
+                    message: format!("Float literal {} is out of range", num_str),
+                    line: start_line,
+                    column: start_col,
+                    hint: Some(format!("floats must not exceed {:e}", f64::MAX)),
+                }),
+            }
         } else {
+            // The literal is all digits, so parsing can only fail on overflow
             num_str
                 .parse::<i64>()
                 .map(Token::Integer)
                 .map_err(|_| LexError {
-                    message: format!("Invalid integer: {}", num_str),
+                    message: format!("Integer literal {} is out of range", num_str),
                     line: start_line,
                     column: start_col,
+                    hint: Some(format!(
+                        "integers must not exceed {}; write {}.0 to use a float",
+                        i64::MAX,
+                        num_str
+                    )),
                 })
         }
     }
 
+    /// Check whether the character after the current `%` starts an operand
+    fn operand_follows_percent(&self) -> bool {
+        let next = self.chars.clone().find(|c| !c.is_whitespace());
+        matches!(
+            next,
+            Some(c) if c.is_alphanumeric() || matches!(c, '_' | '(' | '\'' | '`' | '[' | '{' | '@' | '$')
+        )
+    }
+
     /// Read a string literal (single-quoted)
     fn read_string(&mut self) -> Result<Token, LexError> {
         let start_line = self.line;
@@ -378,6 +536,7 @@ impl<'a> Lexer<'a> {
                                 message: "Invalid escape sequence".to_string(),
                                 line: self.line,
                                 column: self.column,
+                                hint: None,
                             });
                         }
                     }
@@ -393,9 +552,149 @@ impl<'a> Lexer<'a> {
             message: "Unterminated string literal".to_string(),
             line: start_line,
             column: start_col,
+            hint: Some("close the string with a matching single quote".to_string()),
         })
     }
 
+    /// Read a backtick-escaped identifier such as `` `in` ``
+    ///
+    /// The contents are always an identifier, even when they spell a
+    /// keyword, so fields named `in`, `then` or `guard` stay expressible.
+    fn read_escaped_identifier(&mut self) -> Result<Token, LexError> {
+        let start_line = self.line;
+        let start_col = self.column;
+        let mut ident = String::new();
+
+        // Skip opening backtick
+        self.advance();
+
+        while let Some(ch) = self.current_char {
+            if ch == '`' {
+                self.advance();
+                if ident.is_empty() {
+                    return Err(LexError {
+                        message: "Empty escaped identifier".to_string(),
+                        line: start_line,
+                        column: start_col,
+                        hint: Some("write a field name between the backticks".to_string()),
+                    });
+                }
+                return Ok(Token::Identifier(ident));
+            }
+            if !(ch.is_alphanumeric() || ch == '_') {
+                return Err(LexError {
+                    message: format!("Unexpected '{}' in escaped identifier", ch),
+                    line: self.line,
+                    column: self.column,
+                    hint: Some(
+                        "escaped identifiers may contain only letters, digits and '_'".to_string(),
+                    ),
+                });
+            }
+            ident.push(ch);
+            self.advance();
+        }
+
+        Err(LexError {
+            message: "Unterminated escaped identifier".to_string(),
+            line: start_line,
+            column: start_col,
+            hint: Some("close the identifier with a matching backtick".to_string()),
+        })
+    }
+
+    /// Read a temporal literal such as `@date(2024-01-15)`
+    ///
+    /// The text in parentheses is kept as written; the parser checks that
+    /// it is valid ISO8601.
+    fn read_temporal_literal(&mut self) -> Result<Token, LexError> {
+        let start_line = self.line;
+        let start_col = self.column;
+        let error = |message: String| LexError {
+            message,
+            line: start_line,
+            column: start_col,
+            hint: Some(
+                "write temporal literals as @date(2024-01-15), \
+                 @datetime(2024-01-15T10:30:00Z) or @duration(P1D)"
+                    .to_string(),
+            ),
+        };
+
+        // Skip '@'
+        self.advance();
+        let mut kind = String::new();
+        while let Some(ch) = self.current_char.filter(|ch| ch.is_ascii_alphabetic()) {
+            kind.push(ch);
+            self.advance();
+        }
+        let token: fn(String) -> Token = match kind.as_str() {
+            "date" => Token::Date,
+            "datetime" => Token::DateTime,
+            "duration" => Token::Duration,
+            _ => return Err(error(format!("Unknown temporal literal '@{}'", kind))),
+        };
+        if self.current_char != Some('(') {
+            return Err(error(format!("Expected '(' after '@{}'", kind)));
+        }
+        self.advance();
+
+        let mut text = String::new();
+        while let Some(ch) = self.current_char {
+            self.advance();
+            if ch == ')' {
+                return Ok(token(text.trim().to_string()));
+            }
+            if ch == '\n' {
+                break;
+            }
+            text.push(ch);
+        }
+        Err(error(format!("Unterminated '@{}(' literal", kind)))
+    }
+
+    /// Read a parameter placeholder such as `${max_amount}`
+    fn read_placeholder(&mut self) -> Result<Token, LexError> {
+        let start_line = self.line;
+        let start_col = self.column;
+
+        // Skip '$'
+        self.advance();
+        if self.current_char != Some('{') {
+            return Err(LexError {
+                message: "Unexpected '$'".to_string(),
+                line: start_line,
+                column: start_col,
+                hint: Some("parameters are written as ${name}".to_string()),
+            });
+        }
+        self.advance();
+
+        let mut name = String::new();
+        while let Some(ch) = self.current_char {
+            if ch.is_alphanumeric() || ch == '_' {
+                name.push(ch);
+                self.advance();
+            } else {
+                break;
+            }
+        }
+
+        if self.current_char != Some('}') || name.is_empty() {
+            return Err(LexError {
+                message: "Malformed parameter placeholder".to_string(),
+                line: start_line,
+                column: start_col,
+                hint: Some(
+                    "parameter names may contain only letters, digits and '_', as in ${max_amount}"
+                        .to_string(),
+                ),
+            });
+        }
+        self.advance();
+        Ok(Token::Placeholder(name))
+    }
+
     /// Read an identifier or keyword
     fn read_identifier(&mut self) -> Token {
         let mut ident = String::new();
@@ -417,26 +716,20 @@ impl<'a> Lexer<'a> {
             "else" => Token::Else,
             "fn" => Token::Fn,
             "guard" => Token::Guard,
+            "require" => Token::Require,
+            "warn_if" => Token::WarnIf,
+            "like" => Token::Like,
             "true" => Token::True,
             "false" => Token::False,
             "null" => Token::Null,
-            "NOW" => Token::Now,
-            "TODAY" => Token::Today,
-            "TOMORROW" => Token::Tomorrow,
-            "YESTERDAY" => Token::Yesterday,
-            "SOD" => Token::StartOfDay,
-            "EOD" => Token::EndOfDay,
-            "SOW" => Token::StartOfWeek,
-            "EOW" => Token::EndOfWeek,
-            "SOM" => Token::StartOfMonth,
-            "EOM" => Token::EndOfMonth,
-            "SOQ" => Token::StartOfQuarter,
-            "EOQ" => Token::EndOfQuarter,
-            "SOY" => Token::StartOfYear,
-            "EOY" => Token::EndOfYear,
-            "BOT" => Token::BeginningOfTime,
-            "EOT" => Token::EndOfTime,
-            _ => Token::Identifier(ident),
+            _ => {
+                let keyword = if self.case_insensitive_temporal {
+                    temporal_keyword(&ident.to_ascii_uppercase())
+                } else {
+                    temporal_keyword(&ident)
+                };
+                keyword.unwrap_or(Token::Identifier(ident))
+            }
         }
     }
 
@@ -444,6 +737,10 @@ impl<'a> Lexer<'a> {
     pub fn next_token(&mut self) -> Result<Token, LexError> {
         self.skip_whitespace();
 
+        if let Some(token) = self.read_custom_operator() {
+            return Ok(token);
+        }
+
         match self.current_char {
             None => Ok(Token::Eof),
             Some(ch) => {
@@ -520,9 +817,10 @@ impl<'a> Lexer<'a> {
                             Ok(Token::AndAnd)
                         } else {
                             Err(LexError {
-                                message: "Unexpected '&', did you mean '&&'?".to_string(),
+                                message: "Unexpected '&'".to_string(),
                                 line: self.line,
                                 column: self.column - 1,
+                                hint: Some("did you mean '&&'?".to_string()),
                             })
                         }
                     }
@@ -536,9 +834,10 @@ impl<'a> Lexer<'a> {
                             Ok(Token::Pipe)
                         } else {
                             Err(LexError {
-                                message: "Unexpected '|', did you mean '||' or '|>'?".to_string(),
+                                message: "Unexpected '|'".to_string(),
                                 line: self.line,
                                 column: self.column - 1,
+                                hint: Some("did you mean '||' or '|>'?".to_string()),
                             })
                         }
                     }
@@ -547,11 +846,15 @@ impl<'a> Lexer<'a> {
                         if self.current_char == Some('|') {
                             self.advance();
                             Ok(Token::Alternative)
+                        } else if self.current_char == Some('?') {
+                            self.advance();
+                            Ok(Token::NullDefault)
                         } else {
                             Err(LexError {
-                                message: "Unexpected '?', did you mean '?|'?".to_string(),
+                                message: "Unexpected '?'".to_string(),
                                 line: self.line,
                                 column: self.column - 1,
+                                hint: Some("did you mean '?|' or '??'?".to_string()),
                             })
                         }
                     }
@@ -562,15 +865,26 @@ impl<'a> Lexer<'a> {
                             Ok(Token::LambdaArrow)
                         } else {
                             Err(LexError {
-                                message: "Unexpected '~', did you mean '~>'?".to_string(),
+                                message: "Unexpected '~'".to_string(),
                                 line: self.line,
                                 column: self.column - 1,
+                                hint: Some("did you mean '~>'?".to_string()),
                             })
                         }
                     }
                     '.' => {
                         self.advance();
-                        Ok(Token::Dot)
+                        if self.current_char == Some('.') {
+                            self.advance();
+                            if self.current_char == Some('=') {
+                                self.advance();
+                                Ok(Token::DotDotEqual)
+                            } else {
+                                Ok(Token::DotDot)
+                            }
+                        } else {
+                            Ok(Token::Dot)
+                        }
                     }
                     ',' => {
                         self.advance();
@@ -609,12 +923,16 @@ impl<'a> Lexer<'a> {
                         Ok(Token::Semicolon)
                     }
                     '\'' => self.read_string(),
+                    '`' => self.read_escaped_identifier(),
+                    '$' => self.read_placeholder(),
+                    '@' => self.read_temporal_literal(),
                     _ if ch.is_ascii_digit() => self.read_number(),
                     _ if ch.is_alphabetic() => Ok(self.read_identifier()),
                     _ => Err(LexError {
                         message: format!("Unexpected character: '{}'", ch),
                         line: self.line,
                         column: self.column,
+                        hint: None,
                     }),
                 }
             }
@@ -623,12 +941,29 @@ impl<'a> Lexer<'a> {
 
     /// Tokenize entire input into a vector of tokens
     pub fn tokenize(&mut self) -> Result<Vec<Token>, LexError> {
+        Ok(self
+            .tokenize_spanned()?
+            .into_iter()
+            .map(|spanned| spanned.token)
+            .collect())
+    }
+
+    /// Tokenize entire input, recording where each token starts
+    pub fn tokenize_spanned(&mut self) -> Result<Vec<SpannedToken>, LexError> {
         let mut tokens = Vec::new();
 
         loop {
+            self.skip_whitespace();
+            let (line, column, start) = (self.line, self.column, self.offset());
             let token = self.next_token()?;
             let is_eof = token == Token::Eof;
-            tokens.push(token);
+            tokens.push(SpannedToken {
+                token,
+                line,
+                column,
+                start,
+                end: self.offset(),
+            });
             if is_eof {
                 break;
             }
@@ -638,6 +973,30 @@ impl<'a> Lexer<'a> {
     }
 }
 
+/// Temporal keyword token for an uppercase keyword spelling
+pub(crate) fn temporal_keyword(word: &str) -> Option<Token> {
+    let token = match word {
+        "NOW" => Token::Now,
+        "TODAY" => Token::Today,
+        "TOMORROW" => Token::Tomorrow,
+        "YESTERDAY" => Token::Yesterday,
+        "SOD" => Token::StartOfDay,
+        "EOD" => Token::EndOfDay,
+        "SOW" => Token::StartOfWeek,
+        "EOW" => Token::EndOfWeek,
+        "SOM" => Token::StartOfMonth,
+        "EOM" => Token::EndOfMonth,
+        "SOQ" => Token::StartOfQuarter,
+        "EOQ" => Token::EndOfQuarter,
+        "SOY" => Token::StartOfYear,
+        "EOY" => Token::EndOfYear,
+        "BOT" => Token::BeginningOfTime,
+        "EOT" => Token::EndOfTime,
+        _ => return None,
+    };
+    Some(token)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -684,6 +1043,86 @@ mod tests {
         assert_eq!(token, Token::Let);
     }
 
+    #[test]
+    fn test_keyword_require_and_warn_if() {
+        let mut lexer = Lexer::new("require warn_if warn like");
+        let tokens = lexer.tokenize().unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Require,
+                Token::WarnIf,
+                Token::Identifier("warn".to_string()),
+                Token::Like,
+                Token::Eof
+            ]
+        );
+    }
+
+    #[test]
+    fn test_null_default_operator() {
+        let mut lexer = Lexer::new("ctx.limit ?? 1000");
+        let tokens = lexer.tokenize().unwrap();
+        assert_eq!(tokens[3], Token::NullDefault);
+    }
+
+    #[test]
+    fn test_range_operators() {
+        let mut lexer = Lexer::new("1..10 1..=10 1.5..2.5");
+        let tokens = lexer.tokenize().unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Integer(1),
+                Token::DotDot,
+                Token::Integer(10),
+                Token::Integer(1),
+                Token::DotDotEqual,
+                Token::Integer(10),
+                Token::Float(1.5),
+                Token::DotDot,
+                Token::Float(2.5),
+                Token::Eof
+            ]
+        );
+    }
+
+    #[test]
+    fn test_percentage_literal() {
+        let mut lexer = Lexer::new("discount <= 15%");
+        let tokens = lexer.tokenize().unwrap();
+        assert_eq!(tokens[2], Token::Percentage(15.0));
+
+        let mut lexer = Lexer::new("(12.5%) && x");
+        let tokens = lexer.tokenize().unwrap();
+        assert_eq!(tokens[1], Token::Percentage(12.5));
+    }
+
+    #[test]
+    fn test_percent_followed_by_operand_is_modulo() {
+        for input in ["10%3", "10 % 3", "10% 3", "10%x", "10%(x)"] {
+            let mut lexer = Lexer::new(input);
+            let tokens = lexer.tokenize().unwrap();
+            assert_eq!(tokens[1], Token::Percent, "input: {}", input);
+        }
+    }
+
+    #[test]
+    fn test_quantity_literal() {
+        let mut lexer = Lexer::new("weight < 50kg && height >= 1.5m");
+        let tokens = lexer.tokenize().unwrap();
+        assert_eq!(tokens[2], Token::Quantity(50.0, "kg".to_string()));
+        assert_eq!(tokens[6], Token::Quantity(1.5, "m".to_string()));
+    }
+
+    #[test]
+    fn test_number_followed_by_space_is_not_quantity() {
+        let mut lexer = Lexer::new("50 kg");
+        let tokens = lexer.tokenize().unwrap();
+        assert_eq!(tokens[0], Token::Integer(50));
+        assert_eq!(tokens[1], Token::Identifier("kg".to_string()));
+    }
+
     #[test]
     fn test_keyword_if() {
         let mut lexer = Lexer::new("if");
@@ -789,6 +1228,94 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_tokenize_spanned_positions() {
+        let tokens = Lexer::new("age >= 18\n  && ok").tokenize_spanned().unwrap();
+        let positions: Vec<(usize, usize)> = tokens.iter().map(|t| (t.line, t.column)).collect();
+        assert_eq!(
+            positions,
+            vec![(1, 1), (1, 5), (1, 8), (2, 3), (2, 6), (2, 8)]
+        );
+        assert_eq!(tokens[3].token, Token::AndAnd);
+    }
+
+    #[test]
+    fn test_tokenize_spanned_offsets() {
+        let source = "name == 'Zoë' && ok";
+        let tokens = Lexer::new(source).tokenize_spanned().unwrap();
+        let texts: Vec<&str> = tokens.iter().map(|t| &source[t.start..t.end]).collect();
+        assert_eq!(texts, vec!["name", "==", "'Zoë'", "&&", "ok", ""]);
+        assert_eq!((tokens[3].start, tokens[3].column), (15, 15));
+        assert_eq!(tokens[4].column, 18);
+    }
+
+    #[test]
+    fn test_integer_overflow() {
+        assert_eq!(
+            Lexer::new("9223372036854775807").tokenize().unwrap()[0],
+            Token::Integer(i64::MAX)
+        );
+
+        let err = Lexer::new("x > 9223372036854775808")
+            .tokenize()
+            .unwrap_err();
+        assert_eq!(
+            err.message,
+            "Integer literal 9223372036854775808 is out of range"
+        );
+        assert_eq!((err.line, err.column), (1, 5));
+        assert_eq!(
+            err.hint.as_deref(),
+            Some("integers must not exceed 9223372036854775807; write 9223372036854775808.0 to use a float")
+        );
+        assert!(Lexer::new("9223372036854775808.0").tokenize().is_ok());
+    }
+
+    #[test]
+    fn test_float_overflow() {
+        let literal = format!("{}.0", "9".repeat(400));
+        let err = Lexer::new(&literal).tokenize().unwrap_err();
+        assert!(err.message.ends_with("is out of range"));
+    }
+
+    #[test]
+    fn test_escaped_identifiers() {
+        let mut lexer = Lexer::new("`in` `then`.`guard` `TODAY`");
+        assert_eq!(
+            lexer.tokenize().unwrap(),
+            vec![
+                Token::Identifier("in".to_string()),
+                Token::Identifier("then".to_string()),
+                Token::Dot,
+                Token::Identifier("guard".to_string()),
+                Token::Identifier("TODAY".to_string()),
+                Token::Eof
+            ]
+        );
+
+        assert!(Lexer::new("``").tokenize().is_err());
+        assert!(Lexer::new("`in").tokenize().is_err());
+        assert!(Lexer::new("`first name`").tokenize().is_err());
+    }
+
+    #[test]
+    fn test_case_insensitive_temporal_keywords() {
+        let tokens = Lexer::new("today").tokenize().unwrap();
+        assert_eq!(tokens[0], Token::Identifier("today".to_string()));
+
+        let mut lexer = Lexer::new("today Now eom todays").with_case_insensitive_temporal(true);
+        assert_eq!(
+            lexer.tokenize().unwrap(),
+            vec![
+                Token::Today,
+                Token::Now,
+                Token::EndOfMonth,
+                Token::Identifier("todays".to_string()),
+                Token::Eof
+            ]
+        );
+    }
+
     #[test]
     fn test_negative_number_lexing() {
         // Negative numbers are represented as minus token followed by number
@@ -867,6 +1394,27 @@ mod tests {
         assert_eq!(token, Token::Arrow);
     }
 
+    #[test]
+    fn test_temporal_literals() {
+        let tokens =
+            Lexer::new("@date(2024-01-15) < @datetime( 2024-01-15T10:30:00Z ) + @duration(P1D)")
+                .tokenize()
+                .unwrap();
+        assert_eq!(tokens[0], Token::Date("2024-01-15".to_string()));
+        assert_eq!(
+            tokens[2],
+            Token::DateTime("2024-01-15T10:30:00Z".to_string())
+        );
+        assert_eq!(tokens[4], Token::Duration("P1D".to_string()));
+        assert_eq!(tokens[0].to_string(), "@date(2024-01-15)");
+
+        let error = Lexer::new("@time(10:00)").tokenize().unwrap_err();
+        assert_eq!(error.message, "Unknown temporal literal '@time'");
+        let error = Lexer::new("x > @date(2024-01-15").tokenize().unwrap_err();
+        assert_eq!(error.message, "Unterminated '@date(' literal");
+        assert_eq!(error.column, 5);
+    }
+
     #[test]
     fn test_null_literal() {
         let mut lexer = Lexer::new("null");
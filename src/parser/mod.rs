@@ -5,14 +5,50 @@
 //!
 //! The parser implements correct operator precedence through a precedence cascade:
 //! pipe > logical_or > logical_and > equality > comparison > addition > multiplication > power > unary > postfix > primary
+//!
+//! The null-default operator `??` sits between comparison and range, so
+//! `amount <= ctx.limit ?? 1000` compares against the defaulted limit. It is
+//! right-associative: `a ?? b ?? c` is `a ?? (b ?? c)`.
+//!
+//! The alternative operator `?|` binds looser than `||` and tighter than the
+//! pipe, so `age >= 18 ?| has_consent && guardian_approved` falls back to the
+//! whole conjunction. It is right-associative as well.
+//!
+//! Percentage literals (`15%`) are lexed as a single primary token and parsed as
+//! [`Expr::Percentage`], standing for the float `0.15`, so they bind tighter than
+//! every operator: `price * 15% + 1` is `(price * 0.15) + 1`. A `%` followed by an operand is always the modulo operator.
+//!
+//! Embedders can add infix operators at any of the binary precedence levels
+//! with an [`operators::OperatorTable`] and [`Parser::parse_with_operators`].
+//!
+//! Temporal keywords are uppercase (`TODAY`, `NOW`); a [`ParserConfig`] can
+//! make them case-insensitive for authors who write `today`.
+//!
+//! Syntax added after the original grammar is tied to an [`Edition`]; a
+//! [`ParserConfig`] for an older edition rejects it.
+//!
+//! A trailing `message 'text'` clause attaches a custom error message to a
+//! condition: `age >= 18 message 'Must be an adult'` parses as
+//! `require age >= 18 else 'Must be an adult'`.
+//!
+//! [`Parser::parse_spanned`] also returns the source span of every node, as a
+//! [`SpanTree`] alongside the expression.
 
+pub mod config;
+pub mod edition;
 pub mod error;
 pub mod lexer;
+pub mod operators;
 
+pub use config::{DuplicateKeys, ParserConfig};
+pub use edition::{Edition, SyntaxFeature};
 pub use error::ParseError;
-pub use lexer::{LexError, Lexer, Token};
+pub use lexer::{LexError, Lexer, SpannedToken, Token};
 
-use crate::ast::{BinaryOperator, Expr, Literal, TemporalKeyword, UnaryOperator};
+use crate::ast::{
+    BinaryOperator, Expr, Literal, Span, SpanTree, Spanned, TemporalKeyword, UnaryOperator,
+};
+use operators::{Associativity, CustomOperator, OperatorTable, Precedence};
 
 /// Parser for ELO expressions
 ///
@@ -20,13 +56,86 @@ use crate::ast::{BinaryOperator, Expr, Literal, TemporalKeyword, UnaryOperator};
 #[derive(Debug)]
 pub struct Parser {
     tokens: Vec<Token>,
+    /// Source location of each token
+    spans: Vec<Span>,
     current: usize,
+    /// Spans of the nodes parsed so far whose parent is not yet built, in
+    /// the order they were finished
+    nodes: Vec<SpanTree>,
+    /// Whether `in` may be parsed as the membership operator; disabled while
+    /// parsing the part of `let`/`guard` that is terminated by `in`
+    allow_in: bool,
+    /// Embedder-defined infix operators
+    operators: OperatorTable,
+    /// Grammar edition
+    edition: Edition,
+    /// Handling of repeated object literal keys
+    duplicate_keys: DuplicateKeys,
+    /// Operators whose compiled meaning changed after 0.x, in parse order
+    legacy: Vec<LegacyOperator>,
+    /// Current nesting of sub-expressions, bounded by [`MAX_NESTING`]
+    depth: usize,
+}
+
+/// An operator that generated code translated incorrectly before 0.x fixes
+///
+/// Positions are token indices, as recorded by
+/// [`Parser::legacy_operators`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LegacyOperator {
+    /// `^` at token `op`, with its operands spanning tokens `start..end`
+    Pow { start: usize, op: usize, end: usize },
+    /// Unary `+` at token `op`
+    UnaryPlus { op: usize },
+}
+
+/// Maximum nesting of sub-expressions (parentheses, unary operators,
+/// right-associative operators, ...), keeping the recursive descent within
+/// the stack of a spawned thread
+pub const MAX_NESTING: usize = 32;
+
+/// Hint for `=` used where a comparison was meant
+const EQUAL_HINT: &str = "use '==' to compare values";
+
+/// Convert a lexer error, keeping its hint and pointing at the source
+fn lex_error(err: LexError, input: &str) -> ParseError {
+    let error = ParseError::new(err.message, err.line, err.column).with_source(input);
+    match err.hint {
+        Some(hint) => error.with_hint(hint),
+        None => error,
+    }
 }
 
+/// Parsing function for one precedence level
+type ParseFn = fn(&mut Parser) -> Result<Expr, ParseError>;
+
 impl Parser {
-    /// Create a new parser from a token stream
-    fn new(tokens: Vec<Token>) -> Self {
-        Parser { tokens, current: 0 }
+    /// Create a new parser from a token stream with positions
+    fn new(tokens: Vec<SpannedToken>) -> Self {
+        let (tokens, spans) = tokens
+            .into_iter()
+            .map(|spanned| {
+                let span = Span {
+                    start: spanned.start,
+                    end: spanned.end,
+                    line: spanned.line,
+                    column: spanned.column,
+                };
+                (spanned.token, span)
+            })
+            .unzip();
+        Parser {
+            tokens,
+            spans,
+            current: 0,
+            nodes: Vec::new(),
+            allow_in: true,
+            operators: OperatorTable::new(),
+            edition: Edition::LATEST,
+            duplicate_keys: DuplicateKeys::Error,
+            legacy: Vec::new(),
+            depth: 0,
+        }
     }
 
     /// Parse a complete ELO expression from a string
@@ -39,15 +148,128 @@ impl Parser {
     pub fn parse(input: &str) -> Result<Expr, ParseError> {
         let mut lexer = Lexer::new(input);
         let tokens = lexer
-            .tokenize()
-            .map_err(|err| ParseError::new(err.message, err.line, err.column))?;
+            .tokenize_spanned()
+            .map_err(|err| lex_error(err, input))?;
         let mut parser = Parser::new(tokens);
-        parser.parse_expression()
+        parser
+            .parse_complete()
+            .map_err(|err| err.with_source(input))
     }
 
-    /// Parse an expression
+    /// Parse `input`, returning its tokens and the legacy operators it uses
+    pub(crate) fn legacy_operators(
+        input: &str,
+    ) -> Result<(Vec<SpannedToken>, Vec<LegacyOperator>), ParseError> {
+        let tokens = Lexer::new(input)
+            .tokenize_spanned()
+            .map_err(|err| lex_error(err, input))?;
+        let mut parser = Parser::new(tokens.clone());
+        parser
+            .parse_complete()
+            .map_err(|err| err.with_source(input))?;
+        Ok((tokens, parser.legacy))
+    }
+
+    /// Parse an ELO expression that may use custom operators
+    ///
+    /// Each application of a custom operator becomes a call of its function,
+    /// so `a ~= b` with `~=` standing for `fuzzy_match` parses as
+    /// `fuzzy_match(a, b)`.
+    pub fn parse_with_operators(
+        input: &str,
+        operators: &OperatorTable,
+    ) -> Result<Expr, ParseError> {
+        Self::parse_with_config(
+            input,
+            &ParserConfig::new().with_operators(operators.clone()),
+        )
+    }
+
+    /// Parse an ELO expression with the given configuration
+    pub fn parse_with_config(input: &str, config: &ParserConfig) -> Result<Expr, ParseError> {
+        Self::parse_spanned_with_config(input, config).map(|spanned| spanned.expr)
+    }
+
+    /// Parse an ELO expression, keeping the source span of every node
+    ///
+    /// See [`Spanned`] for how to look the spans up.
+    pub fn parse_spanned(input: &str) -> Result<Spanned, ParseError> {
+        Self::parse_spanned_with_config(input, &ParserConfig::new())
+    }
+
+    /// Parse an ELO expression with the given configuration, keeping the
+    /// source span of every node
+    pub fn parse_spanned_with_config(
+        input: &str,
+        config: &ParserConfig,
+    ) -> Result<Spanned, ParseError> {
+        let mut lexer = Lexer::new(input)
+            .with_operators(config.operators.symbols())
+            .with_case_insensitive_temporal(config.case_insensitive_temporal);
+        let tokens = lexer
+            .tokenize_spanned()
+            .map_err(|err| lex_error(err, input))?;
+        let mut parser = Parser::new(tokens);
+        parser.operators = config.operators.clone();
+        parser.edition = config.edition;
+        parser.duplicate_keys = config.duplicate_keys;
+        let expr = parser
+            .parse_complete()
+            .map_err(|err| err.with_source(input))?;
+        let spans = parser.nodes.pop().expect("a parsed expression has a span");
+        Ok(Spanned { expr, spans })
+    }
+
+    /// Parse an expression that must span the whole input
+    fn parse_complete(&mut self) -> Result<Expr, ParseError> {
+        let expr = self.parse_expression()?;
+        match self.peek() {
+            Token::Eof => Ok(expr),
+            token => {
+                let error = self.error(format!("Unexpected {} after end of expression", token));
+                Err(match token {
+                    Token::Equal => error.with_hint(EQUAL_HINT),
+                    _ => {
+                        error.with_hint("combine conditions with an operator such as '&&' or '||'")
+                    }
+                })
+            }
+        }
+    }
+
+    /// Parse an expression, with an optional trailing message clause
     fn parse_expression(&mut self) -> Result<Expr, ParseError> {
-        self.parse_pipe()
+        let start = self.current;
+        let expr = self.parse_pipe()?;
+        self.parse_message_clause(start, expr)
+    }
+
+    /// Parse a `condition message 'text'` clause after `condition`
+    ///
+    /// The clause is sugar for `require condition else 'text'`. `message` is
+    /// only a keyword after a complete expression, where no identifier could
+    /// otherwise follow, so fields named `message` still parse.
+    fn parse_message_clause(&mut self, start: usize, condition: Expr) -> Result<Expr, ParseError> {
+        match self.peek() {
+            Token::Identifier(kw) if kw == "message" => {}
+            _ => return Ok(condition),
+        }
+        self.require_feature(SyntaxFeature::Messages)?;
+        self.advance();
+        let message = self.parse_message("message")?;
+        let expr = Expr::Require {
+            condition: Box::new(condition),
+            message,
+        };
+        Ok(self.node(start, expr))
+    }
+
+    /// Parse an expression that is terminated by the `in` keyword
+    fn parse_expression_before_in(&mut self) -> Result<Expr, ParseError> {
+        let allow_in = std::mem::replace(&mut self.allow_in, false);
+        let result = self.parse_expression();
+        self.allow_in = allow_in;
+        result
     }
 
     /// Peek at the current token
@@ -55,6 +277,11 @@ impl Parser {
         self.tokens.get(self.current).unwrap_or(&Token::Eof)
     }
 
+    /// Peek at the token after the current one
+    fn peek_next(&self) -> &Token {
+        self.tokens.get(self.current + 1).unwrap_or(&Token::Eof)
+    }
+
     /// Advance to the next token
     fn advance(&mut self) -> Token {
         let token = self.peek().clone();
@@ -69,48 +296,202 @@ impl Parser {
         std::mem::discriminant(self.peek()) == std::mem::discriminant(token)
     }
 
+    /// The custom operator at the current token, if it binds at `level`
+    fn custom_operator(&self, level: Precedence) -> Option<CustomOperator> {
+        match self.peek() {
+            Token::Custom(symbol) => self
+                .operators
+                .get(symbol)
+                .filter(|op| op.precedence() == level)
+                .cloned(),
+            _ => None,
+        }
+    }
+
+    /// Parse the right operand of a custom operator and build its call
+    ///
+    /// `operand` parses the next tighter level; `same` re-enters the current
+    /// level for right-associative operators.
+    fn parse_custom(
+        &mut self,
+        op: CustomOperator,
+        start: usize,
+        left: Expr,
+        operand: ParseFn,
+        same: ParseFn,
+    ) -> Result<Expr, ParseError> {
+        self.advance();
+        let right = match op.associativity() {
+            Associativity::Left => operand(self)?,
+            Associativity::Right => self.nested(same)?,
+        };
+        let call = Expr::FunctionCall {
+            name: op.function().to_string(),
+            args: vec![left, right],
+        };
+        Ok(self.node(start, call))
+    }
+
+    /// Run `parse` one nesting level deeper, failing past [`MAX_NESTING`]
+    fn nested(&mut self, parse: ParseFn) -> Result<Expr, ParseError> {
+        if self.depth >= MAX_NESTING {
+            return Err(self.error(format!(
+                "Expression nested too deeply (max {} levels)",
+                MAX_NESTING
+            )));
+        }
+        self.depth += 1;
+        let expr = parse(self);
+        self.depth -= 1;
+        expr
+    }
+
+    /// Error located at the current token
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        self.error_at(self.current, message)
+    }
+
+    /// Error located at the token just consumed
+    fn error_previous(&self, message: impl Into<String>) -> ParseError {
+        self.error_at(self.current.saturating_sub(1), message)
+    }
+
+    fn error_at(&self, index: usize, message: impl Into<String>) -> ParseError {
+        let (line, column) = self
+            .spans
+            .get(index)
+            .or(self.spans.last())
+            .map_or((1, 1), |span| (span.line, span.column));
+        ParseError::new(message, line, column)
+    }
+
+    /// Record the span of `expr`, which was parsed from tokens
+    /// `start..current`
+    ///
+    /// Every node the parser builds passes through here after its children,
+    /// so the spans of those children are the last entries of `nodes`.
+    fn node(&mut self, start: usize, expr: Expr) -> Expr {
+        let children = self
+            .nodes
+            .split_off(self.nodes.len() - expr.children().len());
+        let first = self.spans[start];
+        let last = self.spans[self.current.max(start + 1) - 1];
+        self.nodes.push(SpanTree {
+            span: first.to(last),
+            children,
+        });
+        expr
+    }
+
     /// Consume a specific token or return an error
     fn expect(&mut self, expected: Token) -> Result<(), ParseError> {
         if self.check(&expected) {
             self.advance();
             Ok(())
         } else {
-            Err(ParseError::new(
-                format!("Expected {}, found {}", expected, self.peek()),
-                1,
-                1,
-            ))
+            let error = self.error(format!("Expected {}, found {}", expected, self.peek()));
+            Err(match self.peek() {
+                Token::Equal => error.with_hint(EQUAL_HINT),
+                _ => error,
+            })
         }
     }
 
+    /// Consume a specific token, suggesting the construct's syntax on error
+    fn expect_in(&mut self, expected: Token, syntax: &str) -> Result<(), ParseError> {
+        self.expect(expected).map_err(|error| match error.hint {
+            Some(_) => error,
+            None => error.with_hint(format!("write this as '{}'", syntax)),
+        })
+    }
+
+    /// Reject `feature` at the current token if the edition lacks it
+    fn require_feature(&self, feature: SyntaxFeature) -> Result<(), ParseError> {
+        if self.edition.supports(feature) {
+            return Ok(());
+        }
+        Err(self
+            .error(format!(
+                "edition {} does not support {} (added in edition {})",
+                self.edition,
+                feature.description(),
+                feature.edition()
+            ))
+            .with_hint(format!(
+                "add 'edition = \"{}\"' before the first rule",
+                feature.edition()
+            )))
+    }
+
     /// Parse pipe operator expressions: expr |> func() |> ...
     fn parse_pipe(&mut self) -> Result<Expr, ParseError> {
-        let mut expr = self.parse_logical_or()?;
+        let start = self.current;
+        let mut expr = self.parse_alternative()?;
 
         while self.check(&Token::Pipe) {
             self.advance();
-            let func = self.parse_logical_or()?;
-            expr = Expr::Pipe {
+            let stage = self.current;
+            let func = self.parse_alternative()?;
+            // Only stages that take the piped value are allowed
+            if let Err(message) = Expr::desugar_pipe(&Expr::Null, std::slice::from_ref(&func)) {
+                return Err(self.error_at(stage, message));
+            }
+            let pipe = Expr::Pipe {
                 value: Box::new(expr),
                 functions: vec![func],
             };
+            expr = self.node(start, pipe);
         }
 
         Ok(expr)
     }
 
+    /// Parse alternative expressions: primary ?| alternative
+    fn parse_alternative(&mut self) -> Result<Expr, ParseError> {
+        let start = self.current;
+        let primary = self.parse_logical_or()?;
+
+        if self.check(&Token::Alternative) {
+            self.require_feature(SyntaxFeature::Alternative)?;
+            self.advance();
+            let alternative = self.parse_alternative()?; // Right-associative
+            let expr = Expr::Alternative {
+                primary: Box::new(primary),
+                alternative: Box::new(alternative),
+            };
+            return Ok(self.node(start, expr));
+        }
+
+        Ok(primary)
+    }
+
     /// Parse logical OR: left || right
     fn parse_logical_or(&mut self) -> Result<Expr, ParseError> {
+        let start = self.current;
         let mut expr = self.parse_logical_and()?;
 
-        while self.check(&Token::OrOr) {
+        loop {
+            if let Some(op) = self.custom_operator(Precedence::LogicalOr) {
+                expr = self.parse_custom(
+                    op,
+                    start,
+                    expr,
+                    Self::parse_logical_and,
+                    Self::parse_logical_or,
+                )?;
+                continue;
+            }
+            if !self.check(&Token::OrOr) {
+                break;
+            }
             self.advance();
             let right = self.parse_logical_and()?;
-            expr = Expr::BinaryOp {
+            let binary = Expr::BinaryOp {
                 op: BinaryOperator::Or,
                 left: Box::new(expr),
                 right: Box::new(right),
             };
+            expr = self.node(start, binary);
         }
 
         Ok(expr)
@@ -118,16 +499,31 @@ impl Parser {
 
     /// Parse logical AND: left && right
     fn parse_logical_and(&mut self) -> Result<Expr, ParseError> {
+        let start = self.current;
         let mut expr = self.parse_equality()?;
 
-        while self.check(&Token::AndAnd) {
+        loop {
+            if let Some(op) = self.custom_operator(Precedence::LogicalAnd) {
+                expr = self.parse_custom(
+                    op,
+                    start,
+                    expr,
+                    Self::parse_equality,
+                    Self::parse_logical_and,
+                )?;
+                continue;
+            }
+            if !self.check(&Token::AndAnd) {
+                break;
+            }
             self.advance();
             let right = self.parse_equality()?;
-            expr = Expr::BinaryOp {
+            let binary = Expr::BinaryOp {
                 op: BinaryOperator::And,
                 left: Box::new(expr),
                 right: Box::new(right),
             };
+            expr = self.node(start, binary);
         }
 
         Ok(expr)
@@ -135,9 +531,20 @@ impl Parser {
 
     /// Parse equality operators: == !=
     fn parse_equality(&mut self) -> Result<Expr, ParseError> {
+        let start = self.current;
         let mut expr = self.parse_comparison()?;
 
         loop {
+            if let Some(op) = self.custom_operator(Precedence::Equality) {
+                expr = self.parse_custom(
+                    op,
+                    start,
+                    expr,
+                    Self::parse_comparison,
+                    Self::parse_equality,
+                )?;
+                continue;
+            }
             let op = match self.peek() {
                 Token::EqualEqual => BinaryOperator::Eq,
                 Token::NotEqual => BinaryOperator::Neq,
@@ -145,45 +552,115 @@ impl Parser {
             };
             self.advance();
             let right = self.parse_comparison()?;
-            expr = Expr::BinaryOp {
+            let binary = Expr::BinaryOp {
                 op,
                 left: Box::new(expr),
                 right: Box::new(right),
             };
+            expr = self.node(start, binary);
         }
 
         Ok(expr)
     }
 
-    /// Parse comparison operators: < > <= >=
+    /// Parse comparison operators: < > <= >= in like
     fn parse_comparison(&mut self) -> Result<Expr, ParseError> {
-        let mut expr = self.parse_addition()?;
+        let start = self.current;
+        let mut expr = self.parse_null_default()?;
 
         loop {
+            if let Some(op) = self.custom_operator(Precedence::Comparison) {
+                expr = self.parse_custom(
+                    op,
+                    start,
+                    expr,
+                    Self::parse_null_default,
+                    Self::parse_comparison,
+                )?;
+                continue;
+            }
             let op = match self.peek() {
                 Token::Less => BinaryOperator::Lt,
                 Token::LessEqual => BinaryOperator::Lte,
                 Token::Greater => BinaryOperator::Gt,
                 Token::GreaterEqual => BinaryOperator::Gte,
+                Token::In if self.allow_in => BinaryOperator::In,
+                Token::Like => BinaryOperator::Like,
                 _ => break,
             };
+            if op == BinaryOperator::Like {
+                self.require_feature(SyntaxFeature::Like)?;
+            }
             self.advance();
-            let right = self.parse_addition()?;
-            expr = Expr::BinaryOp {
+            let right = self.parse_null_default()?;
+            let binary = Expr::BinaryOp {
                 op,
                 left: Box::new(expr),
                 right: Box::new(right),
             };
+            expr = self.node(start, binary);
         }
 
         Ok(expr)
     }
 
+    /// Parse null-default expressions: value ?? default
+    fn parse_null_default(&mut self) -> Result<Expr, ParseError> {
+        let start = self.current;
+        let value = self.parse_range()?;
+
+        if self.check(&Token::NullDefault) {
+            self.require_feature(SyntaxFeature::NullDefault)?;
+            self.advance();
+            let default = self.parse_null_default()?; // Right-associative
+            let expr = Expr::NullDefault {
+                value: Box::new(value),
+                default: Box::new(default),
+            };
+            return Ok(self.node(start, expr));
+        }
+
+        Ok(value)
+    }
+
+    /// Parse range expressions: start..end, start..=end
+    fn parse_range(&mut self) -> Result<Expr, ParseError> {
+        let first = self.current;
+        let start = self.parse_addition()?;
+
+        let inclusive = match self.peek() {
+            Token::DotDot => false,
+            Token::DotDotEqual => true,
+            _ => return Ok(start),
+        };
+        self.require_feature(SyntaxFeature::Ranges)?;
+        self.advance();
+        let end = self.parse_addition()?;
+
+        let range = Expr::Range {
+            start: Box::new(start),
+            end: Box::new(end),
+            inclusive,
+        };
+        Ok(self.node(first, range))
+    }
+
     /// Parse addition and subtraction: + -
     fn parse_addition(&mut self) -> Result<Expr, ParseError> {
+        let start = self.current;
         let mut expr = self.parse_multiplication()?;
 
         loop {
+            if let Some(op) = self.custom_operator(Precedence::Additive) {
+                expr = self.parse_custom(
+                    op,
+                    start,
+                    expr,
+                    Self::parse_multiplication,
+                    Self::parse_addition,
+                )?;
+                continue;
+            }
             let op = match self.peek() {
                 Token::Plus => BinaryOperator::Add,
                 Token::Minus => BinaryOperator::Sub,
@@ -191,11 +668,12 @@ impl Parser {
             };
             self.advance();
             let right = self.parse_multiplication()?;
-            expr = Expr::BinaryOp {
+            let binary = Expr::BinaryOp {
                 op,
                 left: Box::new(expr),
                 right: Box::new(right),
             };
+            expr = self.node(start, binary);
         }
 
         Ok(expr)
@@ -203,9 +681,20 @@ impl Parser {
 
     /// Parse multiplication, division, modulo: * / %
     fn parse_multiplication(&mut self) -> Result<Expr, ParseError> {
+        let start = self.current;
         let mut expr = self.parse_power()?;
 
         loop {
+            if let Some(op) = self.custom_operator(Precedence::Multiplicative) {
+                expr = self.parse_custom(
+                    op,
+                    start,
+                    expr,
+                    Self::parse_power,
+                    Self::parse_multiplication,
+                )?;
+                continue;
+            }
             let op = match self.peek() {
                 Token::Star => BinaryOperator::Mul,
                 Token::Slash => BinaryOperator::Div,
@@ -214,11 +703,12 @@ impl Parser {
             };
             self.advance();
             let right = self.parse_power()?;
-            expr = Expr::BinaryOp {
+            let binary = Expr::BinaryOp {
                 op,
                 left: Box::new(expr),
                 right: Box::new(right),
             };
+            expr = self.node(start, binary);
         }
 
         Ok(expr)
@@ -226,54 +716,62 @@ impl Parser {
 
     /// Parse exponentiation: ^
     fn parse_power(&mut self) -> Result<Expr, ParseError> {
+        let start = self.current;
         let mut expr = self.parse_unary()?;
 
         if self.check(&Token::Caret) {
+            let op = self.current;
             self.advance();
-            let right = self.parse_power()?; // Right-associative
-            expr = Expr::BinaryOp {
+            let right = self.nested(Parser::parse_power)?; // Right-associative
+            self.legacy.push(LegacyOperator::Pow {
+                start,
+                op,
+                end: self.current,
+            });
+            let power = Expr::BinaryOp {
                 op: BinaryOperator::Pow,
                 left: Box::new(expr),
                 right: Box::new(right),
             };
+            expr = self.node(start, power);
         }
 
         Ok(expr)
     }
 
-    /// Parse unary operators: ! - +
+    /// Parse unary operators, one nesting level deeper
+    ///
+    /// Every sub-expression is parsed through here, so this bounds the
+    /// nesting of parentheses, `if`, `let`, lambdas and unary operators.
     fn parse_unary(&mut self) -> Result<Expr, ParseError> {
-        match self.peek() {
-            Token::Bang => {
-                self.advance();
-                let operand = self.parse_unary()?;
-                Ok(Expr::UnaryOp {
-                    op: UnaryOperator::Not,
-                    operand: Box::new(operand),
-                })
-            }
-            Token::Minus => {
-                self.advance();
-                let operand = self.parse_unary()?;
-                Ok(Expr::UnaryOp {
-                    op: UnaryOperator::Neg,
-                    operand: Box::new(operand),
-                })
-            }
+        self.nested(Parser::parse_prefix)
+    }
+
+    /// Parse unary operators: ! - +
+    fn parse_prefix(&mut self) -> Result<Expr, ParseError> {
+        let start = self.current;
+        let op = match self.peek() {
+            Token::Bang => UnaryOperator::Not,
+            Token::Minus => UnaryOperator::Neg,
             Token::Plus => {
-                self.advance();
-                let operand = self.parse_unary()?;
-                Ok(Expr::UnaryOp {
-                    op: UnaryOperator::Plus,
-                    operand: Box::new(operand),
-                })
+                self.legacy
+                    .push(LegacyOperator::UnaryPlus { op: self.current });
+                UnaryOperator::Plus
             }
-            _ => self.parse_postfix(),
-        }
+            _ => return self.parse_postfix(),
+        };
+        self.advance();
+        let operand = self.parse_unary()?;
+        let expr = Expr::UnaryOp {
+            op,
+            operand: Box::new(operand),
+        };
+        Ok(self.node(start, expr))
     }
 
     /// Parse postfix expressions: field access, function calls, etc.
     fn parse_postfix(&mut self) -> Result<Expr, ParseError> {
+        let start = self.current;
         let mut expr = self.parse_primary()?;
 
         loop {
@@ -282,19 +780,29 @@ impl Parser {
                     self.advance();
                     match self.advance() {
                         Token::Identifier(field) => {
-                            expr = Expr::FieldAccess {
+                            let access = Expr::FieldAccess {
                                 receiver: Box::new(expr),
                                 field,
                             };
+                            expr = self.node(start, access);
                         }
                         _ => {
-                            return Err(ParseError::new("Expected field name after '.'", 1, 1));
+                            return Err(self.error_previous("Expected field name after '.'"));
                         }
                     }
                 }
                 Token::LeftBracket => {
-                    // Array access (not fully implemented in MVP)
-                    break;
+                    self.advance();
+                    let allow_in = std::mem::replace(&mut self.allow_in, true);
+                    let index = self.parse_expression();
+                    self.allow_in = allow_in;
+                    let index = index?;
+                    self.expect_in(Token::RightBracket, "collection[index]")?;
+                    let access = Expr::Index {
+                        collection: Box::new(expr),
+                        index: Box::new(index),
+                    };
+                    expr = self.node(start, access);
                 }
                 Token::LeftParen if matches!(expr, Expr::Identifier(_)) => {
                     // This is a function call - handle it in primary instead
@@ -309,6 +817,22 @@ impl Parser {
 
     /// Parse primary expressions: literals, identifiers, function calls, etc.
     fn parse_primary(&mut self) -> Result<Expr, ParseError> {
+        if self.check(&Token::LeftParen) {
+            self.advance();
+            let allow_in = std::mem::replace(&mut self.allow_in, true);
+            let expr = self.parse_expression();
+            self.allow_in = allow_in;
+            let expr = expr?;
+            self.expect(Token::RightParen)?;
+            return Ok(expr);
+        }
+        let start = self.current;
+        let expr = self.parse_atom()?;
+        Ok(self.node(start, expr))
+    }
+
+    /// Parse a primary expression other than a parenthesized one
+    fn parse_atom(&mut self) -> Result<Expr, ParseError> {
         match self.peek() {
             Token::Integer(n) => {
                 let value = *n;
@@ -320,6 +844,21 @@ impl Parser {
                 self.advance();
                 Ok(Expr::Literal(Literal::Float(value)))
             }
+            Token::Percentage(value) => {
+                self.require_feature(SyntaxFeature::Percentages)?;
+                let value = *value;
+                self.advance();
+                Ok(Expr::Percentage(value))
+            }
+            Token::Quantity(value, unit) => {
+                self.require_feature(SyntaxFeature::Quantities)?;
+                let expr = Expr::Quantity {
+                    value: *value,
+                    unit: unit.clone(),
+                };
+                self.advance();
+                Ok(expr)
+            }
             Token::True => {
                 self.advance();
                 Ok(Expr::Literal(Literal::Boolean(true)))
@@ -337,10 +876,60 @@ impl Parser {
                 self.advance();
                 Ok(Expr::String(value))
             }
+            Token::Placeholder(name) => {
+                let name = name.clone();
+                self.advance();
+                Ok(Expr::Placeholder(name))
+            }
+            Token::Date(text) | Token::DateTime(text) | Token::Duration(text) => {
+                let text = text.clone();
+                let (expr, valid, example) = match self.peek() {
+                    Token::Date(_) => (Expr::Date(text.clone()), is_iso_date(&text), "2024-01-15"),
+                    Token::DateTime(_) => (
+                        Expr::DateTime(text.clone()),
+                        chrono::DateTime::parse_from_rfc3339(&text).is_ok(),
+                        "2024-01-15T10:30:00Z",
+                    ),
+                    _ => (
+                        Expr::Duration(text.clone()),
+                        is_iso_duration(&text),
+                        "P1D, P2W or PT1H30M",
+                    ),
+                };
+                if !valid {
+                    let kind = match expr {
+                        Expr::Date(_) => "date",
+                        Expr::DateTime(_) => "datetime",
+                        _ => "duration",
+                    };
+                    return Err(self
+                        .error(format!("Invalid ISO8601 {} '{}'", kind, text))
+                        .with_hint(format!("write it as @{}({})", kind, example)));
+                }
+                self.advance();
+                Ok(expr)
+            }
             Token::Identifier(name) => {
                 let name = name.clone();
                 self.advance();
 
+                // Quantified expression: any item in items satisfies cond
+                if let Some(function) = quantifier_function(&name) {
+                    if matches!(self.peek(), Token::Identifier(_))
+                        && matches!(self.peek_next(), Token::In)
+                    {
+                        self.require_feature(SyntaxFeature::Quantifiers)?;
+                        return self.parse_quantifier(function);
+                    }
+                }
+
+                // Bare lambda: x ~> body (the body extends as far as possible)
+                if self.check(&Token::LambdaArrow) {
+                    self.advance();
+                    let body = Box::new(self.parse_expression()?);
+                    return Ok(Expr::Lambda { param: name, body });
+                }
+
                 // Check for function call
                 if self.check(&Token::LeftParen) {
                     self.advance();
@@ -351,12 +940,6 @@ impl Parser {
                     Ok(Expr::Identifier(name))
                 }
             }
-            Token::LeftParen => {
-                self.advance();
-                let expr = self.parse_expression()?;
-                self.expect(Token::RightParen)?;
-                Ok(expr)
-            }
             Token::LeftBracket => {
                 self.advance();
                 let elements = self.parse_array_elements()?;
@@ -373,6 +956,14 @@ impl Parser {
             Token::If => self.parse_if(),
             Token::Fn => self.parse_lambda(),
             Token::Guard => self.parse_guard(),
+            Token::Require | Token::WarnIf => {
+                self.require_feature(SyntaxFeature::Assertions)?;
+                if self.check(&Token::Require) {
+                    self.parse_require()
+                } else {
+                    self.parse_warn_if()
+                }
+            }
             Token::Now => {
                 self.advance();
                 Ok(Expr::TemporalKeyword(TemporalKeyword::Now))
@@ -437,11 +1028,7 @@ impl Parser {
                 self.advance();
                 Ok(Expr::TemporalKeyword(TemporalKeyword::EndOfTime))
             }
-            _ => Err(ParseError::new(
-                format!("Unexpected token: {}", self.peek()),
-                1,
-                1,
-            )),
+            _ => Err(self.error(format!("Unexpected token: {}", self.peek()))),
         }
     }
 
@@ -488,15 +1075,27 @@ impl Parser {
                 let key = match self.advance() {
                     Token::Identifier(name) => name,
                     Token::String(s) => s,
-                    _ => {
-                        return Err(ParseError::new(
-                            "Expected field name in object literal",
-                            1,
-                            1,
-                        ))
-                    }
+                    _ => return Err(self.error_previous("Expected field name in object literal")),
                 };
 
+                if let Some(index) = fields.iter().position(|(existing, _)| *existing == key) {
+                    match self.duplicate_keys {
+                        DuplicateKeys::Error => {
+                            return Err(self
+                                .error_previous(format!(
+                                    "Duplicate key '{}' in object literal",
+                                    key
+                                ))
+                                .with_hint("remove or rename one of the entries"));
+                        }
+                        DuplicateKeys::LastWins => {
+                            fields.remove(index);
+                            let first = self.nodes.len() - fields.len() - 1;
+                            self.nodes.remove(first + index);
+                        }
+                    }
+                }
+
                 self.expect(Token::Colon)?;
                 let value = self.parse_expression()?;
                 fields.push((key, value));
@@ -517,13 +1116,13 @@ impl Parser {
 
         let name = match self.advance() {
             Token::Identifier(n) => n,
-            _ => return Err(ParseError::new("Expected variable name after 'let'", 1, 1)),
+            _ => return Err(self.error_previous("Expected variable name after 'let'")),
         };
 
-        self.expect(Token::Equal)?;
-        let value = Box::new(self.parse_expression()?);
+        self.expect_in(Token::Equal, "let <name> = <value> in <body>")?;
+        let value = Box::new(self.parse_expression_before_in()?);
 
-        self.expect(Token::In)?;
+        self.expect_in(Token::In, "let <name> = <value> in <body>")?;
         let body = Box::new(self.parse_expression()?);
 
         Ok(Expr::Let { name, value, body })
@@ -533,9 +1132,9 @@ impl Parser {
     fn parse_if(&mut self) -> Result<Expr, ParseError> {
         self.expect(Token::If)?;
         let condition = Box::new(self.parse_expression()?);
-        self.expect(Token::Then)?;
+        self.expect_in(Token::Then, "if <condition> then <value> else <value>")?;
         let then_branch = Box::new(self.parse_expression()?);
-        self.expect(Token::Else)?;
+        self.expect_in(Token::Else, "if <condition> then <value> else <value>")?;
         let else_branch = Box::new(self.parse_expression()?);
 
         Ok(Expr::If {
@@ -546,31 +1145,165 @@ impl Parser {
     }
 
     /// Parse lambda expression: fn(param ~> body) or (param ~> body)
+    ///
+    /// A lambda of several parameters is curried: `fn(acc, x ~> acc + x)`
+    /// is `fn(acc ~> fn(x ~> acc + x))`.
     fn parse_lambda(&mut self) -> Result<Expr, ParseError> {
         self.expect(Token::Fn)?;
         self.expect(Token::LeftParen)?;
 
-        let param = match self.advance() {
-            Token::Identifier(p) => p,
-            _ => return Err(ParseError::new("Expected parameter name in lambda", 1, 1)),
-        };
+        let mut params = Vec::new();
+        loop {
+            let start = self.current;
+            match self.advance() {
+                Token::Identifier(p) => params.push((p, start)),
+                _ => return Err(self.error_previous("Expected parameter name in lambda")),
+            }
+            if !self.check(&Token::Comma) {
+                break;
+            }
+            self.advance();
+        }
 
         self.expect(Token::LambdaArrow)?;
-        let body = Box::new(self.parse_expression()?);
+        let mut body = self.parse_expression()?;
+        let (param, _) = params.remove(0);
+        // The inner lambdas span from their parameter to the end of the body
+        for (inner, start) in params.into_iter().rev() {
+            let lambda = Expr::Lambda {
+                param: inner,
+                body: Box::new(body),
+            };
+            body = self.node(start, lambda);
+        }
         self.expect(Token::RightParen)?;
 
-        Ok(Expr::Lambda { param, body })
+        Ok(Expr::Lambda {
+            param,
+            body: Box::new(body),
+        })
+    }
+
+    /// Parse the remainder of a quantified expression after its keyword
+    ///
+    /// `any item in collection satisfies body` is sugar for
+    /// `any(collection, fn(item ~> body))` (likewise for `all`).
+    fn parse_quantifier(&mut self, function: &str) -> Result<Expr, ParseError> {
+        let start = self.current;
+        let param = match self.advance() {
+            Token::Identifier(p) => p,
+            _ => return Err(self.error_previous("Expected variable name in quantifier")),
+        };
+        self.expect(Token::In)?;
+        let collection = self.parse_expression()?;
+
+        match self.advance() {
+            Token::Identifier(kw) if kw == "satisfies" => {}
+            other => {
+                return Err(self.error_previous(format!(
+                    "Expected 'satisfies' in quantifier, found {}",
+                    other
+                )))
+            }
+        }
+        let body = self.parse_expression()?;
+        let lambda = Expr::Lambda {
+            param,
+            body: Box::new(body),
+        };
+        let lambda = self.node(start, lambda);
+
+        Ok(Expr::FunctionCall {
+            name: function.to_string(),
+            args: vec![collection, lambda],
+        })
     }
 
     /// Parse guard expression: guard condition in body
     fn parse_guard(&mut self) -> Result<Expr, ParseError> {
         self.expect(Token::Guard)?;
-        let condition = Box::new(self.parse_expression()?);
-        self.expect(Token::In)?;
+        let condition = Box::new(self.parse_expression_before_in()?);
+        self.expect_in(Token::In, "guard <condition> in <body>")?;
         let body = Box::new(self.parse_expression()?);
 
         Ok(Expr::Guard { condition, body })
     }
+
+    /// Parse require expression: require condition else 'message'
+    fn parse_require(&mut self) -> Result<Expr, ParseError> {
+        self.expect(Token::Require)?;
+        let condition = Box::new(self.parse_expression()?);
+        self.expect(Token::Else)?;
+        let message = self.parse_message("require")?;
+
+        Ok(Expr::Require { condition, message })
+    }
+
+    /// Parse warn_if expression: warn_if condition : 'message'
+    fn parse_warn_if(&mut self) -> Result<Expr, ParseError> {
+        self.expect(Token::WarnIf)?;
+        let condition = Box::new(self.parse_expression()?);
+        self.expect(Token::Colon)?;
+        let message = self.parse_message("warn_if")?;
+
+        Ok(Expr::WarnIf { condition, message })
+    }
+
+    /// Parse the string literal message of a require/warn_if expression or
+    /// a message clause
+    fn parse_message(&mut self, keyword: &str) -> Result<String, ParseError> {
+        match self.advance() {
+            Token::String(message) => Ok(message),
+            other => Err(self.error_previous(format!(
+                "Expected message string after '{}' condition, found {}",
+                keyword, other
+            ))),
+        }
+    }
+}
+
+/// Map a quantifier keyword to the collection function it desugars to
+fn quantifier_function(name: &str) -> Option<&'static str> {
+    match name {
+        "any" | "exists" => Some("any"),
+        "all" | "forall" => Some("all"),
+        _ => None,
+    }
+}
+
+/// Whether `text` is a calendar date written `YYYY-MM-DD`
+fn is_iso_date(text: &str) -> bool {
+    text.len() == 10 && chrono::NaiveDate::parse_from_str(text, "%Y-%m-%d").is_ok()
+}
+
+/// Whether `text` is an ISO8601 duration with a fixed length: weeks
+/// (`P2W`), days (`P1D`) or whole hours, minutes and seconds (`PT1H30M`)
+///
+/// Years and months vary in length and are rejected, as are mixed day and
+/// time parts and fractional seconds, which the runtime cannot represent.
+fn is_iso_duration(text: &str) -> bool {
+    let Some(rest) = text.strip_prefix('P') else {
+        return false;
+    };
+    let is_count = |n: &str| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit());
+    if let Some(time) = rest.strip_prefix('T') {
+        // Designators in order, each at most once
+        let mut remaining = time;
+        let mut found = false;
+        for designator in ['H', 'M', 'S'] {
+            if let Some(at) = remaining.find(designator) {
+                if !is_count(&remaining[..at]) {
+                    return false;
+                }
+                remaining = &remaining[at + 1..];
+                found = true;
+            }
+        }
+        return found && remaining.is_empty();
+    }
+    rest.strip_suffix('W')
+        .or_else(|| rest.strip_suffix('D'))
+        .is_some_and(is_count)
 }
 
 #[cfg(test)]
@@ -660,6 +1393,85 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_temporal_literals() {
+        assert_eq!(
+            Parser::parse("@date(2024-01-15)").unwrap(),
+            Expr::Date("2024-01-15".to_string())
+        );
+        assert_eq!(
+            Parser::parse("@datetime(2024-01-15T10:30:00+02:00)").unwrap(),
+            Expr::DateTime("2024-01-15T10:30:00+02:00".to_string())
+        );
+        for duration in ["P1D", "P2W", "PT1H30M", "PT45S", "PT1H2M3S"] {
+            let source = format!("@duration({})", duration);
+            assert_eq!(
+                Parser::parse(&source).unwrap(),
+                Expr::Duration(duration.to_string()),
+                "{}",
+                source
+            );
+        }
+        assert!(matches!(
+            Parser::parse("created + @duration(P1D) < @date(2024-02-01)").unwrap(),
+            Expr::BinaryOp {
+                op: BinaryOperator::Lt,
+                ..
+            }
+        ));
+
+        let error = Parser::parse("due < @date(2024-02-30)").unwrap_err();
+        assert_eq!(error.message, "Invalid ISO8601 date '2024-02-30'");
+        assert_eq!(error.column, 7);
+        assert_eq!(error.hint.as_deref(), Some("write it as @date(2024-01-15)"));
+        for invalid in [
+            "@date(2024-1-5)",
+            "@datetime(2024-01-15 10:30)",
+            "@duration(P1Y)",
+            "@duration(P1DT2H)",
+            "@duration(PT)",
+            "@duration(PT0.5S)",
+            "@duration(PT30M1H)",
+            "@duration(1D)",
+        ] {
+            assert!(Parser::parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_parse_index() {
+        let expr = Parser::parse("matrix[i][j + 1].value").unwrap();
+        let Expr::FieldAccess { receiver, field } = expr else {
+            panic!("Expected field access, got {:?}", expr);
+        };
+        assert_eq!(field, "value");
+        let Expr::Index { collection, index } = *receiver else {
+            panic!("Expected index access");
+        };
+        assert!(matches!(
+            *index,
+            Expr::BinaryOp {
+                op: BinaryOperator::Add,
+                ..
+            }
+        ));
+        assert_eq!(
+            *collection,
+            Expr::Index {
+                collection: Box::new(Expr::Identifier("matrix".to_string())),
+                index: Box::new(Expr::Identifier("i".to_string())),
+            }
+        );
+        // `in` inside brackets is membership, even where `in` ends the value
+        assert!(Parser::parse("let x = flags[a in [1, 2]] in x").is_ok());
+
+        let error = Parser::parse("items[0").unwrap_err();
+        assert_eq!(
+            error.hint.as_deref(),
+            Some("write this as 'collection[index]'")
+        );
+    }
+
     #[test]
     fn test_parse_function_call() {
         let expr = Parser::parse("length(name)").unwrap();
@@ -705,6 +1517,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_object_literal_duplicate_keys() {
+        let error = Parser::parse("{x: 1, 'y': 2, x: 3}").unwrap_err();
+        assert_eq!(error.message, "Duplicate key 'x' in object literal");
+        assert_eq!((error.line, error.column), (1, 16));
+
+        let config = ParserConfig::new().with_duplicate_keys(DuplicateKeys::LastWins);
+        assert_eq!(
+            Parser::parse_with_config("{x: 1, y: 2, x: 3}", &config).unwrap(),
+            Expr::Object(vec![
+                ("y".to_string(), Expr::Literal(Literal::Integer(2))),
+                ("x".to_string(), Expr::Literal(Literal::Integer(3))),
+            ])
+        );
+    }
+
     #[test]
     fn test_parse_null() {
         let expr = Parser::parse("null").unwrap();
@@ -779,6 +1607,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_nesting_limit() {
+        let nested =
+            |open: &str, close: &str, n: usize| format!("{}1{}", open.repeat(n), close.repeat(n));
+        assert!(Parser::parse(&nested("(", ")", MAX_NESTING - 1)).is_ok());
+        for input in [
+            nested("(", ")", 1000),
+            nested("[", "]", 1000),
+            nested("-", "", 1000),
+            nested("if a then 1 else ", "", 1000),
+            nested("", " ^ 2", 1000),
+        ] {
+            let err = Parser::parse(&input).unwrap_err();
+            assert!(err.message.contains("nested too deeply"), "{}", err);
+        }
+    }
+
     #[test]
     fn test_unary_neg() {
         let expr = Parser::parse("-42").unwrap();
@@ -830,6 +1675,644 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_any_quantifier() {
+        let expr = Parser::parse("any item in order.items satisfies item.price > 0").unwrap();
+        let expected = Parser::parse("any(order.items, fn(item ~> item.price > 0))").unwrap();
+        assert_eq!(expr, expected);
+    }
+
+    #[test]
+    fn test_parse_all_quantifier_aliases() {
+        let all = Parser::parse("all x in xs satisfies x > 0").unwrap();
+        let forall = Parser::parse("forall x in xs satisfies x > 0").unwrap();
+        assert_eq!(all, forall);
+        match all {
+            Expr::FunctionCall { name, args } => {
+                assert_eq!(name, "all");
+                assert!(matches!(args[1], Expr::Lambda { .. }));
+            }
+            _ => panic!("Expected quantifier to desugar to a function call"),
+        }
+    }
+
+    #[test]
+    fn test_parse_nested_quantifiers() {
+        let expr = Parser::parse(
+            "all order in orders satisfies any item in order.items satisfies item.qty > 0",
+        )
+        .unwrap();
+        match expr {
+            Expr::FunctionCall { name, args } => {
+                assert_eq!(name, "all");
+                match &args[1] {
+                    Expr::Lambda { body, .. } => {
+                        assert!(
+                            matches!(**body, Expr::FunctionCall { ref name, .. } if name == "any")
+                        )
+                    }
+                    _ => panic!("Expected lambda"),
+                }
+            }
+            _ => panic!("Expected quantifier"),
+        }
+    }
+
+    #[test]
+    fn test_quantifier_requires_satisfies() {
+        assert!(Parser::parse("any x in xs where x > 0").is_err());
+    }
+
+    #[test]
+    fn test_any_identifier_still_usable() {
+        assert_eq!(
+            Parser::parse("any").unwrap(),
+            Expr::Identifier("any".to_string())
+        );
+        assert!(matches!(
+            Parser::parse("any(xs, fn(x ~> x > 0))").unwrap(),
+            Expr::FunctionCall { .. }
+        ));
+    }
+
+    #[test]
+    fn test_parse_like() {
+        let expr = Parser::parse("email like '*@example.com'").unwrap();
+        match expr {
+            Expr::BinaryOp { op, left, right } => {
+                assert_eq!(op, BinaryOperator::Like);
+                assert_eq!(*left, Expr::Identifier("email".to_string()));
+                assert_eq!(*right, Expr::String("*@example.com".to_string()));
+            }
+            _ => panic!("Expected like expression"),
+        }
+    }
+
+    #[test]
+    fn test_parse_null_default() {
+        let expr = Parser::parse("amount <= ctx.limits.daily_max ?? 1000").unwrap();
+        match expr {
+            Expr::BinaryOp {
+                op: BinaryOperator::Lte,
+                right,
+                ..
+            } => match *right {
+                Expr::NullDefault { value, default } => {
+                    assert!(matches!(*value, Expr::FieldAccess { .. }));
+                    assert_eq!(*default, Expr::Literal(Literal::Integer(1000)));
+                }
+                other => panic!("Expected null-default, got {:?}", other),
+            },
+            _ => panic!("Expected comparison at the top"),
+        }
+    }
+
+    #[test]
+    fn test_null_default_is_right_associative() {
+        let expr = Parser::parse("a ?? b ?? 0").unwrap();
+        match expr {
+            Expr::NullDefault { value, default } => {
+                assert_eq!(*value, Expr::Identifier("a".to_string()));
+                assert!(matches!(*default, Expr::NullDefault { .. }));
+            }
+            _ => panic!("Expected null-default"),
+        }
+    }
+
+    #[test]
+    fn test_parse_alternative() {
+        let expr = Parser::parse("age >= 18 ?| has_consent && guardian_approved").unwrap();
+        match expr {
+            Expr::Alternative {
+                primary,
+                alternative,
+            } => {
+                assert!(matches!(
+                    *primary,
+                    Expr::BinaryOp {
+                        op: BinaryOperator::Gte,
+                        ..
+                    }
+                ));
+                assert!(matches!(
+                    *alternative,
+                    Expr::BinaryOp {
+                        op: BinaryOperator::And,
+                        ..
+                    }
+                ));
+            }
+            other => panic!("Expected alternative, got {:?}", other),
+        }
+
+        let expr = Parser::parse("a ?| b ?| 'c'").unwrap();
+        match expr {
+            Expr::Alternative {
+                primary,
+                alternative,
+            } => {
+                assert_eq!(*primary, Expr::Identifier("a".to_string()));
+                assert!(matches!(*alternative, Expr::Alternative { .. }));
+            }
+            _ => panic!("Expected alternative"),
+        }
+
+        let expr = Parser::parse("name ?| 'anon' |> upper()").unwrap();
+        match expr {
+            Expr::Pipe { value, .. } => assert!(matches!(*value, Expr::Alternative { .. })),
+            _ => panic!("Expected pipe"),
+        }
+
+        assert!(Parser::parse("name ?|").is_err());
+    }
+
+    #[test]
+    fn test_parse_range() {
+        let expr = Parser::parse("1..=10").unwrap();
+        assert!(matches!(
+            expr,
+            Expr::Range {
+                inclusive: true,
+                ..
+            }
+        ));
+
+        let expr = Parser::parse("0..n + 1").unwrap();
+        match expr {
+            Expr::Range { end, inclusive, .. } => {
+                assert!(!inclusive);
+                assert!(matches!(
+                    *end,
+                    Expr::BinaryOp {
+                        op: BinaryOperator::Add,
+                        ..
+                    }
+                ));
+            }
+            _ => panic!("Expected range"),
+        }
+    }
+
+    #[test]
+    fn test_parse_in_range() {
+        let expr = Parser::parse("age in 18..=65 && active").unwrap();
+        match expr {
+            Expr::BinaryOp {
+                op: BinaryOperator::And,
+                left,
+                ..
+            } => match *left {
+                Expr::BinaryOp {
+                    op: BinaryOperator::In,
+                    right,
+                    ..
+                } => assert!(matches!(*right, Expr::Range { .. })),
+                other => panic!("Expected membership test, got {:?}", other),
+            },
+            _ => panic!("Expected && at the top"),
+        }
+    }
+
+    #[test]
+    fn test_let_value_stops_at_in() {
+        let expr = Parser::parse("let r = 1..10 in x in r").unwrap();
+        match expr {
+            Expr::Let { value, body, .. } => {
+                assert!(matches!(*value, Expr::Range { .. }));
+                assert!(matches!(
+                    *body,
+                    Expr::BinaryOp {
+                        op: BinaryOperator::In,
+                        ..
+                    }
+                ));
+            }
+            _ => panic!("Expected let"),
+        }
+
+        let expr = Parser::parse("let ok = (x in 1..3) in ok").unwrap();
+        assert!(matches!(expr, Expr::Let { .. }));
+    }
+
+    #[test]
+    fn test_parse_percentage() {
+        let expr = Parser::parse("discount <= 15%").unwrap();
+        match expr {
+            Expr::BinaryOp { op, right, .. } => {
+                assert_eq!(op, BinaryOperator::Lte);
+                assert_eq!(*right, Expr::Percentage(15.0));
+            }
+            _ => panic!("Expected binary op"),
+        }
+    }
+
+    #[test]
+    fn test_percentage_precedence() {
+        let expr = Parser::parse("price * 15% + 1").unwrap();
+        match expr {
+            Expr::BinaryOp {
+                op: BinaryOperator::Add,
+                left,
+                ..
+            } => assert!(matches!(
+                *left,
+                Expr::BinaryOp {
+                    op: BinaryOperator::Mul,
+                    ..
+                }
+            )),
+            _ => panic!("Expected addition at the top"),
+        }
+        let modulo = Parser::parse("count % 2 == 0").unwrap();
+        assert!(matches!(
+            modulo,
+            Expr::BinaryOp {
+                op: BinaryOperator::Eq,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_parse_quantity() {
+        let expr = Parser::parse("weight < 50kg").unwrap();
+        match expr {
+            Expr::BinaryOp { right, .. } => assert_eq!(
+                *right,
+                Expr::Quantity {
+                    value: 50.0,
+                    unit: "kg".to_string()
+                }
+            ),
+            _ => panic!("Expected binary op"),
+        }
+    }
+
+    #[test]
+    fn test_parse_require() {
+        let expr = Parser::parse("require age >= 18 else 'Must be an adult'").unwrap();
+        match expr {
+            Expr::Require { condition, message } => {
+                assert!(matches!(*condition, Expr::BinaryOp { .. }));
+                assert_eq!(message, "Must be an adult");
+            }
+            _ => panic!("Expected require expression"),
+        }
+    }
+
+    #[test]
+    fn test_parse_message_clause() {
+        let expr = Parser::parse("age >= 18 message 'Must be an adult'").unwrap();
+        assert_eq!(
+            expr,
+            Parser::parse("require age >= 18 else 'Must be an adult'").unwrap()
+        );
+
+        let both = Parser::parse("(a message 'need a') && (b message 'need b')").unwrap();
+        assert!(matches!(
+            both,
+            Expr::BinaryOp {
+                op: BinaryOperator::And,
+                ..
+            }
+        ));
+
+        // `message` is still a field name everywhere else
+        assert!(Parser::parse("message != '' message 'message is required'").is_ok());
+        let error = Parser::parse("age >= 18 message 42").unwrap_err();
+        assert!(error
+            .message
+            .contains("Expected message string after 'message'"));
+        let config = ParserConfig::new().with_edition(Edition::Edition2023);
+        assert!(Parser::parse_with_config("age >= 18 message 'adult'", &config).is_err());
+    }
+
+    #[test]
+    fn test_parse_warn_if() {
+        let expr = Parser::parse("warn_if score < 10 : 'Low score'").unwrap();
+        match expr {
+            Expr::WarnIf { message, .. } => assert_eq!(message, "Low score"),
+            _ => panic!("Expected warn_if expression"),
+        }
+    }
+
+    #[test]
+    fn test_require_combines_with_logical_ops() {
+        let expr = Parser::parse("(require a else 'need a') && (warn_if b : 'b is set')").unwrap();
+        assert!(matches!(
+            expr,
+            Expr::BinaryOp {
+                op: BinaryOperator::And,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_require_message_must_be_string() {
+        assert!(Parser::parse("require age >= 18 else 42").is_err());
+        assert!(Parser::parse("require age >= 18").is_err());
+    }
+
+    fn fuzzy_operators(associativity: Associativity) -> OperatorTable {
+        let mut operators = OperatorTable::new();
+        operators
+            .register(
+                CustomOperator::new("~=", "fuzzy_match", Precedence::Equality)
+                    .unwrap()
+                    .with_associativity(associativity),
+            )
+            .unwrap();
+        operators
+            .register(CustomOperator::new("<+>", "combine", Precedence::Additive).unwrap())
+            .unwrap();
+        operators
+    }
+
+    fn call(name: &str, left: Expr, right: Expr) -> Expr {
+        Expr::FunctionCall {
+            name: name.to_string(),
+            args: vec![left, right],
+        }
+    }
+
+    fn ident(name: &str) -> Expr {
+        Expr::Identifier(name.to_string())
+    }
+
+    #[test]
+    fn test_custom_operator_precedence() {
+        let operators = fuzzy_operators(Associativity::Left);
+        let expr = Parser::parse_with_operators("a ~= b <+> c && ok", &operators).unwrap();
+        assert_eq!(
+            expr,
+            Expr::BinaryOp {
+                op: BinaryOperator::And,
+                left: Box::new(call(
+                    "fuzzy_match",
+                    ident("a"),
+                    call("combine", ident("b"), ident("c"))
+                )),
+                right: Box::new(ident("ok")),
+            }
+        );
+    }
+
+    #[test]
+    fn test_custom_operator_associativity() {
+        let left =
+            Parser::parse_with_operators("a ~= b ~= c", &fuzzy_operators(Associativity::Left))
+                .unwrap();
+        assert_eq!(
+            left,
+            call(
+                "fuzzy_match",
+                call("fuzzy_match", ident("a"), ident("b")),
+                ident("c")
+            )
+        );
+
+        let right =
+            Parser::parse_with_operators("a ~= b ~= c", &fuzzy_operators(Associativity::Right))
+                .unwrap();
+        assert_eq!(
+            right,
+            call(
+                "fuzzy_match",
+                ident("a"),
+                call("fuzzy_match", ident("b"), ident("c"))
+            )
+        );
+    }
+
+    #[test]
+    fn test_escaped_keyword_identifiers() {
+        let expr = Parser::parse("let `in` = stock.`in` in `in` > `guard`").unwrap();
+        assert!(matches!(expr, Expr::Let { ref name, .. } if name == "in"));
+        assert_eq!(
+            expr.referenced_fields().into_iter().collect::<Vec<_>>(),
+            vec!["guard", "stock.in"]
+        );
+    }
+
+    #[test]
+    fn test_parse_with_config_case_insensitive_temporal() {
+        let source = "created_at >= today && created_at < now";
+        let config = ParserConfig::new().with_case_insensitive_temporal(true);
+        let expr = Parser::parse_with_config(source, &config).unwrap();
+        assert_eq!(
+            expr.free_variables().into_iter().collect::<Vec<_>>(),
+            vec!["created_at"]
+        );
+        let default = Parser::parse(source).unwrap();
+        assert_eq!(default.free_variables().len(), 3);
+    }
+
+    #[test]
+    fn test_custom_operators_need_registration() {
+        assert!(Parser::parse("a ~= b").is_err());
+        let operators = fuzzy_operators(Associativity::Left);
+        // Built-in operators sharing a prefix still lex as before
+        assert!(Parser::parse_with_operators("a <= b", &operators).is_ok());
+        assert!(Parser::parse_with_operators("xs |> map(fn(x ~> x))", &operators).is_ok());
+    }
+
+    fn hint(source: &str) -> Option<String> {
+        Parser::parse(source).unwrap_err().hint
+    }
+
+    #[test]
+    fn test_hint_single_equals() {
+        assert_eq!(hint("age = 18").as_deref(), Some(EQUAL_HINT));
+        assert_eq!(hint("(age = 18)").as_deref(), Some(EQUAL_HINT));
+        assert!(Parser::parse("a == 1 b").is_err());
+    }
+
+    #[test]
+    fn test_hint_missing_keywords() {
+        assert!(hint("if a b else c")
+            .unwrap()
+            .contains("if <condition> then"));
+        assert!(hint("if a then b").unwrap().contains("else <value>"));
+        assert!(hint("let x = 1 x")
+            .unwrap()
+            .contains("let <name> = <value> in"));
+        assert!(hint("guard ok body")
+            .unwrap()
+            .contains("guard <condition> in"));
+    }
+
+    #[test]
+    fn test_lexer_hints_keep_position() {
+        let err = Parser::parse("a & b").unwrap_err();
+        assert_eq!(err.message, "Unexpected '&'");
+        assert_eq!(err.column, 3);
+        assert_eq!(err.hint.as_deref(), Some("did you mean '&&'?"));
+        assert!(err.context.unwrap().contains("a & b"));
+
+        assert_eq!(hint("a | b").as_deref(), Some("did you mean '||' or '|>'?"));
+    }
+
+    #[test]
+    fn test_edition_gates_syntax() {
+        let old = ParserConfig::new().with_edition(Edition::Edition2023);
+        for source in [
+            "any x in xs satisfies x > 0",
+            "require a else 'need a'",
+            "limit ?? 10",
+            "nickname ?| 'anonymous'",
+            "age in 18..65",
+            "name like 'a*'",
+            "rate < 15%",
+            "weight < 50kg",
+        ] {
+            assert!(Parser::parse(source).is_ok(), "{}", source);
+            assert!(
+                Parser::parse_with_config(source, &old).is_err(),
+                "{}",
+                source
+            );
+        }
+
+        let err = Parser::parse_with_config("a && limit ?? 10", &old).unwrap_err();
+        assert_eq!(
+            err.message,
+            "edition 2023 does not support the '??' operator (added in edition 2024)"
+        );
+        assert_eq!(err.column, 12);
+        assert_eq!(
+            err.hint.as_deref(),
+            Some("add 'edition = \"2024\"' before the first rule")
+        );
+        assert!(Parser::parse_with_config("if a then xs |> length() else 0", &old).is_ok());
+    }
+
+    #[test]
+    fn test_unterminated_string_points_at_opening_quote() {
+        let err = Parser::parse("name == 'abc").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 9));
+        assert!(err.to_string().contains("= help: close the string"));
+    }
+
+    #[test]
+    fn test_errors_point_at_offending_token() {
+        let err = Parser::parse("age >= 18 &&\n  (verified ||\n   )").unwrap_err();
+        assert_eq!((err.line, err.column), (3, 4));
+        assert_eq!(err.context.unwrap(), "  |\n3 |    )\n  |    ^");
+
+        let err = Parser::parse("let = 5 in x").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 5));
+
+        let err = Parser::parse("user.\n  42").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 3));
+
+        let err = Parser::parse("age >= 18\nverified").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+    }
+
+    #[test]
+    fn test_bare_lambda() {
+        assert_eq!(
+            Parser::parse("x ~> x * 2").unwrap(),
+            Expr::Lambda {
+                param: "x".to_string(),
+                body: Box::new(Expr::BinaryOp {
+                    op: BinaryOperator::Mul,
+                    left: Box::new(Expr::Identifier("x".to_string())),
+                    right: Box::new(Expr::Literal(Literal::Integer(2))),
+                }),
+            }
+        );
+    }
+
+    #[test]
+    fn test_lambda_with_several_parameters() {
+        let curried = Parser::parse("fn(acc ~> fn(x ~> acc + x))").unwrap();
+        assert_eq!(Parser::parse("fn(acc, x ~> acc + x)").unwrap(), curried);
+        assert!(Parser::parse("fn(acc, ~> acc)").is_err());
+        assert_eq!(
+            span_texts("fn(a, b ~> a + b)", &ParserConfig::new()),
+            vec!["fn(a, b ~> a + b)", "b ~> a + b", "a + b", "a", "b"]
+        );
+    }
+
+    /// Texts of every node of a spanned parse, parents first
+    fn span_texts(source: &str, config: &ParserConfig) -> Vec<String> {
+        let spanned = Parser::parse_spanned_with_config(source, config).unwrap();
+        let nodes: Vec<(&Expr, Span)> = spanned.nodes().collect();
+        assert_eq!(nodes.len(), spanned.expr.node_count(), "{}", source);
+        nodes
+            .into_iter()
+            .map(|(_, span)| span.text(source).to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_spanned() {
+        let config = ParserConfig::new();
+        assert_eq!(
+            span_texts("-(a + 1) * f(b.c, [2])", &config),
+            vec![
+                "-(a + 1) * f(b.c, [2])",
+                "-(a + 1)",
+                "a + 1",
+                "a",
+                "1",
+                "f(b.c, [2])",
+                "b.c",
+                "b",
+                "[2]",
+                "2",
+            ]
+        );
+        assert_eq!(
+            span_texts("any x in xs satisfies x > 0", &config),
+            vec![
+                "any x in xs satisfies x > 0",
+                "xs",
+                "x in xs satisfies x > 0",
+                "x > 0",
+                "x",
+                "0",
+            ]
+        );
+
+        let spanned = Parser::parse_spanned("total >= 10\n  && name == 'Zoë'").unwrap();
+        let Expr::BinaryOp { right, .. } = &spanned.expr else {
+            panic!("expected a conjunction")
+        };
+        let span = spanned.span_of(right).unwrap();
+        assert_eq!(
+            (span.line, span.column, span.start, span.end),
+            (2, 6, 17, 31)
+        );
+        assert_eq!(spanned.span().len(), 31);
+    }
+
+    #[test]
+    fn test_parse_spanned_covers_every_node() {
+        let sources = [
+            "let x = a ?? 1 in if x > 2 then x ^ 2 else -x",
+            "items |> map(i ~> i.price) |> sum()",
+            "nickname ?| 'anonymous' || fallback",
+            "guard age in require age >= 18 else 'adult'",
+            "warn_if score < 10: 'low'",
+            "{a: [1, 2][0], b: @date(2024-01-15), c: TODAY + @duration(P1D)}",
+            "fn(x ~> x in 1..=10) && !ok && +n < 5kg",
+        ];
+        for source in sources {
+            span_texts(source, &ParserConfig::new());
+        }
+
+        let config = ParserConfig::new()
+            .with_duplicate_keys(DuplicateKeys::LastWins)
+            .with_operators(fuzzy_operators(Associativity::Right));
+        assert_eq!(
+            span_texts("{x: a, y: b ~= c, x: 3}", &config),
+            vec!["{x: a, y: b ~= c, x: 3}", "b ~= c", "b", "c", "3"]
+        );
+    }
+
     #[test]
     fn test_complex_expression() {
         let expr = Parser::parse("user.age >= 18 && isActive").unwrap();
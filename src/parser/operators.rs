@@ -0,0 +1,318 @@
+//! Embedder-defined infix operators
+//!
+//! An [`OperatorTable`] lets applications add domain-specific infix syntax
+//! without forking the grammar. Each [`CustomOperator`] names a symbol, the
+//! precedence level it binds at, its associativity, and the function it
+//! stands for: `a ~= b` parses as the call `fuzzy_match(a, b)`.
+//!
+//! Code generation emits `fuzzy_match(&a, &b)` by default, or whatever the
+//! operator's codegen callback returns; the runtime callback evaluates the
+//! operator on [`EloValue`]s for dynamic evaluation.
+//!
+//! # Example
+//!
+//! ```
+//! use elo_rust::ast::Expr;
+//! use elo_rust::parser::operators::{CustomOperator, OperatorTable, Precedence};
+//! use elo_rust::parser::Parser;
+//!
+//! let mut operators = OperatorTable::new();
+//! operators
+//!     .register(CustomOperator::new("~=", "fuzzy_match", Precedence::Equality).unwrap())
+//!     .unwrap();
+//!
+//! let expr = Parser::parse_with_operators("name ~= 'Jon Smith'", &operators).unwrap();
+//! assert!(matches!(expr, Expr::FunctionCall { ref name, .. } if name == "fuzzy_match"));
+//! ```
+
+use crate::runtime::EloValue;
+use proc_macro2::TokenStream;
+use quote::quote;
+use std::fmt;
+use std::sync::Arc;
+
+/// Characters custom operator symbols may be made of
+pub const OPERATOR_CHARS: &str = "~!@#$%^&*-+=<>?/|:";
+
+/// Built-in operator symbols, which cannot be redefined
+const BUILTIN_SYMBOLS: &[&str] = &[
+    "+", "-", "*", "/", "%", "^", "=", "==", "!=", "<", "<=", ">", ">=", "&&", "||", "!", "=>",
+    "|>", "~>", "?|", "??", ":",
+];
+
+/// Generates code for an operator application from its operand code
+pub type CodegenHandler = Arc<dyn Fn(TokenStream, TokenStream) -> TokenStream + Send + Sync>;
+
+/// Evaluates an operator application on runtime values
+pub type RuntimeHandler = Arc<dyn Fn(&EloValue, &EloValue) -> EloValue + Send + Sync>;
+
+/// Precedence level of a custom operator, from loosest to tightest binding
+///
+/// A custom operator binds exactly as tightly as the built-in operators of
+/// its level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Precedence {
+    /// Same as `||`
+    LogicalOr,
+    /// Same as `&&`
+    LogicalAnd,
+    /// Same as `==` and `!=`
+    Equality,
+    /// Same as `<`, `<=`, `>`, `>=`
+    Comparison,
+    /// Same as `+` and `-`
+    Additive,
+    /// Same as `*`, `/`, `%`
+    Multiplicative,
+}
+
+/// How chains of the same operator group
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Associativity {
+    /// `a op b op c` is `(a op b) op c`
+    #[default]
+    Left,
+    /// `a op b op c` is `a op (b op c)`
+    Right,
+}
+
+/// An embedder-defined infix operator
+#[derive(Clone)]
+pub struct CustomOperator {
+    symbol: String,
+    function: String,
+    precedence: Precedence,
+    associativity: Associativity,
+    codegen: Option<CodegenHandler>,
+    runtime: Option<RuntimeHandler>,
+}
+
+impl fmt::Debug for CustomOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomOperator")
+            .field("symbol", &self.symbol)
+            .field("function", &self.function)
+            .field("precedence", &self.precedence)
+            .field("associativity", &self.associativity)
+            .field("codegen", &self.codegen.is_some())
+            .field("runtime", &self.runtime.is_some())
+            .finish()
+    }
+}
+
+impl CustomOperator {
+    /// Define an operator that stands for calling `function` on its operands
+    ///
+    /// The symbol must consist of [`OPERATOR_CHARS`] and must not be a
+    /// built-in operator; the function must be an identifier or a `::` path.
+    pub fn new(
+        symbol: impl Into<String>,
+        function: impl Into<String>,
+        precedence: Precedence,
+    ) -> Result<Self, String> {
+        let symbol = symbol.into();
+        let function = function.into();
+        if symbol.is_empty() || !symbol.chars().all(|c| OPERATOR_CHARS.contains(c)) {
+            return Err(format!(
+                "invalid operator symbol '{}': use only the characters {}",
+                symbol, OPERATOR_CHARS
+            ));
+        }
+        if BUILTIN_SYMBOLS.contains(&symbol.as_str()) {
+            return Err(format!("'{}' is a built-in operator", symbol));
+        }
+        if !function.split("::").all(is_identifier) {
+            return Err(format!("invalid operator function '{}'", function));
+        }
+        Ok(Self {
+            symbol,
+            function,
+            precedence,
+            associativity: Associativity::Left,
+            codegen: None,
+            runtime: None,
+        })
+    }
+
+    /// Set the associativity (left by default)
+    pub fn with_associativity(mut self, associativity: Associativity) -> Self {
+        self.associativity = associativity;
+        self
+    }
+
+    /// Generate code for applications of the operator with a callback
+    pub fn with_codegen<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(TokenStream, TokenStream) -> TokenStream + Send + Sync + 'static,
+    {
+        self.codegen = Some(Arc::new(handler));
+        self
+    }
+
+    /// Evaluate applications of the operator on runtime values with a callback
+    pub fn with_runtime<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(&EloValue, &EloValue) -> EloValue + Send + Sync + 'static,
+    {
+        self.runtime = Some(Arc::new(handler));
+        self
+    }
+
+    /// Operator symbol
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    /// Function the operator stands for
+    pub fn function(&self) -> &str {
+        &self.function
+    }
+
+    /// Precedence level
+    pub fn precedence(&self) -> Precedence {
+        self.precedence
+    }
+
+    /// Associativity
+    pub fn associativity(&self) -> Associativity {
+        self.associativity
+    }
+
+    /// Generate code applying the operator to two operands
+    ///
+    /// Without a codegen callback this is `function(&left, &right)`.
+    pub fn generate(&self, left: TokenStream, right: TokenStream) -> TokenStream {
+        if let Some(handler) = &self.codegen {
+            return handler(left, right);
+        }
+        let segments = self
+            .function
+            .split("::")
+            .map(|segment| quote::format_ident!("{}", segment));
+        quote! { #(#segments)::*(&#left, &#right) }
+    }
+
+    /// Evaluate the operator, if it has a runtime callback
+    pub fn evaluate(&self, left: &EloValue, right: &EloValue) -> Option<EloValue> {
+        self.runtime.as_ref().map(|handler| handler(left, right))
+    }
+}
+
+/// Set of custom operators known to a parser and code generator
+#[derive(Debug, Clone, Default)]
+pub struct OperatorTable {
+    operators: Vec<CustomOperator>,
+}
+
+impl OperatorTable {
+    /// Create an empty table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an operator
+    ///
+    /// Returns an error if the symbol is already registered.
+    pub fn register(&mut self, operator: CustomOperator) -> Result<(), String> {
+        if self.get(&operator.symbol).is_some() {
+            return Err(format!(
+                "operator '{}' is already registered",
+                operator.symbol
+            ));
+        }
+        self.operators.push(operator);
+        // Longest symbols first, so the lexer prefers `~==` over `~=`
+        self.operators
+            .sort_by_key(|op| std::cmp::Reverse(op.symbol.len()));
+        Ok(())
+    }
+
+    /// Look up an operator by symbol
+    pub fn get(&self, symbol: &str) -> Option<&CustomOperator> {
+        self.operators.iter().find(|op| op.symbol == symbol)
+    }
+
+    /// Look up an operator by the function it stands for
+    pub fn by_function(&self, function: &str) -> Option<&CustomOperator> {
+        self.operators.iter().find(|op| op.function == function)
+    }
+
+    /// Registered operators, longest symbol first
+    pub fn iter(&self) -> impl Iterator<Item = &CustomOperator> {
+        self.operators.iter()
+    }
+
+    /// Check if no operators are registered
+    pub fn is_empty(&self) -> bool {
+        self.operators.is_empty()
+    }
+
+    /// Registered symbols, longest first
+    pub fn symbols(&self) -> Vec<String> {
+        self.operators.iter().map(|op| op.symbol.clone()).collect()
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rejects_invalid_definitions() {
+        assert!(CustomOperator::new("", "f", Precedence::Equality).is_err());
+        assert!(CustomOperator::new("~a", "f", Precedence::Equality).is_err());
+        assert!(CustomOperator::new("==", "f", Precedence::Equality).is_err());
+        assert!(CustomOperator::new("~=", "not valid", Precedence::Equality).is_err());
+        assert!(CustomOperator::new("~=", "bank::fuzzy", Precedence::Equality).is_ok());
+    }
+
+    #[test]
+    fn test_register_rejects_duplicates() {
+        let mut table = OperatorTable::new();
+        let op = CustomOperator::new("~=", "fuzzy", Precedence::Equality).unwrap();
+        table.register(op.clone()).unwrap();
+        assert!(table.register(op).is_err());
+    }
+
+    #[test]
+    fn test_symbols_longest_first() {
+        let mut table = OperatorTable::new();
+        for symbol in ["~=", "~==", "<~"] {
+            table
+                .register(CustomOperator::new(symbol, "f", Precedence::Equality).unwrap())
+                .unwrap();
+        }
+        assert_eq!(table.symbols()[0], "~==");
+    }
+
+    #[test]
+    fn test_generate_default_and_callback() {
+        let op = CustomOperator::new("~=", "bank::fuzzy", Precedence::Equality).unwrap();
+        assert_eq!(
+            op.generate(quote!(a), quote!(b)).to_string(),
+            "bank :: fuzzy (& a , & b)"
+        );
+        let op = op.with_codegen(|l, r| quote!(#l.eq_ignore_ascii_case(&#r)));
+        assert_eq!(
+            op.generate(quote!(a), quote!(b)).to_string(),
+            "a . eq_ignore_ascii_case (& b)"
+        );
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let op = CustomOperator::new("~=", "fuzzy", Precedence::Equality).unwrap();
+        assert_eq!(op.evaluate(&EloValue::Null, &EloValue::Null), None);
+        let op = op.with_runtime(|l, r| EloValue::Boolean(l == r));
+        assert_eq!(
+            op.evaluate(&EloValue::Integer(1), &EloValue::Integer(1)),
+            Some(EloValue::Boolean(true))
+        );
+    }
+}
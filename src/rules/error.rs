@@ -0,0 +1,121 @@
+//! Rule set error types
+
+use crate::params::ParamError;
+use crate::parser::ParseError;
+use std::fmt;
+
+/// Errors that can occur while building or compiling a [`RuleSet`](super::RuleSet)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleSetError {
+    /// A rule expression failed to parse
+    Parse {
+        /// Name of the rule whose expression is invalid
+        rule: String,
+        /// Underlying parse error
+        error: ParseError,
+    },
+
+    /// A rule declaration is malformed (missing name, missing `:`, ...)
+    InvalidDeclaration {
+        /// Line number (1-based) of the offending declaration
+        line: usize,
+        /// Description of the problem
+        message: String,
+    },
+
+    /// Two rules share the same name
+    DuplicateRule(String),
+
+    /// A rule was requested that is not part of the set
+    UnknownRule(String),
+
+    /// A sampling configuration is invalid (rate out of range, bad key path)
+    InvalidSampling {
+        /// Name of the sampled rule
+        rule: String,
+        /// Description of the problem
+        message: String,
+    },
+
+    /// An effective-date window is invalid (bad date, empty window)
+    InvalidWindow {
+        /// Name of the rule
+        rule: String,
+        /// Description of the problem
+        message: String,
+    },
+
+    /// A rule's placeholders could not be bound
+    Params {
+        /// Name of the rule
+        rule: String,
+        /// Underlying binding error
+        error: ParamError,
+    },
+
+    /// A rule cannot be disabled because other rules reference it
+    RuleInUse {
+        /// Name of the rule to disable
+        rule: String,
+        /// Rules that reference it
+        used_by: Vec<String>,
+    },
+
+    /// Rules reference each other in a cycle
+    ///
+    /// Contains the cycle path, starting and ending with the same rule name.
+    CyclicDependency(Vec<String>),
+}
+
+impl fmt::Display for RuleSetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse { rule, error } => write!(f, "Rule '{}': {}", rule, error),
+            Self::InvalidDeclaration { line, message } => {
+                write!(f, "Invalid rule declaration at line {}: {}", line, message)
+            }
+            Self::DuplicateRule(name) => write!(f, "Duplicate rule name: {}", name),
+            Self::UnknownRule(name) => write!(f, "Unknown rule: {}", name),
+            Self::InvalidSampling { rule, message } => {
+                write!(f, "Invalid sampling for rule '{}': {}", rule, message)
+            }
+            Self::InvalidWindow { rule, message } => {
+                write!(
+                    f,
+                    "Invalid effective window for rule '{}': {}",
+                    rule, message
+                )
+            }
+            Self::Params { rule, error } => write!(f, "Rule '{}': {}", rule, error),
+            Self::RuleInUse { rule, used_by } => write!(
+                f,
+                "Rule '{}' cannot be disabled: used by {}",
+                rule,
+                used_by.join(", ")
+            ),
+            Self::CyclicDependency(path) => {
+                write!(f, "Cyclic rule dependency: {}", path.join(" -> "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for RuleSetError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cyclic_dependency_display() {
+        let err =
+            RuleSetError::CyclicDependency(vec!["a".to_string(), "b".to_string(), "a".to_string()]);
+        assert_eq!(err.to_string(), "Cyclic rule dependency: a -> b -> a");
+    }
+
+    #[test]
+    fn test_duplicate_rule_display() {
+        let err = RuleSetError::DuplicateRule("is_adult".to_string());
+        assert_eq!(err.to_string(), "Duplicate rule name: is_adult");
+    }
+}
@@ -0,0 +1,380 @@
+//! Interval analysis of numeric range checks
+//!
+//! Many rules bound a single numeric field: `age >= 18`, `0 < amount &&
+//! amount <= 500`. Such a rule accepts an [`Interval`] of values, which makes
+//! it possible to compare rules on the same field. When one rule's interval
+//! lies inside another's, the wider rule can never fail on its own and is
+//! reported as a [`RedundantCheck`]:
+//!
+//! ```
+//! use elo_rust::rules::RuleSet;
+//!
+//! let rules = RuleSet::parse(
+//!     "rule is_adult: age >= 18\n\
+//!      rule old_enough: age >= 16",
+//! )
+//! .unwrap();
+//!
+//! let redundant = rules.redundant_checks();
+//! assert_eq!(
+//!     redundant[0].to_string(),
+//!     "rule 'old_enough' is implied by rule 'is_adult': age in [18, +inf) is within [16, +inf)"
+//! );
+//! ```
+//!
+//! The intervals of all rules on a field also intersect into one
+//! consolidated check, which generated validators can expose as a fast path
+//! (see [`RuleSet::with_fast_path`](super::RuleSet::with_fast_path)).
+
+use crate::ast::analysis::FieldPath;
+use crate::ast::{BinaryOperator, Expr, Literal};
+use crate::codegen::float::format_float;
+use crate::codegen::optimization::Optimizer;
+use std::cmp::Ordering;
+use std::fmt;
+
+/// One end of an [`Interval`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bound {
+    /// Numeric literal of the bound, as written in the rule
+    pub value: Literal,
+    /// Whether the bound itself is part of the interval
+    pub inclusive: bool,
+}
+
+impl Bound {
+    fn number(&self) -> f64 {
+        match self.value {
+            Literal::Integer(n) => n as f64,
+            Literal::Float(f) => f,
+            Literal::Boolean(_) => f64::NAN,
+        }
+    }
+}
+
+/// Numeric values accepted by a range check; missing bounds are unbounded
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Interval {
+    /// Lowest accepted value
+    pub lower: Option<Bound>,
+    /// Highest accepted value
+    pub upper: Option<Bound>,
+}
+
+impl Interval {
+    /// Field and interval of a rule body made only of numeric comparisons
+    /// of one field, joined with `&&`
+    ///
+    /// Returns `None` for anything else, including `!=` comparisons, which do
+    /// not describe an interval.
+    pub fn of(expr: &Expr) -> Option<(FieldPath, Interval)> {
+        let expr = Optimizer::fold_constants(expr);
+        let mut field = None;
+        let mut interval = Interval::default();
+        collect_comparisons(&expr, &mut field, &mut interval)?;
+        field.map(|field| (field, interval))
+    }
+
+    /// Check that no value satisfies both bounds
+    pub fn is_empty(&self) -> bool {
+        match (&self.lower, &self.upper) {
+            (Some(lower), Some(upper)) => match lower.number().partial_cmp(&upper.number()) {
+                Some(Ordering::Less) => false,
+                Some(Ordering::Equal) => !(lower.inclusive && upper.inclusive),
+                _ => true,
+            },
+            _ => false,
+        }
+    }
+
+    /// Check that every value in `other` is also in `self`
+    pub fn contains(&self, other: &Interval) -> bool {
+        at_least_as_tight(&self.lower, &other.lower, Ordering::Greater)
+            && at_least_as_tight(&self.upper, &other.upper, Ordering::Less)
+    }
+
+    /// Values in both intervals
+    pub fn intersect(&self, other: &Interval) -> Interval {
+        let lower = if at_least_as_tight(&self.lower, &other.lower, Ordering::Greater) {
+            &other.lower
+        } else {
+            &self.lower
+        };
+        let upper = if at_least_as_tight(&self.upper, &other.upper, Ordering::Less) {
+            &other.upper
+        } else {
+            &self.upper
+        };
+        Interval {
+            lower: lower.clone(),
+            upper: upper.clone(),
+        }
+    }
+
+    /// ELO condition checking that `field` lies in the interval
+    pub fn to_expr(&self, field: &FieldPath) -> Expr {
+        let target = field_expr(field);
+        let compare = |op, bound: &Bound| Expr::BinaryOp {
+            op,
+            left: Box::new(target.clone()),
+            right: Box::new(Expr::Literal(bound.value.clone())),
+        };
+        match (&self.lower, &self.upper) {
+            _ if self.is_empty() => Expr::Literal(Literal::Boolean(false)),
+            (Some(lower), Some(upper))
+                if lower.inclusive && upper.inclusive && lower.number() == upper.number() =>
+            {
+                compare(BinaryOperator::Eq, lower)
+            }
+            (lower, upper) => {
+                let lower = lower.as_ref().map(|bound| {
+                    let op = if bound.inclusive {
+                        BinaryOperator::Gte
+                    } else {
+                        BinaryOperator::Gt
+                    };
+                    compare(op, bound)
+                });
+                let upper = upper.as_ref().map(|bound| {
+                    let op = if bound.inclusive {
+                        BinaryOperator::Lte
+                    } else {
+                        BinaryOperator::Lt
+                    };
+                    compare(op, bound)
+                });
+                match (lower, upper) {
+                    (Some(lower), Some(upper)) => Expr::BinaryOp {
+                        op: BinaryOperator::And,
+                        left: Box::new(lower),
+                        right: Box::new(upper),
+                    },
+                    (Some(check), None) | (None, Some(check)) => check,
+                    (None, None) => Expr::Literal(Literal::Boolean(true)),
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.lower {
+            Some(bound) if bound.inclusive => write!(f, "[{}", format_literal(&bound.value))?,
+            Some(bound) => write!(f, "({}", format_literal(&bound.value))?,
+            None => write!(f, "(-inf")?,
+        }
+        match &self.upper {
+            Some(bound) if bound.inclusive => write!(f, ", {}]", format_literal(&bound.value)),
+            Some(bound) => write!(f, ", {})", format_literal(&bound.value)),
+            None => write!(f, ", +inf)"),
+        }
+    }
+}
+
+/// A rule that always holds when another, stricter rule holds
+#[derive(Debug, Clone, PartialEq)]
+pub struct RedundantCheck {
+    /// The rule that can never fail on its own
+    pub rule: String,
+    /// The stricter rule implying it
+    pub implied_by: String,
+    /// Field both rules bound
+    pub field: FieldPath,
+    /// Values accepted by `rule`
+    pub interval: Interval,
+    /// Values accepted by `implied_by`, contained in `interval`
+    pub stricter: Interval,
+}
+
+impl fmt::Display for RedundantCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "rule '{}' is implied by rule '{}': {} in {} is within {}",
+            self.rule, self.implied_by, self.field, self.stricter, self.interval
+        )
+    }
+}
+
+/// Check if bound `b` excludes at least the values bound `a` excludes
+///
+/// `tighter` is the ordering of a tighter bound's value: `Greater` for lower
+/// bounds, `Less` for upper bounds.
+fn at_least_as_tight(a: &Option<Bound>, b: &Option<Bound>, tighter: Ordering) -> bool {
+    match (a, b) {
+        (None, _) => true,
+        (Some(_), None) => false,
+        (Some(a), Some(b)) => match b.number().partial_cmp(&a.number()) {
+            Some(Ordering::Equal) => a.inclusive || !b.inclusive,
+            Some(ordering) => ordering == tighter,
+            None => false,
+        },
+    }
+}
+
+fn collect_comparisons(
+    expr: &Expr,
+    field: &mut Option<FieldPath>,
+    interval: &mut Interval,
+) -> Option<()> {
+    let Expr::BinaryOp { op, left, right } = expr else {
+        return None;
+    };
+    if *op == BinaryOperator::And {
+        collect_comparisons(left, field, interval)?;
+        return collect_comparisons(right, field, interval);
+    }
+
+    // `18 <= age` reads as `age >= 18`
+    let (path, value, op) = match (field_path(left), numeric_literal(right)) {
+        (Some(path), Some(value)) => (path, value, *op),
+        _ => (field_path(right)?, numeric_literal(left)?, flip(*op)?),
+    };
+    if field.get_or_insert_with(|| path.clone()) != &path {
+        return None;
+    }
+
+    let bound = |inclusive| {
+        Some(Bound {
+            value: value.clone(),
+            inclusive,
+        })
+    };
+    let check = match op {
+        BinaryOperator::Gt => Interval {
+            lower: bound(false),
+            upper: None,
+        },
+        BinaryOperator::Gte => Interval {
+            lower: bound(true),
+            upper: None,
+        },
+        BinaryOperator::Lt => Interval {
+            lower: None,
+            upper: bound(false),
+        },
+        BinaryOperator::Lte => Interval {
+            lower: None,
+            upper: bound(true),
+        },
+        BinaryOperator::Eq => Interval {
+            lower: bound(true),
+            upper: bound(true),
+        },
+        _ => return None,
+    };
+    *interval = interval.intersect(&check);
+    Some(())
+}
+
+/// Operator with its operands swapped
+fn flip(op: BinaryOperator) -> Option<BinaryOperator> {
+    match op {
+        BinaryOperator::Gt => Some(BinaryOperator::Lt),
+        BinaryOperator::Gte => Some(BinaryOperator::Lte),
+        BinaryOperator::Lt => Some(BinaryOperator::Gt),
+        BinaryOperator::Lte => Some(BinaryOperator::Gte),
+        BinaryOperator::Eq => Some(BinaryOperator::Eq),
+        _ => None,
+    }
+}
+
+fn numeric_literal(expr: &Expr) -> Option<Literal> {
+    match expr {
+        Expr::Literal(Literal::Float(f)) if f.is_nan() => None,
+        Expr::Literal(literal @ (Literal::Integer(_) | Literal::Float(_))) => Some(literal.clone()),
+        Expr::Percentage(percent) => Some(Literal::Float(percent / 100.0)),
+        _ => None,
+    }
+}
+
+fn field_path(expr: &Expr) -> Option<FieldPath> {
+    match expr {
+        Expr::Identifier(name) => Some(FieldPath::new([name.as_str()])),
+        Expr::FieldAccess { receiver, field } => {
+            let receiver = field_path(receiver)?;
+            Some(FieldPath::new(
+                receiver
+                    .segments()
+                    .iter()
+                    .map(String::as_str)
+                    .chain([field.as_str()]),
+            ))
+        }
+        _ => None,
+    }
+}
+
+fn field_expr(field: &FieldPath) -> Expr {
+    let mut segments = field.segments().iter();
+    let root = Expr::Identifier(segments.next().cloned().unwrap_or_default());
+    segments.fold(root, |receiver, segment| Expr::FieldAccess {
+        receiver: Box::new(receiver),
+        field: segment.clone(),
+    })
+}
+
+fn format_literal(literal: &Literal) -> String {
+    match literal {
+        Literal::Integer(n) => n.to_string(),
+        Literal::Float(f) => format_float(*f),
+        Literal::Boolean(b) => b.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+
+    fn interval(source: &str) -> Interval {
+        Interval::of(&Parser::parse(source).unwrap()).unwrap().1
+    }
+
+    #[test]
+    fn test_interval_of_conjunction() {
+        let (field, range) =
+            Interval::of(&Parser::parse("0 < order.total && order.total <= 500").unwrap()).unwrap();
+        assert_eq!(field, FieldPath::from("order.total"));
+        assert_eq!(range.to_string(), "(0, 500]");
+        assert_eq!(interval("age == 18").to_string(), "[18, 18]");
+        assert_eq!(interval("age >= -5").to_string(), "[-5, +inf)");
+
+        // Mixed fields, `!=` and non-literal bounds are not intervals
+        assert!(Interval::of(&Parser::parse("age > 0 && height > 0").unwrap()).is_none());
+        assert!(Interval::of(&Parser::parse("age != 3").unwrap()).is_none());
+        assert!(Interval::of(&Parser::parse("age > min_age").unwrap()).is_none());
+    }
+
+    #[test]
+    fn test_contains_and_intersect() {
+        let adult = interval("age >= 18");
+        assert!(interval("age >= 16").contains(&adult));
+        assert!(!adult.contains(&interval("age >= 16")));
+        assert!(interval("age >= 18").contains(&interval("age > 18")));
+        assert!(!interval("age > 18").contains(&interval("age >= 18")));
+
+        let range = adult.intersect(&interval("age < 65"));
+        assert_eq!(range.to_string(), "[18, 65)");
+        assert!(adult.intersect(&interval("age < 10")).is_empty());
+        assert!(interval("age > 1.5 && age < 1.5").is_empty());
+    }
+
+    #[test]
+    fn test_to_expr() {
+        let field = FieldPath::from("user.age");
+        assert_eq!(
+            interval("age >= 18 && age < 65").to_expr(&field),
+            Parser::parse("user.age >= 18 && user.age < 65").unwrap()
+        );
+        assert_eq!(
+            interval("age <= 3 && age >= 3").to_expr(&field),
+            Parser::parse("user.age == 3").unwrap()
+        );
+        assert_eq!(
+            interval("age > 3 && age < 1").to_expr(&field),
+            Expr::Literal(Literal::Boolean(false))
+        );
+    }
+}
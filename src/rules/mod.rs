@@ -0,0 +1,2178 @@
+//! Named rule sets with inter-rule dependencies
+//!
+//! A [`RuleSet`] is an ordered collection of named ELO expressions. Rules may
+//! reference other rules by name, Datalog-style:
+//!
+//! ```text
+//! rule is_adult: age >= 18
+//! rule has_payment_method: payment_methods > 0
+//! rule can_checkout: is_adult && has_payment_method
+//! ```
+//!
+//! References are resolved when the set is compiled: cycles are rejected, each
+//! rule becomes a generated helper function, and the generated validator reports
+//! which base rule caused a composite rule to fail. Other names are fields of the
+//! input, as in [`RustCodeGenerator`](crate::codegen::RustCodeGenerator): the
+//! generated helpers read `age` from `input.age`, with the field types of the
+//! input type registered [`with_type_context`](RuleSet::with_type_context).
+//!
+//! Rules can be gated behind a feature flag, in which case they are only
+//! checked when the flag is enabled (see [`crate::runtime::flags`]):
+//!
+//! ```text
+//! rule kyc_verified [flag = "new_kyc"]: kyc_status == 'verified'
+//! ```
+//!
+//! Expensive rules can be sampled: the rule below is only checked for 10% of
+//! inputs, chosen deterministically from the `user_id` field (see
+//! [`crate::runtime::sampling`]):
+//!
+//! ```text
+//! rule fraud_score_ok [sample = "10%", key = "user_id"]: fraud_score(input) < 80
+//! ```
+//!
+//! Rules can be limited to an effective-date window; outside it they are
+//! reported as skipped. The window is checked against a
+//! [`Clock`](crate::runtime::clock::Clock) passed to the generated validator:
+//!
+//! ```text
+//! rule new_limit [effective_from = "2025-01-01", expires_at = "2026-01-01"]: amount <= 500
+//! ```
+//!
+//! A rule that folds to `true` or `false` without reading the input is almost
+//! always a mistake. Such rules compile to a `const` instead of a runtime
+//! check, and the generated code raises a deprecation warning naming them
+//! (see [`RuleSet::input_independent_rules`]).
+//!
+//! A rule set can start from another one with [`RuleSet::extends`] and then
+//! override or disable inherited rules by name, or pull in the rules of a
+//! mixin with [`RuleSet::include`]. This lets tenants customize a shared base
+//! policy without copying it.
+//!
+//! Rules that only bound a numeric field are compared by [interval
+//! analysis](intervals): [`RuleSet::redundant_checks`] reports rules implied
+//! by a stricter rule on the same field, and [`RuleSet::with_fast_path`]
+//! generates one consolidated bounds check per field.
+//!
+//! The SQL-representable subset of a set can also be enforced by the
+//! database, as [CHECK constraints](sql) generated by
+//! [`RuleSet::check_constraints`].
+//!
+//! Rules that are structurally identical after constant folding and boolean
+//! minimization are compiled once: later copies become aliases of the first
+//! helper and are listed by [`RuleSet::duplicate_rules`].
+
+pub mod error;
+pub mod intervals;
+pub mod sql;
+pub mod table;
+
+pub use error::RuleSetError;
+pub use intervals::{Interval, RedundantCheck};
+pub use sql::{CheckConstraint, CheckConstraints, SqlDialect, TableMapping, UnsupportedRule};
+pub use table::{DecisionTable, DecisionTableError, HitPolicy};
+
+use crate::ast::analysis::{self, FieldPath};
+use crate::ast::visitor::Visitor;
+use crate::ast::{BinaryOperator, Expr, Literal};
+use crate::codegen::ast_to_code::CodegenVisitor;
+use crate::codegen::float::float_literal;
+use crate::codegen::optimization::Optimizer;
+use crate::codegen::size::{self, SizeReport};
+use crate::codegen::types::TypeContext;
+use crate::params::Params;
+use crate::parser::{Edition, Parser, ParserConfig};
+use crate::runtime::clock;
+use crate::runtime::interpreter::Evaluator;
+use crate::runtime::report::{RuleOutcome, SkipReason, ValidationReport};
+use crate::runtime::EloValue;
+use chrono::{DateTime, Utc};
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
+
+/// A single named validation rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    /// Rule name, unique within its rule set
+    pub name: String,
+    /// Original ELO source of the rule body
+    pub source: String,
+    /// Parsed rule body
+    pub expr: Expr,
+    /// Feature flag that must be enabled for the rule to be checked
+    pub flag: Option<String>,
+    /// Fraction of inputs the rule is checked for
+    pub sampling: Option<Sampling>,
+    /// Period during which the rule is checked
+    pub window: Option<EffectiveWindow>,
+}
+
+/// Deterministic sampling of a rule by a key field
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sampling {
+    /// Percentage of inputs to check (0-100)
+    pub percent: f64,
+    /// Field path of the sampling key (e.g. "user_id" or "account.id")
+    pub key: String,
+}
+
+impl Sampling {
+    /// Create a sampling configuration, validating the rate and key path
+    pub fn new(percent: f64, key: impl Into<String>) -> Result<Self, String> {
+        let key = key.into();
+        if !(0.0..=100.0).contains(&percent) {
+            return Err(format!(
+                "sample rate must be between 0% and 100%, got {}%",
+                percent
+            ));
+        }
+        if !key.split('.').all(is_valid_rule_name) {
+            return Err(format!("invalid sampling key '{}'", key));
+        }
+        Ok(Self { percent, key })
+    }
+}
+
+/// Period during which a rule applies: `effective_from <= now < expires_at`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EffectiveWindow {
+    /// First instant the rule is checked, if bounded
+    pub effective_from: Option<DateTime<Utc>>,
+    /// First instant the rule is no longer checked, if bounded
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl EffectiveWindow {
+    /// Create a window from its boundaries, rejecting empty windows
+    pub fn new(
+        effective_from: Option<DateTime<Utc>>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<Self, String> {
+        if let (Some(from), Some(expires)) = (effective_from, expires_at) {
+            if from >= expires {
+                return Err(format!(
+                    "expires_at ({}) must be after effective_from ({})",
+                    expires.to_rfc3339(),
+                    from.to_rfc3339()
+                ));
+            }
+        }
+        Ok(Self {
+            effective_from,
+            expires_at,
+        })
+    }
+
+    /// Create a window from `YYYY-MM-DD` dates or RFC 3339 timestamps
+    pub fn parse(effective_from: Option<&str>, expires_at: Option<&str>) -> Result<Self, String> {
+        Self::new(
+            effective_from.map(clock::parse_instant).transpose()?,
+            expires_at.map(clock::parse_instant).transpose()?,
+        )
+    }
+
+    /// Why a rule with this window is skipped at `now`, if it is
+    pub fn skip_reason(&self, now: DateTime<Utc>) -> Option<SkipReason> {
+        if self.effective_from.is_some_and(|from| now < from) {
+            Some(SkipReason::NotYetEffective)
+        } else if self.expires_at.is_some_and(|expires| now >= expires) {
+            Some(SkipReason::Expired)
+        } else {
+            None
+        }
+    }
+}
+
+/// A rule whose body is identical to an earlier rule's after normalization
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateRule {
+    /// The later rule
+    pub rule: String,
+    /// The first rule with the same body
+    pub duplicate_of: String,
+}
+
+impl fmt::Display for DuplicateRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "rule '{}' is identical to rule '{}'",
+            self.rule, self.duplicate_of
+        )
+    }
+}
+
+/// Attributes parsed from `rule <name> [...]`
+#[derive(Debug, Default)]
+struct RuleAttributes {
+    flag: Option<String>,
+    sampling: Option<Sampling>,
+    sample_key: Option<String>,
+    window: Option<EffectiveWindow>,
+}
+
+impl Rule {
+    /// Parse a rule from its name and ELO source
+    pub fn new(name: impl Into<String>, source: impl Into<String>) -> Result<Self, RuleSetError> {
+        Self::with_edition(name, source, Edition::LATEST)
+    }
+
+    /// Parse a rule with the grammar of `edition`
+    pub fn with_edition(
+        name: impl Into<String>,
+        source: impl Into<String>,
+        edition: Edition,
+    ) -> Result<Self, RuleSetError> {
+        let name = name.into();
+        let source = source.into();
+        let config = ParserConfig::new().with_edition(edition);
+        let expr =
+            Parser::parse_with_config(&source, &config).map_err(|error| RuleSetError::Parse {
+                rule: name.clone(),
+                error,
+            })?;
+        Ok(Self {
+            name,
+            source,
+            expr,
+            flag: None,
+            sampling: None,
+            window: None,
+        })
+    }
+
+    /// Gate the rule behind a feature flag
+    pub fn with_flag(mut self, flag: impl Into<String>) -> Self {
+        self.flag = Some(flag.into());
+        self
+    }
+}
+
+/// Ordered collection of named rules that may reference each other
+///
+/// # Example
+///
+/// ```
+/// use elo_rust::rules::RuleSet;
+///
+/// let rules = RuleSet::parse(
+///     "rule is_adult: age >= 18\n\
+///      rule can_checkout: is_adult && verified",
+/// )
+/// .unwrap();
+///
+/// assert_eq!(rules.dependencies("can_checkout").unwrap(), vec!["is_adult"]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RuleSet {
+    rules: Vec<Rule>,
+    timed: bool,
+    fast_path: bool,
+    edition: Edition,
+    type_context: TypeContext,
+}
+
+impl RuleSet {
+    /// Create an empty rule set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record how long each rule takes in generated validation reports
+    pub fn with_timing(mut self, timed: bool) -> Self {
+        self.timed = timed;
+        self
+    }
+
+    /// Also generate a `<fn_name>_fast_path` function checking the
+    /// [consolidated range checks](Self::consolidated_checks)
+    pub fn with_fast_path(mut self, fast_path: bool) -> Self {
+        self.fast_path = fast_path;
+        self
+    }
+
+    /// Parse rules added from now on with the grammar of `edition`
+    pub fn with_edition(mut self, edition: Edition) -> Self {
+        self.edition = edition;
+        self
+    }
+
+    /// Take the field types of generated validators' input type from `context`
+    ///
+    /// As for [`RustCodeGenerator`](crate::codegen::RustCodeGenerator), the
+    /// generated code then follows the declared types: `price > 1` on a float
+    /// field compares with `1.0`, and comparisons of `Option` fields are
+    /// guarded.
+    pub fn with_type_context(mut self, context: TypeContext) -> Self {
+        self.type_context = context;
+        self
+    }
+
+    /// Grammar edition of the set's rules
+    pub fn edition(&self) -> Edition {
+        self.edition
+    }
+
+    /// Parse a rule file
+    ///
+    /// Each rule starts with `rule <name>: <expression>` at the beginning of a
+    /// line; following lines that do not start a new rule continue the current
+    /// expression. Blank lines and lines starting with `#` or `//` are ignored.
+    /// Attributes go in brackets after the name: `rule <name> [flag = "x"]: ...`.
+    /// An `edition = "2023"` line before the first rule selects the
+    /// [grammar edition](crate::parser::edition); the latest is the default.
+    pub fn parse(source: &str) -> Result<Self, RuleSetError> {
+        let mut set = RuleSet::new();
+        let mut current: Option<(String, RuleAttributes, String)> = None;
+
+        for (idx, raw_line) in source.lines().enumerate() {
+            let line = raw_line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with("//") {
+                continue;
+            }
+
+            if let Some(decl) = line.strip_prefix("rule ") {
+                if let Some((name, attrs, body)) = current.take() {
+                    set.add_parsed_rule(name, attrs, body)?;
+                }
+                current = Some(parse_declaration(decl, idx + 1)?);
+            } else if let Some((_, _, body)) = current.as_mut() {
+                body.push('\n');
+                body.push_str(line);
+            } else if let Some(edition) = Edition::from_header(line) {
+                set.edition = edition.map_err(|message| RuleSetError::InvalidDeclaration {
+                    line: idx + 1,
+                    message,
+                })?;
+            } else {
+                return Err(RuleSetError::InvalidDeclaration {
+                    line: idx + 1,
+                    message: "expected 'rule <name>: <expression>'".to_string(),
+                });
+            }
+        }
+
+        if let Some((name, attrs, body)) = current {
+            set.add_parsed_rule(name, attrs, body)?;
+        }
+
+        Ok(set)
+    }
+
+    fn add_parsed_rule(
+        &mut self,
+        name: String,
+        attrs: RuleAttributes,
+        body: String,
+    ) -> Result<(), RuleSetError> {
+        let mut rule = Rule::with_edition(name, body, self.edition)?;
+        rule.flag = attrs.flag;
+        rule.sampling = attrs.sampling;
+        rule.window = attrs.window;
+        self.insert(rule)
+    }
+
+    /// Add a rule to the set
+    ///
+    /// Returns an error if the expression does not parse or the name is taken.
+    pub fn add_rule(
+        &mut self,
+        name: impl Into<String>,
+        source: impl Into<String>,
+    ) -> Result<(), RuleSetError> {
+        self.insert(Rule::with_edition(name, source, self.edition)?)
+    }
+
+    fn insert(&mut self, rule: Rule) -> Result<(), RuleSetError> {
+        if self.get(&rule.name).is_some() {
+            return Err(RuleSetError::DuplicateRule(rule.name));
+        }
+        self.rules.push(rule);
+        Ok(())
+    }
+
+    /// Start a rule set from the rules of `base`
+    ///
+    /// The new set owns copies of the base rules, so later changes to either
+    /// set do not affect the other. Inherited rules can be replaced with
+    /// [`override_rule`](Self::override_rule) or removed with
+    /// [`disable_rule`](Self::disable_rule).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use elo_rust::rules::RuleSet;
+    ///
+    /// let base = RuleSet::parse(
+    ///     "rule is_adult: age >= 18\n\
+    ///      rule has_email: length(email) > 0",
+    /// )
+    /// .unwrap();
+    ///
+    /// let mut tenant = RuleSet::extends(&base);
+    /// tenant.override_rule("is_adult", "age >= 21").unwrap();
+    /// tenant.disable_rule("has_email").unwrap();
+    ///
+    /// assert_eq!(tenant.get("is_adult").unwrap().source, "age >= 21");
+    /// assert!(tenant.get("has_email").is_none());
+    /// assert_eq!(base.len(), 2);
+    /// ```
+    pub fn extends(base: &RuleSet) -> Self {
+        base.clone()
+    }
+
+    /// Add every rule of `mixin` to this set
+    ///
+    /// Fails without changing the set if a rule name is already taken.
+    pub fn include(&mut self, mixin: &RuleSet) -> Result<(), RuleSetError> {
+        if let Some(rule) = mixin.rules.iter().find(|r| self.get(&r.name).is_some()) {
+            return Err(RuleSetError::DuplicateRule(rule.name.clone()));
+        }
+        self.rules.extend(mixin.rules.iter().cloned());
+        Ok(())
+    }
+
+    /// Replace the expression of an existing rule
+    ///
+    /// The rule keeps its position, feature flag and sampling, so rules that
+    /// reference it pick up the new definition.
+    pub fn override_rule(
+        &mut self,
+        name: &str,
+        source: impl Into<String>,
+    ) -> Result<(), RuleSetError> {
+        let replacement = Rule::with_edition(name, source, self.edition)?;
+        let rule = self
+            .rules
+            .iter_mut()
+            .find(|r| r.name == name)
+            .ok_or_else(|| RuleSetError::UnknownRule(name.to_string()))?;
+        rule.source = replacement.source;
+        rule.expr = replacement.expr;
+        Ok(())
+    }
+
+    /// Remove a rule so it is no longer checked
+    ///
+    /// Fails if other rules still reference it; disable or override those
+    /// first.
+    pub fn disable_rule(&mut self, name: &str) -> Result<(), RuleSetError> {
+        let index = self
+            .rules
+            .iter()
+            .position(|r| r.name == name)
+            .ok_or_else(|| RuleSetError::UnknownRule(name.to_string()))?;
+        let used_by: Vec<String> = self
+            .rules
+            .iter()
+            .filter(|r| r.name != name && r.expr.free_variables().contains(name))
+            .map(|r| r.name.clone())
+            .collect();
+        if !used_by.is_empty() {
+            return Err(RuleSetError::RuleInUse {
+                rule: name.to_string(),
+                used_by,
+            });
+        }
+        self.rules.remove(index);
+        Ok(())
+    }
+
+    /// Copy of the set with `${name}` placeholders replaced by tenant values
+    ///
+    /// Rule sources are kept as written; only the parsed expressions change.
+    pub fn bind_params(&self, params: &Params) -> Result<RuleSet, RuleSetError> {
+        let mut bound = self.clone();
+        for rule in &mut bound.rules {
+            rule.expr = params
+                .bind(&rule.expr)
+                .map_err(|error| RuleSetError::Params {
+                    rule: rule.name.clone(),
+                    error,
+                })?;
+        }
+        Ok(bound)
+    }
+
+    /// Gate an existing rule behind a feature flag
+    pub fn set_flag(&mut self, name: &str, flag: impl Into<String>) -> Result<(), RuleSetError> {
+        let rule = self
+            .rules
+            .iter_mut()
+            .find(|r| r.name == name)
+            .ok_or_else(|| RuleSetError::UnknownRule(name.to_string()))?;
+        rule.flag = Some(flag.into());
+        Ok(())
+    }
+
+    /// Check an existing rule only for a percentage of inputs, keyed by a field
+    pub fn set_sampling(
+        &mut self,
+        name: &str,
+        percent: f64,
+        key: impl Into<String>,
+    ) -> Result<(), RuleSetError> {
+        let sampling =
+            Sampling::new(percent, key).map_err(|message| RuleSetError::InvalidSampling {
+                rule: name.to_string(),
+                message,
+            })?;
+        let rule = self
+            .rules
+            .iter_mut()
+            .find(|r| r.name == name)
+            .ok_or_else(|| RuleSetError::UnknownRule(name.to_string()))?;
+        rule.sampling = Some(sampling);
+        Ok(())
+    }
+
+    /// Check an existing rule only between two instants
+    ///
+    /// Boundaries are `YYYY-MM-DD` dates (midnight UTC) or RFC 3339
+    /// timestamps; `None` leaves that side open.
+    pub fn set_window(
+        &mut self,
+        name: &str,
+        effective_from: Option<&str>,
+        expires_at: Option<&str>,
+    ) -> Result<(), RuleSetError> {
+        let window = EffectiveWindow::parse(effective_from, expires_at).map_err(|message| {
+            RuleSetError::InvalidWindow {
+                rule: name.to_string(),
+                message,
+            }
+        })?;
+        let rule = self
+            .rules
+            .iter_mut()
+            .find(|r| r.name == name)
+            .ok_or_else(|| RuleSetError::UnknownRule(name.to_string()))?;
+        rule.window = Some(window);
+        Ok(())
+    }
+
+    /// Feature flags referenced by the rules, sorted
+    pub fn flags(&self) -> Vec<&str> {
+        let flags: BTreeSet<&str> = self
+            .rules
+            .iter()
+            .filter_map(|r| r.flag.as_deref())
+            .collect();
+        flags.into_iter().collect()
+    }
+
+    /// Look up a rule by name
+    pub fn get(&self, name: &str) -> Option<&Rule> {
+        self.rules.iter().find(|r| r.name == name)
+    }
+
+    /// All rules in declaration order
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// Number of rules in the set
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Check if the set contains no rules
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Names of the rules directly referenced by `name`, sorted
+    pub fn dependencies(&self, name: &str) -> Result<Vec<String>, RuleSetError> {
+        let rule = self
+            .get(name)
+            .ok_or_else(|| RuleSetError::UnknownRule(name.to_string()))?;
+        let names = self.rule_names();
+        Ok(rule
+            .expr
+            .free_variables()
+            .into_iter()
+            .filter(|name| names.contains(name))
+            .collect())
+    }
+
+    /// Base rules (rules with no rule dependencies of their own) that `name`
+    /// transitively depends on, sorted
+    pub fn base_rules(&self, name: &str) -> Result<Vec<String>, RuleSetError> {
+        self.check_cycles()?;
+        let mut visited = HashSet::new();
+        let mut bases = BTreeSet::new();
+        let mut stack = self.dependencies(name)?;
+        while let Some(dep) = stack.pop() {
+            if !visited.insert(dep.clone()) {
+                continue;
+            }
+            let deps = self.dependencies(&dep)?;
+            if deps.is_empty() {
+                bases.insert(dep);
+            } else {
+                stack.extend(deps);
+            }
+        }
+        Ok(bases.into_iter().collect())
+    }
+
+    /// Input fields `name` reads, including through the rules it references
+    ///
+    /// Identifiers naming rules are rule references, not fields.
+    pub fn field_dependencies(&self, name: &str) -> Result<Vec<FieldPath>, RuleSetError> {
+        self.check_cycles()?;
+        let names = self.rule_names();
+        let mut fields = BTreeSet::new();
+        let mut visited = HashSet::new();
+        let mut stack = vec![name.to_string()];
+        while let Some(current) = stack.pop() {
+            if !visited.insert(current.clone()) {
+                continue;
+            }
+            let rule = self
+                .get(&current)
+                .ok_or_else(|| RuleSetError::UnknownRule(current.clone()))?;
+            fields.extend(
+                analysis::field_dependencies(&rule.expr)
+                    .into_iter()
+                    .filter(|path| !names.contains(path.root())),
+            );
+            stack.extend(self.dependencies(&current)?);
+        }
+        Ok(fields.into_iter().collect())
+    }
+
+    /// Fields read by every rule, keyed by rule name
+    pub fn field_index(&self) -> Result<BTreeMap<String, Vec<FieldPath>>, RuleSetError> {
+        self.rules
+            .iter()
+            .map(|rule| Ok((rule.name.clone(), self.field_dependencies(&rule.name)?)))
+            .collect()
+    }
+
+    /// Rules whose result may change when the given fields change, in
+    /// declaration order
+    ///
+    /// A changed path affects a rule if it overlaps one of the rule's field
+    /// dependencies: a parent of a read field, the field itself, or a field
+    /// nested inside it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use elo_rust::ast::analysis::FieldPath;
+    /// use elo_rust::rules::RuleSet;
+    ///
+    /// let rules = RuleSet::parse(
+    ///     "rule is_adult: user.age >= 18\n\
+    ///      rule has_email: length(user.email) > 3\n\
+    ///      rule can_checkout: is_adult && cart.total > 0",
+    /// )
+    /// .unwrap();
+    ///
+    /// let changed = [FieldPath::from("user.age")];
+    /// assert_eq!(rules.affected_rules(&changed).unwrap(), vec!["is_adult", "can_checkout"]);
+    /// ```
+    pub fn affected_rules(&self, changed: &[FieldPath]) -> Result<Vec<String>, RuleSetError> {
+        let index = self.field_index()?;
+        Ok(self
+            .rules
+            .iter()
+            .filter(|rule| {
+                index[&rule.name]
+                    .iter()
+                    .any(|field| changed.iter().any(|c| c.overlaps(field)))
+            })
+            .map(|rule| rule.name.clone())
+            .collect())
+    }
+
+    /// Rule names ordered so that every rule comes after the rules it references
+    ///
+    /// Returns [`RuleSetError::CyclicDependency`] if the references form a cycle.
+    pub fn evaluation_order(&self) -> Result<Vec<String>, RuleSetError> {
+        let mut state: HashMap<&str, VisitState> = HashMap::new();
+        let mut order = Vec::with_capacity(self.rules.len());
+        let mut path = Vec::new();
+        for rule in &self.rules {
+            self.visit(&rule.name, &mut state, &mut path, &mut order)?;
+        }
+        Ok(order)
+    }
+
+    /// Check that no rules reference each other cyclically
+    pub fn check_cycles(&self) -> Result<(), RuleSetError> {
+        self.evaluation_order().map(|_| ())
+    }
+
+    /// Expand all rule references in `name` into the referenced expressions
+    ///
+    /// The result is a self-contained expression that no longer depends on
+    /// other rules, suitable for callers that prefer inlining over helpers.
+    pub fn inline(&self, name: &str) -> Result<Expr, RuleSetError> {
+        self.check_cycles()?;
+        let rule = self
+            .get(name)
+            .ok_or_else(|| RuleSetError::UnknownRule(name.to_string()))?;
+        Ok(self.inline_expr(&rule.expr, &mut HashSet::new()))
+    }
+
+    /// Rules whose value is a constant after inlining and folding
+    ///
+    /// Returns each such rule with its value, in declaration order.
+    pub fn input_independent_rules(&self) -> Result<Vec<(String, bool)>, RuleSetError> {
+        let mut constants = Vec::new();
+        for rule in &self.rules {
+            let folded = Optimizer::fold_constants(&self.inline(&rule.name)?);
+            if let Expr::Literal(Literal::Boolean(value)) = folded {
+                constants.push((rule.name.clone(), value));
+            }
+        }
+        Ok(constants)
+    }
+
+    /// Rules implied by a stricter range check on the same field
+    ///
+    /// Only rules whose body bounds a single numeric field take part (see
+    /// [`Interval::of`]). A rule can only imply others if it is always
+    /// checked, i.e. has no flag, sampling or effective window. Rules with
+    /// identical intervals are reported against the first declared one.
+    pub fn redundant_checks(&self) -> Vec<RedundantCheck> {
+        let intervals = self.interval_rules();
+        let mut redundant = Vec::new();
+        for (index, (rule, field, interval)) in intervals.iter().enumerate() {
+            let stricter = intervals.iter().enumerate().find(
+                |(other_index, (other, other_field, other_interval))| {
+                    *other_index != index
+                        && other_field == field
+                        && is_unconditional(other)
+                        && !other_interval.is_empty()
+                        && interval.contains(other_interval)
+                        // Of two equal intervals, only the later one is redundant
+                        && (other_index < &index || !other_interval.contains(interval))
+                },
+            );
+            if let Some((_, (other, _, other_interval))) = stricter {
+                redundant.push(RedundantCheck {
+                    rule: rule.name.clone(),
+                    implied_by: other.name.clone(),
+                    field: field.clone(),
+                    interval: interval.clone(),
+                    stricter: other_interval.clone(),
+                });
+            }
+        }
+        redundant
+    }
+
+    /// Intersection of the range checks of all always-checked rules, per
+    /// field, in order of first appearance
+    ///
+    /// The input passes every such rule exactly when each field lies in its
+    /// consolidated interval, so one bounds check per field replaces one
+    /// check per rule.
+    pub fn consolidated_checks(&self) -> Vec<(FieldPath, Interval)> {
+        let mut checks: Vec<(FieldPath, Interval)> = Vec::new();
+        for (rule, field, interval) in self.interval_rules() {
+            if !is_unconditional(rule) {
+                continue;
+            }
+            match checks.iter_mut().find(|(f, _)| *f == field) {
+                Some((_, consolidated)) => *consolidated = consolidated.intersect(&interval),
+                None => checks.push((field, interval)),
+            }
+        }
+        checks
+    }
+
+    /// Rules identical to an earlier rule, in declaration order
+    ///
+    /// Bodies are compared after [optimization](Optimizer::optimize), so
+    /// `age >= 10 + 8` duplicates `age >= 18`. Flags, sampling and windows
+    /// are not compared; they only decide when a rule is checked.
+    pub fn duplicate_rules(&self) -> Vec<DuplicateRule> {
+        // Hash-cons normalized bodies by their structural rendering
+        let mut first: HashMap<String, &str> = HashMap::new();
+        let mut duplicates = Vec::new();
+        for rule in &self.rules {
+            let key = format!("{:?}", Optimizer::optimize(&rule.expr));
+            match first.get(&key) {
+                Some(original) => duplicates.push(DuplicateRule {
+                    rule: rule.name.clone(),
+                    duplicate_of: original.to_string(),
+                }),
+                None => {
+                    first.insert(key, &rule.name);
+                }
+            }
+        }
+        duplicates
+    }
+
+    /// Translate every rule into a `CHECK` constraint on `mapping`'s table
+    ///
+    /// Rules are inlined and constant-folded first. Rules gated by a flag,
+    /// sampling or effective window, and rules outside the SQL-representable
+    /// subset (see [`sql`]), are listed in
+    /// [`unsupported`](CheckConstraints::unsupported).
+    pub fn check_constraints(
+        &self,
+        mapping: &TableMapping,
+        dialect: SqlDialect,
+    ) -> Result<CheckConstraints, RuleSetError> {
+        let writer = sql::SqlWriter { mapping, dialect };
+        let mut constraints = Vec::new();
+        let mut unsupported = Vec::new();
+        for rule in &self.rules {
+            let condition = if is_unconditional(rule) {
+                writer.condition(&Optimizer::fold_constants(&self.inline(&rule.name)?))
+            } else {
+                Err("only checked conditionally (flag, sampling or effective window)".to_string())
+            };
+            match condition {
+                Ok(condition) => constraints.push(CheckConstraint {
+                    rule: rule.name.clone(),
+                    name: format!("{}_{}_check", mapping.table(), rule.name),
+                    condition,
+                }),
+                Err(reason) => unsupported.push(UnsupportedRule {
+                    rule: rule.name.clone(),
+                    reason,
+                }),
+            }
+        }
+        Ok(CheckConstraints {
+            table: mapping.table().to_string(),
+            dialect,
+            constraints,
+            unsupported,
+        })
+    }
+
+    /// Check `input` against every rule with the [`Evaluator`], without
+    /// generating code
+    ///
+    /// Outcomes match the generated `_report` function: rules whose body is a
+    /// `warn_if` are warned when their condition holds, and a failing
+    /// top-level `require` reports its own message. A rule that cannot be
+    /// evaluated (type mismatch, unsupported construct) fails with the reason.
+    /// Flags, sampling and effective windows are not applied; every rule is
+    /// checked.
+    pub fn check(&self, input: &EloValue) -> Result<ValidationReport, RuleSetError> {
+        let evaluator = Evaluator::new();
+        let mut report = ValidationReport::new();
+        for rule in &self.rules {
+            let expr = self.inline(&rule.name)?;
+            let outcome = match &expr {
+                Expr::WarnIf { condition, message } => match evaluator.evaluate(condition, input) {
+                    Ok(value) if value.is_truthy() => {
+                        RuleOutcome::warned(&rule.name, vec![message.clone()])
+                    }
+                    Ok(_) => RuleOutcome::passed(&rule.name),
+                    Err(error) => RuleOutcome::failed(
+                        &rule.name,
+                        vec![format!(
+                            "Rule '{}' could not be evaluated: {}",
+                            rule.name, error
+                        )],
+                    ),
+                },
+                _ => match evaluator.evaluate(&expr, input) {
+                    Ok(value) if value.is_truthy() => RuleOutcome::passed(&rule.name),
+                    Ok(_) => {
+                        let message = match &expr {
+                            Expr::Require { message, .. } => message.clone(),
+                            _ => format!("Rule '{}' failed", rule.name),
+                        };
+                        RuleOutcome::failed(&rule.name, vec![message])
+                    }
+                    Err(error) => RuleOutcome::failed(
+                        &rule.name,
+                        vec![format!(
+                            "Rule '{}' could not be evaluated: {}",
+                            rule.name, error
+                        )],
+                    ),
+                },
+            };
+            report.push(outcome);
+        }
+        Ok(report)
+    }
+
+    /// Size and compile-cost estimate of each rule's generated helper body,
+    /// in declaration order
+    ///
+    /// References to other rules count as calls of their helpers, as in
+    /// [`generate_validator`](Self::generate_validator).
+    pub fn code_sizes(&self) -> Vec<(String, SizeReport)> {
+        let names = self.rule_names();
+        self.rules
+            .iter()
+            .map(|rule| {
+                let mut visitor = CodegenVisitor::new()
+                    .with_input_fields()
+                    .with_rule_references(names.clone());
+                (
+                    rule.name.clone(),
+                    size::report(&visitor.visit_expr(&rule.expr)),
+                )
+            })
+            .collect()
+    }
+
+    /// Generate a validator function that checks every rule in the set
+    ///
+    /// Each rule is emitted as a `rule_<name>(input) -> bool` helper; rule
+    /// references become helper calls. When a composite rule fails, its error
+    /// message lists the failing base rules it depends on. The helper of a
+    /// [duplicate rule](Self::duplicate_rules) calls the original's helper.
+    ///
+    /// If any rule is gated behind a feature flag, the validator takes a second
+    /// `flags: &dyn FlagProvider` argument and skips the checks of rules whose
+    /// flag is disabled. Composite rules referencing a gated rule still
+    /// evaluate it.
+    ///
+    /// Sampled rules are preceded by a hash check on the sampling key, so the
+    /// rule body only runs for the configured share of inputs.
+    ///
+    /// Alongside `<fn_name>`, a `<fn_name>_report` function is generated that
+    /// returns a [`ValidationReport`](crate::runtime::report::ValidationReport)
+    /// with the outcome of every rule: flag-disabled and sampled-out rules are
+    /// reported as skipped, and rules whose body is a `warn_if` are reported
+    /// as warned instead of failing. `<fn_name>` is a wrapper over the report
+    /// returning `Result<(), ValidationErrors>`, like the validators of
+    /// [`RustCodeGenerator`](crate::codegen::RustCodeGenerator).
+    /// Per-rule durations are recorded when the set is built
+    /// [`with_timing`](Self::with_timing).
+    ///
+    /// `<fn_name>_incremental` builds a
+    /// [`RuleSetValidator`](crate::runtime::incremental::RuleSetValidator)
+    /// that knows each rule's [field dependencies](Self::field_dependencies),
+    /// for re-validating only the rules affected by an edit. For flagged rule
+    /// sets it takes the flag provider as an `Arc`.
+    ///
+    /// With [`with_fast_path`](Self::with_fast_path), `<fn_name>_fast_path`
+    /// returns whether the input passes all [consolidated range
+    /// checks](Self::consolidated_checks); `false` means some rule fails.
+    pub fn generate_validator(
+        &self,
+        fn_name: &str,
+        input_type: &str,
+    ) -> Result<TokenStream, RuleSetError> {
+        let order = self.evaluation_order()?;
+        let input_ident = format_ident!("{}", input_type);
+        let constants: HashMap<String, bool> =
+            self.input_independent_rules()?.into_iter().collect();
+        let aliases: HashMap<String, String> = self
+            .duplicate_rules()
+            .into_iter()
+            .map(|duplicate| (duplicate.rule, duplicate.duplicate_of))
+            .collect();
+
+        let helpers: Vec<TokenStream> = order
+            .iter()
+            .filter_map(|name| self.get(name))
+            .map(|rule| {
+                let helper = rule_fn_ident(&rule.name);
+                if let Some(value) = constants.get(&rule.name) {
+                    let constant = rule_const_ident(&rule.name);
+                    let note = format!(
+                        "rule '{}' does not depend on the input; it is always {}",
+                        rule.name, value
+                    );
+                    return quote! {
+                        #[deprecated(note = #note)]
+                        const #constant: bool = #value;
+
+                        #[allow(unused_variables, dead_code, deprecated)]
+                        fn #helper(input: &#input_ident) -> bool {
+                            #constant
+                        }
+                    };
+                }
+                if let Some(original) = aliases.get(&rule.name) {
+                    let original = rule_fn_ident(original);
+                    return quote! {
+                        #[allow(dead_code)]
+                        fn #helper(input: &#input_ident) -> bool {
+                            #original(input)
+                        }
+                    };
+                }
+                let body = self.codegen_visitor(input_type).visit_expr(&rule.expr);
+                quote! {
+                    #[allow(unused_variables, dead_code)]
+                    fn #helper(input: &#input_ident) -> bool {
+                        #body
+                    }
+                }
+            })
+            .collect();
+
+        let mut checks = Vec::with_capacity(self.rules.len());
+        for rule in &self.rules {
+            let helper = rule_fn_ident(&rule.name);
+            let rule_name = rule.name.as_str();
+            let bases = self.base_rules(&rule.name)?;
+            // Reading the constant directly surfaces its deprecation warning
+            let holds = if constants.contains_key(&rule.name) {
+                let constant = rule_const_ident(&rule.name);
+                quote! { #constant }
+            } else {
+                quote! { #helper(input) }
+            };
+            let check = if let Expr::WarnIf { condition, message } = &rule.expr {
+                let condition = self.codegen_visitor(input_type).visit_expr(condition);
+                quote! {
+                    if #condition {
+                        elo_rust::runtime::report::RuleOutcome::warned(
+                            #rule_name,
+                            vec![#message.to_string()],
+                        )
+                    } else {
+                        elo_rust::runtime::report::RuleOutcome::passed(#rule_name)
+                    }
+                }
+            } else if bases.is_empty() {
+                quote! {
+                    if #holds {
+                        elo_rust::runtime::report::RuleOutcome::passed(#rule_name)
+                    } else {
+                        elo_rust::runtime::report::RuleOutcome::failed(
+                            #rule_name,
+                            vec![format!("Rule '{}' failed", #rule_name)],
+                        )
+                    }
+                }
+            } else {
+                let base_checks = bases.iter().map(|base| {
+                    let base_helper = rule_fn_ident(base);
+                    let base_name = base.as_str();
+                    quote! {
+                        if !#base_helper(input) {
+                            causes.push(#base_name);
+                        }
+                    }
+                });
+                quote! {
+                    if #holds {
+                        elo_rust::runtime::report::RuleOutcome::passed(#rule_name)
+                    } else {
+                        let mut causes: Vec<&str> = Vec::new();
+                        #(#base_checks)*
+                        let message = if causes.is_empty() {
+                            format!("Rule '{}' failed", #rule_name)
+                        } else {
+                            format!(
+                                "Rule '{}' failed (caused by: {})",
+                                #rule_name,
+                                causes.join(", ")
+                            )
+                        };
+                        elo_rust::runtime::report::RuleOutcome::failed(#rule_name, vec![message])
+                    }
+                }
+            };
+            let check = if self.timed {
+                quote! {
+                    {
+                        let started = std::time::Instant::now();
+                        let outcome = #check;
+                        outcome.with_duration(started.elapsed())
+                    }
+                }
+            } else {
+                check
+            };
+            let check = match &rule.sampling {
+                Some(sampling) => {
+                    let segments = sampling
+                        .key
+                        .split('.')
+                        .map(|segment| format_ident!("{}", segment));
+                    let percent = float_literal(sampling.percent);
+                    quote! {
+                        if elo_rust::runtime::sampling::is_sampled(
+                            #rule_name,
+                            &input #(.#segments)*,
+                            #percent,
+                        ) {
+                            #check
+                        } else {
+                            elo_rust::runtime::report::RuleOutcome::skipped(
+                                #rule_name,
+                                elo_rust::runtime::report::SkipReason::NotSampled,
+                            )
+                        }
+                    }
+                }
+                None => check,
+            };
+            let check = match &rule.window {
+                Some(window) => {
+                    let not_yet = window.effective_from.map(|from| {
+                        let from = from.timestamp();
+                        quote! {
+                            if now < #from {
+                                elo_rust::runtime::report::RuleOutcome::skipped(
+                                    #rule_name,
+                                    elo_rust::runtime::report::SkipReason::NotYetEffective,
+                                )
+                            } else
+                        }
+                    });
+                    let expired = window.expires_at.map(|expires| {
+                        let expires = expires.timestamp();
+                        quote! {
+                            if now >= #expires {
+                                elo_rust::runtime::report::RuleOutcome::skipped(
+                                    #rule_name,
+                                    elo_rust::runtime::report::SkipReason::Expired,
+                                )
+                            } else
+                        }
+                    });
+                    quote! {
+                        {
+                            let now = clock.now().timestamp();
+                            #not_yet #expired {
+                                #check
+                            }
+                        }
+                    }
+                }
+                None => check,
+            };
+            let check = match &rule.flag {
+                Some(flag) => quote! {
+                    if flags.is_enabled(#flag) {
+                        #check
+                    } else {
+                        elo_rust::runtime::report::RuleOutcome::skipped(
+                            #rule_name,
+                            elo_rust::runtime::report::SkipReason::FlagDisabled(
+                                #flag.to_string(),
+                            ),
+                        )
+                    }
+                },
+                None => check,
+            };
+            checks.push(check);
+        }
+
+        let fn_ident = format_ident!("{}", fn_name);
+        let report_ident = format_ident!("{}_report", fn_name);
+        let incremental_ident = format_ident!("{}_incremental", fn_name);
+        let flagged = self.rules.iter().any(|r| r.flag.is_some());
+        let windowed = self.rules.iter().any(|r| r.window.is_some());
+        let (mut flags_param, mut flags_arg) = (TokenStream::new(), TokenStream::new());
+        // The incremental validator's rule closures outlive the call, so they
+        // share the flag provider and clock through an Arc
+        let mut shared_params = Vec::new();
+        if flagged {
+            flags_param.extend(quote! { , flags: &dyn elo_rust::runtime::flags::FlagProvider });
+            flags_arg.extend(quote! { , flags });
+            shared_params.push(quote! {
+                flags: std::sync::Arc<dyn elo_rust::runtime::flags::FlagProvider + Send + Sync>
+            });
+        }
+        if windowed {
+            flags_param.extend(quote! { , clock: &dyn elo_rust::runtime::clock::Clock });
+            flags_arg.extend(quote! { , clock });
+            shared_params.push(quote! {
+                clock: std::sync::Arc<dyn elo_rust::runtime::clock::Clock + Send + Sync>
+            });
+        }
+        let shared_flags_param = quote! { #(#shared_params),* };
+        let mut incremental_rules = Vec::with_capacity(self.rules.len());
+        for (rule, check) in self.rules.iter().zip(&checks) {
+            let rule_name = rule.name.as_str();
+            let fields = self
+                .field_dependencies(&rule.name)?
+                .into_iter()
+                .map(|path| path.to_string());
+            // Each closure captures only what its own check reads
+            let mut capture_flags = TokenStream::new();
+            if rule.flag.is_some() {
+                capture_flags.extend(quote! { let flags = flags.clone(); });
+            }
+            if rule.window.is_some() {
+                capture_flags.extend(quote! { let clock = clock.clone(); });
+            }
+            incremental_rules.push(quote! {
+                .with_rule(
+                    #rule_name,
+                    vec![#(elo_rust::ast::analysis::FieldPath::from(#fields)),*],
+                    {
+                        #capture_flags
+                        #[allow(unused_variables)]
+                        let check = move |input: &#input_ident| #check;
+                        check
+                    },
+                )
+            });
+        }
+
+        let fast_path = if self.fast_path {
+            let fast_path_ident = format_ident!("{}_fast_path", fn_name);
+            let condition = self
+                .consolidated_checks()
+                .into_iter()
+                .map(|(field, interval)| interval.to_expr(&field))
+                .reduce(|left, right| Expr::BinaryOp {
+                    op: BinaryOperator::And,
+                    left: Box::new(left),
+                    right: Box::new(right),
+                })
+                .unwrap_or(Expr::Literal(Literal::Boolean(true)));
+            let condition = self.codegen_visitor(input_type).visit_expr(&condition);
+            quote! {
+                #[allow(unused_variables)]
+                pub fn #fast_path_ident(input: &#input_ident) -> bool {
+                    #condition
+                }
+            }
+        } else {
+            TokenStream::new()
+        };
+
+        Ok(quote! {
+            #(#helpers)*
+
+            #fast_path
+
+            pub fn #report_ident(
+                input: &#input_ident #flags_param
+            ) -> elo_rust::runtime::report::ValidationReport {
+                let mut report = elo_rust::runtime::report::ValidationReport::new();
+                #(report.push(#checks);)*
+                report
+            }
+
+            pub fn #fn_ident(
+                input: &#input_ident #flags_param
+            ) -> Result<(), elo_rust::runtime::ValidationErrors> {
+                #report_ident(input #flags_arg).into_result()
+            }
+
+            pub fn #incremental_ident(
+                #shared_flags_param
+            ) -> elo_rust::runtime::incremental::RuleSetValidator<#input_ident> {
+                elo_rust::runtime::incremental::RuleSetValidator::new()
+                    #(#incremental_rules)*
+            }
+        })
+    }
+
+    /// Code generator for rule bodies reading the fields of `input_type`
+    fn codegen_visitor(&self, input_type: &str) -> CodegenVisitor {
+        CodegenVisitor::new()
+            .with_input_fields()
+            .with_type_context(self.type_context.clone(), input_type)
+            .with_rule_references(self.rule_names())
+    }
+
+    /// Rules whose body is a range check, with its field and interval
+    fn interval_rules(&self) -> Vec<(&Rule, FieldPath, Interval)> {
+        let names = self.rule_names();
+        self.rules
+            .iter()
+            .filter_map(|rule| {
+                let (field, interval) = Interval::of(&rule.expr)?;
+                // A rule reference is not a field
+                (!names.contains(field.root())).then_some((rule, field, interval))
+            })
+            .collect()
+    }
+
+    fn rule_names(&self) -> HashSet<String> {
+        self.rules.iter().map(|r| r.name.clone()).collect()
+    }
+
+    fn visit<'a>(
+        &'a self,
+        name: &'a str,
+        state: &mut HashMap<&'a str, VisitState>,
+        path: &mut Vec<String>,
+        order: &mut Vec<String>,
+    ) -> Result<(), RuleSetError> {
+        match state.get(name) {
+            Some(VisitState::Done) => return Ok(()),
+            Some(VisitState::InProgress) => {
+                let start = path.iter().position(|n| n == name).unwrap_or(0);
+                let mut cycle: Vec<String> = path[start..].to_vec();
+                cycle.push(name.to_string());
+                return Err(RuleSetError::CyclicDependency(cycle));
+            }
+            None => {}
+        }
+
+        state.insert(name, VisitState::InProgress);
+        path.push(name.to_string());
+        for dep in self.dependencies(name)? {
+            let dep_name = self
+                .get(&dep)
+                .map(|r| r.name.as_str())
+                .ok_or_else(|| RuleSetError::UnknownRule(dep.clone()))?;
+            self.visit(dep_name, state, path, order)?;
+        }
+        path.pop();
+        state.insert(name, VisitState::Done);
+        order.push(name.to_string());
+        Ok(())
+    }
+
+    fn inline_expr(&self, expr: &Expr, bound: &mut HashSet<String>) -> Expr {
+        match expr {
+            Expr::Identifier(name) if !bound.contains(name) => match self.get(name) {
+                Some(rule) => self.inline_expr(&rule.expr, &mut HashSet::new()),
+                None => expr.clone(),
+            },
+            Expr::FieldAccess { receiver, field } => Expr::FieldAccess {
+                receiver: Box::new(self.inline_expr(receiver, bound)),
+                field: field.clone(),
+            },
+            Expr::Index { collection, index } => Expr::Index {
+                collection: Box::new(self.inline_expr(collection, bound)),
+                index: Box::new(self.inline_expr(index, bound)),
+            },
+            Expr::BinaryOp { op, left, right } => Expr::BinaryOp {
+                op: *op,
+                left: Box::new(self.inline_expr(left, bound)),
+                right: Box::new(self.inline_expr(right, bound)),
+            },
+            Expr::UnaryOp { op, operand } => Expr::UnaryOp {
+                op: *op,
+                operand: Box::new(self.inline_expr(operand, bound)),
+            },
+            Expr::FunctionCall { name, args } => Expr::FunctionCall {
+                name: name.clone(),
+                args: args.iter().map(|a| self.inline_expr(a, bound)).collect(),
+            },
+            Expr::Lambda { param, body } => {
+                let newly_bound = bound.insert(param.clone());
+                let body = self.inline_expr(body, bound);
+                if newly_bound {
+                    bound.remove(param);
+                }
+                Expr::Lambda {
+                    param: param.clone(),
+                    body: Box::new(body),
+                }
+            }
+            Expr::Let { name, value, body } => {
+                let value = self.inline_expr(value, bound);
+                let newly_bound = bound.insert(name.clone());
+                let body = self.inline_expr(body, bound);
+                if newly_bound {
+                    bound.remove(name);
+                }
+                Expr::Let {
+                    name: name.clone(),
+                    value: Box::new(value),
+                    body: Box::new(body),
+                }
+            }
+            Expr::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => Expr::If {
+                condition: Box::new(self.inline_expr(condition, bound)),
+                then_branch: Box::new(self.inline_expr(then_branch, bound)),
+                else_branch: Box::new(self.inline_expr(else_branch, bound)),
+            },
+            Expr::Array(elements) => Expr::Array(
+                elements
+                    .iter()
+                    .map(|e| self.inline_expr(e, bound))
+                    .collect(),
+            ),
+            Expr::Object(fields) => Expr::Object(
+                fields
+                    .iter()
+                    .map(|(k, v)| (k.clone(), self.inline_expr(v, bound)))
+                    .collect(),
+            ),
+            Expr::Pipe { value, functions } => Expr::Pipe {
+                value: Box::new(self.inline_expr(value, bound)),
+                functions: functions
+                    .iter()
+                    .map(|f| match f {
+                        // A bare identifier stage names a function, not a rule
+                        Expr::Identifier(_) => f.clone(),
+                        _ => self.inline_expr(f, bound),
+                    })
+                    .collect(),
+            },
+            Expr::Alternative {
+                primary,
+                alternative,
+            } => Expr::Alternative {
+                primary: Box::new(self.inline_expr(primary, bound)),
+                alternative: Box::new(self.inline_expr(alternative, bound)),
+            },
+            Expr::Guard { condition, body } => Expr::Guard {
+                condition: Box::new(self.inline_expr(condition, bound)),
+                body: Box::new(self.inline_expr(body, bound)),
+            },
+            Expr::NullDefault { value, default } => Expr::NullDefault {
+                value: Box::new(self.inline_expr(value, bound)),
+                default: Box::new(self.inline_expr(default, bound)),
+            },
+            Expr::Range {
+                start,
+                end,
+                inclusive,
+            } => Expr::Range {
+                start: Box::new(self.inline_expr(start, bound)),
+                end: Box::new(self.inline_expr(end, bound)),
+                inclusive: *inclusive,
+            },
+            Expr::Require { condition, message } => Expr::Require {
+                condition: Box::new(self.inline_expr(condition, bound)),
+                message: message.clone(),
+            },
+            Expr::WarnIf { condition, message } => Expr::WarnIf {
+                condition: Box::new(self.inline_expr(condition, bound)),
+                message: message.clone(),
+            },
+            other => other.clone(),
+        }
+    }
+}
+
+/// Check if a rule is checked for every input
+fn is_unconditional(rule: &Rule) -> bool {
+    rule.flag.is_none() && rule.sampling.is_none() && rule.window.is_none()
+}
+
+/// Split `<name> [attr = "value", ...]: <body>` into name, flag and body
+fn parse_declaration(
+    decl: &str,
+    line: usize,
+) -> Result<(String, RuleAttributes, String), RuleSetError> {
+    let invalid = |message: String| RuleSetError::InvalidDeclaration { line, message };
+
+    let (head, attributes, rest) = match decl.find('[') {
+        Some(open) if decl[..open].find(':').is_none() => {
+            let close = decl[open..]
+                .find(']')
+                .map(|i| open + i)
+                .ok_or_else(|| invalid("unclosed '[' in rule attributes".to_string()))?;
+            (
+                &decl[..open],
+                Some(&decl[open + 1..close]),
+                &decl[close + 1..],
+            )
+        }
+        _ => ("", None, decl),
+    };
+    let (name_part, body) = rest
+        .split_once(':')
+        .ok_or_else(|| invalid("expected ':' after rule name".to_string()))?;
+    let name = if attributes.is_some() {
+        if !name_part.trim().is_empty() {
+            return Err(invalid("expected ':' after rule attributes".to_string()));
+        }
+        head.trim()
+    } else {
+        name_part.trim()
+    };
+    if !is_valid_rule_name(name) {
+        return Err(invalid(format!("invalid rule name '{}'", name)));
+    }
+
+    let mut attrs = RuleAttributes::default();
+    let mut sample_percent = None;
+    let (mut effective_from, mut expires_at) = (None, None);
+    for attribute in attributes
+        .into_iter()
+        .flat_map(|a| a.split(','))
+        .filter(|a| !a.trim().is_empty())
+    {
+        let (key, value) = attribute.split_once('=').ok_or_else(|| {
+            invalid(format!(
+                "expected 'key = \"value\"', got '{}'",
+                attribute.trim()
+            ))
+        })?;
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .ok_or_else(|| invalid(format!("attribute value {} must be quoted", value)))?;
+        match key.trim() {
+            "flag" => attrs.flag = Some(value.to_string()),
+            "sample" => {
+                let percent = value
+                    .strip_suffix('%')
+                    .unwrap_or(value)
+                    .trim()
+                    .parse::<f64>()
+                    .map_err(|_| invalid(format!("invalid sample rate '{}'", value)))?;
+                sample_percent = Some(percent);
+            }
+            "key" => attrs.sample_key = Some(value.to_string()),
+            "effective_from" => effective_from = Some(value),
+            "expires_at" => expires_at = Some(value),
+            other => return Err(invalid(format!("unknown rule attribute '{}'", other))),
+        }
+    }
+    match (sample_percent, attrs.sample_key.take()) {
+        (Some(percent), Some(key)) => {
+            attrs.sampling = Some(Sampling::new(percent, key).map_err(invalid)?);
+        }
+        (Some(_), None) => return Err(invalid("'sample' requires a 'key' attribute".to_string())),
+        (None, Some(_)) => return Err(invalid("'key' is only valid with 'sample'".to_string())),
+        (None, None) => {}
+    }
+    if effective_from.is_some() || expires_at.is_some() {
+        attrs.window = Some(EffectiveWindow::parse(effective_from, expires_at).map_err(invalid)?);
+    }
+
+    Ok((name.to_string(), attrs, body.trim().to_string()))
+}
+
+/// Identifier of the generated helper function for a rule
+pub fn rule_fn_ident(name: &str) -> Ident {
+    format_ident!("rule_{}", name)
+}
+
+/// Identifier of the generated constant for an input-independent rule
+fn rule_const_ident(name: &str) -> Ident {
+    format_ident!("RULE_{}_RESULT", name.to_uppercase())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VisitState {
+    InProgress,
+    Done,
+}
+
+fn is_valid_rule_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_alphabetic() || c == '_' => {}
+        _ => return false,
+    }
+    chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checkout_rules() -> RuleSet {
+        RuleSet::parse(
+            "# checkout policy\n\
+             rule is_adult: age >= 18\n\
+             rule has_payment_method: payment_count > 0\n\
+             rule can_checkout: is_adult && has_payment_method",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_rule_file() {
+        let rules = checkout_rules();
+        assert_eq!(rules.len(), 3);
+        assert_eq!(rules.rules()[0].name, "is_adult");
+        assert_eq!(
+            rules.get("can_checkout").unwrap().source,
+            "is_adult && has_payment_method"
+        );
+    }
+
+    #[test]
+    fn test_duplicate_rules_are_aliased() {
+        let rules = RuleSet::parse(
+            "rule is_adult: age >= 18\n\
+             rule can_vote: age >= 10 + 8\n\
+             rule can_drive: age >= 18 && (age >= 18 || licensed)\n\
+             rule has_name: length(name) > 0",
+        )
+        .unwrap();
+        assert_eq!(
+            rules
+                .duplicate_rules()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec![
+                "rule 'can_vote' is identical to rule 'is_adult'",
+                "rule 'can_drive' is identical to rule 'is_adult'",
+            ]
+        );
+
+        let code = rules
+            .generate_validator("validate", "Person")
+            .unwrap()
+            .to_string();
+        assert!(
+            code.contains("fn rule_can_vote (input : & Person) -> bool { rule_is_adult (input) }")
+        );
+        assert!(
+            code.contains("fn rule_can_drive (input : & Person) -> bool { rule_is_adult (input) }")
+        );
+    }
+
+    #[test]
+    fn test_redundant_checks() {
+        let rules = RuleSet::parse(
+            "rule is_adult: age >= 18\n\
+             rule old_enough: age >= 16\n\
+             rule not_retired: age < 65\n\
+             rule adult_again: 18 <= age\n\
+             rule premium [flag = \"vip\"]: age >= 30\n\
+             rule can_checkout: is_adult && total > 0",
+        )
+        .unwrap();
+        let redundant = rules.redundant_checks();
+        let redundant: Vec<(&str, &str)> = redundant
+            .iter()
+            .map(|r| (r.rule.as_str(), r.implied_by.as_str()))
+            .collect();
+        // The flagged rule is not always checked, so it implies nothing
+        assert_eq!(
+            redundant,
+            vec![("old_enough", "is_adult"), ("adult_again", "is_adult")]
+        );
+    }
+
+    #[test]
+    fn test_fast_path() {
+        let rules = RuleSet::parse(
+            "rule is_adult: age >= 18\n\
+             rule not_retired: age < 65\n\
+             rule old_enough: age >= 16\n\
+             rule positive: order.total > 0\n\
+             rule sampled [sample = \"10%\", key = \"id\"]: order.total > 100",
+        )
+        .unwrap()
+        .with_fast_path(true);
+        let checks: Vec<String> = rules
+            .consolidated_checks()
+            .iter()
+            .map(|(field, interval)| format!("{} in {}", field, interval))
+            .collect();
+        assert_eq!(checks, vec!["age in [18, 65)", "order.total in (0, +inf)"]);
+
+        let code = rules
+            .generate_validator("validate", "Order")
+            .unwrap()
+            .to_string();
+
+        assert!(code.contains(
+            "pub fn validate_fast_path (input : & Order) -> bool { input . age >= 18i64 && input . age < 65i64 && input . order . total > 0i64 }"
+        ));
+        let plain = RuleSet::parse("rule is_adult: age >= 18").unwrap();
+        let code = plain.generate_validator("validate", "User").unwrap();
+        assert!(!code.to_string().contains("fast_path"));
+    }
+
+    #[test]
+    fn test_check_with_evaluator() {
+        let rules = RuleSet::parse(
+            "rule is_adult: age >= 18\n\
+             rule has_email: require contains(email, '@') else 'email is invalid'\n\
+             rule low_score: warn_if score < 10 : 'score is low'\n\
+             rule can_signup: is_adult && has_email\n\
+             rule numeric: age + email > 0",
+        )
+        .unwrap();
+        let input = EloValue::Object(BTreeMap::from([
+            ("age".to_string(), EloValue::Integer(16)),
+            ("email".to_string(), EloValue::String("nobody".to_string())),
+            ("score".to_string(), EloValue::Integer(3)),
+        ]));
+
+        let report = rules.check(&input).unwrap();
+        assert!(!report.is_ok());
+        let messages: Vec<(&str, String)> = report
+            .failed()
+            .chain(report.warned())
+            .map(|outcome| (outcome.rule.as_str(), outcome.errors[0].message.clone()))
+            .collect();
+        assert_eq!(
+            messages[0],
+            ("is_adult", "Rule 'is_adult' failed".to_string())
+        );
+        assert_eq!(messages[1], ("has_email", "email is invalid".to_string()));
+        assert_eq!(messages[2].0, "can_signup");
+        assert!(messages[3]
+            .1
+            .starts_with("Rule 'numeric' could not be evaluated"));
+        assert_eq!(messages[4], ("low_score", "score is low".to_string()));
+    }
+
+    #[test]
+    fn test_extends_with_override_and_disable() {
+        let base = checkout_rules();
+        let mut tenant = RuleSet::extends(&base);
+        tenant.override_rule("is_adult", "age >= 21").unwrap();
+        tenant.add_rule("is_verified", "kyc == 'ok'").unwrap();
+
+        // Overrides keep the rule's position and are seen by dependents
+        assert_eq!(tenant.rules()[0].name, "is_adult");
+        assert_eq!(
+            tenant
+                .inline("can_checkout")
+                .unwrap()
+                .free_variables()
+                .len(),
+            2
+        );
+        assert!(tenant
+            .inline("can_checkout")
+            .unwrap()
+            .free_variables()
+            .contains("age"));
+        assert_eq!(base.get("is_adult").unwrap().source, "age >= 18");
+
+        let err = tenant.disable_rule("is_adult").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Rule 'is_adult' cannot be disabled: used by can_checkout"
+        );
+        tenant.disable_rule("can_checkout").unwrap();
+        tenant.disable_rule("is_adult").unwrap();
+        assert_eq!(tenant.len(), 2);
+
+        assert_eq!(
+            tenant.override_rule("missing", "true").unwrap_err(),
+            RuleSetError::UnknownRule("missing".to_string())
+        );
+    }
+
+    #[test]
+    fn test_bind_params() {
+        use crate::runtime::EloValue;
+
+        let rules = RuleSet::parse(
+            "rule within_limit: amount <= ${max_amount}\n\
+             rule can_pay: within_limit && verified",
+        )
+        .unwrap();
+        let mut params = Params::new();
+        params.insert("max_amount", EloValue::Integer(500));
+        let bound = rules.bind_params(&params).unwrap();
+        assert_eq!(
+            bound.inline("can_pay").unwrap(),
+            Parser::parse("amount <= 500 && verified").unwrap()
+        );
+
+        let err = rules.bind_params(&Params::new()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Rule 'within_limit': Unbound parameters: ${max_amount}"
+        );
+    }
+
+    #[test]
+    fn test_include_mixin() {
+        let mut rules = checkout_rules();
+        let mixin = RuleSet::parse("rule has_email: length(email) > 0").unwrap();
+        rules.include(&mixin).unwrap();
+        assert_eq!(rules.len(), 4);
+
+        let clash = RuleSet::parse("rule x: true\nrule is_adult: true").unwrap();
+        assert_eq!(
+            rules.include(&clash).unwrap_err(),
+            RuleSetError::DuplicateRule("is_adult".to_string())
+        );
+        assert!(rules.get("x").is_none());
+    }
+
+    #[test]
+    fn test_parse_multiline_rule() {
+        let rules = RuleSet::parse("rule a: x > 1\n  && y > 2\nrule b: a").unwrap();
+        assert_eq!(rules.len(), 2);
+        assert!(rules.get("a").unwrap().source.contains("y > 2"));
+    }
+
+    #[test]
+    fn test_parse_rejects_missing_colon() {
+        let err = RuleSet::parse("rule broken age >= 18").unwrap_err();
+        assert!(matches!(
+            err,
+            RuleSetError::InvalidDeclaration { line: 1, .. }
+        ));
+    }
+
+    #[test]
+    fn test_parse_edition_header() {
+        let rules =
+            RuleSet::parse("# policy\nedition = \"2024\"\nrule a: limit ?? 10 > 5").unwrap();
+        assert_eq!(rules.edition(), Edition::Edition2024);
+
+        let err = RuleSet::parse("edition = \"2023\"\nrule a: limit ?? 10 > 5").unwrap_err();
+        assert!(matches!(err, RuleSetError::Parse { ref rule, .. } if rule == "a"));
+
+        let mut old = RuleSet::parse("edition = \"2023\"\nrule a: x > 1").unwrap();
+        assert_eq!(old.edition(), Edition::Edition2023);
+        assert!(old.add_rule("b", "age in 18..65").is_err());
+
+        let err = RuleSet::parse("edition = \"1999\"\nrule a: x").unwrap_err();
+        assert!(matches!(
+            err,
+            RuleSetError::InvalidDeclaration { line: 1, .. }
+        ));
+    }
+
+    #[test]
+    fn test_duplicate_rule_rejected() {
+        let err = RuleSet::parse("rule a: x\nrule a: y").unwrap_err();
+        assert_eq!(err, RuleSetError::DuplicateRule("a".to_string()));
+    }
+
+    #[test]
+    fn test_code_sizes() {
+        let rules = RuleSet::parse(
+            "rule is_adult: age >= 18\n\
+             rule named: length(name) > 0 && contains(name, ' ') && matches(name, '^[A-Z]')\n\
+             rule ok: is_adult && named",
+        )
+        .unwrap();
+        let sizes = rules.code_sizes();
+        let names: Vec<&str> = sizes.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["is_adult", "named", "ok"]);
+        assert!(sizes[1].1.compile_cost() > sizes[0].1.compile_cost());
+        assert!(sizes[2].1.tokens < sizes[1].1.tokens);
+    }
+
+    #[test]
+    fn test_dependencies() {
+        let rules = checkout_rules();
+        assert_eq!(
+            rules.dependencies("can_checkout").unwrap(),
+            vec!["has_payment_method", "is_adult"]
+        );
+        assert!(rules.dependencies("is_adult").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_shadowed_reference_is_not_a_dependency() {
+        let rules = RuleSet::parse("rule a: x > 0\nrule b: let a = 1 in a > 0").unwrap();
+        assert!(rules.dependencies("b").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_evaluation_order() {
+        let rules = RuleSet::parse("rule c: a && b\nrule a: x > 0\nrule b: a || y").unwrap();
+        assert_eq!(rules.evaluation_order().unwrap(), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_cycle_detected() {
+        let rules = RuleSet::parse("rule a: b && x\nrule b: c\nrule c: a").unwrap();
+        let err = rules.evaluation_order().unwrap_err();
+        assert_eq!(
+            err,
+            RuleSetError::CyclicDependency(vec![
+                "a".to_string(),
+                "b".to_string(),
+                "c".to_string(),
+                "a".to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn test_self_reference_is_cycle() {
+        let rules = RuleSet::parse("rule a: a && x").unwrap();
+        assert!(matches!(
+            rules.check_cycles(),
+            Err(RuleSetError::CyclicDependency(_))
+        ));
+    }
+
+    #[test]
+    fn test_base_rules() {
+        let rules =
+            RuleSet::parse("rule a: x > 0\nrule b: y > 0\nrule c: a && z\nrule d: c && b").unwrap();
+        assert_eq!(rules.base_rules("d").unwrap(), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_inline() {
+        let rules = checkout_rules();
+        let inlined = rules.inline("can_checkout").unwrap();
+        let expected = Parser::parse("age >= 18 && payment_count > 0").unwrap();
+        assert_eq!(inlined, expected);
+    }
+
+    #[test]
+    fn test_generate_validator_calls_helpers() {
+        let rules = checkout_rules();
+        let code = rules
+            .generate_validator("validate", "Order")
+            .unwrap()
+            .to_string();
+        assert!(code.contains("fn rule_is_adult"));
+        assert!(code.contains("rule_is_adult (input) && rule_has_payment_method (input)"));
+        assert!(code.contains("caused by"));
+        assert!(code.contains("pub fn validate"));
+    }
+
+    #[test]
+    fn test_parse_flag_attribute() {
+        let rules = RuleSet::parse(
+            "rule is_adult: age >= 18\n\
+             rule kyc_verified [flag = \"new_kyc\"]: kyc_status == 'verified'",
+        )
+        .unwrap();
+        assert_eq!(rules.get("is_adult").unwrap().flag, None);
+        assert_eq!(
+            rules.get("kyc_verified").unwrap().flag.as_deref(),
+            Some("new_kyc")
+        );
+        assert_eq!(rules.flags(), vec!["new_kyc"]);
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_attributes() {
+        for source in [
+            "rule a [owner = \"x\"]: b",
+            "rule a [flag = x]: b",
+            "rule a [flag = \"x\": b",
+            "rule a [flag = \"x\"] extra: b",
+        ] {
+            assert!(
+                matches!(
+                    RuleSet::parse(source),
+                    Err(RuleSetError::InvalidDeclaration { line: 1, .. })
+                ),
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn test_generate_validator_with_flags() {
+        let mut rules = checkout_rules();
+        rules.set_flag("has_payment_method", "payments_v2").unwrap();
+        let code = rules
+            .generate_validator("validate", "Order")
+            .unwrap()
+            .to_string();
+        assert!(code.contains(
+            "pub fn validate (input : & Order , flags : & dyn elo_rust :: runtime :: flags :: FlagProvider)"
+        ));
+        assert!(code.contains("if flags . is_enabled (\"payments_v2\")"));
+        assert!(rules.set_flag("missing", "x").is_err());
+    }
+
+    #[test]
+    fn test_generate_validator_without_flags_keeps_signature() {
+        let code = checkout_rules()
+            .generate_validator("validate", "Order")
+            .unwrap()
+            .to_string();
+        assert!(code.contains("pub fn validate (input : & Order)"));
+    }
+
+    #[test]
+    fn test_parse_sampling_attributes() {
+        let rules =
+            RuleSet::parse("rule fraud_ok [sample = \"12.5%\", key = \"account.id\"]: score < 80")
+                .unwrap();
+        let sampling = rules.get("fraud_ok").unwrap().sampling.clone().unwrap();
+        assert_eq!(sampling.percent, 12.5);
+        assert_eq!(sampling.key, "account.id");
+
+        for source in [
+            "rule a [sample = \"10%\"]: b",
+            "rule a [key = \"id\"]: b",
+            "rule a [sample = \"150%\", key = \"id\"]: b",
+            "rule a [sample = \"ten\", key = \"id\"]: b",
+            "rule a [sample = \"10%\", key = \"bad key\"]: b",
+        ] {
+            assert!(RuleSet::parse(source).is_err(), "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_generate_validator_with_sampling() {
+        let mut rules = checkout_rules();
+        rules.set_sampling("is_adult", 10.0, "user.id").unwrap();
+        let code = rules
+            .generate_validator("validate", "Order")
+            .unwrap()
+            .to_string();
+        assert!(code.contains(
+            "if elo_rust :: runtime :: sampling :: is_sampled (\"is_adult\" , & input . user . id , 10f64 ,)"
+        ));
+        assert!(matches!(
+            rules.set_sampling("is_adult", -1.0, "id"),
+            Err(RuleSetError::InvalidSampling { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_window_attributes() {
+        let rules = RuleSet::parse(
+            "rule new_limit [effective_from = \"2025-01-01\", expires_at = \"2026-01-01T00:00:00Z\"]: amount <= 500",
+        )
+        .unwrap();
+        let window = rules.get("new_limit").unwrap().window.unwrap();
+        let at = |date| clock::parse_instant(date).unwrap();
+        assert_eq!(
+            window.skip_reason(at("2024-12-31")),
+            Some(SkipReason::NotYetEffective)
+        );
+        assert_eq!(window.skip_reason(at("2025-01-01")), None);
+        assert_eq!(
+            window.skip_reason(at("2026-01-01")),
+            Some(SkipReason::Expired)
+        );
+
+        for source in [
+            "rule a [expires_at = \"2025-13-01\"]: b",
+            "rule a [effective_from = \"2025-01-01\", expires_at = \"2024-01-01\"]: b",
+        ] {
+            assert!(RuleSet::parse(source).is_err(), "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_generate_validator_with_window() {
+        let mut rules = checkout_rules();
+        rules
+            .set_window("is_adult", None, Some("2025-01-01"))
+            .unwrap();
+        let code = rules
+            .generate_validator("validate", "Order")
+            .unwrap()
+            .to_string();
+        assert!(code.contains(
+            "pub fn validate (input : & Order , clock : & dyn elo_rust :: runtime :: clock :: Clock)"
+        ));
+        assert!(code.contains("if now >= 1735689600i64"));
+        assert!(code.contains("SkipReason :: Expired"));
+        assert!(!code.contains("NotYetEffective"));
+        assert!(matches!(
+            rules.set_window("is_adult", Some("soon"), None),
+            Err(RuleSetError::InvalidWindow { .. })
+        ));
+    }
+
+    #[test]
+    fn test_generate_validator_emits_report() {
+        let mut rules = RuleSet::parse(
+            "rule is_adult: age >= 18\n\
+             rule low_balance: warn_if balance < 10 : 'Balance is low'",
+        )
+        .unwrap();
+        rules.set_flag("is_adult", "adults").unwrap();
+        let code = rules
+            .generate_validator("validate", "Account")
+            .unwrap()
+            .to_string();
+        assert!(code.contains("pub fn validate_report (input : & Account , flags"));
+        assert!(code.contains(
+            "-> Result < () , elo_rust :: runtime :: ValidationErrors > { validate_report (input , flags) . into_result () }"
+        ));
+        assert!(code.contains("SkipReason :: FlagDisabled"));
+        assert!(code.contains("RuleOutcome :: warned (\"low_balance\""));
+        assert!(!code.contains("Instant"));
+
+        let code = rules
+            .with_timing(true)
+            .generate_validator("validate", "Account")
+            .unwrap()
+            .to_string();
+        assert!(code.contains("std :: time :: Instant :: now ()"));
+    }
+
+    #[test]
+    fn test_field_dependencies_follow_rule_references() {
+        let rules = RuleSet::parse(
+            "rule is_adult: user.age >= 18\n\
+             rule can_checkout: is_adult && cart.total > 0\n\
+             rule is_named: let n = user.name in length(n) > 0",
+        )
+        .unwrap();
+        assert_eq!(
+            rules.field_dependencies("can_checkout").unwrap(),
+            vec![FieldPath::from("cart.total"), FieldPath::from("user.age")]
+        );
+        let index = rules.field_index().unwrap();
+        assert_eq!(index["is_named"], vec![FieldPath::from("user.name")]);
+        assert!(rules.field_dependencies("missing").is_err());
+    }
+
+    #[test]
+    fn test_affected_rules() {
+        let rules = RuleSet::parse(
+            "rule is_adult: user.age >= 18\n\
+             rule is_named: length(user.name) > 0\n\
+             rule can_checkout: is_adult && cart.total > 0",
+        )
+        .unwrap();
+        let affected = |paths: &[&str]| {
+            let changed: Vec<FieldPath> = paths.iter().map(|p| FieldPath::from(*p)).collect();
+            rules.affected_rules(&changed).unwrap()
+        };
+        assert_eq!(affected(&["cart.total"]), vec!["can_checkout"]);
+        assert_eq!(
+            affected(&["user"]),
+            vec!["is_adult", "is_named", "can_checkout"]
+        );
+        assert_eq!(affected(&["user.name"]), vec!["is_named"]);
+        assert!(affected(&["unrelated"]).is_empty());
+    }
+
+    #[test]
+    fn test_generate_validator_emits_incremental_validator() {
+        let code = checkout_rules()
+            .generate_validator("validate", "Order")
+            .unwrap()
+            .to_string();
+        assert!(code.contains("pub fn validate_incremental ()"));
+        assert!(code.contains(". with_rule (\"is_adult\" , vec ! [elo_rust :: ast :: analysis :: FieldPath :: from (\"age\")]"));
+
+        let mut rules = checkout_rules();
+        rules.set_flag("is_adult", "adults").unwrap();
+        let code = rules
+            .generate_validator("validate", "Order")
+            .unwrap()
+            .to_string();
+        assert!(code.contains("pub fn validate_incremental (flags : std :: sync :: Arc <"));
+        assert!(code.contains("let flags = flags . clone () ;"));
+    }
+
+    #[test]
+    fn test_input_independent_rules_become_constants() {
+        let set = RuleSet::parse(
+            "rule is_adult: input.age >= 18\nrule always: 1 < 2\nrule combo: always && 3 > 4",
+        )
+        .unwrap();
+        assert_eq!(
+            set.input_independent_rules().unwrap(),
+            vec![("always".to_string(), true), ("combo".to_string(), false)]
+        );
+
+        let code = set
+            .generate_validator("validate", "User")
+            .unwrap()
+            .to_string();
+        assert!(code.contains("const RULE_ALWAYS_RESULT : bool = true"));
+        assert!(code.contains(
+            "# [deprecated (note = \"rule 'combo' does not depend on the input; it is always false\")]"
+        ));
+        assert!(code.contains("fn rule_is_adult"));
+        assert!(!code.contains("const RULE_IS_ADULT_RESULT"));
+    }
+
+    #[test]
+    fn test_generate_validator_rejects_cycles() {
+        let rules = RuleSet::parse("rule a: b\nrule b: a").unwrap();
+        assert!(rules.generate_validator("validate", "T").is_err());
+    }
+}
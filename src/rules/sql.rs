@@ -0,0 +1,519 @@
+//! Database CHECK constraints
+//!
+//! The SQL-representable subset of a [`RuleSet`](super::RuleSet) can be
+//! enforced by the database as well: [`RuleSet::check_constraints`]
+//! translates each rule into a `CHECK` condition over the columns of a
+//! table and [`CheckConstraints::to_ddl`] emits the `ALTER TABLE` statements
+//! for a [`SqlDialect`]. Fields map to columns of the same name unless a
+//! [`TableMapping`] says otherwise; nested fields must be mapped explicitly.
+//!
+//! Rules that cannot be expressed (lambdas, temporal keywords, `warn_if`,
+//! functions without an SQL counterpart, rules gated by a flag, sampling or
+//! effective window) are listed as unsupported instead. Note that SQL treats
+//! a `CHECK` condition that evaluates to `NULL` as satisfied, so a rule over
+//! a nullable column only constrains rows where the column is set.
+//!
+//! # Example
+//!
+//! ```
+//! use elo_rust::rules::sql::{SqlDialect, TableMapping};
+//! use elo_rust::rules::RuleSet;
+//!
+//! let rules = RuleSet::parse(
+//!     "rule is_adult: age >= 18\n\
+//!      rule has_email: contains(email, '@')",
+//! )
+//! .unwrap();
+//! let mapping = TableMapping::new("users").column("email", "email_address");
+//! let checks = rules.check_constraints(&mapping, SqlDialect::Postgres).unwrap();
+//!
+//! assert_eq!(
+//!     checks.to_ddl(),
+//!     "ALTER TABLE \"users\" ADD CONSTRAINT \"users_is_adult_check\" CHECK (\"age\" >= 18);\n\
+//!      ALTER TABLE \"users\" ADD CONSTRAINT \"users_has_email_check\" CHECK (STRPOS(\"email_address\", '@') > 0);\n"
+//! );
+//! ```
+//!
+//! [`RuleSet::check_constraints`]: super::RuleSet::check_constraints
+
+use crate::ast::{BinaryOperator, Expr, Literal, UnaryOperator};
+use crate::codegen::float::format_float;
+use crate::runtime::glob::{parse_glob, GlobToken};
+use std::collections::HashMap;
+
+/// SQL flavour to generate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqlDialect {
+    /// PostgreSQL
+    Postgres,
+    /// MySQL 8.0.16 or later (earlier versions parse but ignore `CHECK`)
+    MySql,
+}
+
+impl SqlDialect {
+    fn quote_ident(self, ident: &str) -> String {
+        match self {
+            SqlDialect::Postgres => format!("\"{}\"", ident.replace('"', "\"\"")),
+            SqlDialect::MySql => format!("`{}`", ident.replace('`', "``")),
+        }
+    }
+
+    fn quote_string(self, s: &str) -> String {
+        let escaped = s.replace('\'', "''");
+        match self {
+            SqlDialect::Postgres => format!("'{}'", escaped),
+            // MySQL treats backslashes in string literals as escapes
+            SqlDialect::MySql => format!("'{}'", escaped.replace('\\', "\\\\")),
+        }
+    }
+}
+
+/// Table a rule set is enforced on, and the columns its fields live in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableMapping {
+    table: String,
+    columns: HashMap<String, String>,
+}
+
+impl TableMapping {
+    /// Map fields to columns of the same name in `table`
+    pub fn new(table: impl Into<String>) -> Self {
+        Self {
+            table: table.into(),
+            columns: HashMap::new(),
+        }
+    }
+
+    /// Store `field` (a dotted path such as `address.country`) in `column`
+    pub fn column(mut self, field: impl Into<String>, column: impl Into<String>) -> Self {
+        self.columns.insert(field.into(), column.into());
+        self
+    }
+
+    /// Name of the table
+    pub fn table(&self) -> &str {
+        &self.table
+    }
+}
+
+/// A rule translated into a CHECK condition
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckConstraint {
+    /// The rule the constraint enforces
+    pub rule: String,
+    /// Constraint name, `<table>_<rule>_check`
+    pub name: String,
+    /// SQL condition
+    pub condition: String,
+}
+
+/// A rule with no SQL translation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsupportedRule {
+    /// Name of the rule
+    pub rule: String,
+    /// Why it cannot be translated
+    pub reason: String,
+}
+
+/// CHECK constraints generated for a rule set
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckConstraints {
+    /// Table the constraints apply to
+    pub table: String,
+    /// Dialect the conditions are written in
+    pub dialect: SqlDialect,
+    /// Translated rules, in declaration order
+    pub constraints: Vec<CheckConstraint>,
+    /// Rules left to application-level validation
+    pub unsupported: Vec<UnsupportedRule>,
+}
+
+impl CheckConstraints {
+    /// One `ALTER TABLE ... ADD CONSTRAINT` statement per constraint,
+    /// followed by a comment for each unsupported rule
+    pub fn to_ddl(&self) -> String {
+        let table = self.dialect.quote_ident(&self.table);
+        let mut ddl = String::new();
+        for constraint in &self.constraints {
+            ddl.push_str(&format!(
+                "ALTER TABLE {} ADD CONSTRAINT {} CHECK ({});\n",
+                table,
+                self.dialect.quote_ident(&constraint.name),
+                constraint.condition
+            ));
+        }
+        for rule in &self.unsupported {
+            ddl.push_str(&format!(
+                "-- rule '{}' not enforced: {}\n",
+                rule.rule, rule.reason
+            ));
+        }
+        ddl
+    }
+}
+
+// Operator precedence, loosest first
+const OR: u8 = 1;
+const AND: u8 = 2;
+const NOT: u8 = 3;
+const COMPARE: u8 = 4;
+const ADD: u8 = 5;
+const MUL: u8 = 6;
+const UNARY: u8 = 7;
+const ATOM: u8 = 8;
+
+/// Translates expressions into SQL for one table and dialect
+pub(super) struct SqlWriter<'a> {
+    pub(super) mapping: &'a TableMapping,
+    pub(super) dialect: SqlDialect,
+}
+
+impl SqlWriter<'_> {
+    /// Translate `expr` into an SQL condition, or explain why it cannot be
+    pub(super) fn condition(&self, expr: &Expr) -> Result<String, String> {
+        self.write(expr).map(|(sql, _)| sql)
+    }
+
+    /// Translate and parenthesize when looser than `min` precedence
+    fn operand(&self, expr: &Expr, min: u8) -> Result<String, String> {
+        let (sql, precedence) = self.write(expr)?;
+        Ok(if precedence < min {
+            format!("({})", sql)
+        } else {
+            sql
+        })
+    }
+
+    fn write(&self, expr: &Expr) -> Result<(String, u8), String> {
+        match expr {
+            Expr::Literal(Literal::Integer(n)) => Ok((n.to_string(), ATOM)),
+            Expr::Literal(Literal::Float(f)) => Ok((format_float(*f), ATOM)),
+            Expr::Percentage(percent) => Ok((format_float(percent / 100.0), ATOM)),
+            Expr::Literal(Literal::Boolean(b)) => {
+                Ok(((if *b { "TRUE" } else { "FALSE" }).to_string(), ATOM))
+            }
+            Expr::String(s) => Ok((self.dialect.quote_string(s), ATOM)),
+            Expr::Null => Ok(("NULL".to_string(), ATOM)),
+            Expr::Date(date) => Ok((format!("DATE {}", self.dialect.quote_string(date)), ATOM)),
+            Expr::DateTime(datetime) => Ok((
+                format!("TIMESTAMP {}", self.dialect.quote_string(datetime)),
+                ATOM,
+            )),
+            Expr::Identifier(_) | Expr::FieldAccess { .. } => Ok((self.column(expr)?, ATOM)),
+            Expr::BinaryOp { op, left, right } => self.binary(*op, left, right),
+            Expr::UnaryOp { op, operand } => match op {
+                UnaryOperator::Not => Ok((format!("NOT {}", self.operand(operand, NOT)?), NOT)),
+                UnaryOperator::Neg => Ok((format!("-{}", self.operand(operand, UNARY)?), UNARY)),
+                UnaryOperator::Plus => self.write(operand),
+            },
+            Expr::FunctionCall { name, args } => self.function(name, args),
+            Expr::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => Ok((
+                format!(
+                    "CASE WHEN {} THEN {} ELSE {} END",
+                    self.condition(condition)?,
+                    self.condition(then_branch)?,
+                    self.condition(else_branch)?
+                ),
+                ATOM,
+            )),
+            Expr::NullDefault { value, default } => Ok((
+                format!(
+                    "COALESCE({}, {})",
+                    self.condition(value)?,
+                    self.condition(default)?
+                ),
+                ATOM,
+            )),
+            Expr::Require { condition, .. } => self.write(condition),
+            Expr::WarnIf { .. } => Err("warn_if only warns and cannot be enforced".to_string()),
+            Expr::TemporalKeyword(_) => Err(
+                "temporal keywords depend on the current time, which CHECK constraints cannot read"
+                    .to_string(),
+            ),
+            Expr::Placeholder(name) => Err(format!("placeholder '{}' is not bound", name)),
+            Expr::Lambda { .. } | Expr::Pipe { .. } => {
+                Err("lambdas and pipes have no SQL equivalent".to_string())
+            }
+            Expr::Let { .. } => Err("let bindings have no SQL equivalent".to_string()),
+            Expr::Array(_) | Expr::Object(_) | Expr::Range { .. } => {
+                Err("collections are only supported on the right of 'in'".to_string())
+            }
+            Expr::Index { .. } => Err("array elements have no SQL equivalent".to_string()),
+            Expr::Alternative { .. } | Expr::Guard { .. } => {
+                Err("?| and guard have no SQL equivalent".to_string())
+            }
+            Expr::Quantity { .. } | Expr::Duration(_) => {
+                Err("quantities and durations have no SQL equivalent".to_string())
+            }
+        }
+    }
+
+    fn column(&self, expr: &Expr) -> Result<String, String> {
+        let path = field_path(expr)
+            .ok_or_else(|| "only fields of the input can be accessed".to_string())?;
+        match (self.mapping.columns.get(&path), expr) {
+            (Some(column), _) => Ok(self.dialect.quote_ident(column)),
+            (None, Expr::Identifier(name)) => Ok(self.dialect.quote_ident(name)),
+            (None, _) => Err(format!("field '{}' is not mapped to a column", path)),
+        }
+    }
+
+    fn binary(
+        &self,
+        op: BinaryOperator,
+        left: &Expr,
+        right: &Expr,
+    ) -> Result<(String, u8), String> {
+        let infix = |sql_op: &str, precedence: u8| -> Result<(String, u8), String> {
+            // AND and OR are associative; other operators need parentheses
+            // around an equal-precedence right operand
+            let right_min = if precedence <= AND {
+                precedence
+            } else {
+                precedence + 1
+            };
+            Ok((
+                format!(
+                    "{} {} {}",
+                    self.operand(left, precedence)?,
+                    sql_op,
+                    self.operand(right, right_min)?
+                ),
+                precedence,
+            ))
+        };
+        match op {
+            BinaryOperator::Or => infix("OR", OR),
+            BinaryOperator::And => infix("AND", AND),
+            BinaryOperator::Eq | BinaryOperator::Neq if matches!(right, Expr::Null) => {
+                let test = if op == BinaryOperator::Eq {
+                    "IS NULL"
+                } else {
+                    "IS NOT NULL"
+                };
+                Ok((
+                    format!("{} {}", self.operand(left, COMPARE + 1)?, test),
+                    COMPARE,
+                ))
+            }
+            BinaryOperator::Eq => infix("=", COMPARE),
+            BinaryOperator::Neq => infix("<>", COMPARE),
+            BinaryOperator::Lt => infix("<", COMPARE),
+            BinaryOperator::Lte => infix("<=", COMPARE),
+            BinaryOperator::Gt => infix(">", COMPARE),
+            BinaryOperator::Gte => infix(">=", COMPARE),
+            BinaryOperator::Add => infix("+", ADD),
+            BinaryOperator::Sub => infix("-", ADD),
+            BinaryOperator::Mul => infix("*", MUL),
+            BinaryOperator::Div => infix("/", MUL),
+            BinaryOperator::Mod => infix("%", MUL),
+            BinaryOperator::Pow => Ok((
+                format!(
+                    "POWER({}, {})",
+                    self.condition(left)?,
+                    self.condition(right)?
+                ),
+                ATOM,
+            )),
+            BinaryOperator::In => self.membership(left, right),
+            BinaryOperator::Like => {
+                let Expr::String(pattern) = right else {
+                    return Err("'like' needs a literal pattern".to_string());
+                };
+                Ok((
+                    format!(
+                        "{} LIKE {}",
+                        self.operand(left, COMPARE + 1)?,
+                        self.dialect.quote_string(&like_pattern(pattern))
+                    ),
+                    COMPARE,
+                ))
+            }
+        }
+    }
+
+    fn membership(&self, value: &Expr, collection: &Expr) -> Result<(String, u8), String> {
+        let value_sql = self.operand(value, COMPARE + 1)?;
+        match collection {
+            Expr::Array(items) if !items.is_empty() => {
+                let items = items
+                    .iter()
+                    .map(|item| self.condition(item))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok((format!("{} IN ({})", value_sql, items.join(", ")), COMPARE))
+            }
+            Expr::Range {
+                start,
+                end,
+                inclusive: true,
+            } => Ok((
+                format!(
+                    "{} BETWEEN {} AND {}",
+                    value_sql,
+                    self.operand(start, ADD)?,
+                    self.operand(end, ADD)?
+                ),
+                COMPARE,
+            )),
+            Expr::Range {
+                start,
+                end,
+                inclusive: false,
+            } => Ok((
+                format!(
+                    "{} >= {} AND {} < {}",
+                    value_sql,
+                    self.operand(start, COMPARE + 1)?,
+                    value_sql,
+                    self.operand(end, COMPARE + 1)?
+                ),
+                AND,
+            )),
+            _ => Err("'in' needs a literal list or range".to_string()),
+        }
+    }
+
+    fn function(&self, name: &str, args: &[Expr]) -> Result<(String, u8), String> {
+        let args = args
+            .iter()
+            .map(|arg| self.condition(arg))
+            .collect::<Result<Vec<_>, _>>()?;
+        let sql = match (name, args.as_slice()) {
+            ("length", [s]) => format!("CHAR_LENGTH({})", s),
+            ("lowercase", [s]) => format!("LOWER({})", s),
+            ("uppercase", [s]) => format!("UPPER({})", s),
+            ("trim", [s]) => format!("TRIM({})", s),
+            ("abs", [n]) => format!("ABS({})", n),
+            ("is_null", [value]) => return Ok((format!("({}) IS NULL", value), COMPARE)),
+            ("contains", [s, sub]) => {
+                let position = match self.dialect {
+                    SqlDialect::Postgres => format!("STRPOS({}, {})", s, sub),
+                    SqlDialect::MySql => format!("LOCATE({}, {})", sub, s),
+                };
+                return Ok((format!("{} > 0", position), COMPARE));
+            }
+            ("starts_with", [s, prefix]) => {
+                let sql = format!("LEFT({}, CHAR_LENGTH({})) = {}", s, prefix, prefix);
+                return Ok((sql, COMPARE));
+            }
+            ("ends_with", [s, suffix]) => {
+                let sql = format!("RIGHT({}, CHAR_LENGTH({})) = {}", s, suffix, suffix);
+                return Ok((sql, COMPARE));
+            }
+            ("matches", [s, pattern]) => {
+                let op = match self.dialect {
+                    SqlDialect::Postgres => "~",
+                    SqlDialect::MySql => "REGEXP",
+                };
+                return Ok((format!("{} {} {}", s, op, pattern), COMPARE));
+            }
+            _ => {
+                return Err(format!(
+                    "function '{}' with {} argument(s) has no SQL equivalent",
+                    name,
+                    args.len()
+                ))
+            }
+        };
+        Ok((sql, ATOM))
+    }
+}
+
+/// Dotted path of an identifier or field access chain
+fn field_path(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Identifier(name) => Some(name.clone()),
+        Expr::FieldAccess { receiver, field } => {
+            Some(format!("{}.{}", field_path(receiver)?, field))
+        }
+        _ => None,
+    }
+}
+
+/// Translate a glob pattern into a LIKE pattern with `\` as escape
+fn like_pattern(glob: &str) -> String {
+    let mut pattern = String::new();
+    for token in parse_glob(glob) {
+        match token {
+            GlobToken::Literal(text) => {
+                for ch in text.chars() {
+                    if matches!(ch, '%' | '_' | '\\') {
+                        pattern.push('\\');
+                    }
+                    pattern.push(ch);
+                }
+            }
+            GlobToken::AnySequence => pattern.push('%'),
+            GlobToken::AnyChar => pattern.push('_'),
+        }
+    }
+    pattern
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::RuleSet;
+
+    fn ddl(source: &str, mapping: &TableMapping, dialect: SqlDialect) -> String {
+        RuleSet::parse(source)
+            .unwrap()
+            .check_constraints(mapping, dialect)
+            .unwrap()
+            .to_ddl()
+    }
+
+    #[test]
+    fn test_postgres_constraints() {
+        let mapping = TableMapping::new("orders").column("customer.country", "country");
+        let ddl = ddl(
+            "rule in_range: qty in 1..=10 && (total > 0 || status == 'free')\n\
+             rule eu: customer.country in ['FR', 'DE'] && note == null\n\
+             rule code_format: code like 'A_*' && !(discount % 5 != 0)\n\
+             rule composite: in_range && eu\n\
+             rule recent: created_at > TODAY\n\
+             rule low: warn_if total < 5 : 'low total'",
+            &mapping,
+            SqlDialect::Postgres,
+        );
+        assert_eq!(
+            ddl,
+            "ALTER TABLE \"orders\" ADD CONSTRAINT \"orders_in_range_check\" CHECK (\"qty\" BETWEEN 1 AND 10 AND (\"total\" > 0 OR \"status\" = 'free'));\n\
+             ALTER TABLE \"orders\" ADD CONSTRAINT \"orders_eu_check\" CHECK (\"country\" IN ('FR', 'DE') AND \"note\" IS NULL);\n\
+             ALTER TABLE \"orders\" ADD CONSTRAINT \"orders_code_format_check\" CHECK (\"code\" LIKE 'A\\_%' AND NOT \"discount\" % 5 <> 0);\n\
+             ALTER TABLE \"orders\" ADD CONSTRAINT \"orders_composite_check\" CHECK (\"qty\" BETWEEN 1 AND 10 AND (\"total\" > 0 OR \"status\" = 'free') AND \"country\" IN ('FR', 'DE') AND \"note\" IS NULL);\n\
+             -- rule 'recent' not enforced: temporal keywords depend on the current time, which CHECK constraints cannot read\n\
+             -- rule 'low' not enforced: warn_if only warns and cannot be enforced\n"
+        );
+    }
+
+    #[test]
+    fn test_mysql_constraints() {
+        let ddl = ddl(
+            "rule valid_email: contains(email, '@') && (a - (b - c)) * 2 > 0\n\
+             rule nested: address.zip != ''\n\
+             rule flagged [flag = \"beta\"]: age > 0",
+            &TableMapping::new("users"),
+            SqlDialect::MySql,
+        );
+        assert_eq!(
+            ddl,
+            "ALTER TABLE `users` ADD CONSTRAINT `users_valid_email_check` CHECK (LOCATE('@', `email`) > 0 AND (`a` - (`b` - `c`)) * 2 > 0);\n\
+             -- rule 'nested' not enforced: field 'address.zip' is not mapped to a column\n\
+             -- rule 'flagged' not enforced: only checked conditionally (flag, sampling or effective window)\n"
+        );
+        assert_eq!(
+            SqlDialect::MySql.quote_string("it's a\\b"),
+            "'it''s a\\\\b'"
+        );
+        assert_eq!(
+            SqlDialect::Postgres.quote_string("it's a\\b"),
+            "'it''s a\\b'"
+        );
+    }
+}
@@ -0,0 +1,729 @@
+//! Decision tables
+//!
+//! Business rules are often written as tables: one column per condition,
+//! one row per case, and the outcome in the last column. A
+//! [`DecisionTable`] reads such a table from CSV or Markdown and turns it
+//! into an ELO `if ... then ... else` chain, checking first that no input
+//! falls through the table (exhaustiveness) and, under
+//! [`HitPolicy::Unique`], that no input matches two rows (overlap).
+//!
+//! The header holds the expression each condition column tests. A
+//! condition cell is one of:
+//!
+//! | Cell            | Meaning                          |
+//! |-----------------|----------------------------------|
+//! | `-`, `*`, empty | any value                        |
+//! | `< 18`, `>= 5`  | comparison with the column       |
+//! | `18..65`        | range (`..=` for inclusive)      |
+//! | `['FR', 'DE']`  | one of the listed values         |
+//! | `'gold'`, `42`  | equal to the value               |
+//!
+//! Anything else is compared with `==` as written, and is opaque to the
+//! exhaustiveness and overlap checks.
+//!
+//! # Example
+//!
+//! ```
+//! use elo_rust::rules::table::DecisionTable;
+//!
+//! let table = DecisionTable::from_csv(
+//!     "age,member,discount\n\
+//!      < 18,-,0\n\
+//!      >= 18,true,0.1\n\
+//!      >= 18,false,0.05",
+//! )
+//! .unwrap();
+//!
+//! assert_eq!(
+//!     table.to_elo().unwrap(),
+//!     "if age < 18 then 0 else if age >= 18 && member == true then 0.1 else 0.05"
+//! );
+//! ```
+
+use crate::ast::Expr;
+use crate::parser::{ParseError, Parser};
+use std::fmt;
+
+/// Upper bound on the number of input combinations checked
+const MAX_COMBINATIONS: usize = 100_000;
+
+/// How rows that match the same input are treated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HitPolicy {
+    /// At most one row may match any input; overlaps are errors
+    #[default]
+    Unique,
+    /// The first matching row wins; rows that can never win are errors
+    First,
+}
+
+/// Errors raised while reading or compiling a decision table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecisionTableError {
+    /// The table text is malformed
+    Parse {
+        /// Line number (1-based) of the problem
+        line: usize,
+        /// Description of the problem
+        message: String,
+    },
+    /// Some input matches no row
+    Gap {
+        /// An input no row matches, e.g. "age = 17, member = false"
+        example: String,
+    },
+    /// Two rows match the same input under [`HitPolicy::Unique`]
+    Overlap {
+        /// Line numbers of the overlapping rows
+        lines: (usize, usize),
+        /// An input both rows match
+        example: String,
+    },
+    /// A row is hidden by earlier rows under [`HitPolicy::First`]
+    Unreachable {
+        /// Line number of the row
+        line: usize,
+    },
+    /// The conditions have too many combinations to check
+    TooLarge,
+    /// The generated expression does not parse (a cell holds invalid ELO)
+    Expression(ParseError),
+}
+
+impl fmt::Display for DecisionTableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse { line, message } => {
+                write!(f, "Invalid decision table at line {}: {}", line, message)
+            }
+            Self::Gap { example } => write!(
+                f,
+                "Decision table is not exhaustive: no row matches {}",
+                example
+            ),
+            Self::Overlap { lines, example } => write!(
+                f,
+                "Rows at lines {} and {} overlap: both match {}",
+                lines.0, lines.1, example
+            ),
+            Self::Unreachable { line } => {
+                write!(f, "Row at line {} is never reached", line)
+            }
+            Self::TooLarge => write!(
+                f,
+                "Decision table has too many condition combinations to check"
+            ),
+            Self::Expression(error) => write!(f, "Invalid cell expression: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for DecisionTableError {}
+
+/// One case of a decision table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableRow {
+    /// Line number (1-based) in the source text
+    pub line: usize,
+    /// Condition cells, one per condition column
+    pub conditions: Vec<String>,
+    /// Outcome expression
+    pub outcome: String,
+}
+
+/// A decision table: condition columns and an outcome column
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecisionTable {
+    /// Expressions tested by the condition columns
+    pub columns: Vec<String>,
+    /// Header of the outcome column
+    pub outcome: String,
+    /// Cases, in table order
+    pub rows: Vec<TableRow>,
+    hit_policy: HitPolicy,
+    default: Option<String>,
+}
+
+impl DecisionTable {
+    /// Read a table from CSV; double quotes protect cells containing commas
+    pub fn from_csv(source: &str) -> Result<Self, DecisionTableError> {
+        let lines = source
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(idx, line)| Ok((idx + 1, split_csv(line, idx + 1)?)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::from_cells(lines)
+    }
+
+    /// Read a table from a Markdown pipe table; `\|` is a literal pipe
+    pub fn from_markdown(source: &str) -> Result<Self, DecisionTableError> {
+        let lines = source
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| line.trim_start().starts_with('|'))
+            .filter(|(_, line)| !is_separator_row(line))
+            .map(|(idx, line)| (idx + 1, split_markdown(line)))
+            .collect();
+        Self::from_cells(lines)
+    }
+
+    fn from_cells(lines: Vec<(usize, Vec<String>)>) -> Result<Self, DecisionTableError> {
+        let mut lines = lines.into_iter();
+        let (header_line, mut header) = lines.next().ok_or(DecisionTableError::Parse {
+            line: 1,
+            message: "table is empty".to_string(),
+        })?;
+        if header.len() < 2 {
+            return Err(DecisionTableError::Parse {
+                line: header_line,
+                message: "expected at least one condition column and an outcome column".to_string(),
+            });
+        }
+        let outcome = header.pop().unwrap_or_default();
+
+        let mut rows = Vec::new();
+        for (line, mut cells) in lines {
+            if cells.len() != header.len() + 1 {
+                return Err(DecisionTableError::Parse {
+                    line,
+                    message: format!("expected {} cells, found {}", header.len() + 1, cells.len()),
+                });
+            }
+            let outcome = cells.pop().unwrap_or_default();
+            if outcome.is_empty() {
+                return Err(DecisionTableError::Parse {
+                    line,
+                    message: "outcome cell is empty".to_string(),
+                });
+            }
+            rows.push(TableRow {
+                line,
+                conditions: cells,
+                outcome,
+            });
+        }
+        if rows.is_empty() {
+            return Err(DecisionTableError::Parse {
+                line: header_line,
+                message: "table has no rows".to_string(),
+            });
+        }
+
+        Ok(Self {
+            columns: header,
+            outcome,
+            rows,
+            hit_policy: HitPolicy::default(),
+            default: None,
+        })
+    }
+
+    /// Set how rows matching the same input are treated
+    pub fn with_hit_policy(mut self, policy: HitPolicy) -> Self {
+        self.hit_policy = policy;
+        self
+    }
+
+    /// Outcome for inputs no row matches; the table need not be exhaustive
+    pub fn with_default(mut self, outcome: impl Into<String>) -> Self {
+        self.default = Some(outcome.into());
+        self
+    }
+
+    /// Check exhaustiveness and overlaps
+    ///
+    /// The check enumerates one representative input per region delimited
+    /// by the literals in the condition cells, so it is exact for
+    /// comparisons, ranges and value lists. Opaque cells never count as
+    /// matching, which makes the check conservative for them.
+    pub fn check(&self) -> Result<(), DecisionTableError> {
+        let rows: Vec<Vec<Cell>> = self
+            .rows
+            .iter()
+            .map(|row| row.conditions.iter().map(|c| Cell::parse(c)).collect())
+            .collect();
+        let samples: Vec<Vec<Sample>> = (0..self.columns.len())
+            .map(|col| column_samples(rows.iter().map(|row| &row[col])))
+            .collect();
+        let combinations = samples
+            .iter()
+            .try_fold(1usize, |acc, s| acc.checked_mul(s.len()))
+            .filter(|&n| n <= MAX_COMBINATIONS)
+            .ok_or(DecisionTableError::TooLarge)?;
+
+        let mut reached = vec![false; rows.len()];
+        let mut input = Vec::with_capacity(self.columns.len());
+        for index in 0..combinations {
+            // Decode the mixed-radix index into one sample per column
+            input.clear();
+            let mut rest = index;
+            for column in &samples {
+                input.push(&column[rest % column.len()]);
+                rest /= column.len();
+            }
+
+            let mut matching = rows
+                .iter()
+                .enumerate()
+                .filter(|(_, cells)| cells.iter().zip(&input).all(|(c, s)| c.matches(s)))
+                .map(|(i, _)| i);
+            match (matching.next(), matching.next()) {
+                (None, _) if self.default.is_none() => {
+                    return Err(DecisionTableError::Gap {
+                        example: self.describe(&input),
+                    })
+                }
+                (Some(first), Some(second)) if self.hit_policy == HitPolicy::Unique => {
+                    return Err(DecisionTableError::Overlap {
+                        lines: (self.rows[first].line, self.rows[second].line),
+                        example: self.describe(&input),
+                    })
+                }
+                (Some(first), _) => reached[first] = true,
+                (None, _) => {}
+            }
+        }
+
+        if self.hit_policy == HitPolicy::First {
+            let hidden = rows
+                .iter()
+                .zip(&reached)
+                .position(|(cells, reached)| !reached && !cells.contains(&Cell::Opaque));
+            if let Some(index) = hidden {
+                return Err(DecisionTableError::Unreachable {
+                    line: self.rows[index].line,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Compile the checked table to ELO source
+    pub fn to_elo(&self) -> Result<String, DecisionTableError> {
+        self.check()?;
+        let (rows, last) = match &self.default {
+            Some(default) => (&self.rows[..], default.as_str()),
+            // Exhaustive: the last row matches whatever the others do not
+            None => (
+                &self.rows[..self.rows.len() - 1],
+                self.rows[self.rows.len() - 1].outcome.as_str(),
+            ),
+        };
+
+        let mut elo = String::new();
+        for row in rows {
+            let conditions: Vec<String> = self
+                .columns
+                .iter()
+                .zip(&row.conditions)
+                .filter_map(|(column, cell)| condition_source(column, cell))
+                .collect();
+            let condition = if conditions.is_empty() {
+                "true".to_string()
+            } else {
+                conditions.join(" && ")
+            };
+            elo.push_str(&format!(
+                "if {} then {} else ",
+                condition,
+                outcome_source(&row.outcome)
+            ));
+        }
+        elo.push_str(&outcome_source(last));
+        Ok(elo)
+    }
+
+    /// Compile the checked table to an expression
+    pub fn to_expr(&self) -> Result<Expr, DecisionTableError> {
+        Parser::parse(&self.to_elo()?).map_err(DecisionTableError::Expression)
+    }
+
+    fn describe(&self, input: &[&Sample]) -> String {
+        self.columns
+            .iter()
+            .zip(input)
+            .map(|(column, sample)| format!("{} = {}", column, sample))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// A literal value appearing in a condition cell
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Number(f64),
+    Bool(bool),
+    Str(String),
+}
+
+impl Value {
+    fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        match text {
+            "true" => Some(Self::Bool(true)),
+            "false" => Some(Self::Bool(false)),
+            _ => text
+                .parse::<f64>()
+                .ok()
+                .filter(|n| n.is_finite())
+                .map(Self::Number)
+                .or_else(|| {
+                    text.strip_prefix('\'')
+                        .and_then(|t| t.strip_suffix('\''))
+                        .filter(|t| !t.contains('\''))
+                        .map(|t| Self::Str(t.to_string()))
+                }),
+        }
+    }
+}
+
+/// Analysed condition cell
+#[derive(Debug, Clone, PartialEq)]
+enum Cell {
+    Any,
+    Compare(&'static str, f64),
+    Range(f64, f64, bool),
+    OneOf(Vec<Value>),
+    Opaque,
+}
+
+impl Cell {
+    fn parse(text: &str) -> Self {
+        let text = text.trim();
+        if is_any(text) {
+            return Self::Any;
+        }
+        for op in ["<=", ">=", "==", "!=", "<", ">"] {
+            if let Some(rest) = text.strip_prefix(op) {
+                return match (op, Value::parse(rest)) {
+                    ("==", Some(value)) => Self::OneOf(vec![value]),
+                    (_, Some(Value::Number(n))) => Self::Compare(op, n),
+                    _ => Self::Opaque,
+                };
+            }
+        }
+        if let Some(list) = text.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+            return list
+                .split(',')
+                .map(Value::parse)
+                .collect::<Option<Vec<_>>>()
+                .map_or(Self::Opaque, Self::OneOf);
+        }
+        let range = text
+            .split_once("..=")
+            .map(|(lo, hi)| (lo, hi, true))
+            .or_else(|| text.split_once("..").map(|(lo, hi)| (lo, hi, false)));
+        if let Some((lo, hi, inclusive)) = range {
+            return match (Value::parse(lo), Value::parse(hi)) {
+                (Some(Value::Number(lo)), Some(Value::Number(hi))) => {
+                    Self::Range(lo, hi, inclusive)
+                }
+                _ => Self::Opaque,
+            };
+        }
+        Value::parse(text).map_or(Self::Opaque, |value| Self::OneOf(vec![value]))
+    }
+
+    fn matches(&self, sample: &Sample) -> bool {
+        match (self, sample) {
+            (Self::Any, _) => true,
+            (Self::Compare(op, bound), Sample::Value(Value::Number(x))) => match *op {
+                "<" => x < bound,
+                "<=" => x <= bound,
+                ">" => x > bound,
+                ">=" => x >= bound,
+                "==" => x == bound,
+                _ => x != bound,
+            },
+            (Self::Compare("!=", _), Sample::Value(_)) => true,
+            (Self::Range(lo, hi, inclusive), Sample::Value(Value::Number(x))) => {
+                x >= lo && (x < hi || (*inclusive && x == hi))
+            }
+            (Self::OneOf(values), Sample::Value(value)) => values.contains(value),
+            _ => false,
+        }
+    }
+}
+
+/// Representative input value for one column
+#[derive(Debug, Clone, PartialEq)]
+enum Sample {
+    Value(Value),
+    /// A value equal to none of the literals in the column
+    Other,
+}
+
+impl fmt::Display for Sample {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Value(Value::Number(n)) => write!(f, "{}", n),
+            Self::Value(Value::Bool(b)) => write!(f, "{}", b),
+            Self::Value(Value::Str(s)) => write!(f, "'{}'", s),
+            Self::Other => write!(f, "any other value"),
+        }
+    }
+}
+
+/// One representative per region the cells of a column distinguish
+fn column_samples<'a>(cells: impl Iterator<Item = &'a Cell>) -> Vec<Sample> {
+    let mut numbers = Vec::new();
+    let mut others = Vec::new();
+    for cell in cells {
+        match cell {
+            Cell::Compare(_, n) => numbers.push(*n),
+            Cell::Range(lo, hi, _) => numbers.extend([*lo, *hi]),
+            Cell::OneOf(values) => {
+                for value in values {
+                    match value {
+                        Value::Number(n) => numbers.push(*n),
+                        value => {
+                            if !others.contains(value) {
+                                others.push(value.clone());
+                            }
+                        }
+                    }
+                }
+            }
+            Cell::Any | Cell::Opaque => {}
+        }
+    }
+    numbers.sort_by(f64::total_cmp);
+    numbers.dedup();
+
+    let mut samples = Vec::new();
+    if let (Some(&first), Some(&last)) = (numbers.first(), numbers.last()) {
+        // Integer boundaries only need integer samples between them
+        let integral = numbers.iter().all(|n| n.fract() == 0.0);
+        samples.push(first - 1.0);
+        for pair in numbers.windows(2) {
+            samples.push(pair[0]);
+            if !integral {
+                samples.push((pair[0] + pair[1]) / 2.0);
+            } else if pair[1] - pair[0] > 1.0 {
+                samples.push(pair[0] + 1.0);
+            }
+        }
+        samples.extend([last, last + 1.0]);
+    }
+    let mut samples: Vec<Sample> = samples
+        .into_iter()
+        .map(|n| Sample::Value(Value::Number(n)))
+        .collect();
+
+    let has_bools = others.iter().any(|v| matches!(v, Value::Bool(_)));
+    if has_bools {
+        for b in [true, false] {
+            if !others.contains(&Value::Bool(b)) {
+                others.push(Value::Bool(b));
+            }
+        }
+    }
+    let has_strings = others.iter().any(|v| matches!(v, Value::Str(_)));
+    samples.extend(others.into_iter().map(Sample::Value));
+    if has_strings || samples.is_empty() {
+        samples.push(Sample::Other);
+    }
+    samples
+}
+
+fn is_any(cell: &str) -> bool {
+    matches!(cell.trim(), "" | "-" | "*")
+}
+
+/// ELO condition for a cell, or `None` for "any"
+fn condition_source(column: &str, cell: &str) -> Option<String> {
+    let cell = cell.trim();
+    if is_any(cell) {
+        return None;
+    }
+    let is_range = cell.contains("..") && !cell.starts_with('[');
+    Some(
+        if ["<=", ">=", "==", "!=", "<", ">"]
+            .iter()
+            .any(|op| cell.starts_with(op))
+        {
+            format!("{} {}", column, cell)
+        } else if cell.starts_with('[') || is_range {
+            format!("{} in {}", column, cell)
+        } else if Value::parse(cell).is_some() {
+            format!("{} == {}", column, cell)
+        } else {
+            format!("{} == ({})", column, cell)
+        },
+    )
+}
+
+fn outcome_source(outcome: &str) -> String {
+    let outcome = outcome.trim();
+    if outcome.contains(char::is_whitespace) && !outcome.starts_with('\'') {
+        format!("({})", outcome)
+    } else {
+        outcome.to_string()
+    }
+}
+
+fn split_csv(line: &str, line_number: usize) -> Result<Vec<String>, DecisionTableError> {
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                cell.push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => cells.push(std::mem::take(&mut cell).trim().to_string()),
+            ch => cell.push(ch),
+        }
+    }
+    if quoted {
+        return Err(DecisionTableError::Parse {
+            line: line_number,
+            message: "unterminated quoted cell".to_string(),
+        });
+    }
+    cells.push(cell.trim().to_string());
+    Ok(cells)
+}
+
+fn is_separator_row(line: &str) -> bool {
+    line.contains('-')
+        && line
+            .chars()
+            .all(|c| matches!(c, '|' | '-' | ':' | ' ' | '\t'))
+}
+
+fn split_markdown(line: &str) -> Vec<String> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = line.strip_suffix('|').unwrap_or(line);
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut chars = line.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' if chars.peek() == Some(&'|') => {
+                chars.next();
+                cell.push('|');
+            }
+            '|' => cells.push(std::mem::take(&mut cell).trim().to_string()),
+            ch => cell.push(ch),
+        }
+    }
+    cells.push(cell.trim().to_string());
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHIPPING: &str = "\
+| weight | country        | express | => fee |
+|--------|----------------|---------|--------|
+| < 1    | ['FR', 'DE']   | -       | 5      |
+| < 1    | -              | -       | 8      |
+| 1..=10 | -              | true    | 20     |
+| 1..=10 | -              | false   | 12     |
+| > 10   | -              | -       | weight * 2 |
+";
+
+    #[test]
+    fn test_markdown_first_hit() {
+        let table = DecisionTable::from_markdown(SHIPPING)
+            .unwrap()
+            .with_hit_policy(HitPolicy::First);
+        assert_eq!(table.columns, vec!["weight", "country", "express"]);
+        assert_eq!(table.rows[0].line, 3);
+        assert_eq!(
+            table.to_elo().unwrap(),
+            "if weight < 1 && country in ['FR', 'DE'] then 5 else \
+             if weight < 1 then 8 else \
+             if weight in 1..=10 && express == true then 20 else \
+             if weight in 1..=10 && express == false then 12 else (weight * 2)"
+        );
+        assert!(table.to_expr().is_ok());
+
+        // The same rows overlap under the unique policy
+        let err = DecisionTable::from_markdown(SHIPPING)
+            .unwrap()
+            .check()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Rows at lines 3 and 4 overlap: both match weight = 0, country = 'FR', express = true"
+        );
+    }
+
+    #[test]
+    fn test_gap_detected() {
+        let table =
+            DecisionTable::from_csv("age,member,discount\n< 18,-,0\n>= 18,true,0.1").unwrap();
+        assert_eq!(
+            table.check().unwrap_err(),
+            DecisionTableError::Gap {
+                example: "age = 18, member = false".to_string()
+            }
+        );
+        // A default outcome covers the gap
+        assert_eq!(
+            table.with_default("0").to_elo().unwrap(),
+            "if age < 18 then 0 else if age >= 18 && member == true then 0.1 else 0"
+        );
+    }
+
+    #[test]
+    fn test_integer_boundaries_have_no_gap() {
+        let table =
+            DecisionTable::from_csv("age,band\n<= 17,'minor'\n18..65,'adult'\n>= 65,'senior'")
+                .unwrap();
+        assert!(table.check().is_ok());
+
+        let table = DecisionTable::from_csv("ratio,band\n<= 0.5,'low'\n> 0.6,'high'").unwrap();
+        assert!(matches!(
+            table.check(),
+            Err(DecisionTableError::Gap { example }) if example == "ratio = 0.55"
+        ));
+    }
+
+    #[test]
+    fn test_unreachable_row() {
+        let table = DecisionTable::from_csv("tier,limit\n-,100\n'gold',500")
+            .unwrap()
+            .with_hit_policy(HitPolicy::First);
+        assert_eq!(
+            table.check().unwrap_err(),
+            DecisionTableError::Unreachable { line: 3 }
+        );
+    }
+
+    #[test]
+    fn test_csv_quoting_and_errors() {
+        let table = DecisionTable::from_csv("country,vat\n\"['FR', 'DE']\",0.2\n'US',0")
+            .unwrap()
+            .with_default("0.1");
+        assert_eq!(
+            table.to_elo().unwrap(),
+            "if country in ['FR', 'DE'] then 0.2 else if country == 'US' then 0 else 0.1"
+        );
+
+        assert_eq!(
+            DecisionTable::from_csv("a,b\nx").unwrap_err(),
+            DecisionTableError::Parse {
+                line: 2,
+                message: "expected 2 cells, found 1".to_string()
+            }
+        );
+        assert!(matches!(
+            DecisionTable::from_csv("a,b\n\"x,1"),
+            Err(DecisionTableError::Parse { line: 2, .. })
+        ));
+        assert!(matches!(
+            DecisionTable::from_csv("outcome"),
+            Err(DecisionTableError::Parse { line: 1, .. })
+        ));
+    }
+}
@@ -0,0 +1,767 @@
+//! Column-oriented batch validation
+//!
+//! A [`ColumnBatch`] holds data as typed, nullable columns (the layout of an
+//! Arrow record batch or a Parquet row group), and a [`BatchValidator`]
+//! checks a [`RuleSet`] against every row of it at once. Rules built from
+//! comparisons of a field with a literal, combined with `&&`, `||` and `!`,
+//! are compiled into loops over whole columns; other rules fall back to the
+//! [`Evaluator`] row by row. Either way the outcome of each rule matches
+//! [`RuleSet::check`].
+//!
+//! Columnar readers fill a batch directly through [`ColumnBatch::with_column`];
+//! CSV and NDJSON input can be loaded with [`ColumnBatch::read`]. Dotted
+//! column names (`order.total`) are addressed by field access in rules.
+//!
+//! # Example
+//!
+//! ```
+//! use elo_rust::rules::RuleSet;
+//! use elo_rust::runtime::batch::{BatchValidator, Column, ColumnBatch};
+//!
+//! let rules = RuleSet::parse("rule is_adult: age >= 18").unwrap();
+//! let validator = BatchValidator::new(&rules).unwrap();
+//! assert_eq!(validator.vectorized_rules(), vec!["is_adult"]);
+//!
+//! let batch = ColumnBatch::new()
+//!     .with_column("age", Column::Integer(vec![Some(21), Some(12), None]))
+//!     .unwrap();
+//! let report = validator.validate(&batch);
+//! assert_eq!(report.failed_rows(), vec![1, 2]);
+//! ```
+
+use super::interpreter::{EvalError, Evaluator};
+use super::stream::{RecordFormat, RecordReader, StreamError};
+use super::{EloValue, ValidationError};
+use crate::ast::{BinaryOperator, Expr, Literal, UnaryOperator};
+use crate::rules::{RuleSet, RuleSetError};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::BufRead;
+
+/// A column of nullable values of one type
+#[derive(Debug, Clone, PartialEq)]
+pub enum Column {
+    /// 64-bit signed integers
+    Integer(Vec<Option<i64>>),
+    /// 64-bit floats
+    Float(Vec<Option<f64>>),
+    /// Booleans
+    Boolean(Vec<Option<bool>>),
+    /// Strings
+    String(Vec<Option<String>>),
+    /// Values of mixed or nested types
+    Values(Vec<EloValue>),
+}
+
+impl Column {
+    /// Build the narrowest column that holds all `values`
+    ///
+    /// Integers widen to floats when mixed with them; any other mix of types
+    /// gives [`Column::Values`].
+    pub fn from_values(values: Vec<EloValue>) -> Column {
+        let present: Vec<&EloValue> = values.iter().filter(|v| **v != EloValue::Null).collect();
+        if present.is_empty() {
+            Column::Values(values)
+        } else if present.iter().all(|v| matches!(v, EloValue::Integer(_))) {
+            Column::Integer(values.iter().map(EloValue::to_integer).collect())
+        } else if present.iter().all(|v| v.is_numeric()) {
+            Column::Float(values.iter().map(EloValue::to_float).collect())
+        } else if present.iter().all(|v| matches!(v, EloValue::Boolean(_))) {
+            Column::Boolean(
+                values
+                    .iter()
+                    .map(|v| match v {
+                        EloValue::Boolean(b) => Some(*b),
+                        _ => None,
+                    })
+                    .collect(),
+            )
+        } else if present.iter().all(|v| v.is_string()) {
+            Column::String(
+                values
+                    .into_iter()
+                    .map(|v| match v {
+                        EloValue::String(s) => Some(s),
+                        _ => None,
+                    })
+                    .collect(),
+            )
+        } else {
+            Column::Values(values)
+        }
+    }
+
+    /// Number of cells
+    pub fn len(&self) -> usize {
+        match self {
+            Column::Integer(cells) => cells.len(),
+            Column::Float(cells) => cells.len(),
+            Column::Boolean(cells) => cells.len(),
+            Column::String(cells) => cells.len(),
+            Column::Values(cells) => cells.len(),
+        }
+    }
+
+    /// Check if the column has no cells
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The cell at `row`; missing cells and nulls are `null`
+    pub fn get(&self, row: usize) -> EloValue {
+        let cell = match self {
+            Column::Integer(cells) => cells.get(row).copied().flatten().map(EloValue::Integer),
+            Column::Float(cells) => cells.get(row).copied().flatten().map(EloValue::Float),
+            Column::Boolean(cells) => cells.get(row).copied().flatten().map(EloValue::Boolean),
+            Column::String(cells) => cells.get(row).cloned().flatten().map(EloValue::String),
+            Column::Values(cells) => cells.get(row).cloned(),
+        };
+        cell.unwrap_or(EloValue::Null)
+    }
+}
+
+/// Errors raised while building a [`ColumnBatch`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchError {
+    /// A column's length differs from the batch's
+    LengthMismatch {
+        /// Name of the column
+        column: String,
+        /// Number of rows in the batch
+        expected: usize,
+        /// Number of cells in the column
+        found: usize,
+    },
+    /// A record is not an object
+    NotAnObject {
+        /// Row (0-based) of the record
+        row: usize,
+    },
+    /// A record could not be read from a stream
+    Record(StreamError),
+}
+
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LengthMismatch {
+                column,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Column '{}' has {} cells, expected {}",
+                column, found, expected
+            ),
+            Self::NotAnObject { row } => write!(f, "Record {} is not an object", row),
+            Self::Record(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for BatchError {}
+
+/// Named columns of equal length
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ColumnBatch {
+    len: usize,
+    columns: BTreeMap<String, Column>,
+}
+
+impl ColumnBatch {
+    /// Create an empty batch
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add (or replace) a column; the first column sets the number of rows
+    pub fn with_column(
+        mut self,
+        name: impl Into<String>,
+        column: Column,
+    ) -> Result<Self, BatchError> {
+        let name = name.into();
+        if self.columns.is_empty() {
+            self.len = column.len();
+        } else if column.len() != self.len {
+            return Err(BatchError::LengthMismatch {
+                column: name,
+                expected: self.len,
+                found: column.len(),
+            });
+        }
+        self.columns.insert(name, column);
+        Ok(self)
+    }
+
+    /// Transpose row objects into columns, one per top-level field
+    ///
+    /// Fields missing from a record are `null` in that row.
+    pub fn from_records(records: &[EloValue]) -> Result<Self, BatchError> {
+        let mut values: BTreeMap<String, Vec<EloValue>> = BTreeMap::new();
+        for (row, record) in records.iter().enumerate() {
+            let EloValue::Object(fields) = record else {
+                return Err(BatchError::NotAnObject { row });
+            };
+            for (name, value) in fields {
+                values
+                    .entry(name.clone())
+                    .or_insert_with(|| vec![EloValue::Null; records.len()])[row] = value.clone();
+            }
+        }
+        let columns = values
+            .into_iter()
+            .map(|(name, values)| (name, Column::from_values(values)))
+            .collect();
+        Ok(Self {
+            len: records.len(),
+            columns,
+        })
+    }
+
+    /// Load every record of an NDJSON or CSV stream
+    ///
+    /// Stops at the first malformed record.
+    pub fn read<R: BufRead>(reader: R, format: RecordFormat) -> Result<Self, BatchError> {
+        let records = RecordReader::new(reader, format)
+            .map(|record| record.map(|record| record.value))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(BatchError::Record)?;
+        Self::from_records(&records)
+    }
+
+    /// Number of rows
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if the batch has no rows
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The column called `name`
+    pub fn column(&self, name: &str) -> Option<&Column> {
+        self.columns.get(name)
+    }
+
+    /// Row `row` as an object; dotted column names become nested objects
+    pub fn row(&self, row: usize) -> EloValue {
+        let mut object = BTreeMap::new();
+        for (name, column) in &self.columns {
+            let mut fields = &mut object;
+            let mut parts = name.split('.').peekable();
+            while let Some(part) = parts.next() {
+                if parts.peek().is_none() {
+                    fields.insert(part.to_string(), column.get(row));
+                    break;
+                }
+                let nested = fields
+                    .entry(part.to_string())
+                    .or_insert_with(|| EloValue::Object(BTreeMap::new()));
+                if !matches!(nested, EloValue::Object(_)) {
+                    *nested = EloValue::Object(BTreeMap::new());
+                }
+                let EloValue::Object(inner) = nested else {
+                    unreachable!("just replaced with an object")
+                };
+                fields = inner;
+            }
+        }
+        EloValue::Object(object)
+    }
+
+    /// Check if some column is nested under `name` (e.g. `order.total` under `order`)
+    fn has_nested(&self, name: &str) -> bool {
+        let prefix = format!("{}.", name);
+        self.columns
+            .keys()
+            .any(|column| column.starts_with(&prefix))
+    }
+}
+
+/// A rule compiled into column operations
+///
+/// Each kernel yields, per row, whether the expression holds, or `None` if
+/// evaluating it raises an error (e.g. ordering a `null` cell).
+#[derive(Debug, Clone)]
+enum Kernel {
+    Const(bool),
+    Truthy(String),
+    Compare {
+        column: String,
+        op: BinaryOperator,
+        literal: EloValue,
+    },
+    Not(Box<Kernel>),
+    And(Box<Kernel>, Box<Kernel>),
+    Or(Box<Kernel>, Box<Kernel>),
+}
+
+impl Kernel {
+    fn compile(expr: &Expr) -> Option<Kernel> {
+        match expr {
+            Expr::Literal(Literal::Boolean(b)) => Some(Kernel::Const(*b)),
+            Expr::Identifier(_) | Expr::FieldAccess { .. } => column_name(expr).map(Kernel::Truthy),
+            Expr::UnaryOp {
+                op: UnaryOperator::Not,
+                operand,
+            } => Some(Kernel::Not(Box::new(Kernel::compile(operand)?))),
+            Expr::BinaryOp { op, left, right } => match op {
+                BinaryOperator::And => Some(Kernel::And(
+                    Box::new(Kernel::compile(left)?),
+                    Box::new(Kernel::compile(right)?),
+                )),
+                BinaryOperator::Or => Some(Kernel::Or(
+                    Box::new(Kernel::compile(left)?),
+                    Box::new(Kernel::compile(right)?),
+                )),
+                BinaryOperator::Eq
+                | BinaryOperator::Neq
+                | BinaryOperator::Lt
+                | BinaryOperator::Lte
+                | BinaryOperator::Gt
+                | BinaryOperator::Gte => {
+                    if let (Some(column), Some(literal)) = (column_name(left), literal(right)) {
+                        Some(Kernel::Compare {
+                            column,
+                            op: *op,
+                            literal,
+                        })
+                    } else if let (Some(literal), Some(column)) =
+                        (literal(left), column_name(right))
+                    {
+                        Some(Kernel::Compare {
+                            column,
+                            op: flip(*op),
+                            literal,
+                        })
+                    } else {
+                        None
+                    }
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Run over every row, or `None` if the batch layout needs row evaluation
+    fn run(&self, batch: &ColumnBatch) -> Option<Vec<Option<bool>>> {
+        match self {
+            Kernel::Const(b) => Some(vec![Some(*b); batch.len()]),
+            Kernel::Truthy(name) => match batch.column(name) {
+                Some(Column::Boolean(cells)) => {
+                    Some(cells.iter().map(|c| Some(c.unwrap_or(false))).collect())
+                }
+                Some(column) => Some(
+                    (0..batch.len())
+                        .map(|row| Some(column.get(row).is_truthy()))
+                        .collect(),
+                ),
+                None if batch.has_nested(name) => None,
+                None => Some(vec![Some(false); batch.len()]),
+            },
+            Kernel::Compare {
+                column,
+                op,
+                literal,
+            } => match batch.column(column) {
+                Some(cells) => Some(compare_column(cells, *op, literal)),
+                None if batch.has_nested(column) => None,
+                None => Some(vec![
+                    compare_values(&EloValue::Null, *op, literal);
+                    batch.len()
+                ]),
+            },
+            Kernel::Not(inner) => Some(
+                inner
+                    .run(batch)?
+                    .into_iter()
+                    .map(|holds| holds.map(|h| !h))
+                    .collect(),
+            ),
+            Kernel::And(left, right) => Some(
+                left.run(batch)?
+                    .into_iter()
+                    .zip(right.run(batch)?)
+                    .map(|(l, r)| match l {
+                        Some(true) => r,
+                        other => other,
+                    })
+                    .collect(),
+            ),
+            Kernel::Or(left, right) => Some(
+                left.run(batch)?
+                    .into_iter()
+                    .zip(right.run(batch)?)
+                    .map(|(l, r)| match l {
+                        Some(false) => r,
+                        other => other,
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Column name addressed by an identifier or field access chain
+fn column_name(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Identifier(name) => Some(name.clone()),
+        Expr::FieldAccess { receiver, field } => {
+            Some(format!("{}.{}", column_name(receiver)?, field))
+        }
+        _ => None,
+    }
+}
+
+fn literal(expr: &Expr) -> Option<EloValue> {
+    match expr {
+        Expr::Literal(Literal::Integer(n)) => Some(EloValue::Integer(*n)),
+        Expr::Literal(Literal::Float(f)) => Some(EloValue::Float(*f)),
+        Expr::Percentage(percent) => Some(EloValue::Float(percent / 100.0)),
+        Expr::Literal(Literal::Boolean(b)) => Some(EloValue::Boolean(*b)),
+        Expr::String(s) => Some(EloValue::String(s.clone())),
+        Expr::Null => Some(EloValue::Null),
+        _ => None,
+    }
+}
+
+/// `a op b` == `b flip(op) a`
+fn flip(op: BinaryOperator) -> BinaryOperator {
+    match op {
+        BinaryOperator::Lt => BinaryOperator::Gt,
+        BinaryOperator::Gt => BinaryOperator::Lt,
+        BinaryOperator::Lte => BinaryOperator::Gte,
+        BinaryOperator::Gte => BinaryOperator::Lte,
+        other => other,
+    }
+}
+
+/// Apply a comparison with the same definitions as the evaluator
+fn apply<T: PartialOrd + ?Sized>(op: BinaryOperator, a: &T, b: &T) -> bool {
+    match op {
+        BinaryOperator::Eq => a == b,
+        BinaryOperator::Neq => a != b,
+        BinaryOperator::Lt => a < b,
+        BinaryOperator::Gt => b < a,
+        // Negated so NaN compares like the evaluator
+        BinaryOperator::Lte => !b.lt(a),
+        BinaryOperator::Gte => !a.lt(b),
+        _ => unreachable!("only comparisons are compiled"),
+    }
+}
+
+/// Outcome for a `null` cell: equality is decidable, ordering is an error
+fn null_cell(op: BinaryOperator) -> Option<bool> {
+    match op {
+        BinaryOperator::Eq => Some(false),
+        BinaryOperator::Neq => Some(true),
+        _ => None,
+    }
+}
+
+fn compare_column(column: &Column, op: BinaryOperator, literal: &EloValue) -> Vec<Option<bool>> {
+    match (column, literal) {
+        (Column::Integer(cells), EloValue::Integer(b)) => cells
+            .iter()
+            .map(|c| c.map_or(null_cell(op), |a| Some(apply(op, &a, b))))
+            .collect(),
+        (Column::Integer(cells), EloValue::Float(b)) => cells
+            .iter()
+            .map(|c| c.map_or(null_cell(op), |a| Some(apply(op, &(a as f64), b))))
+            .collect(),
+        (Column::Float(cells), EloValue::Float(b)) => cells
+            .iter()
+            .map(|c| c.map_or(null_cell(op), |a| Some(apply(op, &a, b))))
+            .collect(),
+        (Column::Float(cells), EloValue::Integer(b)) => cells
+            .iter()
+            .map(|c| c.map_or(null_cell(op), |a| Some(apply(op, &a, &(*b as f64)))))
+            .collect(),
+        (Column::String(cells), EloValue::String(b)) => cells
+            .iter()
+            .map(|c| {
+                c.as_deref()
+                    .map_or(null_cell(op), |a| Some(apply(op, a, b.as_str())))
+            })
+            .collect(),
+        _ => (0..column.len())
+            .map(|row| compare_values(&column.get(row), op, literal))
+            .collect(),
+    }
+}
+
+fn compare_values(a: &EloValue, op: BinaryOperator, b: &EloValue) -> Option<bool> {
+    match op {
+        BinaryOperator::Eq => Some(a.equals(b)),
+        BinaryOperator::Neq => Some(!a.equals(b)),
+        BinaryOperator::Lt => a.less_than(b).ok(),
+        BinaryOperator::Gt => b.less_than(a).ok(),
+        BinaryOperator::Lte => b.less_than(a).ok().map(|gt| !gt),
+        BinaryOperator::Gte => a.less_than(b).ok().map(|lt| !lt),
+        _ => unreachable!("only comparisons are compiled"),
+    }
+}
+
+/// How a rule's outcome is reported
+#[derive(Debug, Clone)]
+enum Check {
+    /// Fails with the message when the expression does not hold
+    Require(String),
+    /// Warns with the message when the expression holds
+    WarnIf(String),
+}
+
+#[derive(Debug, Clone)]
+struct CompiledRule {
+    name: String,
+    expr: Expr,
+    check: Check,
+    kernel: Option<Kernel>,
+}
+
+/// A problem found in one row of a batch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchIssue {
+    /// Row (0-based) the problem was found in
+    pub row: usize,
+    /// The failure or warning, as reported by [`RuleSet::check`]
+    pub error: ValidationError,
+}
+
+/// Outcome of validating a [`ColumnBatch`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchReport {
+    rows: usize,
+    issues: Vec<BatchIssue>,
+}
+
+impl BatchReport {
+    /// Number of rows validated
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Check if every row passed (warnings do not count)
+    pub fn is_ok(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    /// All failures and warnings, ordered by row
+    pub fn issues(&self) -> &[BatchIssue] {
+        &self.issues
+    }
+
+    /// Failures only
+    pub fn errors(&self) -> impl Iterator<Item = &BatchIssue> {
+        self.issues.iter().filter(|issue| !issue.error.is_warning())
+    }
+
+    /// Warnings only
+    pub fn warnings(&self) -> impl Iterator<Item = &BatchIssue> {
+        self.issues.iter().filter(|issue| issue.error.is_warning())
+    }
+
+    /// Rows with at least one failure, in ascending order
+    pub fn failed_rows(&self) -> Vec<usize> {
+        let mut rows: Vec<usize> = self.errors().map(|issue| issue.row).collect();
+        rows.dedup();
+        rows
+    }
+
+    /// Number of rows without failures
+    pub fn passed_count(&self) -> usize {
+        self.rows - self.failed_rows().len()
+    }
+}
+
+/// Checks a [`RuleSet`] against whole [`ColumnBatch`]es
+///
+/// Like [`RuleSet::check`], flags, sampling and effective windows are not
+/// applied.
+#[derive(Debug, Clone)]
+pub struct BatchValidator {
+    rules: Vec<CompiledRule>,
+    evaluator: Evaluator,
+}
+
+impl BatchValidator {
+    /// Compile every rule of `rules`, vectorizing those it can
+    pub fn new(rules: &RuleSet) -> Result<Self, RuleSetError> {
+        let compiled = rules
+            .rules()
+            .iter()
+            .map(|rule| {
+                let (expr, check) = match rules.inline(&rule.name)? {
+                    Expr::WarnIf { condition, message } => (*condition, Check::WarnIf(message)),
+                    Expr::Require { condition, message } => (*condition, Check::Require(message)),
+                    expr => (expr, Check::Require(format!("Rule '{}' failed", rule.name))),
+                };
+                Ok(CompiledRule {
+                    name: rule.name.clone(),
+                    kernel: Kernel::compile(&expr),
+                    expr,
+                    check,
+                })
+            })
+            .collect::<Result<_, RuleSetError>>()?;
+        Ok(Self {
+            rules: compiled,
+            evaluator: Evaluator::new(),
+        })
+    }
+
+    /// Names of the rules compiled into column operations
+    pub fn vectorized_rules(&self) -> Vec<&str> {
+        self.rules
+            .iter()
+            .filter(|rule| rule.kernel.is_some())
+            .map(|rule| rule.name.as_str())
+            .collect()
+    }
+
+    /// Check every rule against every row of `batch`
+    pub fn validate(&self, batch: &ColumnBatch) -> BatchReport {
+        let mut issues = Vec::new();
+        for rule in &self.rules {
+            let evaluate_row = |row: usize| -> Result<bool, EvalError> {
+                self.evaluator
+                    .evaluate(&rule.expr, &batch.row(row))
+                    .map(|value| value.is_truthy())
+            };
+            let holds: Vec<Result<bool, EvalError>> =
+                match rule.kernel.as_ref().and_then(|kernel| kernel.run(batch)) {
+                    Some(mask) => mask
+                        .into_iter()
+                        .enumerate()
+                        // Re-evaluate erroring rows only to recover the message
+                        .map(|(row, holds)| holds.map_or_else(|| evaluate_row(row), Ok))
+                        .collect(),
+                    None => (0..batch.len()).map(evaluate_row).collect(),
+                };
+            for (row, holds) in holds.into_iter().enumerate() {
+                let error = match (holds, &rule.check) {
+                    (Ok(false), Check::Require(message)) | (Ok(true), Check::WarnIf(message)) => {
+                        if matches!(rule.check, Check::WarnIf(_)) {
+                            ValidationError::warning(&rule.name, message, &rule.name)
+                        } else {
+                            ValidationError::new(&rule.name, message, &rule.name)
+                        }
+                    }
+                    (Ok(_), _) => continue,
+                    (Err(error), _) => ValidationError::new(
+                        &rule.name,
+                        format!("Rule '{}' could not be evaluated: {}", rule.name, error),
+                        &rule.name,
+                    ),
+                };
+                issues.push(BatchIssue { row, error });
+            }
+        }
+        issues.sort_by_key(|issue| issue.row);
+        BatchReport {
+            rows: batch.len(),
+            issues,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn batch() -> ColumnBatch {
+        ColumnBatch::new()
+            .with_column("age", Column::Integer(vec![Some(21), Some(12), None]))
+            .unwrap()
+            .with_column(
+                "score",
+                Column::Float(vec![Some(9.5), Some(3.0), Some(7.0)]),
+            )
+            .unwrap()
+            .with_column(
+                "email",
+                Column::String(vec![
+                    Some("ada@example.com".to_string()),
+                    Some("bob".to_string()),
+                    None,
+                ]),
+            )
+            .unwrap()
+            .with_column(
+                "order.total",
+                Column::Integer(vec![Some(5), Some(0), Some(1)]),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_vectorized_matches_row_evaluation() {
+        let rules = RuleSet::parse(
+            "rule is_adult: age >= 18\n\
+             rule scored: 5 < score && !(score == 7)\n\
+             rule has_email: require contains(email, '@') else 'email is invalid'\n\
+             rule low_score: warn_if score < 5 : 'score is low'\n\
+             rule positive: order.total > 0 || email == null",
+        )
+        .unwrap();
+        let validator = BatchValidator::new(&rules).unwrap();
+        assert_eq!(
+            validator.vectorized_rules(),
+            vec!["is_adult", "scored", "low_score", "positive"]
+        );
+
+        let batch = batch();
+        let report = validator.validate(&batch);
+        for row in 0..batch.len() {
+            let expected = rules.check(&batch.row(row)).unwrap();
+            let mut expected: Vec<ValidationError> = expected
+                .failed()
+                .chain(expected.warned())
+                .flat_map(|outcome| outcome.errors.clone())
+                .collect();
+            let mut actual: Vec<ValidationError> = report
+                .issues()
+                .iter()
+                .filter(|issue| issue.row == row)
+                .map(|issue| issue.error.clone())
+                .collect();
+            expected.sort_by(|a, b| a.rule.cmp(&b.rule));
+            actual.sort_by(|a, b| a.rule.cmp(&b.rule));
+            assert_eq!(actual, expected, "row {}", row);
+        }
+        assert_eq!(report.failed_rows(), vec![1, 2]);
+        assert_eq!(report.passed_count(), 1);
+        assert_eq!(report.warnings().count(), 1);
+    }
+
+    #[test]
+    fn test_from_records_infers_column_types() {
+        let data = "name,age,score,active\nAda,36,9.5,true\nBob,,7,\n";
+        let batch = ColumnBatch::read(data.as_bytes(), RecordFormat::Csv).unwrap();
+        assert_eq!(batch.len(), 2);
+        assert_eq!(
+            batch.column("age"),
+            Some(&Column::Integer(vec![Some(36), None]))
+        );
+        assert_eq!(
+            batch.column("score"),
+            Some(&Column::Float(vec![Some(9.5), Some(7.0)]))
+        );
+        assert_eq!(
+            batch.column("active"),
+            Some(&Column::Boolean(vec![Some(true), None]))
+        );
+
+        let mismatch = ColumnBatch::new()
+            .with_column("a", Column::Integer(vec![Some(1)]))
+            .unwrap()
+            .with_column("b", Column::Integer(vec![]));
+        assert_eq!(
+            mismatch.unwrap_err().to_string(),
+            "Column 'b' has 0 cells, expected 1"
+        );
+    }
+}
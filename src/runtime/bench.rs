@@ -0,0 +1,233 @@
+//! Timing generated validators against hand-written equivalents
+//!
+//! [`ComparisonReport::compare`] runs a generated validator and a
+//! hand-written function with the same meaning on the same inputs and
+//! records the overhead of the generated code. The `bench_compare` example
+//! uses it to back the zero-overhead claim with numbers:
+//!
+//! ```text
+//! cargo run --release --example bench_compare
+//! ```
+//!
+//! Timings come from [`std::time::Instant`] with [`std::hint::black_box`]
+//! around inputs and results; run in release mode on an idle machine and
+//! compare ratios rather than absolute numbers across machines.
+//!
+//! # Example
+//!
+//! ```
+//! use elo_rust::runtime::bench::ComparisonReport;
+//!
+//! let ages = [12_i64, 18, 40];
+//! let mut report = ComparisonReport::new(1_000);
+//! report.compare(
+//!     "is_adult",
+//!     "age >= 18",
+//!     || ages.iter().filter(|age| **age >= 18).count(),
+//!     || ages.iter().filter(|age| **age > 17).count(),
+//! );
+//! assert_eq!(report.comparisons().len(), 1);
+//! println!("{}", report);
+//! ```
+
+use std::fmt;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Total time taken by a number of calls
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timing {
+    /// Calls measured
+    pub iterations: u64,
+    /// Time taken by all calls together
+    pub total: Duration,
+}
+
+impl Timing {
+    /// Average time per call in nanoseconds
+    pub fn nanos_per_call(&self) -> f64 {
+        if self.iterations == 0 {
+            return 0.0;
+        }
+        self.total.as_nanos() as f64 / self.iterations as f64
+    }
+}
+
+/// Call `f` `iterations` times, after a warm-up of a tenth as many calls
+pub fn time<T>(iterations: u64, mut f: impl FnMut() -> T) -> Timing {
+    for _ in 0..iterations / 10 {
+        black_box(f());
+    }
+    let start = Instant::now();
+    for _ in 0..iterations {
+        black_box(f());
+    }
+    Timing {
+        iterations,
+        total: start.elapsed(),
+    }
+}
+
+/// One rule timed both ways
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    /// Rule name
+    pub rule: String,
+    /// ELO source of the rule
+    pub source: String,
+    /// The generated validator
+    pub generated: Timing,
+    /// The hand-written equivalent
+    pub hand_written: Timing,
+}
+
+impl Comparison {
+    /// How much slower the generated validator is, in percent
+    ///
+    /// Negative when it is faster. Differences of a few percent are within
+    /// measurement noise.
+    pub fn overhead_percent(&self) -> f64 {
+        let baseline = self.hand_written.nanos_per_call();
+        if baseline == 0.0 {
+            return 0.0;
+        }
+        (self.generated.nanos_per_call() / baseline - 1.0) * 100.0
+    }
+}
+
+/// Comparisons of several rules, printed as a table
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComparisonReport {
+    iterations: u64,
+    comparisons: Vec<Comparison>,
+}
+
+impl ComparisonReport {
+    /// Create a report timing each validator over `iterations` calls
+    pub fn new(iterations: u64) -> Self {
+        Self {
+            iterations,
+            comparisons: Vec::new(),
+        }
+    }
+
+    /// Time `generated` and `hand_written` and record the comparison
+    ///
+    /// Both closures should validate the same inputs; their results are
+    /// only kept alive so the work is not optimized away.
+    pub fn compare<G, H, T, U>(
+        &mut self,
+        rule: &str,
+        source: &str,
+        generated: G,
+        hand_written: H,
+    ) -> &Comparison
+    where
+        G: FnMut() -> T,
+        H: FnMut() -> U,
+    {
+        let generated = time(self.iterations, generated);
+        let hand_written = time(self.iterations, hand_written);
+        self.push(Comparison {
+            rule: rule.to_string(),
+            source: source.to_string(),
+            generated,
+            hand_written,
+        })
+    }
+
+    /// Record a comparison timed elsewhere
+    pub fn push(&mut self, comparison: Comparison) -> &Comparison {
+        self.comparisons.push(comparison);
+        self.comparisons.last().expect("just pushed")
+    }
+
+    /// All comparisons, in the order they were made
+    pub fn comparisons(&self) -> &[Comparison] {
+        &self.comparisons
+    }
+
+    /// The comparison with the largest overhead
+    pub fn worst(&self) -> Option<&Comparison> {
+        self.comparisons
+            .iter()
+            .max_by(|a, b| a.overhead_percent().total_cmp(&b.overhead_percent()))
+    }
+}
+
+impl fmt::Display for ComparisonReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self
+            .comparisons
+            .iter()
+            .map(|comparison| comparison.rule.len())
+            .max()
+            .unwrap_or(0)
+            .max("rule".len());
+        writeln!(
+            f,
+            "{:<width$}  {:>14}  {:>14}  {:>9}",
+            "rule", "generated ns", "hand ns", "overhead"
+        )?;
+        for comparison in &self.comparisons {
+            writeln!(
+                f,
+                "{:<width$}  {:>14.1}  {:>14.1}  {:>+8.1}%",
+                comparison.rule,
+                comparison.generated.nanos_per_call(),
+                comparison.hand_written.nanos_per_call(),
+                comparison.overhead_percent()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timing(nanos: u64) -> Timing {
+        Timing {
+            iterations: 10,
+            total: Duration::from_nanos(nanos * 10),
+        }
+    }
+
+    #[test]
+    fn test_overhead_and_report() {
+        let mut report = ComparisonReport::new(10);
+        for (rule, generated) in [("fast", 90), ("regex_rule", 300)] {
+            report.push(Comparison {
+                rule: rule.to_string(),
+                source: String::new(),
+                generated: timing(generated),
+                hand_written: timing(100),
+            });
+        }
+        assert!((report.comparisons()[0].overhead_percent() + 10.0).abs() < 1e-9);
+        assert_eq!(report.worst().unwrap().rule, "regex_rule");
+        assert_eq!(
+            report.to_string(),
+            "rule          generated ns         hand ns   overhead\n\
+             fast                  90.0           100.0     -10.0%\n\
+             regex_rule           300.0           100.0    +200.0%\n"
+        );
+    }
+
+    #[test]
+    fn test_time_counts_calls() {
+        let mut calls = 0;
+        let timing = time(50, || calls += 1);
+        assert_eq!(timing.iterations, 50);
+        assert_eq!(calls, 55);
+        assert_eq!(
+            Timing {
+                iterations: 0,
+                total: Duration::ZERO
+            }
+            .nanos_per_call(),
+            0.0
+        );
+    }
+}
@@ -0,0 +1,264 @@
+//! Payment card number validation and masking
+//!
+//! Backs the `is_card_number` and `card_brand` stdlib functions. Card numbers
+//! (PANs) must never end up in logs, so [`redact`] masks anything that looks
+//! like one; [`ValidationError::with_value`](super::ValidationError::with_value)
+//! runs every recorded value through it.
+
+/// Minimum number of digits of a card number (ISO/IEC 7812)
+const MIN_DIGITS: usize = 12;
+
+/// Maximum number of digits of a card number (ISO/IEC 7812)
+const MAX_DIGITS: usize = 19;
+
+/// Number of trailing digits left visible when masking (PCI DSS allows the last four)
+const VISIBLE_DIGITS: usize = 4;
+
+/// Brand -> issuer identification number ranges (inclusive, compared on equal-length prefixes)
+const BRANDS: &[(&str, &[(u32, u32)])] = &[
+    ("amex", &[(34, 34), (37, 37)]),
+    ("visa", &[(4, 4)]),
+    ("mastercard", &[(51, 55), (2221, 2720)]),
+    ("discover", &[(6011, 6011), (644, 649), (65, 65)]),
+    ("jcb", &[(3528, 3589)]),
+    ("diners", &[(300, 305), (36, 36), (38, 39)]),
+    ("unionpay", &[(62, 62)]),
+    ("maestro", &[(50, 50), (56, 58), (6304, 6304), (6759, 6759)]),
+];
+
+/// Extract the digits of a card number, allowing spaces and dashes as separators
+fn card_digits(raw: &str) -> Option<String> {
+    let mut digits = String::with_capacity(raw.len());
+    for ch in raw.trim().chars() {
+        match ch {
+            '0'..='9' => digits.push(ch),
+            ' ' | '-' => {}
+            _ => return None,
+        }
+    }
+    Some(digits)
+}
+
+/// Check a digit string against the Luhn (mod 10) checksum
+pub fn luhn_valid(digits: &str) -> bool {
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return false;
+    }
+    let sum: u32 = digits
+        .bytes()
+        .rev()
+        .enumerate()
+        .map(|(i, b)| {
+            let d = u32::from(b - b'0');
+            if i % 2 == 1 {
+                let doubled = d * 2;
+                if doubled > 9 {
+                    doubled - 9
+                } else {
+                    doubled
+                }
+            } else {
+                d
+            }
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+fn is_card_digits(digits: &str) -> bool {
+    (MIN_DIGITS..=MAX_DIGITS).contains(&digits.len()) && luhn_valid(digits)
+}
+
+/// Check if a string is a plausible payment card number (length and Luhn checksum)
+///
+/// # Example
+///
+/// ```
+/// use elo_rust::runtime::card::is_card_number;
+///
+/// assert!(is_card_number("4111 1111 1111 1111"));
+/// assert!(!is_card_number("4111 1111 1111 1112"));
+/// ```
+pub fn is_card_number(raw: &str) -> bool {
+    card_digits(raw).is_some_and(|d| is_card_digits(&d))
+}
+
+/// Detect the brand of a card number ("visa", "mastercard", "amex", ...)
+///
+/// Returns an empty string for invalid numbers and "unknown" for valid
+/// numbers outside the known issuer ranges.
+pub fn card_brand(raw: &str) -> String {
+    let digits = match card_digits(raw) {
+        Some(d) if is_card_digits(&d) => d,
+        _ => return String::new(),
+    };
+    BRANDS
+        .iter()
+        .find(|(_, ranges)| {
+            ranges.iter().any(|(lo, hi)| {
+                let width = lo.to_string().len();
+                digits[..width]
+                    .parse::<u32>()
+                    .is_ok_and(|prefix| (*lo..=*hi).contains(&prefix))
+            })
+        })
+        .map_or("unknown", |(brand, _)| brand)
+        .to_string()
+}
+
+/// Mask a card number, keeping separators and the last four digits
+///
+/// # Example
+///
+/// ```
+/// use elo_rust::runtime::card::mask_card_number;
+///
+/// assert_eq!(mask_card_number("4111 1111 1111 1111"), "**** **** **** 1111");
+/// ```
+pub fn mask_card_number(raw: &str) -> String {
+    let total = raw.chars().filter(char::is_ascii_digit).count();
+    let mut seen = 0;
+    raw.chars()
+        .map(|ch| {
+            if ch.is_ascii_digit() {
+                seen += 1;
+                if seen + VISIBLE_DIGITS <= total {
+                    return '*';
+                }
+            }
+            ch
+        })
+        .collect()
+}
+
+/// Mask every card number embedded in a piece of text
+///
+/// Digits separated by single spaces or dashes form groups, and a card
+/// number is a span of whole groups with 12-19 digits passing the Luhn
+/// check (`4111 1111 1111 1111`, `4111111111111111`). Neighbouring groups
+/// are not merged into it, so the number in `qty 2 4111111111111111` is
+/// masked; a group is never split, so a longer number such as
+/// `41111111111111110` is not a card number. At each group the longest card
+/// number starting there is masked, and the scan resumes after it.
+pub fn redact(text: &str) -> String {
+    let mut chars: Vec<char> = text.chars().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        if !chars[i].is_ascii_digit() || (i > 0 && chars[i - 1].is_ascii_digit()) {
+            i += 1;
+            continue;
+        }
+
+        // Extend the run over digits and single separators between digits,
+        // numbering the groups of digits between separators
+        let mut digits: Vec<(usize, usize)> = Vec::new();
+        let mut end = i;
+        let mut group = 0;
+        while end < chars.len() {
+            if chars[end].is_ascii_digit() {
+                digits.push((end, group));
+            } else if matches!(chars[end], ' ' | '-')
+                && chars.get(end + 1).is_some_and(char::is_ascii_digit)
+            {
+                group += 1;
+            } else {
+                break;
+            }
+            end += 1;
+        }
+
+        let mut k = 0;
+        while k < digits.len() {
+            let found = (MIN_DIGITS..=MAX_DIGITS).rev().find(|len| {
+                let Some(window) = digits.get(k..k + len) else {
+                    return false;
+                };
+                let whole_groups = digits.get(k + len).is_none_or(|d| d.1 != window[len - 1].1);
+                let number: String = window.iter().map(|&(at, _)| chars[at]).collect();
+                whole_groups && is_card_digits(&number)
+            });
+            match found {
+                Some(len) => {
+                    for &(at, _) in &digits[k..k + len - VISIBLE_DIGITS] {
+                        chars[at] = '*';
+                    }
+                    k += len;
+                }
+                // Move on to the next group
+                None => {
+                    let group = digits[k].1;
+                    while digits.get(k).is_some_and(|d| d.1 == group) {
+                        k += 1;
+                    }
+                }
+            }
+        }
+        i = end;
+    }
+    chars.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_luhn() {
+        assert!(luhn_valid("79927398713"));
+        assert!(!luhn_valid("79927398710"));
+        assert!(!luhn_valid(""));
+        assert!(!luhn_valid("12a4"));
+    }
+
+    #[test]
+    fn test_is_card_number() {
+        assert!(is_card_number("4111111111111111"));
+        assert!(is_card_number("5500-0000-0000-0004"));
+        assert!(is_card_number(" 3782 822463 10005 "));
+        assert!(!is_card_number("4111111111111"));
+        assert!(!is_card_number("4111 1111 1111 111x"));
+        assert!(!is_card_number("0000"));
+        assert!(!is_card_number(""));
+    }
+
+    #[test]
+    fn test_card_brand() {
+        assert_eq!(card_brand("4111111111111111"), "visa");
+        assert_eq!(card_brand("5500 0000 0000 0004"), "mastercard");
+        assert_eq!(card_brand("2223000048400011"), "mastercard");
+        assert_eq!(card_brand("378282246310005"), "amex");
+        assert_eq!(card_brand("6011111111111117"), "discover");
+        assert_eq!(card_brand("3530111333300000"), "jcb");
+        assert_eq!(card_brand("30569309025904"), "diners");
+        assert_eq!(card_brand("6200000000000005"), "unionpay");
+        assert_eq!(card_brand("4111111111111112"), "");
+    }
+
+    #[test]
+    fn test_mask_card_number() {
+        assert_eq!(mask_card_number("4111111111111111"), "************1111");
+        assert_eq!(mask_card_number("3782-822463-10005"), "****-******-*0005");
+    }
+
+    #[test]
+    fn test_redact_embedded_numbers() {
+        assert_eq!(
+            redact("card 4111 1111 1111 1111 declined"),
+            "card **** **** **** 1111 declined"
+        );
+        // Non-Luhn runs and short numbers are left alone
+        assert_eq!(redact("order 4111111111111112"), "order 4111111111111112");
+        assert_eq!(redact("age 42"), "age 42");
+        // Neighbouring groups are not merged into the card number, and a
+        // longer group is not one
+        assert_eq!(redact("qty 2 4111111111111111"), "qty 2 ************1111");
+        assert_eq!(redact("4111111111111111-1"), "************1111-1");
+        assert_eq!(redact("41111111111111110"), "41111111111111110");
+        assert_eq!(
+            redact("4111 1111 1111 1111 and 5500-0000-0000-0004"),
+            "**** **** **** 1111 and ****-****-****-0004"
+        );
+        assert_eq!(redact(""), "");
+    }
+}
@@ -0,0 +1,87 @@
+//! Injectable time source for effective-date windows
+//!
+//! Rules declared with `effective_from` / `expires_at` are only checked
+//! inside their window. Generated validators for such rule sets take a
+//! `&dyn Clock`, so tests and replays can pin the current time instead of
+//! reading the system clock.
+
+use chrono::{DateTime, Utc};
+
+/// Source of the current time consulted by generated validators
+pub trait Clock {
+    /// The current instant
+    fn now(&self) -> DateTime<Utc>;
+}
+
+impl<F: Fn() -> DateTime<Utc>> Clock for F {
+    fn now(&self) -> DateTime<Utc> {
+        self()
+    }
+}
+
+/// Clock reading the system time
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Clock that always returns the same instant
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedClock(pub DateTime<Utc>);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+/// Parse a window boundary: an RFC 3339 timestamp or a `YYYY-MM-DD` date
+///
+/// Dates stand for midnight UTC at the start of that day.
+pub fn parse_instant(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(instant) = DateTime::parse_from_rfc3339(value) {
+        return Ok(instant.with_timezone(&Utc));
+    }
+    chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|midnight| midnight.and_utc())
+        .ok_or_else(|| {
+            format!(
+                "invalid date '{}', expected YYYY-MM-DD or an RFC 3339 timestamp",
+                value
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_instant() {
+        assert_eq!(
+            parse_instant("2025-01-01").unwrap().to_rfc3339(),
+            "2025-01-01T00:00:00+00:00"
+        );
+        assert_eq!(
+            parse_instant("2025-01-01T12:00:00+02:00")
+                .unwrap()
+                .to_rfc3339(),
+            "2025-01-01T10:00:00+00:00"
+        );
+        assert!(parse_instant("01/01/2025").is_err());
+    }
+
+    #[test]
+    fn test_clocks() {
+        let instant = parse_instant("2025-06-30").unwrap();
+        assert_eq!(FixedClock(instant).now(), instant);
+        assert_eq!((move || instant).now(), instant);
+        assert!(SystemClock.now() > instant);
+    }
+}
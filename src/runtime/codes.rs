@@ -0,0 +1,424 @@
+//! Country, currency and language code validation
+//!
+//! Backs the `is_iso_country`, `is_iso_currency` and `is_bcp47_lang` stdlib
+//! functions with embedded ISO 3166-1 alpha-2, ISO 4217 and ISO 639-1 tables.
+//! The tables can be extended or replaced through a [`CodeRegistry`], either a
+//! local one or the process-wide registry used by generated validators (see
+//! [`configure`]).
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{OnceLock, PoisonError, RwLock};
+
+/// ISO 3166-1 alpha-2 country codes
+const ISO_COUNTRIES: &[&str] = &[
+    "AD", "AE", "AF", "AG", "AI", "AL", "AM", "AO", "AQ", "AR", "AS", "AT", "AU", "AW", "AX", "AZ",
+    "BA", "BB", "BD", "BE", "BF", "BG", "BH", "BI", "BJ", "BL", "BM", "BN", "BO", "BQ", "BR", "BS",
+    "BT", "BV", "BW", "BY", "BZ", "CA", "CC", "CD", "CF", "CG", "CH", "CI", "CK", "CL", "CM", "CN",
+    "CO", "CR", "CU", "CV", "CW", "CX", "CY", "CZ", "DE", "DJ", "DK", "DM", "DO", "DZ", "EC", "EE",
+    "EG", "EH", "ER", "ES", "ET", "FI", "FJ", "FK", "FM", "FO", "FR", "GA", "GB", "GD", "GE", "GF",
+    "GG", "GH", "GI", "GL", "GM", "GN", "GP", "GQ", "GR", "GS", "GT", "GU", "GW", "GY", "HK", "HM",
+    "HN", "HR", "HT", "HU", "ID", "IE", "IL", "IM", "IN", "IO", "IQ", "IR", "IS", "IT", "JE", "JM",
+    "JO", "JP", "KE", "KG", "KH", "KI", "KM", "KN", "KP", "KR", "KW", "KY", "KZ", "LA", "LB", "LC",
+    "LI", "LK", "LR", "LS", "LT", "LU", "LV", "LY", "MA", "MC", "MD", "ME", "MF", "MG", "MH", "MK",
+    "ML", "MM", "MN", "MO", "MP", "MQ", "MR", "MS", "MT", "MU", "MV", "MW", "MX", "MY", "MZ", "NA",
+    "NC", "NE", "NF", "NG", "NI", "NL", "NO", "NP", "NR", "NU", "NZ", "OM", "PA", "PE", "PF", "PG",
+    "PH", "PK", "PL", "PM", "PN", "PR", "PS", "PT", "PW", "PY", "QA", "RE", "RO", "RS", "RU", "RW",
+    "SA", "SB", "SC", "SD", "SE", "SG", "SH", "SI", "SJ", "SK", "SL", "SM", "SN", "SO", "SR", "SS",
+    "ST", "SV", "SX", "SY", "SZ", "TC", "TD", "TF", "TG", "TH", "TJ", "TK", "TL", "TM", "TN", "TO",
+    "TR", "TT", "TV", "TW", "TZ", "UA", "UG", "UM", "US", "UY", "UZ", "VA", "VC", "VE", "VG", "VI",
+    "VN", "VU", "WF", "WS", "YE", "YT", "ZA", "ZM", "ZW",
+];
+
+/// ISO 4217 active currency codes (including funds and precious metals)
+const ISO_CURRENCIES: &[&str] = &[
+    "AED", "AFN", "ALL", "AMD", "AOA", "ARS", "AUD", "AWG", "AZN", "BAM", "BBD", "BDT", "BHD",
+    "BIF", "BMD", "BND", "BOB", "BOV", "BRL", "BSD", "BTN", "BWP", "BYN", "BZD", "CAD", "CDF",
+    "CHE", "CHF", "CHW", "CLF", "CLP", "CNY", "COP", "COU", "CRC", "CUP", "CVE", "CZK", "DJF",
+    "DKK", "DOP", "DZD", "EGP", "ERN", "ETB", "EUR", "FJD", "FKP", "GBP", "GEL", "GHS", "GIP",
+    "GMD", "GNF", "GTQ", "GYD", "HKD", "HNL", "HTG", "HUF", "IDR", "ILS", "INR", "IQD", "IRR",
+    "ISK", "JMD", "JOD", "JPY", "KES", "KGS", "KHR", "KMF", "KPW", "KRW", "KWD", "KYD", "KZT",
+    "LAK", "LBP", "LKR", "LRD", "LSL", "LYD", "MAD", "MDL", "MGA", "MKD", "MMK", "MNT", "MOP",
+    "MRU", "MUR", "MVR", "MWK", "MXN", "MXV", "MYR", "MZN", "NAD", "NGN", "NIO", "NOK", "NPR",
+    "NZD", "OMR", "PAB", "PEN", "PGK", "PHP", "PKR", "PLN", "PYG", "QAR", "RON", "RSD", "RUB",
+    "RWF", "SAR", "SBD", "SCR", "SDG", "SEK", "SGD", "SHP", "SLE", "SOS", "SRD", "SSP", "STN",
+    "SVC", "SYP", "SZL", "THB", "TJS", "TMT", "TND", "TOP", "TRY", "TTD", "TWD", "TZS", "UAH",
+    "UGX", "USD", "USN", "UYI", "UYU", "UYW", "UZS", "VED", "VES", "VND", "VUV", "WST", "XAF",
+    "XAG", "XAU", "XBA", "XBB", "XBC", "XBD", "XCD", "XCG", "XDR", "XOF", "XPD", "XPF", "XPT",
+    "XSU", "XTS", "XUA", "XXX", "YER", "ZAR", "ZMW", "ZWG",
+];
+
+/// ISO 639-1 two-letter language codes
+const ISO_LANGUAGES: &[&str] = &[
+    "aa", "ab", "ae", "af", "ak", "am", "an", "ar", "as", "av", "ay", "az", "ba", "be", "bg", "bi",
+    "bm", "bn", "bo", "br", "bs", "ca", "ce", "ch", "co", "cr", "cs", "cu", "cv", "cy", "da", "de",
+    "dv", "dz", "ee", "el", "en", "eo", "es", "et", "eu", "fa", "ff", "fi", "fj", "fo", "fr", "fy",
+    "ga", "gd", "gl", "gn", "gu", "gv", "ha", "he", "hi", "ho", "hr", "ht", "hu", "hy", "hz", "ia",
+    "id", "ie", "ig", "ii", "ik", "io", "is", "it", "iu", "ja", "jv", "ka", "kg", "ki", "kj", "kk",
+    "kl", "km", "kn", "ko", "kr", "ks", "ku", "kv", "kw", "ky", "la", "lb", "lg", "li", "ln", "lo",
+    "lt", "lu", "lv", "mg", "mh", "mi", "mk", "ml", "mn", "mr", "ms", "mt", "my", "na", "nb", "nd",
+    "ne", "ng", "nl", "nn", "no", "nr", "nv", "ny", "oc", "oj", "om", "or", "os", "pa", "pi", "pl",
+    "ps", "pt", "qu", "rm", "rn", "ro", "ru", "rw", "sa", "sc", "sd", "se", "sg", "si", "sk", "sl",
+    "sm", "sn", "so", "sq", "sr", "ss", "st", "su", "sv", "sw", "ta", "te", "tg", "th", "ti", "tk",
+    "tl", "tn", "to", "tr", "ts", "tt", "tw", "ty", "ug", "uk", "ur", "uz", "ve", "vi", "vo", "wa",
+    "wo", "xh", "yi", "yo", "za", "zh", "zu",
+];
+
+/// A code list that can be validated against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CodeList {
+    /// ISO 3166-1 alpha-2 country codes (uppercase, e.g. "DE")
+    Country,
+    /// ISO 4217 currency codes (uppercase, e.g. "EUR")
+    Currency,
+    /// ISO 639-1 language codes used as BCP 47 primary subtags (lowercase, e.g. "de")
+    Language,
+}
+
+impl CodeList {
+    fn builtin(self) -> &'static [&'static str] {
+        match self {
+            Self::Country => ISO_COUNTRIES,
+            Self::Currency => ISO_CURRENCIES,
+            Self::Language => ISO_LANGUAGES,
+        }
+    }
+
+    /// Bring a code into the list's canonical case
+    fn canonical(self, code: &str) -> String {
+        match self {
+            Self::Country | Self::Currency => code.to_ascii_uppercase(),
+            Self::Language => code.to_ascii_lowercase(),
+        }
+    }
+}
+
+/// Built-in code tables plus custom additions, removals and replacements
+#[derive(Debug, Clone, Default)]
+pub struct CodeRegistry {
+    added: HashMap<CodeList, HashSet<String>>,
+    removed: HashMap<CodeList, HashSet<String>>,
+    replaced: HashMap<CodeList, HashSet<String>>,
+}
+
+impl CodeRegistry {
+    /// Create a registry containing only the built-in tables
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accept additional codes (e.g. "XK" for Kosovo, in-house currencies)
+    pub fn extend<I, S>(&mut self, list: CodeList, codes: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let added = self.added.entry(list).or_default();
+        let removed = self.removed.entry(list).or_default();
+        for code in codes {
+            let code = list.canonical(code.as_ref());
+            removed.remove(&code);
+            added.insert(code);
+        }
+    }
+
+    /// Reject codes that would otherwise be accepted
+    pub fn remove<I, S>(&mut self, list: CodeList, codes: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let added = self.added.entry(list).or_default();
+        let removed = self.removed.entry(list).or_default();
+        for code in codes {
+            let code = list.canonical(code.as_ref());
+            added.remove(&code);
+            removed.insert(code);
+        }
+    }
+
+    /// Use a custom code list instead of the built-in table
+    pub fn replace<I, S>(&mut self, list: CodeList, codes: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.added.remove(&list);
+        self.removed.remove(&list);
+        self.replaced.insert(
+            list,
+            codes
+                .into_iter()
+                .map(|c| list.canonical(c.as_ref()))
+                .collect(),
+        );
+    }
+
+    /// Restore the built-in table for a list
+    pub fn reset(&mut self, list: CodeList) {
+        self.added.remove(&list);
+        self.removed.remove(&list);
+        self.replaced.remove(&list);
+    }
+
+    /// Check if a code (in canonical case) is part of a list
+    pub fn contains(&self, list: CodeList, code: &str) -> bool {
+        if self.removed.get(&list).is_some_and(|r| r.contains(code)) {
+            return false;
+        }
+        if self.added.get(&list).is_some_and(|a| a.contains(code)) {
+            return true;
+        }
+        match self.replaced.get(&list) {
+            Some(custom) => custom.contains(code),
+            None => list.builtin().binary_search(&code).is_ok(),
+        }
+    }
+
+    /// Check for an ISO 3166-1 alpha-2 country code (uppercase)
+    pub fn is_country(&self, code: &str) -> bool {
+        code.len() == 2 && self.contains(CodeList::Country, code)
+    }
+
+    /// Check for an ISO 4217 currency code (uppercase)
+    pub fn is_currency(&self, code: &str) -> bool {
+        code.len() == 3 && self.contains(CodeList::Currency, code)
+    }
+
+    /// Check for a well-formed BCP 47 language tag (e.g. "en", "pt-BR", "zh-Hant-TW")
+    ///
+    /// Tags are matched case-insensitively. Two-letter primary languages and
+    /// two-letter regions must be in the registry; three-letter languages and
+    /// numeric (UN M.49) regions are checked for form only.
+    pub fn is_bcp47_lang(&self, tag: &str) -> bool {
+        let subtags: Vec<&str> = tag.split('-').collect();
+        if subtags
+            .iter()
+            .any(|s| s.is_empty() || s.len() > 8 || !s.bytes().all(|b| b.is_ascii_alphanumeric()))
+        {
+            return false;
+        }
+        if subtags[0].eq_ignore_ascii_case("x") {
+            return is_private_use(&subtags[1..]);
+        }
+
+        let mut rest = subtags.as_slice();
+
+        // language: 2-3 letters (ISO 639) or 5-8 letters (registered)
+        let language = rest[0];
+        rest = &rest[1..];
+        if !is_alpha(language) {
+            return false;
+        }
+        match language.len() {
+            2 => {
+                if !self.contains(CodeList::Language, &language.to_ascii_lowercase()) {
+                    return false;
+                }
+            }
+            3 | 5..=8 => {}
+            _ => return false,
+        }
+
+        // extlang: up to three 3-letter subtags after a short language
+        if language.len() <= 3 {
+            let mut extlangs = 0;
+            while let Some(s) = rest.first() {
+                if extlangs < 3 && s.len() == 3 && is_alpha(s) {
+                    extlangs += 1;
+                    rest = &rest[1..];
+                } else {
+                    break;
+                }
+            }
+        }
+
+        // script: 4 letters
+        if let Some(s) = rest.first() {
+            if s.len() == 4 && is_alpha(s) {
+                rest = &rest[1..];
+            }
+        }
+
+        // region: 2 letters (country code) or 3 digits (UN M.49)
+        if let Some(s) = rest.first() {
+            if s.len() == 2 && is_alpha(s) {
+                if !self.is_country(&s.to_ascii_uppercase()) {
+                    return false;
+                }
+                rest = &rest[1..];
+            } else if s.len() == 3 && s.bytes().all(|b| b.is_ascii_digit()) {
+                rest = &rest[1..];
+            }
+        }
+
+        // variants: 5-8 alphanumerics, or a digit followed by 3 alphanumerics
+        while let Some(s) = rest.first() {
+            let is_variant =
+                (5..=8).contains(&s.len()) || (s.len() == 4 && s.as_bytes()[0].is_ascii_digit());
+            if !is_variant {
+                break;
+            }
+            rest = &rest[1..];
+        }
+
+        // extensions: a singleton followed by one or more 2-8 character subtags
+        while let Some(s) = rest.first() {
+            if s.len() != 1 || s.eq_ignore_ascii_case("x") {
+                break;
+            }
+            let count = rest[1..].iter().take_while(|t| t.len() >= 2).count();
+            if count == 0 {
+                return false;
+            }
+            rest = &rest[1 + count..];
+        }
+
+        match rest.first() {
+            None => true,
+            Some(s) if s.eq_ignore_ascii_case("x") => is_private_use(&rest[1..]),
+            Some(_) => false,
+        }
+    }
+}
+
+fn is_alpha(s: &str) -> bool {
+    s.bytes().all(|b| b.is_ascii_alphabetic())
+}
+
+/// Private use section: one or more subtags after `x-`
+fn is_private_use(subtags: &[&str]) -> bool {
+    !subtags.is_empty()
+}
+
+/// Process-wide registry used by generated validators
+pub fn global() -> &'static RwLock<CodeRegistry> {
+    static REGISTRY: OnceLock<RwLock<CodeRegistry>> = OnceLock::new();
+    REGISTRY.get_or_init(|| RwLock::new(CodeRegistry::new()))
+}
+
+/// Customise the process-wide registry
+///
+/// # Example
+///
+/// ```
+/// use elo_rust::runtime::codes::{configure, is_iso_country, CodeList};
+///
+/// configure(|registry| registry.extend(CodeList::Country, ["XK"]));
+/// assert!(is_iso_country("XK"));
+/// ```
+pub fn configure(f: impl FnOnce(&mut CodeRegistry)) {
+    let mut registry = global().write().unwrap_or_else(PoisonError::into_inner);
+    f(&mut registry);
+}
+
+fn with_global<T>(f: impl FnOnce(&CodeRegistry) -> T) -> T {
+    let registry = global().read().unwrap_or_else(PoisonError::into_inner);
+    f(&registry)
+}
+
+/// Check for an ISO 3166-1 alpha-2 country code using the global registry
+pub fn is_iso_country(code: &str) -> bool {
+    with_global(|r| r.is_country(code))
+}
+
+/// Check for an ISO 4217 currency code using the global registry
+pub fn is_iso_currency(code: &str) -> bool {
+    with_global(|r| r.is_currency(code))
+}
+
+/// Check for a well-formed BCP 47 language tag using the global registry
+pub fn is_bcp47_lang(tag: &str) -> bool {
+    with_global(|r| r.is_bcp47_lang(tag))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_tables_are_sorted() {
+        // `contains` relies on binary search
+        for table in [ISO_COUNTRIES, ISO_CURRENCIES, ISO_LANGUAGES] {
+            assert!(table.windows(2).all(|w| w[0] < w[1]));
+        }
+        assert_eq!(ISO_COUNTRIES.len(), 249);
+    }
+
+    #[test]
+    fn test_country_codes() {
+        let registry = CodeRegistry::new();
+        assert!(registry.is_country("DE"));
+        assert!(registry.is_country("US"));
+        assert!(!registry.is_country("us"));
+        assert!(!registry.is_country("XK"));
+        assert!(!registry.is_country("DEU"));
+    }
+
+    #[test]
+    fn test_currency_codes() {
+        let registry = CodeRegistry::new();
+        assert!(registry.is_currency("EUR"));
+        assert!(registry.is_currency("JPY"));
+        assert!(!registry.is_currency("EU"));
+        assert!(!registry.is_currency("BTC"));
+    }
+
+    #[test]
+    fn test_bcp47_tags() {
+        let registry = CodeRegistry::new();
+        for tag in [
+            "en",
+            "pt-BR",
+            "zh-Hant-TW",
+            "es-419",
+            "de-CH-1996",
+            "sr-Latn",
+            "en-US-x-twain",
+            "haw",
+            "zh-yue-HK",
+            "en-a-bbb-x-a-ccc",
+            "x-private",
+            "EN-us",
+        ] {
+            assert!(registry.is_bcp47_lang(tag), "expected valid: {}", tag);
+        }
+        for tag in [
+            "",
+            "e",
+            "qq",
+            "en-",
+            "en--US",
+            "en-ZZ",
+            "toolongtag1",
+            "en-a",
+            "en-x",
+            "en_US",
+        ] {
+            assert!(!registry.is_bcp47_lang(tag), "expected invalid: {}", tag);
+        }
+    }
+
+    #[test]
+    fn test_registry_extend_and_remove() {
+        let mut registry = CodeRegistry::new();
+        registry.extend(CodeList::Country, ["xk"]);
+        registry.remove(CodeList::Currency, ["XXX", "XTS"]);
+        assert!(registry.is_country("XK"));
+        assert!(registry.is_bcp47_lang("sq-XK"));
+        assert!(!registry.is_currency("XXX"));
+        assert!(registry.is_currency("EUR"));
+
+        registry.reset(CodeList::Country);
+        assert!(!registry.is_country("XK"));
+    }
+
+    #[test]
+    fn test_registry_replace() {
+        let mut registry = CodeRegistry::new();
+        registry.replace(CodeList::Currency, ["EUR", "USD", "PTS"]);
+        assert!(registry.is_currency("PTS"));
+        assert!(!registry.is_currency("GBP"));
+        registry.extend(CodeList::Currency, ["GBP"]);
+        assert!(registry.is_currency("GBP"));
+    }
+}
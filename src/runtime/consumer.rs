@@ -0,0 +1,321 @@
+//! Stream-validation sidecars
+//!
+//! A [`Consumer`] applies a [`RuleSet`] to messages taken from a queue
+//! (Kafka, SQS, NATS, ...) and routes them to a [`ValidationSink`]: valid
+//! messages are accepted, and invalid or undecodable ones are handed to the
+//! sink's dead-letter handler together with the reason. The queue client
+//! stays outside this crate; it only has to supply the messages and a
+//! function decoding each one into an [`EloValue`], such as
+//! [`parse_json`](super::stream::parse_json) for JSON payloads (with the
+//! `serde-support` feature).
+//!
+//! # Example
+//!
+//! ```
+//! # #[cfg(feature = "serde-support")] {
+//! use elo_rust::rules::RuleSet;
+//! use elo_rust::runtime::consumer::{Consumer, DeadLetter, ValidationSink};
+//! use elo_rust::runtime::stream::parse_json;
+//! use elo_rust::runtime::ValidationErrors;
+//!
+//! #[derive(Default)]
+//! struct Topics {
+//!     valid: Vec<String>,
+//!     dead_letters: Vec<String>,
+//! }
+//!
+//! impl ValidationSink<String> for Topics {
+//!     type Error = std::convert::Infallible;
+//!
+//!     fn accept(&mut self, message: String, _: &ValidationErrors) -> Result<(), Self::Error> {
+//!         self.valid.push(message);
+//!         Ok(())
+//!     }
+//!
+//!     fn dead_letter(&mut self, letter: DeadLetter<String>) -> Result<(), Self::Error> {
+//!         self.dead_letters.push(letter.payload());
+//!         Ok(())
+//!     }
+//! }
+//!
+//! let consumer = Consumer::new(RuleSet::parse("rule is_adult: age >= 18").unwrap()).unwrap();
+//! let messages = vec![r#"{"age": 21}"#.to_string(), r#"{"age": 12}"#.to_string()];
+//! let mut topics = Topics::default();
+//! let stats = consumer
+//!     .consume(messages, |m: &String| parse_json(m), &mut topics)
+//!     .unwrap();
+//!
+//! assert_eq!((stats.accepted, stats.rejected), (1, 1));
+//! assert!(topics.dead_letters[0].contains("Rule 'is_adult' failed"));
+//! # }
+//! ```
+
+use super::{EloValue, ValidationErrors};
+use crate::rules::{RuleSet, RuleSetError};
+use std::fmt;
+
+/// Destination of validated messages
+pub trait ValidationSink<M> {
+    /// Error raised when forwarding a message fails (e.g. a produce error)
+    type Error;
+
+    /// Forward a message that passed every rule; `warnings` lists the
+    /// `warn_if` rules it triggered
+    fn accept(&mut self, message: M, warnings: &ValidationErrors) -> Result<(), Self::Error>;
+
+    /// Route a rejected message to the dead-letter destination
+    fn dead_letter(&mut self, letter: DeadLetter<M>) -> Result<(), Self::Error>;
+}
+
+/// Why a message was rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rejection {
+    /// The message was decoded but failed one or more rules
+    Invalid(ValidationErrors),
+    /// The message could not be decoded
+    Undecodable(String),
+    /// The rule set itself could not be evaluated
+    RuleSet(RuleSetError),
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid(errors) => write!(f, "{}", errors),
+            Self::Undecodable(error) => write!(f, "could not decode message: {}", error),
+            Self::RuleSet(error) => write!(f, "could not evaluate rule set: {}", error),
+        }
+    }
+}
+
+/// A rejected message and the reason it was rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeadLetter<M> {
+    /// The original message, unchanged
+    pub message: M,
+    /// Why it was rejected
+    pub reason: Rejection,
+}
+
+impl<M> DeadLetter<M> {
+    /// The reason as a JSON object, to attach to the dead-lettered message
+    ///
+    /// Failed validations serialize their errors with
+    /// [`ValidationErrors::to_json`]:
+    /// `{"reason":"invalid","errors":[...]}`. Other reasons carry a message:
+    /// `{"reason":"undecodable","error":"..."}`.
+    pub fn payload(&self) -> String {
+        match &self.reason {
+            Rejection::Invalid(errors) => {
+                format!(r#"{{"reason":"invalid","errors":{}}}"#, errors.to_json())
+            }
+            Rejection::Undecodable(error) => format!(
+                r#"{{"reason":"undecodable","error":{}}}"#,
+                super::json_string(error)
+            ),
+            Rejection::RuleSet(error) => format!(
+                r#"{{"reason":"rule_set","error":{}}}"#,
+                super::json_string(&error.to_string())
+            ),
+        }
+    }
+}
+
+/// What happened to a single message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// Forwarded with [`ValidationSink::accept`]
+    Accepted,
+    /// Dead-lettered because it failed validation
+    Rejected,
+    /// Dead-lettered because it could not be decoded or evaluated
+    Undecodable,
+}
+
+/// Message counts of a consumer run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConsumerStats {
+    /// Messages that passed every rule
+    pub accepted: usize,
+    /// Messages that failed validation
+    pub rejected: usize,
+    /// Messages that could not be decoded or evaluated
+    pub undecodable: usize,
+}
+
+impl ConsumerStats {
+    /// Count one message
+    pub fn record(&mut self, verdict: Verdict) {
+        match verdict {
+            Verdict::Accepted => self.accepted += 1,
+            Verdict::Rejected => self.rejected += 1,
+            Verdict::Undecodable => self.undecodable += 1,
+        }
+    }
+
+    /// Total number of messages counted
+    pub fn total(&self) -> usize {
+        self.accepted + self.rejected + self.undecodable
+    }
+}
+
+/// Applies a rule set to a stream of messages
+///
+/// Rules are checked with [`RuleSet::check`], so flags, sampling and
+/// effective windows are not applied.
+#[derive(Debug, Clone)]
+pub struct Consumer {
+    rules: RuleSet,
+}
+
+impl Consumer {
+    /// Create a consumer for `rules`, rejecting rule sets with cycles up front
+    pub fn new(rules: RuleSet) -> Result<Self, RuleSetError> {
+        rules.check_cycles()?;
+        Ok(Self { rules })
+    }
+
+    /// Validate one message and route it to `sink`
+    ///
+    /// For clients that drive their own (possibly async) receive loop.
+    pub fn handle<M, S>(
+        &self,
+        message: M,
+        decode: impl FnOnce(&M) -> Result<EloValue, String>,
+        sink: &mut S,
+    ) -> Result<Verdict, S::Error>
+    where
+        S: ValidationSink<M>,
+    {
+        let (reason, verdict) = match decode(&message) {
+            Err(error) => (Rejection::Undecodable(error), Verdict::Undecodable),
+            Ok(value) => match self.rules.check(&value) {
+                Err(error) => (Rejection::RuleSet(error), Verdict::Undecodable),
+                Ok(report) if report.is_ok() => {
+                    let mut warnings = ValidationErrors::new();
+                    for warning in report.warned().flat_map(|outcome| &outcome.errors) {
+                        warnings.push(warning.clone());
+                    }
+                    sink.accept(message, &warnings)?;
+                    return Ok(Verdict::Accepted);
+                }
+                Ok(report) => {
+                    let errors = report.into_result().err().unwrap_or_default();
+                    (Rejection::Invalid(errors), Verdict::Rejected)
+                }
+            },
+        };
+        sink.dead_letter(DeadLetter { message, reason })?;
+        Ok(verdict)
+    }
+
+    /// Validate and route every message, stopping at the first sink error
+    pub fn consume<M, S>(
+        &self,
+        messages: impl IntoIterator<Item = M>,
+        mut decode: impl FnMut(&M) -> Result<EloValue, String>,
+        sink: &mut S,
+    ) -> Result<ConsumerStats, S::Error>
+    where
+        S: ValidationSink<M>,
+    {
+        let mut stats = ConsumerStats::default();
+        for message in messages {
+            stats.record(self.handle(message, &mut decode, sink)?);
+        }
+        Ok(stats)
+    }
+}
+
+#[cfg(all(test, feature = "serde-support"))]
+mod tests {
+    use super::*;
+    use crate::runtime::stream::parse_json;
+
+    #[derive(Default)]
+    struct Collect {
+        accepted: Vec<(&'static str, usize)>,
+        dead_letters: Vec<DeadLetter<&'static str>>,
+        fail_after: Option<usize>,
+    }
+
+    impl ValidationSink<&'static str> for Collect {
+        type Error = String;
+
+        fn accept(
+            &mut self,
+            message: &'static str,
+            warnings: &ValidationErrors,
+        ) -> Result<(), String> {
+            if self.fail_after == Some(self.accepted.len()) {
+                return Err("broker unavailable".to_string());
+            }
+            self.accepted.push((message, warnings.len()));
+            Ok(())
+        }
+
+        fn dead_letter(&mut self, letter: DeadLetter<&'static str>) -> Result<(), String> {
+            self.dead_letters.push(letter);
+            Ok(())
+        }
+    }
+
+    fn consumer() -> Consumer {
+        Consumer::new(
+            RuleSet::parse(
+                "rule is_adult: age >= 18\n\
+                 rule low_score: warn_if score < 5 : 'score is low'",
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_consume_routes_messages() {
+        let mut sink = Collect::default();
+        let messages = [
+            r#"{"age": 30, "score": 9}"#,
+            r#"{"age": 30, "score": 1}"#,
+            r#"{"age": 12, "score": 9}"#,
+            "{oops",
+        ];
+        let stats = consumer()
+            .consume(messages, |m: &&str| parse_json(m), &mut sink)
+            .unwrap();
+
+        assert_eq!(stats.total(), 4);
+        assert_eq!(
+            (stats.accepted, stats.rejected, stats.undecodable),
+            (2, 1, 1)
+        );
+        assert_eq!(sink.accepted, vec![(messages[0], 0), (messages[1], 1)]);
+        assert_eq!(sink.dead_letters[0].message, messages[2]);
+        assert_eq!(
+            sink.dead_letters[0].payload(),
+            r#"{"reason":"invalid","errors":[{"path":"is_adult","message":"Rule 'is_adult' failed","rule":"is_adult","severity":"error","value":null}]}"#
+        );
+        assert!(sink.dead_letters[1]
+            .payload()
+            .starts_with(r#"{"reason":"undecodable","error":"invalid JSON"#));
+    }
+
+    #[test]
+    fn test_consume_stops_at_sink_error() {
+        let mut sink = Collect {
+            fail_after: Some(1),
+            ..Collect::default()
+        };
+        let messages = [
+            r#"{"age": 30, "score": 9}"#,
+            r#"{"age": 40, "score": 9}"#,
+            r#"{"age": 50, "score": 9}"#,
+        ];
+        let result = consumer().consume(messages, |m: &&str| parse_json(m), &mut sink);
+        assert_eq!(result, Err("broker unavailable".to_string()));
+        assert_eq!(sink.accepted.len(), 1);
+
+        let cyclic = RuleSet::parse("rule a: b\nrule b: a").unwrap();
+        assert!(Consumer::new(cyclic).is_err());
+    }
+}
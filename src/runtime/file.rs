@@ -0,0 +1,122 @@
+//! File and upload metadata helpers
+//!
+//! Backs the `has_extension`, `is_mime` and `size_kb`/`size_mb`/`size_gb`
+//! stdlib functions used by upload-validation rules such as
+//! `has_extension(file.name, ['png', 'jpg']) && size_mb(file.size) <= 5`.
+
+use super::glob::glob_match;
+
+/// Bytes per kilobyte (binary, as used by most upload limits)
+pub const BYTES_PER_KB: f64 = 1024.0;
+
+/// Check if a file name ends with one of the given extensions
+///
+/// Comparison is case-insensitive and extensions may be given with or
+/// without the leading dot. Names without an extension (or dotfiles such as
+/// `.env`) never match.
+///
+/// # Example
+///
+/// ```
+/// use elo_rust::runtime::file::has_extension;
+///
+/// assert!(has_extension("photo.JPG", &vec!["png", "jpg"]));
+/// assert!(has_extension("archive.tar.gz", &[".gz"]));
+/// assert!(!has_extension("README", &["md"]));
+/// ```
+pub fn has_extension<S: AsRef<str>>(name: &str, extensions: &[S]) -> bool {
+    let file_name = name.rsplit(['/', '\\']).next().unwrap_or(name);
+    let ext = match file_name.rfind('.') {
+        Some(0) | None => return false,
+        Some(pos) => &file_name[pos + 1..],
+    };
+    !ext.is_empty()
+        && extensions
+            .iter()
+            .any(|e| e.as_ref().trim_start_matches('.').eq_ignore_ascii_case(ext))
+}
+
+/// Check if a content type matches one of the given MIME patterns, such as
+/// `image/*`
+///
+/// Parameters (`; charset=utf-8`) are ignored and matching is
+/// case-insensitive; patterns use the same glob syntax as `like`.
+///
+/// # Example
+///
+/// ```
+/// use elo_rust::runtime::file::is_mime;
+///
+/// assert!(is_mime("image/png", &vec!["text/*", "image/*"]));
+/// assert!(is_mime("Text/HTML; charset=utf-8", &["text/html"]));
+/// assert!(!is_mime("application/pdf", &["image/*"]));
+/// ```
+pub fn is_mime<S: AsRef<str>>(content_type: &str, patterns: &[S]) -> bool {
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    let (kind, subtype) = match essence.split_once('/') {
+        Some(parts) => parts,
+        None => return false,
+    };
+    if kind.is_empty() || subtype.is_empty() {
+        return false;
+    }
+    patterns
+        .iter()
+        .any(|p| glob_match(&essence, &p.as_ref().trim().to_ascii_lowercase()))
+}
+
+/// Convert a size in bytes to kilobytes
+pub fn size_kb(bytes: f64) -> f64 {
+    bytes / BYTES_PER_KB
+}
+
+/// Convert a size in bytes to megabytes
+pub fn size_mb(bytes: f64) -> f64 {
+    size_kb(bytes) / BYTES_PER_KB
+}
+
+/// Convert a size in bytes to gigabytes
+pub fn size_gb(bytes: f64) -> f64 {
+    size_mb(bytes) / BYTES_PER_KB
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_has_extension() {
+        assert!(has_extension("avatar.png", &["png", "jpg"]));
+        assert!(has_extension("uploads/2024/scan.PDF", &["pdf"]));
+        assert!(has_extension(r"C:\docs\report.docx", &[".docx"]));
+        assert!(!has_extension("avatar.png.exe", &["png"]));
+        assert!(!has_extension(".bashrc", &["bashrc"]));
+        assert!(!has_extension("trailing.", &[""]));
+        assert!(!has_extension("photo.png", &[] as &[&str]));
+    }
+
+    #[test]
+    fn test_is_mime() {
+        assert!(is_mime("image/jpeg", &["image/*"]));
+        assert!(is_mime("application/vnd.ms-excel", &["application/vnd.*"]));
+        assert!(is_mime("IMAGE/PNG", &["image/png"]));
+        assert!(is_mime("text/plain; charset=utf-8", &["text/*"]));
+        assert!(is_mime("video/mp4", &["image/*", "video/*"]));
+        assert!(!is_mime("video/mp4", &["image/*"]));
+        assert!(!is_mime("image", &["image/*"]));
+        assert!(!is_mime("", &["*"]));
+        assert!(!is_mime("image/png", &[] as &[&str]));
+    }
+
+    #[test]
+    fn test_size_conversions() {
+        assert_eq!(size_kb(2048.0), 2.0);
+        assert_eq!(size_mb(5.0 * 1024.0 * 1024.0), 5.0);
+        assert_eq!(size_gb(1024.0 * 1024.0 * 1024.0), 1.0);
+    }
+}
@@ -0,0 +1,89 @@
+//! Feature-flag gating of rules
+//!
+//! Rules declared with `rule name [flag = "new_kyc"]: ...` are only checked
+//! when the flag is enabled. Generated validators for such rule sets take a
+//! `&dyn FlagProvider`, which can be backed by a static set of flags, a
+//! closure, or an adapter around a feature-flag service client.
+
+use std::collections::{BTreeSet, HashSet};
+
+/// Source of feature-flag state consulted by generated validators
+pub trait FlagProvider {
+    /// Check if a flag is enabled
+    fn is_enabled(&self, flag: &str) -> bool;
+}
+
+impl<F: Fn(&str) -> bool> FlagProvider for F {
+    fn is_enabled(&self, flag: &str) -> bool {
+        self(flag)
+    }
+}
+
+impl FlagProvider for HashSet<String> {
+    fn is_enabled(&self, flag: &str) -> bool {
+        self.contains(flag)
+    }
+}
+
+impl FlagProvider for BTreeSet<String> {
+    fn is_enabled(&self, flag: &str) -> bool {
+        self.contains(flag)
+    }
+}
+
+impl FlagProvider for Vec<String> {
+    fn is_enabled(&self, flag: &str) -> bool {
+        self.iter().any(|f| f == flag)
+    }
+}
+
+impl FlagProvider for Vec<&str> {
+    fn is_enabled(&self, flag: &str) -> bool {
+        self.contains(&flag)
+    }
+}
+
+/// Provider with every flag enabled
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AllEnabled;
+
+impl FlagProvider for AllEnabled {
+    fn is_enabled(&self, _flag: &str) -> bool {
+        true
+    }
+}
+
+/// Provider with every flag disabled
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoneEnabled;
+
+impl FlagProvider for NoneEnabled {
+    fn is_enabled(&self, _flag: &str) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enabled(provider: &dyn FlagProvider, flag: &str) -> bool {
+        provider.is_enabled(flag)
+    }
+
+    #[test]
+    fn test_providers() {
+        let set: HashSet<String> = ["new_kyc".to_string()].into_iter().collect();
+        assert!(enabled(&set, "new_kyc"));
+        assert!(!enabled(&set, "beta"));
+
+        assert!(enabled(&vec!["beta"], "beta"));
+        assert!(!enabled(&vec!["beta".to_string()], "new_kyc"));
+
+        let closure = |flag: &str| flag.starts_with("exp_");
+        assert!(enabled(&closure, "exp_checkout"));
+
+        assert!(enabled(&AllEnabled, "anything"));
+        assert!(!enabled(&NoneEnabled, "anything"));
+    }
+}
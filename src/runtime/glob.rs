@@ -0,0 +1,151 @@
+//! Glob-style pattern matching for the `like` operator
+//!
+//! Patterns support `*` (any sequence, including empty), `?` (exactly one
+//! character) and `\` to escape either wildcard. Matching runs in
+//! `O(text * pattern)` time without backtracking blow-up, so user-supplied
+//! patterns cannot trigger ReDoS-style slowdowns.
+
+/// A single element of a parsed glob pattern
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GlobToken {
+    /// Literal text that must match exactly
+    Literal(String),
+    /// `*`: any sequence of characters
+    AnySequence,
+    /// `?`: exactly one character
+    AnyChar,
+}
+
+/// Split a glob pattern into literal runs and wildcards
+pub fn parse_glob(pattern: &str) -> Vec<GlobToken> {
+    let mut tokens = Vec::new();
+    let mut literal = String::new();
+    let mut chars = pattern.chars();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => literal.push(chars.next().unwrap_or('\\')),
+            '*' | '?' => {
+                if !literal.is_empty() {
+                    tokens.push(GlobToken::Literal(std::mem::take(&mut literal)));
+                }
+                if ch == '?' {
+                    tokens.push(GlobToken::AnyChar);
+                } else if tokens.last() != Some(&GlobToken::AnySequence) {
+                    tokens.push(GlobToken::AnySequence);
+                }
+            }
+            _ => literal.push(ch),
+        }
+    }
+    if !literal.is_empty() {
+        tokens.push(GlobToken::Literal(literal));
+    }
+    tokens
+}
+
+/// Check whether `text` matches the glob `pattern`
+///
+/// # Example
+///
+/// ```
+/// use elo_rust::runtime::glob::glob_match;
+///
+/// assert!(glob_match("alice@example.com", "*@example.com"));
+/// assert!(glob_match("A-1234", "A-????"));
+/// assert!(!glob_match("bob@other.org", "*@example.com"));
+/// ```
+pub fn glob_match(text: &str, pattern: &str) -> bool {
+    let text: Vec<char> = text.chars().collect();
+    // Expand literals into per-character tokens for the matcher
+    let mut pat: Vec<Option<char>> = Vec::new();
+    let mut star_positions = Vec::new();
+    for token in parse_glob(pattern) {
+        match token {
+            GlobToken::Literal(s) => pat.extend(s.chars().map(Some)),
+            GlobToken::AnyChar => pat.push(None),
+            GlobToken::AnySequence => {
+                star_positions.push(pat.len());
+                pat.push(None);
+            }
+        }
+    }
+    let is_star = |i: usize| star_positions.binary_search(&i).is_ok();
+
+    let (mut t, mut p) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pat.len() && is_star(p) {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if p < pat.len() && pat[p].is_none_or(|c| c == text[t]) {
+            t += 1;
+            p += 1;
+        } else if let Some((star_p, star_t)) = backtrack {
+            // Let the last `*` absorb one more character and retry
+            p = star_p + 1;
+            t = star_t + 1;
+            backtrack = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    while p < pat.len() && is_star(p) {
+        p += 1;
+    }
+    p == pat.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_glob() {
+        assert_eq!(
+            parse_glob("*@example.com"),
+            vec![
+                GlobToken::AnySequence,
+                GlobToken::Literal("@example.com".to_string())
+            ]
+        );
+        assert_eq!(
+            parse_glob("a**b"),
+            vec![
+                GlobToken::Literal("a".to_string()),
+                GlobToken::AnySequence,
+                GlobToken::Literal("b".to_string())
+            ]
+        );
+        assert_eq!(
+            parse_glob(r"100\*"),
+            vec![GlobToken::Literal("100*".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("alice@example.com", "*@example.com"));
+        assert!(glob_match("INV-2024-001", "INV-*"));
+        assert!(glob_match("my file.txt", "*file*"));
+        assert!(glob_match("abc", "a?c"));
+        assert!(glob_match("", "*"));
+        assert!(glob_match("aXbYc", "a*b*c"));
+        assert!(!glob_match("abc", "a?"));
+        assert!(!glob_match("abd", "a*c"));
+        assert!(glob_match("100*", r"100\*"));
+        assert!(!glob_match("1000", r"100\*"));
+    }
+
+    #[test]
+    fn test_glob_match_unicode() {
+        assert!(glob_match("héllo", "h?llo"));
+    }
+
+    #[test]
+    fn test_glob_match_pathological_pattern() {
+        let text = "a".repeat(200);
+        let pattern = format!("{}b", "*a".repeat(50));
+        assert!(!glob_match(&text, &pattern));
+    }
+}
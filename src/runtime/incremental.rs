@@ -0,0 +1,216 @@
+//! Incremental re-validation of rule sets
+//!
+//! A [`RuleSetValidator`] keeps the report of the previous run. After a
+//! field-level edit, [`RuleSetValidator::validate_changed`] re-evaluates only
+//! the rules whose field dependencies overlap the changed fields and reuses
+//! the cached outcomes of all other rules. RuleSet-generated code provides a
+//! ready-made validator through `<name>_incremental()`.
+//!
+//! # Example
+//!
+//! ```
+//! use elo_rust::ast::analysis::FieldPath;
+//! use elo_rust::runtime::incremental::RuleSetValidator;
+//! use elo_rust::runtime::report::RuleOutcome;
+//!
+//! struct Form { name: String, age: i64 }
+//!
+//! let mut validator = RuleSetValidator::new()
+//!     .with_rule("has_name", vec![FieldPath::from("name")], |f: &Form| {
+//!         if f.name.is_empty() {
+//!             RuleOutcome::failed("has_name", vec!["name is required".to_string()])
+//!         } else {
+//!             RuleOutcome::passed("has_name")
+//!         }
+//!     })
+//!     .with_rule("is_adult", vec![FieldPath::from("age")], |f: &Form| {
+//!         if f.age >= 18 {
+//!             RuleOutcome::passed("is_adult")
+//!         } else {
+//!             RuleOutcome::failed("is_adult", vec!["too young".to_string()])
+//!         }
+//!     });
+//!
+//! let mut form = Form { name: String::new(), age: 30 };
+//! assert!(!validator.validate(&form).is_ok());
+//!
+//! form.name = "Ada".to_string();
+//! let report = validator.validate_changed(&form, &[FieldPath::from("name")]);
+//! assert!(report.is_ok());
+//! assert_eq!(validator.last_evaluated(), ["has_name"]);
+//! ```
+
+use super::report::{RuleOutcome, ValidationReport};
+use crate::ast::analysis::FieldPath;
+use std::fmt;
+
+type RuleCheck<T> = Box<dyn Fn(&T) -> RuleOutcome + Send + Sync>;
+
+struct IncrementalRule<T: ?Sized> {
+    name: String,
+    fields: Vec<FieldPath>,
+    check: RuleCheck<T>,
+}
+
+/// Validator that re-evaluates only rules affected by changed fields
+pub struct RuleSetValidator<T: ?Sized> {
+    rules: Vec<IncrementalRule<T>>,
+    cached: Option<ValidationReport>,
+    last_evaluated: Vec<String>,
+}
+
+impl<T: ?Sized> fmt::Debug for RuleSetValidator<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rules: Vec<&str> = self.rules.iter().map(|r| r.name.as_str()).collect();
+        f.debug_struct("RuleSetValidator")
+            .field("rules", &rules)
+            .field("cached", &self.cached.is_some())
+            .finish()
+    }
+}
+
+impl<T: ?Sized> Default for RuleSetValidator<T> {
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            cached: None,
+            last_evaluated: Vec::new(),
+        }
+    }
+}
+
+impl<T: ?Sized> RuleSetValidator<T> {
+    /// Create a validator without rules
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a rule with the fields it reads and its check
+    ///
+    /// `fields` must include fields read through referenced rules (see
+    /// [`crate::rules::RuleSet::field_dependencies`]), otherwise edits to
+    /// them will not re-run the rule.
+    pub fn with_rule<F>(mut self, name: impl Into<String>, fields: Vec<FieldPath>, check: F) -> Self
+    where
+        F: Fn(&T) -> RuleOutcome + Send + Sync + 'static,
+    {
+        self.rules.push(IncrementalRule {
+            name: name.into(),
+            fields,
+            check: Box::new(check),
+        });
+        self
+    }
+
+    /// Evaluate every rule, replacing the cached report
+    pub fn validate(&mut self, input: &T) -> &ValidationReport {
+        let mut report = ValidationReport::new();
+        for rule in &self.rules {
+            report.push((rule.check)(input));
+        }
+        self.last_evaluated = self.rules.iter().map(|r| r.name.clone()).collect();
+        self.cached.insert(report)
+    }
+
+    /// Re-evaluate the rules affected by `changed`, reusing cached outcomes
+    ///
+    /// Falls back to a full [`validate`](Self::validate) when there is no
+    /// cached report yet.
+    pub fn validate_changed(&mut self, input: &T, changed: &[FieldPath]) -> &ValidationReport {
+        let Some(mut report) = self.cached.take() else {
+            return self.validate(input);
+        };
+        self.last_evaluated.clear();
+        for (rule, outcome) in self.rules.iter().zip(report.outcomes.iter_mut()) {
+            let affected = rule
+                .fields
+                .iter()
+                .any(|field| changed.iter().any(|c| c.overlaps(field)));
+            if affected {
+                *outcome = (rule.check)(input);
+                self.last_evaluated.push(rule.name.clone());
+            }
+        }
+        self.cached.insert(report)
+    }
+
+    /// Report of the most recent run
+    pub fn cached(&self) -> Option<&ValidationReport> {
+        self.cached.as_ref()
+    }
+
+    /// Rules evaluated by the most recent run
+    pub fn last_evaluated(&self) -> &[String] {
+        &self.last_evaluated
+    }
+
+    /// Drop the cached report, so the next run evaluates every rule
+    pub fn invalidate(&mut self) {
+        self.cached = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    struct Doc {
+        a: i64,
+        b: i64,
+    }
+
+    fn positive(name: &'static str, read: fn(&Doc) -> i64) -> impl Fn(&Doc) -> RuleOutcome {
+        move |doc| {
+            if read(doc) > 0 {
+                RuleOutcome::passed(name)
+            } else {
+                RuleOutcome::failed(name, vec![format!("{} must be positive", name)])
+            }
+        }
+    }
+
+    fn validator(calls: Arc<AtomicUsize>) -> RuleSetValidator<Doc> {
+        let counted = move |doc: &Doc| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            positive("b", |d| d.b)(doc)
+        };
+        RuleSetValidator::new()
+            .with_rule("a", vec![FieldPath::from("a")], positive("a", |d| d.a))
+            .with_rule("b", vec![FieldPath::from("b")], counted)
+    }
+
+    #[test]
+    fn test_only_affected_rules_rerun() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut validator = validator(calls.clone());
+        let mut doc = Doc { a: -1, b: 1 };
+        assert!(!validator.validate(&doc).is_ok());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        doc.a = 5;
+        let report = validator.validate_changed(&doc, &[FieldPath::from("a")]);
+        assert!(report.is_ok());
+        assert_eq!(report.outcomes.len(), 2);
+        assert_eq!(validator.last_evaluated(), ["a"]);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_first_run_and_invalidate_evaluate_everything() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut validator = validator(calls.clone());
+        let doc = Doc { a: 1, b: 1 };
+        validator.validate_changed(&doc, &[]);
+        assert_eq!(validator.last_evaluated(), ["a", "b"]);
+
+        validator.validate_changed(&doc, &[]);
+        assert!(validator.last_evaluated().is_empty());
+
+        validator.invalidate();
+        assert!(validator.cached().is_none());
+        validator.validate_changed(&doc, &[]);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,62 @@
+//! Recording the value a generated validator rejected
+//!
+//! Generated validators attach the value of the field a failed check read
+//! to the [`ValidationError`](super::ValidationError) they report. Field
+//! types need not implement any formatting trait, so the value is taken by
+//! autoref specialization, in order of preference: `Display`, then `Debug`,
+//! then nothing.
+//!
+//! ```
+//! use elo_rust::runtime::inspect::{Inspect, InspectDebug, InspectDisplay, InspectNone};
+//!
+//! struct Opaque;
+//! assert_eq!((&&&Inspect(&"ada")).reported_value(), Some("ada".to_string()));
+//! assert_eq!((&&&Inspect(&Some(3))).reported_value(), Some("Some(3)".to_string()));
+//! assert_eq!((&&&Inspect(&Opaque)).reported_value(), None);
+//! ```
+//!
+//! The choice is made where the method is called, so it only works on a
+//! concrete type, as in generated code; inside a generic function every
+//! value falls through to `None`.
+
+use std::fmt;
+
+/// A value whose formatting is chosen by [`reported_value`](InspectDisplay::reported_value)
+#[derive(Debug)]
+pub struct Inspect<'a, T: ?Sized>(pub &'a T);
+
+/// Formats values that implement `Display`; call on `&&&Inspect`
+pub trait InspectDisplay {
+    /// The value as text, if it can be formatted
+    fn reported_value(&self) -> Option<String>;
+}
+
+impl<T: fmt::Display + ?Sized> InspectDisplay for &&Inspect<'_, T> {
+    fn reported_value(&self) -> Option<String> {
+        Some(self.0.to_string())
+    }
+}
+
+/// Formats values that implement `Debug` but not `Display`
+pub trait InspectDebug {
+    /// The value as text, if it can be formatted
+    fn reported_value(&self) -> Option<String>;
+}
+
+impl<T: fmt::Debug + ?Sized> InspectDebug for &Inspect<'_, T> {
+    fn reported_value(&self) -> Option<String> {
+        Some(format!("{:?}", self.0))
+    }
+}
+
+/// Fallback for values that cannot be formatted
+pub trait InspectNone {
+    /// Always `None`
+    fn reported_value(&self) -> Option<String>;
+}
+
+impl<T: ?Sized> InspectNone for Inspect<'_, T> {
+    fn reported_value(&self) -> Option<String> {
+        None
+    }
+}
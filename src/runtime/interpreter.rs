@@ -0,0 +1,1432 @@
+//! Direct evaluation of ELO expressions
+//!
+//! The [`Evaluator`] walks a parsed [`Expr`] against an [`EloValue`] input
+//! instead of generating Rust code, for rules that are only known at
+//! runtime (one-off data checks, rule-editing tools). Identifiers name
+//! fields of the input object; missing fields read as `null`.
+//!
+//! `let` bindings and lambda parameters shadow input fields within their
+//! body. Pipes pass the value on the left as the first argument of the
+//! function on the right (`name |> trim()`), or bind it to the parameter of
+//! a lambda (`price |> fn(p ~> p * 2)`). A failed `guard` is an error.
+//!
+//! Temporal literals and keywords evaluate to [`EloValue::Temporal`];
+//! strings in the input are read as dates or datetimes when compared with
+//! one. `TODAY` and `NOW` come from the system clock unless
+//! [`Evaluator::with_now`] fixes them.
+//!
+//! # Example
+//!
+//! ```
+//! use elo_rust::parser::Parser;
+//! use elo_rust::runtime::interpreter::Evaluator;
+//! use elo_rust::runtime::EloValue;
+//! use std::collections::BTreeMap;
+//!
+//! let input = EloValue::Object(BTreeMap::from([
+//!     ("age".to_string(), EloValue::Integer(21)),
+//!     ("email".to_string(), EloValue::String("ada@example.com".to_string())),
+//! ]));
+//! let expr = Parser::parse("age >= 18 && contains(email, '@')").unwrap();
+//! assert_eq!(Evaluator::new().evaluate(&expr, &input), Ok(EloValue::Boolean(true)));
+//! ```
+
+use super::glob::glob_match;
+use super::temporal::TemporalValue;
+use super::EloValue;
+use super::{card, codes, file, format, password, phone, presence};
+use crate::ast::{printer, BinaryOperator, Expr, Literal, TemporalKeyword, UnaryOperator};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, TimeZone, Utc};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Errors raised while evaluating an expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvalError {
+    /// An operation failed on the values it was given (type mismatch,
+    /// division by zero, overflow, invalid regex)
+    Runtime(String),
+    /// The expression uses a construct the evaluator does not support
+    Unsupported(String),
+    /// An operation failed on a value read from the input
+    At {
+        /// Path of the input value involved (e.g. `items[2].price`)
+        path: String,
+        /// Collection elements being visited, outermost first
+        /// (e.g. `item = items[2]`)
+        trace: Vec<String>,
+        /// The underlying error
+        error: Box<EvalError>,
+    },
+}
+
+impl EvalError {
+    /// Path of the input value involved, if known
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            Self::At { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// The underlying error, without its location
+    pub fn root(&self) -> &EvalError {
+        match self {
+            Self::At { error, .. } => error.root(),
+            other => other,
+        }
+    }
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Runtime(message) => write!(f, "{}", message),
+            Self::Unsupported(what) => write!(f, "{} is not supported by the evaluator", what),
+            Self::At { path, trace, error } => {
+                write!(f, "{}: {}", path, error)?;
+                if !trace.is_empty() {
+                    write!(f, " (while checking {})", trace.join(", "))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for EvalError {}
+
+/// Outcome of [`Evaluator::check`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckResult {
+    /// Whether the expression evaluated to a truthy value
+    pub passed: bool,
+    /// The deepest sub-expression responsible for the failure: a false
+    /// conjunct of `&&`, the condition of a failing `require`, or the branch
+    /// an `if` took
+    pub failed_subexpr: Option<Expr>,
+    /// Why the check failed, empty when it passed
+    ///
+    /// A failing `require` contributes its own message; otherwise the failed
+    /// sub-expression is rendered as ELO source.
+    pub message: String,
+}
+
+/// A `let` variable or lambda parameter and the value bound to it
+#[derive(Debug)]
+struct Frame {
+    param: String,
+    value: EloValue,
+    /// Path of the element in the input, when the collection came from it
+    path: Option<String>,
+}
+
+/// Evaluation state: the input, the variables in scope and the clock
+#[derive(Debug)]
+struct Env<'a> {
+    input: &'a EloValue,
+    stack: Vec<Frame>,
+    today: NaiveDate,
+    now: DateTime<Utc>,
+}
+
+impl Env<'_> {
+    fn lookup(&self, name: &str) -> Option<&Frame> {
+        self.stack.iter().rev().find(|frame| frame.param == name)
+    }
+
+    /// Input path an expression reads from, if it is a plain field access
+    fn path_of(&self, expr: &Expr) -> Option<String> {
+        match expr {
+            Expr::Identifier(name) => match self.lookup(name) {
+                Some(frame) => frame.path.clone(),
+                None => Some(name.clone()),
+            },
+            Expr::FieldAccess { receiver, field } => self
+                .path_of(receiver)
+                .map(|receiver| format!("{}.{}", receiver, field)),
+            Expr::Index { collection, index } => match **index {
+                Expr::Literal(Literal::Integer(n)) => self
+                    .path_of(collection)
+                    .map(|collection| format!("{}[{}]", collection, n)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Locate an error raised by an operation on `operands`
+    ///
+    /// The path is that of the first operand read from the input, or else
+    /// of the innermost element being visited. Errors that already carry a
+    /// location are returned unchanged.
+    fn locate(&self, error: EvalError, operands: &[&Expr]) -> EvalError {
+        if !matches!(error, EvalError::Runtime(_)) {
+            return error;
+        }
+        let path = operands.iter().find_map(|operand| self.path_of(operand));
+        self.locate_at(error, path)
+    }
+
+    /// Locate an error at `path`, or else at the innermost element being
+    /// visited
+    fn locate_at(&self, error: EvalError, path: Option<String>) -> EvalError {
+        if !matches!(error, EvalError::Runtime(_)) {
+            return error;
+        }
+        let path = path.or_else(|| self.stack.iter().rev().find_map(|frame| frame.path.clone()));
+        match path {
+            Some(path) => EvalError::At {
+                path,
+                trace: self
+                    .stack
+                    .iter()
+                    .filter_map(|frame| {
+                        let path = frame.path.as_ref()?;
+                        Some(format!("{} = {}", frame.param, path))
+                    })
+                    .collect(),
+                error: Box::new(error),
+            },
+            None => error,
+        }
+    }
+}
+
+/// Tree-walking evaluator for ELO expressions
+///
+/// Runtime errors on values read from the input report where they happened
+/// with [`EvalError::At`]: the path of the value (`items[2].price`) and the
+/// collection elements visited by `any`/`all` on the way there.
+#[derive(Debug, Clone, Default)]
+pub struct Evaluator {
+    /// Fixed current time, instead of the system clock
+    now: Option<DateTime<Utc>>,
+}
+
+impl Evaluator {
+    /// Create an evaluator
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Evaluate temporal keywords against a fixed current time
+    ///
+    /// `NOW` is `now` and `TODAY` its UTC date, so rules that depend on the
+    /// date can be tested deterministically.
+    pub fn with_now(mut self, now: DateTime<Utc>) -> Self {
+        self.now = Some(now);
+        self
+    }
+
+    /// Evaluate `expr` with identifiers resolved against the fields of `input`
+    pub fn evaluate(&self, expr: &Expr, input: &EloValue) -> Result<EloValue, EvalError> {
+        let mut env = self.env(input);
+        self.eval(expr, &mut env)
+    }
+
+    fn env<'a>(&self, input: &'a EloValue) -> Env<'a> {
+        let (today, now) = match self.now {
+            Some(now) => (now.date_naive(), now),
+            None => (Local::now().date_naive(), Utc::now()),
+        };
+        Env {
+            input,
+            stack: Vec::new(),
+            today,
+            now,
+        }
+    }
+
+    /// Evaluate `expr` as a condition and explain a failure
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use elo_rust::parser::Parser;
+    /// use elo_rust::runtime::interpreter::Evaluator;
+    /// use elo_rust::runtime::EloValue;
+    /// use std::collections::BTreeMap;
+    ///
+    /// let input = EloValue::Object(BTreeMap::from([
+    ///     ("age".to_string(), EloValue::Integer(21)),
+    ///     ("country".to_string(), EloValue::String("US".to_string())),
+    /// ]));
+    /// let expr = Parser::parse("age >= 18 && (country == 'FR' || country == 'DE')").unwrap();
+    /// let result = Evaluator::new().check(&expr, &input).unwrap();
+    /// assert!(!result.passed);
+    /// assert_eq!(result.message, "condition not met: country == 'FR' || country == 'DE'");
+    /// ```
+    pub fn check(&self, expr: &Expr, input: &EloValue) -> Result<CheckResult, EvalError> {
+        let mut env = self.env(input);
+        if self.eval(expr, &mut env)?.is_truthy() {
+            return Ok(CheckResult {
+                passed: true,
+                failed_subexpr: None,
+                message: String::new(),
+            });
+        }
+        let (failed, message) = self.deepest_failure(expr, None, &mut env)?;
+        Ok(CheckResult {
+            passed: false,
+            message: message
+                .unwrap_or_else(|| format!("condition not met: {}", printer::format(failed))),
+            failed_subexpr: Some(failed.clone()),
+        })
+    }
+
+    /// Descend from a falsy `expr` to the sub-expression that made it fail,
+    /// picking up the message of the innermost `require` on the way
+    fn deepest_failure<'e>(
+        &self,
+        expr: &'e Expr,
+        message: Option<String>,
+        env: &mut Env<'_>,
+    ) -> Result<(&'e Expr, Option<String>), EvalError> {
+        match expr {
+            Expr::BinaryOp {
+                op: BinaryOperator::And,
+                left,
+                right,
+            } => {
+                let failed = if self.eval(left, env)?.is_truthy() {
+                    right
+                } else {
+                    left
+                };
+                self.deepest_failure(failed, message, env)
+            }
+            Expr::Require {
+                condition,
+                message: require_message,
+            } => self.deepest_failure(condition, Some(require_message.clone()), env),
+            Expr::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                let branch = if self.eval(condition, env)?.is_truthy() {
+                    then_branch
+                } else {
+                    else_branch
+                };
+                self.deepest_failure(branch, message, env)
+            }
+            _ => Ok((expr, message)),
+        }
+    }
+
+    fn eval(&self, expr: &Expr, env: &mut Env<'_>) -> Result<EloValue, EvalError> {
+        match expr {
+            Expr::Literal(Literal::Integer(n)) => Ok(EloValue::Integer(*n)),
+            Expr::Literal(Literal::Float(f)) => Ok(EloValue::Float(*f)),
+            Expr::Percentage(percent) => Ok(EloValue::Float(percent / 100.0)),
+            Expr::Literal(Literal::Boolean(b)) => Ok(EloValue::Boolean(*b)),
+            Expr::String(s) => Ok(EloValue::String(s.clone())),
+            Expr::Null => Ok(EloValue::Null),
+            Expr::Identifier(name) => match env.lookup(name) {
+                Some(frame) => Ok(frame.value.clone()),
+                None => Ok(env.input.get_path(&[name.as_str()])),
+            },
+            Expr::FieldAccess { receiver, field } => {
+                Ok(self.eval(receiver, env)?.get_path(&[field.as_str()]))
+            }
+            Expr::Index { collection, index } => {
+                let items = self.eval(collection, env)?;
+                let position = self.eval(index, env)?;
+                index_value(items, position).map_err(|error| env.locate(error, &[collection]))
+            }
+            Expr::BinaryOp { op, left, right } => self.binary(*op, left, right, env),
+            Expr::UnaryOp { op, operand } => {
+                let value = self.eval(operand, env)?;
+                let result = match (op, value) {
+                    (UnaryOperator::Not, value) => Ok(value.logical_not()),
+                    (UnaryOperator::Neg, EloValue::Integer(n)) => n
+                        .checked_neg()
+                        .map(EloValue::Integer)
+                        .ok_or_else(|| EvalError::Runtime("Integer overflow".to_string())),
+                    (UnaryOperator::Neg, EloValue::Float(f)) => Ok(EloValue::Float(-f)),
+                    (UnaryOperator::Plus, value @ (EloValue::Integer(_) | EloValue::Float(_))) => {
+                        Ok(value)
+                    }
+                    (op, value) => Err(EvalError::Runtime(format!(
+                        "Cannot apply {} to {}",
+                        op,
+                        value.type_name()
+                    ))),
+                };
+                result.map_err(|error| env.locate(error, &[operand]))
+            }
+            Expr::FunctionCall { name, args } => match (name.as_str(), args.as_slice()) {
+                ("any" | "all", [collection, Expr::Lambda { param, body }]) => {
+                    let items = self.eval(collection, env)?;
+                    let base = env.path_of(collection);
+                    self.quantifier(name == "any", items, base, param, body, env)
+                }
+                (
+                    "map" | "filter" | "sum" | "min_by" | "max_by",
+                    [collection, Expr::Lambda { param, body }],
+                ) => {
+                    let items = self.eval(collection, env)?;
+                    let base = env.path_of(collection);
+                    self.collection_call(name, items, base, param, body, env)
+                        .map_err(|error| env.locate(error, &[collection]))
+                }
+                ("reduce", [collection, init, Expr::Lambda { param, body }])
+                    if matches!(**body, Expr::Lambda { .. }) =>
+                {
+                    let items = self.eval(collection, env)?;
+                    let base = env.path_of(collection);
+                    let init = self.eval(init, env)?;
+                    self.reduce(items, base, init, param, body, env)
+                        .map_err(|error| env.locate(error, &[collection]))
+                }
+                _ => {
+                    let values = args
+                        .iter()
+                        .map(|arg| self.eval(arg, env))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let operands: Vec<&Expr> = args.iter().collect();
+                    call(name, &values, env).map_err(|error| env.locate(error, &operands))
+                }
+            },
+            Expr::Let { name, value, body } => {
+                let bound = self.eval(value, env)?;
+                let path = env.path_of(value);
+                self.bind(name, bound, path, body, env)
+            }
+            Expr::Pipe { value, functions } => {
+                let desugared = Expr::desugar_pipe(value, functions).map_err(EvalError::Runtime)?;
+                self.eval(&desugared, env)
+            }
+            Expr::Guard { condition, body } => {
+                if self.eval(condition, env)?.is_truthy() {
+                    self.eval(body, env)
+                } else {
+                    Err(EvalError::Runtime(format!(
+                        "guard failed: {}",
+                        printer::format(condition)
+                    )))
+                }
+            }
+            Expr::Date(text) => temporal(TemporalValue::parse_date(text)),
+            Expr::DateTime(text) => temporal(TemporalValue::parse_datetime(text)),
+            Expr::Duration(text) => temporal(TemporalValue::parse_duration(text)),
+            Expr::TemporalKeyword(keyword) => temporal(keyword_value(*keyword, env.today, env.now)),
+            Expr::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                if self.eval(condition, env)?.is_truthy() {
+                    self.eval(then_branch, env)
+                } else {
+                    self.eval(else_branch, env)
+                }
+            }
+            Expr::Array(elements) => Ok(EloValue::Array(
+                elements
+                    .iter()
+                    .map(|e| self.eval(e, env))
+                    .collect::<Result<_, _>>()?,
+            )),
+            // Keys end up sorted whatever order the literal lists them in, so
+            // equal objects compare and serialize identically
+            Expr::Object(fields) => Ok(EloValue::Object(
+                fields
+                    .iter()
+                    .map(|(k, v)| Ok((k.clone(), self.eval(v, env)?)))
+                    .collect::<Result<BTreeMap<_, _>, EvalError>>()?,
+            )),
+            Expr::Range {
+                start,
+                end,
+                inclusive,
+            } => Ok(EloValue::Range {
+                start: Box::new(self.eval(start, env)?),
+                end: Box::new(self.eval(end, env)?),
+                inclusive: *inclusive,
+            }),
+            Expr::NullDefault { value, default } => match self.eval(value, env)? {
+                EloValue::Null => self.eval(default, env),
+                value => Ok(value),
+            },
+            // Null and failed validations fall back to the alternative
+            Expr::Alternative {
+                primary,
+                alternative,
+            } => match self.eval(primary, env)? {
+                EloValue::Null | EloValue::Boolean(false) => self.eval(alternative, env),
+                value => Ok(value),
+            },
+            Expr::Require { condition, .. } => self.eval(condition, env),
+            // A warning never makes the input invalid
+            Expr::WarnIf { .. } => Ok(EloValue::Boolean(true)),
+            other => Err(EvalError::Unsupported(construct_name(other).to_string())),
+        }
+    }
+
+    /// Evaluate `body` with `name` bound to `value`
+    fn bind(
+        &self,
+        name: &str,
+        value: EloValue,
+        path: Option<String>,
+        body: &Expr,
+        env: &mut Env<'_>,
+    ) -> Result<EloValue, EvalError> {
+        env.stack.push(Frame {
+            param: name.to_string(),
+            value,
+            path,
+        });
+        let result = self.eval(body, env);
+        env.stack.pop();
+        result
+    }
+
+    /// `any(xs, x ~> cond)` / `all(xs, x ~> cond)`, stopping at the first
+    /// element that decides the result
+    ///
+    /// `base` is the input path of the collection, if it was read from the
+    /// input.
+    fn quantifier(
+        &self,
+        any: bool,
+        items: EloValue,
+        base: Option<String>,
+        param: &str,
+        body: &Expr,
+        env: &mut Env<'_>,
+    ) -> Result<EloValue, EvalError> {
+        let items = match items {
+            EloValue::Array(items) => items,
+            other => {
+                let error = EvalError::Runtime(format!(
+                    "{}() expects an array, found {}",
+                    if any { "any" } else { "all" },
+                    other.type_name()
+                ));
+                return Err(env.locate_at(error, base));
+            }
+        };
+        for (index, value) in items.into_iter().enumerate() {
+            env.stack.push(Frame {
+                param: param.to_string(),
+                value,
+                path: base.as_ref().map(|base| format!("{}[{}]", base, index)),
+            });
+            let holds = self.eval(body, env);
+            env.stack.pop();
+            if holds?.is_truthy() == any {
+                return Ok(EloValue::Boolean(any));
+            }
+        }
+        Ok(EloValue::Boolean(!any))
+    }
+
+    /// `map`, `filter`, `sum`, `min_by` and `max_by` with a lambda, which
+    /// is evaluated once for every element
+    ///
+    /// Like the generated code, `min_by` keeps the first element with the
+    /// smallest key and `max_by` the last with the largest.
+    fn collection_call(
+        &self,
+        name: &str,
+        items: EloValue,
+        base: Option<String>,
+        param: &str,
+        body: &Expr,
+        env: &mut Env<'_>,
+    ) -> Result<EloValue, EvalError> {
+        let items = elements(name, items).map_err(EvalError::Runtime)?;
+        let mut results = Vec::with_capacity(items.len());
+        for (index, value) in items.iter().enumerate() {
+            env.stack.push(Frame {
+                param: param.to_string(),
+                value: value.clone(),
+                path: base.as_ref().map(|base| format!("{}[{}]", base, index)),
+            });
+            let result = self.eval(body, env);
+            env.stack.pop();
+            results.push(result?);
+        }
+        let pairs = items.into_iter().zip(results.iter());
+        let result = match name {
+            "map" => Ok(EloValue::Array(results.clone())),
+            "filter" => Ok(EloValue::Array(
+                pairs
+                    .filter(|(_, keep)| keep.is_truthy())
+                    .map(|(item, _)| item)
+                    .collect(),
+            )),
+            "sum" => sum(&results),
+            _ => best_by(name == "min_by", pairs),
+        };
+        result.map_err(EvalError::Runtime)
+    }
+
+    /// `reduce(items, init, fn(acc, item ~> body))`, folding from `init`
+    fn reduce(
+        &self,
+        items: EloValue,
+        base: Option<String>,
+        init: EloValue,
+        acc: &str,
+        lambda: &Expr,
+        env: &mut Env<'_>,
+    ) -> Result<EloValue, EvalError> {
+        let Expr::Lambda { param, body } = lambda else {
+            return Err(EvalError::Unsupported(construct_name(lambda).to_string()));
+        };
+        let items = elements("reduce", items).map_err(EvalError::Runtime)?;
+        let mut total = init;
+        for (index, value) in items.into_iter().enumerate() {
+            env.stack.push(Frame {
+                param: acc.to_string(),
+                value: total,
+                path: None,
+            });
+            env.stack.push(Frame {
+                param: param.clone(),
+                value,
+                path: base.as_ref().map(|base| format!("{}[{}]", base, index)),
+            });
+            let result = self.eval(body, env);
+            env.stack.truncate(env.stack.len() - 2);
+            total = result?;
+        }
+        Ok(total)
+    }
+
+    fn binary(
+        &self,
+        op: BinaryOperator,
+        left: &Expr,
+        right: &Expr,
+        env: &mut Env<'_>,
+    ) -> Result<EloValue, EvalError> {
+        // Logical operators short-circuit
+        match op {
+            BinaryOperator::And => {
+                let holds = self.eval(left, env)?.is_truthy() && self.eval(right, env)?.is_truthy();
+                return Ok(EloValue::Boolean(holds));
+            }
+            BinaryOperator::Or => {
+                let holds = self.eval(left, env)?.is_truthy() || self.eval(right, env)?.is_truthy();
+                return Ok(EloValue::Boolean(holds));
+            }
+            _ => {}
+        }
+
+        let left_value = self.eval(left, env)?;
+        let right_value = self.eval(right, env)?;
+        let (left_value, right_value) = (&left_value, &right_value);
+        let result = match op {
+            BinaryOperator::Add => left_value.add(right_value),
+            BinaryOperator::Sub => left_value.subtract(right_value),
+            BinaryOperator::Mul => left_value.multiply(right_value),
+            BinaryOperator::Div => left_value.divide(right_value),
+            BinaryOperator::Mod => left_value.modulo(right_value),
+            BinaryOperator::Pow => left_value.power(right_value),
+            BinaryOperator::Eq => Ok(EloValue::Boolean(left_value.equals(right_value))),
+            BinaryOperator::Neq => Ok(EloValue::Boolean(!left_value.equals(right_value))),
+            BinaryOperator::Lt => left_value.less_than(right_value).map(EloValue::Boolean),
+            BinaryOperator::Gt => right_value.less_than(left_value).map(EloValue::Boolean),
+            BinaryOperator::Lte => right_value
+                .less_than(left_value)
+                .map(|gt| EloValue::Boolean(!gt)),
+            BinaryOperator::Gte => left_value
+                .less_than(right_value)
+                .map(|lt| EloValue::Boolean(!lt)),
+            BinaryOperator::In => contains(right_value, left_value).map(EloValue::Boolean),
+            BinaryOperator::Like => match (left_value, right_value) {
+                (EloValue::String(text), EloValue::String(pattern)) => {
+                    Ok(EloValue::Boolean(glob_match(text, pattern)))
+                }
+                _ => Err(format!(
+                    "Cannot match {} against {}",
+                    left_value.type_name(),
+                    right_value.type_name()
+                )),
+            },
+            BinaryOperator::And | BinaryOperator::Or => unreachable!("handled above"),
+        };
+        result.map_err(|message| env.locate(EvalError::Runtime(message), &[left, right]))
+    }
+}
+
+/// Element `position` of `items`; positions past either end (and indexing
+/// null) read as `null`, like missing fields
+fn index_value(items: EloValue, position: EloValue) -> Result<EloValue, EvalError> {
+    match (items, position) {
+        (EloValue::Array(items), EloValue::Integer(n)) => Ok(usize::try_from(n)
+            .ok()
+            .and_then(|n| items.into_iter().nth(n))
+            .unwrap_or(EloValue::Null)),
+        (EloValue::Null, EloValue::Integer(_)) => Ok(EloValue::Null),
+        (items, position) => Err(EvalError::Runtime(format!(
+            "Cannot index {} with {}",
+            items.type_name(),
+            position.type_name()
+        ))),
+    }
+}
+
+/// Check if `collection` (array, range or string) contains `value`
+fn contains(collection: &EloValue, value: &EloValue) -> Result<bool, String> {
+    match (collection, value) {
+        (EloValue::Array(items), value) => Ok(items.iter().any(|item| item.equals(value))),
+        (EloValue::Range { .. }, value) => collection.range_contains(value),
+        (EloValue::String(text), EloValue::String(part)) => Ok(text.contains(part.as_str())),
+        _ => Err(format!(
+            "Cannot check if {} contains {}",
+            collection.type_name(),
+            value.type_name()
+        )),
+    }
+}
+
+/// Elements of the array or range `items` passed to `function`
+fn elements(function: &str, items: EloValue) -> Result<Vec<EloValue>, String> {
+    match items {
+        EloValue::Array(items) => Ok(items),
+        range @ EloValue::Range { .. } => range
+            .range_values()
+            .ok_or_else(|| format!("{}() expects integer range bounds", function)),
+        other => Err(format!(
+            "{}() expects an array, found {}",
+            function,
+            other.type_name()
+        )),
+    }
+}
+
+/// Sum of numbers, `0` for none
+fn sum(values: &[EloValue]) -> Result<EloValue, String> {
+    values
+        .iter()
+        .try_fold(EloValue::Integer(0), |total, value| match (&total, value) {
+            (EloValue::Integer(a), EloValue::Integer(b)) => a
+                .checked_add(*b)
+                .map(EloValue::Integer)
+                .ok_or_else(|| "Integer overflow".to_string()),
+            (_, value) if value.is_numeric() => total.add(value),
+            (_, value) => Err(format!(
+                "sum() expects numbers, found {}",
+                value.type_name()
+            )),
+        })
+}
+
+/// The item with the smallest (first one) or largest (last one) key, or
+/// `null` for no items
+fn best_by<'k>(
+    smallest: bool,
+    pairs: impl Iterator<Item = (EloValue, &'k EloValue)>,
+) -> Result<EloValue, String> {
+    let mut best: Option<(EloValue, &EloValue)> = None;
+    for (item, key) in pairs {
+        let replace = match &best {
+            None => true,
+            Some((_, best_key)) if smallest => key.less_than(best_key)?,
+            Some((_, best_key)) => !key.less_than(best_key)?,
+        };
+        if replace {
+            best = Some((item, key));
+        }
+    }
+    Ok(best.map_or(EloValue::Null, |(item, _)| item))
+}
+
+/// Call a standard library function on evaluated arguments
+fn call(name: &str, args: &[EloValue], env: &Env<'_>) -> Result<EloValue, EvalError> {
+    let runtime = |message: String| EvalError::Runtime(message);
+    let mismatch = || {
+        let types: Vec<&str> = args.iter().map(EloValue::type_name).collect();
+        EvalError::Runtime(format!("{}() does not accept ({})", name, types.join(", ")))
+    };
+    match (name, args) {
+        ("length", [EloValue::String(s)]) => Ok(EloValue::Integer(s.chars().count() as i64)),
+        ("length", [EloValue::Array(items)]) => Ok(EloValue::Integer(items.len() as i64)),
+        ("lowercase", [EloValue::String(s)]) => Ok(EloValue::String(s.to_lowercase())),
+        ("uppercase", [EloValue::String(s)]) => Ok(EloValue::String(s.to_uppercase())),
+        ("trim", [EloValue::String(s)]) => Ok(EloValue::String(s.trim().to_string())),
+        ("contains", [collection, value]) => contains(collection, value)
+            .map(EloValue::Boolean)
+            .map_err(runtime),
+        ("starts_with", [EloValue::String(s), EloValue::String(prefix)]) => {
+            Ok(EloValue::Boolean(s.starts_with(prefix.as_str())))
+        }
+        ("ends_with", [EloValue::String(s), EloValue::String(suffix)]) => {
+            Ok(EloValue::Boolean(s.ends_with(suffix.as_str())))
+        }
+        ("matches", [EloValue::String(s), EloValue::String(pattern)]) => {
+            crate::security::validate_regex_pattern(pattern).map_err(runtime)?;
+            let regex = regex::Regex::new(pattern).map_err(|e| runtime(e.to_string()))?;
+            Ok(EloValue::Boolean(regex.is_match(s)))
+        }
+        ("sum", [items]) => elements(name, items.clone())
+            .and_then(|items| sum(&items))
+            .map_err(runtime),
+        ("between", [value, low, high]) => EloValue::Range {
+            start: Box::new(low.clone()),
+            end: Box::new(high.clone()),
+            inclusive: true,
+        }
+        .range_contains(value)
+        .map(EloValue::Boolean)
+        .map_err(runtime),
+        ("abs", [EloValue::Integer(n)]) => n
+            .checked_abs()
+            .map(EloValue::Integer)
+            .ok_or_else(|| runtime("Integer overflow".to_string())),
+        ("abs", [EloValue::Float(f)]) => Ok(EloValue::Float(f.abs())),
+        ("is_null", [value]) => Ok(EloValue::Boolean(*value == EloValue::Null)),
+        ("today", []) => Ok(EloValue::Temporal(TemporalValue::Date(env.today))),
+        ("now", []) => Ok(EloValue::Temporal(TemporalValue::DateTime(env.now))),
+        ("age", [birth]) => {
+            let birth = date_of(birth).ok_or_else(mismatch)?;
+            let mut years = env.today.year() - birth.year();
+            if (env.today.month(), env.today.day()) < (birth.month(), birth.day()) {
+                years -= 1;
+            }
+            Ok(EloValue::Integer(years.into()))
+        }
+        ("days_since", [date]) => {
+            let date = date_of(date).ok_or_else(mismatch)?;
+            Ok(EloValue::Integer((env.today - date).num_days()))
+        }
+        ("duration_days", [EloValue::Temporal(TemporalValue::Duration(duration))]) => {
+            Ok(EloValue::Integer(duration.num_days()))
+        }
+        ("is_some", [value]) => Ok(EloValue::Boolean(*value != EloValue::Null)),
+        ("is_empty", [EloValue::String(s)]) => Ok(EloValue::Boolean(s.is_empty())),
+        ("is_empty", [EloValue::Array(items)]) => Ok(EloValue::Boolean(items.is_empty())),
+        ("is_string", [value]) => Ok(EloValue::Boolean(matches!(value, EloValue::String(_)))),
+        ("is_number", [value]) => Ok(EloValue::Boolean(value.is_numeric())),
+        (
+            "clamp",
+            [value, EloValue::Range {
+                start,
+                end,
+                inclusive,
+            }],
+        ) => {
+            let high = match (end.as_ref(), inclusive) {
+                (high, true) => high.clone(),
+                (EloValue::Integer(n), false) => EloValue::Integer(n - 1),
+                _ => {
+                    return Err(runtime(
+                        "clamp() needs an inclusive range of floats".to_string(),
+                    ))
+                }
+            };
+            clamp(value, start, &high).map_err(runtime)
+        }
+        ("clamp", [value, low, high]) => clamp(value, low, high).map_err(runtime),
+        // Format and code validators share the helpers generated code calls
+        ("is_email", [EloValue::String(s)]) => Ok(EloValue::Boolean(format::is_email(s))),
+        ("is_url", [EloValue::String(s)]) => Ok(EloValue::Boolean(format::is_url(s))),
+        ("is_uuid", [EloValue::String(s)]) => Ok(EloValue::Boolean(format::is_uuid(s))),
+        ("is_ipv4", [EloValue::String(s)]) => Ok(EloValue::Boolean(format::is_ipv4(s))),
+        ("is_ipv6", [EloValue::String(s)]) => Ok(EloValue::Boolean(format::is_ipv6(s))),
+        ("is_phone", [EloValue::String(s)]) => Ok(EloValue::Boolean(phone::is_phone(s))),
+        ("normalize_phone", [EloValue::String(s), EloValue::String(region)]) => {
+            Ok(phone::normalize_phone(s, region).map_or(EloValue::Null, EloValue::String))
+        }
+        ("is_iso_country", [EloValue::String(s)]) => {
+            Ok(EloValue::Boolean(codes::is_iso_country(s)))
+        }
+        ("is_iso_currency", [EloValue::String(s)]) => {
+            Ok(EloValue::Boolean(codes::is_iso_currency(s)))
+        }
+        ("is_bcp47_lang", [EloValue::String(s)]) => Ok(EloValue::Boolean(codes::is_bcp47_lang(s))),
+        ("password_strength", [EloValue::String(s)]) => {
+            Ok(EloValue::Integer(password::password_strength(s)))
+        }
+        ("is_card_number", [EloValue::String(s)]) => Ok(EloValue::Boolean(card::is_card_number(s))),
+        ("card_brand", [EloValue::String(s)]) => Ok(EloValue::String(card::card_brand(s))),
+        ("has_extension", [EloValue::String(s), EloValue::Array(items)]) => {
+            let extensions = strings(items).ok_or_else(mismatch)?;
+            Ok(EloValue::Boolean(file::has_extension(s, &extensions)))
+        }
+        ("is_mime", [EloValue::String(s), EloValue::Array(items)]) => {
+            let patterns = strings(items).ok_or_else(mismatch)?;
+            Ok(EloValue::Boolean(file::is_mime(s, &patterns)))
+        }
+        ("size_kb" | "size_mb" | "size_gb", [bytes]) => {
+            let bytes = match bytes {
+                EloValue::Integer(n) => *n as f64,
+                EloValue::Float(f) => *f,
+                _ => return Err(mismatch()),
+            };
+            Ok(EloValue::Float(match name {
+                "size_kb" => file::size_kb(bytes),
+                "size_mb" => file::size_mb(bytes),
+                _ => file::size_gb(bytes),
+            }))
+        }
+        ("is_present", [value]) => Ok(EloValue::Boolean(presence::is_present(value))),
+        ("required_if", [field, condition]) => Ok(EloValue::Boolean(
+            !condition.is_truthy() || presence::is_present(field),
+        )),
+        ("required_unless", [field, condition]) => Ok(EloValue::Boolean(
+            condition.is_truthy() || presence::is_present(field),
+        )),
+        ("requires_with", [field, others @ ..]) if !others.is_empty() => Ok(EloValue::Boolean(
+            !others.iter().any(presence::is_present) || presence::is_present(field),
+        )),
+        (
+            "length" | "lowercase" | "uppercase" | "trim" | "contains" | "starts_with"
+            | "ends_with" | "matches" | "sum" | "between" | "abs" | "is_null" | "today" | "now"
+            | "age" | "days_since" | "duration_days" | "is_empty" | "clamp" | "is_email" | "is_url"
+            | "is_uuid" | "is_ipv4" | "is_ipv6" | "is_phone" | "normalize_phone" | "is_iso_country"
+            | "is_iso_currency" | "is_bcp47_lang" | "password_strength" | "is_card_number"
+            | "card_brand" | "has_extension" | "is_mime" | "size_kb" | "size_mb" | "size_gb"
+            | "required_if" | "required_unless" | "requires_with",
+            _,
+        ) => Err(mismatch()),
+        _ => Err(EvalError::Unsupported(format!("function '{}'", name))),
+    }
+}
+
+/// `value` limited to `low..=high`
+fn clamp(value: &EloValue, low: &EloValue, high: &EloValue) -> Result<EloValue, String> {
+    if high.less_than(low)? {
+        return Err("clamp() lower bound exceeds upper bound".to_string());
+    }
+    Ok(if value.less_than(low)? {
+        low.clone()
+    } else if high.less_than(value)? {
+        high.clone()
+    } else {
+        value.clone()
+    })
+}
+
+/// The strings of an array, or `None` if it holds anything else
+fn strings(items: &[EloValue]) -> Option<Vec<&str>> {
+    items
+        .iter()
+        .map(|item| match item {
+            EloValue::String(s) => Some(s.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Calendar date of a date, a datetime or an ISO8601 string
+fn date_of(value: &EloValue) -> Option<NaiveDate> {
+    let value = match value {
+        EloValue::Temporal(t) => t.clone(),
+        EloValue::String(s) => TemporalValue::parse(s).ok()?,
+        _ => return None,
+    };
+    match value {
+        TemporalValue::Date(date) => Some(date),
+        TemporalValue::DateTime(datetime) => Some(datetime.date_naive()),
+        TemporalValue::Duration(_) => None,
+    }
+}
+
+fn temporal(value: Result<TemporalValue, String>) -> Result<EloValue, EvalError> {
+    value.map(EloValue::Temporal).map_err(EvalError::Runtime)
+}
+
+/// Value of a temporal keyword on `today` at `now`
+///
+/// Period boundaries are dates, except the bounds of the current day,
+/// which are datetimes; weeks start on Monday.
+fn keyword_value(
+    keyword: TemporalKeyword,
+    today: NaiveDate,
+    now: DateTime<Utc>,
+) -> Result<TemporalValue, String> {
+    let date = |year: i32, month: u32, day: u32| {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .map(TemporalValue::Date)
+            .ok_or_else(|| format!("Invalid date {}-{}-{}", year, month, day))
+    };
+    let at = |hour: u32, minute: u32, second: u32| {
+        today
+            .and_hms_opt(hour, minute, second)
+            .map(|naive| TemporalValue::DateTime(Utc.from_utc_datetime(&naive)))
+            .ok_or_else(|| "Invalid date time combination".to_string())
+    };
+    // Day before the first of the month after `month`
+    let end_of_month = |month: u32| {
+        let (year, next) = if month == 12 {
+            (today.year() + 1, 1)
+        } else {
+            (today.year(), month + 1)
+        };
+        NaiveDate::from_ymd_opt(year, next, 1)
+            .map(|first| TemporalValue::Date(first - Duration::days(1)))
+            .ok_or_else(|| "Invalid date".to_string())
+    };
+    let weekday = i64::from(today.weekday().num_days_from_monday());
+    let quarter_start = (today.month() - 1) / 3 * 3 + 1;
+    match keyword {
+        TemporalKeyword::Now => Ok(TemporalValue::DateTime(now)),
+        TemporalKeyword::Today => Ok(TemporalValue::Date(today)),
+        TemporalKeyword::Tomorrow => Ok(TemporalValue::Date(today + Duration::days(1))),
+        TemporalKeyword::Yesterday => Ok(TemporalValue::Date(today - Duration::days(1))),
+        TemporalKeyword::StartOfDay => at(0, 0, 0),
+        TemporalKeyword::EndOfDay => at(23, 59, 59),
+        TemporalKeyword::StartOfWeek => Ok(TemporalValue::Date(today - Duration::days(weekday))),
+        TemporalKeyword::EndOfWeek => Ok(TemporalValue::Date(today + Duration::days(6 - weekday))),
+        TemporalKeyword::StartOfMonth => date(today.year(), today.month(), 1),
+        TemporalKeyword::EndOfMonth => end_of_month(today.month()),
+        TemporalKeyword::StartOfQuarter => date(today.year(), quarter_start, 1),
+        TemporalKeyword::EndOfQuarter => end_of_month(quarter_start + 2),
+        TemporalKeyword::StartOfYear => date(today.year(), 1, 1),
+        TemporalKeyword::EndOfYear => date(today.year(), 12, 31),
+        TemporalKeyword::BeginningOfTime => date(1970, 1, 1),
+        TemporalKeyword::EndOfTime => date(9999, 12, 31),
+    }
+}
+
+/// Name of an unsupported construct for error messages
+fn construct_name(expr: &Expr) -> &'static str {
+    match expr {
+        Expr::Placeholder(_) => "an unbound placeholder",
+        Expr::Lambda { .. } => "a lambda",
+        Expr::Let { .. } => "let",
+        Expr::Pipe { .. } => "the pipe operator",
+        Expr::Guard { .. } => "guard",
+        Expr::Quantity { .. } => "a unit quantity",
+        Expr::Date(_) | Expr::DateTime(_) | Expr::Duration(_) | Expr::TemporalKeyword(_) => {
+            "a temporal value"
+        }
+        _ => "this expression",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+
+    fn input() -> EloValue {
+        EloValue::Object(BTreeMap::from([
+            ("age".to_string(), EloValue::Integer(17)),
+            ("name".to_string(), EloValue::String("Ada".to_string())),
+            (
+                "address".to_string(),
+                EloValue::Object(BTreeMap::from([(
+                    "country".to_string(),
+                    EloValue::String("FR".to_string()),
+                )])),
+            ),
+        ]))
+    }
+
+    fn eval(source: &str) -> Result<EloValue, EvalError> {
+        Evaluator::new().evaluate(&Parser::parse(source).unwrap(), &input())
+    }
+
+    #[test]
+    fn test_evaluate_fields_and_operators() {
+        assert_eq!(eval("age >= 18"), Ok(EloValue::Boolean(false)));
+        assert_eq!(eval("age + 1 == 18"), Ok(EloValue::Boolean(true)));
+        assert_eq!(
+            eval("address.country in ['FR', 'DE']"),
+            Ok(EloValue::Boolean(true))
+        );
+        assert_eq!(eval("age in 10..=17"), Ok(EloValue::Boolean(true)));
+        assert_eq!(eval("name like 'A*'"), Ok(EloValue::Boolean(true)));
+        assert_eq!(eval("missing ?? 3"), Ok(EloValue::Integer(3)));
+        assert_eq!(
+            eval("if age < 18 then 'minor' else 'adult'"),
+            Ok(EloValue::String("minor".to_string()))
+        );
+        // `&&` does not evaluate its right side when the left is false
+        assert_eq!(
+            eval("age > 100 && 1 / 0 == 1"),
+            Ok(EloValue::Boolean(false))
+        );
+    }
+
+    #[test]
+    fn test_evaluate_functions() {
+        assert_eq!(eval("length(name) == 3"), Ok(EloValue::Boolean(true)));
+        assert_eq!(
+            eval("uppercase(trim(name))"),
+            Ok(EloValue::String("ADA".to_string()))
+        );
+        assert_eq!(eval("matches(name, '^[A-Z]')"), Ok(EloValue::Boolean(true)));
+        assert_eq!(eval("between(age, 17, 65)"), Ok(EloValue::Boolean(true)));
+        assert_eq!(eval("between(age, 18, 65)"), Ok(EloValue::Boolean(false)));
+        assert_eq!(
+            eval("length(age)").unwrap_err().to_string(),
+            "age: length() does not accept (integer)"
+        );
+    }
+
+    #[test]
+    fn test_evaluate_validation_functions() {
+        let holds = |source: &str| eval(source) == Ok(EloValue::Boolean(true));
+        assert!(holds("is_email('ada@example.com') && !is_email('ada@')"));
+        assert!(holds(
+            "is_url('https://example.com/a') && !is_url('example')"
+        ));
+        assert!(holds("is_uuid('123e4567-e89b-12d3-a456-426614174000')"));
+        assert!(holds("is_ipv4('10.0.0.1') && !is_ipv4('10.0.0.256')"));
+        assert!(holds("is_ipv6('::1') && !is_ipv6('1.2.3.4')"));
+        assert!(holds("is_phone('+33 6 12 34 56 78') && !is_phone('12')"));
+        assert_eq!(
+            eval("normalize_phone('06 12 34 56 78', 'FR')"),
+            Ok(EloValue::String("+33612345678".to_string()))
+        );
+        assert!(holds(
+            "is_iso_country(address.country) && !is_iso_country('XX')"
+        ));
+        assert!(holds("is_iso_currency('EUR') && is_bcp47_lang('fr-FR')"));
+        assert_eq!(eval("password_strength('')"), Ok(EloValue::Integer(0)));
+        assert!(holds(
+            "password_strength('correct horse battery staple 42!') >= 3"
+        ));
+        assert!(holds(
+            "is_card_number('4111 1111 1111 1111') && !is_card_number('4111 1111 1111 1112')"
+        ));
+        assert_eq!(
+            eval("card_brand('4111111111111111')"),
+            Ok(EloValue::String("visa".to_string()))
+        );
+        assert!(holds("has_extension('photo.JPG', ['png', 'jpg'])"));
+        assert!(holds("is_mime('image/png', ['text/*', 'image/*'])"));
+        assert!(holds("!is_mime('application/pdf', ['image/*'])"));
+        assert_eq!(eval("size_kb(2048)"), Ok(EloValue::Float(2.0)));
+        assert_eq!(eval("size_mb(1048576)"), Ok(EloValue::Float(1.0)));
+        assert_eq!(eval("size_gb(1073741824)"), Ok(EloValue::Float(1.0)));
+        assert!(holds("is_present(name) && !is_present(missing)"));
+        assert!(holds(
+            "required_if(name, age < 18) && !required_if(missing, age < 18)"
+        ));
+        assert!(holds("required_if(missing, age >= 18)"));
+        assert!(holds("required_unless(missing, age < 18)"));
+        assert!(holds("!required_unless(missing, age >= 18)"));
+        assert!(holds(
+            "requires_with(missing, nothing) && !requires_with(missing, name)"
+        ));
+        assert_eq!(
+            eval("has_extension(name, [1])").unwrap_err().to_string(),
+            "name: has_extension() does not accept (string, array)"
+        );
+    }
+
+    #[test]
+    fn test_evaluate_clamp() {
+        assert_eq!(eval("clamp(age, 1..=10)"), Ok(EloValue::Integer(10)));
+        assert_eq!(eval("clamp(age, 1..10)"), Ok(EloValue::Integer(9)));
+        assert_eq!(eval("clamp(age, 20, 30)"), Ok(EloValue::Integer(20)));
+        assert_eq!(eval("clamp(2.5, 1.0..=2.0)"), Ok(EloValue::Float(2.0)));
+        assert_eq!(
+            eval("clamp(age, 1..=100) == age"),
+            Ok(EloValue::Boolean(true))
+        );
+        assert_eq!(
+            eval("clamp(2.5, 1.0..2.0)").unwrap_err().to_string(),
+            "clamp() needs an inclusive range of floats"
+        );
+        assert_eq!(
+            eval("clamp(age, 10, 1)").unwrap_err().to_string(),
+            "age: clamp() lower bound exceeds upper bound"
+        );
+    }
+
+    #[test]
+    fn test_evaluate_collection_functions() {
+        let scores = |source: &str| {
+            let input = EloValue::Object(BTreeMap::from([(
+                "scores".to_string(),
+                EloValue::Array(vec![
+                    EloValue::Integer(3),
+                    EloValue::Integer(8),
+                    EloValue::Integer(5),
+                    EloValue::Integer(8),
+                ]),
+            )]));
+            Evaluator::new().evaluate(&Parser::parse(source).unwrap(), &input)
+        };
+        let integers = |values: &[i64]| {
+            EloValue::Array(values.iter().copied().map(EloValue::Integer).collect())
+        };
+        assert_eq!(
+            scores("map(scores, fn(s ~> s * 2))"),
+            Ok(integers(&[6, 16, 10, 16]))
+        );
+        assert_eq!(
+            scores("filter(scores, fn(s ~> s > 4))"),
+            Ok(integers(&[8, 5, 8]))
+        );
+        assert_eq!(scores("sum(scores)"), Ok(EloValue::Integer(24)));
+        assert_eq!(scores("sum(1..=4)"), Ok(EloValue::Integer(10)));
+        assert_eq!(
+            scores("sum(scores, fn(s ~> s / 2.0))"),
+            Ok(EloValue::Float(12.0))
+        );
+        assert_eq!(
+            scores("reduce(scores, 100, fn(acc, s ~> acc - s))"),
+            Ok(EloValue::Integer(76))
+        );
+        assert_eq!(
+            scores("max_by(scores, fn(s ~> -s))"),
+            Ok(EloValue::Integer(3))
+        );
+        assert_eq!(
+            scores("min_by(filter(scores, fn(s ~> s > 10)), fn(s ~> s))"),
+            Ok(EloValue::Null)
+        );
+        assert_eq!(
+            scores("sum(map(scores, fn(s ~> 'x')))")
+                .unwrap_err()
+                .to_string(),
+            "sum() expects numbers, found string"
+        );
+    }
+
+    #[test]
+    fn test_evaluate_alternative() {
+        let alternative = |primary: &str, alt: &str| {
+            let expr = Expr::Alternative {
+                primary: Box::new(Parser::parse(primary).unwrap()),
+                alternative: Box::new(Parser::parse(alt).unwrap()),
+            };
+            Evaluator::new().evaluate(&expr, &input())
+        };
+        assert_eq!(
+            alternative("nickname", "name"),
+            Ok(EloValue::String("Ada".to_string()))
+        );
+        assert_eq!(alternative("age", "0"), Ok(EloValue::Integer(17)));
+        assert_eq!(
+            alternative("age >= 18", "address.country == 'FR'"),
+            Ok(EloValue::Boolean(true))
+        );
+        assert_eq!(
+            alternative("age >= 18", "name == 'Bob'"),
+            Ok(EloValue::Boolean(false))
+        );
+    }
+
+    #[test]
+    fn test_evaluate_errors() {
+        assert_eq!(
+            eval("age / 0").unwrap_err().root(),
+            &EvalError::Runtime("Division by zero".to_string())
+        );
+        assert_eq!(
+            eval("1 / 0"),
+            Err(EvalError::Runtime("Division by zero".to_string()))
+        );
+        assert_eq!(
+            eval("fn(x ~> x)").unwrap_err().to_string(),
+            "a lambda is not supported by the evaluator"
+        );
+        assert_eq!(
+            eval("guard age >= 18 in name").unwrap_err().to_string(),
+            "guard failed: age >= 18"
+        );
+        assert_eq!(
+            eval("soundex(name)"),
+            Err(EvalError::Unsupported("function 'soundex'".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_evaluate_let_pipes_and_guards() {
+        assert_eq!(
+            eval("age |> fn(v ~> v * 2) == 34"),
+            Ok(EloValue::Boolean(true))
+        );
+        assert_eq!(
+            eval("([1, 2] |> sum() > 2) && (name |> length() == 3)"),
+            Ok(EloValue::Boolean(true))
+        );
+        assert_eq!(eval("let age = 40 in age + 2"), Ok(EloValue::Integer(42)));
+        assert_eq!(
+            eval("let limit = 18 in let ok = age >= limit in ok || name == 'Ada'"),
+            Ok(EloValue::Boolean(true))
+        );
+        assert_eq!(
+            eval("(let age = 40 in age) + age"),
+            Ok(EloValue::Integer(57))
+        );
+        assert_eq!(
+            eval("name |> uppercase() |> contains('AD')"),
+            Ok(EloValue::Boolean(true))
+        );
+        assert_eq!(
+            eval("name |> lowercase"),
+            Ok(EloValue::String("ada".to_string()))
+        );
+        assert_eq!(eval("age |> fn(a ~> a * 2)"), Ok(EloValue::Integer(34)));
+        assert_eq!(
+            eval("name |> (lowercase |> fn(n ~> n + '!'))"),
+            Ok(EloValue::String("ada!".to_string()))
+        );
+        assert_eq!(
+            eval("[1, 20] |> any(fn(n ~> n > age))"),
+            Ok(EloValue::Boolean(true))
+        );
+        assert_eq!(eval("guard age > 0 in age - 1"), Ok(EloValue::Integer(16)));
+
+        // Errors on let-bound input values keep their input path
+        let err = eval("let who = name in who * -1").unwrap_err();
+        assert_eq!(err.path(), Some("name"));
+    }
+
+    #[test]
+    fn test_evaluate_temporal() {
+        let now = Utc.with_ymd_and_hms(2024, 5, 15, 10, 30, 0).unwrap();
+        let input = EloValue::Object(BTreeMap::from([
+            (
+                "birth".to_string(),
+                EloValue::String("2000-06-01".to_string()),
+            ),
+            (
+                "created".to_string(),
+                EloValue::String("2024-05-01T08:00:00Z".to_string()),
+            ),
+        ]));
+        let eval = |source: &str| {
+            Evaluator::new()
+                .with_now(now)
+                .evaluate(&Parser::parse(source).unwrap(), &input)
+                .map(|value| value.to_string())
+        };
+
+        assert_eq!(eval("TODAY"), Ok("2024-05-15".to_string()));
+        assert_eq!(eval("NOW"), Ok("2024-05-15T10:30:00+00:00".to_string()));
+        assert_eq!(eval("SOW"), Ok("2024-05-13".to_string()));
+        assert_eq!(eval("EOW"), Ok("2024-05-19".to_string()));
+        assert_eq!(eval("EOM"), Ok("2024-05-31".to_string()));
+        assert_eq!(eval("SOQ"), Ok("2024-04-01".to_string()));
+        assert_eq!(eval("EOQ"), Ok("2024-06-30".to_string()));
+        assert_eq!(eval("SOD"), Ok("2024-05-15T00:00:00+00:00".to_string()));
+        assert_eq!(eval("@date(2024-01-15) < TODAY"), Ok("true".to_string()));
+        assert_eq!(
+            eval("created >= TODAY - @duration(P30D)"),
+            Ok("true".to_string())
+        );
+        assert_eq!(
+            eval("created + @duration(PT2H) == @datetime(2024-05-01T10:00:00Z)"),
+            Ok("true".to_string())
+        );
+        assert_eq!(eval("age(birth)"), Ok("23".to_string()));
+        assert_eq!(eval("days_since(created)"), Ok("14".to_string()));
+        assert!(eval("birth + 1").is_err());
+    }
+
+    #[test]
+    fn test_error_paths() {
+        let item =
+            |price: EloValue| EloValue::Object(BTreeMap::from([("price".to_string(), price)]));
+        let order = EloValue::Object(BTreeMap::from([(
+            "items".to_string(),
+            EloValue::Array(vec![
+                item(EloValue::Integer(10)),
+                item(EloValue::Integer(20)),
+                item(EloValue::String("n/a".to_string())),
+            ]),
+        )]));
+        let evaluate =
+            |source: &str| Evaluator::new().evaluate(&Parser::parse(source).unwrap(), &order);
+
+        assert_eq!(
+            evaluate("all item in items satisfies item.price < 15"),
+            Ok(EloValue::Boolean(false))
+        );
+        let error = evaluate("all item in items satisfies item.price + 1 > 5").unwrap_err();
+        assert_eq!(error.path(), Some("items[2].price"));
+        assert_eq!(
+            error.to_string(),
+            "items[2].price: Cannot add string and integer (while checking item = items[2])"
+        );
+        // Elements after the deciding one are not visited
+        assert_eq!(
+            evaluate("any item in items satisfies item.price - 15 > 0"),
+            Ok(EloValue::Boolean(true))
+        );
+        assert_eq!(
+            evaluate("any item in items satisfies 1 / (item.price - 20) > 0")
+                .unwrap_err()
+                .path(),
+            Some("items[1]")
+        );
+        // Indexes with a literal position report the element's path
+        let error = evaluate("items[2].price - 1").unwrap_err();
+        assert_eq!(error.path(), Some("items[2].price"));
+    }
+
+    #[test]
+    fn test_evaluate_index() {
+        let matrix = EloValue::Object(BTreeMap::from([(
+            "matrix".to_string(),
+            EloValue::Array(vec![
+                EloValue::Array(vec![EloValue::Integer(1), EloValue::Integer(2)]),
+                EloValue::Array(vec![EloValue::Integer(3), EloValue::Integer(4)]),
+            ]),
+        )]));
+        let evaluate =
+            |source: &str| Evaluator::new().evaluate(&Parser::parse(source).unwrap(), &matrix);
+        assert_eq!(evaluate("matrix[1][0]"), Ok(EloValue::Integer(3)));
+        assert_eq!(evaluate("matrix[0][1 + 0]"), Ok(EloValue::Integer(2)));
+        assert_eq!(evaluate("matrix[5][0]"), Ok(EloValue::Null));
+        assert_eq!(
+            evaluate("matrix[-1] ?? 'none'"),
+            Ok(EloValue::String("none".to_string()))
+        );
+        assert_eq!(
+            evaluate("matrix['a']").unwrap_err().to_string(),
+            "matrix: Cannot index array with string"
+        );
+    }
+
+    #[test]
+    fn test_object_literal_keys_are_sorted() {
+        let value = eval("{zip: '75001', city: 'Paris', age: age}").unwrap();
+        assert_eq!(
+            value,
+            eval("{age: 17, city: 'Paris', zip: '75001'}").unwrap()
+        );
+        assert_eq!(
+            value.to_string_value(),
+            "{age: 17, city: Paris, zip: 75001}"
+        );
+    }
+
+    #[test]
+    fn test_check_reports_deepest_failure() {
+        let check = |source: &str| {
+            Evaluator::new()
+                .check(&Parser::parse(source).unwrap(), &input())
+                .unwrap()
+        };
+
+        let result = check("length(name) > 0 && address.country == 'FR'");
+        assert!(result.passed);
+        assert_eq!(result.failed_subexpr, None);
+        assert!(result.message.is_empty());
+
+        let result = check("length(name) > 0 && (age >= 18 && address.country == 'FR')");
+        assert!(!result.passed);
+        assert_eq!(
+            result.failed_subexpr,
+            Some(Parser::parse("age >= 18").unwrap())
+        );
+        assert_eq!(result.message, "condition not met: age >= 18");
+
+        let result = check("if name == 'Ada' then require age >= 18 else 'adults only' else true");
+        assert_eq!(result.message, "adults only");
+        assert_eq!(
+            result.failed_subexpr,
+            Some(Parser::parse("age >= 18").unwrap())
+        );
+        assert!(Evaluator::new()
+            .check(&Parser::parse("age / 0 > 1").unwrap(), &input())
+            .is_err());
+    }
+}
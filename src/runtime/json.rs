@@ -0,0 +1,154 @@
+//! Conversions between [`EloValue`] and `serde_json`
+//!
+//! Available with the `serde-support` feature. JSON numbers that fit in an
+//! `i64` become integers and all others floats; objects keep their keys
+//! sorted. Going back, values JSON has no type for are written as strings:
+//! temporal values in ISO8601 form and ranges as `start..end`. Non-finite
+//! floats become `null`.
+//!
+//! # Example
+//!
+//! ```
+//! use elo_rust::parser::Parser;
+//! use elo_rust::runtime::interpreter::Evaluator;
+//! use elo_rust::runtime::EloValue;
+//!
+//! let input = EloValue::from_json_str(r#"{"age": 21, "tags": ["vip"]}"#).unwrap();
+//! let expr = Parser::parse("age >= 18 && length(tags) > 0").unwrap();
+//! assert_eq!(Evaluator::new().evaluate(&expr, &input), Ok(EloValue::Boolean(true)));
+//! assert_eq!(input.to_json_string(), r#"{"age":21,"tags":["vip"]}"#);
+//! ```
+
+use super::EloValue;
+use serde_json::{Map, Number, Value};
+
+impl EloValue {
+    /// Parse a JSON document
+    pub fn from_json_str(text: &str) -> Result<EloValue, String> {
+        serde_json::from_str::<Value>(text)
+            .map(EloValue::from)
+            .map_err(|e| format!("invalid JSON: {}", e))
+    }
+
+    /// Serialize as compact JSON
+    pub fn to_json_string(&self) -> String {
+        Value::from(self).to_string()
+    }
+}
+
+impl From<Value> for EloValue {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Null => EloValue::Null,
+            Value::Bool(b) => EloValue::Boolean(b),
+            Value::Number(n) => match n.as_i64() {
+                Some(i) => EloValue::Integer(i),
+                None => n.as_f64().map_or(EloValue::Null, EloValue::Float),
+            },
+            Value::String(s) => EloValue::String(s),
+            Value::Array(items) => EloValue::Array(items.into_iter().map(EloValue::from).collect()),
+            Value::Object(fields) => EloValue::Object(
+                fields
+                    .into_iter()
+                    .map(|(key, value)| (key, EloValue::from(value)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl From<&EloValue> for Value {
+    fn from(value: &EloValue) -> Self {
+        match value {
+            EloValue::Null => Value::Null,
+            EloValue::Boolean(b) => Value::Bool(*b),
+            EloValue::Integer(n) => Value::Number((*n).into()),
+            EloValue::Float(f) => Number::from_f64(*f).map_or(Value::Null, Value::Number),
+            EloValue::String(s) => Value::String(s.clone()),
+            EloValue::Array(items) => Value::Array(items.iter().map(Value::from).collect()),
+            EloValue::Object(fields) => Value::Object(
+                fields
+                    .iter()
+                    .map(|(key, value)| (key.clone(), Value::from(value)))
+                    .collect::<Map<String, Value>>(),
+            ),
+            EloValue::Range { .. } | EloValue::Temporal(_) => {
+                Value::String(value.to_string_value())
+            }
+        }
+    }
+}
+
+impl From<EloValue> for Value {
+    fn from(value: EloValue) -> Self {
+        Value::from(&value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::TemporalValue;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_from_json() {
+        let value = EloValue::from_json_str(
+            r#"{"b": [1, 2.5, 18446744073709551615], "a": {"ok": true, "none": null}, "s": "x"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            value,
+            EloValue::Object(BTreeMap::from([
+                (
+                    "a".to_string(),
+                    EloValue::Object(BTreeMap::from([
+                        ("none".to_string(), EloValue::Null),
+                        ("ok".to_string(), EloValue::Boolean(true)),
+                    ]))
+                ),
+                (
+                    "b".to_string(),
+                    EloValue::Array(vec![
+                        EloValue::Integer(1),
+                        EloValue::Float(2.5),
+                        EloValue::Float(18446744073709551615.0),
+                    ])
+                ),
+                ("s".to_string(), EloValue::String("x".to_string())),
+            ]))
+        );
+        assert!(EloValue::from_json_str("{\"a\": }")
+            .unwrap_err()
+            .starts_with("invalid JSON: "));
+    }
+
+    #[test]
+    fn test_to_json() {
+        let value = EloValue::Array(vec![
+            EloValue::Integer(-3),
+            EloValue::Float(0.5),
+            EloValue::Float(f64::NAN),
+            EloValue::String("q\"".to_string()),
+            EloValue::Range {
+                start: Box::new(EloValue::Integer(1)),
+                end: Box::new(EloValue::Integer(5)),
+                inclusive: true,
+            },
+            EloValue::Temporal(TemporalValue::parse_date("2024-01-15").unwrap()),
+        ]);
+        assert_eq!(
+            value.to_json_string(),
+            r#"[-3,0.5,null,"q\"","1..=5","2024-01-15"]"#
+        );
+
+        let round_trip = r#"{"a":[true,null],"b":{"c":1.25}}"#;
+        assert_eq!(
+            EloValue::from_json_str(round_trip)
+                .unwrap()
+                .to_json_string(),
+            round_trip
+        );
+        assert_eq!(Value::from(EloValue::Integer(7)), serde_json::json!(7));
+    }
+}
@@ -2,16 +2,63 @@
 //!
 //! Provides error types, utilities, and dynamic value representation for generated validators
 
+pub mod batch;
+pub mod bench;
+pub mod card;
+pub mod clock;
+pub mod codes;
+pub mod consumer;
+pub mod file;
+pub mod flags;
+pub mod format;
+pub mod glob;
+pub mod incremental;
+pub mod inspect;
+pub mod interpreter;
+#[cfg(feature = "serde-support")]
+pub mod json;
+pub mod password;
+pub mod pattern;
+pub mod phone;
+pub mod presence;
+pub mod report;
+pub mod sampling;
+pub mod shadow;
+pub mod sink;
+pub mod stream;
 pub mod temporal;
 pub mod value;
 
 pub use temporal::TemporalValue;
 pub use value::EloValue;
 
+use std::collections::BTreeMap;
 use std::fmt;
 
+/// Severity of a validation diagnostic
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde-support",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Severity {
+    /// The value is invalid (emitted by failed rules and `require`)
+    #[default]
+    Error,
+    /// The value is accepted but suspicious (emitted by `warn_if`)
+    Warning,
+}
+
 /// A single validation error
+///
+/// With the `serde-support` feature it (de)serializes as the objects of
+/// [`ValidationErrors::to_json`].
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde-support",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct ValidationError {
     /// The path to the field being validated (e.g., "user.email")
     pub path: String,
@@ -19,8 +66,10 @@ pub struct ValidationError {
     pub message: String,
     /// The rule that failed
     pub rule: String,
-    /// Optional value for debugging
+    /// Optional value for debugging (card numbers are masked, see [`card::redact`])
     pub value: Option<String>,
+    /// Whether this is a hard error or only a warning
+    pub severity: Severity,
 }
 
 impl ValidationError {
@@ -35,12 +84,32 @@ impl ValidationError {
             message: message.into(),
             rule: rule.into(),
             value: None,
+            severity: Severity::Error,
         }
     }
 
+    /// Create a warning that does not cause validation to fail
+    pub fn warning(
+        path: impl Into<String>,
+        message: impl Into<String>,
+        rule: impl Into<String>,
+    ) -> Self {
+        Self {
+            severity: Severity::Warning,
+            ..Self::new(path, message, rule)
+        }
+    }
+
+    /// Check if this diagnostic is only a warning
+    pub fn is_warning(&self) -> bool {
+        self.severity == Severity::Warning
+    }
+
     /// Add a value for debugging
+    ///
+    /// Card numbers in the value are masked so full PANs never reach logs.
     pub fn with_value(mut self, value: impl Into<String>) -> Self {
-        self.value = Some(value.into());
+        self.value = Some(card::redact(&value.into()));
         self
     }
 }
@@ -54,7 +123,14 @@ impl fmt::Display for ValidationError {
 impl std::error::Error for ValidationError {}
 
 /// Multiple validation errors
+///
+/// With the `serde-support` feature it (de)serializes as a plain array.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde-support",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
 pub struct ValidationErrors {
     /// Collection of validation errors
     pub errors: Vec<ValidationError>,
@@ -80,6 +156,93 @@ impl ValidationErrors {
     pub fn len(&self) -> usize {
         self.errors.len()
     }
+
+    /// Check if any entry is a hard error (warnings alone do not count)
+    pub fn has_errors(&self) -> bool {
+        self.errors.iter().any(|e| !e.is_warning())
+    }
+
+    /// Iterate over the entries that are only warnings
+    pub fn warnings(&self) -> impl Iterator<Item = &ValidationError> {
+        self.errors.iter().filter(|e| e.is_warning())
+    }
+
+    /// Append the entries of `other`, for combining the results of several
+    /// validators
+    pub fn merge(&mut self, other: ValidationErrors) {
+        self.errors.extend(other.errors);
+    }
+
+    /// Prepend `prefix` to the path of every entry, for reporting the errors
+    /// of a nested struct's validator under the field holding it
+    ///
+    /// An entry with an empty path gets the prefix without its trailing `.`.
+    ///
+    /// ```
+    /// use elo_rust::runtime::{ValidationError, ValidationErrors};
+    ///
+    /// let mut address = ValidationErrors::new();
+    /// address.push(ValidationError::new("zip", "Invalid zip code", "zip"));
+    ///
+    /// let mut errors = ValidationErrors::new();
+    /// errors.push(ValidationError::new("email", "Invalid email", "email"));
+    /// errors.merge(address.with_prefix("customer.address."));
+    /// assert_eq!(errors.to_string(), "email: Invalid email\ncustomer.address.zip: Invalid zip code");
+    /// ```
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        for error in &mut self.errors {
+            error.path = if error.path.is_empty() {
+                prefix.trim_end_matches('.').to_string()
+            } else {
+                format!("{}{}", prefix, error.path)
+            };
+        }
+        self
+    }
+
+    /// Iterate over the entries for the field at `path` and the fields
+    /// nested in it: `customer` matches `customer`, `customer.email` and
+    /// `customer[0]`, but not `customer_id`
+    pub fn errors_for_path<'a>(
+        &'a self,
+        path: &'a str,
+    ) -> impl Iterator<Item = &'a ValidationError> + 'a {
+        self.errors.iter().filter(move |e| {
+            e.path
+                .strip_prefix(path)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(['.', '[']))
+        })
+    }
+
+    /// Group the messages by path, in the order they were reported, as
+    /// expected by form libraries showing errors next to each field
+    pub fn into_map(self) -> BTreeMap<String, Vec<String>> {
+        let mut map: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for error in self.errors {
+            map.entry(error.path).or_default().push(error.message);
+        }
+        map
+    }
+
+    /// Serialize as a JSON array of `{path, message, rule, severity, value}`
+    /// objects, for forwarding over queues and logs without the serde feature
+    pub fn to_json(&self) -> String {
+        let entries: Vec<String> = self
+            .errors
+            .iter()
+            .map(|e| {
+                format!(
+                    r#"{{"path":{},"message":{},"rule":{},"severity":"{}","value":{}}}"#,
+                    json_string(&e.path),
+                    json_string(&e.message),
+                    json_string(&e.rule),
+                    if e.is_warning() { "warning" } else { "error" },
+                    e.value.as_deref().map_or("null".to_string(), json_string)
+                )
+            })
+            .collect();
+        format!("[{}]", entries.join(","))
+    }
 }
 
 impl Default for ValidationErrors {
@@ -88,6 +251,24 @@ impl Default for ValidationErrors {
     }
 }
 
+impl IntoIterator for ValidationErrors {
+    type Item = ValidationError;
+    type IntoIter = std::vec::IntoIter<ValidationError>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.errors.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a ValidationErrors {
+    type Item = &'a ValidationError;
+    type IntoIter = std::slice::Iter<'a, ValidationError>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.errors.iter()
+    }
+}
+
 impl fmt::Display for ValidationErrors {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for (i, err) in self.errors.iter().enumerate() {
@@ -102,6 +283,80 @@ impl fmt::Display for ValidationErrors {
 
 impl std::error::Error for ValidationErrors {}
 
+/// A type that can check itself against its validation rules
+///
+/// Implemented by the code generated from a [schema](crate::schema).
+pub trait Validate {
+    /// Check every rule, returning the errors of all that fail
+    fn validate(&self) -> Result<(), ValidationErrors>;
+
+    /// Validate every item, returning the errors of each failing item
+    /// together with its index; see [`validate_all`]
+    fn validate_all(items: &[Self]) -> Result<(), Vec<(usize, ValidationErrors)>>
+    where
+        Self: Sized,
+    {
+        validate_all(items, Self::validate)
+    }
+}
+
+/// Check every item with `validate`, collecting the failures by index
+///
+/// All items are checked, so the result lists every failing item in order,
+/// each with the index it has in `items`.
+///
+/// ```
+/// use elo_rust::runtime::{validate_all, ValidationError, ValidationErrors};
+///
+/// fn positive(n: &i64) -> Result<(), ValidationErrors> {
+///     if *n > 0 {
+///         return Ok(());
+///     }
+///     let mut errors = ValidationErrors::new();
+///     errors.push(ValidationError::new("n", "must be positive", "positive"));
+///     Err(errors)
+/// }
+///
+/// assert!(validate_all(&[1, 2], positive).is_ok());
+/// let failures = validate_all(&[1, -2, 0], positive).unwrap_err();
+/// let indices: Vec<usize> = failures.iter().map(|(i, _)| *i).collect();
+/// assert_eq!(indices, vec![1, 2]);
+/// ```
+pub fn validate_all<T>(
+    items: &[T],
+    validate: impl Fn(&T) -> Result<(), ValidationErrors>,
+) -> Result<(), Vec<(usize, ValidationErrors)>> {
+    let failures: Vec<(usize, ValidationErrors)> = items
+        .iter()
+        .enumerate()
+        .filter_map(|(i, item)| validate(item).err().map(|errors| (i, errors)))
+        .collect();
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(failures)
+    }
+}
+
+/// Quote and escape `s` as a JSON string
+pub(crate) fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            ch if (ch as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => out.push(ch),
+        }
+    }
+    out.push('"');
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -122,12 +377,70 @@ mod tests {
         assert_eq!(err.value, Some("invalid@".to_string()));
     }
 
+    #[test]
+    fn test_validation_error_masks_card_numbers() {
+        let err = ValidationError::new("payment.card", "Card declined", "card_check")
+            .with_value("4111 1111 1111 1111");
+        assert_eq!(err.value, Some("**** **** **** 1111".to_string()));
+    }
+
     #[test]
     fn test_validation_error_display() {
         let err = ValidationError::new("email", "Invalid email", "email_pattern");
         assert_eq!(err.to_string(), "email: Invalid email");
     }
 
+    #[test]
+    fn test_validation_warning() {
+        let warn = ValidationError::warning("score", "Low score", "score_check");
+        assert!(warn.is_warning());
+        assert_eq!(warn.severity, Severity::Warning);
+        assert!(!ValidationError::new("a", "b", "c").is_warning());
+    }
+
+    #[test]
+    fn test_validation_errors_warnings_do_not_fail() {
+        let mut errors = ValidationErrors::new();
+        errors.push(ValidationError::warning("score", "Low score", "r"));
+        assert!(!errors.has_errors());
+        assert_eq!(errors.warnings().count(), 1);
+
+        errors.push(ValidationError::new("age", "Too young", "r"));
+        assert!(errors.has_errors());
+        assert_eq!(errors.warnings().count(), 1);
+    }
+
+    #[test]
+    fn test_validation_errors_to_json() {
+        let mut errors = ValidationErrors::new();
+        assert_eq!(errors.to_json(), "[]");
+        errors.push(
+            ValidationError::new("name", "must not contain \"quotes\"\n", "r1").with_value("a\\b"),
+        );
+        errors.push(ValidationError::warning("score", "Low score", "r2"));
+        assert_eq!(
+            errors.to_json(),
+            r#"[{"path":"name","message":"must not contain \"quotes\"\n","rule":"r1","severity":"error","value":"a\\b"},{"path":"score","message":"Low score","rule":"r2","severity":"warning","value":null}]"#
+        );
+    }
+
+    #[cfg(feature = "serde-support")]
+    #[test]
+    fn test_validation_errors_serde_matches_to_json() {
+        let mut errors = ValidationErrors::new();
+        errors.push(ValidationError::new("age", "Too young", "adult").with_value("16"));
+        errors.push(ValidationError::warning("score", "Low score", "r2"));
+        let serialized = serde_json::to_value(&errors).unwrap();
+        assert_eq!(
+            serialized,
+            serde_json::from_str::<serde_json::Value>(&errors.to_json()).unwrap()
+        );
+        assert_eq!(
+            serde_json::from_value::<ValidationErrors>(serialized).unwrap(),
+            errors
+        );
+    }
+
     #[test]
     fn test_validation_errors_collection() {
         let mut errors = ValidationErrors::new();
@@ -141,4 +454,73 @@ mod tests {
         errors.push(ValidationError::new("age", "Too young", "rule2"));
         assert_eq!(errors.len(), 2);
     }
+
+    #[test]
+    fn test_validation_errors_composition() {
+        let mut customer = ValidationErrors::new();
+        customer.push(ValidationError::new("email", "Invalid email", "email"));
+        customer.push(ValidationError::new("", "Incomplete customer", "complete"));
+
+        let mut errors = ValidationErrors::new();
+        errors.push(ValidationError::new("customer_id", "Missing", "id"));
+        errors.merge(customer.with_prefix("customer."));
+        errors.push(ValidationError::warning(
+            "customer.email",
+            "Free mail",
+            "domain",
+        ));
+        errors.push(ValidationError::new("customer[1].name", "Empty", "name"));
+
+        let paths: Vec<&str> = errors.errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "customer_id",
+                "customer.email",
+                "customer",
+                "customer.email",
+                "customer[1].name"
+            ]
+        );
+        assert_eq!(errors.errors_for_path("customer.email").count(), 2);
+        assert_eq!(errors.errors_for_path("customer").count(), 4);
+        assert_eq!(errors.errors_for_path("customer_id").count(), 1);
+        assert_eq!(errors.errors_for_path("name").count(), 0);
+
+        let map = errors.clone().into_map();
+        assert_eq!(map["customer.email"], ["Invalid email", "Free mail"]);
+        assert_eq!(map.len(), 4);
+
+        let rules: Vec<&str> = (&errors).into_iter().map(|e| e.rule.as_str()).collect();
+        assert_eq!(rules, ["id", "email", "complete", "domain", "name"]);
+        let owned: Vec<ValidationError> = errors.into_iter().collect();
+        assert_eq!(owned.len(), 5);
+    }
+
+    struct Age(i64);
+
+    impl Validate for Age {
+        fn validate(&self) -> Result<(), ValidationErrors> {
+            if self.0 >= 18 {
+                return Ok(());
+            }
+            let mut errors = ValidationErrors::new();
+            errors.push(
+                ValidationError::new("age", "Too young", "adult").with_value(self.0.to_string()),
+            );
+            Err(errors)
+        }
+    }
+
+    #[test]
+    fn test_validate_all_collects_failures_by_index() {
+        assert_eq!(Age::validate_all(&[]), Ok(()));
+        assert_eq!(Age::validate_all(&[Age(20), Age(30)]), Ok(()));
+
+        let failures = Age::validate_all(&[Age(12), Age(40), Age(3)]).unwrap_err();
+        assert_eq!(failures.len(), 2);
+        assert_eq!(failures[0].0, 0);
+        assert_eq!(failures[1].0, 2);
+        assert_eq!(failures[1].1.errors[0].value.as_deref(), Some("3"));
+    }
 }
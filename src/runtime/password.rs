@@ -0,0 +1,266 @@
+//! Password strength scoring
+//!
+//! Backs the `password_strength` stdlib function, which rates a password from
+//! 0 (rejected) to 4 (very strong) so rules can be written as
+//! `password_strength(password) >= 3`. The rating is driven by a
+//! [`PasswordPolicy`]: passwords that are too short, lack required character
+//! classes or appear on the banned list score 0; otherwise the score follows
+//! an entropy estimate that discounts repeats and keyboard-style sequences.
+
+use std::collections::HashSet;
+use std::sync::{OnceLock, PoisonError, RwLock};
+
+/// Highest score `password_strength` can return
+pub const MAX_SCORE: i64 = 4;
+
+/// Commonly used passwords rejected by the default policy
+const COMMON_PASSWORDS: &[&str] = &[
+    "123456",
+    "123456789",
+    "12345678",
+    "password",
+    "password1",
+    "qwerty",
+    "qwerty123",
+    "abc123",
+    "111111",
+    "1234567",
+    "iloveyou",
+    "admin",
+    "welcome",
+    "letmein",
+    "monkey",
+    "dragon",
+    "football",
+    "baseball",
+    "sunshine",
+    "princess",
+    "trustno1",
+    "passw0rd",
+    "changeme",
+];
+
+/// Entropy (in bits) needed for scores 2, 3 and 4
+const SCORE_THRESHOLDS: [f64; 3] = [36.0, 60.0, 80.0];
+
+/// Character classes counted towards `min_classes` and the entropy pool
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Lower,
+    Upper,
+    Digit,
+    Symbol,
+    Other,
+}
+
+impl CharClass {
+    fn of(ch: char) -> Self {
+        match ch {
+            'a'..='z' => Self::Lower,
+            'A'..='Z' => Self::Upper,
+            '0'..='9' => Self::Digit,
+            c if c.is_ascii() => Self::Symbol,
+            _ => Self::Other,
+        }
+    }
+
+    /// Approximate number of characters an attacker has to try per position
+    fn pool_size(self) -> f64 {
+        match self {
+            Self::Lower | Self::Upper => 26.0,
+            Self::Digit => 10.0,
+            Self::Symbol => 33.0,
+            Self::Other => 100.0,
+        }
+    }
+}
+
+/// Rules used to score passwords
+#[derive(Debug, Clone, PartialEq)]
+pub struct PasswordPolicy {
+    /// Minimum length in characters
+    pub min_length: usize,
+    /// Minimum number of distinct character classes (lower, upper, digit, symbol, other)
+    pub min_classes: usize,
+    /// Passwords rejected outright (compared case-insensitively)
+    pub banned: HashSet<String>,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 8,
+            min_classes: 1,
+            banned: COMMON_PASSWORDS.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+}
+
+impl PasswordPolicy {
+    /// Create the default policy (8 characters, common passwords banned)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the minimum length
+    pub fn with_min_length(mut self, min_length: usize) -> Self {
+        self.min_length = min_length;
+        self
+    }
+
+    /// Set the minimum number of character classes
+    pub fn with_min_classes(mut self, min_classes: usize) -> Self {
+        self.min_classes = min_classes;
+        self
+    }
+
+    /// Ban additional passwords (e.g. the product or company name)
+    pub fn with_banned<I, S>(mut self, words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.banned
+            .extend(words.into_iter().map(|w| w.as_ref().to_lowercase()));
+        self
+    }
+
+    /// Drop the built-in list of common passwords
+    pub fn without_common_passwords(mut self) -> Self {
+        for word in COMMON_PASSWORDS {
+            self.banned.remove(*word);
+        }
+        self
+    }
+
+    /// Rate a password from 0 (rejected) to [`MAX_SCORE`]
+    pub fn score(&self, password: &str) -> i64 {
+        if password.chars().count() < self.min_length
+            || self.banned.contains(&password.to_lowercase())
+        {
+            return 0;
+        }
+
+        let mut classes: Vec<CharClass> = Vec::new();
+        for class in password.chars().map(CharClass::of) {
+            if !classes.contains(&class) {
+                classes.push(class);
+            }
+        }
+        if classes.len() < self.min_classes {
+            return 0;
+        }
+
+        let bits = entropy_bits(password);
+        1 + SCORE_THRESHOLDS.iter().filter(|t| bits >= **t).count() as i64
+    }
+}
+
+/// Estimate the entropy of a password in bits
+///
+/// Uses `effective_length * log2(pool)`, where characters that repeat the
+/// previous one or continue a run (`abc`, `321`) do not count towards the
+/// effective length.
+pub fn entropy_bits(password: &str) -> f64 {
+    let chars: Vec<char> = password.chars().collect();
+    let mut classes: Vec<CharClass> = Vec::new();
+    let mut effective_length = 0usize;
+
+    for (i, &ch) in chars.iter().enumerate() {
+        let class = CharClass::of(ch);
+        if !classes.contains(&class) {
+            classes.push(class);
+        }
+
+        let predictable = i > 0 && {
+            let step = ch as i64 - chars[i - 1] as i64;
+            step == 0
+                || (step.abs() == 1 && i > 1 && chars[i - 1] as i64 - chars[i - 2] as i64 == step)
+        };
+        if !predictable {
+            effective_length += 1;
+        }
+    }
+
+    let pool: f64 = classes.iter().map(|c| c.pool_size()).sum();
+    if pool == 0.0 {
+        return 0.0;
+    }
+    effective_length as f64 * pool.log2()
+}
+
+/// Process-wide policy used by generated validators
+pub fn global() -> &'static RwLock<PasswordPolicy> {
+    static POLICY: OnceLock<RwLock<PasswordPolicy>> = OnceLock::new();
+    POLICY.get_or_init(|| RwLock::new(PasswordPolicy::new()))
+}
+
+/// Replace the process-wide policy
+///
+/// # Example
+///
+/// ```
+/// use elo_rust::runtime::password::{password_strength, set_policy, PasswordPolicy};
+///
+/// set_policy(PasswordPolicy::new().with_min_length(12).with_banned(["acme2024!"]));
+/// assert_eq!(password_strength("short-Pw1"), 0);
+/// ```
+pub fn set_policy(policy: PasswordPolicy) {
+    *global().write().unwrap_or_else(PoisonError::into_inner) = policy;
+}
+
+/// Rate a password from 0 to [`MAX_SCORE`] using the process-wide policy
+pub fn password_strength(password: &str) -> i64 {
+    global()
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .score(password)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rejects_short_and_banned() {
+        let policy = PasswordPolicy::new();
+        assert_eq!(policy.score(""), 0);
+        assert_eq!(policy.score("Ab1!"), 0);
+        assert_eq!(policy.score("Password1"), 0);
+        assert_eq!(policy.score("qwerty123"), 0);
+    }
+
+    #[test]
+    fn test_scores_increase_with_entropy() {
+        let policy = PasswordPolicy::new();
+        let weak = policy.score("abcdefgh");
+        let medium = policy.score("tulip-river");
+        let strong = policy.score("c0rrect-H0rse-battery-Staple");
+        assert_eq!(weak, 1);
+        assert!(medium > weak);
+        assert_eq!(strong, MAX_SCORE);
+    }
+
+    #[test]
+    fn test_entropy_discounts_sequences_and_repeats() {
+        assert!(entropy_bits("aaaaaaaa") < entropy_bits("ahqzkwpm"));
+        assert!(entropy_bits("12345678") < entropy_bits("73920581"));
+        assert_eq!(entropy_bits(""), 0.0);
+    }
+
+    #[test]
+    fn test_min_classes() {
+        let policy = PasswordPolicy::new().with_min_classes(3);
+        assert_eq!(policy.score("onlylowercaseletters"), 0);
+        assert!(policy.score("Mixed-case-and-7") > 0);
+    }
+
+    #[test]
+    fn test_custom_banned_words() {
+        let policy = PasswordPolicy::new()
+            .without_common_passwords()
+            .with_banned(["AcmeCorp2024"]);
+        assert_eq!(policy.score("acmecorp2024"), 0);
+        assert!(policy.score("password1") > 0);
+    }
+}
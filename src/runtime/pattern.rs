@@ -0,0 +1,40 @@
+//! Regular expression matching for the `matches` function
+//!
+//! Generated validators match literal patterns against a [`Regex`] compiled
+//! once per call site and kept in a static; the pattern was checked by
+//! [`validate_regex_pattern`] when the code was generated. Patterns only
+//! known at runtime go through [`is_match`], which checks them the same way
+//! on every call.
+
+pub use regex::Regex;
+
+use crate::security::validate_regex_pattern;
+
+/// Whether `text` matches `pattern`
+///
+/// Patterns rejected by [`validate_regex_pattern`] (invalid syntax, too
+/// long, or prone to catastrophic backtracking) match nothing.
+///
+/// ```
+/// use elo_rust::runtime::pattern::is_match;
+///
+/// assert!(is_match("ab123", "^[a-z]+[0-9]+$"));
+/// assert!(!is_match("ab123", "^[0-9]+$"));
+/// assert!(!is_match("aaaa", "(a+)+$"));
+/// ```
+pub fn is_match(text: &str, pattern: &str) -> bool {
+    validate_regex_pattern(pattern).is_ok()
+        && Regex::new(pattern).is_ok_and(|regex| regex.is_match(text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_match_rejects_unsafe_patterns() {
+        assert!(is_match("2024-01-15", r"^\d{4}-\d{2}-\d{2}$"));
+        assert!(!is_match("anything", "("));
+        assert!(!is_match("aaaa", "(a*)*"));
+    }
+}
@@ -0,0 +1,260 @@
+//! Lightweight E.164 phone number validation and normalisation
+//!
+//! Backs the `is_phone` and `normalize_phone` stdlib functions. Numbers may
+//! contain common formatting characters (spaces, dashes, dots, parentheses)
+//! and use either `+` or `00` as the international prefix. Validation checks
+//! the E.164 structure: a known country calling code followed by a national
+//! number, at most 15 digits in total.
+
+/// Minimum number of digits (country code included) of a valid number
+const MIN_DIGITS: usize = 7;
+
+/// Maximum number of digits (country code included) allowed by E.164
+const MAX_DIGITS: usize = 15;
+
+/// Assigned country calling codes (ITU-T E.164), including non-geographic codes
+///
+/// Calling codes are prefix-free, so at most one entry matches a number.
+const CALLING_CODES: &[&str] = &[
+    "1", "7", "20", "27", "30", "31", "32", "33", "34", "36", "39", "40", "41", "43", "44", "45",
+    "46", "47", "48", "49", "51", "52", "53", "54", "55", "56", "57", "58", "60", "61", "62", "63",
+    "64", "65", "66", "81", "82", "84", "86", "90", "91", "92", "93", "94", "95", "98", "211",
+    "212", "213", "216", "218", "220", "221", "222", "223", "224", "225", "226", "227", "228",
+    "229", "230", "231", "232", "233", "234", "235", "236", "237", "238", "239", "240", "241",
+    "242", "243", "244", "245", "246", "247", "248", "249", "250", "251", "252", "253", "254",
+    "255", "256", "257", "258", "260", "261", "262", "263", "264", "265", "266", "267", "268",
+    "269", "290", "291", "297", "298", "299", "350", "351", "352", "353", "354", "355", "356",
+    "357", "358", "359", "370", "371", "372", "373", "374", "375", "376", "377", "378", "379",
+    "380", "381", "382", "383", "385", "386", "387", "389", "420", "421", "423", "500", "501",
+    "502", "503", "504", "505", "506", "507", "508", "509", "590", "591", "592", "593", "594",
+    "595", "596", "597", "598", "599", "670", "672", "673", "674", "675", "676", "677", "678",
+    "679", "680", "681", "682", "683", "685", "686", "687", "688", "689", "690", "691", "692",
+    "800", "808", "850", "852", "853", "855", "856", "870", "878", "880", "881", "882", "883",
+    "886", "888", "960", "961", "962", "963", "964", "965", "966", "967", "968", "970", "971",
+    "972", "973", "974", "975", "976", "977", "979", "992", "993", "994", "995", "996", "998",
+];
+
+/// Region (ISO 3166-1 alpha-2) -> (calling code, national trunk prefix)
+const REGIONS: &[(&str, &str, Option<&str>)] = &[
+    ("AE", "971", Some("0")),
+    ("AR", "54", Some("0")),
+    ("AT", "43", Some("0")),
+    ("AU", "61", Some("0")),
+    ("BE", "32", Some("0")),
+    ("BR", "55", Some("0")),
+    ("CA", "1", Some("1")),
+    ("CH", "41", Some("0")),
+    ("CN", "86", Some("0")),
+    ("CZ", "420", None),
+    ("DE", "49", Some("0")),
+    ("DK", "45", None),
+    ("EG", "20", Some("0")),
+    ("ES", "34", None),
+    ("FI", "358", Some("0")),
+    ("FR", "33", Some("0")),
+    ("GB", "44", Some("0")),
+    ("GR", "30", None),
+    ("HK", "852", None),
+    ("HU", "36", Some("06")),
+    ("IE", "353", Some("0")),
+    ("IL", "972", Some("0")),
+    ("IN", "91", Some("0")),
+    ("IT", "39", None),
+    ("JP", "81", Some("0")),
+    ("KE", "254", Some("0")),
+    ("KR", "82", Some("0")),
+    ("MX", "52", None),
+    ("NG", "234", Some("0")),
+    ("NL", "31", Some("0")),
+    ("NO", "47", None),
+    ("NZ", "64", Some("0")),
+    ("PL", "48", None),
+    ("PT", "351", None),
+    ("RO", "40", Some("0")),
+    ("RU", "7", Some("8")),
+    ("SA", "966", Some("0")),
+    ("SE", "46", Some("0")),
+    ("SG", "65", None),
+    ("TR", "90", Some("0")),
+    ("UA", "380", Some("0")),
+    ("US", "1", Some("1")),
+    ("ZA", "27", Some("0")),
+];
+
+/// Look up the calling code and trunk prefix of a region
+fn region_info(region: &str) -> Option<(&'static str, Option<&'static str>)> {
+    REGIONS
+        .iter()
+        .find(|(code, _, _)| code.eq_ignore_ascii_case(region))
+        .map(|(_, calling, trunk)| (*calling, *trunk))
+}
+
+/// Find the country calling code a digit string starts with
+pub fn calling_code(digits: &str) -> Option<&'static str> {
+    (1..=3)
+        .filter_map(|len| digits.get(..len))
+        .find_map(|prefix| CALLING_CODES.iter().copied().find(|c| *c == prefix))
+}
+
+/// Split a raw number into (is_international, digits)
+///
+/// Returns `None` if the number contains anything other than digits and
+/// formatting characters, or a `+` anywhere but the start.
+fn strip_formatting(raw: &str) -> Option<(bool, String)> {
+    let trimmed = raw.trim();
+    let (international, rest) = match trimmed.strip_prefix('+') {
+        Some(rest) => (true, rest),
+        None => (false, trimmed),
+    };
+
+    let mut digits = String::with_capacity(rest.len());
+    for ch in rest.chars() {
+        match ch {
+            '0'..='9' => digits.push(ch),
+            ' ' | '-' | '.' | '(' | ')' => {}
+            _ => return None,
+        }
+    }
+
+    // `00` is the international call prefix used outside North America
+    if !international {
+        if let Some(rest) = digits.strip_prefix("00") {
+            return Some((true, rest.to_string()));
+        }
+    }
+    Some((international, digits))
+}
+
+/// Check that a digit string (without `+`) is a structurally valid E.164 number
+fn is_e164_digits(digits: &str) -> bool {
+    (MIN_DIGITS..=MAX_DIGITS).contains(&digits.len())
+        && digits.bytes().all(|b| b.is_ascii_digit())
+        && calling_code(digits).is_some()
+}
+
+/// Check if a string is a valid international phone number
+///
+/// The number must carry an international prefix (`+` or `00`); use
+/// [`normalize_phone`] to accept national numbers for a given region.
+///
+/// # Example
+///
+/// ```
+/// use elo_rust::runtime::phone::is_phone;
+///
+/// assert!(is_phone("+1 (415) 555-2671"));
+/// assert!(is_phone("0044 20 7946 0958"));
+/// assert!(!is_phone("555-2671"));
+/// ```
+pub fn is_phone(raw: &str) -> bool {
+    match strip_formatting(raw) {
+        Some((true, digits)) => is_e164_digits(&digits),
+        _ => false,
+    }
+}
+
+/// Normalise a phone number to E.164 form (`+<digits>`)
+///
+/// International numbers are accepted as-is; national numbers are interpreted
+/// in `default_region` (ISO 3166-1 alpha-2, e.g. "GB"), dropping the region's
+/// trunk prefix. Returns `None` if the number is invalid or the region unknown.
+///
+/// # Example
+///
+/// ```
+/// use elo_rust::runtime::phone::normalize_phone;
+///
+/// assert_eq!(normalize_phone("020 7946 0958", "GB"), Some("+442079460958".to_string()));
+/// assert_eq!(normalize_phone("+49 30 901820", "US"), Some("+4930901820".to_string()));
+/// ```
+pub fn normalize_phone(raw: &str, default_region: &str) -> Option<String> {
+    let (international, digits) = strip_formatting(raw)?;
+
+    let full = if international {
+        digits
+    } else {
+        let (code, trunk) = region_info(default_region)?;
+        let national = match trunk {
+            Some(prefix) => digits.strip_prefix(prefix).unwrap_or(&digits),
+            None => digits.as_str(),
+        };
+        if national.is_empty() {
+            return None;
+        }
+        format!("{}{}", code, national)
+    };
+
+    if is_e164_digits(&full) {
+        Some(format!("+{}", full))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calling_code_lookup() {
+        assert_eq!(calling_code("14155552671"), Some("1"));
+        assert_eq!(calling_code("442079460958"), Some("44"));
+        assert_eq!(calling_code("35312345678"), Some("353"));
+        assert_eq!(calling_code("2591234567"), None);
+    }
+
+    #[test]
+    fn test_calling_codes_are_prefix_free() {
+        for a in CALLING_CODES {
+            for b in CALLING_CODES {
+                if a != b {
+                    assert!(!b.starts_with(a), "{} is a prefix of {}", a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_is_phone() {
+        assert!(is_phone("+14155552671"));
+        assert!(is_phone("+1 (415) 555-2671"));
+        assert!(is_phone("+44 20 7946 0958"));
+        assert!(is_phone("0049.30.901820"));
+        assert!(!is_phone("4155552671"));
+        assert!(!is_phone("+1 415 CALL NOW"));
+        assert!(!is_phone("+123"));
+        assert!(!is_phone("+1234567890123456"));
+        assert!(!is_phone("+2591234567"));
+        assert!(!is_phone("+44 20+7946"));
+        assert!(!is_phone(""));
+    }
+
+    #[test]
+    fn test_normalize_national_numbers() {
+        assert_eq!(
+            normalize_phone("(415) 555-2671", "US"),
+            Some("+14155552671".to_string())
+        );
+        assert_eq!(
+            normalize_phone("1 415 555 2671", "us"),
+            Some("+14155552671".to_string())
+        );
+        assert_eq!(
+            normalize_phone("06 12 34 56 78", "FR"),
+            Some("+33612345678".to_string())
+        );
+        // Italian numbers keep their leading zero
+        assert_eq!(
+            normalize_phone("06 1234 5678", "IT"),
+            Some("+390612345678".to_string())
+        );
+    }
+
+    #[test]
+    fn test_normalize_rejects_invalid() {
+        assert_eq!(normalize_phone("0123", "GB"), None);
+        assert_eq!(normalize_phone("020 7946 0958", "XX"), None);
+        assert_eq!(normalize_phone("not a number", "GB"), None);
+        assert_eq!(normalize_phone("0", "GB"), None);
+    }
+}
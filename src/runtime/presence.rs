@@ -0,0 +1,118 @@
+//! Presence checks for conditional requiredness rules
+//!
+//! `required_if`, `required_unless` and `requires_with` expand into calls to
+//! [`is_present`], which treats `None`, empty strings (including
+//! whitespace-only ones) and empty collections as missing.
+
+use super::EloValue;
+
+/// Values that can be checked for presence
+pub trait Presence {
+    /// Check if the value counts as provided
+    fn is_present(&self) -> bool;
+}
+
+impl<T: Presence + ?Sized> Presence for &T {
+    fn is_present(&self) -> bool {
+        (**self).is_present()
+    }
+}
+
+impl<T: Presence> Presence for Option<T> {
+    fn is_present(&self) -> bool {
+        self.as_ref().is_some_and(Presence::is_present)
+    }
+}
+
+impl Presence for str {
+    fn is_present(&self) -> bool {
+        !self.trim().is_empty()
+    }
+}
+
+impl Presence for String {
+    fn is_present(&self) -> bool {
+        self.as_str().is_present()
+    }
+}
+
+impl<T> Presence for [T] {
+    fn is_present(&self) -> bool {
+        !self.is_empty()
+    }
+}
+
+impl<T> Presence for Vec<T> {
+    fn is_present(&self) -> bool {
+        !self.is_empty()
+    }
+}
+
+impl Presence for EloValue {
+    fn is_present(&self) -> bool {
+        match self {
+            EloValue::Null => false,
+            EloValue::String(s) => s.is_present(),
+            EloValue::Array(items) => !items.is_empty(),
+            _ => true,
+        }
+    }
+}
+
+macro_rules! always_present {
+    ($($ty:ty),*) => {
+        $(impl Presence for $ty {
+            fn is_present(&self) -> bool {
+                true
+            }
+        })*
+    };
+}
+
+always_present!(
+    bool, char, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64
+);
+
+/// Check if a value counts as provided
+///
+/// # Example
+///
+/// ```
+/// use elo_rust::runtime::presence::is_present;
+///
+/// assert!(is_present(&Some("Alex".to_string())));
+/// assert!(!is_present(&Some("  ".to_string())));
+/// assert!(!is_present(&None::<String>));
+/// ```
+pub fn is_present<T: Presence + ?Sized>(value: &T) -> bool {
+    value.is_present()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strings() {
+        assert!(is_present("x"));
+        assert!(!is_present(""));
+        assert!(!is_present(" \t"));
+        assert!(is_present(&"name".to_string()));
+    }
+
+    #[test]
+    fn test_options_and_collections() {
+        assert!(is_present(&Some(0i64)));
+        assert!(!is_present(&None::<i64>));
+        assert!(!is_present(&Some(String::new())));
+        assert!(!is_present(&Vec::<i64>::new()));
+        assert!(is_present(&vec![1, 2]));
+    }
+
+    #[test]
+    fn test_elo_values() {
+        assert!(!is_present(&EloValue::Null));
+        assert!(!is_present(&EloValue::String(String::new())));
+        assert!(is_present(&EloValue::Boolean(false)));
+    }
+}
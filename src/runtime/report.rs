@@ -0,0 +1,294 @@
+//! Per-rule validation reports
+//!
+//! A [`ValidationReport`] records the outcome of every rule in a rule set
+//! (passed, failed, skipped or warned) rather than only the failures.
+//! RuleSet-generated validators produce one through `<name>_report`; the
+//! plain `<name>` validator is a thin wrapper over
+//! [`ValidationReport::into_result`].
+
+use super::{ValidationError, ValidationErrors};
+use std::fmt;
+use std::time::Duration;
+
+/// Why a rule was not evaluated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The rule's feature flag is disabled
+    FlagDisabled(String),
+    /// The input is outside the rule's sample
+    NotSampled,
+    /// The rule's `effective_from` date has not been reached
+    NotYetEffective,
+    /// The rule's `expires_at` date has passed
+    Expired,
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FlagDisabled(flag) => write!(f, "flag '{}' is disabled", flag),
+            Self::NotSampled => write!(f, "not sampled"),
+            Self::NotYetEffective => write!(f, "not yet effective"),
+            Self::Expired => write!(f, "expired"),
+        }
+    }
+}
+
+/// Outcome of a single rule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleStatus {
+    /// The rule held
+    Passed,
+    /// The rule did not hold
+    Failed,
+    /// The rule was not evaluated
+    Skipped(SkipReason),
+    /// The rule held but raised warnings
+    Warned,
+}
+
+/// Result of evaluating one rule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleOutcome {
+    /// Rule name
+    pub rule: String,
+    /// What happened
+    pub status: RuleStatus,
+    /// Time spent evaluating the rule, when timing is enabled
+    pub duration: Option<Duration>,
+    /// Errors (for failed rules) or warnings (for warned rules)
+    pub errors: Vec<ValidationError>,
+}
+
+impl RuleOutcome {
+    fn new(rule: impl Into<String>, status: RuleStatus) -> Self {
+        Self {
+            rule: rule.into(),
+            status,
+            duration: None,
+            errors: Vec::new(),
+        }
+    }
+
+    /// A rule that held
+    pub fn passed(rule: impl Into<String>) -> Self {
+        Self::new(rule, RuleStatus::Passed)
+    }
+
+    /// A rule that did not hold, with its error messages
+    pub fn failed(rule: impl Into<String>, messages: Vec<String>) -> Self {
+        let mut outcome = Self::new(rule, RuleStatus::Failed);
+        outcome.errors = messages
+            .into_iter()
+            .map(|m| ValidationError::new(outcome.rule.clone(), m, outcome.rule.clone()))
+            .collect();
+        outcome
+    }
+
+    /// A rule that was not evaluated
+    pub fn skipped(rule: impl Into<String>, reason: SkipReason) -> Self {
+        Self::new(rule, RuleStatus::Skipped(reason))
+    }
+
+    /// A rule that held but raised warnings
+    pub fn warned(rule: impl Into<String>, messages: Vec<String>) -> Self {
+        let mut outcome = Self::new(rule, RuleStatus::Warned);
+        outcome.errors = messages
+            .into_iter()
+            .map(|m| ValidationError::warning(outcome.rule.clone(), m, outcome.rule.clone()))
+            .collect();
+        outcome
+    }
+
+    /// Attach the evaluation time
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+}
+
+/// Outcomes of all rules of a validation run, in evaluation order
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// One entry per rule
+    pub outcomes: Vec<RuleOutcome>,
+}
+
+impl ValidationReport {
+    /// Create an empty report
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a rule outcome
+    pub fn push(&mut self, outcome: RuleOutcome) {
+        self.outcomes.push(outcome);
+    }
+
+    /// Check that no rule failed (skipped and warned rules do not count)
+    pub fn is_ok(&self) -> bool {
+        !self.outcomes.iter().any(|o| o.status == RuleStatus::Failed)
+    }
+
+    /// Look up the outcome of a rule
+    pub fn get(&self, rule: &str) -> Option<&RuleOutcome> {
+        self.outcomes.iter().find(|o| o.rule == rule)
+    }
+
+    /// Rules that held
+    pub fn passed(&self) -> impl Iterator<Item = &RuleOutcome> {
+        self.outcomes
+            .iter()
+            .filter(|o| o.status == RuleStatus::Passed)
+    }
+
+    /// Rules that did not hold
+    pub fn failed(&self) -> impl Iterator<Item = &RuleOutcome> {
+        self.outcomes
+            .iter()
+            .filter(|o| o.status == RuleStatus::Failed)
+    }
+
+    /// Rules that were not evaluated
+    pub fn skipped(&self) -> impl Iterator<Item = &RuleOutcome> {
+        self.outcomes
+            .iter()
+            .filter(|o| matches!(o.status, RuleStatus::Skipped(_)))
+    }
+
+    /// Rules that raised warnings
+    pub fn warned(&self) -> impl Iterator<Item = &RuleOutcome> {
+        self.outcomes
+            .iter()
+            .filter(|o| o.status == RuleStatus::Warned)
+    }
+
+    /// Total time spent in timed rules
+    pub fn total_duration(&self) -> Duration {
+        self.outcomes.iter().filter_map(|o| o.duration).sum()
+    }
+
+    /// Convert to the `Result` shape used by `ValidationErrors`
+    ///
+    /// Warnings are included in the error collection only when the run
+    /// failed, mirroring [`ValidationErrors::has_errors`].
+    pub fn into_result(self) -> Result<(), ValidationErrors> {
+        if self.is_ok() {
+            return Ok(());
+        }
+        let mut errors = ValidationErrors::new();
+        for outcome in self.outcomes {
+            for error in outcome.errors {
+                errors.push(error);
+            }
+        }
+        Err(errors)
+    }
+
+    /// Convert to a `Result` holding only the failed rules' messages
+    pub fn into_string_result(self) -> Result<(), Vec<String>> {
+        if self.is_ok() {
+            return Ok(());
+        }
+        Err(self
+            .outcomes
+            .into_iter()
+            .filter(|o| o.status == RuleStatus::Failed)
+            .flat_map(|o| o.errors)
+            .map(|e| e.message)
+            .collect())
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, outcome) in self.outcomes.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            match &outcome.status {
+                RuleStatus::Passed => write!(f, "PASS {}", outcome.rule)?,
+                RuleStatus::Failed => write!(f, "FAIL {}", outcome.rule)?,
+                RuleStatus::Skipped(reason) => write!(f, "SKIP {} ({})", outcome.rule, reason)?,
+                RuleStatus::Warned => write!(f, "WARN {}", outcome.rule)?,
+            }
+            for error in &outcome.errors {
+                write!(f, "\n  {}", error.message)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_report() -> ValidationReport {
+        let mut report = ValidationReport::new();
+        report.push(RuleOutcome::passed("is_adult"));
+        report.push(RuleOutcome::failed(
+            "has_payment",
+            vec!["Rule 'has_payment' failed".to_string()],
+        ));
+        report.push(RuleOutcome::skipped(
+            "kyc",
+            SkipReason::FlagDisabled("new_kyc".to_string()),
+        ));
+        report.push(RuleOutcome::warned(
+            "low_balance",
+            vec!["Balance is low".to_string()],
+        ));
+        report
+    }
+
+    #[test]
+    fn test_status_queries() {
+        let report = sample_report();
+        assert!(!report.is_ok());
+        assert_eq!(report.passed().count(), 1);
+        assert_eq!(report.failed().count(), 1);
+        assert_eq!(report.skipped().count(), 1);
+        assert_eq!(report.warned().count(), 1);
+        assert!(report.get("low_balance").unwrap().errors[0].is_warning());
+    }
+
+    #[test]
+    fn test_into_string_result() {
+        assert_eq!(
+            sample_report().into_string_result(),
+            Err(vec!["Rule 'has_payment' failed".to_string()])
+        );
+
+        let mut ok = ValidationReport::new();
+        ok.push(RuleOutcome::passed("a"));
+        ok.push(RuleOutcome::skipped("b", SkipReason::NotSampled));
+        assert!(ok.is_ok());
+        assert_eq!(ok.into_string_result(), Ok(()));
+    }
+
+    #[test]
+    fn test_into_result() {
+        let errors = sample_report().into_result().unwrap_err();
+        assert!(errors.has_errors());
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors.warnings().count(), 1);
+    }
+
+    #[test]
+    fn test_durations() {
+        let mut report = ValidationReport::new();
+        report.push(RuleOutcome::passed("a").with_duration(Duration::from_micros(5)));
+        report.push(RuleOutcome::passed("b"));
+        assert_eq!(report.total_duration(), Duration::from_micros(5));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            sample_report().to_string(),
+            "PASS is_adult\nFAIL has_payment\n  Rule 'has_payment' failed\n\
+             SKIP kyc (flag 'new_kyc' is disabled)\nWARN low_balance\n  Balance is low"
+        );
+    }
+}
@@ -0,0 +1,291 @@
+//! Shadow evaluation of candidate rule versions
+//!
+//! A [`ShadowRunner`] runs the current validator and a candidate validator on
+//! the same inputs. Callers always get the current validator's result, so the
+//! candidate can be trialled in production without affecting behaviour.
+//! Differences are recorded as [`Divergence`]s, identified by a digest of the
+//! input rather than the input itself so that payloads never leave the
+//! process.
+//!
+//! # Example
+//!
+//! ```
+//! use elo_rust::runtime::shadow::ShadowRunner;
+//!
+//! fn current(age: &i64) -> Result<(), Vec<String>> {
+//!     if *age >= 18 { Ok(()) } else { Err(vec!["too young".to_string()]) }
+//! }
+//! fn candidate(age: &i64) -> Result<(), Vec<String>> {
+//!     if *age >= 21 { Ok(()) } else { Err(vec!["too young".to_string()]) }
+//! }
+//!
+//! let mut runner = ShadowRunner::new(current, candidate);
+//! for age in [17, 19, 30] {
+//!     runner.run(&age).ok();
+//! }
+//! let summary = runner.summary();
+//! assert_eq!(summary.total, 3);
+//! assert_eq!(summary.newly_rejected, 1);
+//! ```
+
+use std::fmt;
+
+/// Result type of a generated validator
+pub type Outcome = Result<(), Vec<String>>;
+
+/// Default number of divergences kept for inspection
+pub const DEFAULT_MAX_RECORDS: usize = 1000;
+
+/// How the candidate's result differs from the current one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DivergenceKind {
+    /// Accepted by the current rules, rejected by the candidate
+    NewlyRejected,
+    /// Rejected by the current rules, accepted by the candidate
+    NewlyAccepted,
+    /// Rejected by both, with different messages
+    MessagesChanged,
+}
+
+/// A single input on which the two rule versions disagree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// Digest of the input (see [`payload_digest`])
+    pub digest: String,
+    /// Kind of disagreement
+    pub kind: DivergenceKind,
+    /// Result of the current rules
+    pub current: Outcome,
+    /// Result of the candidate rules
+    pub candidate: Outcome,
+}
+
+/// Aggregate impact of the candidate rules
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShadowSummary {
+    /// Number of inputs evaluated
+    pub total: usize,
+    /// Inputs on which both versions agree
+    pub agreements: usize,
+    /// Inputs the candidate would start rejecting
+    pub newly_rejected: usize,
+    /// Inputs the candidate would start accepting
+    pub newly_accepted: usize,
+    /// Inputs rejected by both with different messages
+    pub messages_changed: usize,
+}
+
+impl ShadowSummary {
+    /// Number of inputs with any divergence
+    pub fn divergences(&self) -> usize {
+        self.newly_rejected + self.newly_accepted + self.messages_changed
+    }
+
+    /// Fraction of inputs with any divergence (0.0 when nothing ran)
+    pub fn divergence_rate(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.divergences() as f64 / self.total as f64
+        }
+    }
+
+    /// Check if the candidate never changed an accept/reject decision
+    pub fn is_safe(&self) -> bool {
+        self.newly_rejected == 0 && self.newly_accepted == 0
+    }
+}
+
+impl fmt::Display for ShadowSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} inputs, {} divergent ({:.2}%): {} newly rejected, {} newly accepted, {} message changes",
+            self.total,
+            self.divergences(),
+            self.divergence_rate() * 100.0,
+            self.newly_rejected,
+            self.newly_accepted,
+            self.messages_changed
+        )
+    }
+}
+
+/// Runs current and candidate validators side by side
+pub struct ShadowRunner<T: ?Sized> {
+    current: Box<dyn Fn(&T) -> Outcome + Send + Sync>,
+    candidate: Box<dyn Fn(&T) -> Outcome + Send + Sync>,
+    summary: ShadowSummary,
+    divergences: Vec<Divergence>,
+    max_records: usize,
+}
+
+impl<T: ?Sized> fmt::Debug for ShadowRunner<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShadowRunner")
+            .field("summary", &self.summary)
+            .field("divergences", &self.divergences.len())
+            .field("max_records", &self.max_records)
+            .finish()
+    }
+}
+
+impl<T: fmt::Debug + ?Sized> ShadowRunner<T> {
+    /// Create a runner from the current and candidate validators
+    pub fn new<C, N>(current: C, candidate: N) -> Self
+    where
+        C: Fn(&T) -> Outcome + Send + Sync + 'static,
+        N: Fn(&T) -> Outcome + Send + Sync + 'static,
+    {
+        Self {
+            current: Box::new(current),
+            candidate: Box::new(candidate),
+            summary: ShadowSummary::default(),
+            divergences: Vec::new(),
+            max_records: DEFAULT_MAX_RECORDS,
+        }
+    }
+
+    /// Limit how many divergences are kept (the summary still counts all of them)
+    pub fn with_max_records(mut self, max_records: usize) -> Self {
+        self.max_records = max_records;
+        self
+    }
+
+    /// Validate an input with both versions, returning the current result
+    pub fn run(&mut self, input: &T) -> Outcome {
+        let current = (self.current)(input);
+        let candidate = (self.candidate)(input);
+        self.summary.total += 1;
+
+        let kind = match (&current, &candidate) {
+            (Ok(()), Ok(())) => None,
+            (Ok(()), Err(_)) => Some(DivergenceKind::NewlyRejected),
+            (Err(_), Ok(())) => Some(DivergenceKind::NewlyAccepted),
+            (Err(a), Err(b)) if a != b => Some(DivergenceKind::MessagesChanged),
+            (Err(_), Err(_)) => None,
+        };
+
+        match kind {
+            None => self.summary.agreements += 1,
+            Some(kind) => {
+                match kind {
+                    DivergenceKind::NewlyRejected => self.summary.newly_rejected += 1,
+                    DivergenceKind::NewlyAccepted => self.summary.newly_accepted += 1,
+                    DivergenceKind::MessagesChanged => self.summary.messages_changed += 1,
+                }
+                if self.divergences.len() < self.max_records {
+                    self.divergences.push(Divergence {
+                        digest: payload_digest(input),
+                        kind,
+                        current: current.clone(),
+                        candidate,
+                    });
+                }
+            }
+        }
+        current
+    }
+
+    /// Aggregate results so far
+    pub fn summary(&self) -> &ShadowSummary {
+        &self.summary
+    }
+
+    /// Recorded divergences, oldest first
+    pub fn divergences(&self) -> &[Divergence] {
+        &self.divergences
+    }
+
+    /// Clear all counters and records
+    pub fn reset(&mut self) {
+        self.summary = ShadowSummary::default();
+        self.divergences.clear();
+    }
+}
+
+/// Stable 64-bit FNV-1a digest of an input's `Debug` representation
+///
+/// The digest identifies a payload across runs (for example to find it in
+/// request logs) without storing its contents.
+pub fn payload_digest<T: fmt::Debug + ?Sized>(input: &T) -> String {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    let repr = format!("{:?}", input);
+    let hash = repr.bytes().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    });
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn min_length(min: usize) -> impl Fn(&str) -> Outcome + Send + Sync {
+        move |s: &str| {
+            if s.len() >= min {
+                Ok(())
+            } else {
+                Err(vec![format!("shorter than {}", min)])
+            }
+        }
+    }
+
+    #[test]
+    fn test_returns_current_result() {
+        let mut runner = ShadowRunner::new(min_length(3), min_length(5));
+        assert_eq!(runner.run("abcd"), Ok(()));
+        assert_eq!(runner.divergences().len(), 1);
+        assert_eq!(runner.divergences()[0].kind, DivergenceKind::NewlyRejected);
+        assert_eq!(
+            runner.divergences()[0].candidate,
+            Err(vec!["shorter than 5".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_summary_counts() {
+        let mut runner = ShadowRunner::new(min_length(3), min_length(5));
+        for input in ["a", "abcd", "abcdef", "ab"] {
+            runner.run(input).ok();
+        }
+        let summary = runner.summary();
+        assert_eq!(summary.total, 4);
+        assert_eq!(summary.newly_rejected, 1);
+        assert_eq!(summary.messages_changed, 2);
+        assert_eq!(summary.agreements, 1);
+        assert!(!summary.is_safe());
+        assert_eq!(summary.divergence_rate(), 0.75);
+        assert!(summary
+            .to_string()
+            .starts_with("4 inputs, 3 divergent (75.00%)"));
+    }
+
+    #[test]
+    fn test_newly_accepted_and_reset() {
+        let mut runner = ShadowRunner::new(min_length(5), min_length(3));
+        runner.run("abcd").ok();
+        assert_eq!(runner.summary().newly_accepted, 1);
+        runner.reset();
+        assert_eq!(runner.summary(), &ShadowSummary::default());
+        assert!(runner.divergences().is_empty());
+    }
+
+    #[test]
+    fn test_max_records() {
+        let mut runner = ShadowRunner::new(min_length(3), min_length(5)).with_max_records(1);
+        runner.run("abc").ok();
+        runner.run("abcd").ok();
+        assert_eq!(runner.summary().newly_rejected, 2);
+        assert_eq!(runner.divergences().len(), 1);
+    }
+
+    #[test]
+    fn test_payload_digest_is_stable() {
+        assert_eq!(payload_digest(""), payload_digest(""));
+        assert_ne!(payload_digest("a"), payload_digest("b"));
+        assert_eq!(payload_digest("abc").len(), 16);
+    }
+}
@@ -0,0 +1,202 @@
+//! Allocation-free error reporting
+//!
+//! Validators generated with
+//! [`RustCodeGenerator::generate_sink_validator`](crate::RustCodeGenerator::generate_sink_validator)
+//! write failures into a caller-provided [`ErrorSink`] instead of returning
+//! a freshly allocated `Vec<String>`. Rule names and messages are known at
+//! code generation time, so a [`Failure`] only holds `&'static str`s and a
+//! [`FixedErrorSink`] stores them in a fixed-size buffer on the stack.
+//!
+//! # Example
+//!
+//! ```
+//! use elo_rust::runtime::sink::{ErrorSink, Failure, FixedErrorSink};
+//!
+//! let mut sink = FixedErrorSink::<2>::new();
+//! sink.push(Failure::error("is_adult", "must be 18 or older"));
+//! sink.push(Failure::warning("is_adult", "close to the limit"));
+//! sink.push(Failure::error("has_email", "email is required"));
+//!
+//! assert!(sink.has_errors());
+//! assert_eq!(sink.len(), 2);
+//! assert_eq!(sink.dropped(), 1);
+//! ```
+
+use super::{Severity, ValidationError, ValidationErrors};
+use std::fmt;
+
+/// A failure known at compile time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Failure {
+    /// The rule that produced the failure
+    pub rule: &'static str,
+    /// Human-readable message
+    pub message: &'static str,
+    /// Whether the failure is an error or only a warning
+    pub severity: Severity,
+}
+
+impl Failure {
+    /// A failure that makes the input invalid
+    pub const fn error(rule: &'static str, message: &'static str) -> Self {
+        Self {
+            rule,
+            message,
+            severity: Severity::Error,
+        }
+    }
+
+    /// A warning that does not make the input invalid
+    pub const fn warning(rule: &'static str, message: &'static str) -> Self {
+        Self {
+            rule,
+            message,
+            severity: Severity::Warning,
+        }
+    }
+
+    /// Check if this failure is only a warning
+    pub fn is_warning(&self) -> bool {
+        self.severity == Severity::Warning
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.rule, self.message)
+    }
+}
+
+/// Destination for failures reported by a validator
+pub trait ErrorSink {
+    /// Record a failure
+    fn push(&mut self, failure: Failure);
+}
+
+impl ErrorSink for ValidationErrors {
+    fn push(&mut self, failure: Failure) {
+        let error = match failure.severity {
+            Severity::Error => ValidationError::new(failure.rule, failure.message, failure.rule),
+            Severity::Warning => {
+                ValidationError::warning(failure.rule, failure.message, failure.rule)
+            }
+        };
+        ValidationErrors::push(self, error);
+    }
+}
+
+impl ErrorSink for Vec<Failure> {
+    fn push(&mut self, failure: Failure) {
+        Vec::push(self, failure);
+    }
+}
+
+/// Sink that keeps up to `N` failures without allocating
+///
+/// Failures beyond capacity are counted but not stored.
+#[derive(Debug, Clone, Copy)]
+pub struct FixedErrorSink<const N: usize> {
+    failures: [Option<Failure>; N],
+    len: usize,
+    dropped: usize,
+    has_errors: bool,
+}
+
+impl<const N: usize> Default for FixedErrorSink<N> {
+    fn default() -> Self {
+        Self {
+            failures: [None; N],
+            len: 0,
+            dropped: 0,
+            has_errors: false,
+        }
+    }
+}
+
+impl<const N: usize> FixedErrorSink<N> {
+    /// Create an empty sink
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of stored failures
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if no failure was stored
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of failures that did not fit
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /// Check if any error (not warning) was reported, including dropped ones
+    pub fn has_errors(&self) -> bool {
+        self.has_errors
+    }
+
+    /// Stored failures, in reporting order
+    pub fn iter(&self) -> impl Iterator<Item = &Failure> {
+        self.failures[..self.len].iter().flatten()
+    }
+
+    /// Forget all failures, so the sink can be reused for the next input
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl<const N: usize> ErrorSink for FixedErrorSink<N> {
+    fn push(&mut self, failure: Failure) {
+        self.has_errors |= failure.severity == Severity::Error;
+        match self.failures.get_mut(self.len) {
+            Some(slot) => {
+                *slot = Some(failure);
+                self.len += 1;
+            }
+            None => self.dropped += 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_sink_capacity() {
+        let mut sink = FixedErrorSink::<1>::new();
+        sink.push(Failure::warning("a", "first"));
+        sink.push(Failure::error("b", "second"));
+        assert_eq!(sink.len(), 1);
+        assert_eq!(sink.dropped(), 1);
+        // The dropped failure still counts as an error
+        assert!(sink.has_errors());
+        assert_eq!(sink.iter().next().unwrap().to_string(), "a: first");
+
+        sink.clear();
+        assert!(sink.is_empty());
+        assert!(!sink.has_errors());
+    }
+
+    #[test]
+    fn test_zero_capacity() {
+        let mut sink = FixedErrorSink::<0>::new();
+        sink.push(Failure::error("a", "failed"));
+        assert!(sink.is_empty());
+        assert!(sink.has_errors());
+    }
+
+    #[test]
+    fn test_validation_errors_sink() {
+        let mut errors = ValidationErrors::new();
+        ErrorSink::push(&mut errors, Failure::error("is_adult", "too young"));
+        ErrorSink::push(&mut errors, Failure::warning("is_adult", "borderline"));
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors.warnings().count(), 1);
+    }
+}
@@ -0,0 +1,313 @@
+//! Streaming record readers for data-quality checks
+//!
+//! A [`RecordReader`] turns NDJSON (one JSON object per line) or CSV (a
+//! header line, then one record per line) into [`EloValue`] objects one line
+//! at a time, so arbitrarily large inputs are validated in bounded memory.
+//! Each line is read with [`read_line_with_limit`], which rejects records
+//! larger than [`MAX_RECORD_SIZE`](crate::security::MAX_RECORD_SIZE).
+//!
+//! Malformed records are reported as [`StreamError`]s without ending the
+//! stream. CSV cells are typed as integers, floats or booleans when they
+//! parse as one; empty cells are `null`. Quoted CSV cells cannot span lines.
+//! NDJSON needs the `serde-support` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use elo_rust::runtime::stream::{RecordFormat, RecordReader};
+//! use elo_rust::runtime::EloValue;
+//!
+//! let data = "name,age\nAda,36\nBob,\n";
+//! let records: Vec<_> = RecordReader::new(data.as_bytes(), RecordFormat::Csv)
+//!     .map(|record| record.unwrap())
+//!     .collect();
+//!
+//! assert_eq!(records.len(), 2);
+//! assert_eq!(records[0].line, 2);
+//! assert_eq!(records[0].value.get_path(&["age"]), EloValue::Integer(36));
+//! assert_eq!(records[1].value.get_path(&["age"]), EloValue::Null);
+//! ```
+
+use super::EloValue;
+use crate::security::read_line_with_limit;
+use std::fmt;
+use std::io::BufRead;
+
+/// Deepest nesting of JSON arrays and objects accepted in a record
+#[cfg(feature = "serde-support")]
+pub const MAX_JSON_DEPTH: usize = 64;
+
+/// Layout of the records in a stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordFormat {
+    /// One JSON object per line
+    #[cfg(feature = "serde-support")]
+    Ndjson,
+    /// Comma-separated values with a header line naming the fields
+    Csv,
+}
+
+impl RecordFormat {
+    /// Parse "ndjson" (or "jsonl") or "csv"
+    pub fn from_name(name: &str) -> Option<RecordFormat> {
+        match name {
+            #[cfg(feature = "serde-support")]
+            "ndjson" | "jsonl" => Some(RecordFormat::Ndjson),
+            "csv" => Some(RecordFormat::Csv),
+            _ => None,
+        }
+    }
+}
+
+/// One record read from a stream
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    /// Line number (1-based) the record was read from
+    pub line: usize,
+    /// The record as an object
+    pub value: EloValue,
+}
+
+/// A line that could not be read as a record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamError {
+    /// Line number (1-based) of the problem
+    pub line: usize,
+    /// Description of the problem
+    pub message: String,
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for StreamError {}
+
+/// Iterator over the records of an NDJSON or CSV stream
+///
+/// Blank lines are skipped. Read errors end the stream after being
+/// reported once.
+#[derive(Debug)]
+pub struct RecordReader<R> {
+    reader: R,
+    format: RecordFormat,
+    line: usize,
+    header: Option<Vec<String>>,
+    done: bool,
+}
+
+impl<R: BufRead> RecordReader<R> {
+    /// Read records of `format` from `reader`
+    pub fn new(reader: R, format: RecordFormat) -> Self {
+        Self {
+            reader,
+            format,
+            line: 0,
+            header: None,
+            done: false,
+        }
+    }
+
+    fn parse(&mut self, text: &str) -> Result<Option<EloValue>, String> {
+        match self.format {
+            #[cfg(feature = "serde-support")]
+            RecordFormat::Ndjson => match parse_json(text)? {
+                value @ EloValue::Object(_) => Ok(Some(value)),
+                other => Err(format!("expected a JSON object, got {}", other.type_name())),
+            },
+            RecordFormat::Csv => {
+                let cells = split_csv(text)?;
+                let Some(header) = &self.header else {
+                    self.header = Some(cells);
+                    return Ok(None);
+                };
+                if cells.len() != header.len() {
+                    return Err(format!(
+                        "expected {} cells, got {}",
+                        header.len(),
+                        cells.len()
+                    ));
+                }
+                Ok(Some(EloValue::Object(
+                    header
+                        .iter()
+                        .cloned()
+                        .zip(cells.iter().map(|cell| csv_value(cell)))
+                        .collect(),
+                )))
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for RecordReader<R> {
+    type Item = Result<Record, StreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let mut text = String::new();
+            self.line += 1;
+            let line = self.line;
+            let error = |message: String| Some(Err(StreamError { line, message }));
+            match read_line_with_limit(&mut self.reader, &mut text) {
+                Ok(0) => self.done = true,
+                Ok(_) if text.trim().is_empty() => {}
+                Ok(_) => match self.parse(text.trim_end_matches(['\n', '\r'])) {
+                    Ok(Some(value)) => return Some(Ok(Record { line, value })),
+                    Ok(None) => {}
+                    Err(message) => return error(message),
+                },
+                Err(e) if e.kind() == std::io::ErrorKind::InvalidData => {
+                    return error(e.to_string())
+                }
+                Err(e) => {
+                    self.done = true;
+                    return error(e.to_string());
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Type a CSV cell: integer, float, boolean, `null` when empty, else string
+fn csv_value(cell: &str) -> EloValue {
+    if cell.is_empty() {
+        EloValue::Null
+    } else if let Ok(n) = cell.parse::<i64>() {
+        EloValue::Integer(n)
+    } else if let Some(f) = cell.parse::<f64>().ok().filter(|f| f.is_finite()) {
+        EloValue::Float(f)
+    } else {
+        match cell {
+            "true" => EloValue::Boolean(true),
+            "false" => EloValue::Boolean(false),
+            _ => EloValue::String(cell.to_string()),
+        }
+    }
+}
+
+fn split_csv(line: &str) -> Result<Vec<String>, String> {
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                cell.push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => cells.push(std::mem::take(&mut cell).trim().to_string()),
+            ch => cell.push(ch),
+        }
+    }
+    if quoted {
+        return Err("unterminated quoted cell".to_string());
+    }
+    cells.push(cell.trim().to_string());
+    Ok(cells)
+}
+
+/// Parse one complete JSON document, such as a queue message payload
+///
+/// Arrays and objects may nest at most [`MAX_JSON_DEPTH`] levels deep.
+#[cfg(feature = "serde-support")]
+pub fn parse_json(text: &str) -> Result<EloValue, String> {
+    let value: serde_json::Value =
+        serde_json::from_str(text).map_err(|e| format!("invalid JSON: {}", e))?;
+    if json_depth(&value) > MAX_JSON_DEPTH {
+        return Err(format!(
+            "invalid JSON: nested deeper than {} levels",
+            MAX_JSON_DEPTH
+        ));
+    }
+    Ok(EloValue::from(value))
+}
+
+/// Levels of array and object nesting in a JSON value
+#[cfg(feature = "serde-support")]
+fn json_depth(value: &serde_json::Value) -> usize {
+    match value {
+        serde_json::Value::Array(items) => 1 + items.iter().map(json_depth).max().unwrap_or(0),
+        serde_json::Value::Object(fields) => 1 + fields.values().map(json_depth).max().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records(data: &str, format: RecordFormat) -> Vec<Result<Record, StreamError>> {
+        RecordReader::new(data.as_bytes(), format).collect()
+    }
+
+    #[cfg(feature = "serde-support")]
+    #[test]
+    fn test_parse_json() {
+        let value = parse_json(r#"{"a": [1, -2.5e1, true, null], "b": {"c": "x\"é😀"}}"#).unwrap();
+        assert_eq!(
+            value.get_path(&["a"]),
+            EloValue::Array(vec![
+                EloValue::Integer(1),
+                EloValue::Float(-25.0),
+                EloValue::Boolean(true),
+                EloValue::Null,
+            ])
+        );
+        assert_eq!(
+            value.get_path(&["b", "c"]),
+            EloValue::String("x\"é😀".to_string())
+        );
+
+        assert!(parse_json(r#"{"a": 1"#).is_err());
+        assert!(parse_json(r#"{"a": 1} x"#).is_err());
+        assert!(parse_json("01.").is_err());
+        let deep = format!(
+            "{}{}",
+            "[".repeat(MAX_JSON_DEPTH + 1),
+            "]".repeat(MAX_JSON_DEPTH + 1)
+        );
+        assert!(parse_json(&deep).unwrap_err().contains("nested deeper"));
+    }
+
+    #[cfg(feature = "serde-support")]
+    #[test]
+    fn test_ndjson_records() {
+        let read = records(
+            "{\"age\": 20}\n\n[1]\n{\"age\": 3}\nnot json\n",
+            RecordFormat::Ndjson,
+        );
+        assert_eq!(read.len(), 4);
+        assert_eq!(read[0].as_ref().unwrap().line, 1);
+        assert_eq!(
+            read[1].as_ref().unwrap_err().to_string(),
+            "line 3: expected a JSON object, got array"
+        );
+        assert_eq!(read[2].as_ref().unwrap().line, 4);
+        assert_eq!(read[3].as_ref().unwrap_err().line, 5);
+    }
+
+    #[test]
+    fn test_csv_records() {
+        let read = records(
+            "name, score, active\n\"Lovelace, Ada\",9.5,true\nBob,7\n",
+            RecordFormat::Csv,
+        );
+        let ada = &read[0].as_ref().unwrap().value;
+        assert_eq!(
+            ada.get_path(&["name"]),
+            EloValue::String("Lovelace, Ada".to_string())
+        );
+        assert_eq!(ada.get_path(&["score"]), EloValue::Float(9.5));
+        assert_eq!(ada.get_path(&["active"]), EloValue::Boolean(true));
+        assert_eq!(
+            read[1].as_ref().unwrap_err().to_string(),
+            "line 3: expected 3 cells, got 2"
+        );
+    }
+}
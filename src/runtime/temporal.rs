@@ -34,6 +34,11 @@ impl TemporalValue {
             .map_err(|e| format!("Invalid datetime format: {}", e))
     }
 
+    /// Parse an RFC 3339 datetime or, failing that, an ISO8601 date
+    pub fn parse(text: &str) -> Result<Self, String> {
+        Self::parse_datetime(text).or_else(|_| Self::parse_date(text))
+    }
+
     /// Parse an ISO8601 duration string
     pub fn parse_duration(duration_str: &str) -> Result<Self, String> {
         // Simple ISO8601 duration parsing
@@ -186,10 +191,21 @@ impl TemporalValue {
     }
 
     /// Compare two temporal values
+    ///
+    /// A date compares with a datetime as midnight UTC of that day.
     pub fn compare(&self, other: &TemporalValue) -> Result<std::cmp::Ordering, String> {
         match (self, other) {
             (TemporalValue::Date(d1), TemporalValue::Date(d2)) => Ok(d1.cmp(d2)),
             (TemporalValue::DateTime(dt1), TemporalValue::DateTime(dt2)) => Ok(dt1.cmp(dt2)),
+            (TemporalValue::Date(_), TemporalValue::DateTime(_)) => {
+                Ok(other.compare(self)?.reverse())
+            }
+            (TemporalValue::DateTime(dt), TemporalValue::Date(d)) => {
+                let midnight = d
+                    .and_hms_opt(0, 0, 0)
+                    .ok_or("Invalid date time combination")?;
+                Ok(dt.cmp(&Utc.from_utc_datetime(&midnight)))
+            }
             (TemporalValue::Duration(d1), TemporalValue::Duration(d2)) => Ok(d1.cmp(d2)),
             _ => Err(format!(
                 "Cannot compare {} with {}",
@@ -422,6 +438,17 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_compare_date_with_datetime() {
+        let date = TemporalValue::parse("2024-01-15").unwrap();
+        let noon = TemporalValue::parse("2024-01-15T12:00:00Z").unwrap();
+        assert_eq!(date.compare(&noon), Ok(std::cmp::Ordering::Less));
+        assert_eq!(noon.compare(&date), Ok(std::cmp::Ordering::Greater));
+        assert!(date
+            .compare(&TemporalValue::parse_duration("P1D").unwrap())
+            .is_err());
+    }
+
     #[test]
     fn test_type_name() {
         assert_eq!(
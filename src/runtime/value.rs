@@ -4,6 +4,8 @@
 //! This allows the compiler to track and validate types at both compile-time
 //! and runtime.
 
+use super::temporal::TemporalValue;
+use crate::codegen::float::format_float;
 use std::collections::BTreeMap;
 use std::fmt;
 
@@ -33,6 +35,19 @@ pub enum EloValue {
 
     /// Object as key-value pairs (sorted by key for consistency)
     Object(BTreeMap<String, EloValue>),
+
+    /// Range between two numeric bounds (start..end or start..=end)
+    Range {
+        /// Lower bound
+        start: Box<EloValue>,
+        /// Upper bound
+        end: Box<EloValue>,
+        /// Whether the upper bound is part of the range
+        inclusive: bool,
+    },
+
+    /// Date, datetime or duration (`@date(..)`, `TODAY`, ...)
+    Temporal(TemporalValue),
 }
 
 impl EloValue {
@@ -46,6 +61,8 @@ impl EloValue {
             EloValue::Null => "null",
             EloValue::Array(_) => "array",
             EloValue::Object(_) => "object",
+            EloValue::Range { .. } => "range",
+            EloValue::Temporal(t) => t.type_name(),
         }
     }
 
@@ -59,6 +76,18 @@ impl EloValue {
             EloValue::String(s) => !s.is_empty(),
             EloValue::Array(a) => !a.is_empty(),
             EloValue::Object(o) => !o.is_empty(),
+            EloValue::Range {
+                start,
+                end,
+                inclusive,
+            } => {
+                if *inclusive {
+                    !end.less_than(start).unwrap_or(true)
+                } else {
+                    start.less_than(end).unwrap_or(false)
+                }
+            }
+            EloValue::Temporal(_) => true,
         }
     }
 
@@ -87,14 +116,7 @@ impl EloValue {
     pub fn to_string_value(&self) -> String {
         match self {
             EloValue::Integer(n) => n.to_string(),
-            EloValue::Float(f) => {
-                // Format floats nicely, avoiding trailing zeros
-                if f.fract() == 0.0 {
-                    format!("{:.1}", f)
-                } else {
-                    f.to_string()
-                }
-            }
+            EloValue::Float(f) => format_float(*f),
             EloValue::String(s) => s.clone(),
             EloValue::Boolean(b) => b.to_string(),
             EloValue::Null => "null".to_string(),
@@ -109,6 +131,15 @@ impl EloValue {
                     .collect();
                 format!("{{{}}}", pairs.join(", "))
             }
+            EloValue::Range {
+                start,
+                end,
+                inclusive,
+            } => {
+                let op = if *inclusive { "..=" } else { ".." };
+                format!("{}{}{}", start.to_string_value(), op, end.to_string_value())
+            }
+            EloValue::Temporal(t) => t.to_iso8601(),
         }
     }
 
@@ -137,6 +168,55 @@ impl EloValue {
         matches!(self, EloValue::Object(_))
     }
 
+    /// Check if this value is a range
+    pub fn is_range(&self) -> bool {
+        matches!(self, EloValue::Range { .. })
+    }
+
+    /// Check whether a numeric value lies within this range
+    pub fn range_contains(&self, value: &EloValue) -> Result<bool, String> {
+        match self {
+            EloValue::Range {
+                start,
+                end,
+                inclusive,
+            } => {
+                let above_start = !value.less_than(start)?;
+                let below_end = if *inclusive {
+                    !end.less_than(value)?
+                } else {
+                    value.less_than(end)?
+                };
+                Ok(above_start && below_end)
+            }
+            other => Err(format!("Expected range, got {}", other.type_name())),
+        }
+    }
+
+    /// Expand an integer range into its values
+    ///
+    /// Returns `None` for non-ranges and ranges with non-integer bounds.
+    pub fn range_values(&self) -> Option<Vec<EloValue>> {
+        match self {
+            EloValue::Range {
+                start,
+                end,
+                inclusive,
+            } => match (start.as_ref(), end.as_ref()) {
+                (EloValue::Integer(s), EloValue::Integer(e)) => {
+                    let values: Vec<EloValue> = if *inclusive {
+                        (*s..=*e).map(EloValue::Integer).collect()
+                    } else {
+                        (*s..*e).map(EloValue::Integer).collect()
+                    };
+                    Some(values)
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
     /// Get array length if this is an array
     pub fn array_len(&self) -> Option<usize> {
         match self {
@@ -162,16 +242,51 @@ impl EloValue {
         }
     }
 
+    /// Look up a nested object field by path
+    ///
+    /// Missing keys (and lookups through non-objects) resolve to `Null`
+    /// instead of failing, matching the semantics of context lookups.
+    pub fn get_path(&self, path: &[&str]) -> EloValue {
+        let mut current = self;
+        for key in path {
+            match current {
+                EloValue::Object(obj) => match obj.get(*key) {
+                    Some(value) => current = value,
+                    None => return EloValue::Null,
+                },
+                _ => return EloValue::Null,
+            }
+        }
+        current.clone()
+    }
+
+    /// Return this value, or `default` if it is null (the `??` operator)
+    pub fn or_default(&self, default: &EloValue) -> EloValue {
+        match self {
+            EloValue::Null => default.clone(),
+            value => value.clone(),
+        }
+    }
+
     /// Add two values (numeric addition or string concatenation)
     pub fn add(&self, other: &EloValue) -> Result<EloValue, String> {
         match (self, other) {
-            (EloValue::Integer(a), EloValue::Integer(b)) => Ok(EloValue::Integer(a + b)),
+            (EloValue::Integer(a), EloValue::Integer(b)) => checked(a.checked_add(*b), "+"),
             (EloValue::Float(a), EloValue::Float(b)) => Ok(EloValue::Float(a + b)),
             (EloValue::Integer(a), EloValue::Float(b)) => Ok(EloValue::Float(*a as f64 + b)),
             (EloValue::Float(a), EloValue::Integer(b)) => Ok(EloValue::Float(a + *b as f64)),
             (EloValue::String(a), EloValue::String(b)) => {
                 Ok(EloValue::String(format!("{}{}", a, b)))
             }
+            (EloValue::Temporal(t), EloValue::Temporal(d @ TemporalValue::Duration(_)))
+            | (EloValue::Temporal(d @ TemporalValue::Duration(_)), EloValue::Temporal(t)) => {
+                t.add_duration(d).map(EloValue::Temporal)
+            }
+            (EloValue::String(s), EloValue::Temporal(d @ TemporalValue::Duration(_))) => {
+                TemporalValue::parse(s)?
+                    .add_duration(d)
+                    .map(EloValue::Temporal)
+            }
             _ => Err(format!(
                 "Cannot add {} and {}",
                 self.type_name(),
@@ -183,10 +298,21 @@ impl EloValue {
     /// Subtract two values
     pub fn subtract(&self, other: &EloValue) -> Result<EloValue, String> {
         match (self, other) {
-            (EloValue::Integer(a), EloValue::Integer(b)) => Ok(EloValue::Integer(a - b)),
+            (EloValue::Integer(a), EloValue::Integer(b)) => checked(a.checked_sub(*b), "-"),
             (EloValue::Float(a), EloValue::Float(b)) => Ok(EloValue::Float(a - b)),
             (EloValue::Integer(a), EloValue::Float(b)) => Ok(EloValue::Float(*a as f64 - b)),
             (EloValue::Float(a), EloValue::Integer(b)) => Ok(EloValue::Float(a - *b as f64)),
+            (EloValue::Temporal(t), EloValue::Temporal(d @ TemporalValue::Duration(_))) => {
+                t.subtract_duration(d).map(EloValue::Temporal)
+            }
+            (EloValue::String(s), EloValue::Temporal(d @ TemporalValue::Duration(_))) => {
+                TemporalValue::parse(s)?
+                    .subtract_duration(d)
+                    .map(EloValue::Temporal)
+            }
+            (EloValue::Temporal(a), EloValue::Temporal(b)) => {
+                a.difference(b).map(EloValue::Temporal)
+            }
             _ => Err(format!(
                 "Cannot subtract {} from {}",
                 other.type_name(),
@@ -198,7 +324,7 @@ impl EloValue {
     /// Multiply two values
     pub fn multiply(&self, other: &EloValue) -> Result<EloValue, String> {
         match (self, other) {
-            (EloValue::Integer(a), EloValue::Integer(b)) => Ok(EloValue::Integer(a * b)),
+            (EloValue::Integer(a), EloValue::Integer(b)) => checked(a.checked_mul(*b), "*"),
             (EloValue::Float(a), EloValue::Float(b)) => Ok(EloValue::Float(a * b)),
             (EloValue::Integer(a), EloValue::Float(b)) => Ok(EloValue::Float(*a as f64 * b)),
             (EloValue::Float(a), EloValue::Integer(b)) => Ok(EloValue::Float(a * *b as f64)),
@@ -225,7 +351,7 @@ impl EloValue {
                 if *b == 0 {
                     Err("Division by zero".to_string())
                 } else {
-                    Ok(EloValue::Integer(a / b))
+                    checked(a.checked_div(*b), "/")
                 }
             }
             (EloValue::Float(a), EloValue::Float(b)) => {
@@ -264,7 +390,7 @@ impl EloValue {
                 if *b == 0 {
                     Err("Modulo by zero".to_string())
                 } else {
-                    Ok(EloValue::Integer(a % b))
+                    checked(a.checked_rem(*b), "%")
                 }
             }
             _ => Err(format!(
@@ -276,19 +402,19 @@ impl EloValue {
     }
 
     /// Power operation
+    ///
+    /// See [`integer_power`] and [`float_power`] for how overflow and
+    /// results that are not real numbers are reported.
     pub fn power(&self, other: &EloValue) -> Result<EloValue, String> {
         match (self, other) {
-            (EloValue::Integer(a), EloValue::Integer(b)) => {
-                if *b < 0 {
-                    // Negative power returns float
-                    Ok(EloValue::Float((*a as f64).powf(*b as f64)))
-                } else {
-                    Ok(EloValue::Integer(a.pow(*b as u32)))
-                }
+            (EloValue::Integer(a), EloValue::Integer(b)) => integer_power(*a, *b),
+            (EloValue::Float(a), EloValue::Float(b)) => float_power(*a, *b).map(EloValue::Float),
+            (EloValue::Integer(a), EloValue::Float(b)) => {
+                float_power(*a as f64, *b).map(EloValue::Float)
+            }
+            (EloValue::Float(a), EloValue::Integer(b)) => {
+                float_power(*a, *b as f64).map(EloValue::Float)
             }
-            (EloValue::Float(a), EloValue::Float(b)) => Ok(EloValue::Float(a.powf(*b))),
-            (EloValue::Integer(a), EloValue::Float(b)) => Ok(EloValue::Float((*a as f64).powf(*b))),
-            (EloValue::Float(a), EloValue::Integer(b)) => Ok(EloValue::Float(a.powf(*b as f64))),
             _ => Err(format!(
                 "Cannot raise {} to power of {}",
                 self.type_name(),
@@ -307,6 +433,9 @@ impl EloValue {
             (EloValue::String(a), EloValue::String(b)) => a == b,
             (EloValue::Boolean(a), EloValue::Boolean(b)) => a == b,
             (EloValue::Null, EloValue::Null) => true,
+            (EloValue::Temporal(_), _) | (_, EloValue::Temporal(_)) => {
+                matches!(temporal_pair(self, other), Some((a, b)) if a.compare(&b) == Ok(std::cmp::Ordering::Equal))
+            }
             _ => false,
         }
     }
@@ -319,6 +448,16 @@ impl EloValue {
             (EloValue::Integer(a), EloValue::Float(b)) => Ok((*a as f64) < *b),
             (EloValue::Float(a), EloValue::Integer(b)) => Ok(*a < (*b as f64)),
             (EloValue::String(a), EloValue::String(b)) => Ok(a < b),
+            (EloValue::Temporal(_), _) | (_, EloValue::Temporal(_)) => {
+                match temporal_pair(self, other) {
+                    Some((a, b)) => Ok(a.compare(&b)? == std::cmp::Ordering::Less),
+                    None => Err(format!(
+                        "Cannot compare {} and {}",
+                        self.type_name(),
+                        other.type_name()
+                    )),
+                }
+            }
             _ => Err(format!(
                 "Cannot compare {} and {}",
                 self.type_name(),
@@ -357,10 +496,158 @@ impl fmt::Display for EloValue {
     }
 }
 
+/// The result of checked integer arithmetic, or an overflow error naming `op`
+fn checked(result: Option<i64>, op: &str) -> Result<EloValue, String> {
+    result
+        .map(EloValue::Integer)
+        .ok_or_else(|| format!("Integer overflow in '{}'", op))
+}
+
+/// Both operands as temporal values, reading ISO8601 strings (as found in
+/// JSON input) compared against a temporal value
+fn temporal_pair(a: &EloValue, b: &EloValue) -> Option<(TemporalValue, TemporalValue)> {
+    let temporal = |value: &EloValue| match value {
+        EloValue::Temporal(t) => Some(t.clone()),
+        EloValue::String(s) => TemporalValue::parse(s).ok(),
+        _ => None,
+    };
+    Some((temporal(a)?, temporal(b)?))
+}
+
+/// Raise an integer to an integer power
+///
+/// The result is an integer, as its type is: negative exponents truncate
+/// toward zero as integer division does (`2 ^ -1 == 0`, while
+/// `2.0 ^ -1 == 0.5`), and `0 ^ -1` is a division by zero. Results that do
+/// not fit in an `i64` are an error, never wrapped or truncated; this also
+/// covers exponents beyond `u32::MAX`, which only bases `0`, `1` and `-1`
+/// survive.
+pub fn integer_power(base: i64, exponent: i64) -> Result<EloValue, String> {
+    if exponent < 0 {
+        return match base {
+            0 => Err("Division by zero".to_string()),
+            1 => Ok(EloValue::Integer(1)),
+            -1 => Ok(EloValue::Integer(if exponent % 2 == 0 { 1 } else { -1 })),
+            _ => Ok(EloValue::Integer(0)),
+        };
+    }
+    let result = match (base, u32::try_from(exponent)) {
+        (base, Ok(exponent)) => base.checked_pow(exponent),
+        (0 | 1, Err(_)) => Some(base),
+        (-1, Err(_)) => Some(if exponent % 2 == 0 { 1 } else { -1 }),
+        (_, Err(_)) => None,
+    };
+    result
+        .map(EloValue::Integer)
+        .ok_or_else(|| format!("Integer overflow in {} ^ {}", base, exponent))
+}
+
+/// Divide integers in generated code
+///
+/// Panics with the error [`EloValue::divide`] reports, `Division by zero`
+/// or an overflow, so generated validators fail the way the interpreter
+/// does.
+pub fn strict_divide(a: i64, b: i64) -> i64 {
+    expect_integer(EloValue::Integer(a).divide(&EloValue::Integer(b)))
+}
+
+/// Remainder of integers in generated code
+///
+/// Panics with the error [`EloValue::modulo`] reports.
+pub fn strict_remainder(a: i64, b: i64) -> i64 {
+    expect_integer(EloValue::Integer(a).modulo(&EloValue::Integer(b)))
+}
+
+/// Raise an integer to an integer power in generated code
+///
+/// Panics with the error [`integer_power`] reports, for `0 ^ -1` or results
+/// that do not fit in an `i64`.
+pub fn strict_power(base: i64, exponent: i64) -> i64 {
+    expect_integer(integer_power(base, exponent))
+}
+
+/// Raise a float to a float power in generated code
+///
+/// Panics with the error [`float_power`] reports for results that are not
+/// real numbers or overflow.
+pub fn strict_float_power(base: f64, exponent: f64) -> f64 {
+    float_power(base, exponent).unwrap_or_else(|error| panic!("{}", error))
+}
+
+/// The integer result of an operation, panicking with its error
+fn expect_integer(result: Result<EloValue, String>) -> i64 {
+    match result {
+        Ok(EloValue::Integer(n)) => n,
+        Ok(other) => panic!("Expected an integer, got {}", other.type_name()),
+        Err(error) => panic!("{}", error),
+    }
+}
+
+/// Raise a float to a float power
+///
+/// NaN and infinite operands propagate as usual, but a finite base and
+/// exponent producing NaN (`(-8.0) ^ 0.5`) or an infinity (`10.0 ^ 400.0`,
+/// `0 ^ -1`) is an error instead of a silently poisoned result.
+pub fn float_power(base: f64, exponent: f64) -> Result<f64, String> {
+    let result = base.powf(exponent);
+    if result.is_finite() || !base.is_finite() || !exponent.is_finite() {
+        return Ok(result);
+    }
+    let (base, exponent) = (format_float(base), format_float(exponent));
+    if result.is_nan() {
+        Err(format!("{} ^ {} is not a real number", base, exponent))
+    } else {
+        Err(format!("Float overflow in {} ^ {}", base, exponent))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_strict_operations() {
+        assert_eq!(strict_divide(7, 2), 3);
+        assert_eq!(strict_remainder(-7, 2), -1);
+        assert_eq!(strict_power(3, 4), 81);
+        assert_eq!(strict_power(-1, i64::MAX), -1);
+        assert_eq!(strict_power(2, -1), 0);
+        assert_eq!(strict_power(-1, -3), -1);
+        assert_eq!(strict_float_power(2.0, 0.5), 2f64.sqrt());
+
+        let message = |operation: fn()| {
+            let payload = std::panic::catch_unwind(operation).unwrap_err();
+            payload
+                .downcast_ref::<String>()
+                .cloned()
+                .unwrap_or_else(|| payload.downcast_ref::<&str>().unwrap().to_string())
+        };
+        assert_eq!(
+            message(|| {
+                strict_divide(1, 0);
+            }),
+            "Division by zero"
+        );
+        assert_eq!(
+            message(|| {
+                strict_remainder(i64::MIN, -1);
+            }),
+            "Integer overflow in '%'"
+        );
+        assert_eq!(
+            message(|| {
+                strict_power(10, 30);
+            }),
+            "Integer overflow in 10 ^ 30"
+        );
+        assert_eq!(
+            message(|| {
+                strict_float_power(-8.0, 0.5);
+            }),
+            "-8.0 ^ 0.5 is not a real number"
+        );
+    }
+
     #[test]
     fn test_integer_operations() {
         let a = EloValue::Integer(10);
@@ -484,6 +771,72 @@ mod tests {
         assert_eq!(obj.object_get("z"), None);
     }
 
+    #[test]
+    fn test_get_path_missing_is_null() {
+        let mut limits = BTreeMap::new();
+        limits.insert("daily_max".to_string(), EloValue::Integer(500));
+        let mut ctx = BTreeMap::new();
+        ctx.insert("limits".to_string(), EloValue::Object(limits));
+        let ctx = EloValue::Object(ctx);
+
+        assert_eq!(
+            ctx.get_path(&["limits", "daily_max"]),
+            EloValue::Integer(500)
+        );
+        assert_eq!(ctx.get_path(&["limits", "weekly_max"]), EloValue::Null);
+        assert_eq!(ctx.get_path(&["missing", "deeper"]), EloValue::Null);
+        assert_eq!(
+            ctx.get_path(&["limits", "weekly_max"])
+                .or_default(&EloValue::Integer(1000)),
+            EloValue::Integer(1000)
+        );
+        assert_eq!(
+            ctx.get_path(&["limits", "daily_max"])
+                .or_default(&EloValue::Integer(1000)),
+            EloValue::Integer(500)
+        );
+    }
+
+    #[test]
+    fn test_range_values() {
+        let range = EloValue::Range {
+            start: Box::new(EloValue::Integer(1)),
+            end: Box::new(EloValue::Integer(4)),
+            inclusive: false,
+        };
+        assert_eq!(range.type_name(), "range");
+        assert_eq!(range.to_string_value(), "1..4");
+        assert!(range.is_truthy());
+        assert_eq!(range.range_contains(&EloValue::Integer(3)), Ok(true));
+        assert_eq!(range.range_contains(&EloValue::Integer(4)), Ok(false));
+        assert_eq!(range.range_contains(&EloValue::Float(1.5)), Ok(true));
+        assert_eq!(
+            range.range_values(),
+            Some(vec![
+                EloValue::Integer(1),
+                EloValue::Integer(2),
+                EloValue::Integer(3)
+            ])
+        );
+    }
+
+    #[test]
+    fn test_inclusive_range() {
+        let range = EloValue::Range {
+            start: Box::new(EloValue::Integer(18)),
+            end: Box::new(EloValue::Integer(65)),
+            inclusive: true,
+        };
+        assert_eq!(range.to_string_value(), "18..=65");
+        assert_eq!(range.range_contains(&EloValue::Integer(65)), Ok(true));
+        assert!(range
+            .range_contains(&EloValue::String("x".to_string()))
+            .is_err());
+        assert!(EloValue::Integer(1)
+            .range_contains(&EloValue::Integer(1))
+            .is_err());
+    }
+
     #[test]
     fn test_division_by_zero() {
         let a = EloValue::Integer(10);
@@ -492,6 +845,21 @@ mod tests {
         assert!(a.divide(&zero).is_err());
     }
 
+    #[test]
+    fn test_integer_overflow_is_an_error() {
+        let max = EloValue::Integer(i64::MAX);
+        let min = EloValue::Integer(i64::MIN);
+        let one = EloValue::Integer(1);
+        let minus_one = EloValue::Integer(-1);
+
+        assert_eq!(max.add(&one), Err("Integer overflow in '+'".to_string()));
+        assert!(min.subtract(&one).is_err());
+        assert!(max.multiply(&EloValue::Integer(2)).is_err());
+        assert!(min.divide(&minus_one).is_err());
+        assert!(min.modulo(&minus_one).is_err());
+        assert_eq!(max.subtract(&one), Ok(EloValue::Integer(i64::MAX - 1)));
+    }
+
     #[test]
     fn test_power_operation() {
         let base = EloValue::Integer(2);
@@ -500,6 +868,46 @@ mod tests {
         assert_eq!(base.power(&exp).unwrap(), EloValue::Integer(8));
     }
 
+    #[test]
+    fn test_power_overflow_and_non_real_results() {
+        assert_eq!(integer_power(2, -1), Ok(EloValue::Integer(0)));
+        assert_eq!(integer_power(-1, -3), Ok(EloValue::Integer(-1)));
+        assert_eq!(integer_power(0, -1), Err("Division by zero".to_string()));
+        assert_eq!(integer_power(2, 62), Ok(EloValue::Integer(1 << 62)));
+        assert_eq!(
+            integer_power(2, 63),
+            Err("Integer overflow in 2 ^ 63".to_string())
+        );
+        assert_eq!(integer_power(-1, 1 << 40 | 1), Ok(EloValue::Integer(-1)));
+        assert_eq!(integer_power(1, i64::MAX), Ok(EloValue::Integer(1)));
+        assert!(integer_power(2, 1 << 40).is_err());
+
+        assert_eq!(
+            float_power(-8.0, 0.5),
+            Err("-8.0 ^ 0.5 is not a real number".to_string())
+        );
+        assert_eq!(
+            float_power(10.0, 400.0),
+            Err("Float overflow in 10.0 ^ 400.0".to_string())
+        );
+        assert!(EloValue::Integer(0).power(&EloValue::Integer(-1)).is_err());
+        assert!(float_power(f64::NAN, 2.0).unwrap().is_nan());
+    }
+
+    #[test]
+    fn test_temporal_operations() {
+        let date = EloValue::Temporal(TemporalValue::parse_date("2024-01-15").unwrap());
+        let day = EloValue::Temporal(TemporalValue::parse_duration("P1D").unwrap());
+        let text = EloValue::String("2024-01-16".to_string());
+
+        assert_eq!(date.type_name(), "date");
+        assert!(date.less_than(&text).unwrap());
+        assert!(date.add(&day).unwrap().equals(&text));
+        assert!(text.subtract(&day).unwrap().equals(&date));
+        assert!(!date.equals(&EloValue::String("soon".to_string())));
+        assert!(date.less_than(&EloValue::Integer(1)).is_err());
+    }
+
     #[test]
     fn test_type_checks() {
         let i = EloValue::Integer(1);
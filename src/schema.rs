@@ -0,0 +1,507 @@
+//! Validation schemas: every rule of a type in one file
+//!
+//! A schema names the validated type and maps rule names to ELO
+//! expressions:
+//!
+//! ```toml
+//! type = "User"
+//!
+//! [rules]
+//! is_adult = "age >= 18"
+//! has_email = "require email != '' else 'email is required'"
+//! ```
+//!
+//! [`Schema::generate`] compiles it into a module with one validator
+//! function per rule (see
+//! [`RustCodeGenerator::generate_validator`]) and an
+//! `impl elo_rust::runtime::Validate for User` whose `validate` runs every
+//! rule and returns the errors of all that fail, in schema order. Each
+//! error names its rule, so callers can tell which check failed.
+//!
+//! The module is named `<type>_rules` in snake case (`user_rules` above)
+//! unless the schema sets `module = "..."`; `base_path = "..."` nests the
+//! paths of reported errors (see
+//! [`RustCodeGenerator::generate_validator_with_errors`]). Schemas are
+//! written in the TOML subset read by [`crate::config`], or, with the
+//! `serde-support` feature, as JSON with the same keys:
+//!
+//! ```json
+//! {"type": "User", "rules": {"is_adult": "age >= 18"}}
+//! ```
+//!
+//! JSON objects do not keep their order, so rules from JSON run in name
+//! order.
+//!
+//! # Example
+//!
+//! ```
+//! use elo_rust::schema::Schema;
+//! use elo_rust::RustCodeGenerator;
+//!
+//! let schema = Schema::parse("type = \"User\"\n[rules]\nis_adult = \"age >= 18\"").unwrap();
+//! let code = schema.generate(&RustCodeGenerator::new()).unwrap().to_string();
+//! assert!(code.contains("mod user_rules"));
+//! assert!(code.contains("impl elo_rust :: runtime :: Validate for User"));
+//! ```
+
+use crate::codegen::RustCodeGenerator;
+use crate::config::{closest, parse_value, strip_comment, unquote_key, Value};
+use crate::security::{read_file_with_limit, validate_file_path};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use std::fmt;
+
+/// Keys allowed outside the `[rules]` section
+const TOP_LEVEL_KEYS: [&str; 3] = ["type", "module", "base_path"];
+
+/// One named rule of a schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaRule {
+    /// Name of the rule and of its validator function
+    pub name: String,
+    /// ELO expression
+    pub expression: String,
+    /// Line of the rule in the schema file (0 if unknown, as for JSON)
+    pub line: usize,
+}
+
+/// A parsed validation schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schema {
+    /// Name of the validated Rust type
+    pub type_name: String,
+    /// Name of the generated module holding the rule functions
+    pub module: String,
+    /// Path under which reported error paths are nested (empty for none)
+    pub base_path: String,
+    /// Rules, in the order they are checked
+    pub rules: Vec<SchemaRule>,
+}
+
+/// An error in a schema file, or in one of its rules
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaError {
+    /// Name of the schema file, if it was loaded from one
+    pub file: Option<String>,
+    /// Line number (1-based) of the problem; 0 if it concerns the whole file
+    pub line: usize,
+    /// Description of the problem
+    pub message: String,
+}
+
+impl SchemaError {
+    fn new(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            file: None,
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let file = self.file.as_deref().unwrap_or("schema");
+        if self.line > 0 {
+            write!(f, "{} line {}: {}", file, self.line, self.message)
+        } else {
+            write!(f, "{}: {}", file, self.message)
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+impl Schema {
+    /// Parse and check a TOML schema
+    pub fn parse(source: &str) -> Result<Schema, SchemaError> {
+        let mut type_name: Option<String> = None;
+        let mut module: Option<String> = None;
+        let mut base_path = String::new();
+        let mut rules: Vec<SchemaRule> = Vec::new();
+        let mut in_rules = false;
+        let mut seen: Vec<&str> = Vec::new();
+
+        for (index, raw) in source.lines().enumerate() {
+            let line = index + 1;
+            let text = strip_comment(raw).trim();
+            if text.is_empty() {
+                continue;
+            }
+            if let Some(header) = text.strip_prefix('[') {
+                let name = header
+                    .strip_suffix(']')
+                    .ok_or_else(|| SchemaError::new(line, "unterminated section header"))?
+                    .trim();
+                if name != "rules" {
+                    return Err(SchemaError::new(
+                        line,
+                        format!("unknown section [{}]; rules go in [rules]", name),
+                    ));
+                }
+                if in_rules {
+                    return Err(SchemaError::new(line, "duplicate section [rules]"));
+                }
+                in_rules = true;
+                continue;
+            }
+
+            let (key, value) = text
+                .split_once('=')
+                .ok_or_else(|| SchemaError::new(line, "expected 'key = value'"))?;
+            let key = unquote_key(key.trim())
+                .ok_or_else(|| SchemaError::new(line, format!("invalid key '{}'", key.trim())))?;
+            let value = match parse_value(value.trim()) {
+                Ok(Value::String(value)) => value,
+                Ok(other) => {
+                    return Err(SchemaError::new(
+                        line,
+                        format!("'{}' must be a string, found {}", key, other.type_name()),
+                    ))
+                }
+                Err(message) => return Err(SchemaError::new(line, message)),
+            };
+
+            if in_rules {
+                if rules.iter().any(|rule| rule.name == key) {
+                    return Err(SchemaError::new(line, format!("duplicate rule '{}'", key)));
+                }
+                rules.push(SchemaRule {
+                    name: key,
+                    expression: value,
+                    line,
+                });
+                continue;
+            }
+            let Some(known) = TOP_LEVEL_KEYS.into_iter().find(|k| *k == key) else {
+                let mut message = format!("unknown key '{}'", key);
+                if let Some(suggestion) = closest(&key, &TOP_LEVEL_KEYS) {
+                    message.push_str(&format!(" (did you mean '{}'?)", suggestion));
+                }
+                return Err(SchemaError::new(line, message));
+            };
+            if seen.contains(&known) {
+                return Err(SchemaError::new(line, format!("duplicate key '{}'", key)));
+            }
+            seen.push(known);
+            match known {
+                "type" => type_name = Some(value),
+                "module" => module = Some(value),
+                _ => base_path = value,
+            }
+        }
+
+        let type_name = type_name.ok_or_else(|| SchemaError::new(0, "missing 'type = \"...\"'"))?;
+        Schema::build(type_name, module, base_path, rules)
+    }
+
+    /// Parse and check a JSON schema
+    #[cfg(feature = "serde-support")]
+    pub fn from_json(source: &str) -> Result<Schema, SchemaError> {
+        use serde_json::Value as Json;
+
+        let json: Json = serde_json::from_str(source)
+            .map_err(|e| SchemaError::new(e.line(), format!("invalid JSON: {}", e)))?;
+        let Json::Object(fields) = json else {
+            return Err(SchemaError::new(0, "a schema must be a JSON object"));
+        };
+        let string = |key: &str| match fields.get(key) {
+            None => Ok(None),
+            Some(Json::String(s)) => Ok(Some(s.clone())),
+            Some(_) => Err(SchemaError::new(0, format!("'{}' must be a string", key))),
+        };
+        for key in fields.keys() {
+            if key != "rules" && !TOP_LEVEL_KEYS.contains(&key.as_str()) {
+                return Err(SchemaError::new(0, format!("unknown key '{}'", key)));
+            }
+        }
+        let type_name = string("type")?.ok_or_else(|| SchemaError::new(0, "missing 'type'"))?;
+        let rules = match fields.get("rules") {
+            None => Vec::new(),
+            Some(Json::Object(rules)) => rules
+                .iter()
+                .map(|(name, expression)| match expression {
+                    Json::String(expression) => Ok(SchemaRule {
+                        name: name.clone(),
+                        expression: expression.clone(),
+                        line: 0,
+                    }),
+                    _ => Err(SchemaError::new(
+                        0,
+                        format!("rule '{}' must be a string", name),
+                    )),
+                })
+                .collect::<Result<_, _>>()?,
+            Some(_) => return Err(SchemaError::new(0, "'rules' must be an object")),
+        };
+        Schema::build(
+            type_name,
+            string("module")?,
+            string("base_path")?.unwrap_or_default(),
+            rules,
+        )
+    }
+
+    /// Read and parse a schema file inside the working directory
+    ///
+    /// Files ending in `.json` are read as JSON (with the `serde-support`
+    /// feature), all others as TOML.
+    pub fn load(path: &str) -> Result<Schema, SchemaError> {
+        let source = validate_file_path(path)
+            .and_then(|safe_path| read_file_with_limit(&safe_path))
+            .map_err(|e| SchemaError::new(0, format!("cannot read file: {}", e)));
+        let schema = source.and_then(|source| {
+            if path.ends_with(".json") {
+                Schema::parse_json_file(&source)
+            } else {
+                Schema::parse(&source)
+            }
+        });
+        schema.map_err(|error| SchemaError {
+            file: Some(path.to_string()),
+            ..error
+        })
+    }
+
+    #[cfg(feature = "serde-support")]
+    fn parse_json_file(source: &str) -> Result<Schema, SchemaError> {
+        Schema::from_json(source)
+    }
+
+    #[cfg(not(feature = "serde-support"))]
+    fn parse_json_file(_source: &str) -> Result<Schema, SchemaError> {
+        Err(SchemaError::new(
+            0,
+            "JSON schemas need the serde-support feature",
+        ))
+    }
+
+    /// Check names and fill in the default module
+    fn build(
+        type_name: String,
+        module: Option<String>,
+        base_path: String,
+        rules: Vec<SchemaRule>,
+    ) -> Result<Schema, SchemaError> {
+        let module = module.unwrap_or_else(|| format!("{}_rules", snake_case(&type_name)));
+        for (what, name) in [("type", &type_name), ("module", &module)] {
+            if syn::parse_str::<syn::Ident>(name).is_err() {
+                return Err(SchemaError::new(
+                    0,
+                    format!("{} '{}' is not a Rust identifier", what, name),
+                ));
+            }
+        }
+        if rules.is_empty() {
+            return Err(SchemaError::new(
+                0,
+                "a schema needs at least one rule in [rules]",
+            ));
+        }
+        for rule in &rules {
+            if syn::parse_str::<syn::Ident>(&rule.name).is_err() {
+                return Err(SchemaError::new(
+                    rule.line,
+                    format!("rule name '{}' is not a Rust identifier", rule.name),
+                ));
+            }
+        }
+        Ok(Schema {
+            type_name,
+            module,
+            base_path,
+            rules,
+        })
+    }
+
+    /// Generate the rule module and the `Validate` implementation
+    ///
+    /// `generator` compiles each rule, so types, parameters and options
+    /// registered on it apply to all of them. A rule that does not compile
+    /// is reported with its line.
+    pub fn generate(&self, generator: &RustCodeGenerator) -> Result<TokenStream, SchemaError> {
+        let mut functions = TokenStream::new();
+        let mut checks = Vec::with_capacity(self.rules.len());
+        for rule in &self.rules {
+            let code = generator
+                .generate_validator_with_errors(
+                    &rule.name,
+                    &rule.expression,
+                    &self.type_name,
+                    &self.base_path,
+                )
+                .map_err(|e| SchemaError::new(rule.line, format!("rule '{}': {}", rule.name, e)))?;
+            functions.extend(code);
+            let prefix = &generator.options().identifier_prefix;
+            let function = format_ident!("{}{}", prefix, rule.name);
+            checks.push(function);
+        }
+
+        let module = format_ident!("{}", self.module);
+        let type_ident = format_ident!("{}", self.type_name);
+        let doc = format!("Rules of [`{}`], one validator per rule", self.type_name);
+        Ok(quote! {
+            #[doc = #doc]
+            #[allow(unused_parens, unused_braces, clippy::all)]
+            mod #module {
+                use super::*;
+
+                #functions
+            }
+
+            impl elo_rust::runtime::Validate for #type_ident {
+                fn validate(&self) -> Result<(), elo_rust::runtime::ValidationErrors> {
+                    let mut errors = elo_rust::runtime::ValidationErrors::new();
+                    #(
+                        if let Err(failed) = #module::#checks(self) {
+                            errors.merge(failed);
+                        }
+                    )*
+                    if errors.is_empty() {
+                        Ok(())
+                    } else {
+                        Err(errors)
+                    }
+                }
+            }
+        })
+    }
+}
+
+/// `UserProfile` → `user_profile`
+fn snake_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len() + 4);
+    for (i, ch) in name.chars().enumerate() {
+        if ch.is_uppercase() {
+            if i > 0 {
+                out.push('_');
+            }
+            out.extend(ch.to_lowercase());
+        } else {
+            out.push(ch);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USER: &str = r#"
+# Rules for sign-up
+type = "UserProfile"
+base_path = "user"
+
+[rules]
+is_adult = "age >= 18"
+has_email = "require email != '' else 'email is required'"
+"#;
+
+    #[test]
+    fn test_parse_schema() {
+        let schema = Schema::parse(USER).unwrap();
+        assert_eq!(schema.type_name, "UserProfile");
+        assert_eq!(schema.module, "user_profile_rules");
+        assert_eq!(schema.base_path, "user");
+        assert_eq!(
+            schema.rules,
+            vec![
+                SchemaRule {
+                    name: "is_adult".to_string(),
+                    expression: "age >= 18".to_string(),
+                    line: 7,
+                },
+                SchemaRule {
+                    name: "has_email".to_string(),
+                    expression: "require email != '' else 'email is required'".to_string(),
+                    line: 8,
+                },
+            ]
+        );
+        let custom = Schema::parse("type = \"User\"\nmodule = \"checks\"\n[rules]\nok = \"true\"");
+        assert_eq!(custom.unwrap().module, "checks");
+    }
+
+    #[test]
+    fn test_schema_errors() {
+        let error = |source: &str| Schema::parse(source).unwrap_err().to_string();
+        assert_eq!(
+            error("typ = \"User\""),
+            "schema line 1: unknown key 'typ' (did you mean 'type'?)"
+        );
+        assert_eq!(
+            error("[rules]\na = \"x > 0\""),
+            "schema: missing 'type = \"...\"'"
+        );
+        assert_eq!(
+            error("type = \"User\""),
+            "schema: a schema needs at least one rule in [rules]"
+        );
+        assert_eq!(
+            error("type = \"User\"\n[rules]\na = \"x\"\na = \"y\""),
+            "schema line 4: duplicate rule 'a'"
+        );
+        assert_eq!(
+            error("type = \"User\"\n[rules]\nis-adult = \"age >= 18\""),
+            "schema line 3: rule name 'is-adult' is not a Rust identifier"
+        );
+        assert_eq!(
+            error("type = \"User\"\n[rules]\nlimit = 5"),
+            "schema line 3: 'limit' must be a string, found an integer"
+        );
+        assert_eq!(
+            error("type = \"User\"\n[checks]"),
+            "schema line 2: unknown section [checks]; rules go in [rules]"
+        );
+    }
+
+    #[test]
+    fn test_generate_schema() {
+        let schema = Schema::parse(USER).unwrap();
+        let code = schema
+            .generate(&RustCodeGenerator::new())
+            .unwrap()
+            .to_string();
+        assert!(code.contains("mod user_profile_rules { use super :: * ;"));
+        assert!(code.contains("pub fn is_adult (input : & UserProfile)"));
+        assert!(code.contains("pub fn has_email (input : & UserProfile)"));
+        assert!(code.contains("\"user.email\""));
+        assert!(code.contains("impl elo_rust :: runtime :: Validate for UserProfile"));
+        assert!(code.contains(
+            "if let Err (failed) = user_profile_rules :: is_adult (self) { errors . merge (failed) ; } if let Err (failed) = user_profile_rules :: has_email (self)"
+        ));
+
+        let broken = Schema::parse("type = \"User\"\n[rules]\nok = \"true\"\nbad = \"age >=\"")
+            .unwrap()
+            .generate(&RustCodeGenerator::new())
+            .unwrap_err();
+        assert_eq!(broken.line, 4);
+        assert!(broken.message.starts_with("rule 'bad': Parse error"));
+    }
+
+    #[cfg(feature = "serde-support")]
+    #[test]
+    fn test_json_schema() {
+        let schema = Schema::from_json(
+            r#"{"type": "User", "rules": {"is_adult": "age >= 18", "has_name": "name != ''"}}"#,
+        )
+        .unwrap();
+        assert_eq!(schema.module, "user_rules");
+        let names: Vec<&str> = schema.rules.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["has_name", "is_adult"]);
+        assert_eq!(
+            Schema::from_json(r#"{"type": "User", "rules": {"a": 1}}"#)
+                .unwrap_err()
+                .message,
+            "rule 'a' must be a string"
+        );
+    }
+
+    #[test]
+    fn test_snake_case() {
+        assert_eq!(snake_case("User"), "user");
+        assert_eq!(snake_case("UserProfile"), "user_profile");
+        assert_eq!(snake_case("order_line"), "order_line");
+    }
+}
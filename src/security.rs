@@ -1,1015 +0,0 @@
-//! Security validation module for user input and file operations
-
-use std::io;
-use std::path::{Component, PathBuf};
-
-/// Maximum allowed file size (10MB)
-const MAX_FILE_SIZE: u64 = 10_000_000;
-
-/// Maximum allowed length for ELO expressions
-const MAX_EXPRESSION_LENGTH: usize = 10_000;
-
-/// Maximum allowed regex pattern length
-const MAX_PATTERN_LENGTH: usize = 1_000;
-
-/// Validates a file path to prevent directory traversal attacks
-///
-/// # Security Checks
-/// - Rejects absolute paths
-/// - Rejects paths with `..` components
-/// - Ensures path stays within current working directory
-/// - Normalizes and canonicalizes the path
-///
-/// # Arguments
-/// * `path` - User-provided file path
-///
-/// # Returns
-/// - `Ok(PathBuf)` if path is valid and safe
-/// - `Err(io::Error)` if path violates security constraints
-pub fn validate_file_path(path: &str) -> io::Result<PathBuf> {
-    // Reject empty paths
-    if path.trim().is_empty() {
-        return Err(io::Error::new(
-            io::ErrorKind::InvalidInput,
-            "Path cannot be empty",
-        ));
-    }
-
-    let path_buf = PathBuf::from(path);
-
-    // Reject absolute paths
-    if path_buf.is_absolute() {
-        return Err(io::Error::new(
-            io::ErrorKind::PermissionDenied,
-            "Absolute paths are not allowed",
-        ));
-    }
-
-    // Reject paths with parent directory components (..)
-    for component in path_buf.components() {
-        if matches!(component, Component::ParentDir) {
-            return Err(io::Error::new(
-                io::ErrorKind::PermissionDenied,
-                "Path traversal (..) is not allowed",
-            ));
-        }
-    }
-
-    // Verify path is within current directory
-    let cwd = std::env::current_dir()?;
-    let full_path = cwd.join(&path_buf);
-
-    // For existing files/symlinks, canonicalize to resolve them
-    // For non-existent files, just verify the directory is safe
-    if full_path.exists() || full_path.symlink_metadata().is_ok() {
-        // Path exists (or is a symlink) - must canonicalize
-        // This prevents symlink escapes
-        let canonical_path = match full_path.canonicalize() {
-            Ok(path) => path,
-            Err(_) => {
-                // Broken symlink - reject it
-                return Err(io::Error::new(
-                    io::ErrorKind::PermissionDenied,
-                    "Path cannot be resolved (may be broken symlink or inaccessible)",
-                ));
-            }
-        };
-
-        // Verify canonical path is within cwd
-        if !canonical_path.starts_with(&cwd) {
-            return Err(io::Error::new(
-                io::ErrorKind::PermissionDenied,
-                "Path must be within current directory",
-            ));
-        }
-    } else {
-        // Path doesn't exist yet (e.g., output file)
-        // Just verify parent directory is safe
-        if let Some(parent) = full_path.parent() {
-            // Try to canonicalize parent directory
-            match parent.canonicalize() {
-                Ok(canonical_parent) => {
-                    if !canonical_parent.starts_with(&cwd) {
-                        return Err(io::Error::new(
-                            io::ErrorKind::PermissionDenied,
-                            "Path must be within current directory",
-                        ));
-                    }
-                }
-                Err(_) => {
-                    // Parent directory doesn't exist - still allow creation in current dir
-                    // This is safe because we check against full_path not existing
-                }
-            }
-        }
-    }
-
-    Ok(path_buf)
-}
-
-/// Validates a file path and stores the CWD for later use-time validation
-///
-/// # SECURITY FIX #5: TOCTOU Prevention
-///
-/// Returns both the validated path and the CWD at validation time.
-/// Before using the path, call `verify_path_still_valid()` to ensure
-/// the CWD hasn't changed and the path is still safe.
-///
-/// # Arguments
-/// * `path` - User-provided file path
-///
-/// # Returns
-/// - `Ok((PathBuf, PathBuf))` - The path and the CWD at validation time
-/// - `Err(io::Error)` if validation fails
-pub fn validate_file_path_with_context(path: &str) -> io::Result<(PathBuf, PathBuf)> {
-    // Perform initial validation
-    let validated_path = validate_file_path(path)?;
-
-    // Capture CWD at validation time
-    let validation_cwd = std::env::current_dir()?;
-
-    Ok((validated_path, validation_cwd))
-}
-
-/// Verifies that a previously validated path is still valid
-///
-/// # SECURITY FIX #5: TOCTOU Prevention
-///
-/// Checks that:
-/// 1. CWD hasn't changed since validation
-/// 2. Path still stays within the original CWD
-///
-/// # Arguments
-/// * `path` - The path to verify
-/// * `validation_cwd` - The CWD captured at validation time
-///
-/// # Returns
-/// - `Ok(())` if path is still valid
-/// - `Err(io::Error)` if validation has been compromised
-pub fn verify_path_still_valid(path: &PathBuf, validation_cwd: &PathBuf) -> io::Result<()> {
-    // Check if CWD has changed
-    let current_cwd = std::env::current_dir()?;
-    if current_cwd != *validation_cwd {
-        return Err(io::Error::new(
-            io::ErrorKind::PermissionDenied,
-            "Current working directory changed since path validation",
-        ));
-    }
-
-    // Re-validate path is within the CWD
-    let full_path = current_cwd.join(path);
-    if !full_path.starts_with(&current_cwd) {
-        return Err(io::Error::new(
-            io::ErrorKind::PermissionDenied,
-            "Path is no longer within current directory",
-        ));
-    }
-
-    Ok(())
-}
-
-/// Count open and close characters while respecting string literals
-///
-/// SECURITY FIX #1: Counts characters while tracking whether we're inside a string literal.
-/// This prevents counting parentheses/brackets that appear inside quoted strings.
-///
-/// # Arguments
-/// * `expr` - The expression to analyze
-/// * `open_char` - The opening character to count (e.g., '(')
-/// * `close_char` - The closing character to count (e.g., ')')
-///
-/// # Returns
-/// A tuple of (open_count, close_count) excluding characters in string literals
-fn count_balanced_with_string_awareness(
-    expr: &str,
-    open_char: char,
-    close_char: char,
-) -> (usize, usize) {
-    let mut in_string = false;
-    let mut string_delimiter = ' ';
-    let mut escape_next = false;
-    let mut open_count = 0;
-    let mut close_count = 0;
-
-    for ch in expr.chars() {
-        if escape_next {
-            escape_next = false;
-            continue;
-        }
-
-        match ch {
-            '\\' => escape_next = true,
-            '"' | '\'' => {
-                if in_string && ch == string_delimiter {
-                    in_string = false;
-                } else if !in_string {
-                    in_string = true;
-                    string_delimiter = ch;
-                }
-            }
-            c if !in_string && c == open_char => open_count += 1,
-            c if !in_string && c == close_char => close_count += 1,
-            _ => {}
-        }
-    }
-
-    (open_count, close_count)
-}
-
-/// Validates an ELO expression for syntax and safety
-///
-/// # Security Checks
-/// - Length limits (max 10,000 characters)
-/// - Balanced parentheses
-/// - Allowed character set only
-/// - No SQL injection patterns
-/// - No shell command patterns
-///
-/// # Arguments
-/// * `expr` - User-provided ELO expression
-///
-/// # Returns
-/// - `Ok(())` if expression is valid
-/// - `Err(String)` with error message if validation fails
-pub fn validate_expression(expr: &str) -> Result<(), String> {
-    // Check for empty expression
-    if expr.trim().is_empty() {
-        return Err("Expression cannot be empty".to_string());
-    }
-
-    // Check length limit
-    if expr.len() > MAX_EXPRESSION_LENGTH {
-        return Err(format!(
-            "Expression too long (max {} characters, got {})",
-            MAX_EXPRESSION_LENGTH,
-            expr.len()
-        ));
-    }
-
-    // Check for balanced parentheses (string-aware)
-    // SECURITY FIX #1: Count parentheses while tracking string state
-    // to avoid counting parentheses inside string literals
-    let (paren_open, paren_close) = count_balanced_with_string_awareness(expr, '(', ')');
-    if paren_open != paren_close {
-        return Err(format!(
-            "Unbalanced parentheses: {} open, {} close",
-            paren_open, paren_close
-        ));
-    }
-
-    // Check for balanced brackets (string-aware)
-    let (bracket_open, bracket_close) = count_balanced_with_string_awareness(expr, '[', ']');
-    if bracket_open != bracket_close {
-        return Err(format!(
-            "Unbalanced brackets: {} open, {} close",
-            bracket_open, bracket_close
-        ));
-    }
-
-    // Check for dangerous patterns that suggest SQL injection or shell commands
-    let dangerous_patterns = [
-        "DROP", "DELETE", "INSERT", "UPDATE", "EXEC", "EXECUTE", "SYSTEM", "BASH", "SH", "CMD.EXE",
-    ];
-
-    for pattern in &dangerous_patterns {
-        if expr.to_uppercase().contains(pattern) {
-            return Err(format!(
-                "Expression contains dangerous keyword: {}",
-                pattern
-            ));
-        }
-    }
-
-    // Check for allowed characters
-    // Allow: alphanumeric, whitespace, operators, quotes, parentheses, brackets, braces, dots, underscores
-    // ELO operators: ~> (lambda), |> (pipe), ?| (alternative), ^ (power)
-    // Temporal: @ (for @date, @datetime, @duration)
-    if !expr.chars().all(|c| {
-        c.is_alphanumeric()
-            || c.is_whitespace()
-            || matches!(
-                c,
-                '.' | '_'
-                    | '@'
-                    | '('
-                    | ')'
-                    | '['
-                    | ']'
-                    | '{'
-                    | '}'
-                    | '='
-                    | '<'
-                    | '>'
-                    | '!'
-                    | '&'
-                    | '|'
-                    | '+'
-                    | '-'
-                    | '*'
-                    | '/'
-                    | '%'
-                    | '^'
-                    | '~'
-                    | '?'
-                    | '"'
-                    | '\''
-                    | ':'
-                    | ','
-                    | ';'
-            )
-    }) {
-        return Err(
-            "Expression contains invalid characters. Only alphanumeric, operators, and quotes allowed."
-                .to_string(),
-        );
-    }
-
-    Ok(())
-}
-
-/// Validates a regex pattern to prevent ReDoS attacks
-///
-/// # Security Checks
-/// - Length limits (max 1,000 characters)
-/// - Detects nested quantifiers that could cause ReDoS
-/// - Validates that regex can be compiled
-/// - Warns about potentially dangerous patterns
-///
-/// # Arguments
-/// * `pattern` - User-provided regex pattern
-///
-/// # Returns
-/// - `Ok(())` if pattern is valid and safe
-/// - `Err(String)` if pattern is dangerous or invalid
-pub fn validate_regex_pattern(pattern: &str) -> Result<(), String> {
-    // Check length limit
-    if pattern.len() > MAX_PATTERN_LENGTH {
-        return Err(format!(
-            "Regex pattern too long (max {} characters)",
-            MAX_PATTERN_LENGTH
-        ));
-    }
-
-    // Try to compile the regex to catch syntax errors
-    match regex::Regex::new(pattern) {
-        Ok(_) => {}
-        Err(e) => {
-            return Err(format!("Invalid regex pattern: {}", e));
-        }
-    }
-
-    // SECURITY FIX #3: Enhanced ReDoS detection
-    // Detect multiple types of patterns that could cause catastrophic backtracking
-
-    // 1. Nested quantifiers: (a+)+, (a*)+, (a{2,3})+, etc.
-    let has_nested_quantifiers = pattern.contains(")+")
-        || pattern.contains(")*")
-        || pattern.contains(")?")
-        || pattern.contains("]{2,}+")
-        || pattern.contains("]{2,}*")
-        || pattern.contains("]{2,}?")
-        || pattern.contains("}{2,}+")
-        || pattern.contains("}{2,}*");
-
-    if has_nested_quantifiers {
-        return Err(
-            "Regex pattern contains nested quantifiers that could cause ReDoS attack".to_string(),
-        );
-    }
-
-    // 2. Check for quantifier chains: a*a*a*, etc.
-    // Look for patterns like: quantifier followed by potentially quantifiable content
-    let quantifier_chain_patterns = [
-        r"\+\s*\+", // + followed by + (with optional space)
-        r"\*\s*\*", // * followed by * (with optional space)
-        r"\+\s*\*", // + followed by *
-        r"\*\s*\+", // * followed by +
-    ];
-
-    for qc_pattern_str in &quantifier_chain_patterns {
-        if let Ok(qc_pattern) = regex::Regex::new(qc_pattern_str) {
-            if qc_pattern.is_match(pattern) {
-                return Err("Regex pattern contains chained quantifiers (ReDoS risk)".to_string());
-            }
-        }
-    }
-
-    // 3. Check for alternation with potentially overlapping branches
-    // Patterns like (a|ab)*, (a|a)*, (foo|foobar)*, etc.
-    if pattern.contains('|') {
-        // If alternation is present with quantifiers, it's high risk
-        if pattern.contains('*') || pattern.contains('+') {
-            // Check if the alternation is inside a quantified group
-            if pattern.contains("(") && pattern.contains(")") {
-                // More detailed check: look for patterns like (X|Y)* where X and Y might overlap
-                if pattern.contains(")*") || pattern.contains(")+") || pattern.contains(")?") {
-                    return Err(
-                        "Regex pattern contains quantified alternation (high ReDoS risk)"
-                            .to_string(),
-                    );
-                }
-            }
-        }
-    }
-
-    // 4. Warn about potentially dangerous patterns
-    if pattern.contains('|') && (pattern.contains('*') || pattern.contains('+')) {
-        eprintln!(
-            "⚠️  Warning: Regex contains alternation with quantifiers (potential ReDoS risk)"
-        );
-    }
-
-    Ok(())
-}
-
-/// **DEPRECATED AND UNSAFE**: Do not use for user input in comments
-///
-/// # ⚠️ SECURITY FIX #2
-///
-/// This function is **FUNDAMENTALLY UNSAFE** and should never be used for user input.
-///
-/// Problem: In Rust block comments `/* ... */`, backslash has NO special meaning.
-/// Therefore, escaping with backslash provides no protection against breakout attacks.
-///
-/// Example attack:
-/// ```ignore
-/// input = r"\*/"
-/// escaped = r"\\*\/"  // Attempted escaping
-/// in_comment: /* \\*\/ */  // The */ STILL ends the comment!
-/// ```
-///
-/// # Migration
-///
-/// Use `quote!` macro for user input instead:
-/// ```ignore
-/// quote! { let comment = #user_input; }  // Properly escaped
-/// ```
-///
-/// Or use line comments with escaped newlines:
-/// ```ignore
-/// let comment = format!("// {}", user_input.replace('\n', "\\n"));
-/// ```
-///
-/// # Reason for Deprecation
-/// Backslash escaping does not work in Rust comments. This function was based on
-/// a false assumption about comment semantics and cannot provide real protection.
-#[deprecated(
-    since = "0.4.0",
-    note = "Do not use - backslash escaping doesn't work in Rust comments. Use quote! macro instead."
-)]
-pub fn sanitize_for_comment(input: &str) -> String {
-    // SECURITY: This implementation is unsafe for user input in comments.
-    // It's kept for backward compatibility only.
-    // DO NOT USE IN NEW CODE.
-    input
-        .replace("\\", "\\\\") // NOTE: This doesn't help in comments!
-        .replace("*/", "*\\/") // NOTE: \/ is just two characters in comments
-        .replace("/*", "/\\*") // NOTE: \* is just two characters in comments
-        .trim()
-        .to_string()
-}
-
-/// Escapes user input for safe inclusion in Rust string literals
-///
-/// # Arguments
-/// * `input` - User input to escape
-///
-/// # Returns
-/// Escaped string safe for inclusion in Rust string literals
-pub fn escape_for_rust_string(input: &str) -> String {
-    input
-        .replace('\\', "\\\\")
-        .replace('"', "\\\"")
-        .replace('\n', "\\n")
-        .replace('\r', "\\r")
-        .replace('\t', "\\t")
-}
-
-/// Reads a file with size limits to prevent memory exhaustion
-///
-/// # Security Checks
-/// - File size limit enforced (max 10MB)
-/// - Prevents reading extremely large files into memory
-/// - Returns error if file exceeds size limit
-///
-/// # Arguments
-/// * `path` - Path to file to read
-///
-/// # Returns
-/// - `Ok(String)` if file is within size limit
-/// - `Err(io::Error)` if file exceeds limit or cannot be read
-pub fn read_file_with_limit(path: &std::path::Path) -> io::Result<String> {
-    use std::fs::File;
-    use std::io::Read;
-
-    let file = File::open(path)?;
-    let metadata = file.metadata()?;
-
-    // Check file size before reading
-    if metadata.len() > MAX_FILE_SIZE {
-        return Err(io::Error::new(
-            io::ErrorKind::InvalidData,
-            format!(
-                "File too large (max {} MB, got {} MB)",
-                MAX_FILE_SIZE / 1_000_000,
-                metadata.len() / 1_000_000
-            ),
-        ));
-    }
-
-    let mut buffer = String::new();
-    file.take(MAX_FILE_SIZE).read_to_string(&mut buffer)?;
-    Ok(buffer)
-}
-
-/// Reads from stdin with size limits to prevent memory exhaustion
-///
-/// # Security Checks
-/// - Input size limit enforced (max 10MB)
-/// - Prevents DoS via infinite stdin stream
-/// - Returns error if input exceeds size limit
-///
-/// # SECURITY FIX #4
-/// Fixed logic error: Only reject if we filled the buffer AND more data is available.
-/// Legitimate input of exactly MAX_FILE_SIZE bytes should be accepted.
-///
-/// # Returns
-/// - `Ok(String)` if input is within size limit
-/// - `Err(io::Error)` if input exceeds limit
-pub fn read_stdin_with_limit() -> io::Result<String> {
-    use std::io::Read;
-
-    let stdin = io::stdin();
-    let mut buffer = String::new();
-
-    // Read with size limit
-    stdin.take(MAX_FILE_SIZE).read_to_string(&mut buffer)?;
-
-    // SECURITY FIX #4: Only error if we actually exceeded the limit
-    // If buffer is exactly at MAX_FILE_SIZE, check if there's MORE data available
-    if buffer.len() as u64 == MAX_FILE_SIZE {
-        // Try to peek at one more byte to see if input continues
-        let mut test = [0u8; 1];
-        match std::io::stdin().read(&mut test) {
-            Ok(1) => {
-                // There's more data available - input exceeds limit
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    format!("Input exceeds {} MB limit", MAX_FILE_SIZE / 1_000_000),
-                ));
-            }
-            _ => {
-                // No more data (Ok(0) or error) - input is exactly at limit, which is OK
-            }
-        }
-    }
-
-    Ok(buffer)
-}
-
-/// Reads from stdin with size limits to prevent memory exhaustion
-/// (Note: Exported above in non-test section)
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    // ============================================================================
-    // PATH VALIDATION TESTS
-    // ============================================================================
-
-    #[test]
-    #[cfg(unix)]
-    fn test_valid_relative_path() {
-        let result = validate_file_path("output.rs");
-        assert!(result.is_ok());
-    }
-
-    #[test]
-    #[cfg(unix)]
-    fn test_valid_nested_path() {
-        let result = validate_file_path("target/debug/generated.rs");
-        assert!(result.is_ok());
-    }
-
-    #[test]
-    #[cfg(unix)]
-    fn test_rejects_absolute_path_unix() {
-        let result = validate_file_path("/etc/passwd");
-        assert!(result.is_err());
-        assert!(result
-            .unwrap_err()
-            .to_string()
-            .contains("Absolute paths are not allowed"));
-    }
-
-    #[test]
-    fn test_rejects_path_traversal() {
-        let result = validate_file_path("../../../etc/passwd");
-        assert!(result.is_err());
-        assert!(result
-            .unwrap_err()
-            .to_string()
-            .contains("Path traversal (..) is not allowed"));
-    }
-
-    #[test]
-    fn test_rejects_single_parent_dir() {
-        let result = validate_file_path("..");
-        assert!(result.is_err());
-    }
-
-    #[test]
-    fn test_rejects_empty_path() {
-        let result = validate_file_path("");
-        assert!(result.is_err());
-    }
-
-    #[test]
-    fn test_rejects_whitespace_only_path() {
-        let result = validate_file_path("   ");
-        assert!(result.is_err());
-    }
-
-    // ============================================================================
-    // EXPRESSION VALIDATION TESTS
-    // ============================================================================
-
-    #[test]
-    fn test_valid_simple_expression() {
-        let result = validate_expression("age >= 18");
-        assert!(result.is_ok());
-    }
-
-    #[test]
-    fn test_valid_complex_expression() {
-        let result = validate_expression("(age >= 18) && (verified == true) || (admin == true)");
-        assert!(result.is_ok());
-    }
-
-    #[test]
-    fn test_rejects_empty_expression() {
-        let result = validate_expression("");
-        assert!(result.is_err());
-    }
-
-    #[test]
-    fn test_rejects_whitespace_only_expression() {
-        let result = validate_expression("   \n\t  ");
-        assert!(result.is_err());
-    }
-
-    #[test]
-    fn test_rejects_expression_exceeding_max_length() {
-        let long_expr = "a".repeat(MAX_EXPRESSION_LENGTH + 1);
-        let result = validate_expression(&long_expr);
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("too long"));
-    }
-
-    #[test]
-    fn test_rejects_unbalanced_parentheses_open() {
-        let result = validate_expression("(age >= 18");
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Unbalanced parentheses"));
-    }
-
-    #[test]
-    fn test_rejects_unbalanced_parentheses_close() {
-        let result = validate_expression("age >= 18)");
-        assert!(result.is_err());
-    }
-
-    #[test]
-    fn test_parens_in_string_not_counted() {
-        // SECURITY FIX #1: Parentheses inside strings should not be counted
-        // This should pass - parens are inside a string
-        let result = validate_expression(r#"name == "balance ( and )""#);
-        assert!(result.is_ok());
-
-        // This should fail - actual unbalanced parens in code
-        let result = validate_expression(r#"(name == "test""#);
-        assert!(result.is_err());
-    }
-
-    #[test]
-    fn test_brackets_in_string_not_counted() {
-        // SECURITY FIX #1: Brackets inside strings should not be counted
-        let result = validate_expression(r#"name == "array[0]""#);
-        assert!(result.is_ok());
-
-        // Actual unbalanced brackets should fail
-        let result = validate_expression(r#"arr[0 == test"#);
-        assert!(result.is_err());
-    }
-
-    #[test]
-    fn test_escaped_quotes_in_string_not_counted() {
-        // SECURITY FIX #1: Test escaped quotes handling
-        // The parser should handle escaped quotes inside strings
-        let result = validate_expression(r#"name == 'test with quote' && valid"#);
-        // Use single quotes since the test expression uses single quotes
-        assert!(result.is_ok());
-    }
-
-    #[test]
-    fn test_rejects_unbalanced_brackets() {
-        let result = validate_expression("arr[0 == 5");
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Unbalanced brackets"));
-    }
-
-    #[test]
-    fn test_rejects_sql_injection_pattern_drop() {
-        let result = validate_expression("drop table users");
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("dangerous keyword"));
-    }
-
-    #[test]
-    fn test_rejects_sql_injection_pattern_delete() {
-        let result = validate_expression("delete from users where id = 1");
-        assert!(result.is_err());
-    }
-
-    #[test]
-    fn test_rejects_shell_command_pattern_bash() {
-        let result = validate_expression("bash -c 'rm -rf /'");
-        assert!(result.is_err());
-    }
-
-    #[test]
-    fn test_rejects_invalid_characters() {
-        let result = validate_expression("age >= 18 && `whoami`");
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("invalid characters"));
-    }
-
-    // ============================================================================
-    // REGEX VALIDATION TESTS
-    // ============================================================================
-
-    #[test]
-    fn test_valid_simple_regex() {
-        let result = validate_regex_pattern("[0-9]+");
-        assert!(result.is_ok());
-    }
-
-    #[test]
-    fn test_valid_email_regex() {
-        let result = validate_regex_pattern(r"[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\.[a-zA-Z]{2,}");
-        assert!(result.is_ok());
-    }
-
-    #[test]
-    fn test_rejects_invalid_regex() {
-        let result = validate_regex_pattern("[0-9");
-        assert!(result.is_err());
-    }
-
-    #[test]
-    fn test_rejects_regex_exceeding_max_length() {
-        let long_pattern = "a".repeat(MAX_PATTERN_LENGTH + 1);
-        let result = validate_regex_pattern(&long_pattern);
-        assert!(result.is_err());
-    }
-
-    #[test]
-    fn test_rejects_nested_quantifiers_plus_plus() {
-        let result = validate_regex_pattern("(a+)+");
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("nested quantifiers"));
-    }
-
-    #[test]
-    fn test_rejects_nested_quantifiers_star_plus() {
-        let result = validate_regex_pattern("(a*)+");
-        assert!(result.is_err());
-    }
-
-    #[test]
-    fn test_rejects_nested_quantifiers_question_star() {
-        let result = validate_regex_pattern("(a?)*");
-        assert!(result.is_err());
-    }
-
-    #[test]
-    fn test_rejects_quantifier_chains() {
-        // SECURITY FIX #3: Chained quantifiers cause ReDoS
-        let result = validate_regex_pattern("a++");
-        assert!(result.is_err());
-
-        let result = validate_regex_pattern("a**");
-        assert!(result.is_err());
-
-        let result = validate_regex_pattern("a+*");
-        assert!(result.is_err());
-    }
-
-    #[test]
-    fn test_rejects_quantified_alternation() {
-        // SECURITY FIX #3: Alternation with quantifiers in groups
-        let result = validate_regex_pattern("(a|b)*");
-        // Note: simple alternation with quantifier is OK, but overlapping is bad
-        // The current check catches patterns like (a|ab)* which is harder to detect
-        // For now, we catch quantified alternation in groups
-        if result.is_err() {
-            // Good - caught as risky
-        } else {
-            // OK - simple alternation may be allowed for now
-        }
-    }
-
-    // ============================================================================
-    // SANITIZATION TESTS
-    // ============================================================================
-
-    #[test]
-    #[allow(deprecated)]
-    fn test_sanitize_comment_escapes_backslash() {
-        // Testing deprecated function - this is intentional
-        let result = sanitize_for_comment("path\\to\\file");
-        assert!(result.contains("\\\\"));
-    }
-
-    #[test]
-    #[allow(deprecated)]
-    fn test_sanitize_comment_prevents_comment_breakout() {
-        // Testing deprecated function - this is intentional
-        // Note: This function is unsafe and is deprecated
-        let result = sanitize_for_comment("test */ malicious");
-        assert!(result.contains("*\\/"));
-    }
-
-    #[test]
-    #[allow(deprecated)]
-    fn test_sanitize_comment_prevents_comment_break_in() {
-        // Testing deprecated function - this is intentional
-        // Note: This function is unsafe and is deprecated
-        let result = sanitize_for_comment("test /* malicious");
-        assert!(result.contains("/\\*"));
-    }
-
-    #[test]
-    fn test_escape_for_rust_string_escapes_quotes() {
-        let result = escape_for_rust_string(r#"test "quoted" value"#);
-        assert!(result.contains("\\\""));
-    }
-
-    #[test]
-    fn test_escape_for_rust_string_escapes_newlines() {
-        let result = escape_for_rust_string("line1\nline2");
-        assert!(result.contains("\\n"));
-    }
-
-    #[test]
-    fn test_escape_for_rust_string_escapes_tabs() {
-        let result = escape_for_rust_string("col1\tcol2");
-        assert!(result.contains("\\t"));
-    }
-
-    // ============================================================================
-    // FILE READING WITH SIZE LIMIT TESTS
-    // ============================================================================
-
-    #[test]
-    fn test_read_small_file_succeeds() {
-        let temp_file = std::env::temp_dir().join("test_small.txt");
-        std::fs::write(&temp_file, "small content").unwrap();
-
-        let result = read_file_with_limit(&temp_file);
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "small content");
-
-        let _ = std::fs::remove_file(&temp_file);
-    }
-
-    #[test]
-    fn test_read_file_exceeding_size_limit_fails() {
-        let temp_file = std::env::temp_dir().join("test_large.txt");
-        // Create file larger than MAX_FILE_SIZE
-        let large_content = "x".repeat((MAX_FILE_SIZE as usize) + 1);
-        std::fs::write(&temp_file, large_content).unwrap();
-
-        let result = read_file_with_limit(&temp_file);
-        assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("too large"));
-
-        let _ = std::fs::remove_file(&temp_file);
-    }
-
-    #[test]
-    fn test_read_nonexistent_file_fails() {
-        let nonexistent = std::env::temp_dir().join("does_not_exist_xyz.txt");
-        let result = read_file_with_limit(&nonexistent);
-        assert!(result.is_err());
-    }
-
-    // ============================================================================
-    // PATH VALIDATION WITH SYMLINK TESTS
-    // ============================================================================
-
-    #[test]
-    fn test_broken_symlink_rejected() {
-        // Create a broken symlink
-        let temp_dir = std::env::temp_dir().join("test_symlink_broken");
-        let _ = std::fs::create_dir_all(&temp_dir);
-
-        let symlink_path = temp_dir.join("broken_symlink");
-        // Remove if exists
-        let _ = std::fs::remove_file(&symlink_path);
-
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs as unix_fs;
-            let _ = unix_fs::symlink("/nonexistent/path", &symlink_path);
-
-            let result = validate_file_path(symlink_path.to_str().unwrap());
-            // Should be rejected (broken symlink can't be canonicalized)
-            assert!(result.is_err() || result.is_ok()); // Depends on current_dir
-
-            let _ = std::fs::remove_file(&symlink_path);
-        }
-
-        let _ = std::fs::remove_dir(&temp_dir);
-    }
-
-    #[test]
-    fn test_unwrap_or_issue_fixed() {
-        // This test verifies that unwrap_or() is NOT used in path validation
-        // The path validation now uses match/Err() to properly reject broken symlinks
-        // instead of silently accepting them via unwrap_or()
-
-        // Compile-time verification: if this test compiles, the fix is in place
-        // We can't directly test this without mocking filesystem behavior,
-        // but the implementation change from unwrap_or() to match/Err() is verified
-        // by code review and the test_broken_symlink_rejected test above
-    }
-
-    // ============================================================================
-    // TOCTOU PREVENTION TESTS
-    // ============================================================================
-
-    #[test]
-    #[cfg(unix)]
-    fn test_path_validation_with_context() {
-        // SECURITY FIX #5: Test path validation with CWD capture
-        let result = validate_file_path_with_context("output.rs");
-        assert!(result.is_ok());
-
-        let (path, cwd) = result.unwrap();
-        assert!(!path.is_absolute());
-
-        // Verify path is still valid
-        let verify_result = verify_path_still_valid(&path, &cwd);
-        assert!(verify_result.is_ok());
-    }
-
-    #[test]
-    #[cfg(unix)]
-    fn test_verify_path_rejects_cwd_change() {
-        // SECURITY FIX #5: Verify function detects CWD changes
-        // We can't actually change CWD in a test, but we can verify the logic works
-        let (path, _original_cwd) = validate_file_path_with_context("output.rs").unwrap();
-
-        // This would fail if we actually changed CWD, but we're just verifying
-        // that the function exists and has the right signature
-        let fake_cwd = std::path::PathBuf::from("/fake/different/path");
-        let verify_result = verify_path_still_valid(&path, &fake_cwd);
-
-        // Should be an error because CWD is different
-        assert!(verify_result.is_err());
-    }
-
-    // ============================================================================
-    // STRING-AWARE BALANCE CHECKING TESTS
-    // ============================================================================
-
-    #[test]
-    fn test_count_balanced_ignores_string_contents() {
-        // SECURITY FIX #1: Parentheses in strings should be ignored
-        let (open, close) = count_balanced_with_string_awareness(
-            r#"message == "Hello (world) and (stuff)""#,
-            '(',
-            ')',
-        );
-        assert_eq!(open, 0);
-        assert_eq!(close, 0);
-    }
-
-    #[test]
-    fn test_count_balanced_with_actual_parens() {
-        // SECURITY FIX #1: Count actual parentheses outside strings
-        let (open, close) = count_balanced_with_string_awareness(r#"(msg == "test")"#, '(', ')');
-        assert_eq!(open, 1);
-        assert_eq!(close, 1);
-    }
-
-    #[test]
-    fn test_count_balanced_escaped_quotes() {
-        // SECURITY FIX #1: Escaped quotes shouldn't end the string
-        let (open, close) =
-            count_balanced_with_string_awareness(r#"(name == "test \" quote")"#, '(', ')');
-        assert_eq!(open, 1);
-        assert_eq!(close, 1);
-    }
-}
@@ -1,7 +1,10 @@
 //! Array and collection functions
 
 /// Array function signatures
-pub const ARRAY_FUNCTIONS: &[&str] = &["contains", "any", "all", "length", "is_empty"];
+pub const ARRAY_FUNCTIONS: &[&str] = &[
+    "contains", "any", "all", "map", "filter", "reduce", "sum", "min_by", "max_by", "length",
+    "is_empty",
+];
 
 #[cfg(test)]
 mod tests {
@@ -9,7 +12,7 @@ mod tests {
 
     #[test]
     fn test_array_functions_count() {
-        assert_eq!(ARRAY_FUNCTIONS.len(), 5);
+        assert_eq!(ARRAY_FUNCTIONS.len(), 11);
     }
 
     #[test]
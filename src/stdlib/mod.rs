@@ -1,12 +1,35 @@
 //! Standard library function implementations
 //!
 //! Defines all supported ELO standard library functions that can be called
-//! from generated validators
+//! from generated validators.
+//!
+//! [`all_functions`] is the canonical table of their signatures. Type
+//! inference reads return types from it, [`complete`] offers names to
+//! editors, and [`markdown_reference`] renders the reference documentation,
+//! so all three stay in sync with the table. Functions accepting several
+//! argument types (`length`, `contains`, `is_empty`) have one entry per
+//! overload.
+//!
+//! # Example
+//!
+//! ```
+//! use elo_rust::stdlib::{self, FunctionCategory};
+//!
+//! let length: Vec<String> = stdlib::lookup("length").map(|f| f.to_string()).collect();
+//! assert_eq!(length, ["length(string) -> integer", "length([any]) -> integer"]);
+//! assert!(stdlib::functions_in(FunctionCategory::DateTime).any(|f| f.name == "days_since"));
+//! assert_eq!(stdlib::complete("starts"), ["starts_with"]);
+//! ```
+
+use std::fmt;
+use std::sync::OnceLock;
 
 pub mod array;
 pub mod datetime;
+pub mod number;
 pub mod string;
 pub mod types;
+pub mod validation;
 
 /// Standard library function metadata
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -19,6 +42,40 @@ pub struct FunctionSignature {
     pub return_type: String,
     /// Function category
     pub category: FunctionCategory,
+    /// Crate version that first shipped the function
+    pub since: String,
+    /// One-line description
+    pub description: String,
+}
+
+impl FunctionSignature {
+    /// Whether the last parameter may be repeated (written `any...`)
+    pub fn is_variadic(&self) -> bool {
+        self.params
+            .last()
+            .is_some_and(|param| param.ends_with("..."))
+    }
+
+    /// Whether a call with `count` arguments matches this signature
+    pub fn accepts_arity(&self, count: usize) -> bool {
+        if self.is_variadic() {
+            count >= self.params.len()
+        } else {
+            count == self.params.len()
+        }
+    }
+}
+
+impl fmt::Display for FunctionSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}({}) -> {}",
+            self.name,
+            self.params.join(", "),
+            self.return_type
+        )
+    }
 }
 
 /// Categories of standard library functions
@@ -32,6 +89,203 @@ pub enum FunctionCategory {
     Array,
     /// Type checking and conversion
     Type,
+    /// Arithmetic on numbers
+    Number,
+    /// Format validators for common identifiers (phone numbers, ...)
+    Validation,
+}
+
+impl FunctionCategory {
+    /// All categories, in documentation order
+    pub const ALL: [FunctionCategory; 6] = [
+        FunctionCategory::String,
+        FunctionCategory::DateTime,
+        FunctionCategory::Array,
+        FunctionCategory::Type,
+        FunctionCategory::Number,
+        FunctionCategory::Validation,
+    ];
+
+    /// Heading used in the reference documentation
+    pub fn title(&self) -> &'static str {
+        match self {
+            FunctionCategory::String => "String functions",
+            FunctionCategory::DateTime => "Date and time functions",
+            FunctionCategory::Array => "Array functions",
+            FunctionCategory::Type => "Type functions",
+            FunctionCategory::Number => "Number functions",
+            FunctionCategory::Validation => "Format validation functions",
+        }
+    }
+}
+
+/// Name, parameters, return type, category, version and description of
+/// every standard library function
+///
+/// Types use the names of [`InferredType`](crate::codegen::type_inference::InferredType);
+/// `fn` is a lambda and a trailing `...` marks a repeatable parameter.
+#[allow(clippy::type_complexity)]
+#[rustfmt::skip]
+const FUNCTIONS: &[(&str, &[&str], &str, FunctionCategory, &str, &str)] = &[
+    ("matches", &["string", "string"], "boolean", FunctionCategory::String, "0.1.0", "Whether the string matches a regular expression"),
+    ("contains", &["string", "string"], "boolean", FunctionCategory::String, "0.1.0", "Whether the string contains a substring"),
+    ("length", &["string"], "integer", FunctionCategory::String, "0.1.0", "Number of characters in the string"),
+    ("uppercase", &["string"], "string", FunctionCategory::String, "0.1.0", "The string in upper case"),
+    ("lowercase", &["string"], "string", FunctionCategory::String, "0.1.0", "The string in lower case"),
+    ("trim", &["string"], "string", FunctionCategory::String, "0.1.0", "The string without leading and trailing whitespace"),
+    ("starts_with", &["string", "string"], "boolean", FunctionCategory::String, "0.1.0", "Whether the string begins with a prefix"),
+    ("ends_with", &["string", "string"], "boolean", FunctionCategory::String, "0.1.0", "Whether the string ends with a suffix"),
+    ("today", &[], "date", FunctionCategory::DateTime, "0.1.0", "The current local date"),
+    ("now", &[], "datetime", FunctionCategory::DateTime, "0.1.0", "The current UTC date and time"),
+    ("age", &["date"], "integer", FunctionCategory::DateTime, "0.1.0", "Whole years elapsed since a birth date"),
+    ("days_since", &["date"], "integer", FunctionCategory::DateTime, "0.1.0", "Days elapsed since a date"),
+    ("duration_days", &["duration"], "integer", FunctionCategory::DateTime, "0.1.0", "Length of a duration in whole days"),
+    ("contains", &["[any]", "any"], "boolean", FunctionCategory::Array, "0.1.0", "Whether the array has an element equal to the value"),
+    ("any", &["[any]", "fn"], "boolean", FunctionCategory::Array, "0.1.0", "Whether the predicate holds for at least one element"),
+    ("all", &["[any]", "fn"], "boolean", FunctionCategory::Array, "0.1.0", "Whether the predicate holds for every element"),
+    ("map", &["[any]", "fn"], "[any]", FunctionCategory::Array, "0.5.0", "The results of the function for every element"),
+    ("filter", &["[any]", "fn"], "[any]", FunctionCategory::Array, "0.5.0", "The elements for which the predicate holds"),
+    ("reduce", &["[any]", "any", "fn"], "any", FunctionCategory::Array, "0.5.0", "Fold the elements into one value, starting from the initial value"),
+    ("sum", &["[number]"], "number", FunctionCategory::Array, "0.5.0", "Sum of the elements, 0 for an empty array"),
+    ("sum", &["[any]", "fn"], "number", FunctionCategory::Array, "0.5.0", "Sum of the function over the elements"),
+    ("min_by", &["[any]", "fn"], "any?", FunctionCategory::Array, "0.5.0", "The element with the smallest key, or null for an empty array"),
+    ("max_by", &["[any]", "fn"], "any?", FunctionCategory::Array, "0.5.0", "The element with the largest key, or null for an empty array"),
+    ("length", &["[any]"], "integer", FunctionCategory::Array, "0.1.0", "Number of elements in the array"),
+    ("is_empty", &["[any]"], "boolean", FunctionCategory::Array, "0.1.0", "Whether the array has no elements"),
+    ("is_null", &["any"], "boolean", FunctionCategory::Type, "0.1.0", "Whether the value is null or missing"),
+    ("is_some", &["any"], "boolean", FunctionCategory::Type, "0.1.0", "Whether the value is present"),
+    ("is_empty", &["string"], "boolean", FunctionCategory::Type, "0.1.0", "Whether the string has no characters"),
+    ("is_string", &["any"], "boolean", FunctionCategory::Type, "0.1.0", "Whether the value is a string"),
+    ("is_number", &["any"], "boolean", FunctionCategory::Type, "0.1.0", "Whether the value is an integer or a float"),
+    ("abs", &["number"], "number", FunctionCategory::Number, "0.5.0", "Absolute value of the number"),
+    ("clamp", &["number", "range"], "number", FunctionCategory::Number, "0.5.0", "The number limited to a range; float ranges must include their end"),
+    ("clamp", &["number", "number", "number"], "number", FunctionCategory::Number, "0.5.0", "The number limited to a lower and an upper bound"),
+    ("is_phone", &["string"], "boolean", FunctionCategory::Validation, "0.5.0", "Whether the string is a plausible E.164 or national phone number"),
+    ("normalize_phone", &["string", "string"], "string?", FunctionCategory::Validation, "0.5.0", "The phone number in E.164 form for a default region, or null"),
+    ("is_iso_country", &["string"], "boolean", FunctionCategory::Validation, "0.5.0", "Whether the string is an ISO 3166-1 alpha-2 country code"),
+    ("is_iso_currency", &["string"], "boolean", FunctionCategory::Validation, "0.5.0", "Whether the string is an ISO 4217 currency code"),
+    ("is_bcp47_lang", &["string"], "boolean", FunctionCategory::Validation, "0.5.0", "Whether the string is a well-formed BCP 47 language tag"),
+    ("password_strength", &["string"], "integer", FunctionCategory::Validation, "0.5.0", "Password strength score from 0 (weak) to 4 (strong)"),
+    ("is_card_number", &["string"], "boolean", FunctionCategory::Validation, "0.5.0", "Whether the string is a payment card number with a valid checksum"),
+    ("card_brand", &["string"], "string", FunctionCategory::Validation, "0.5.0", "Brand of a payment card number"),
+    ("is_email", &["string"], "boolean", FunctionCategory::Validation, "0.5.0", "Whether the string is an email address"),
+    ("is_url", &["string"], "boolean", FunctionCategory::Validation, "0.5.0", "Whether the string is an absolute URL with a host"),
+    ("is_uuid", &["string"], "boolean", FunctionCategory::Validation, "0.5.0", "Whether the string is a hyphenated UUID"),
+    ("is_ipv4", &["string"], "boolean", FunctionCategory::Validation, "0.5.0", "Whether the string is an IPv4 address"),
+    ("is_ipv6", &["string"], "boolean", FunctionCategory::Validation, "0.5.0", "Whether the string is an IPv6 address"),
+    ("has_extension", &["string", "[string]"], "boolean", FunctionCategory::Validation, "0.5.0", "Whether the file name ends with one of the extensions"),
+    ("is_mime", &["string", "[string]"], "boolean", FunctionCategory::Validation, "0.5.0", "Whether the MIME type matches one of the patterns"),
+    ("size_kb", &["integer"], "float", FunctionCategory::Validation, "0.5.0", "A size in bytes expressed in kilobytes"),
+    ("size_mb", &["integer"], "float", FunctionCategory::Validation, "0.5.0", "A size in bytes expressed in megabytes"),
+    ("size_gb", &["integer"], "float", FunctionCategory::Validation, "0.5.0", "A size in bytes expressed in gigabytes"),
+    ("between", &["any", "any", "any"], "boolean", FunctionCategory::Validation, "0.5.0", "Whether the value lies between two bounds, both included"),
+    ("is_present", &["any"], "boolean", FunctionCategory::Validation, "0.5.0", "Whether the value is neither null nor blank"),
+    ("required_if", &["any", "boolean"], "boolean", FunctionCategory::Validation, "0.5.0", "The field must be present when the condition holds"),
+    ("required_unless", &["any", "boolean"], "boolean", FunctionCategory::Validation, "0.5.0", "The field must be present unless the condition holds"),
+    ("requires_with", &["any", "any..."], "boolean", FunctionCategory::Validation, "0.5.0", "The field must be present when any of the others is"),
+];
+
+/// Functions whose result depends on more than their arguments: the clock,
+/// or the `require` messages the presence helpers report
+const IMPURE_FUNCTIONS: &[&str] = &[
+    "today",
+    "now",
+    "age",
+    "days_since",
+    "required_if",
+    "required_unless",
+    "requires_with",
+];
+
+/// Whether a call of the function `name` may be evaluated once and reused
+///
+/// Standard library functions are pure unless they read the clock or report
+/// messages. Functions missing from the table, such as those behind custom
+/// operators, are taken as impure.
+pub fn is_pure(name: &str) -> bool {
+    !IMPURE_FUNCTIONS.contains(&name) && lookup(name).next().is_some()
+}
+
+/// Every standard library function, one entry per overload, grouped by
+/// category
+pub fn all_functions() -> &'static [FunctionSignature] {
+    static ALL: OnceLock<Vec<FunctionSignature>> = OnceLock::new();
+    ALL.get_or_init(|| {
+        FUNCTIONS
+            .iter()
+            .map(
+                |(name, params, return_type, category, since, description)| FunctionSignature {
+                    name: name.to_string(),
+                    params: params.iter().map(|param| param.to_string()).collect(),
+                    return_type: return_type.to_string(),
+                    category: *category,
+                    since: since.to_string(),
+                    description: description.to_string(),
+                },
+            )
+            .collect()
+    })
+}
+
+/// Functions of one category
+pub fn functions_in(
+    category: FunctionCategory,
+) -> impl Iterator<Item = &'static FunctionSignature> {
+    all_functions()
+        .iter()
+        .filter(move |function| function.category == category)
+}
+
+/// All overloads of the function called `name`
+pub fn lookup(name: &str) -> impl Iterator<Item = &'static FunctionSignature> + '_ {
+    all_functions()
+        .iter()
+        .filter(move |function| function.name == name)
+}
+
+/// Functions available in crate version `version` (e.g. "0.4.1")
+pub fn available_in(version: &str) -> impl Iterator<Item = &'static FunctionSignature> + '_ {
+    all_functions()
+        .iter()
+        .filter(move |function| parse_version(&function.since) <= parse_version(version))
+}
+
+/// Sorted, distinct function names starting with `prefix`
+pub fn complete(prefix: &str) -> Vec<&'static str> {
+    let mut names: Vec<&'static str> = all_functions()
+        .iter()
+        .map(|function| function.name.as_str())
+        .filter(|name| name.starts_with(prefix))
+        .collect();
+    names.sort_unstable();
+    names.dedup();
+    names
+}
+
+/// Reference documentation for every function, as Markdown
+pub fn markdown_reference() -> String {
+    let mut doc = String::from("# Standard library\n");
+    for category in FunctionCategory::ALL {
+        doc.push_str(&format!(
+            "\n## {}\n\n| Function | Description | Since |\n|---|---|---|\n",
+            category.title()
+        ));
+        for function in functions_in(category) {
+            doc.push_str(&format!(
+                "| `{}` | {} | {} |\n",
+                function, function.description, function.since
+            ));
+        }
+    }
+    doc
+}
+
+/// Numeric components of a version, for ordering
+fn parse_version(version: &str) -> Vec<u64> {
+    version
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
 }
 
 #[cfg(test)]
@@ -45,8 +299,67 @@ mod tests {
             params: vec!["&str".to_string(), "&str".to_string()],
             return_type: "bool".to_string(),
             category: FunctionCategory::String,
+            since: "0.1.0".to_string(),
+            description: "Whether the string matches a regular expression".to_string(),
         };
         assert_eq!(sig.name, "matches");
         assert_eq!(sig.params.len(), 2);
     }
+
+    #[test]
+    fn test_table_matches_category_lists() {
+        let lists: [(FunctionCategory, &[&str]); 6] = [
+            (FunctionCategory::String, string::STRING_FUNCTIONS),
+            (FunctionCategory::DateTime, datetime::DATETIME_FUNCTIONS),
+            (FunctionCategory::Array, array::ARRAY_FUNCTIONS),
+            (FunctionCategory::Type, types::TYPE_FUNCTIONS),
+            (FunctionCategory::Number, number::NUMBER_FUNCTIONS),
+            (
+                FunctionCategory::Validation,
+                validation::VALIDATION_FUNCTIONS,
+            ),
+        ];
+        for (category, names) in lists {
+            // Overloads in one category share an entry of the list
+            let mut table: Vec<&str> = functions_in(category).map(|f| f.name.as_str()).collect();
+            table.dedup();
+            assert_eq!(table, names, "{:?}", category);
+        }
+    }
+
+    #[test]
+    fn test_lookup_and_arity() {
+        let requires_with = lookup("requires_with").next().unwrap();
+        assert!(requires_with.is_variadic());
+        assert!(requires_with.accepts_arity(3));
+        assert!(!requires_with.accepts_arity(1));
+        assert!(lookup("today").next().unwrap().accepts_arity(0));
+        assert_eq!(lookup("soundex").count(), 0);
+        assert_eq!(
+            complete("is_i"),
+            ["is_ipv4", "is_ipv6", "is_iso_country", "is_iso_currency"]
+        );
+        assert_eq!(available_in("0.4.1").count(), 23);
+        assert_eq!(available_in("0.5.0").count(), all_functions().len());
+    }
+
+    #[test]
+    fn test_is_pure() {
+        assert!(is_pure("length"));
+        assert!(is_pure("trim"));
+        assert!(is_pure("map"));
+        assert!(!is_pure("today"));
+        assert!(!is_pure("age"));
+        assert!(!is_pure("required_if"));
+        assert!(!is_pure("fuzzy_match"));
+    }
+
+    #[test]
+    fn test_markdown_reference() {
+        let doc = markdown_reference();
+        assert!(doc.starts_with("# Standard library\n\n## String functions\n"));
+        assert!(doc.contains(
+            "| `age(date) -> integer` | Whole years elapsed since a birth date | 0.1.0 |"
+        ));
+    }
 }
@@ -0,0 +1,15 @@
+//! Numeric functions
+
+/// Numeric function signatures
+pub const NUMBER_FUNCTIONS: &[&str] = &["abs", "clamp"];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number_functions_exist() {
+        assert!(NUMBER_FUNCTIONS.contains(&"abs"));
+        assert!(NUMBER_FUNCTIONS.contains(&"clamp"));
+    }
+}
@@ -1,4 +1,8 @@
 //! String manipulation functions
+//!
+//! The patterns of `matches` are regular expressions, checked by
+//! [`validate_regex_pattern`](crate::security::validate_regex_pattern) when
+//! validators are generated (see [`crate::runtime::pattern`]).
 
 /// String function signatures
 pub const STRING_FUNCTIONS: &[&str] = &[
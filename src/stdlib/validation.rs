@@ -0,0 +1,53 @@
+//! Format validation functions
+
+/// Validation function signatures
+pub const VALIDATION_FUNCTIONS: &[&str] = &[
+    "is_phone",
+    "normalize_phone",
+    "is_iso_country",
+    "is_iso_currency",
+    "is_bcp47_lang",
+    "password_strength",
+    "is_card_number",
+    "card_brand",
+    "is_email",
+    "is_url",
+    "is_uuid",
+    "is_ipv4",
+    "is_ipv6",
+    "has_extension",
+    "is_mime",
+    "size_kb",
+    "size_mb",
+    "size_gb",
+    "between",
+    "is_present",
+    "required_if",
+    "required_unless",
+    "requires_with",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phone_functions_exist() {
+        assert!(VALIDATION_FUNCTIONS.contains(&"is_phone"));
+        assert!(VALIDATION_FUNCTIONS.contains(&"normalize_phone"));
+    }
+
+    #[test]
+    fn test_code_list_functions_exist() {
+        assert!(VALIDATION_FUNCTIONS.contains(&"is_iso_country"));
+        assert!(VALIDATION_FUNCTIONS.contains(&"is_iso_currency"));
+        assert!(VALIDATION_FUNCTIONS.contains(&"is_bcp47_lang"));
+    }
+
+    #[test]
+    fn test_format_functions_exist() {
+        for name in ["is_email", "is_url", "is_uuid", "is_ipv4", "is_ipv6"] {
+            assert!(VALIDATION_FUNCTIONS.contains(&name), "{}", name);
+        }
+    }
+}
@@ -8,14 +8,19 @@ use std::process::Command;
 
 /// Helper function to get the compiled binary path
 fn get_binary_path() -> PathBuf {
+    get_named_binary_path("elo")
+}
+
+/// Path of the compiled binary `name`
+fn get_named_binary_path(name: &str) -> PathBuf {
     let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
     path.push("target/debug");
 
     #[cfg(windows)]
-    path.push("elo.exe");
+    path.push(format!("{}.exe", name));
 
     #[cfg(not(windows))]
-    path.push("elo");
+    path.push(name);
 
     path
 }
@@ -246,7 +251,7 @@ fn test_validate_from_file() {
 fn test_validate_short_input_flag() {
     let input_file = "test_validate2.elo";
 
-    fs::write(input_file, "email contains at-sign").unwrap();
+    fs::write(input_file, "contains(email, '@')").unwrap();
 
     let output = run_elo(&["validate", "-i", input_file]);
 
@@ -255,6 +260,112 @@ fn test_validate_short_input_flag() {
     let _ = fs::remove_file(input_file);
 }
 
+#[test]
+#[cfg_attr(windows, ignore = "File paths require Unix-like semantics")]
+fn test_validate_parses_expression() {
+    let input_file = "test_validate_parse.elo";
+
+    fs::write(input_file, "updated_at >= TODAY && shipping_cost > 0").unwrap();
+    let output = run_elo(&["validate", "--input", input_file]);
+    assert!(output.status.success());
+
+    fs::write(input_file, "clamp(age, 1..10) > 3 && abs(delta) < 5").unwrap();
+    let output = run_elo(&["validate", "--input", input_file]);
+    assert!(output.status.success());
+
+    fs::write(input_file, "system('reboot') == 0").unwrap();
+    let output = run_elo(&["validate", "--input", input_file]);
+    let _ = fs::remove_file(input_file);
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("unknown function: system"));
+}
+
+#[test]
+#[cfg(feature = "serde-support")]
+#[cfg_attr(windows, ignore = "File paths require Unix-like semantics")]
+fn test_validate_ndjson_records() {
+    let rules_file = "test_validate_records.elo";
+    let data_file = "test_validate_records.ndjson";
+
+    fs::write(rules_file, "rule is_adult: age >= 18").unwrap();
+    fs::write(
+        data_file,
+        "{\"age\": 21}\n{\"age\": 12}\nnot json\n{\"age\": 40}\n",
+    )
+    .unwrap();
+
+    let output = run_elo(&["validate", "--rules", rules_file, "--data", data_file]);
+
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("✓ line 1"));
+    assert!(stdout.contains("✗ line 2: Rule 'is_adult' failed"));
+    assert!(stdout.contains("✗ line 3: invalid JSON"));
+    assert!(stdout.contains("4 records: 2 passed, 1 failed, 1 invalid"));
+
+    let _ = fs::remove_file(rules_file);
+    let _ = fs::remove_file(data_file);
+}
+
+#[test]
+#[cfg(feature = "serde-support")]
+#[cfg_attr(windows, ignore = "File paths require Unix-like semantics")]
+fn test_validate_records_integer_overflow_is_reported() {
+    let rules_file = "test_validate_overflow.elo";
+    let data_file = "test_validate_overflow.ndjson";
+
+    fs::write(rules_file, "rule doubled: amount * 2 > 0").unwrap();
+    fs::write(
+        data_file,
+        "{\"amount\": 9223372036854775807}\n{\"amount\": 4}\n",
+    )
+    .unwrap();
+
+    let output = run_elo(&[
+        "validate", "--rules", rules_file, "--data", data_file, "--format", "ndjson",
+    ]);
+    let _ = fs::remove_file(rules_file);
+    let _ = fs::remove_file(data_file);
+
+    // Reported as a failed record instead of a panic (exit code 101)
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Integer overflow"), "{}", stdout);
+    assert!(stdout.contains("✓ line 2"));
+}
+
+#[test]
+#[cfg_attr(windows, ignore = "File paths require Unix-like semantics")]
+fn test_validate_csv_records_from_stdin() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let rules_file = "test_validate_records_csv.elo";
+    fs::write(rules_file, "rule is_adult: age >= 18").unwrap();
+
+    let mut child = Command::new(get_binary_path())
+        .args(["validate", "--rules", rules_file, "--format", "csv"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Failed to execute elo command");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"name,age\nAda,36\nBob,19\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("2 records: 2 passed, 0 failed, 0 invalid"));
+
+    let _ = fs::remove_file(rules_file);
+}
+
 #[test]
 fn test_validate_help() {
     let output = run_elo(&["validate", "--help"]);
@@ -306,6 +417,28 @@ fn test_compile_string_functions() {
     assert!(stdout.contains("pub fn validate"));
 }
 
+#[test]
+fn test_compile_backtick_identifiers() {
+    let output = run_elo(&["compile", "--expression", "`in` > 0"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("input . r#in > 0i64"));
+}
+
+#[test]
+fn test_compile_binds_placeholders() {
+    let expr = "amount <= ${max_amount}";
+    let output = run_elo(&["compile", "--expression", expr, "--param", "max_amount=500"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("input . amount <= 500i64"));
+
+    let unbound = run_elo(&["compile", "--expression", expr]);
+    let stderr = String::from_utf8_lossy(&unbound.stderr);
+    assert!(stderr.contains("Unbound parameters: ${max_amount}"));
+    assert!(!stderr.contains("invalid characters"));
+}
+
 #[test]
 fn test_compile_array_functions() {
     let expr = "roles.contains(admin) || permissions.any(elevated)";
@@ -314,3 +447,472 @@ fn test_compile_array_functions() {
     let stdout = String::from_utf8_lossy(&output.stdout);
     assert!(stdout.contains("pub fn validate"));
 }
+
+// ============================================================================
+// LINT COMMAND
+// ============================================================================
+
+#[test]
+fn test_compile_deny_panics() {
+    let output = run_elo(&[
+        "compile",
+        "--expression",
+        "guard age > 0 in age < 150",
+        "--deny-panics",
+    ]);
+    // A failed guard is reported as a validation error, so nothing can panic
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success());
+    assert!(!stderr.contains("Generated code may panic"));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("guard failed: age > 0"));
+}
+
+#[test]
+#[cfg_attr(windows, ignore = "File paths require Unix-like semantics")]
+fn test_lint_reads_config_file() {
+    let config_file = "test_lint_config.toml";
+    fs::write(
+        config_file,
+        "[lint.levels]\nfloat_equality = \"deny\"\nbool_comparison = \"allow\"\n",
+    )
+    .unwrap();
+
+    let output = run_elo(&[
+        "lint",
+        "--config",
+        config_file,
+        "--expression",
+        "price == 0.1 && verified == true",
+    ]);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("error[float_equality]"));
+    assert!(!stdout.contains("bool_comparison"));
+
+    // Flags override the file
+    let output = run_elo(&[
+        "lint",
+        "--config",
+        config_file,
+        "--warn",
+        "float_equality",
+        "--expression",
+        "price == 0.1",
+    ]);
+    assert!(output.status.success());
+
+    let _ = fs::remove_file(config_file);
+}
+
+#[test]
+#[cfg_attr(windows, ignore = "File paths require Unix-like semantics")]
+fn test_invalid_config_file_fails() {
+    let config_file = "test_invalid_config.toml";
+    fs::write(config_file, "[codegen]\ndeny_panic = true\n").unwrap();
+
+    let output = run_elo(&[
+        "compile",
+        "--config",
+        config_file,
+        "--expression",
+        "age >= 18",
+    ]);
+    let _ = fs::remove_file(config_file);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains(
+        "test_invalid_config.toml line 2: unknown key 'deny_panic' in [codegen] (did you mean 'deny_panics'?)"
+    ));
+}
+
+#[test]
+fn test_stats_reports_complexity() {
+    let output = run_elo(&["stats", "--expression", "matches(email, '@') && age >= 18"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("nodes:          7"));
+    assert!(stdout.contains("function calls: 1"));
+    assert!(stdout.contains("regexes:        1"));
+    assert!(stdout.contains("estimated cost:"));
+}
+
+#[test]
+fn test_stats_warns_about_nested_lambdas() {
+    let expr =
+        "all(a, fn(x ~> all(x, fn(y ~> all(y, fn(z ~> all(z, fn(w ~> all(w, fn(v ~> v))))))))))";
+    let output = run_elo(&["stats", "--expression", expr]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("lambda depth:   5"));
+    assert!(stdout.contains("⚠ Expression too complex (lambda nesting 5, max 4)"));
+}
+
+#[test]
+#[cfg_attr(windows, ignore = "File paths require Unix-like semantics")]
+fn test_stats_rule_code_sizes() {
+    let rules_file = "test_stats_rules.elo";
+    fs::write(
+        rules_file,
+        "rule is_adult: age >= 18\nrule named: length(name) > 0\n",
+    )
+    .unwrap();
+    let output = run_elo(&["stats", "--rules", rules_file]);
+    let _ = fs::remove_file(rules_file);
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert!(lines[0].starts_with("rule ") && lines[0].contains("tokens  functions"));
+    assert!(lines[1].starts_with("is_adult"));
+    assert!(lines[2].starts_with("named"));
+    assert!(lines[3].starts_with("total"));
+}
+
+#[test]
+fn test_stats_invalid_expression_fails() {
+    let output = run_elo(&["stats", "--expression", "age >="]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_table_converts_csv() {
+    let input_file = "test_table.csv";
+    fs::write(
+        input_file,
+        "age,member,discount\n< 18,-,0\n>= 18,true,0.1\n>= 18,false,0.05\n",
+    )
+    .unwrap();
+
+    let output = run_elo(&["table", "--input", input_file]);
+    let _ = fs::remove_file(input_file);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        stdout.trim(),
+        "if age < 18 then 0 else if age >= 18 && member == true then 0.1 else 0.05"
+    );
+}
+
+#[test]
+fn test_table_gap_fails() {
+    let input_file = "test_table_gap.md";
+    fs::write(
+        input_file,
+        "| age | discount |\n|---|---|\n| < 18 | 0 |\n| > 18 | 0.1 |\n",
+    )
+    .unwrap();
+
+    let output = run_elo(&["table", "--input", input_file]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("no row matches age = 18"));
+
+    let output = run_elo(&["table", "--input", input_file, "--default", "0"]);
+    let _ = fs::remove_file(input_file);
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_lint_reports_warnings() {
+    let output = run_elo(&["lint", "--expression", "verified == true && age >= 18"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("warning[bool_comparison]"));
+}
+
+#[test]
+fn test_lint_clean_expression() {
+    let output = run_elo(&["lint", "--expression", "age >= 18"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("No lints found"));
+}
+
+#[test]
+fn test_lint_deny_fails() {
+    let output = run_elo(&[
+        "lint",
+        "--expression",
+        "price == 0.1",
+        "--deny",
+        "float_equality",
+    ]);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("error[float_equality]"));
+}
+
+#[test]
+fn test_lint_allow_silences() {
+    let output = run_elo(&[
+        "lint",
+        "--expression",
+        "(age) > 1",
+        "--allow",
+        "redundant_parens",
+    ]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("redundant_parens"));
+}
+
+#[test]
+fn test_lint_json_format() {
+    let output = run_elo(&[
+        "lint",
+        "--expression",
+        "verified == true",
+        "--format",
+        "json",
+    ]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with(r#"{"version":1,"diagnostics":[{"code":"bool_comparison","severity":"warning","file":"<expression>""#));
+    assert!(stdout.contains(r#""span":{"line":1,"column":1,"start":0,"end":16}"#));
+}
+
+#[test]
+fn test_parse_errors_are_shown_readably() {
+    let output = run_elo(&["lint", "--expression", "a & b"]);
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Unexpected '&'\n  |\n1 | a & b\n  |   ^\n  = help: did you mean '&&'?\n")
+    );
+    assert!(stderr.ends_with("Error: Invalid ELO expression\n"));
+    assert!(!stderr.contains("Custom {"));
+}
+
+#[test]
+fn test_lint_sarif_reports_parse_errors() {
+    let output = run_elo(&["lint", "--expression", "a & b", "--format", "sarif"]);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(r#""version":"2.1.0""#));
+    assert!(stdout.contains(r#""ruleId":"parse_error","level":"error""#));
+    assert!(stdout
+        .contains(r#""region":{"startLine":1,"startColumn":3,"byteOffset":2,"byteLength":1}"#));
+}
+
+#[test]
+fn test_lint_fix_expression() {
+    let output = run_elo(&["lint", "--expression", "(age = 18)", "--fix"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("age == 18\n"));
+    assert!(stdout.contains("No lints found"));
+}
+
+#[test]
+#[cfg_attr(windows, ignore = "File paths require Unix-like semantics")]
+fn test_lint_fix_rewrites_file() {
+    let input_file = "test_lint_fix.elo";
+    fs::write(input_file, "if (verified) then age = 18\n").unwrap();
+
+    let output = run_elo(&["lint", "--input", input_file, "--fix"]);
+    let fixed = fs::read_to_string(input_file).unwrap();
+    let _ = fs::remove_file(input_file);
+
+    assert!(output.status.success());
+    assert_eq!(fixed, "if verified then age == 18 else false\n");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Applied 3 fix(es) to test_lint_fix.elo"));
+}
+
+#[test]
+fn test_lint_fix_leaves_non_boolean_if_alone() {
+    let output = run_elo(&["lint", "--expression", "if a then 1", "--fix"]);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("if a then 1\n"));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Applied 0 fix(es)"));
+    assert!(stderr.contains("Expected else"));
+}
+
+// ============================================================================
+// MIGRATE COMMAND
+// ============================================================================
+
+#[test]
+fn test_migrate_preserves_results() {
+    let output = run_elo(&["migrate", "--expression", "a / b ^ 2 > +c"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.trim(), "a / (b * 2) > -c");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("line 1, column 7: '^' compiled to '*'"));
+}
+
+#[test]
+fn test_migrate_adopt_fixes() {
+    let output = run_elo(&["migrate", "--expression", "+x > 0", "--adopt-fixes"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.trim(), "x > 0");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("result changes sign"));
+}
+
+// ============================================================================
+// CHECK COMMAND
+// ============================================================================
+
+#[test]
+fn test_check_reports_examples() {
+    let output = run_elo(&["check", "--expression", "age >= 18"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("passes for: age = 18"));
+    assert!(stdout.contains("fails for: age = 17"));
+}
+
+#[test]
+fn test_check_unsatisfiable_fails() {
+    let output = run_elo(&["check", "--expression", "age >= 18 && age < 16"]);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("no input passes this expression"));
+}
+
+// ============================================================================
+// ELO-RUSTC
+// ============================================================================
+
+/// Run `elo-rustc`, feeding `stdin` to it
+fn run_elo_rustc(args: &[&str], stdin: &str) -> std::process::Output {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let mut child = Command::new(get_named_binary_path("elo-rustc"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to execute elo-rustc");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn test_elo_rustc_compiles_stdin() {
+    let output = run_elo_rustc(&["--name", "is_adult", "--type", "User"], "age >= 18\n");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("// Generated by elo-rustc"));
+    assert!(stdout.contains(
+        "pub fn is_adult (input : & User) -> Result < () , elo_rust :: runtime :: ValidationErrors >"
+    ));
+}
+
+#[test]
+fn test_elo_rustc_compiles_backtick_identifiers() {
+    let output = run_elo_rustc(&[], "`in` > 0\n");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("input . r#in > 0i64"));
+}
+
+#[test]
+fn test_elo_rustc_binds_placeholders() {
+    let output = run_elo_rustc(&["-p", "max_amount=500"], "amount <= ${max_amount}\n");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("input . amount <= 500i64"));
+
+    let bad = run_elo_rustc(&["--param", "max_amount"], "amount <= ${max_amount}\n");
+    assert_eq!(bad.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&bad.stderr).contains("expected name=value"));
+}
+
+#[test]
+#[cfg_attr(windows, ignore = "File paths require Unix-like semantics")]
+fn test_elo_rustc_writes_rs_file_next_to_input() {
+    let input = "test_elo_rustc_input.elo";
+    let output_file = "test_elo_rustc_input.rs";
+    fs::write(input, "require age >= 18 else 'adult'").unwrap();
+
+    let output = run_elo_rustc(&[input, "--base-path", "user"], "");
+    assert!(output.status.success());
+    let contents = fs::read_to_string(output_file).unwrap();
+    assert!(contents.contains("pub fn validate"));
+    assert!(contents.contains("\"user.age\""));
+
+    let _ = fs::remove_file(input);
+    let _ = fs::remove_file(output_file);
+}
+
+#[test]
+#[cfg_attr(windows, ignore = "File paths require Unix-like semantics")]
+fn test_elo_rustc_writes_into_out_dir() {
+    let out_dir = std::env::temp_dir().join("elo_rustc_out_dir");
+    fs::create_dir_all(&out_dir).unwrap();
+    let out_dir_arg = out_dir.to_string_lossy().into_owned();
+    let input = "test_elo_rustc_out_dir.elo";
+    fs::write(input, "age >= 18").unwrap();
+
+    let output = run_elo_rustc(&[input, "--out-dir", &out_dir_arg], "");
+    let escaped = run_elo_rustc(&["--out-dir", &out_dir_arg, "-o", "../x.rs"], "age >= 18");
+    let _ = fs::remove_file(input);
+
+    assert!(output.status.success());
+    let contents = fs::read_to_string(out_dir.join("test_elo_rustc_out_dir.rs")).unwrap();
+    assert!(contents.contains("pub fn validate"));
+    assert_eq!(escaped.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&escaped.stderr).contains("Path traversal"));
+
+    let _ = fs::remove_dir_all(&out_dir);
+}
+
+#[test]
+#[cfg_attr(windows, ignore = "File paths require Unix-like semantics")]
+fn test_elo_rustc_failures_exit_non_zero() {
+    let output_file = "test_elo_rustc_invalid.rs";
+    let output = run_elo_rustc(&["-o", output_file], "age >=");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("error: Parse error"));
+    assert!(!PathBuf::from(output_file).exists());
+
+    let output = run_elo_rustc(&["-o", "/tmp/elo_rustc_out.rs"], "age >= 18");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Absolute paths are not allowed"));
+
+    let output = run_elo_rustc(&["--frobnicate"], "");
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+#[cfg_attr(windows, ignore = "File paths require Unix-like semantics")]
+fn test_elo_rustc_compiles_schema() {
+    let schema = "test_elo_rustc_schema.toml";
+    let output_file = "test_elo_rustc_schema.rs";
+    fs::write(
+        schema,
+        "type = \"Order\"\n\n[rules]\npositive = \"total > 0\"\nhas_items = \"require items > 0 else 'empty order'\"\n",
+    )
+    .unwrap();
+
+    let output = run_elo_rustc(&[schema], "");
+    assert!(output.status.success());
+    let contents = fs::read_to_string(output_file).unwrap();
+    assert!(contents.contains("mod order_rules"));
+    assert!(contents.contains("pub fn positive (input : & Order)"));
+    assert!(contents.contains("pub fn has_items (input : & Order)"));
+    assert!(contents.contains("impl elo_rust :: runtime :: Validate for Order"));
+
+    fs::write(schema, "type = \"Order\"\n[rules]\nbroken = \"total >\"\n").unwrap();
+    let output = run_elo_rustc(&[schema, "-o", "test_elo_rustc_broken.rs"], "");
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.starts_with("error: test_elo_rustc_schema.toml line 3: rule 'broken'"));
+    assert!(!PathBuf::from("test_elo_rustc_broken.rs").exists());
+
+    let _ = fs::remove_file(schema);
+    let _ = fs::remove_file(output_file);
+}
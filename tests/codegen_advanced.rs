@@ -3,7 +3,7 @@
 //! Tests verify that generated code for advanced expressions is valid Rust
 
 use elo_rust::ast::visitor::Visitor;
-use elo_rust::codegen::ast_to_code::CodegenVisitor;
+use elo_rust::codegen::ast_to_code::{CodegenVisitor, GuardPolicy};
 use elo_rust::codegen::RustCodeGenerator;
 use elo_rust::parser::Parser;
 
@@ -61,8 +61,14 @@ fn test_codegen_guard_expression() {
     let tokens = visitor.visit_expr(&expr);
     let code = tokens.to_string();
 
+    // A failed guard yields the default value instead of panicking
     assert!(code.contains("if"));
-    assert!(code.contains("panic"));
+    assert!(!code.contains("panic"));
+    assert!(code.contains(":: core :: default :: Default :: default ()"));
+
+    let mut visitor = CodegenVisitor::new().with_guard_policy(GuardPolicy::Panic);
+    let code = visitor.visit_expr(&expr).to_string();
+    assert!(code.contains("panic ! (\"{}\" , \"guard failed: x > 0\")"));
 }
 
 #[test]
@@ -78,6 +84,17 @@ fn test_codegen_pipe_expression() {
     assert!(code.len() > 5);
 }
 
+#[test]
+fn test_codegen_pipe_into_lambda() {
+    let expr = Parser::parse("(price |> fn(p ~> p * 2)) > 10").expect("Parse failed");
+    let code = CodegenVisitor::new().visit_expr(&expr).to_string();
+    assert_eq!(code, "{ let p = price ; p * 2i64 } > 10i64");
+
+    let expr = Parser::parse("name |> (trim |> fn(n ~> n == 'ada'))").expect("Parse failed");
+    let code = CodegenVisitor::new().visit_expr(&expr).to_string();
+    assert_eq!(code, "{ let n = name . trim () ; n == \"ada\" }");
+}
+
 #[test]
 fn test_codegen_array_literal() {
     let expr = Parser::parse("[1, 2, 3]").expect("Parse failed");
@@ -95,7 +112,7 @@ fn test_codegen_object_literal() {
     let tokens = visitor.visit_expr(&expr);
     let code = tokens.to_string();
 
-    assert!(code.contains("vec"));
+    assert!(code.contains("BTreeMap :: from"));
 }
 
 #[test]
@@ -149,7 +166,7 @@ fn test_codegen_object_with_expressions() {
     let tokens = visitor.visit_expr(&expr);
     let code = tokens.to_string();
 
-    assert!(code.contains("vec"));
+    assert!(code.contains("BTreeMap :: from"));
 }
 
 #[test]
@@ -190,7 +207,7 @@ fn test_codegen_empty_object() {
     let tokens = visitor.visit_expr(&expr);
     let code = tokens.to_string();
 
-    assert!(code.contains("vec"));
+    assert!(code.contains("BTreeMap :: from"));
 }
 
 #[test]
@@ -244,3 +261,805 @@ fn test_codegen_all_advanced_features() {
         );
     }
 }
+
+#[test]
+fn test_codegen_require_collects_message() {
+    let generator = RustCodeGenerator::new();
+    let code = generator
+        .generate_validator("validate", "require age >= 18 else 'Must be an adult'", "T")
+        .unwrap()
+        .to_string();
+    assert!(code.contains("ValidationError :: new (\"age\" , \"Must be an adult\" , \"validate\")"));
+    assert!(code.contains("Inspect (& input . age)"));
+    assert!(code.contains("Err (__elo_errors)"));
+    assert!(!code.contains("panic"));
+    assert!(!code.contains("validate_with_warnings"));
+}
+
+#[test]
+fn test_codegen_guard_reports_failure() {
+    let generator = RustCodeGenerator::new();
+    let code = generator
+        .generate_validator("validate", "guard age > 0 in age < 150", "T")
+        .unwrap()
+        .to_string();
+    assert!(code
+        .contains("ValidationError :: new (\"age\" , \"guard failed: age > 0\" , \"validate\")"));
+    assert!(code.contains("__elo_errors . push"));
+    assert!(code.contains("Err (__elo_errors)"));
+    assert!(!code.contains("panic"));
+
+    let code = generator
+        .generate_sink_validator("validate", "guard age > 0 in age < 150", "T")
+        .unwrap()
+        .to_string();
+    assert!(code.contains("let mut __elo_failed = false"));
+    assert!(code.contains("Failure :: error (\"validate\" , \"guard failed: age > 0\")"));
+}
+
+#[test]
+fn test_codegen_warn_if_generates_warning_variant() {
+    let generator = RustCodeGenerator::new();
+    let code = generator
+        .generate_validator(
+            "validate",
+            "(require age >= 18 else 'Too young') && (warn_if age > 120 : 'Suspicious age')",
+            "T",
+        )
+        .unwrap()
+        .to_string();
+    assert!(code.contains("pub fn validate_with_warnings"));
+    assert!(
+        code.contains("ValidationError :: warning (\"age\" , \"Suspicious age\" , \"validate\")")
+    );
+    assert!(code.contains("validate_with_warnings (input) . 0"));
+}
+
+#[test]
+fn test_codegen_require_without_diagnostics_is_condition() {
+    let expr = Parser::parse("require x > 0 else 'positive'").unwrap();
+    let mut visitor = CodegenVisitor::new();
+    let code = visitor.visit_expr(&expr).to_string();
+    assert_eq!(code, "x > 0i64");
+    assert!(visitor.uses_require());
+}
+
+#[test]
+fn test_codegen_range_membership() {
+    let expr = Parser::parse("age in 18..=65").unwrap();
+    let code = CodegenVisitor::new().visit_expr(&expr).to_string();
+    assert_eq!(code, "(18i64 ..= 65i64) . contains (& (age))");
+
+    let expr = Parser::parse("score in 0..100").unwrap();
+    let code = CodegenVisitor::new().visit_expr(&expr).to_string();
+    assert!(code.contains("(0i64 .. 100i64)"));
+}
+
+#[test]
+fn test_codegen_between() {
+    let expr = Parser::parse("between(age, 18, 65)").unwrap();
+    let code = CodegenVisitor::new().visit_expr(&expr).to_string();
+    assert_eq!(code, "(18i64 ..= 65i64) . contains (& (age))");
+
+    let expr = Parser::parse("between(price, min_price, max_price * 2)").unwrap();
+    let code = CodegenVisitor::new().visit_expr(&expr).to_string();
+    assert_eq!(
+        code,
+        "(min_price ..= max_price * 2i64) . contains (& (price))"
+    );
+}
+
+#[test]
+fn test_codegen_collection_functions_bind_element_types() {
+    use elo_rust::codegen::types::{RustType, TypeContext, TypeInfo};
+
+    let mut order = TypeInfo::new("Order");
+    order.add_field("prices", RustType::Array(Box::new(RustType::Float)));
+    let mut context = TypeContext::new();
+    context.register_type("Order", order);
+    let code = RustCodeGenerator::with_context(context)
+        .generate_validator(
+            "validate",
+            "sum(map(prices, fn(p ~> p ^ 2))) < reduce(prices, 0.0, fn(acc, p ~> acc + p))",
+            "Order",
+        )
+        .unwrap()
+        .to_string();
+    // `p` is a float, so the power is a float power
+    assert!(
+        code.contains("map (| p | { elo_rust :: runtime :: value :: strict_float_power (p , (2i64) as f64) })"),
+        "{}",
+        code
+    );
+    assert!(
+        code.contains(". fold (0f64 , | acc , p | acc + p)"),
+        "{}",
+        code
+    );
+}
+
+#[test]
+fn test_codegen_clamp_to_range() {
+    let expr = Parser::parse("clamp(qty, 1..=10)").unwrap();
+    let code = CodegenVisitor::new().visit_expr(&expr).to_string();
+    assert_eq!(code, "(qty) . clamp (1i64 , 10i64)");
+
+    let expr = Parser::parse("clamp(qty, 0..10)").unwrap();
+    let code = CodegenVisitor::new().visit_expr(&expr).to_string();
+    assert_eq!(code, "(qty) . clamp (0i64 , 10i64 - 1)");
+}
+
+#[test]
+fn test_codegen_quantifier_over_range() {
+    let expr = Parser::parse("any i in 1..n satisfies i * i == n").unwrap();
+    let code = CodegenVisitor::new().visit_expr(&expr).to_string();
+    assert!(code.starts_with("(1i64 .. n) . any (| i |"));
+}
+
+#[test]
+fn test_codegen_null_default() {
+    let expr = Parser::parse("amount <= ctx.limits.daily_max ?? 1000").unwrap();
+    let code = CodegenVisitor::new().visit_expr(&expr).to_string();
+    assert_eq!(
+        code,
+        "amount <= ctx . limits . daily_max . unwrap_or (1000i64)"
+    );
+
+    let expr = Parser::parse("ctx.limit ?? base * 2").unwrap();
+    let code = CodegenVisitor::new().visit_expr(&expr).to_string();
+    assert!(code.contains("unwrap_or_else (|| base * 2i64)"));
+}
+
+#[test]
+fn test_codegen_like_simple_patterns() {
+    let cases = [
+        (
+            "email like '*@example.com'",
+            "email . ends_with (\"@example.com\")",
+        ),
+        ("code like 'INV-*'", "code . starts_with (\"INV-\")"),
+        ("name like '*admin*'", "name . contains (\"admin\")"),
+        ("status like 'active'", "status == \"active\""),
+        ("anything like '*'", "true"),
+    ];
+    for (source, expected) in cases {
+        let expr = Parser::parse(source).unwrap();
+        let code = CodegenVisitor::new().visit_expr(&expr).to_string();
+        assert_eq!(code, expected, "source: {}", source);
+    }
+}
+
+#[test]
+fn test_codegen_like_prefix_and_suffix() {
+    let expr = Parser::parse("file like 'report*.pdf'").unwrap();
+    let code = CodegenVisitor::new().visit_expr(&expr).to_string();
+    assert!(code.contains("file . len () >= 10usize"));
+    assert!(code.contains("starts_with (\"report\")"));
+    assert!(code.contains("ends_with (\".pdf\")"));
+}
+
+#[test]
+fn test_codegen_like_falls_back_to_matcher() {
+    let expr = Parser::parse("sku like 'A-??-*-X'").unwrap();
+    let code = CodegenVisitor::new().visit_expr(&expr).to_string();
+    assert!(code.contains("elo_rust :: runtime :: glob :: glob_match (& sku , \"A-??-*-X\")"));
+
+    let expr = Parser::parse("name like pattern").unwrap();
+    let code = CodegenVisitor::new().visit_expr(&expr).to_string();
+    assert!(code.contains("glob_match (& name , & pattern)"));
+}
+
+#[test]
+fn test_codegen_password_strength_comparison() {
+    let expr = Parser::parse("password_strength(password) >= 3").unwrap();
+    let code = CodegenVisitor::new().visit_expr(&expr).to_string();
+    assert!(code.contains("elo_rust :: runtime :: password :: password_strength (& password)"));
+    assert!(code.contains(">= 3i64"));
+}
+
+#[test]
+fn test_codegen_card_brand_comparison() {
+    let expr = Parser::parse("is_card_number(card) && card_brand(card) == 'visa'").unwrap();
+    let code = CodegenVisitor::new().visit_expr(&expr).to_string();
+    assert!(code.contains("elo_rust :: runtime :: card :: is_card_number (& card)"));
+    assert!(code.contains("elo_rust :: runtime :: card :: card_brand (& card)"));
+}
+
+#[test]
+fn test_codegen_format_checks() {
+    let expr = Parser::parse("is_email(email) && (is_ipv4(host) || is_ipv6(host))").unwrap();
+    let code = CodegenVisitor::new().visit_expr(&expr).to_string();
+    assert!(code.contains("elo_rust :: runtime :: format :: is_email (& email)"));
+    assert!(code.contains("elo_rust :: runtime :: format :: is_ipv4 (& host)"));
+    assert!(code.contains("elo_rust :: runtime :: format :: is_ipv6 (& host)"));
+}
+
+#[test]
+fn test_codegen_upload_rule() {
+    let expr = Parser::parse(
+        "has_extension(name, ['png', 'jpg']) && is_mime(content_type, ['image/*']) && size_mb(size) <= 5",
+    )
+    .unwrap();
+    let code = CodegenVisitor::new().visit_expr(&expr).to_string();
+    assert!(code.contains(
+        "elo_rust :: runtime :: file :: has_extension (& name , & vec ! [\"png\" , \"jpg\"])"
+    ));
+    assert!(code.contains(
+        "elo_rust :: runtime :: file :: is_mime (& content_type , & vec ! [\"image/*\"])"
+    ));
+    assert!(code.contains("elo_rust :: runtime :: file :: size_mb ((size) as f64)"));
+}
+
+#[test]
+fn test_generate_validator_required_if_message() {
+    let generator = RustCodeGenerator::new();
+    let code = generator
+        .generate_validator(
+            "validate_person",
+            "required_if(input.spouse_name, input.marital_status == 'married')",
+            "Person",
+        )
+        .unwrap()
+        .to_string();
+    assert!(code.contains("__elo_errors"));
+    assert!(code.contains("elo_rust :: runtime :: presence :: is_present (& input . spouse_name)"));
+    assert!(
+        code.contains("\"input.spouse_name is required when input.marital_status == 'married'\"")
+    );
+    // The condition is negated as a whole
+    assert!(code.contains(
+        "! (input . marital_status == \"married\") || elo_rust :: runtime :: presence :: is_present"
+    ));
+}
+
+#[test]
+fn test_generate_validator_custom_operator() {
+    use elo_rust::parser::operators::{CustomOperator, OperatorTable, Precedence};
+
+    let mut operators = OperatorTable::new();
+    operators
+        .register(CustomOperator::new("~=", "bank::fuzzy_match", Precedence::Equality).unwrap())
+        .unwrap();
+    operators
+        .register(
+            CustomOperator::new("=~", "ci_eq", Precedence::Equality)
+                .unwrap()
+                .with_codegen(|l, r| quote::quote!(#l.eq_ignore_ascii_case(&#r))),
+        )
+        .unwrap();
+
+    let generator = RustCodeGenerator::new().with_operators(operators);
+    let code = generator
+        .generate_validator(
+            "validate_payee",
+            "input.name ~= input.account_name && input.country =~ 'gb'",
+            "Payee",
+        )
+        .unwrap()
+        .to_string();
+    assert!(code.contains("bank :: fuzzy_match (& input . name , & input . account_name)"));
+    assert!(code.contains("input . country . eq_ignore_ascii_case (& \"gb\")"));
+    assert!(RustCodeGenerator::new()
+        .generate_validator("v", "input.name ~= input.account_name", "Payee")
+        .is_err());
+}
+
+#[test]
+fn test_codegen_escaped_keyword_fields() {
+    let expr = Parser::parse("`in` > 0 && order.`type` == 'gift' && `then`").expect("Parse failed");
+    let mut visitor = CodegenVisitor::new();
+    let code = visitor.visit_expr(&expr).to_string();
+
+    assert!(code.contains("r#in"));
+    assert!(code.contains("order . r#type"));
+    // `then` is a keyword in ELO only
+    assert!(code.contains("then"));
+    assert!(!code.contains("r#then"));
+}
+
+#[test]
+fn test_generate_validator_deny_panics() {
+    use elo_rust::codegen::audit::PanicPolicy;
+
+    let gen = RustCodeGenerator::new().with_panic_policy(PanicPolicy::Deny);
+    assert!(gen
+        .generate_validator("check_age", "input.age >= 18", "User")
+        .is_ok());
+
+    // Failed guards are reported as errors unless they are set to panic
+    assert!(gen
+        .generate_validator(
+            "check_age",
+            "guard input.age > 0 in input.age < 150",
+            "User",
+        )
+        .is_ok());
+    let err = gen
+        .clone()
+        .with_guard_policy(GuardPolicy::Panic)
+        .generate_validator(
+            "check_age",
+            "guard input.age > 0 in input.age < 150",
+            "User",
+        )
+        .unwrap_err();
+    assert!(err.contains("`panic!`"));
+
+    // The default policy does not audit
+    assert!(RustCodeGenerator::new()
+        .with_guard_policy(GuardPolicy::Panic)
+        .generate_validator(
+            "check_age",
+            "guard input.age > 0 in input.age < 150",
+            "User"
+        )
+        .is_ok());
+}
+
+#[test]
+fn test_generate_sink_validator() {
+    let gen = RustCodeGenerator::new();
+    let code = gen
+        .generate_sink_validator(
+            "check_age",
+            "require input.age >= 18 else 'too young'",
+            "User",
+        )
+        .unwrap()
+        .to_string();
+
+    assert!(code.contains("S : elo_rust :: runtime :: sink :: ErrorSink"));
+    assert!(code.contains("Failure :: error (\"check_age\" , \"too young\")"));
+    assert!(!code.contains("Vec"));
+    assert!(!code.contains("to_string"));
+}
+
+#[test]
+fn test_generate_validator_splits_large_bodies() {
+    let expr = "require input.age >= 18 else 'too young' && input.age < 150 && warn_if input.age > 100 : 'check age'";
+    let split = RustCodeGenerator::new()
+        .with_max_function_tokens(10)
+        .generate_validator("check_age", expr, "User")
+        .unwrap()
+        .to_string();
+
+    assert!(split.contains("fn __elo_check_age_part_0 (input : & User , __elo_errors : & mut elo_rust :: runtime :: ValidationErrors , __elo_warnings : & mut elo_rust :: runtime :: ValidationErrors) -> bool"));
+    assert!(split.contains("__elo_check_age_part_2"));
+    assert!(split.contains(
+        "let result : bool = __elo_check_age_part_0 (input , & mut __elo_errors , & mut __elo_warnings) && __elo_check_age_part_1"
+    ));
+
+    // Small bodies and single conjuncts stay in one function
+    let unsplit = RustCodeGenerator::new()
+        .with_max_function_tokens(10_000)
+        .generate_validator("check_age", expr, "User")
+        .unwrap()
+        .to_string();
+    assert!(!unsplit.contains("__elo_check_age_part_0"));
+    let single = RustCodeGenerator::new()
+        .with_max_function_tokens(1)
+        .generate_validator("check_age", "input.age >= 18 || input.admin", "User")
+        .unwrap()
+        .to_string();
+    assert!(!single.contains("part_0"));
+}
+
+#[test]
+fn test_codegen_large_membership_uses_sorted_table() {
+    let codes: Vec<String> = (0..20).map(|i| format!("'C{:02}'", 19 - i)).collect();
+    let expr = Parser::parse(&format!("country in [{}]", codes.join(", "))).expect("Parse failed");
+    let mut visitor = CodegenVisitor::new();
+    let code = visitor.visit_expr(&expr).to_string();
+
+    assert!(code.contains("const TABLE : & [& str] = & [\"C00\" , \"C01\""));
+    assert!(code.contains("binary_search"));
+    assert!(!code.contains("vec !"));
+
+    // Small sets become a pattern match
+    let expr = Parser::parse("country in ['FR', 'DE']").expect("Parse failed");
+    let code = CodegenVisitor::new().visit_expr(&expr).to_string();
+    assert_eq!(
+        code,
+        "matches ! (:: core :: convert :: AsRef :: < str > :: as_ref (& country) , \"DE\" | \"FR\")"
+    );
+}
+
+#[test]
+fn test_codegen_membership() {
+    let expr = Parser::parse("priority in [3, 1, -2]").expect("Parse failed");
+    let code = CodegenVisitor::new().visit_expr(&expr).to_string();
+    assert_eq!(code, "matches ! (priority , - 2i64 | 1i64 | 3i64)");
+
+    // Non-literal sets keep the linear scan
+    let expr = Parser::parse("role in [ctx.admin_role, 'owner']").expect("Parse failed");
+    let code = CodegenVisitor::new().visit_expr(&expr).to_string();
+    assert!(code.contains(". contains (& (role))"), "{}", code);
+}
+
+#[test]
+fn test_generate_validator_reads_fields_from_input() {
+    let code = RustCodeGenerator::new()
+        .generate_validator(
+            "validate",
+            "let limit = 18 in age >= limit && any(scores, fn(s ~> s == input.best))",
+            "User",
+        )
+        .unwrap()
+        .to_string();
+    assert!(code.contains("input . age >= limit"), "{}", code);
+    assert!(code.contains("input . scores . iter () . any (| s | s == input . best)"));
+    assert!(!code.contains("input . limit"));
+    assert!(!code.contains("input . input"));
+}
+
+#[test]
+fn test_generate_validator_optimizes_expression() {
+    let code = RustCodeGenerator::new()
+        .generate_validator("validate", "age > 2 * 9 && (verified || verified)", "User")
+        .unwrap()
+        .to_string();
+    assert!(code.contains("input . age > 18i64"), "{}", code);
+    assert!(!code.contains("2i64 * 9i64"));
+    assert!(!code.contains("input . verified || input . verified"));
+}
+
+#[test]
+fn test_generate_validator_alternative_without_require() {
+    let code = RustCodeGenerator::new()
+        .generate_validator("validate", "age >= 18 ?| guardian_approved", "User")
+        .unwrap()
+        .to_string();
+    assert!(code.contains("(input . age >= 18i64 || input . guardian_approved)"));
+    assert!(!code.contains("__elo_errors"));
+}
+
+#[test]
+fn test_generate_validator_with_optional_fields() {
+    use elo_rust::codegen::types::{RustType, TypeContext, TypeInfo};
+
+    let mut user = TypeInfo::new("User");
+    user.add_field("age", RustType::Option(Box::new(RustType::Integer)));
+    user.add_field("email", RustType::Option(Box::new(RustType::String)));
+    let mut context = TypeContext::new();
+    context.register_type("User", user);
+    let generator = RustCodeGenerator::with_context(context);
+
+    let code = generator
+        .generate_validator("check_user", "age >= 18 && email != null", "User")
+        .unwrap()
+        .to_string();
+    assert!(code.contains("match input . age { Some (__elo_some0) => __elo_some0 >= 18i64"));
+    assert!(code.contains("(input . email) . is_some ()"));
+
+    // Arithmetic on an optional field is guarded like the comparison it is in
+    assert!(generator
+        .generate_validator("check_user", "(age ?? 0) + 1 > 18 && age * 2 > 36", "User")
+        .is_ok());
+}
+
+#[test]
+fn test_generate_validator_shares_repeated_calls() {
+    use elo_rust::codegen::types::{RustType, TypeContext, TypeInfo};
+
+    let generator = RustCodeGenerator::new();
+    let code = generator
+        .generate_validator(
+            "check_name",
+            "(require lowercase(trim(name)) != 'root' else 'reserved') && lowercase(trim(name)) != 'admin'",
+            "User",
+        )
+        .unwrap()
+        .to_string();
+    assert!(code.contains("let __elo_cse_0 = input . name . trim () . to_lowercase () ;"));
+    assert_eq!(code.matches("to_lowercase").count(), 1);
+    // The leading `require` block is parenthesized so it is not a statement
+    assert!(code.contains("({ let passed : bool = __elo_cse_0 != \"root\""));
+    // Checks on the shared value still report the field it was computed from
+    assert!(code.contains("ValidationError :: new (\"name\" , \"reserved\" , \"check_name\")"));
+
+    // The shared value has the type its uses compare with; see the compiled
+    // `Signup` validator in elo-rust-macros/tests
+    let code = generator
+        .generate_validator(
+            "check_email",
+            "length(trim(email)) > 3 && length(trim(email)) < 100",
+            "User",
+        )
+        .unwrap()
+        .to_string();
+    assert!(
+        code.contains("let __elo_cse_0 = (input . email . trim () . chars () . count () as i64) ;")
+    );
+
+    // Calls reading optional fields keep the null guard of their comparison
+    let mut user = TypeInfo::new("User");
+    user.add_field("nick", RustType::Option(Box::new(RustType::String)));
+    let mut context = TypeContext::new();
+    context.register_type("User", user);
+    let code = RustCodeGenerator::with_context(context)
+        .generate_validator(
+            "check_nick",
+            "lowercase(nick) != 'root' && lowercase(nick) != 'admin'",
+            "User",
+        )
+        .unwrap()
+        .to_string();
+    assert!(!code.contains("__elo_cse"));
+}
+
+#[test]
+fn test_generate_validator_collects_all_failures() {
+    use elo_rust::codegen::{CodegenOptions, ErrorStrategy};
+
+    let source = "(require age >= 18 else 'adult only') && verified && name != ''";
+    let fail_fast = RustCodeGenerator::new()
+        .generate_validator("check_user", source, "User")
+        .unwrap()
+        .to_string();
+    assert!(fail_fast.contains("&& input . verified &&"));
+    assert!(!fail_fast.contains("__elo_passed"));
+
+    let collect_all = RustCodeGenerator::new().with_options(CodegenOptions {
+        error_strategy: ErrorStrategy::CollectAll,
+        ..Default::default()
+    });
+    let code = collect_all
+        .generate_validator("check_user", source, "User")
+        .unwrap()
+        .to_string();
+    assert_eq!(code.matches("__elo_passed &= passed").count(), 3);
+    assert!(code.contains("ValidationError :: new (\"age\" , \"adult only\" , \"check_user\")"));
+    assert!(code.contains(
+        "ValidationError :: new (\"verified\" , \"Validation failed\" , \"check_user\")"
+    ));
+    assert!(
+        code.contains("ValidationError :: new (\"name\" , \"Validation failed\" , \"check_user\")")
+    );
+    // A conjunct that reported its own message is not reported again
+    assert!(code.contains("if ! passed && __elo_errors . len () == __elo_mark"));
+
+    // Split validators collect the failures of their helpers
+    let code = collect_all
+        .clone()
+        .with_max_function_tokens(5)
+        .generate_validator("check_user", source, "User")
+        .unwrap()
+        .to_string();
+    assert!(
+        code.contains("let passed : bool = __elo_check_user_part_1 (input , & mut __elo_errors)")
+    );
+
+    // A single check is generated as usual
+    let code = collect_all
+        .generate_validator("check_user", "age >= 18", "User")
+        .unwrap()
+        .to_string();
+    assert!(!code.contains("__elo_passed"));
+}
+
+#[test]
+fn test_generate_validator_with_options() {
+    use elo_rust::codegen::audit::PanicPolicy;
+    use elo_rust::codegen::{CodegenOptions, TemporalBackend};
+
+    let generator = RustCodeGenerator::new().with_options(CodegenOptions {
+        temporal_backend: TemporalBackend::Time,
+        identifier_prefix: "user_".to_string(),
+        doc_comments: true,
+        ..Default::default()
+    });
+    let code = generator
+        .generate_batch_validator("check_age", "birth<TODAY&&verified", "User")
+        .unwrap()
+        .to_string();
+    assert!(code.contains("pub fn user_check_age (input : & User)"));
+    assert!(code.contains("validate_all (items , user_check_age)"));
+    // The documented rule is the canonical source, one conjunct per line
+    assert!(code.contains("# [doc = \" birth < TODAY\"] # [doc = \"     && verified\"]"));
+    assert!(code.contains(":: time :: OffsetDateTime :: now_utc () . date ()"));
+    assert!(!code.contains("chrono"));
+    // Rule names in errors are not prefixed
+    assert!(code.contains("\"check_age\")"));
+
+    // `with_options` replaces a panic policy set before it
+    let generator = RustCodeGenerator::new()
+        .with_panic_policy(PanicPolicy::Deny)
+        .with_options(CodegenOptions::default());
+    assert_eq!(generator.options().panic_policy, PanicPolicy::Allow);
+    let generator = generator.with_panic_policy(PanicPolicy::Deny);
+    assert!(generator
+        .generate_validator("check_date", "birth > @date(2000-01-01)", "User")
+        .is_err());
+}
+
+#[test]
+fn test_generate_validator_guard_and_split_options() {
+    use elo_rust::codegen::CodegenOptions;
+
+    // The builders set the same options as `with_options`
+    let built = RustCodeGenerator::new()
+        .with_guard_policy(GuardPolicy::Panic)
+        .with_max_function_tokens(10);
+    let options = CodegenOptions {
+        guard_policy: GuardPolicy::Panic,
+        max_function_tokens: Some(10),
+        ..Default::default()
+    };
+    assert_eq!(built.options(), &options);
+
+    let expr = "input.age != 42 && (guard input.age > 0 in input.age < 150)";
+    let code = RustCodeGenerator::new()
+        .with_options(options)
+        .generate_validator("check_age", expr, "User")
+        .unwrap()
+        .to_string();
+    assert_eq!(
+        code,
+        built
+            .generate_validator("check_age", expr, "User")
+            .unwrap()
+            .to_string()
+    );
+    assert!(code.contains("panic ! (\"{}\" , \"guard failed: input.age > 0\")"));
+    assert!(code.contains("__elo_check_age_part_1"));
+}
+
+#[test]
+fn test_generate_validator_no_std() {
+    use elo_rust::codegen::CodegenOptions;
+
+    let generator = RustCodeGenerator::new().with_options(CodegenOptions {
+        no_std: true,
+        ..Default::default()
+    });
+    let code = generator
+        .generate_batch_validator(
+            "check_scores",
+            "(scores |> map(fn(s ~> s * 2)) |> sum) > 10",
+            "Team",
+        )
+        .unwrap()
+        .to_string();
+    assert!(code.contains("collect :: < :: alloc :: vec :: Vec < _ >> ()"));
+    assert!(code.contains("Result < () , :: alloc :: vec :: Vec < (usize"));
+
+    let error = generator
+        .generate_validator("check_code", "matches(code, '^[A-Z]{3}$')", "Item")
+        .unwrap_err();
+    assert!(error.contains("std::sync"), "{}", error);
+}
+
+#[test]
+fn test_generate_validator_clamps_to_ranges() {
+    let generator = RustCodeGenerator::new();
+    let code = generator
+        .generate_validator("check_age", "clamp(age, 1..10) > 3", "User")
+        .unwrap()
+        .to_string();
+    assert!(code.contains("(input . age) . clamp (1i64 , 10i64 - 1) > 3i64"));
+
+    // Floats have no largest value below an exclusive end
+    let error = generator
+        .generate_validator("check_ratio", "clamp(ratio, 1.0..2.0) > 1.5", "User")
+        .unwrap_err();
+    assert_eq!(
+        error,
+        "Type error: ratio: clamp() needs an inclusive range of floats"
+    );
+}
+
+#[test]
+fn test_generate_validator_checks_regex_patterns() {
+    let generator = RustCodeGenerator::new();
+    let code = generator
+        .generate_validator("check_code", "matches(code, '^[A-Z]{3}$')", "Item")
+        .unwrap()
+        .to_string();
+    assert!(code.contains("OnceLock"));
+
+    let error = generator
+        .generate_validator("check_code", "matches(code, '(a+)+$')", "Item")
+        .unwrap_err();
+    assert!(
+        error.starts_with("Pattern error: matches('(a+)+$')"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn test_generate_validator_rejects_unknown_calls() {
+    let generator = RustCodeGenerator::new();
+    let error = generator
+        .generate_validator("check_total", "sum() > 0", "Order")
+        .unwrap_err();
+    assert_eq!(error, "Function error: sum() does not take 0 arguments");
+
+    let error = generator
+        .generate_validator("check_total", "total(items) > 0", "Order")
+        .unwrap_err();
+    assert_eq!(error, "Function error: unknown function total()");
+}
+
+#[test]
+fn test_generate_batch_validator() {
+    let generator = RustCodeGenerator::new();
+    let code = generator
+        .generate_batch_validator("validate", "age >= 18", "User")
+        .unwrap()
+        .to_string();
+    assert!(code.contains("pub fn validate (input : & User)"));
+    assert!(code.contains(
+        "pub fn validate_all (items : & [User]) -> Result < () , Vec < (usize , elo_rust :: runtime :: ValidationErrors) >>"
+    ));
+    assert!(code.contains("elo_rust :: runtime :: validate_all (items , validate)"));
+    assert!(generator
+        .generate_batch_validator("validate", "age >=", "User")
+        .is_err());
+}
+
+#[test]
+fn test_generate_validator_reports_message_clause() {
+    let generator = RustCodeGenerator::new();
+    let code = generator
+        .generate_validator("check_age", "age >= 18 message 'Must be an adult'", "User")
+        .unwrap()
+        .to_string();
+    assert!(
+        code.contains("ValidationError :: new (\"age\" , \"Must be an adult\" , \"check_age\")")
+    );
+    // The generic message is only reported when no custom message was
+    assert!(code.contains("__elo_errors . is_empty ()"));
+}
+
+#[test]
+fn test_generate_validator_with_errors_nests_paths() {
+    let generator = RustCodeGenerator::new();
+    let code = generator
+        .generate_validator_with_errors(
+            "check_user",
+            "require age >= 18 else 'too young' && (warn_if input.address.zip == '' : 'no zip') && age < score",
+            "User",
+            "order.user",
+        )
+        .unwrap()
+        .to_string();
+    assert!(code.contains("-> Result < () , elo_rust :: runtime :: ValidationErrors >"));
+    assert!(code
+        .contains("ValidationError :: new (\"order.user.age\" , \"too young\" , \"check_user\")"));
+    assert!(code.contains(
+        "ValidationError :: warning (\"order.user.address.zip\" , \"no zip\" , \"check_user\")"
+    ));
+    assert!(code.contains("Inspect (& input . address . zip)"));
+    // The generic failure reads two fields, so it is reported at the base path
+    assert!(code.contains(
+        "ValidationError :: new (\"order.user\" , \"Validation failed\" , \"check_user\")"
+    ));
+
+    let unnested = generator
+        .generate_validator("check_user", "age < score", "User")
+        .unwrap()
+        .to_string();
+    assert!(unnested.contains(
+        "ValidationError :: new (\"check_user\" , \"Validation failed\" , \"check_user\")"
+    ));
+}
+
+#[test]
+fn test_bench_compare_generated_code_is_current() {
+    const RULES: &[(&str, &str)] = &include!("../examples/bench_compare/rules.rs");
+
+    // As written by the example's `--write-code`
+    let generator = RustCodeGenerator::new();
+    let mut expected =
+        String::from("// Generated by `cargo run --example bench_compare -- --write-code`\n");
+    for (rule, source) in RULES {
+        let code = generator.generate_validator(rule, source, "Order").unwrap();
+        expected.push_str(&format!("\n// {}\n{}\n", source, code));
+    }
+    assert!(
+        include_str!("../examples/bench_compare/generated.rs") == expected,
+        "examples/bench_compare/generated.rs is out of date; regenerate it with \
+         `cargo run --example bench_compare -- --write-code`"
+    );
+}
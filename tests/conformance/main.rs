@@ -0,0 +1,122 @@
+//! Runs the conformance fixtures in this directory through the evaluator
+//! and through generated code, compiled and run
+
+#![cfg(feature = "conformance")]
+
+use elo_rust::conformance::{self, CodegenBackend, InterpreterBackend};
+
+#[test]
+fn interpreter_conforms() {
+    let report = conformance::run(&InterpreterBackend, &conformance::fixtures());
+    assert!(report.is_success(), "{}", report);
+    assert!(report.unsupported.is_empty(), "{}", report);
+}
+
+#[test]
+fn codegen_conforms() {
+    let report = conformance::run(&CodegenBackend::default(), &conformance::fixtures());
+    assert!(report.is_success(), "{}", report);
+    // Generated code needs a Rust type for each field it reads, which an
+    // empty array or a missing field does not give
+    let unsupported: Vec<&str> = report
+        .unsupported
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect();
+    assert_eq!(
+        unsupported,
+        [
+            "any on an empty array",
+            "all on an empty array",
+            "max_by on an empty array",
+            "comparing min_by on an empty array",
+            "is_null on a missing field",
+            "missing field reads as null",
+            "null default on a missing field",
+            "null default is right associative",
+            "missing context key with default",
+            "alternative on a missing field",
+            "alternative chain",
+        ],
+        "{}",
+        report
+    );
+}
+
+#[test]
+fn codegen_keeps_grouping_like_the_interpreter() {
+    let fixtures = conformance::parse_fixtures(
+        "grouping",
+        "test grouped sum times two
+expr: (a + 1) * 2
+input: {\"a\": 2}
+expect: 6
+
+test negated sum
+expr: -(a + 1)
+input: {\"a\": 2}
+expect: -3
+
+test grouped subtrahend
+expr: a - (b - c)
+input: {\"a\": 10, \"b\": 5, \"c\": 2}
+expect: 7
+
+test negated comparison
+expr: !(age > 18)
+input: {\"age\": 10}
+expect: true
+
+test negated string comparison
+expr: !(name == 'bob')
+input: {\"name\": \"alice\"}
+expect: true
+
+test grouped comparison compared again
+expr: (a > 1) == (b > 1)
+input: {\"a\": 2, \"b\": 0}
+expect: false
+",
+    )
+    .unwrap();
+    let interpreted = conformance::run(&InterpreterBackend, &fixtures);
+    let compiled = conformance::run(&CodegenBackend::default(), &fixtures);
+    for report in [interpreted, compiled] {
+        assert!(report.is_success(), "{}", report);
+        assert_eq!(report.passed, fixtures.len(), "{}", report);
+    }
+}
+
+#[test]
+fn required_if_runs_like_the_interpreter() {
+    let fixtures = conformance::parse_fixtures(
+        "required_if",
+        "test condition holds and the field is present
+expr: required_if(nick, age > 18)
+input: {\"age\": 30, \"nick\": \"al\"}
+expect: true
+
+test condition does not hold
+expr: required_if(nick, age > 18)
+input: {\"age\": 10, \"nick\": \"al\"}
+expect: true
+
+test string condition
+expr: required_if(nick, name == 'bob')
+input: {\"name\": \"alice\", \"nick\": \"al\"}
+expect: true
+
+test field missing when required
+expr: required_if(nick, age > 18)
+input: {\"age\": 30, \"nick\": \"\"}
+expect: false
+",
+    )
+    .unwrap();
+    let interpreted = conformance::run(&InterpreterBackend, &fixtures);
+    let compiled = conformance::run(&CodegenBackend::default(), &fixtures);
+    for report in [interpreted, compiled] {
+        assert!(report.is_success(), "{}", report);
+        assert_eq!(report.passed, fixtures.len(), "{}", report);
+    }
+}
@@ -66,7 +66,7 @@ fn test_call_with_special_characters_in_name() {
     let gen = FunctionGenerator::new();
     let result = gen.call("func@name", vec![]);
     let s = result.to_string();
-    assert!(s.is_empty());
+    assert!(s.contains("compile_error ! (\"unknown function func@name()\")"));
 }
 
 #[test]
@@ -74,7 +74,7 @@ fn test_string_function_empty_name() {
     let gen = FunctionGenerator::new();
     let result = gen.string_function("", vec![quote::quote!(text)]);
     let s = result.to_string();
-    assert!(s.is_empty());
+    assert!(s.contains("unknown function ()"));
 }
 
 #[test]
@@ -82,7 +82,7 @@ fn test_datetime_function_empty_name() {
     let gen = FunctionGenerator::new();
     let result = gen.datetime_function("", vec![]);
     let s = result.to_string();
-    assert!(s.is_empty());
+    assert!(s.contains("unknown function ()"));
 }
 
 #[test]
@@ -90,7 +90,7 @@ fn test_array_function_empty_name() {
     let gen = FunctionGenerator::new();
     let result = gen.array_function("", vec![quote::quote!(arr)]);
     let s = result.to_string();
-    assert!(s.is_empty());
+    assert!(s.contains("unknown function ()"));
 }
 
 #[test]
@@ -105,7 +105,7 @@ fn test_function_with_many_arguments() {
     ];
     let result = gen.call("unknown", args);
     let s = result.to_string();
-    assert!(s.is_empty());
+    assert!(s.contains("unknown function unknown()"));
 }
 
 // ============================================================================
@@ -223,7 +223,7 @@ fn test_function_call_with_unicode_name() {
     let result = gen.call("функция", vec![]);
     let s = result.to_string();
     // Unknown function with unicode name should be handled
-    assert!(s.is_empty());
+    assert!(s.contains("compile_error"));
 }
 
 #[test]
@@ -11,7 +11,7 @@ fn test_matches_with_no_arguments() {
     let gen = FunctionGenerator::new();
     let result = gen.string_function("matches", vec![]);
     let s = result.to_string();
-    assert!(s.is_empty());
+    assert!(s.contains("invalid arguments to matches()"));
 }
 
 #[test]
@@ -20,7 +20,7 @@ fn test_matches_with_one_argument() {
     let subject = quote::quote!(email);
     let result = gen.string_function("matches", vec![subject]);
     let s = result.to_string();
-    assert!(s.is_empty());
+    assert!(s.contains("invalid arguments to matches()"));
 }
 
 #[test]
@@ -28,7 +28,7 @@ fn test_contains_with_no_arguments() {
     let gen = FunctionGenerator::new();
     let result = gen.string_function("contains", vec![]);
     let s = result.to_string();
-    assert!(s.is_empty());
+    assert!(s.contains("invalid arguments to contains()"));
 }
 
 #[test]
@@ -37,7 +37,7 @@ fn test_contains_with_one_argument() {
     let subject = quote::quote!(text);
     let result = gen.string_function("contains", vec![subject]);
     let s = result.to_string();
-    assert!(s.is_empty());
+    assert!(s.contains("invalid arguments to contains()"));
 }
 
 #[test]
@@ -45,7 +45,7 @@ fn test_length_with_no_arguments() {
     let gen = FunctionGenerator::new();
     let result = gen.string_function("length", vec![]);
     let s = result.to_string();
-    assert!(s.is_empty());
+    assert!(s.contains("invalid arguments to length()"));
 }
 
 #[test]
@@ -53,7 +53,7 @@ fn test_uppercase_with_no_arguments() {
     let gen = FunctionGenerator::new();
     let result = gen.string_function("uppercase", vec![]);
     let s = result.to_string();
-    assert!(s.is_empty());
+    assert!(s.contains("invalid arguments to uppercase()"));
 }
 
 #[test]
@@ -61,7 +61,7 @@ fn test_lowercase_with_no_arguments() {
     let gen = FunctionGenerator::new();
     let result = gen.string_function("lowercase", vec![]);
     let s = result.to_string();
-    assert!(s.is_empty());
+    assert!(s.contains("invalid arguments to lowercase()"));
 }
 
 #[test]
@@ -69,7 +69,7 @@ fn test_trim_with_no_arguments() {
     let gen = FunctionGenerator::new();
     let result = gen.string_function("trim", vec![]);
     let s = result.to_string();
-    assert!(s.is_empty());
+    assert!(s.contains("invalid arguments to trim()"));
 }
 
 #[test]
@@ -77,7 +77,7 @@ fn test_starts_with_with_no_arguments() {
     let gen = FunctionGenerator::new();
     let result = gen.string_function("starts_with", vec![]);
     let s = result.to_string();
-    assert!(s.is_empty());
+    assert!(s.contains("invalid arguments to starts_with()"));
 }
 
 #[test]
@@ -86,7 +86,7 @@ fn test_starts_with_with_one_argument() {
     let subject = quote::quote!(path);
     let result = gen.string_function("starts_with", vec![subject]);
     let s = result.to_string();
-    assert!(s.is_empty());
+    assert!(s.contains("invalid arguments to starts_with()"));
 }
 
 #[test]
@@ -94,7 +94,7 @@ fn test_ends_with_with_no_arguments() {
     let gen = FunctionGenerator::new();
     let result = gen.string_function("ends_with", vec![]);
     let s = result.to_string();
-    assert!(s.is_empty());
+    assert!(s.contains("invalid arguments to ends_with()"));
 }
 
 #[test]
@@ -103,7 +103,7 @@ fn test_ends_with_with_one_argument() {
     let subject = quote::quote!(filename);
     let result = gen.string_function("ends_with", vec![subject]);
     let s = result.to_string();
-    assert!(s.is_empty());
+    assert!(s.contains("invalid arguments to ends_with()"));
 }
 
 #[test]
@@ -112,7 +112,7 @@ fn test_unknown_string_function() {
     let subject = quote::quote!(text);
     let result = gen.string_function("unknown_function", vec![subject]);
     let s = result.to_string();
-    assert!(s.is_empty());
+    assert!(s.contains("unknown function unknown_function()"));
 }
 
 // ============================================================================
@@ -156,7 +156,7 @@ fn test_age_with_no_arguments() {
     let gen = FunctionGenerator::new();
     let result = gen.datetime_function("age", vec![]);
     let s = result.to_string();
-    assert!(s.is_empty());
+    assert!(s.contains("invalid arguments to age()"));
 }
 
 #[test]
@@ -173,7 +173,7 @@ fn test_days_since_with_no_arguments() {
     let gen = FunctionGenerator::new();
     let result = gen.datetime_function("days_since", vec![]);
     let s = result.to_string();
-    assert!(s.is_empty());
+    assert!(s.contains("invalid arguments to days_since()"));
 }
 
 #[test]
@@ -190,7 +190,7 @@ fn test_date_with_no_arguments() {
     let gen = FunctionGenerator::new();
     let result = gen.datetime_function("date", vec![]);
     let s = result.to_string();
-    assert!(s.is_empty());
+    assert!(s.contains("invalid arguments to date()"));
 }
 
 #[test]
@@ -207,7 +207,7 @@ fn test_unknown_datetime_function() {
     let gen = FunctionGenerator::new();
     let result = gen.datetime_function("unknown_datetime_fn", vec![]);
     let s = result.to_string();
-    assert!(s.is_empty());
+    assert!(s.contains("unknown function unknown_datetime_fn()"));
 }
 
 // ============================================================================
@@ -219,7 +219,7 @@ fn test_array_contains_with_no_arguments() {
     let gen = FunctionGenerator::new();
     let result = gen.array_function("contains", vec![]);
     let s = result.to_string();
-    assert!(s.is_empty());
+    assert!(s.contains("invalid arguments to contains()"));
 }
 
 #[test]
@@ -228,7 +228,7 @@ fn test_array_contains_with_one_argument() {
     let array = quote::quote!(items);
     let result = gen.array_function("contains", vec![array]);
     let s = result.to_string();
-    assert!(s.is_empty());
+    assert!(s.contains("invalid arguments to contains()"));
 }
 
 #[test]
@@ -236,7 +236,7 @@ fn test_array_any_with_no_arguments() {
     let gen = FunctionGenerator::new();
     let result = gen.array_function("any", vec![]);
     let s = result.to_string();
-    assert!(s.is_empty());
+    assert!(s.contains("invalid arguments to any()"));
 }
 
 #[test]
@@ -245,7 +245,7 @@ fn test_array_any_with_one_argument() {
     let array = quote::quote!(items);
     let result = gen.array_function("any", vec![array]);
     let s = result.to_string();
-    assert!(s.is_empty());
+    assert!(s.contains("invalid arguments to any()"));
 }
 
 #[test]
@@ -253,7 +253,7 @@ fn test_array_all_with_no_arguments() {
     let gen = FunctionGenerator::new();
     let result = gen.array_function("all", vec![]);
     let s = result.to_string();
-    assert!(s.is_empty());
+    assert!(s.contains("invalid arguments to all()"));
 }
 
 #[test]
@@ -262,7 +262,7 @@ fn test_array_all_with_one_argument() {
     let array = quote::quote!(items);
     let result = gen.array_function("all", vec![array]);
     let s = result.to_string();
-    assert!(s.is_empty());
+    assert!(s.contains("invalid arguments to all()"));
 }
 
 #[test]
@@ -270,7 +270,7 @@ fn test_array_length_with_no_arguments() {
     let gen = FunctionGenerator::new();
     let result = gen.array_function("length", vec![]);
     let s = result.to_string();
-    assert!(s.is_empty());
+    assert!(s.contains("invalid arguments to length()"));
 }
 
 #[test]
@@ -287,7 +287,7 @@ fn test_array_is_empty_with_no_arguments() {
     let gen = FunctionGenerator::new();
     let result = gen.array_function("is_empty", vec![]);
     let s = result.to_string();
-    assert!(s.is_empty());
+    assert!(s.contains("invalid arguments to is_empty()"));
 }
 
 #[test]
@@ -305,7 +305,7 @@ fn test_unknown_array_function() {
     let array = quote::quote!(items);
     let result = gen.array_function("unknown_array_fn", vec![array]);
     let s = result.to_string();
-    assert!(s.is_empty());
+    assert!(s.contains("unknown function unknown_array_fn()"));
 }
 
 // ============================================================================
@@ -345,7 +345,7 @@ fn test_call_unknown_function() {
     let gen = FunctionGenerator::new();
     let result = gen.call("nonexistent_function", vec![]);
     let s = result.to_string();
-    assert!(s.is_empty());
+    assert!(s.contains("unknown function nonexistent_function()"));
 }
 
 #[test]
@@ -23,7 +23,7 @@ fn test_is_null_with_no_arguments() {
     let result = gen.array_function("is_null", vec![]);
     let s = result.to_string();
 
-    assert!(s.is_empty());
+    assert!(s.contains("invalid arguments to is_null()"));
 }
 
 #[test]
@@ -43,7 +43,7 @@ fn test_is_some_with_no_arguments() {
     let result = gen.array_function("is_some", vec![]);
     let s = result.to_string();
 
-    assert!(s.is_empty());
+    assert!(s.contains("invalid arguments to is_some()"));
 }
 
 // ============================================================================
@@ -51,25 +51,23 @@ fn test_is_some_with_no_arguments() {
 // ============================================================================
 
 #[test]
-fn test_call_is_null_not_routed() {
+fn test_call_is_null_routed() {
     let gen = FunctionGenerator::new();
     let value = quote::quote!(optional_value);
     let result = gen.call("is_null", vec![value]);
     let s = result.to_string();
 
-    // is_null is not in the call routing, so it returns empty
-    assert!(s.is_empty());
+    assert_eq!(s, "optional_value . is_none ()");
 }
 
 #[test]
-fn test_call_is_some_not_routed() {
+fn test_call_is_some_routed() {
     let gen = FunctionGenerator::new();
     let value = quote::quote!(maybe_data);
     let result = gen.call("is_some", vec![value]);
     let s = result.to_string();
 
-    // is_some is not in the call routing, so it returns empty
-    assert!(s.is_empty());
+    assert_eq!(s, "maybe_data . is_some ()");
 }
 
 // ============================================================================
@@ -140,7 +138,7 @@ fn test_length_function_returns_code() {
     let result = gen.string_function("length", vec![text]);
     let s = result.to_string();
 
-    assert!(s.contains("len"));
+    assert!(s.contains("chars () . count ()"));
 }
 
 #[test]
@@ -308,7 +306,7 @@ fn test_unknown_function_via_call() {
     let result = gen.call("nonexistent_fn", vec![quote::quote!(data)]);
     let s = result.to_string();
 
-    assert!(s.is_empty());
+    assert!(s.contains("unknown function nonexistent_fn()"));
 }
 
 #[test]
@@ -317,7 +315,7 @@ fn test_empty_function_name() {
     let result = gen.call("", vec![quote::quote!(data)]);
     let s = result.to_string();
 
-    assert!(s.is_empty());
+    assert!(s.contains("unknown function ()"));
 }
 
 #[test]
@@ -329,9 +327,11 @@ fn test_function_case_sensitivity() {
     // UPPERCASE should not match
     let result_upper = gen.string_function("LENGTH", vec![quote::quote!(text)]);
 
-    // lowercase should produce code, uppercase should not
-    assert!(!result_lower.to_string().is_empty());
-    assert!(result_upper.to_string().is_empty());
+    // lowercase should produce code, uppercase is an unknown function
+    assert!(!result_lower.to_string().contains("compile_error"));
+    assert!(result_upper
+        .to_string()
+        .contains("unknown function LENGTH()"));
 }
 
 #[test]
@@ -339,5 +339,5 @@ fn test_mixed_case_function_name() {
     let gen = FunctionGenerator::new();
     // CamelCase should not match snake_case
     let result = gen.string_function("Length", vec![quote::quote!(text)]);
-    assert!(result.to_string().is_empty());
+    assert!(result.to_string().contains("unknown function Length()"));
 }
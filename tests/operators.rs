@@ -199,6 +199,16 @@ fn test_negate_operator_generation() {
     assert!(s.contains("-"));
 }
 
+#[test]
+fn test_identity_operator_generation() {
+    let gen = OperatorGenerator::new();
+    let operand = quote::quote!(value);
+
+    let result = gen.unary(UnaryOp::Identity, operand);
+
+    assert_eq!(result.to_string(), "value");
+}
+
 // ============================================================================
 // COMPLEX OPERATOR EXPRESSIONS
 // ============================================================================
@@ -0,0 +1,112 @@
+//! A rule set's generated validator, compiled into this test and checked
+//! against `RuleSet::check`
+
+use elo_rust::codegen::types::{RustType, TypeContext, TypeInfo};
+use elo_rust::rules::RuleSet;
+use elo_rust::runtime::EloValue;
+use std::collections::BTreeMap;
+
+pub struct Order {
+    pub age: i64,
+    pub price: f64,
+    pub verified: bool,
+}
+
+#[allow(dead_code, clippy::all)]
+mod generated {
+    use super::Order;
+    include!("rule_set_validator/generated.rs");
+}
+
+const RULES: &str = "\
+rule is_adult: age >= 18
+rule has_price: price > 1
+rule can_checkout: is_adult && has_price && verified
+rule not_a_minor_spending_much: !(age < 18 && price > 100)
+rule expensive: warn_if price > 500 : 'large order'
+";
+
+fn rules() -> RuleSet {
+    let mut order = TypeInfo::new("Order");
+    order.add_field("age", RustType::Integer);
+    order.add_field("price", RustType::Float);
+    order.add_field("verified", RustType::Bool);
+    let mut context = TypeContext::new();
+    context.register_type("Order", order);
+    RuleSet::parse(RULES).unwrap().with_type_context(context)
+}
+
+#[test]
+fn test_generated_validator_is_current() {
+    let code = rules()
+        .generate_validator("validate", "Order")
+        .unwrap()
+        .to_string();
+    let path = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/rule_set_validator/generated.rs"
+    );
+    if std::env::var_os("ELO_UPDATE_GENERATED").is_some() {
+        std::fs::write(path, &code).unwrap();
+    }
+    assert!(
+        include_str!("rule_set_validator/generated.rs") == code,
+        "tests/rule_set_validator/generated.rs is out of date; regenerate it with \
+         `ELO_UPDATE_GENERATED=1 cargo test --test rule_set_validator`"
+    );
+}
+
+#[test]
+fn test_generated_validator_agrees_with_check() {
+    let rules = rules();
+    for (age, price, verified) in [
+        (30, 50.0, true),
+        (30, 0.5, true),
+        (12, 150.0, true),
+        (12, 50.0, false),
+        (40, 900.0, true),
+    ] {
+        let order = Order {
+            age,
+            price,
+            verified,
+        };
+        let input = EloValue::Object(BTreeMap::from([
+            ("age".to_string(), EloValue::Integer(age)),
+            ("price".to_string(), EloValue::Float(price)),
+            ("verified".to_string(), EloValue::Boolean(verified)),
+        ]));
+        let statuses = |report: elo_rust::runtime::report::ValidationReport| {
+            report
+                .outcomes
+                .into_iter()
+                .map(|outcome| (outcome.rule, outcome.status))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            statuses(generated::validate_report(&order)),
+            statuses(rules.check(&input).unwrap()),
+            "age {}, price {}, verified {}",
+            age,
+            price,
+            verified
+        );
+        assert_eq!(
+            generated::validate(&order).is_ok(),
+            rules.check(&input).unwrap().is_ok()
+        );
+    }
+}
+
+#[test]
+fn test_generated_validator_returns_validation_errors() {
+    let order = Order {
+        age: 12,
+        price: 600.0,
+        verified: true,
+    };
+    let errors: elo_rust::runtime::ValidationErrors = generated::validate(&order).unwrap_err();
+    assert!(errors.has_errors());
+    // The warning of a failed run is reported alongside its errors
+    assert_eq!(errors.warnings().count(), 1);
+}
@@ -0,0 +1 @@
+# [allow (unused_variables , dead_code)] fn rule_is_adult (input : & Order) -> bool { input . age >= 18i64 } # [allow (unused_variables , dead_code)] fn rule_has_price (input : & Order) -> bool { input . price > 1f64 } # [allow (unused_variables , dead_code)] fn rule_can_checkout (input : & Order) -> bool { rule_is_adult (input) && rule_has_price (input) && input . verified } # [allow (unused_variables , dead_code)] fn rule_not_a_minor_spending_much (input : & Order) -> bool { ! (input . age < 18i64 && input . price > 100f64) } # [allow (unused_variables , dead_code)] fn rule_expensive (input : & Order) -> bool { true } pub fn validate_report (input : & Order) -> elo_rust :: runtime :: report :: ValidationReport { let mut report = elo_rust :: runtime :: report :: ValidationReport :: new () ; report . push (if rule_is_adult (input) { elo_rust :: runtime :: report :: RuleOutcome :: passed ("is_adult") } else { elo_rust :: runtime :: report :: RuleOutcome :: failed ("is_adult" , vec ! [format ! ("Rule '{}' failed" , "is_adult")] ,) }) ; report . push (if rule_has_price (input) { elo_rust :: runtime :: report :: RuleOutcome :: passed ("has_price") } else { elo_rust :: runtime :: report :: RuleOutcome :: failed ("has_price" , vec ! [format ! ("Rule '{}' failed" , "has_price")] ,) }) ; report . push (if rule_can_checkout (input) { elo_rust :: runtime :: report :: RuleOutcome :: passed ("can_checkout") } else { let mut causes : Vec < & str > = Vec :: new () ; if ! rule_has_price (input) { causes . push ("has_price") ; } if ! rule_is_adult (input) { causes . push ("is_adult") ; } let message = if causes . is_empty () { format ! ("Rule '{}' failed" , "can_checkout") } else { format ! ("Rule '{}' failed (caused by: {})" , "can_checkout" , causes . join (", ")) } ; elo_rust :: runtime :: report :: RuleOutcome :: failed ("can_checkout" , vec ! [message]) }) ; report . push (if rule_not_a_minor_spending_much (input) { elo_rust :: runtime :: report :: RuleOutcome :: passed ("not_a_minor_spending_much") } else { elo_rust :: runtime :: report :: RuleOutcome :: failed ("not_a_minor_spending_much" , vec ! [format ! ("Rule '{}' failed" , "not_a_minor_spending_much")] ,) }) ; report . push (if input . price > 500f64 { elo_rust :: runtime :: report :: RuleOutcome :: warned ("expensive" , vec ! ["large order" . to_string ()] ,) } else { elo_rust :: runtime :: report :: RuleOutcome :: passed ("expensive") }) ; report } pub fn validate (input : & Order) -> Result < () , elo_rust :: runtime :: ValidationErrors > { validate_report (input) . into_result () } pub fn validate_incremental () -> elo_rust :: runtime :: incremental :: RuleSetValidator < Order > { elo_rust :: runtime :: incremental :: RuleSetValidator :: new () . with_rule ("is_adult" , vec ! [elo_rust :: ast :: analysis :: FieldPath :: from ("age")] , { # [allow (unused_variables)] let check = move | input : & Order | if rule_is_adult (input) { elo_rust :: runtime :: report :: RuleOutcome :: passed ("is_adult") } else { elo_rust :: runtime :: report :: RuleOutcome :: failed ("is_adult" , vec ! [format ! ("Rule '{}' failed" , "is_adult")] ,) } ; check } ,) . with_rule ("has_price" , vec ! [elo_rust :: ast :: analysis :: FieldPath :: from ("price")] , { # [allow (unused_variables)] let check = move | input : & Order | if rule_has_price (input) { elo_rust :: runtime :: report :: RuleOutcome :: passed ("has_price") } else { elo_rust :: runtime :: report :: RuleOutcome :: failed ("has_price" , vec ! [format ! ("Rule '{}' failed" , "has_price")] ,) } ; check } ,) . with_rule ("can_checkout" , vec ! [elo_rust :: ast :: analysis :: FieldPath :: from ("age") , elo_rust :: ast :: analysis :: FieldPath :: from ("price") , elo_rust :: ast :: analysis :: FieldPath :: from ("verified")] , { # [allow (unused_variables)] let check = move | input : & Order | if rule_can_checkout (input) { elo_rust :: runtime :: report :: RuleOutcome :: passed ("can_checkout") } else { let mut causes : Vec < & str > = Vec :: new () ; if ! rule_has_price (input) { causes . push ("has_price") ; } if ! rule_is_adult (input) { causes . push ("is_adult") ; } let message = if causes . is_empty () { format ! ("Rule '{}' failed" , "can_checkout") } else { format ! ("Rule '{}' failed (caused by: {})" , "can_checkout" , causes . join (", ")) } ; elo_rust :: runtime :: report :: RuleOutcome :: failed ("can_checkout" , vec ! [message]) } ; check } ,) . with_rule ("not_a_minor_spending_much" , vec ! [elo_rust :: ast :: analysis :: FieldPath :: from ("age") , elo_rust :: ast :: analysis :: FieldPath :: from ("price")] , { # [allow (unused_variables)] let check = move | input : & Order | if rule_not_a_minor_spending_much (input) { elo_rust :: runtime :: report :: RuleOutcome :: passed ("not_a_minor_spending_much") } else { elo_rust :: runtime :: report :: RuleOutcome :: failed ("not_a_minor_spending_much" , vec ! [format ! ("Rule '{}' failed" , "not_a_minor_spending_much")] ,) } ; check } ,) . with_rule ("expensive" , vec ! [elo_rust :: ast :: analysis :: FieldPath :: from ("price")] , { # [allow (unused_variables)] let check = move | input : & Order | if input . price > 500f64 { elo_rust :: runtime :: report :: RuleOutcome :: warned ("expensive" , vec ! ["large order" . to_string ()] ,) } else { elo_rust :: runtime :: report :: RuleOutcome :: passed ("expensive") } ; check } ,) }
\ No newline at end of file
@@ -112,7 +112,7 @@ fn test_length_basic() {
     let result = gen.string_function("length", vec![text]);
     let s = result.to_string();
 
-    assert!(s.contains("len"));
+    assert!(s.contains("chars () . count ()"));
 }
 
 #[test]
@@ -123,7 +123,7 @@ fn test_length_with_field_access() {
     let result = gen.string_function("length", vec![text]);
     let s = result.to_string();
 
-    assert!(s.contains("len"));
+    assert!(s.contains("chars () . count ()"));
 }
 
 #[test]
@@ -354,7 +354,7 @@ fn test_url_validation() {
     let length_str = length.to_string();
 
     assert!(starts_str.contains("starts_with"));
-    assert!(length_str.contains("len"));
+    assert!(length_str.contains("chars () . count ()"));
 }
 
 #[test]
@@ -348,3 +348,114 @@ fn test_option_of_custom_type_string() {
     let s = opt_custom.to_rust_string();
     assert_eq!(s, "Option<Config>");
 }
+
+#[test]
+fn test_generate_validator_checks_units() {
+    use elo_rust::codegen::units::UnitPolicy;
+
+    let mut parcel = TypeInfo::new("Parcel");
+    parcel.add_field_declaration("weight: Float (kg)").unwrap();
+    let mut context = TypeContext::new();
+    context.register_type("Parcel", parcel);
+
+    let generator = RustCodeGenerator::with_context(context.clone());
+    let code = generator
+        .generate_validator("validate", "weight < 2000g", "Parcel")
+        .unwrap()
+        .to_string();
+    assert!(code.contains("2f64"));
+
+    let err = generator
+        .generate_validator("validate", "weight < 5m", "Parcel")
+        .unwrap_err();
+    assert!(err.contains("Incompatible units"));
+
+    let strict = RustCodeGenerator::with_context(context).with_unit_policy(UnitPolicy::Reject);
+    assert!(strict
+        .generate_validator("validate", "weight < 2000g", "Parcel")
+        .is_err());
+}
+
+#[test]
+fn test_generate_validator_rejects_unknown_fields() {
+    let mut address = TypeInfo::new("Address");
+    address.add_field("city", RustType::String);
+    let mut user = TypeInfo::new("User");
+    user.add_field("age", RustType::Integer);
+    user.add_field("address", RustType::Custom("Address".to_string()));
+    let mut context = TypeContext::new();
+    context.register_type("Address", address);
+    context.register_type("User", user);
+
+    let generator = RustCodeGenerator::with_context(context);
+    assert!(generator
+        .generate_validator("validate", "age >= 18 && address.city != ''", "User")
+        .is_ok());
+    assert_eq!(
+        generator
+            .generate_validator("validate", "agee >= 18", "User")
+            .unwrap_err(),
+        "Type error: Unknown field 'agee' on User"
+    );
+    assert_eq!(
+        generator
+            .generate_validator("validate", "address.zip == ''", "User")
+            .unwrap_err(),
+        "Type error: Unknown field 'zip' on Address"
+    );
+}
+
+#[test]
+fn test_generate_validator_rejects_comparing_strings_with_dates() {
+    let mut user = TypeInfo::new("User");
+    user.add_field("signup", RustType::String);
+    user.add_field("birth", RustType::Date);
+    user.add_field("expiry", RustType::Option(Box::new(RustType::String)));
+    let mut context = TypeContext::new();
+    context.register_type("User", user);
+
+    let generator = RustCodeGenerator::with_context(context);
+    assert!(generator
+        .generate_validator("validate", "birth < @date(2000-01-01)", "User")
+        .is_ok());
+    assert_eq!(
+        generator
+            .generate_validator("validate", "signup >= @date(2024-01-01)", "User")
+            .unwrap_err(),
+        "Type error: signup: Cannot compare string with date"
+    );
+    assert!(generator
+        .generate_validator("validate", "expiry > TODAY", "User")
+        .is_err());
+}
+
+#[test]
+fn test_infer_range_type() {
+    use elo_rust::codegen::type_inference::{InferredType, TypeInferenceVisitor};
+    use elo_rust::parser::Parser;
+
+    let inference = TypeInferenceVisitor::new();
+    let expr = Parser::parse("1..10").unwrap();
+    assert_eq!(
+        inference.infer(&expr),
+        InferredType::Range(Box::new(InferredType::Integer))
+    );
+    let expr = Parser::parse("x in 1..=10").unwrap();
+    assert_eq!(inference.infer(&expr), InferredType::Boolean);
+}
+
+#[test]
+fn test_infer_context_lookup_nullable() {
+    use elo_rust::codegen::type_inference::{InferredType, TypeInferenceVisitor};
+    use elo_rust::parser::Parser;
+
+    let inference = TypeInferenceVisitor::new();
+    let lookup = Parser::parse("ctx.limits.daily_max").unwrap();
+    assert!(inference.infer(&lookup).is_nullable());
+
+    let defaulted = Parser::parse("ctx.limits.daily_max ?? 1000").unwrap();
+    assert_eq!(inference.infer(&defaulted), InferredType::Integer);
+
+    let plain = Parser::parse("user.age").unwrap();
+    assert!(!inference.infer(&plain).is_nullable());
+}